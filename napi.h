@@ -10,10 +10,52 @@
 #include <stdlib.h>
 
 
+#define ALS_CAP_DEBUG_TOOLS (1 << 5)
+
+#define ALS_CAP_DOTNET (1 << 4)
+
+#define ALS_CAP_LEGACY_PTR (1 << 3)
+
+#define ALS_CAP_NETWORKING (1 << 1)
+
+#define ALS_CAP_SCRIPTING 1
+
+#define ALS_CAP_WASM (1 << 2)
+
 #define ALS_DEBUG_ORDERS true
 
+#define ALS_ERR_INVALID_ARGUMENT 5
+
+#define ALS_ERR_INVALID_HANDLE 4
+
+#define ALS_ERR_INVALID_ID 3
+
+#define ALS_ERR_INVALID_STRING 2
+
+#define ALS_ERR_NONE 0
+
+#define ALS_ERR_NULL_POINTER 1
+
+#define ALS_ERR_OPERATION_FAILED 6
+
+#define ALS_KILL_XP 10
+
+enum ALS_TurnPhase {
+  PlayerTurn = 0,
+  MoveSelect = 1,
+  PlayerMoves = 2,
+  EnemyTurn = 3,
+  AttackSelect = 4,
+  Victory = 5,
+  Defeat = 6,
+  Intro = 7,
+};
+typedef uint8_t ALS_TurnPhase;
+
 typedef struct ALS_ResourceSet ALS_ResourceSet;
 
+typedef struct ALS_Runtime ALS_Runtime;
+
 typedef struct ALS_StateListener ALS_StateListener;
 
 typedef struct ALS_Unit ALS_Unit;
@@ -26,18 +68,137 @@ typedef struct ALS_IdList ALS_IdList;
 
 typedef struct ALS_World ALS_World;
 
+typedef struct ALS_UnitTypeStats {
+  float max_health;
+  float mov_rate;
+  float attack_dur;
+  uint8_t movement;
+  uint8_t range;
+} ALS_UnitTypeStats;
+
+typedef struct ALS_BattleStats {
+  uint32_t turns;
+  float damage_dealt;
+  float damage_received;
+  uint32_t units_lost;
+  uint32_t enemies_defeated;
+  uint32_t xp_gained;
+} ALS_BattleStats;
+
+typedef struct ALS_Difficulty {
+  float player_damage;
+  float enemy_damage;
+  float enemy_health;
+  float aggression;
+} ALS_Difficulty;
+
+typedef struct ALS_ElevationRules {
+  int32_t step;
+  uint8_t max_climb;
+  uint8_t range_bonus;
+  float damage_bonus;
+} ALS_ElevationRules;
+
+typedef struct ALS_RawEvent {
+  uint8_t kind;
+  uint8_t uid;
+  uint8_t arg;
+  float value;
+  int32_t x;
+  int32_t y;
+} ALS_RawEvent;
+
+typedef uint64_t ALS_Handle;
+
 typedef float (*ALS_DfuncType)(uint8_t, uint8_t);
 
 typedef void (*ALS_CInitHandle)(void);
 
 typedef void (*ALS_CTurnHandle)(struct ALS_World*, struct ALS_OrderList*);
 
+typedef void (*ALS_CInitCtxHandle)(void*);
+
+typedef void (*ALS_CTurnCtxHandle)(struct ALS_World*, struct ALS_OrderList*, void*);
+
+typedef void (*ALS_CUpdateHandle)(struct ALS_World*, float, void*);
+
+typedef void (*ALS_CUnitDeathHandle)(struct ALS_World*, uint8_t, uint8_t, void*);
+
+typedef void (*ALS_CAttackHandle)(struct ALS_World*, uint8_t, uint8_t, float, void*);
+
+typedef void (*ALS_CResultsHandle)(struct ALS_World*, int32_t, void*);
+
+typedef void (*ALS_CPhaseHandle)(struct ALS_World*, ALS_TurnPhase, void*);
+
+typedef void (*ALS_CDrawHandle)(const struct ALS_World*, void*);
+
+typedef int32_t (*ALS_CVictoryHandle)(const struct ALS_World*, void*);
+
+typedef void (*ALS_CExitHandle)(struct ALS_World*, void*);
+typedef void (*ALS_CTimeoutHandle)(struct ALS_World*, bool, void*);
+typedef void (*ALS_CMapLoadedHandle)(struct ALS_World*, const char*, void*);
+typedef void (*ALS_CPickupHandle)(struct ALS_World*, uint8_t, uint8_t, float, void*);
+typedef void (*ALS_CNarrationHandle)(struct ALS_World*, const char*, void*);
+typedef void (*ALS_CMoveCompleteHandle)(struct ALS_World*, uint8_t, int32_t, int32_t, void*);
+typedef void (*ALS_CTweenHandle)(struct ALS_World*, uint32_t, void*);
+typedef void (*ALS_CScheduleHandle)(struct ALS_World*, uint32_t, void*);
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsInitHandle)(void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsTurnHandle)(struct ALS_World*, struct ALS_OrderList*, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsUpdateHandle)(struct ALS_World*, float, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsUnitDeathHandle)(struct ALS_World*, uint8_t, uint8_t, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsAttackHandle)(struct ALS_World*, uint8_t, uint8_t, float, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsResultsHandle)(struct ALS_World*, int32_t, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsPhaseHandle)(struct ALS_World*, ALS_TurnPhase, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsDrawHandle)(const struct ALS_World*, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef int32_t (*ALS_CsVictoryHandle)(const struct ALS_World*, void*);
+#endif
+
+#if defined(ALS_DOTNET)
+typedef void (*ALS_CsExitHandle)(struct ALS_World*, void*);
+typedef void (*ALS_CsTimeoutHandle)(struct ALS_World*, int32_t, void*);
+typedef void (*ALS_CsMapLoadedHandle)(struct ALS_World*, const uint16_t*, void*);
+typedef void (*ALS_CsPickupHandle)(struct ALS_World*, uint8_t, uint8_t, float, void*);
+typedef void (*ALS_CsNarrationHandle)(struct ALS_World*, const uint16_t*, void*);
+typedef void (*ALS_CsMoveCompleteHandle)(struct ALS_World*, uint8_t, int32_t, int32_t, void*);
+typedef void (*ALS_CsTweenHandle)(struct ALS_World*, uint32_t, void*);
+typedef void (*ALS_CsScheduleHandle)(struct ALS_World*, uint32_t, void*);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsBeginS_Display(int32_t sw,
                        int32_t sh,
                        const char *t,
                        struct ALS_ResourceSet *rs,
                        struct ALS_World *w);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 void alsBegin_Display(int32_t sw,
                       int32_t sh,
                       bool vsync,
@@ -47,17 +208,99 @@ void alsBegin_Display(int32_t sw,
                       struct ALS_World *w,
                       struct ALS_StateListener *sl,
                       float mvl);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindAttack(struct ALS_StateListener *sl, ALS_CAttackHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindBannerDone(struct ALS_StateListener *sl, ALS_CPhaseHandle f, void *ctx);
+#endif
 
 void alsBindDamageFunc(struct ALS_World *w, ALS_DfuncType f);
 
+#if defined(ALS_LEGACY_PTR)
+void alsBindDraw(struct ALS_StateListener *sl, ALS_CDrawHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindExit(struct ALS_StateListener *sl, ALS_CExitHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsBindInit(struct ALS_StateListener *sl, ALS_CInitHandle f);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindInitCtx(struct ALS_StateListener *sl, ALS_CInitCtxHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindMapLoaded(struct ALS_StateListener *sl, ALS_CMapLoadedHandle f, void *ctx);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
+void alsBindMoveComplete(struct ALS_StateListener *sl, ALS_CMoveCompleteHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindNarration(struct ALS_StateListener *sl, ALS_CNarrationHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindPhaseChange(struct ALS_StateListener *sl, ALS_CPhaseHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindPickup(struct ALS_StateListener *sl, ALS_CPickupHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindResults(struct ALS_StateListener *sl, ALS_CResultsHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindTimeout(struct ALS_StateListener *sl, ALS_CTimeoutHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindTransitionDone(struct ALS_StateListener *sl, ALS_CPhaseHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsBindTurn(struct ALS_StateListener *sl, ALS_CTurnHandle f);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindTurnCtx(struct ALS_StateListener *sl, ALS_CTurnCtxHandle f, void *ctx);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
+void alsBindUnitDeath(struct ALS_StateListener *sl, ALS_CUnitDeathHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindUpdate(struct ALS_StateListener *sl, ALS_CUpdateHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsBindVictoryCheck(struct ALS_StateListener *sl, ALS_CVictoryHandle f, void *ctx);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+struct ALS_World *alsBlankHex_World(int32_t ox, int32_t oy, int32_t tx, int32_t ty);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 struct ALS_World *alsBlank_World(void);
+#endif
+
+void alsClearError(void);
 
 void alsCreateStatic(struct ALS_World *w, uint8_t tex_id, int32_t cx, int32_t cy);
 
+#if defined(ALS_LEGACY_PTR)
 void alsDefAnim(struct ALS_UnitType *u,
                 uint32_t fw,
                 uint32_t fh,
@@ -66,7 +309,9 @@ void alsDefAnim(struct ALS_UnitType *u,
                 uint32_t cfy,
                 float fr,
                 bool flip);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 void alsDefAnimUnmuted(struct ALS_UnitType *u,
                        uint32_t fw,
                        uint32_t fh,
@@ -77,21 +322,98 @@ void alsDefAnimUnmuted(struct ALS_UnitType *u,
                        bool flip,
                        uint8_t snd,
                        bool lp);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsDefIdleAnim(struct ALS_UnitType *u,
+                    uint32_t fw,
+                    uint32_t fh,
+                    uint8_t frn,
+                    uint32_t cfx,
+                    uint32_t cfy,
+                    float fr,
+                    bool flip);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsDefStandAnim(struct ALS_UnitType *u,
+                     uint8_t facing,
+                     uint32_t fw,
+                     uint32_t fh,
+                     uint8_t frn,
+                     uint32_t cfx,
+                     uint32_t cfy,
+                     float fr,
+                     bool flip);
+#endif
+
+int32_t alsGetLastError(const char **msg);
 
 uint8_t alsGetTypeID(const struct ALS_World *w, uint8_t uid);
 
 struct ALS_IdList *alsIdList(const struct ALS_World *w);
 
+#if defined(ALS_LEGACY_PTR)
+struct ALS_Runtime *alsInit_Display(int32_t sw,
+                                    int32_t sh,
+                                    bool vsync,
+                                    uint32_t fps,
+                                    const char *t,
+                                    struct ALS_ResourceSet *rs,
+                                    struct ALS_World *w,
+                                    struct ALS_StateListener *sl,
+                                    float mvl);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+struct ALS_Runtime *alsInit_DisplayFromSettings(const char *path,
+                                                const char *t,
+                                                struct ALS_ResourceSet *rs,
+                                                struct ALS_World *w,
+                                                struct ALS_StateListener *sl);
+#endif
+
 bool alsLoadMap(struct ALS_World *w, const char *fpath);
 
+#if defined(ALS_LEGACY_PTR)
 void alsMapFont(struct ALS_ResourceSet *rs, uint8_t id, const char *path);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
+void alsMapFontAtlas(struct ALS_ResourceSet *rs,
+                     uint8_t id,
+                     const char *path,
+                     const int32_t *sizes,
+                     size_t nsizes,
+                     const uint32_t *ranges,
+                     size_t nranges);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsMapMusic(struct ALS_ResourceSet *rs, uint8_t id, const char *path);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsMapPalette(struct ALS_ResourceSet *rs, uint8_t id, const int32_t *colors, size_t n);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 void alsMapSound(struct ALS_ResourceSet *rs, uint8_t id, const char *path);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 void alsMapTexture(struct ALS_ResourceSet *rs, uint8_t id, const char *path);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
+bool alsMapTextureEx(struct ALS_ResourceSet *rs,
+                     uint8_t id,
+                     const char *path,
+                     uint8_t filter,
+                     bool mipmaps);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsMapTextureRegion(struct ALS_ResourceSet *rs,
                          uint8_t tid,
                          uint8_t parent_id,
@@ -99,11 +421,17 @@ void alsMapTextureRegion(struct ALS_ResourceSet *rs,
                          float y,
                          float w,
                          float h);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 struct ALS_ResourceSet *alsNewResourceSet(void);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 struct ALS_StateListener *alsNewStateListener(void);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 struct ALS_UnitType *alsNewUnitType(uint8_t tid,
                                     const char *name,
                                     float health,
@@ -111,10 +439,69 @@ struct ALS_UnitType *alsNewUnitType(uint8_t tid,
                                     uint8_t movt,
                                     uint8_t ran,
                                     float adur);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
 void alsRegisterUnitType(struct ALS_World *w, struct ALS_UnitType *u, uint8_t id);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+bool alsSetActions(struct ALS_UnitType *u, int32_t attacks, bool move_after_attack);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsSetCombatStats(struct ALS_UnitType *u, float attack, float defense);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsSetIdleDelay(struct ALS_UnitType *u, float secs);
+#endif
 
+#if defined(ALS_LEGACY_PTR)
+bool alsSetNinePatch(struct ALS_ResourceSet *rs,
+                     uint8_t id,
+                     int32_t left,
+                     int32_t top,
+                     int32_t right,
+                     int32_t bottom);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+bool alsSetPortrait(struct ALS_UnitType *u, int32_t tex);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+bool alsSetSight(struct ALS_UnitType *u, int32_t sight);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+void alsSetTeamColors(struct ALS_ResourceSet *rs, const int32_t *colors, size_t n);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+bool alsSetUiAnim(struct ALS_ResourceSet *rs,
+                  uint8_t id,
+                  uint32_t fw,
+                  uint32_t fh,
+                  uint8_t frn,
+                  float fr);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
 void alsSetUnitInfo(struct ALS_UnitType *u, const char *s);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+bool alsSetVoice(struct ALS_UnitType *u,
+                 uint8_t kind,
+                 const uint8_t *sounds,
+                 uintptr_t n,
+                 float cooldown);
+#endif
+
+#if defined(ALS_LEGACY_PTR)
+struct ALS_World *alsShutdown_Runtime(struct ALS_Runtime *rt);
+#endif
 
 uint8_t alsSpawnUnit(struct ALS_World *w,
                      uint8_t tid,
@@ -123,37 +510,1451 @@ uint8_t alsSpawnUnit(struct ALS_World *w,
                      int32_t tint,
                      bool plr);
 
+#if defined(ALS_LEGACY_PTR)
+bool alsStep_Runtime(struct ALS_Runtime *rt);
+#endif
+
 bool alsTilePermAt(const struct ALS_World *w, int32_t x, int32_t y);
 
 bool alsVerifyUID(const struct ALS_World *w, uint8_t uid);
 
-void alsnFreeVec(struct ALS_IdList *v);
+#if defined(ALS_LEGACY_PTR)
+struct ALS_World *alsWorld_Runtime(struct ALS_Runtime *rt);
+#endif
 
-float alsnGetUnitHealth(struct ALS_World *w, uint8_t uid);
+#if defined(ALS_DOTNET)
+int32_t alsdAddIntroSlide(ALS_Handle w, uint8_t tex, const uint16_t *caption, float duration);
+#endif
 
-float alsnGetUnitX(const struct ALS_Unit *w);
+#if defined(ALS_DOTNET)
+int32_t alsdAddObjective(ALS_Handle w, const uint16_t *text);
+#endif
 
-float alsnGetUnitY(const struct ALS_Unit *w);
+#if defined(ALS_DOTNET)
+uint32_t alsdAddTween(ALS_Handle w,
+                      float from,
+                      float to,
+                      float duration,
+                      uint8_t easing,
+                      ALS_CsTweenHandle f,
+                      void *ctx);
+#endif
 
-size_t alsnGetWorldHeight(const struct ALS_World *w);
+#if defined(ALS_DOTNET)
+int32_t alsdAddUnitPatrolPoint(ALS_Handle w, uint8_t uid, int32_t x, int32_t y);
+#endif
 
-size_t alsnGetWorldWidth(const struct ALS_World *w);
+#if defined(ALS_DOTNET)
+int32_t alsdApplySettings(ALS_Handle rt, const uint16_t *path);
+#endif
 
-bool alsnIsUnitFoe(const struct ALS_Unit *uref);
+#if defined(ALS_DOTNET)
+int32_t alsdBegin_Display(int32_t sw,
+                          int32_t sh,
+                          int32_t vsync,
+                          uint32_t fps,
+                          const uint16_t *t,
+                          ALS_Handle rs,
+                          ALS_Handle w,
+                          ALS_Handle sl,
+                          float mvl);
+#endif
 
-void alsnPushAttackOrder(struct ALS_OrderList *i,
-                         uint8_t uid,
-                         uint8_t target,
-                         int32_t tx,
-                         int32_t ty);
+#if defined(ALS_DOTNET)
+int32_t alsdBindAttack(ALS_Handle sl, ALS_CsAttackHandle f, void *ctx);
+#endif
 
-void alsnPushDefeatOrder(struct ALS_OrderList *i);
+#if defined(ALS_DOTNET)
+int32_t alsdBindBannerDone(ALS_Handle sl, ALS_CsPhaseHandle f, void *ctx);
+#endif
 
-void alsnPushMoveOrder(struct ALS_OrderList *i, uint8_t uid, int32_t tx, int32_t ty);
+#if defined(ALS_DOTNET)
+int32_t alsdBindDraw(ALS_Handle sl, ALS_CsDrawHandle f, void *ctx);
+#endif
 
-void alsnPushMutHealthOrder(struct ALS_OrderList *i, uint8_t uid, float val, bool is_rel);
+#if defined(ALS_DOTNET)
+int32_t alsdBindExit(ALS_Handle sl, ALS_CsExitHandle f, void *ctx);
+#endif
 
-void alsnPushVictoryOrder(struct ALS_OrderList *i);
+#if defined(ALS_DOTNET)
+int32_t alsdBindInit(ALS_Handle sl, ALS_CsInitHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindMapLoaded(ALS_Handle sl, ALS_CsMapLoadedHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindMoveComplete(ALS_Handle sl, ALS_CsMoveCompleteHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindNarration(ALS_Handle sl, ALS_CsNarrationHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindPhaseChange(ALS_Handle sl, ALS_CsPhaseHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindPickup(ALS_Handle sl, ALS_CsPickupHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindResults(ALS_Handle sl, ALS_CsResultsHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindTimeout(ALS_Handle sl, ALS_CsTimeoutHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindTransitionDone(ALS_Handle sl, ALS_CsPhaseHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindTurn(ALS_Handle sl, ALS_CsTurnHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindUnitDeath(ALS_Handle sl, ALS_CsUnitDeathHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindUpdate(ALS_Handle sl, ALS_CsUpdateHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdBindVictoryCheck(ALS_Handle sl, ALS_CsVictoryHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdCancelScheduled(ALS_Handle w, uint32_t id);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdCancelTween(ALS_Handle w, uint32_t id);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdClearPuzzle(ALS_Handle w);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdClearTileLayers(ALS_Handle w);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdClearTutorial(ALS_Handle w);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdClearUnitBehavior(ALS_Handle w, uint8_t uid);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdClearUnitTypeAi(ALS_Handle w, uint8_t type_id);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdCreateStatic(ALS_Handle w, uint8_t tex_id, int32_t cx, int32_t cy);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdDefAnim(ALS_Handle u,
+                    uint32_t fw,
+                    uint32_t fh,
+                    uint8_t frn,
+                    uint32_t cfx,
+                    uint32_t cfy,
+                    float fr,
+                    int32_t flip);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdDefAnimUnmuted(ALS_Handle u,
+                           uint32_t fw,
+                           uint32_t fh,
+                           uint8_t frn,
+                           uint32_t cfx,
+                           uint32_t cfy,
+                           float fr,
+                           int32_t flip,
+                           uint8_t snd,
+                           int32_t lp);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdDefIdleAnim(ALS_Handle u,
+                        uint32_t fw,
+                        uint32_t fh,
+                        uint8_t frn,
+                        uint32_t cfx,
+                        uint32_t cfy,
+                        float fr,
+                        int32_t flip);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdDefStandAnim(ALS_Handle u,
+                         uint8_t facing,
+                         uint32_t fw,
+                         uint32_t fh,
+                         uint8_t frn,
+                         uint32_t cfx,
+                         uint32_t cfy,
+                         float fr,
+                         int32_t flip);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdDeleteSlot(const uint16_t *dir, uint32_t slot);
+#endif
+
+#if defined(ALS_DOTNET)
+ALS_Handle alsdDeriveUnitType(ALS_Handle w, uint8_t base, const uint16_t *name);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdEnableEventQueue(ALS_Handle w, int32_t enable);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetDifficulty(ALS_Handle w, struct ALS_Difficulty *out);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetInfluence(ALS_Handle w,
+                         int32_t x,
+                         int32_t y,
+                         int32_t player,
+                         float *out_threat,
+                         float *out_support);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetLastError(uint16_t *buf, int32_t cap);
+#endif
+
+#if defined(ALS_DOTNET)
+int64_t alsdGetProfileValue(const struct ALS_World *w, const uint16_t *key);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetSlotInfo(const uint16_t *dir, uint32_t slot, uint32_t *turn, uint64_t *time);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetTurnTimer(ALS_Handle rt, float *turn_left, float *bank_left);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetTweenValue(ALS_Handle w, uint32_t id, float *out);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdGetUnitHp(ALS_Handle w, uint8_t uid, int32_t *out_hp, int32_t *out_max);
+#endif
+
+#if defined(ALS_DOTNET)
+ALS_Handle alsdInit_Display(int32_t sw,
+                            int32_t sh,
+                            int32_t vsync,
+                            uint32_t fps,
+                            const uint16_t *t,
+                            ALS_Handle rs,
+                            ALS_Handle w,
+                            ALS_Handle sl,
+                            float mvl);
+#endif
+
+#if defined(ALS_DOTNET)
+ALS_Handle alsdInit_DisplayFromSettings(const uint16_t *path,
+                                        const uint16_t *t,
+                                        ALS_Handle rs,
+                                        ALS_Handle w,
+                                        ALS_Handle sl);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdIsTileVisible(ALS_Handle w, int32_t x, int32_t y);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdIsValid(ALS_Handle h);
+#endif
+
+#if defined(ALS_DOTNET)
+size_t alsdListSlots(const uint16_t *dir, uint32_t *out, size_t cap);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadAi(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadBattle(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadMap(ALS_Handle w, const uint16_t *fpath);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadMapRon(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadPuzzle(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadSlot(ALS_Handle rt, const uint16_t *dir, uint32_t slot);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdLoadTutorial(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapFont(ALS_Handle rs, uint8_t id, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapFontAtlas(ALS_Handle rs,
+                         uint8_t id,
+                         const uint16_t *path,
+                         const int32_t *sizes,
+                         int32_t nsizes,
+                         const uint32_t *ranges,
+                         int32_t nranges);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapMusic(ALS_Handle rs, uint8_t id, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapPalette(ALS_Handle rs, uint8_t id, const int32_t *colors, int32_t n);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapSound(ALS_Handle rs, uint8_t id, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapTexture(ALS_Handle rs, uint8_t id, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdMapTextureRegion(ALS_Handle rs,
+                             uint8_t tid,
+                             uint8_t parent_id,
+                             float x,
+                             float y,
+                             float w,
+                             float h);
+#endif
+
+#if defined(ALS_DOTNET)
+ALS_Handle alsdNewStateListener(void);
+#endif
+
+#if defined(ALS_DOTNET)
+ALS_Handle alsdNewUnitType(uint8_t tid,
+                           const uint16_t *name,
+                           float health,
+                           float mov_rate,
+                           uint8_t movt,
+                           uint8_t ran,
+                           float adur);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdOpenProfile(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdPanCamera(ALS_Handle w, float x, float y, float duration, uint8_t easing);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdPanToTile(ALS_Handle w, int32_t x, int32_t y, float duration);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdPlacePickup(ALS_Handle w, uint8_t tex, int32_t x, int32_t y, uint8_t kind, float value);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdPollEvent(ALS_Handle w, struct ALS_RawEvent *out);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdPollEventJson(ALS_Handle w, uint16_t *buf, int32_t cap);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdRegisterUnitType(ALS_Handle w, ALS_Handle u, uint8_t id);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdRelease(ALS_Handle h);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdRemovePickup(ALS_Handle w, int32_t x, int32_t y);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSaveAudioSettings(ALS_Handle rt, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSaveBattle(const struct ALS_World *w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSaveMap(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSaveMapRon(ALS_Handle w, const uint16_t *path);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSaveSlot(ALS_Handle rt, const uint16_t *dir, uint32_t slot);
+#endif
+
+#if defined(ALS_DOTNET)
+uint32_t alsdScheduleAfter(ALS_Handle w, float secs, ALS_CsScheduleHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+uint32_t alsdScheduleEvery(ALS_Handle w, float secs, ALS_CsScheduleHandle f, void *ctx);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetActions(ALS_Handle u, int32_t attacks, int32_t move_after_attack);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetAiBudget(ALS_Handle rt, float ms);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetAiController(ALS_Handle w, int32_t enabled, float aggressiveness);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetAiDebug(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetAutosave(ALS_Handle rt, const uint16_t *path, uint32_t every, int32_t before_enemy);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetBackgroundFps(ALS_Handle rt, uint32_t fps);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetBannerTheme(ALS_Handle rt,
+                           int32_t tex,
+                           int32_t font,
+                           int32_t bg,
+                           int32_t fg,
+                           float time);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCameraAutoFollow(ALS_Handle w, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCameraFollow(ALS_Handle w, uint8_t uid, int32_t follow);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCameraFollowRate(ALS_Handle w, float rate);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCleanScreenshots(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCombatStats(ALS_Handle u, float attack, float defense);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetConfirmExit(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetControlHints(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetCustomDifficulty(ALS_Handle w, const struct ALS_Difficulty *d);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetDangerZone(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetDefaultActions(ALS_Handle w, uint8_t attacks, int32_t move_after_attack);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetDifficulty(ALS_Handle w, uint8_t preset);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetElevation(ALS_Handle w, const uint8_t *heights, uintptr_t n);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetElevationRules(ALS_Handle w, const struct ALS_ElevationRules *r);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetFogOfWar(ALS_Handle w, int32_t enabled);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetHighContrast(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetHpRounding(ALS_Handle w, uint8_t mode);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetHudHidden(ALS_Handle rt, int32_t hidden);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetIdleDelay(ALS_Handle u, float secs);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetInputGuard(ALS_Handle rt, float debounce, float min_screen_time);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetInputScheme(ALS_Handle w, uint8_t scheme);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetPauseUnfocused(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetPopupStyle(ALS_Handle rt, uint8_t kind, int32_t color, int32_t font);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetPortrait(ALS_Handle u, int32_t tex);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetPortraitSize(ALS_Handle rt, float size);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetProfileValue(struct ALS_World *w, const uint16_t *key, int64_t v);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetRumble(ALS_Handle rt,
+                      float hit_strength,
+                      float hit_duration,
+                      float select_strength,
+                      float select_duration);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetScreenshotDir(ALS_Handle rt, const uint16_t *dir);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetSight(ALS_Handle u, int32_t sight);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetSimFps(ALS_Handle rt, uint32_t fps);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetStatusIcon(ALS_Handle rt, uint8_t status, int32_t tex);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetTeamColors(ALS_Handle rs, const int32_t *colors, int32_t n);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetTouchInput(ALS_Handle rt, int32_t on);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetTransition(ALS_Handle rt, uint8_t kind, float time, int32_t color);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetTurnTimer(ALS_Handle rt,
+                         float per_turn,
+                         float bank,
+                         float increment,
+                         int32_t auto_wait);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetUnitGuard(ALS_Handle w, uint8_t uid, int32_t x, int32_t y, uint8_t radius);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetUnitHold(ALS_Handle w, uint8_t uid);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetUnitInfo(ALS_Handle u, const uint16_t *s);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetUnitPortrait(ALS_Handle w, uint8_t uid, int32_t tex);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetUnitTypeAi(ALS_Handle w,
+                          uint8_t type_id,
+                          const uint16_t *path,
+                          const uint16_t *root);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetVoice(ALS_Handle u,
+                     uint8_t kind,
+                     const uint8_t *sounds,
+                     uintptr_t n,
+                     float cooldown);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSetZoom(ALS_Handle w, float zoom);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdShowPopup(ALS_Handle w, uint8_t uid, const uint16_t *text, uint8_t kind);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdShowScrollingText(ALS_Handle w, const uint16_t *text, float speed);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdSpawnUnit(ALS_Handle w,
+                      uint8_t tid,
+                      int32_t tx,
+                      int32_t ty,
+                      int32_t tint,
+                      int32_t plr,
+                      uint8_t *out_uid);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdStateDiff(ALS_Handle a, ALS_Handle b, uint16_t *buf, int32_t cap);
+#endif
+
+#if defined(ALS_DOTNET)
+int32_t alsdStep_Runtime(ALS_Handle rt);
+#endif
+
+bool alshAddIntroSlide(ALS_Handle w, uint8_t tex, const char *caption, float duration);
+
+int32_t alshAddObjective(ALS_Handle w, const char *text);
+
+int32_t alshAddTileLayer(ALS_Handle w, uint8_t kind, const uint8_t *tiles, size_t n);
+
+uint32_t alshAddTween(ALS_Handle w,
+                      float from,
+                      float to,
+                      float duration,
+                      uint8_t easing,
+                      ALS_CTweenHandle f,
+                      void *ctx);
+
+bool alshAddUnitPatrolPoint(ALS_Handle w, uint8_t uid, int32_t x, int32_t y);
+
+bool alshApplySettings(ALS_Handle rt, const char *path);
+
+bool alshBegin_Display(int32_t sw,
+                       int32_t sh,
+                       bool vsync,
+                       uint32_t fps,
+                       const char *t,
+                       ALS_Handle rs,
+                       ALS_Handle w,
+                       ALS_Handle sl,
+                       float mvl);
+
+bool alshBindAttack(ALS_Handle sl, ALS_CAttackHandle f, void *ctx);
+
+bool alshBindBannerDone(ALS_Handle sl, ALS_CPhaseHandle f, void *ctx);
+
+bool alshBindDamageFunc(ALS_Handle w, ALS_DfuncType f);
+
+bool alshBindDraw(ALS_Handle sl, ALS_CDrawHandle f, void *ctx);
+
+bool alshBindExit(ALS_Handle sl, ALS_CExitHandle f, void *ctx);
+
+bool alshBindInit(ALS_Handle sl, ALS_CInitHandle f);
+
+bool alshBindInitCtx(ALS_Handle sl, ALS_CInitCtxHandle f, void *ctx);
+
+bool alshBindMapLoaded(ALS_Handle sl, ALS_CMapLoadedHandle f, void *ctx);
+
+bool alshBindMoveComplete(ALS_Handle sl, ALS_CMoveCompleteHandle f, void *ctx);
+
+bool alshBindNarration(ALS_Handle sl, ALS_CNarrationHandle f, void *ctx);
+
+bool alshBindPhaseChange(ALS_Handle sl, ALS_CPhaseHandle f, void *ctx);
+
+bool alshBindPickup(ALS_Handle sl, ALS_CPickupHandle f, void *ctx);
+
+bool alshBindResults(ALS_Handle sl, ALS_CResultsHandle f, void *ctx);
+
+bool alshBindTimeout(ALS_Handle sl, ALS_CTimeoutHandle f, void *ctx);
+
+bool alshBindTransitionDone(ALS_Handle sl, ALS_CPhaseHandle f, void *ctx);
+
+bool alshBindTurn(ALS_Handle sl, ALS_CTurnHandle f);
+
+bool alshBindTurnCtx(ALS_Handle sl, ALS_CTurnCtxHandle f, void *ctx);
+
+bool alshBindUnitDeath(ALS_Handle sl, ALS_CUnitDeathHandle f, void *ctx);
+
+bool alshBindUpdate(ALS_Handle sl, ALS_CUpdateHandle f, void *ctx);
+
+bool alshBindVictoryCheck(ALS_Handle sl, ALS_CVictoryHandle f, void *ctx);
+
+ALS_Handle alshBlankHex_World(int32_t ox, int32_t oy, int32_t tx, int32_t ty);
+
+ALS_Handle alshBlank_World(void);
+
+bool alshCancelScheduled(ALS_Handle w, uint32_t id);
+
+bool alshCancelTween(ALS_Handle w, uint32_t id);
+
+bool alshClearIntroSlides(ALS_Handle w);
+
+bool alshClearPuzzle(ALS_Handle w);
+
+bool alshClearTileLayers(ALS_Handle w);
+
+bool alshClearTutorial(ALS_Handle w);
+
+bool alshClearUnitBehavior(ALS_Handle w, uint8_t uid);
+
+bool alshClearUnitTypeAi(ALS_Handle w, uint8_t type_id);
+
+bool alshCreateStatic(ALS_Handle w, uint8_t tex_id, int32_t cx, int32_t cy);
+
+bool alshDefAnim(ALS_Handle u,
+                 uint32_t fw,
+                 uint32_t fh,
+                 uint8_t frn,
+                 uint32_t cfx,
+                 uint32_t cfy,
+                 float fr,
+                 bool flip);
+
+bool alshDefAnimUnmuted(ALS_Handle u,
+                        uint32_t fw,
+                        uint32_t fh,
+                        uint8_t frn,
+                        uint32_t cfx,
+                        uint32_t cfy,
+                        float fr,
+                        bool flip,
+                        uint8_t snd,
+                        bool lp);
+
+bool alshDefIdleAnim(ALS_Handle u,
+                     uint32_t fw,
+                     uint32_t fh,
+                     uint8_t frn,
+                     uint32_t cfx,
+                     uint32_t cfy,
+                     float fr,
+                     bool flip);
+
+bool alshDefStandAnim(ALS_Handle u,
+                      uint8_t facing,
+                      uint32_t fw,
+                      uint32_t fh,
+                      uint8_t frn,
+                      uint32_t cfx,
+                      uint32_t cfy,
+                      float fr,
+                      bool flip);
+
+ALS_Handle alshDeriveUnitType(ALS_Handle w, uint8_t base, const char *name);
+
+bool alshEnableEventQueue(ALS_Handle w, bool enable);
+
+#if defined(ALS_DEBUG_TOOLS)
+bool alshForceOutcome(ALS_Handle w, bool victory);
+#endif
+
+bool alshGetDifficulty(ALS_Handle w, struct ALS_Difficulty *out);
+
+int32_t alshGetGrid(ALS_Handle w);
+
+bool alshGetInfluence(ALS_Handle w,
+                      int32_t x,
+                      int32_t y,
+                      bool player,
+                      float *out_threat,
+                      float *out_support);
+
+uint8_t alshGetInputScheme(ALS_Handle w);
+
+bool alshGetTurnTimer(ALS_Handle rt, float *turn_left, float *bank_left);
+
+bool alshGetTweenValue(ALS_Handle w, uint32_t id, float *out);
+
+bool alshGetUnitHp(ALS_Handle w, uint8_t uid, int32_t *out_hp, int32_t *out_max);
+
+float alshGetZoom(ALS_Handle w);
+
+int32_t alshGridDistance(ALS_Handle w, int32_t x1, int32_t y1, int32_t x2, int32_t y2);
+
+ALS_Handle alshInit_Display(int32_t sw,
+                            int32_t sh,
+                            bool vsync,
+                            uint32_t fps,
+                            const char *t,
+                            ALS_Handle rs,
+                            ALS_Handle w,
+                            ALS_Handle sl,
+                            float mvl);
+
+ALS_Handle alshInit_DisplayFromSettings(const char *path,
+                                        const char *t,
+                                        ALS_Handle rs,
+                                        ALS_Handle w,
+                                        ALS_Handle sl);
+
+bool alshIsTileVisible(ALS_Handle w, int32_t x, int32_t y);
+
+bool alshIsValid(ALS_Handle h);
+
+bool alshLoadAi(ALS_Handle w, const char *path);
+
+bool alshLoadBattle(ALS_Handle w, const char *path);
+
+bool alshLoadMap(ALS_Handle w, const char *fpath);
+
+bool alshLoadMapRon(ALS_Handle w, const char *path);
+
+bool alshLoadPuzzle(ALS_Handle w, const char *path);
+
+bool alshLoadSlot(ALS_Handle rt, const char *dir, uint32_t slot);
+
+bool alshLoadTutorial(ALS_Handle w, const char *path);
+
+bool alshMapFont(ALS_Handle rs, uint8_t id, const char *path);
+
+bool alshMapFontAtlas(ALS_Handle rs,
+                      uint8_t id,
+                      const char *path,
+                      const int32_t *sizes,
+                      size_t nsizes,
+                      const uint32_t *ranges,
+                      size_t nranges);
+
+bool alshMapMusic(ALS_Handle rs, uint8_t id, const char *path);
+
+bool alshMapPalette(ALS_Handle rs, uint8_t id, const int32_t *colors, size_t n);
+
+bool alshMapSound(ALS_Handle rs, uint8_t id, const char *path);
+
+bool alshMapTexture(ALS_Handle rs, uint8_t id, const char *path);
+
+bool alshMapTextureEx(ALS_Handle rs, uint8_t id, const char *path, uint8_t filter, bool mipmaps);
+
+bool alshMapTextureRegion(ALS_Handle rs,
+                          uint8_t tid,
+                          uint8_t parent_id,
+                          float x,
+                          float y,
+                          float w,
+                          float h);
+
+ALS_Handle alshNewResourceSet(void);
+
+ALS_Handle alshNewStateListener(void);
+
+ALS_Handle alshNewUnitType(uint8_t tid,
+                           const char *name,
+                           float health,
+                           float mov_rate,
+                           uint8_t movt,
+                           uint8_t ran,
+                           float adur);
+
+bool alshOpenProfile(ALS_Handle w, const char *path);
+
+bool alshPanCamera(ALS_Handle w, float x, float y, float duration, uint8_t easing);
+
+bool alshPanToTile(ALS_Handle w, int32_t x, int32_t y, float duration);
+
+bool alshPlacePickup(ALS_Handle w, uint8_t tex, int32_t x, int32_t y, uint8_t kind, float value);
+
+bool alshPollEvent(ALS_Handle w, struct ALS_RawEvent *out);
+
+char *alshPollEventJson(ALS_Handle w);
+
+bool alshRegisterUnitType(ALS_Handle w, ALS_Handle u, uint8_t id);
+
+bool alshRelease(ALS_Handle h);
+
+bool alshRemovePickup(ALS_Handle w, int32_t x, int32_t y);
+
+char *alshResourceReport(ALS_Handle rt);
+
+bool alshSaveAudioSettings(ALS_Handle rt, const char *path);
+
+bool alshSaveMap(ALS_Handle w, const char *path);
+
+bool alshSaveMapRon(ALS_Handle w, const char *path);
+
+bool alshSaveSlot(ALS_Handle rt, const char *dir, uint32_t slot);
+
+uint32_t alshScheduleAfter(ALS_Handle w, float secs, ALS_CScheduleHandle f, void *ctx);
+
+uint32_t alshScheduleEvery(ALS_Handle w, float secs, ALS_CScheduleHandle f, void *ctx);
+
+bool alshSetActions(ALS_Handle u, int32_t attacks, bool move_after_attack);
+
+bool alshSetAiBudget(ALS_Handle rt, float ms);
+
+bool alshSetAiController(ALS_Handle w, bool enabled, float aggressiveness);
+
+bool alshSetAiDebug(ALS_Handle rt, bool on);
+
+bool alshSetAutosave(ALS_Handle rt, const char *path, uint32_t every, bool before_enemy);
+
+bool alshSetBackgroundFps(ALS_Handle rt, uint32_t fps);
+
+bool alshSetBannerTheme(ALS_Handle rt,
+                        int32_t tex,
+                        int32_t font,
+                        int32_t bg,
+                        int32_t fg,
+                        float time);
+
+bool alshSetCameraAutoFollow(ALS_Handle w, bool on);
+
+bool alshSetCameraFollow(ALS_Handle w, uint8_t uid, bool follow);
+
+bool alshSetCameraFollowRate(ALS_Handle w, float rate);
+
+#if defined(ALS_DEBUG_TOOLS)
+bool alshSetCheat(ALS_Handle w, uint8_t cheat, bool on);
+#endif
+
+bool alshSetCleanScreenshots(ALS_Handle rt, bool on);
+
+bool alshSetCombatStats(ALS_Handle u, float attack, float defense);
+
+bool alshSetConfirmExit(ALS_Handle rt, bool on);
+
+bool alshSetControlHints(ALS_Handle rt, bool on);
+
+bool alshSetCustomDifficulty(ALS_Handle w, const struct ALS_Difficulty *d);
+
+bool alshSetDangerZone(ALS_Handle rt, bool on);
+
+bool alshSetDefaultActions(ALS_Handle w, uint8_t attacks, bool move_after_attack);
+
+bool alshSetDifficulty(ALS_Handle w, uint8_t preset);
+
+bool alshSetElevation(ALS_Handle w, const uint8_t *heights, uintptr_t n);
+
+bool alshSetElevationRules(ALS_Handle w, const struct ALS_ElevationRules *r);
+
+bool alshSetFogOfWar(ALS_Handle w, bool enabled);
+
+bool alshSetHighContrast(ALS_Handle rt, bool on);
+
+bool alshSetHpRounding(ALS_Handle w, uint8_t mode);
+
+bool alshSetHudHidden(ALS_Handle rt, bool hidden);
+
+bool alshSetIdleDelay(ALS_Handle u, float secs);
+
+bool alshSetInputGuard(ALS_Handle rt, float debounce, float min_screen_time);
+
+bool alshSetInputScheme(ALS_Handle w, uint8_t scheme);
+
+bool alshSetMemoryBudget(ALS_Handle rs, uint64_t bytes);
+
+bool alshSetNinePatch(ALS_Handle rs,
+                      uint8_t id,
+                      int32_t left,
+                      int32_t top,
+                      int32_t right,
+                      int32_t bottom);
+
+bool alshSetPauseUnfocused(ALS_Handle rt, bool on);
+
+bool alshSetPopupStyle(ALS_Handle rt, uint8_t kind, int32_t color, int32_t font);
+
+bool alshSetPortrait(ALS_Handle u, int32_t tex);
+
+bool alshSetPortraitSize(ALS_Handle rt, float size);
+
+bool alshSetRumble(ALS_Handle rt,
+                   float hit_strength,
+                   float hit_duration,
+                   float select_strength,
+                   float select_duration);
+
+bool alshSetScreenshotDir(ALS_Handle rt, const char *dir);
+
+bool alshSetSight(ALS_Handle u, int32_t sight);
+
+bool alshSetSimFps(ALS_Handle rt, uint32_t fps);
+
+bool alshSetStatusIcon(ALS_Handle rt, uint8_t status, int32_t tex);
+
+bool alshSetTeamColors(ALS_Handle rs, const int32_t *colors, size_t n);
+
+bool alshSetTextScale(ALS_Handle rt, float scale);
+
+bool alshSetTouchInput(ALS_Handle rt, bool on);
+
+bool alshSetTransition(ALS_Handle rt, uint8_t kind, float time, int32_t color);
+
+bool alshSetTurnTimer(ALS_Handle rt, float per_turn, float bank, float increment, bool auto_wait);
+
+bool alshSetUiAnim(ALS_Handle rs, uint8_t id, uint32_t fw, uint32_t fh, uint8_t frn, float fr);
+
+bool alshSetUiScale(ALS_Handle rt, float scale);
+
+bool alshSetUnitGuard(ALS_Handle w, uint8_t uid, int32_t x, int32_t y, uint8_t radius);
+
+bool alshSetUnitHold(ALS_Handle w, uint8_t uid);
+
+bool alshSetUnitInfo(ALS_Handle u, const char *s);
+
+bool alshSetUnitPortrait(ALS_Handle w, uint8_t uid, int32_t tex);
+
+bool alshSetUnitTypeAi(ALS_Handle w, uint8_t type_id, const char *path, const char *root);
+
+bool alshSetVoice(ALS_Handle u, uint8_t kind, const uint8_t *sounds, uintptr_t n, float cooldown);
+
+bool alshSetZoom(ALS_Handle w, float zoom);
+
+bool alshShowPopup(ALS_Handle w, uint8_t uid, const char *text, uint8_t kind);
+
+bool alshShowScrollingText(ALS_Handle w, const char *text, float speed);
+
+ALS_Handle alshShutdown_Runtime(ALS_Handle rt);
+
+bool alshSpawnUnit(ALS_Handle w,
+                   uint8_t tid,
+                   int32_t tx,
+                   int32_t ty,
+                   int32_t tint,
+                   bool plr,
+                   uint8_t *out_uid);
+
+char *alshStateDiff(ALS_Handle a, ALS_Handle b);
+
+uint64_t alshStateHash(ALS_Handle w);
+
+bool alshStep_Runtime(ALS_Handle rt);
+
+struct ALS_World *alshWorld_Runtime(ALS_Handle rt);
+
+bool alsnAddAmbientRegion(struct ALS_World *w,
+                          uint8_t track,
+                          int32_t x0,
+                          int32_t y0,
+                          int32_t x1,
+                          int32_t y1,
+                          float falloff,
+                          float volume);
+
+bool alsnAddIntroSlide(struct ALS_World *w, uint8_t tex, const char *caption, float duration);
+
+int32_t alsnAddObjective(struct ALS_World *w, const char *text);
+
+int32_t alsnAddTileLayer(struct ALS_World *w, uint8_t kind, const uint8_t *tiles, size_t n);
+
+uint32_t alsnAddTween(struct ALS_World *w,
+                      float from,
+                      float to,
+                      float duration,
+                      uint8_t easing,
+                      ALS_CTweenHandle f,
+                      void *ctx);
+
+bool alsnAddUnitPatrolPoint(struct ALS_World *w, uint8_t uid, int32_t x, int32_t y);
+
+bool alsnApplySettings(struct ALS_Runtime *rt, const char *path);
+
+bool alsnCancelScheduled(struct ALS_World *w, uint32_t id);
+
+bool alsnCancelTween(struct ALS_World *w, uint32_t id);
+
+bool alsnClearAmbientRegions(struct ALS_World *w);
+
+bool alsnClearIntroSlides(struct ALS_World *w);
+
+bool alsnClearObjectives(struct ALS_World *w);
+
+bool alsnClearPuzzle(struct ALS_World *w);
+
+bool alsnClearTileLayers(struct ALS_World *w);
+
+bool alsnClearTutorial(struct ALS_World *w);
+
+bool alsnClearUnitBehavior(struct ALS_World *w, uint8_t uid);
+
+bool alsnClearUnitTypeAi(struct ALS_World *w, uint8_t type_id);
+
+char *alsnDataDir(const char *app);
+
+bool alsnDeleteSlot(const char *dir, uint32_t slot);
+
+#if defined(ALS_LEGACY_PTR)
+struct ALS_UnitType *alsnDeriveUnitType(const struct ALS_World *w, uint8_t base, const char *name);
+#endif
+
+bool alsnDespawnUnit(struct ALS_World *w, uint8_t uid);
+
+bool alsnEnableEventQueue(struct ALS_World *w, bool enable);
+
+int32_t alsnFindPath(const struct ALS_World *w,
+                     int32_t sx,
+                     int32_t sy,
+                     int32_t gx,
+                     int32_t gy,
+                     int32_t *out,
+                     size_t cap);
+
+#if defined(ALS_DEBUG_TOOLS)
+bool alsnForceOutcome(struct ALS_World *w, bool victory);
+#endif
+
+void alsnFreeString(char *s);
+
+void alsnFreeVec(struct ALS_IdList *v);
+
+bool alsnGetBattleStats(const struct ALS_World *w, struct ALS_BattleStats *out);
+
+bool alsnGetCamera(const struct ALS_World *w, float *out_x, float *out_y);
+
+uint32_t alsnGetCapabilities(void);
+
+char *alsnGetDefeatText(const struct ALS_World *w);
+
+bool alsnGetDifficulty(const struct ALS_World *w, struct ALS_Difficulty *out);
+
+int32_t alsnGetElevation(const struct ALS_World *w, int32_t x, int32_t y);
+
+int32_t alsnGetGrid(const struct ALS_World *w);
+
+int32_t alsnGetHeightAdvantage(const struct ALS_World *w, uint8_t atk, uint8_t def);
+
+bool alsnGetInfluence(const struct ALS_World *w,
+                      int32_t x,
+                      int32_t y,
+                      bool player,
+                      float *out_threat,
+                      float *out_support);
+
+uint8_t alsnGetInputScheme(const struct ALS_World *w);
+
+char *alsnGetIntroText(const struct ALS_World *w);
+
+char *alsnGetMapTitle(const struct ALS_World *w);
+
+int64_t alsnGetProfileValue(const struct ALS_World *w, const char *key);
+
+int32_t alsnGetPuzzleState(const struct ALS_World *w);
+
+char *alsnGetSlotInfo(const char *dir, uint32_t slot, uint32_t *turn, uint64_t *time);
+
+bool alsnGetTurnTimer(const struct ALS_Runtime *rt, float *turn_left, float *bank_left);
+
+int32_t alsnGetTutorialStep(const struct ALS_World *w);
+
+bool alsnGetTweenValue(const struct ALS_World *w, uint32_t id, float *out);
+
+int32_t alsnGetUnitBehavior(const struct ALS_World *w, uint8_t uid);
+
+float alsnGetUnitHealth(struct ALS_World *w, uint8_t uid);
+
+bool alsnGetUnitHp(const struct ALS_World *w, uint8_t uid, int32_t *out_hp, int32_t *out_max);
+
+bool alsnGetUnitTile(const struct ALS_World *w, uint8_t uid, int32_t *out_x, int32_t *out_y);
+
+bool alsnGetUnitTypeStats(const struct ALS_World *w, uint8_t tid, struct ALS_UnitTypeStats *out);
+
+float alsnGetUnitX(const struct ALS_Unit *w);
+
+float alsnGetUnitY(const struct ALS_Unit *w);
+
+const char *alsnGetVersion(void);
+
+char *alsnGetVictoryText(const struct ALS_World *w);
+
+size_t alsnGetWorldHeight(const struct ALS_World *w);
+
+size_t alsnGetWorldWidth(const struct ALS_World *w);
+
+float alsnGetZoom(const struct ALS_World *w);
+
+int32_t alsnGridDistance(const struct ALS_World *w, int32_t x1, int32_t y1, int32_t x2, int32_t y2);
+
+bool alsnIsTileAtRange(int32_t x1, int32_t y1, int32_t x2, int32_t y2, uint8_t r);
+
+bool alsnIsTileVisible(const struct ALS_World *w, int32_t x, int32_t y);
+
+bool alsnIsUnitFoe(const struct ALS_Unit *uref);
+
+bool alsnIsUnitPlayer(const struct ALS_World *w, uint8_t uid);
+
+size_t alsnListSlots(const char *dir, uint32_t *out, size_t cap);
+
+bool alsnLoadAi(struct ALS_World *w, const char *path);
+
+bool alsnLoadBattle(struct ALS_World *w, const char *path);
+
+bool alsnLoadMapRon(struct ALS_World *w, const char *path);
+
+bool alsnLoadPuzzle(struct ALS_World *w, const char *path);
+
+bool alsnLoadSlot(struct ALS_Runtime *rt, const char *dir, uint32_t slot);
+
+bool alsnLoadTutorial(struct ALS_World *w, const char *path);
+
+bool alsnOpenProfile(struct ALS_World *w, const char *path);
+
+bool alsnPanCamera(struct ALS_World *w, float x, float y, float duration, uint8_t easing);
+
+bool alsnPanToTile(struct ALS_World *w, int32_t x, int32_t y, float duration);
+
+bool alsnPlacePickup(struct ALS_World *w,
+                     uint8_t tex,
+                     int32_t x,
+                     int32_t y,
+                     uint8_t kind,
+                     float value);
+
+bool alsnPlaySound(struct ALS_World *w, uint8_t id);
+
+bool alsnPlayStinger(struct ALS_World *w, uint8_t kind);
+
+bool alsnPollEvent(struct ALS_World *w, struct ALS_RawEvent *out);
+
+char *alsnPollEventJson(struct ALS_World *w);
+
+void alsnPushAttackOrder(struct ALS_OrderList *i,
+                         uint8_t uid,
+                         uint8_t target,
+                         int32_t tx,
+                         int32_t ty);
+
+void alsnPushDefeatOrder(struct ALS_OrderList *i);
+
+void alsnPushMoveOrder(struct ALS_OrderList *i, uint8_t uid, int32_t tx, int32_t ty);
+
+void alsnPushMutHealthOrder(struct ALS_OrderList *i, uint8_t uid, float val, bool is_rel);
+
+void alsnPushVictoryOrder(struct ALS_OrderList *i);
+
+size_t alsnReachableTiles(const struct ALS_World *w, uint8_t uid, int32_t *out, size_t cap);
+
+bool alsnRemovePickup(struct ALS_World *w, int32_t x, int32_t y);
+
+char *alsnResourceReport(const struct ALS_Runtime *rt);
+
+bool alsnSaveAudioSettings(struct ALS_Runtime *rt, const char *path);
+
+bool alsnSaveBattle(const struct ALS_World *w, const char *path);
+
+bool alsnSaveMap(const struct ALS_World *w, const char *path);
+
+bool alsnSaveMapRon(const struct ALS_World *w, const char *path);
+
+bool alsnSaveProfile(struct ALS_World *w);
+
+bool alsnSaveSlot(struct ALS_Runtime *rt, const char *dir, uint32_t slot);
+
+uint32_t alsnScheduleAfter(struct ALS_World *w, float secs, ALS_CScheduleHandle f, void *ctx);
+
+uint32_t alsnScheduleEvery(struct ALS_World *w, float secs, ALS_CScheduleHandle f, void *ctx);
+
+bool alsnSetAiBudget(struct ALS_Runtime *rt, float ms);
+
+bool alsnSetAiController(struct ALS_World *w, bool enabled, float aggressiveness);
+
+bool alsnSetAiDebug(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetAutosave(struct ALS_Runtime *rt, const char *path, uint32_t every, bool before_enemy);
+
+bool alsnSetBackgroundFps(struct ALS_Runtime *rt, uint32_t fps);
+
+bool alsnSetBannerTheme(struct ALS_Runtime *rt,
+                        int32_t tex,
+                        int32_t font,
+                        int32_t bg,
+                        int32_t fg,
+                        float time);
+
+bool alsnSetCamera(struct ALS_World *w, float x, float y);
+
+bool alsnSetCameraAutoFollow(struct ALS_World *w, bool on);
+
+bool alsnSetCameraFollow(struct ALS_World *w, uint8_t uid, bool follow);
+
+bool alsnSetCameraFollowRate(struct ALS_World *w, float rate);
+
+#if defined(ALS_DEBUG_TOOLS)
+bool alsnSetCheat(struct ALS_World *w, uint8_t cheat, bool on);
+#endif
+
+bool alsnSetCleanScreenshots(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetConfirmExit(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetControlHints(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetCustomDifficulty(struct ALS_World *w, const struct ALS_Difficulty *d);
+
+bool alsnSetDangerZone(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetDefaultActions(struct ALS_World *w, uint8_t attacks, bool move_after_attack);
+
+bool alsnSetDifficulty(struct ALS_World *w, uint8_t preset);
+
+bool alsnSetDuckLevel(struct ALS_World *w, float level);
+
+bool alsnSetElevation(struct ALS_World *w, const uint8_t *heights, uintptr_t n);
+
+bool alsnSetElevationRules(struct ALS_World *w, const struct ALS_ElevationRules *r);
+
+bool alsnSetFogOfWar(struct ALS_World *w, bool enabled);
+
+bool alsnSetHighContrast(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetHpRounding(struct ALS_World *w, uint8_t mode);
+
+bool alsnSetHudHidden(struct ALS_Runtime *rt, bool hidden);
+
+bool alsnSetInputGuard(struct ALS_Runtime *rt, float debounce, float min_screen_time);
+
+bool alsnSetInputScheme(struct ALS_World *w, uint8_t scheme);
+
+bool alsnSetMasterVolume(struct ALS_World *w, float v);
+
+bool alsnSetMemoryBudget(struct ALS_ResourceSet *rs, uint64_t bytes);
+
+bool alsnSetMusicVolume(struct ALS_World *w, float v);
+
+bool alsnSetMuteUnfocused(struct ALS_World *w, bool on);
+
+bool alsnSetObjectiveState(struct ALS_World *w, uint32_t idx, uint8_t state);
+
+bool alsnSetPauseUnfocused(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetPopupStyle(struct ALS_Runtime *rt, uint8_t kind, int32_t color, int32_t font);
+
+bool alsnSetPortraitSize(struct ALS_Runtime *rt, float size);
+
+bool alsnSetProfileValue(struct ALS_World *w, const char *key, int64_t v);
+
+bool alsnSetRumble(struct ALS_Runtime *rt,
+                   float hit_strength,
+                   float hit_duration,
+                   float select_strength,
+                   float select_duration);
+
+bool alsnSetScreenshotDir(struct ALS_Runtime *rt, const char *dir);
+
+bool alsnSetSfxVolume(struct ALS_World *w, float v);
+
+bool alsnSetSimFps(struct ALS_Runtime *rt, uint32_t fps);
+
+bool alsnSetStatusIcon(struct ALS_Runtime *rt, uint8_t status, int32_t tex);
+
+bool alsnSetStinger(struct ALS_World *w, uint8_t kind, uint8_t sound_id, float length);
+
+bool alsnSetTextScale(struct ALS_Runtime *rt, float scale);
+
+bool alsnSetTouchInput(struct ALS_Runtime *rt, bool on);
+
+bool alsnSetTransition(struct ALS_Runtime *rt, uint8_t kind, float time, int32_t color);
+
+bool alsnSetTurnTimer(struct ALS_Runtime *rt,
+                      float per_turn,
+                      float bank,
+                      float increment,
+                      bool auto_wait);
+
+bool alsnSetUiScale(struct ALS_Runtime *rt, float scale);
+
+bool alsnSetUnitAnimPaused(struct ALS_World *w, uint8_t uid, bool paused);
+
+bool alsnSetUnitAnimSpeed(struct ALS_World *w, uint8_t uid, float speed);
+
+bool alsnSetUnitGuard(struct ALS_World *w, uint8_t uid, int32_t x, int32_t y, uint8_t radius);
+
+bool alsnSetUnitHold(struct ALS_World *w, uint8_t uid);
+
+bool alsnSetUnitPalette(struct ALS_World *w, uint8_t uid, uint8_t pid, bool enable);
+
+bool alsnSetUnitPortrait(struct ALS_World *w, uint8_t uid, int32_t tex);
+
+bool alsnSetUnitState(struct ALS_World *w, uint8_t uid, uint8_t state);
+
+bool alsnSetUnitTint(struct ALS_World *w, uint8_t uid, int32_t tint);
+
+bool alsnSetUnitTypeAi(struct ALS_World *w, uint8_t type_id, const char *path, const char *root);
+
+bool alsnSetZoom(struct ALS_World *w, float zoom);
+
+bool alsnShowPopup(struct ALS_World *w, uint8_t uid, const char *text, uint8_t kind);
+
+bool alsnShowScrollingText(struct ALS_World *w, const char *text, float speed);
+
+char *alsnStateDiff(const struct ALS_World *a, const struct ALS_World *b);
+
+uint64_t alsnStateHash(const struct ALS_World *w);
+
+bool alsnStopMusic(struct ALS_World *w);
+
+bool alsnTileAt(const struct ALS_World *w, float sx, float sy, int32_t *out_tx, int32_t *out_ty);
+
+int32_t alsnTileDistance(int32_t x1, int32_t y1, int32_t x2, int32_t y2);
+
+bool alsnTileToScreen(const struct ALS_World *w,
+                      int32_t tx,
+                      int32_t ty,
+                      float *out_sx,
+                      float *out_sy);
+
+bool alsnUnitAt(const struct ALS_World *w, int32_t x, int32_t y, uint8_t *out_uid);
+
+size_t alsnUnitCount(const struct ALS_World *w);
 
 const struct ALS_Unit *alsnUnitRef(struct ALS_World *w, uint8_t uid);
 
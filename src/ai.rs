@@ -0,0 +1,316 @@
+//! Behavior trees for the built-in AI, defined in a small TOML file so enemy behavior can be tuned without recompiling.
+//! A file defines named nodes, each a table `[node.<name>]` with a `kind` and the parameters of that kind, and assigns trees (by the name of their root node) to unit types in the table `[types]`.
+//! Composite nodes refer to their children by name.
+//!
+//! | Kind | Parameters | Succeeds if |
+//! | ---- | ---------- | ----------- |
+//! | `sequence` | `children` | every child succeeds, tried in order until one fails |
+//! | `selector` | `children` | any child succeeds, tried in order until one does |
+//! | `invert` | `children` (exactly one) | the child fails |
+//! | `enemy_in_range` | | a player unit is within range of the tile the unit ends its move on |
+//! | `enemy_within` | `radius` | a player unit is within `radius` tiles (scaled by [aggression](crate::world::Difficulty)) |
+//! | `health_below` | `ratio` | the unit has less than `ratio` of its maximum health |
+//! | `threat_below` | `limit` | the [threat](crate::world::influence) on the tile the unit ends its move on is less than `limit` |
+//! | `near_tile` | `x`, `y`, `radius` | the unit ends its move within `radius` tiles of (`x`, `y`) |
+//! | `turn_at_least` | `turn` | the current turn is at least `turn` |
+//! | `has_behavior` | | the unit has a standing [Behavior](crate::world::Behavior) |
+//! | `attack` | | a player unit is within range; the weakest one is attacked |
+//! | `approach` | | the unit has not moved, and there is a player unit; it walks toward the nearest one |
+//! | `retreat` | | the unit has not moved, and there is a player unit; it walks to the least threatened tile, farthest from the nearest one |
+//! | `move_to` | `x`, `y` | the unit has not moved; it walks toward (`x`, `y`) |
+//! | `behavior` | | the unit has not moved, and has a standing behavior; it carries it out, attacking as usual |
+//! | `wait` | | always |
+//!
+//! A tree assigned by the map (see [World::load_ai](crate::world::World::load_ai)) takes precedence over one assigned to the [UnitType](crate::world::UnitType::set_behavior_tree).
+//! # Example
+//! ```
+//! let text = r#"
+//! [types]
+//! 0 = "skirmish"
+//!
+//! [node.skirmish]
+//! kind = "selector"
+//! children = ["flee", "fight"]
+//!
+//! [node.flee]
+//! kind = "sequence"
+//! children = ["hurt", "away"]
+//!
+//! [node.hurt]
+//! kind = "health_below"
+//! ratio = 0.3
+//!
+//! [node.away]
+//! kind = "retreat"
+//!
+//! [node.fight]
+//! kind = "sequence"
+//! children = ["close_in", "strike"]
+//!
+//! [node.close_in]
+//! kind = "approach"
+//!
+//! [node.strike]
+//! kind = "attack"
+//! "#;
+//! let set = alesia::ai::TreeSet::parse(text).unwrap();
+//! assert_eq!(set.type_trees().len(), 1);
+//! assert!(set.tree("flee").is_some());
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::utils::toml_strip_comment;
+use crate::utils::toml_string;
+use crate::world;
+use crate::world::World;
+use crate::world::AiTurn;
+
+/// A node of a behavior tree. Children are indices into the nodes of the tree.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+	Sequence(Vec<usize>),
+	Selector(Vec<usize>),
+	Invert(usize),
+	EnemyInRange,
+	EnemyWithin(i32),
+	HealthBelow(f32),
+	ThreatBelow(f32),
+	NearTile((i32, i32), i32),
+	TurnAtLeast(u32),
+	HasBehavior,
+	Attack,
+	Approach,
+	Retreat,
+	MoveTo((i32, i32)),
+	Standing,
+	Wait
+}
+
+/// A behavior tree, deciding the turn of an enemy unit. See the [module documentation](self) for the node kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BehaviorTree {
+	nodes: Rc<Vec<Node>>,
+	root: usize
+}
+
+impl BehaviorTree {
+	/// Decide the turn of a unit, planning its walk and target. Returns true if the root node succeeds.
+	pub(crate) fn run(&self, w: &mut World, t: &mut AiTurn) -> bool {
+		self.eval(self.root, w, t)
+	}
+
+	fn eval(&self, i: usize, w: &mut World, t: &mut AiTurn) -> bool {
+		let uid = t.plan.uid;
+		let moved = !t.plan.path.is_empty();
+		match &self.nodes[i] {
+			Node::Sequence(c) => c.iter().all(|&c| self.eval(c, w, t)),
+			Node::Selector(c) => c.iter().any(|&c| self.eval(c, w, t)),
+			Node::Invert(c) => !self.eval(*c, w, t),
+			Node::EnemyInRange => t.weakest_in_range(w).is_some(),
+			Node::EnemyWithin(r) => t.nearest_player((*r as f32 * w.difficulty().aggression).round() as i32).is_some(),
+			Node::HealthBelow(f) => {
+				let (h, mh) = world::_unit_health(w, uid);
+				h < f * mh
+			},
+			Node::ThreatBelow(v) => t.influence(w).threat(t.end()) < *v,
+			Node::NearTile(tile, r) => w.grid().distance(t.end(), *tile) <= *r,
+			Node::TurnAtLeast(n) => w.turn() >= *n,
+			Node::HasBehavior => w.units[&uid].behavior.is_some(),
+			Node::Attack => {
+				if t.plan.target.is_none() {
+					t.plan.target = t.weakest_in_range(w);
+				}
+				t.plan.target.is_some()
+			},
+			Node::Approach | Node::Retreat if moved => false,
+			Node::Approach => match t.nearest_player(i32::MAX) {
+				Some(p) => {
+					t.walk(w, p.1, |_| true);
+					true
+				},
+				None => false
+			},
+			Node::Retreat => match t.nearest_player(i32::MAX) {
+				Some(p) => {
+					// The least threatened tile, farthest from the nearest player unit.
+					let m = t.influence(w);
+					let mut tiles = world::reachable_tiles(w, uid);
+					tiles.push(t.start);
+					tiles.sort();
+					let away = |c: &(i32, i32)| -w.grid().distance(*c, p.1);
+					let best = tiles.into_iter().filter(|&c| t.is_free(c))
+						.min_by(|a, b| m.threat(*a).partial_cmp(&m.threat(*b)).unwrap_or(Ordering::Equal).then(away(a).cmp(&away(b))));
+					if let Some(b) = best {
+						t.walk(w, b, |_| true);
+					}
+					true
+				},
+				None => false
+			},
+			Node::MoveTo(tile) => {
+				if !moved {
+					t.walk(w, *tile, |_| true);
+				}
+				!moved
+			},
+			Node::Standing => {
+				if moved || !world::standing_walk(w, t) {
+					return false;
+				}
+				if t.plan.target.is_none() {
+					t.plan.target = t.weakest_in_range(w);
+				}
+				true
+			},
+			Node::Wait => true
+		}
+	}
+}
+
+/// The behavior trees defined in a file, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeSet {
+	nodes: Rc<Vec<Node>>,
+	names: HashMap<String, usize>,
+	types: Vec<(u8, usize)>
+}
+
+/// Fields of a node table, as written.
+#[derive(Default)]
+struct RawNode {
+	line: usize,
+	kind: Option<String>,
+	children: Option<Vec<String>>,
+	params: HashMap<String, f64>
+}
+
+fn toml_string_array(v: &str) -> Option<Vec<String>> {
+	let inner = v.strip_prefix('[')?.strip_suffix(']')?.trim();
+	if inner.is_empty() {
+		return Some(vec![]);
+	}
+	inner.split(',').map(|s| toml_string(s.trim())).collect()
+}
+
+fn valid_name(n: &str) -> bool {
+	!n.is_empty() && n.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl TreeSet {
+	/// Parse the text of a behavior tree file. Returns an error message, with the line at fault, if it is malformed,
+	/// refers to undefined nodes, or has a node among its own descendants.
+	pub fn parse(text: &str) -> Result<TreeSet, String> {
+		let mut raw: Vec<(String, RawNode)> = vec![];
+		let mut types = vec![];
+		let mut table = String::new();
+		for (i, line) in text.lines().enumerate() {
+			let bad = || format!("invalid line {}: {}", i + 1, line.trim());
+			let line = toml_strip_comment(line).trim();
+			if line.is_empty() {
+				continue;
+			}
+			if let Some(t) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				table = t.trim().to_string();
+				if let Some(name) = table.strip_prefix("node.") {
+					if !valid_name(name) || raw.iter().any(|(n, _)| n == name) {
+						return Err(format!("invalid or duplicate node name on line {}: {}", i + 1, name));
+					}
+					raw.push((name.to_string(), RawNode { line: i + 1, ..RawNode::default() }));
+				} else if table != "types" {
+					return Err(bad());
+				}
+				continue;
+			}
+			let (k, v) = line.split_once('=').map(|(k, v)| (k.trim(), v.trim())).ok_or_else(bad)?;
+			match (table.as_str(), raw.last_mut()) {
+				("types", _) => types.push((k.parse::<u8>().map_err(|_| bad())?, toml_string(v).ok_or_else(bad)?, i + 1)),
+				(_, Some((_, n))) => match k {
+					"kind" => n.kind = Some(toml_string(v).ok_or_else(bad)?),
+					"children" => n.children = Some(toml_string_array(v).ok_or_else(bad)?),
+					"radius" | "ratio" | "limit" | "x" | "y" | "turn" => { n.params.insert(k.to_string(), v.parse::<f64>().ok().filter(|f| f.is_finite()).ok_or_else(bad)?); },
+					_ => return Err(bad())
+				},
+				_ => return Err(bad())
+			}
+		}
+		let names: HashMap<String, usize> = raw.iter().enumerate().map(|(i, (n, _))| (n.clone(), i)).collect();
+		let mut nodes = vec![];
+		for (name, r) in &raw {
+			let err = |what: &str| format!("node {} (line {}): {}", name, r.line, what);
+			let param = |k: &str| r.params.get(k).cloned().ok_or_else(|| err(&format!("missing {}", k)));
+			let int = |k: &str| param(k).map(|v| v as i32);
+			let children = || -> Result<Vec<usize>, String> {
+				let c = r.children.as_ref().filter(|c| !c.is_empty()).ok_or_else(|| err("missing children"))?;
+				c.iter().map(|c| names.get(c).cloned().ok_or_else(|| err(&format!("undefined child {}", c)))).collect()
+			};
+			let kind = r.kind.as_deref().ok_or_else(|| err("missing kind"))?;
+			if r.children.is_some() && !["sequence", "selector", "invert"].contains(&kind) {
+				return Err(err("only sequence, selector and invert nodes have children"));
+			}
+			nodes.push(match kind {
+				"sequence" => Node::Sequence(children()?),
+				"selector" => Node::Selector(children()?),
+				"invert" => match children()?.as_slice() {
+					&[c] => Node::Invert(c),
+					_ => return Err(err("invert takes exactly one child"))
+				},
+				"enemy_in_range" => Node::EnemyInRange,
+				"enemy_within" => Node::EnemyWithin(int("radius")?),
+				"health_below" => Node::HealthBelow(param("ratio")? as f32),
+				"threat_below" => Node::ThreatBelow(param("limit")? as f32),
+				"near_tile" => Node::NearTile((int("x")?, int("y")?), int("radius")?),
+				"turn_at_least" => Node::TurnAtLeast(int("turn")?.max(0) as u32),
+				"has_behavior" => Node::HasBehavior,
+				"attack" => Node::Attack,
+				"approach" => Node::Approach,
+				"retreat" => Node::Retreat,
+				"move_to" => Node::MoveTo((int("x")?, int("y")?)),
+				"behavior" => Node::Standing,
+				"wait" => Node::Wait,
+				k => return Err(err(&format!("unknown kind {}", k)))
+			});
+		}
+		// Every node must be finished with before it is reached again.
+		fn acyclic(nodes: &[Node], i: usize, open: &mut Vec<usize>) -> bool {
+			if open.contains(&i) {
+				return false;
+			}
+			open.push(i);
+			let ok = match &nodes[i] {
+				Node::Sequence(c) | Node::Selector(c) => c.iter().all(|&c| acyclic(nodes, c, open)),
+				Node::Invert(c) => acyclic(nodes, *c, open),
+				_ => true
+			};
+			open.pop();
+			ok
+		}
+		for (i, (name, _)) in raw.iter().enumerate() {
+			if !acyclic(&nodes, i, &mut vec![]) {
+				return Err(format!("node {} is among its own descendants", name));
+			}
+		}
+		let types = types.into_iter().map(|(tid, root, line)| match names.get(&root) {
+			Some(&r) => Ok((tid, r)),
+			None => Err(format!("undefined node {} assigned to type {} (line {})", root, tid, line))
+		}).collect::<Result<_, String>>()?;
+		Ok(TreeSet { nodes: Rc::new(nodes), names: names, types: types })
+	}
+
+	/// Read and parse a behavior tree file, see [parse](TreeSet::parse).
+	pub fn load(path: &str) -> Result<TreeSet, String> {
+		let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+		TreeSet::parse(&text)
+	}
+
+	/// Returns the tree rooted at the node with specified name, if defined.
+	pub fn tree(&self, name: &str) -> Option<BehaviorTree> {
+		self.names.get(name).map(|&r| BehaviorTree { nodes: self.nodes.clone(), root: r })
+	}
+
+	/// Returns the trees assigned to unit types in the table `[types]`, as (type id, tree).
+	pub fn type_trees(&self) -> Vec<(u8, BehaviorTree)> {
+		self.types.iter().map(|&(tid, r)| (tid, BehaviorTree { nodes: self.nodes.clone(), root: r })).collect()
+	}
+}
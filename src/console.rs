@@ -0,0 +1,261 @@
+//! Drop-down debug console, toggled with the `toggle_console` key (see [KeyBindings](crate::input::KeyBindings); default is the grave accent, i.e, backtick).
+//! Bind the key to `KEY_NULL` to disable the console, e.g, in release builds of a game.
+//!
+//! The console accepts the following commands, completed with TAB; UP and DOWN recall earlier commands, and ESCAPE closes the console.
+//!
+//! | Command | Effect |
+//! | ------- | ------ |
+//! | `spawn <type> <x> <y> [enemy]` | Spawn a player unit (or an enemy unit) of a registered type on a free tile. |
+//! | `health <uid> <value>` | Set the health of a unit, up to its maximum; a unit with no health dies on the next update. |
+//! | `teleport <uid> <x> <y>` | Move a unit to a free tile at once. |
+//! | `reveal` | Show or hide the danger zone, i.e, the tiles enemy units could attack next turn. |
+//! | `end_turn` | End the player turn, closing the console. |
+//! | `units` | List the alive units. |
+//! | `clear` | Clear the output. |
+//! | `help` | List the commands. |
+//!
+//! With the `debug_tools` feature, the [cheats](crate::debug_tools) are available as well:
+//!
+//! | Command | Effect |
+//! | ------- | ------ |
+//! | `god` | Turn god mode on or off. |
+//! | `infinite_movement` | Turn infinite movement on or off. |
+//! | `reveal_all` | Always show, or stop always showing, the danger zone and the AI debug overlay. |
+//! | `win`, `lose` | Win or lose the game, closing the console. |
+//!
+//! Game input is ignored while the console is open, and the turn timer stops; orders being carried out still are.
+
+#[cfg(feature = "debug_tools")]
+use crate::debug_tools;
+use crate::input::InputHandler;
+use crate::world;
+use crate::world::World;
+use std::collections::VecDeque;
+
+/// Names of all commands, in the order listed by `help`.
+#[cfg(not(feature = "debug_tools"))]
+pub const COMMANDS: [&str; 8] = ["spawn", "health", "teleport", "reveal", "end_turn", "units", "clear", "help"];
+/// Names of all commands, in the order listed by `help`.
+#[cfg(feature = "debug_tools")]
+pub const COMMANDS: [&str; 13] = ["spawn", "health", "teleport", "reveal", "end_turn", "units", "clear", "help", "god", "infinite_movement", "reveal_all", "win", "lose"];
+
+/// Most lines of output kept; the oldest lines are dropped beyond this.
+const OUTPUT_CAPACITY: usize = 64;
+/// Most commands kept in the history.
+const HISTORY_CAPACITY: usize = 32;
+
+#[cfg(not(feature = "debug_tools"))]
+const HELP: &str = "spawn <type> <x> <y> [enemy], health <uid> <value>, teleport <uid> <x> <y>, reveal, end_turn, units, clear, help";
+#[cfg(feature = "debug_tools")]
+const HELP: &str = "spawn <type> <x> <y> [enemy], health <uid> <value>, teleport <uid> <x> <y>, reveal, end_turn, units, clear, help, god, infinite_movement, reveal_all, win, lose";
+
+/// State of the console: whether it is open, the line being typed, output and history.
+#[derive(Debug, Default)]
+pub(crate) struct Console {
+	pub(crate) open: bool,
+	pub(crate) input: String,
+	pub(crate) output: VecDeque<String>,
+	history: Vec<String>,
+	/// Index into the history of the command recalled, if any.
+	recalled: Option<usize>
+}
+
+impl Console {
+	/// Open or close the console, keeping the line being typed.
+	pub(crate) fn toggle(&mut self) {
+		self.open = !self.open;
+	}
+
+	/// Append a line of output, discarding the oldest line if full.
+	fn print(&mut self, line: String) {
+		if self.output.len() == OUTPUT_CAPACITY {
+			self.output.pop_front();
+		}
+		self.output.push_back(line);
+	}
+
+	/// Run the line being typed, and clear it.
+	pub(crate) fn submit(&mut self, w: &mut World, is: &mut InputHandler) {
+		let line = std::mem::take(&mut self.input);
+		self.recalled = None;
+		if line.trim().is_empty() {
+			return;
+		}
+		if self.history.last() != Some(&line) {
+			if self.history.len() == HISTORY_CAPACITY {
+				self.history.remove(0);
+			}
+			self.history.push(line.clone());
+		}
+		self.exec(&line, w, is);
+	}
+
+	/// Replace the line being typed with an older (`back` true) or newer command from the history.
+	pub(crate) fn recall(&mut self, back: bool) {
+		if self.history.is_empty() {
+			return;
+		}
+		let i = match (self.recalled, back) {
+			(None, true) => self.history.len() - 1,
+			(None, false) => return,
+			(Some(i), true) => i.saturating_sub(1),
+			(Some(i), false) if i + 1 < self.history.len() => i + 1,
+			(Some(_), false) => {
+				self.recalled = None;
+				self.input.clear();
+				return;
+			}
+		};
+		self.recalled = Some(i);
+		self.input = self.history[i].clone();
+	}
+
+	/// Complete the command being typed; if several commands match, complete their common prefix and list them.
+	pub(crate) fn complete(&mut self) {
+		if self.input.contains(' ') {
+			return;
+		}
+		let matches: Vec<&str> = COMMANDS.iter().copied().filter(|c| c.starts_with(self.input.as_str())).collect();
+		match matches.as_slice() {
+			[] => {},
+			[c] => self.input = format!("{} ", c),
+			[first, rest @ ..] => {
+				let mut n = first.len();
+				for c in rest {
+					n = n.min(first.bytes().zip(c.bytes()).take_while(|(a, b)| a == b).count());
+				}
+				self.input = first[..n].to_string();
+				self.print(matches.join("  "));
+			}
+		}
+	}
+
+	/// Run a command, printing the line and its result.
+	pub(crate) fn exec(&mut self, line: &str, w: &mut World, is: &mut InputHandler) {
+		self.print(format!("> {}", line));
+		let args: Vec<&str> = line.split_whitespace().collect();
+		let res = match args.as_slice() {
+			["spawn", t, x, y] => spawn(w, t, x, y, true),
+			["spawn", t, x, y, "enemy"] => spawn(w, t, x, y, false),
+			["health", u, v] => set_health(w, u, v),
+			["teleport", u, x, y] => teleport(w, u, x, y),
+			["reveal"] => {
+				is.show_danger = !is.show_danger;
+				Ok(format!("danger zone {}", if is.show_danger { "shown" } else { "hidden" }))
+			},
+			["end_turn"] => if is.get_state() == 0 {
+				is.request_end_turn();
+				// Input is ignored while the console is open, so the turn would not end until it is closed.
+				self.open = false;
+				Ok(String::from("ending turn"))
+			} else {
+				Err(String::from("the turn can only be ended with no unit selected, in the player turn"))
+			},
+			["units"] => {
+				let mut ids = world::id_list(w);
+				ids.sort_unstable();
+				Ok(ids.iter().map(|id| {
+					let u = &w.units[id];
+					format!("{}: type {} at ({}, {}), {} hp, {}", id, world::get_type_id(w, *id), u.wpos.x as i32, u.wpos.y as i32, u.health, if u.player { "player" } else { "enemy" })
+				}).collect::<Vec<_>>().join("\n"))
+			},
+			["clear"] => {
+				self.output.clear();
+				return;
+			},
+			["help"] => Ok(String::from(HELP)),
+			#[cfg(feature = "debug_tools")]
+			["god"] => Ok(toggle_cheat(w, debug_tools::Cheat::GodMode, "god mode")),
+			#[cfg(feature = "debug_tools")]
+			["infinite_movement"] => Ok(toggle_cheat(w, debug_tools::Cheat::InfiniteMovement, "infinite movement")),
+			#[cfg(feature = "debug_tools")]
+			["reveal_all"] => Ok(toggle_cheat(w, debug_tools::Cheat::Reveal, "reveal")),
+			#[cfg(feature = "debug_tools")]
+			[c @ ("win" | "lose")] => {
+				if *c == "win" { debug_tools::instant_win(w) } else { debug_tools::instant_lose(w) }
+				// The outcome takes effect once game input is handled again.
+				self.open = false;
+				Ok(format!("{} at the next idle moment", if *c == "win" { "winning" } else { "losing" }))
+			},
+			[c, ..] if COMMANDS.contains(c) => Err(format!("usage: {}", HELP.split(", ").find(|u| u.starts_with(c)).unwrap_or(c))),
+			[c, ..] => Err(format!("unknown command {:?}, see help", c)),
+			[] => return
+		};
+		match res {
+			Ok(s) => {
+				for l in s.lines() {
+					self.print(l.to_string());
+				}
+			},
+			Err(e) => self.print(format!("error: {}", e))
+		}
+	}
+}
+
+fn parse<T: std::str::FromStr>(s: &str, what: &str) -> Result<T, String> {
+	s.parse().map_err(|_| format!("invalid {} {:?}", what, s))
+}
+
+/// Parse the id of an alive unit.
+fn unit(w: &World, s: &str) -> Result<u8, String> {
+	let uid = parse(s, "unit id")?;
+	if !w.units.contains_key(&uid) {
+		return Err(format!("no unit with id {}", uid));
+	}
+	Ok(uid)
+}
+
+/// Parse a tile, which must allow movement and be free.
+fn free_tile(w: &World, x: &str, y: &str) -> Result<(i32, i32), String> {
+	let t = (parse(x, "x")?, parse(y, "y")?);
+	let (mw, mh) = w.map_size();
+	if w.show_map() && (t.0 >= mw as i32 || t.1 >= mh as i32) {
+		return Err(format!("tile ({}, {}) is outside the map", t.0, t.1));
+	}
+	if !world::tile_type_at(w, t.0, t.1).allowed() {
+		return Err(format!("tile ({}, {}) does not allow movement", t.0, t.1));
+	}
+	if let Some(u) = world::unit_at(w, t.0, t.1) {
+		return Err(format!("tile ({}, {}) is taken by unit {}", t.0, t.1, u));
+	}
+	Ok(t)
+}
+
+fn spawn(w: &mut World, t: &str, x: &str, y: &str, plr: bool) -> Result<String, String> {
+	let tid = parse(t, "unit type")?;
+	if !w.unit_types.contains_key(&tid) {
+		return Err(format!("no unit type with id {}", tid));
+	}
+	let tile = free_tile(w, x, y)?;
+	// Same tints as units placed by the map.
+	let uid = world::spawn_unit(w, tid, tile, if plr { -1 } else { -0x38ffc328 }, plr);
+	Ok(format!("spawned unit {}", uid))
+}
+
+fn set_health(w: &mut World, u: &str, v: &str) -> Result<String, String> {
+	let v: f32 = parse(v, "health")?;
+	if !v.is_finite() {
+		return Err(format!("invalid health {}", v));
+	}
+	let uid = unit(w, u)?;
+	let max = world::_unit_health(w, uid).1;
+	let u = w.units.get_mut(&uid).unwrap();
+	u.health = v.clamp(0.0, max);
+	Ok(format!("unit {} has {} hp", uid, u.health))
+}
+
+fn teleport(w: &mut World, u: &str, x: &str, y: &str) -> Result<String, String> {
+	let uid = unit(w, u)?;
+	let t = free_tile(w, x, y)?;
+	let u = w.units.get_mut(&uid).unwrap();
+	u.wpos.x = t.0 as f32;
+	u.wpos.y = t.1 as f32;
+	Ok(format!("unit {} moved to ({}, {})", uid, t.0, t.1))
+}
+
+#[cfg(feature = "debug_tools")]
+fn toggle_cheat(w: &mut World, c: debug_tools::Cheat, name: &str) -> String {
+	let on = !debug_tools::cheat(w, c);
+	debug_tools::set_cheat(w, c, on);
+	format!("{} {}", name, if on { "on" } else { "off" })
+}
@@ -0,0 +1,91 @@
+//! Cheats for debugging and testing games, compiled in only with the `debug_tools` feature, so that release builds cannot reach them.
+//! Cheats are set on a world; besides Rust code and tests, they are available from the [console](crate::console), the native API (`alsnSetCheat` and `alsnForceOutcome`) and Python.
+//!
+//! | Cheat | Effect |
+//! | ----- | ------ |
+//! | [GodMode](Cheat::GodMode) | Player units take no damage from attacks. |
+//! | [InfiniteMovement](Cheat::InfiniteMovement) | Player units may move [INFINITE_MOVEMENT] tiles per turn. |
+//! | [Reveal](Cheat::Reveal) | The danger zone and the AI debug overlay are shown, whatever the input toggles. |
+//!
+//! [instant_win] and [instant_lose] end the game once the player turn is idle, or once the orders being carried out are done.
+//! # Example
+//! ```
+//! use alesia::debug_tools::{self, Cheat};
+//! let mut w = alesia::world::World::blank();
+//! debug_tools::set_cheat(&mut w, Cheat::GodMode, true);
+//! assert!(debug_tools::cheat(&w, Cheat::GodMode));
+//! ```
+
+use crate::input::Outcome;
+use crate::world::World;
+
+/// Tiles per turn that player units may move with the [InfiniteMovement](Cheat::InfiniteMovement) cheat.
+pub const INFINITE_MOVEMENT: u8 = 64;
+
+/// Cheats that can be turned on or off.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cheat {
+	/// Player units take no damage.
+	GodMode = 0,
+	/// Player units may move [INFINITE_MOVEMENT] tiles per turn.
+	InfiniteMovement = 1,
+	/// The danger zone and the AI debug overlay are always shown.
+	Reveal = 2
+}
+
+impl Cheat {
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<Cheat> {
+		match v {
+			0 => Some(Cheat::GodMode),
+			1 => Some(Cheat::InfiniteMovement),
+			2 => Some(Cheat::Reveal),
+			_ => None
+		}
+	}
+}
+
+/// Cheats set on a world, and the outcome forced, if any.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Cheats {
+	pub(crate) god_mode: bool,
+	pub(crate) infinite_movement: bool,
+	pub(crate) reveal: bool,
+	pub(crate) outcome: Option<Outcome>
+}
+
+/// Turn a cheat on or off.
+pub fn set_cheat(w: &mut World, c: Cheat, on: bool) {
+	let cs = &mut w.cheats;
+	match c {
+		Cheat::GodMode => cs.god_mode = on,
+		Cheat::InfiniteMovement => cs.infinite_movement = on,
+		Cheat::Reveal => cs.reveal = on
+	}
+}
+
+/// Returns true if the cheat is on.
+pub fn cheat(w: &World, c: Cheat) -> bool {
+	let cs = &w.cheats;
+	match c {
+		Cheat::GodMode => cs.god_mode,
+		Cheat::InfiniteMovement => cs.infinite_movement,
+		Cheat::Reveal => cs.reveal
+	}
+}
+
+/// Win the game, as if the victory check had returned [Outcome::Victory].
+pub fn instant_win(w: &mut World) {
+	w.cheats.outcome = Some(Outcome::Victory);
+}
+
+/// Lose the game, as if the victory check had returned [Outcome::Defeat].
+pub fn instant_lose(w: &mut World) {
+	w.cheats.outcome = Some(Outcome::Defeat);
+}
+
+/// Take the outcome forced by [instant_win] or [instant_lose], if any.
+pub(crate) fn take_outcome(w: &mut World) -> Option<Outcome> {
+	w.cheats.outcome.take()
+}
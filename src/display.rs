@@ -9,9 +9,16 @@
 //! ```
 
 use std::cmp::Ordering;
+use crate::console::Console;
 
 const BOX_STATICS: bool = false;
 const RENDER_FILTER_GAP: i32 = 2;
+/// Width of unit outlines in pixels.
+const OUTLINE_WIDTH: f32 = 2.0;
+/// Opacity of text backdrops in high-contrast mode, see [Display::set_high_contrast].
+const HC_BACKDROP: f32 = 0.9;
+/// Zoom of sprites in the animation viewer.
+const VIEWER_ZOOM: f32 = 3.0;
 const GRAYCOL: Color = Color {
         r: 200,
         g: 150,
@@ -30,6 +37,43 @@ const TITLE_OFF: f32 = 75.0;
 
 const INTRO_OFF: f32 = 165.0;
 
+/// Size of the battle log panel, and of its text.
+const LOG_WIDTH: f32 = 320.0;
+const LOG_HEIGHT: f32 = 150.0;
+const LOG_FONT: f32 = 18.0;
+/// Width of the objective tracker.
+const OBJ_WIDTH: f32 = 280.0;
+/// Width of the battle statistics panel of the results screen.
+const RESULTS_WIDTH: f32 = 320.0;
+/// Width of windows drawn with a nine-patch texture, in HUD pixels.
+const WINDOW_WIDTH: f32 = 640.0;
+/// Longest fade of a slide of the intro slideshow, in seconds.
+const SLIDE_FADE: f32 = 0.5;
+/// Height of the strip beneath a slide, for its caption.
+const SLIDE_CAPTION: f32 = 120.0;
+/// Font size of scrolling text screens, and how many times faster they scroll while SPACE or the left mouse button is held.
+const SCROLL_FONT: f32 = 23.0;
+const SCROLL_FAST: f32 = 4.0;
+/// Size of the Wait button of the unit panel.
+const WAIT_WIDTH: f32 = 96.0;
+const WAIT_HEIGHT: f32 = 30.0;
+/// Font size of the control hints bar.
+const HINTS_FONT: f32 = 18.0;
+/// Colour of the highlights of a tutorial.
+const TUTORIAL_COLOR: Color = Color::ORANGE;
+/// Colour of the tile under the gamepad cursor.
+const CURSOR_COLOR: Color = Color::SKYBLUE;
+/// Pixels scrolled per notch of the mouse wheel.
+const SCROLL_STEP: f32 = 24.0;
+/// Most simulation steps carried out in one frame; time beyond this is dropped, instead of stalling the next frames.
+const MAX_SIM_STEPS: u32 = 8;
+/// Font size of popups, and the height (in pixels) they rise before fading.
+const POPUP_FONT: f32 = 16.0;
+const POPUP_RISE: f32 = 24.0;
+/// Lines of output shown by the debug console, above the line being typed.
+const CONSOLE_LINES: usize = 12;
+const CONSOLE_FONT: f32 = 18.0;
+
 const PROHIBITED_TCOL: Color = Color {
 	r: 190,
 	g: 116,
@@ -64,21 +108,59 @@ const PLAYER_TCOL: Color = Color {
 use raylib::text::Font;
 use raylib::math::Rectangle;
 use raylib::texture::Texture2D;
+use raylib::texture::NPatchInfo;
+use raylib::consts::NPatchLayout;
 use raylib::prelude::RaylibTexture2D;
 use raylib::drawing::RaylibDrawHandle;
 use raylib::drawing::RaylibMode2D;
 use crate::utils::StateListener;
 use crate::input::InputHandler;
+use crate::input::TurnPhase;
+use crate::input::Outcome;
+use crate::input::KeyBindings;
+use crate::tutorial::Highlight;
+use crate::tutorial::TutorialControl;
+use crate::tutorial::TutorialStep;
+use crate::tutorial::UiElement;
+use crate::input::TurnTimer;
+use crate::input::InputGuard;
+use crate::input::Rumble;
+use crate::input::InputScheme;
+use crate::input::gamepad_pressed;
+use crate::input::gamepad_down;
+use crate::input::CONFIRM_BUTTON;
+use crate::input::SKIP_BUTTON;
+use crate::tween::Easing;
 use raylib::RaylibHandle;
+use raylib::RaylibThread;
 use raylib::ffi::KeyboardKey;
+use raylib::ffi::MouseButton;
 use raylib::math::Vector2;
 use raylib::camera::Camera2D;
 use crate::world::World;
+use crate::world::ObjectiveState;
+use crate::world::Slide;
+use crate::world::ScrollText;
+use crate::world::UnitStats;
+use crate::world::UnitStatus;
+use crate::world::HpRounding;
+use crate::world::PopupKind;
+use crate::world::LayerKind;
+use crate::world::Grid;
+use crate::touch::TouchTracker;
 use crate::utils::ResourceSet;
+use crate::utils::Settings;
+use crate::save::Autosave;
+use crate::save::SnapshotInfo;
+use crate::save::SaveSlots;
+use crate::save::THUMB_WIDTH;
+use crate::replay::Replay;
 use raylib::prelude::Color;
 use raylib::prelude::RaylibDraw;
 use raylib::prelude::RaylibMode2DExt;
 use raylib::prelude::RaylibAudio;
+use raylib::prelude::Shader;
+use raylib::prelude::RaylibShaderModeExt;
 
 /// A plain struct with fields for width, height and title of window.
 pub struct Display {
@@ -88,6 +170,20 @@ pub struct Display {
 	height: i32,
 	/// The target fps
 	fps: u32,
+	/// Rate of simulation steps, or 0 for one step per frame
+	sim_fps: u32,
+	/// The target fps while the window is not focused, or 0 to keep the target fps
+	background_fps: u32,
+	/// Time (in milliseconds) the built-in AI may spend deciding per frame, or 0 to decide at once
+	ai_budget: f32,
+	/// Time limits of the player turn
+	turn_timer: TurnTimer,
+	/// Protection against accidental input
+	input_guard: InputGuard,
+	/// Flag to read touch gestures rather than mouse clicks
+	touch_input: bool,
+	/// Gamepad rumble on feedback events
+	rumble: Rumble,
 	/// Flag to enable or disable vsync
 	vsync: bool,
 	/// Title of the window
@@ -95,7 +191,246 @@ pub struct Display {
 	/// Master Volume
 	mvolume: f32,
 	/// Clear colour
-	col: Color
+	col: Color,
+	/// Scale factor of HUD elements
+	ui_scale: f32,
+	/// Scale factor of font sizes
+	text_scale: f32,
+	/// Flag to draw outlines, overlays and text backdrops for high contrast
+	high_contrast: bool,
+	/// Look of HUD elements
+	theme: Theme,
+	/// Keys bound to player actions
+	keys: KeyBindings,
+	/// Flag to start in fullscreen mode
+	fullscreen: bool,
+	/// Flag to pause while the window is not focused or minimized
+	pause_unfocused: bool,
+	/// Flag to ask for confirmation when the window is closed
+	confirm_exit: bool,
+	/// Seed of the random number generator, if set
+	seed: Option<u32>,
+	/// Flag to skip the level introduction
+	skip_intro: bool,
+	/// Autosave options, if enabled
+	autosave: Option<Autosave>,
+	/// Directory screenshots are saved to; the working directory if None
+	screenshot_dir: Option<String>,
+	/// Flag to hide the HUD in screenshots, even if it is shown
+	clean_screenshots: bool,
+	/// Flag to show the control hints bar
+	control_hints: bool,
+	/// Volume of background music and sound effects, applied when the game begins
+	volumes: (f32, f32),
+	/// Flag to mute audio while the window is not focused, applied when the game begins
+	mute_unfocused: bool,
+	/// Path the replay of the game is written to, if recorded
+	replay_path: Option<String>
+}
+
+/// Configurable look of HUD elements, see [Display::set_theme].
+#[derive(Debug, Clone)]
+pub struct Theme {
+	/// Texture id of the phase banner background, stretched across the screen. If None, a strip of `banner_color` is drawn instead.
+	pub banner_tex: Option<u8>,
+	/// Font id of the phase banner text. If None, the default font is used.
+	pub banner_font: Option<u8>,
+	/// Colour of the phase banner, when there is no banner texture.
+	pub banner_color: Color,
+	/// Colour of the phase banner text.
+	pub banner_text_color: Color,
+	/// Seconds the phase banner takes to cross the screen; 0 disables the banner.
+	pub banner_time: f32,
+	/// Banner title at the start of the player phase.
+	pub player_phase_text: String,
+	/// Banner title at the start of the enemy phase.
+	pub enemy_phase_text: String,
+	/// Effect of the transitions between screens, i.e, when the introduction is dismissed, when the battle is won or lost, and when a map is loaded.
+	pub transition: Transition,
+	/// Seconds a transition takes, the first half covering the screen left and the second revealing the next; 0 disables transitions.
+	/// When a map is loaded, only the second half is shown. Input is ignored during a transition.
+	pub transition_time: f32,
+	/// Colour the screen is covered with during a transition.
+	pub transition_color: Color,
+	/// Side of the portrait of the selected unit in the unit panel, in HUD pixels; the panel is enlarged to fit it. 0 hides portraits.
+	/// See [UnitType::set_portrait](crate::world::UnitType::set_portrait).
+	pub portrait_size: f32,
+	/// Texture ids of the icons shown before each [UnitStatus] in the unit information panel, indexed by its value; statuses without an icon are shown by name only.
+	pub status_icons: [Option<u8>; UnitStatus::COUNT],
+	/// Colours of the text rising from units, for each [PopupKind], indexed by its value.
+	pub popup_colors: [Color; PopupKind::COUNT],
+	/// Font ids of the text rising from units, for each [PopupKind]; None uses the default font.
+	pub popup_fonts: [Option<u8>; PopupKind::COUNT],
+	/// Tint of tiles unseen by player units under fog of war, see [World::set_fog_of_war](crate::world::World::set_fog_of_war).
+	pub fog_color: Color
+}
+
+impl Default for Theme {
+	fn default() -> Theme {
+		Theme {
+			banner_tex: None,
+			banner_font: None,
+			banner_color: Color::BLACK.fade(0.7),
+			banner_text_color: Color::WHITE,
+			banner_time: 1.6,
+			player_phase_text: "Player Phase".to_string(),
+			enemy_phase_text: "Enemy Phase".to_string(),
+			transition: Transition::Fade,
+			transition_time: 0.6,
+			transition_color: Color::BLACK,
+			portrait_size: 96.0,
+			status_icons: [None; UnitStatus::COUNT],
+			popup_colors: [Color::RED, Color::GREEN, Color::YELLOW, Color::LIGHTGRAY, Color::ORANGE],
+			popup_fonts: [None; PopupKind::COUNT],
+			fog_color: Color::DARKGRAY
+		}
+	}
+}
+
+/// Effects of the transitions between screens, see [Theme::transition].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+	/// Fade to the transition colour, and back.
+	Fade = 0,
+	/// A circle closing on the centre of the screen, and opening again.
+	Iris = 1,
+	/// A curtain sliding in from the left, and out to the right.
+	Slide = 2
+}
+
+impl Transition {
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<Transition> {
+		match v {
+			0 => Some(Transition::Fade),
+			1 => Some(Transition::Iris),
+			2 => Some(Transition::Slide),
+			_ => None
+		}
+	}
+}
+
+/// Transition between screens in progress: the state whose screen is left (None if it is only revealed), and the time since it began.
+struct ScreenTransition {
+	from: Option<u8>,
+	t: f32
+}
+
+/// Fraction of the screen covered by a transition of `duration` seconds, `t` seconds in: rising from 0 to 1 over the first half, and falling back to 0 over the second.
+pub(crate) fn transition_cover(t: f32, duration: f32) -> f32 {
+	let half = 0.5 * duration;
+	if half <= 0.0 {
+		return 0.0;
+	}
+	if t < half {
+		Easing::Smooth.apply(t / half)
+	} else {
+		1.0 - Easing::Smooth.apply(((t - half) / half).min(1.0))
+	}
+}
+
+/// Slideshow shown before the intro text: the index of the slide shown, and the time since it appeared.
+struct SlidePlayer {
+	index: usize,
+	t: f32
+}
+
+/// Opacity of a slide of `duration` seconds, `t` seconds after it appeared: fading in and out over a quarter of the duration, or [SLIDE_FADE] seconds if shorter.
+pub(crate) fn slide_alpha(t: f32, duration: f32) -> f32 {
+	let f = (0.25 * duration).min(SLIDE_FADE);
+	if f <= 0.0 {
+		return 1.0;
+	}
+	(t / f).min((duration - t) / f).clamp(0.0, 1.0)
+}
+
+/// Scrolling text screen shown: the text, pixels scrolled, and whether the game ends after it.
+struct ScrollPlayer {
+	text: ScrollText,
+	offset: f32,
+	ending: bool
+}
+
+/// Hint at the bottom of a full-screen text or slide, naming the controls of the input scheme, e.g, "Click to continue, ESC to skip".
+pub(crate) fn hint(scheme: InputScheme, prompt: &str, action: &str) -> String {
+	match scheme.skip_prompt() {
+		Some(skip) => format!("{} to {}, {} to skip", prompt, action, skip),
+		None => format!("{} to {}", prompt, action)
+	}
+}
+
+/// Returns true once text `text_height` pixels tall, scrolled up by `offset` pixels from the bottom of a screen `height` pixels tall, has left the screen.
+pub(crate) fn scroll_done(offset: f32, height: f32, text_height: f32) -> bool {
+	offset >= height + text_height
+}
+
+/// Styled text of the stats of a unit, for the unit information panel, with the icons of its statuses (see [Theme::status_icons]) and health rounded by `r`.
+pub(crate) fn stat_text(st: &UnitStats, icons: &[Option<u8>], r: HpRounding) -> String {
+	let mut s = format!("[b]{}[/b] ({})\nHP: [b]{}[/b] / {}\nAttack: [b]{:.1}[/b]", st.name, if st.player { "player" } else { "enemy" }, r.apply(st.health), r.apply(st.max_health), st.attack);
+	if st.boost > 0.0 {
+		s.push_str(&format!(" [green](+{:.0}%)[/green]", 100.0 * st.boost));
+	}
+	s.push_str(&format!("\nDefense: [b]{:.1}[/b]\nMovement: [b]{}[/b]\nRange: [b]{}[/b]", st.defense, st.movement, st.range));
+	if !st.statuses.is_empty() {
+		let names: Vec<String> = st.statuses.iter().map(|x| match icons.get(*x as usize).copied().flatten() {
+			Some(id) => format!("{{icon:{}}}{}", id, x.name()),
+			None => x.name().to_string()
+		}).collect();
+		s.push_str(&format!("\n[yellow]{}[/yellow]", names.join("  ")));
+	}
+	s
+}
+
+/// Returns the area of the element of the HUD highlighted by `h`, in HUD co-ordinates, if it is one and is shown; `hints` is the text of the control hints bar, if shown.
+fn ui_rect(disp: &Display, rs: &ResourceSet, w: &World, is: &InputHandler, h: &Highlight, hints: Option<&str>) -> Option<Rectangle> {
+	let portrait = if is.group().is_empty() { crate::world::unit_portrait(w, is.cur_id) } else { None };
+	let panel = is.show || !is.group().is_empty();
+	match *h {
+		Highlight::Ui(UiElement::UnitPanel) if panel => Some(disp.unit_panel(rs, portrait).0),
+		Highlight::Ui(UiElement::WaitButton) if panel && is.can_wait() => Some(disp.wait_button(rs, portrait)),
+		Highlight::Ui(UiElement::Log) if is.show_log => Some(disp.log_rect()),
+		Highlight::Ui(UiElement::Minimap) if is.show && is.show_info => {
+			let side = (disp.hud_size().0 / 4) as f32;
+			Some(Rectangle::new(disp.hud_size().0 as f32 - side, 0.0, side, disp.minimap_height(w)))
+		},
+		Highlight::Ui(UiElement::ControlHints) => hints.map(|t| disp.hints_rect(rs, t)),
+		_ => None
+	}
+}
+
+/// Text of the unit information panel for the selected unit: its live stats, followed by the info string of its type, if any.
+fn info_text(w: &World, is: &InputHandler, th: &Theme) -> Option<String> {
+	let mut st = crate::world::unit_stats(w, is.cur_id)?;
+	if is.has_acted(is.cur_id) {
+		st.statuses.push(UnitStatus::Acted);
+	}
+	let mut s = stat_text(&st, &th.status_icons, w.hp_rounding());
+	if let Some(info) = crate::world::_unit_info(w, is.cur_id) {
+		s.push_str("\n\n");
+		s.push_str(info);
+	}
+	Some(s)
+}
+
+/// Banner announcing a new phase, and the time since it appeared.
+struct Banner {
+	phase: TurnPhase,
+	title: String,
+	sub: String,
+	t: f32
+}
+
+/// Horizontal offset of the phase banner, from `-width` (off screen, left) through 0 (centred) to `width` (off screen, right),
+/// at progress `p` in [0, 1]. The banner eases in over the first quarter, holds, then eases out over the last quarter.
+pub(crate) fn banner_offset(p: f32, width: f32) -> f32 {
+	if p < 0.25 {
+		-width * (1.0 - Easing::Smooth.apply(p / 0.25))
+	} else if p > 0.75 {
+		width * Easing::Smooth.apply((p - 0.75) / 0.25)
+	} else {
+		0.0
+	}
 }
 
 struct Renderable<'a> {
@@ -104,6 +439,8 @@ struct Renderable<'a> {
 	tex: &'a Texture2D,
 	reg: Option<Rectangle>,
 	tint: Color,
+	shader: Option<&'a Shader>,
+	outline: Option<Color>,
 	is_static: bool
 }
 
@@ -115,6 +452,8 @@ impl Renderable<'_> {
 			tex: tex,
 			reg: None,
 			tint: Color::WHITE,
+			shader: None,
+			outline: None,
 			is_static: true
 		}
 	}
@@ -126,21 +465,45 @@ impl Renderable<'_> {
 			tex: tex,
 			reg: Some(rec),
 			tint: Color::WHITE,
+			shader: None,
+			outline: None,
 			is_static: true	
 		}
 	}
 
-	fn new_unit(tex: &Texture2D, wpos: Vector2, spos: Vector2, rec: Rectangle, tint: Color) -> Renderable {
+	fn new_unit<'a>(tex: &'a Texture2D, wpos: Vector2, spos: Vector2, rec: Rectangle, tint: Color, shader: Option<&'a Shader>, outline: Option<Color>) -> Renderable<'a> {
 		Renderable {
 			tex: tex,
 			wpos: wpos,
 			spos: spos,
 			reg: Some(rec),
 			tint: tint,
+			shader: shader,
+			outline: outline,
 			is_static: false
 		}
 	}
 
+	/// Draw the silhouette of this renderable in the outline colour, offset `width` pixels in all four directions. Must be drawn with the outline shader.
+	fn draw_outline(&self, d: &mut impl RaylibDraw, width: f32) {
+		if let (Some(col), Some(rec)) = (self.outline, self.reg) {
+			for (dx, dy) in [(-width, 0.0), (width, 0.0), (0.0, -width), (0.0, width)].iter() {
+				d.draw_texture_rec(self.tex, rec, self.spos + Vector2::new(*dx, *dy), col);
+			}
+		}
+	}
+
+	/// Draw this renderable, returning the size of the drawn texture (region).
+	fn draw(&self, d: &mut impl RaylibDraw) -> (i32, i32) {
+		if let Some(rec) = self.reg {
+			d.draw_texture_rec(self.tex, rec, self.spos, self.tint);
+			(rec.width as i32, rec.height as i32)
+		} else {
+			d.draw_texture_v(self.tex, self.spos, self.tint);
+			(self.tex.width(), self.tex.height())
+		}
+	}
+
 	fn cmp(r1: &Renderable, r2: &Renderable) -> Ordering{
 		let diff = r1.wpos - r2.wpos;
 		if diff.x < 0.0 {
@@ -157,6 +520,11 @@ impl Renderable<'_> {
 			}
 		}
 	}
+
+	/// Order of drawing on hexagonal grids: row by row, as rows overlap those above them.
+	fn cmp_rows(r1: &Renderable, r2: &Renderable) -> Ordering {
+		r1.wpos.y.partial_cmp(&r2.wpos.y).unwrap_or(Ordering::Equal).then(r1.wpos.x.partial_cmp(&r2.wpos.x).unwrap_or(Ordering::Equal))
+	}
 }
 
 impl Display {
@@ -167,27 +535,280 @@ impl Display {
 			height: height,
 			title: title.to_string(),
 			fps: fps,
+			sim_fps: 0,
+			background_fps: 0,
+			ai_budget: 0.0,
+			turn_timer: TurnTimer::default(),
+			input_guard: InputGuard::default(),
+			touch_input: false,
+			rumble: Rumble::default(),
 			vsync: vsync,
 			mvolume: mvolume,
-			col: col
+			col: col,
+			ui_scale: 1.0,
+			text_scale: 1.0,
+			high_contrast: false,
+			theme: Theme::default(),
+			keys: KeyBindings::default(),
+			fullscreen: false,
+			pause_unfocused: false,
+			confirm_exit: false,
+			seed: None,
+			skip_intro: false,
+			autosave: None,
+			screenshot_dir: None,
+			clean_screenshots: false,
+			control_hints: true,
+			volumes: (1.0, 1.0),
+			mute_unfocused: false,
+			replay_path: None
 		}
 	}
 
+	/// Constructor method. Returns display struct with the resolution, fullscreen mode, frame rates, AI budget, vsync, volumes and muting, ui and text scale, high-contrast mode and key bindings of `s`, and black background.
+	pub fn from_settings(s: &Settings, title: &str) -> Display {
+		let mut d = Display::new(s.width, s.height, s.fps, s.vsync, title, Color::BLACK, s.master_volume);
+		d.sim_fps = s.sim_fps;
+		d.background_fps = s.background_fps;
+		d.set_ai_budget(s.ai_budget_ms);
+		d.set_ui_scale(s.ui_scale);
+		d.set_text_scale(s.text_scale);
+		d.high_contrast = s.high_contrast;
+		d.keys = s.keys;
+		d.fullscreen = s.fullscreen;
+		d.pause_unfocused = s.pause_unfocused;
+		d.volumes = (s.music_volume, s.sfx_volume);
+		d.mute_unfocused = s.mute_unfocused;
+		d
+	}
+
 	/// Constructor method. Returns display struct with specified width, height, title, 60fps, vsync-enabled and black background.
 	pub fn new_s(width: i32, height: i32, title: &str) -> Display {
 		Display::new(width, height, 60, true, title, Color::BLACK, 1.0)
 	}
 
+	/// Carry out orders and update units at a fixed `fps` steps per second, independent of the frame rate, e.g, to keep battles deterministic on fast displays.
+	/// Several steps may run in one frame, or none. Default is 0, i.e, one step per frame.
+	pub fn set_sim_fps(&mut self, fps: u32) {
+		self.sim_fps = fps;
+	}
+
+	/// Lower the target fps to `fps` while the window is not focused, to save power. Default is 0, i.e, no throttling.
+	pub fn set_background_fps(&mut self, fps: u32) {
+		self.background_fps = fps;
+	}
+
+	/// Spread the decisions of the built-in AI across frames during the enemy turn, spending at most about `ms` milliseconds per frame,
+	/// so large searches do not stall the frame; a "Thinking" indicator is shown meanwhile. Default is 0, i.e, all decisions are made at once.
+	pub fn set_ai_budget(&mut self, ms: f32) {
+		self.ai_budget = if ms.is_finite() { ms.max(0.0) } else { 0.0 };
+	}
+
+	/// Limit the time of each player turn to `per_turn` seconds, and of all player turns together to `bank` seconds (0 for no limit), chess-clock style,
+	/// adding `increment` seconds to the bank at the start of each turn. A countdown is shown while the player may give orders.
+	/// When time runs out, the `on_timeout` [callback](StateListener) is invoked and the turn ends; the orders given to the selected unit are carried out if `auto_wait`, or discarded.
+	/// Default is no limit.
+	pub fn set_turn_timer(&mut self, per_turn: f32, bank: f32, increment: f32, auto_wait: bool) {
+		self.turn_timer = TurnTimer::new(per_turn, bank, increment, auto_wait);
+	}
+
+	/// Ignore an action repeated within `debounce` seconds, e.g, a double click on a tile, which could give an order twice; and keep the intro and results screens
+	/// shown for at least `min_screen_time` seconds before a click dismisses them. Default is 0.25 and 0 seconds; see [InputGuard].
+	pub fn set_input_guard(&mut self, debounce: f32, min_screen_time: f32) {
+		self.input_guard = InputGuard::new(debounce, min_screen_time);
+	}
+
+	/// Read touch input: tap to select, drag to pan, pinch to zoom, and long-press to show unit information; see [touch](crate::touch). Default is false, i.e, mouse input.
+	pub fn set_touch_input(&mut self, on: bool) {
+		self.touch_input = on;
+	}
+
+	/// Rumble a gamepad when a player unit takes a hit, and when a unit is selected, each with a strength (0 to 1) and duration (in seconds); 0 disables. Default is no rumble.
+	/// raylib does not drive gamepad motors, so rumbles are published as [Rumble](crate::event::EngineEvent::Rumble) events, for the game to play with its platform's gamepad API; see [Rumble].
+	pub fn set_rumble(&mut self, on_hit: (f32, f32), on_select: (f32, f32)) {
+		self.rumble = Rumble::new(on_hit, on_select);
+	}
+
+	/// Pause the game while the window is not focused or minimized: orders are not carried out, input and timers stop, and audio is muted. Default is false.
+	pub fn set_pause_unfocused(&mut self, on: bool) {
+		self.pause_unfocused = on;
+	}
+
+	/// Ask "Are you sure?" when the window is closed, before the game exits. Closing the window again while asked exits at once. Default is false.
+	pub fn set_confirm_exit(&mut self, on: bool) {
+		self.confirm_exit = on;
+	}
+
+	/// Set the scale factor of HUD elements (unit information, minimap, message windows). Default is 1.0.
+	pub fn set_ui_scale(&mut self, s: f32) {
+		if s > 0.0 {
+			self.ui_scale = s;
+		} else {
+			eprintln!("warning [display]: Ignoring non-positive ui scale {}", s);
+		}
+	}
+
+	/// Set the scale factor of font sizes across the HUD, windows, scrolling text and popups, e.g, for readability; text panels grow to fit. Applies on top of the ui scale. Default is 1.0.
+	pub fn set_text_scale(&mut self, s: f32) {
+		if s > 0.0 {
+			self.text_scale = s;
+		} else {
+			eprintln!("warning [display]: Ignoring non-positive text scale {}", s);
+		}
+	}
+
+	/// Switch to a high-contrast look: unit outlines and highlight frames twice as thick, opaque minimap colours, tile overlays (movement and attack range, danger zone, tutorial highlights) drawn twice as opaque,
+	/// nearly opaque backdrops behind text, and hints in white. Default is false.
+	pub fn set_high_contrast(&mut self, on: bool) {
+		self.high_contrast = on;
+	}
+
+	/// Font size `size`, scaled by the text scale.
+	fn text_size(&self, size: f32) -> f32 {
+		size * self.text_scale
+	}
+
+	/// Integer font size `size`, scaled by the text scale, for raylib's default text.
+	fn text_px(&self, size: i32) -> i32 {
+		(size as f32 * self.text_scale).round() as i32
+	}
+
+	/// Factor applied to the width of outlines and highlight frames: 2 in high-contrast mode, else 1.
+	fn line_scale(&self) -> f32 {
+		if self.high_contrast { 2.0 } else { 1.0 }
+	}
+
+	/// Width of a frame `w` pixels wide, accounting for high-contrast mode.
+	fn line_width(&self, w: i32) -> i32 {
+		(w as f32 * self.line_scale()) as i32
+	}
+
+	/// Backdrop behind text, `a` opaque; nearly opaque in high-contrast mode.
+	fn backdrop(&self, a: f32) -> Color {
+		Color::BLACK.fade(if self.high_contrast { HC_BACKDROP.max(a) } else { a })
+	}
+
+	/// Colour of hints beneath full-screen text, white in high-contrast mode.
+	fn hint_color(&self) -> Color {
+		if self.high_contrast { Color::WHITE } else { Color::GRAY }
+	}
+
+	/// Colour `c` of a minimap cell or marker, opaque in high-contrast mode.
+	fn map_color(&self, c: Color) -> Color {
+		if self.high_contrast { Color { a: 255, ..c } } else { c }
+	}
+
+	/// Draw a tile overlay, i.e, the texture with the specified id at (x, y); twice in high-contrast mode, so it is more opaque.
+	fn _draw_overlay(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, id: u8, x: i32, y: i32, tint: Color) {
+		self._draw_ui(d, rs, id, x, y, tint);
+		if self.high_contrast {
+			self._draw_ui(d, rs, id, x, y, tint);
+		}
+	}
+
+	/// Set the look of HUD elements, e.g, the phase banner.
+	pub fn set_theme(&mut self, t: Theme) {
+		self.theme = t;
+	}
+
+	/// Set the keys bound to player actions.
+	pub fn set_key_bindings(&mut self, k: KeyBindings) {
+		self.keys = k;
+	}
+
+	/// Start the game in fullscreen mode, or in a window (the default).
+	pub fn set_fullscreen(&mut self, on: bool) {
+		self.fullscreen = on;
+	}
+
+	/// Seed the random number generator (used e.g, for idle animations) once the window is created, for reproducible runs.
+	pub fn set_seed(&mut self, seed: u32) {
+		self.seed = Some(seed);
+	}
+
+	/// Skip the level introduction, starting the game at the player turn.
+	pub fn set_skip_intro(&mut self, skip: bool) {
+		self.skip_intro = skip;
+	}
+
+	/// Save screenshots (CTRL + S by default) to `dir` as `screenshot-<time in ms>.png`, rather than to `screen.png` in the working directory. See [data_dir](crate::utils::data_dir).
+	pub fn set_screenshot_dir(&mut self, dir: &str) {
+		self.screenshot_dir = Some(dir.to_string());
+	}
+
+	/// Hide all HUD elements and overlays in screenshots, as if toggled off (H by default) for the frame captured.
+	pub fn set_clean_screenshots(&mut self, on: bool) {
+		self.clean_screenshots = on;
+	}
+
+	/// Show or hide the bar at the bottom of the screen naming the controls available, e.g, "LMB: select, RMB: info, ENTER: end turn", as bound and for the input device last used. Shown by default.
+	/// See [control_hints](crate::input::control_hints).
+	pub fn set_control_hints(&mut self, on: bool) {
+		self.control_hints = on;
+	}
+
+	/// Returns the path the next screenshot is saved to, creating the screenshot directory if needed.
+	fn screenshot_path(&self) -> String {
+		match &self.screenshot_dir {
+			Some(d) => {
+				if let Err(e) = std::fs::create_dir_all(d) {
+					eprintln!("warning [display]: failed to create {}: {}", d, e);
+				}
+				let t = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_millis());
+				format!("{}/screenshot-{}.png", d, t)
+			},
+			None => "screen.png".to_string()
+		}
+	}
+
+	/// Save a [snapshot](crate::save) of the battle to `path` at the start of every `every` player turns (never if 0), and also as the enemy phase begins if `before_enemy` is set.
+	/// If the previous session did not shut down normally, the player is asked whether to resume from the autosave when the game begins.
+	pub fn set_autosave(&mut self, path: &str, every: u32, before_enemy: bool) {
+		self.autosave = Some(Autosave { path: path.to_string(), every: every, before_enemy: before_enemy });
+	}
+
+	/// Record the orders carried out in the game as a [replay](crate::replay), written to `path` when the game ends or the results screen is dismissed.
+	/// Set a [simulation rate](Display::set_sim_fps) as well, so that the replay can be carried out as it was played.
+	pub fn record_replay(&mut self, path: &str) {
+		self.replay_path = Some(path.to_string());
+	}
+
+	/// Size of the screen in HUD co-ordinates, i.e, accounting for the ui scale.
+	fn hud_size(&self) -> (i32, i32) {
+		((self.width as f32 / self.ui_scale) as i32, (self.height as f32 / self.ui_scale) as i32)
+	}
+
 	/// Overload for `Display.begin`, uses default state listener, which ignores all notifications.
 	pub fn begin_s(self, rs: ResourceSet, w: World) {
 		self.begin(rs, w, StateListener::new());
 	}
 
-	/// Begin the draw-update loop.
-	pub fn begin(self, mut rs: ResourceSet, mut w: World, mut sl: StateListener) {
+	/// Begin the draw-update loop. Blocks until the window is closed.
+	pub fn begin(self, rs: ResourceSet, w: World, sl: StateListener) {
+		let mut rt = self.init(rs, w, sl);
+		while rt.step() {}
+		rt.shutdown();
+	}
+
+	/// Open an animation viewer instead of the game, to validate the animations of all registered unit types. Blocks until the window is closed.
+	/// * LEFT / RIGHT - select the unit type.
+	/// * UP / DOWN - select the unit state.
+	/// * SPACE - pause or resume the animation.
+	/// * ',' / '.' - step to the previous or next frame while paused.
+	/// * O - toggle onion-skin, i.e, the previous frame drawn translucently beneath the current one.
+	pub fn view_anims(self, rs: ResourceSet, w: World) {
+		let mut rt = self.init(rs, w, StateListener::new());
+		let mut v = AnimViewer::default();
+		while rt.view_step(&mut v) {}
+		rt.shutdown();
+	}
+
+	/// Create the window, load all resources and return a [Runtime], without entering the draw-update loop.
+	/// The caller then drives the loop using [Runtime::step], and finally calls [Runtime::shutdown].
+	pub fn init(self, mut rs: ResourceSet, mut w: World, mut sl: StateListener) -> Runtime {
 		// Initialization
 		let mut rb = raylib::init();
-		let mut cam = Camera2D {
+		let cam = Camera2D {
 			target: Vector2::new(0.0,0.0),
 			offset: Vector2::new(0.0,0.0),
 			rotation: 0.0,
@@ -199,90 +820,103 @@ impl Display {
 		}
 		let (mut rl, thread) = rb.build();
 		rl.set_exit_key(Some(KeyboardKey::KEY_NULL));
+		if self.fullscreen {
+			rl.toggle_fullscreen();
+		}
+		// Window creation seeds the generator with the current time, so a fixed seed must come after it.
+		if let Some(s) = self.seed {
+			unsafe { srand(s) };
+		}
 		let mut rlau = RaylibAudio::init_audio_device();
 		// Load resources
 		println!("info [alesia/display.rs] : Loading resources from resource set.");
-		crate::utils::load_all(&mut rs, &mut rl, &thread);
+		let loaded = crate::utils::load_all(&mut rs, &mut rl, &thread);
+		for msg in crate::world::validate_animations(&w, |id| rs.texture_size(id)) {
+			eprintln!("warning [resources]: {}", msg);
+		}
 		rl.set_target_fps(self.fps);
-		rlau.set_master_volume(self.mvolume);
+		if self.mvolume != 1.0 {
+			w.set_master_volume(self.mvolume);
+		}
+		rlau.set_master_volume(w.mixer().effective_master_volume());
+		if self.volumes != (1.0, 1.0) {
+			w.set_music_volume(self.volumes.0);
+			w.set_sfx_volume(self.volumes.1);
+		}
+		if self.mute_unfocused {
+			w.set_mute_unfocused(true);
+		}
 		sl.notify_init();
+		for (id, kind, ok) in loaded {
+			sl.publish(&mut w, crate::event::EngineEvent::ResourceLoaded { id: id, kind: kind, ok: ok });
+		}
+		if let Some(m) = w.take_loaded_map() {
+			sl.notify_map_loaded(&mut w, &m);
+		}
 
 		if let Some(a) = rs.get_music(w.bgm_id) {
 			rlau.play_music_stream(a);
 		}
 
 		let mut is = InputHandler::new();
-
-		// Main loop
-		while !rl.window_should_close() {
-			let r = rl.get_mouse_position();
-			// Draw scope. All rendering occurs here.
-			{	
-				let mut d = rl.begin_drawing(&thread);
-				d.clear_background(self.col);
-				// Camera scope.
-				{
-					_man_cam(&mut cam, &w);
-					let mut d = d.begin_mode2D(cam);
-					self._draw_world(&mut d, &w, &rs, &is, &r, &mut rlau);
-				}
-				// HUD Goes here.
-				d.draw_fps(0,0);
-
-				if is.show {
-					let rtex = rs.get_texture(0xf2);
-					d.draw_texture(rtex, 0,0, Color::WHITE);
-					d.draw_rectangle_lines_ex(HPREC, 3, Color::BLACK);
-					let (h, mh) = crate::world::_unit_health(&w, is.cur_id);
-					let hfrac = (h as f32) / (mh as f32);
-					let width = (116.0*(hfrac)) as i32;
-					let col = Color {
-						r: (255.0*(1.0-hfrac)) as u8,
-						g: (255.0*hfrac) as u8,
-						b: 0,
-						a: 255
-					};
-					d.draw_rectangle((XOFF as i32)+2, 42, width, 15, col);
-					d.draw_text_ex(rs.get_default_font(), &format!("HP: {} / {}", h, mh), Vector2::new(XOFF,64.0), 22.0, 1.0, Color::BLACK);
-					d.draw_text_ex(rs.get_default_font(), &format!("{}", is), Vector2::new(XOFF,20.0), 22.0, 1.2, Color::BLACK);
-					if is.show_info {
-						let tex = rs.get_texture(0xf8);
-						d.draw_texture(tex, 0, rtex.height(), Color::WHITE);
-						match crate::world::_unit_info(&w, is.cur_id) {
-							Some(text) => {
-								d.draw_text_ex(rs.get_default_font(), text, Vector2::new(XOFF, rtex.height() as f32 + 20.0), 22.0, 1.0, Color::BLACK);
-							},
-							None => {}
-						}
-						self._draw_minimap(&mut d, &w);
-					}
-				}
-				if is.get_state() == 7 {
-					self._draw_window(0xf5, w.map_title(), w.intro_text(), &rs, &mut d);
-				} else if is.get_state() == 5 {
-					self._draw_window(0xf6, "Victory is thine", w.victory_text(), &rs, &mut d);
-				} else if is.get_state() == 6 {
-					self._draw_window(0xf6, "'Tis defeat", w.defeat_text(), &rs, &mut d);
-				}
-			}
-			// Save screenshot
-			if rl.is_key_pressed(KeyboardKey::KEY_S) && rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) {
-				rl.take_screenshot(&thread,"screen.png");
-			}
-			// Camera controls are always active.
-			_cam_control(&mut w, &rl);
-			is.handle(&mut w, &rl, &mut sl, &mut rlau, &mut rs);
-			if let Some(a) = rs.get_music(w.bgm_id) {
-				rlau.update_music_stream(a);
+		is.keys = self.keys;
+		is.ai_budget = self.ai_budget;
+		is.timer = self.turn_timer;
+		is.guard = self.input_guard;
+		is.touch = if self.touch_input { Some(TouchTracker::default()) } else { None };
+		is.rumble = self.rumble;
+		if self.skip_intro {
+			is.skip_intro();
+		}
+		if let Some((p, acted)) = w.resume.take() {
+			is.skip_intro();
+			is.resume(&mut w, p, &acted);
+		}
+		if self.replay_path.is_some() {
+			if self.sim_fps == 0 {
+				eprintln!("warning [display]: recording a replay without a simulation rate; it may not be carried out as played");
 			}
+			is.replay = Some(Replay { sim_fps: self.sim_fps, start: String::new(), phases: Vec::new() });
+		}
+		let recovery = self.autosave.as_ref().and_then(|a| a.begin_session());
+		let phase = is.get_state();
+		let slides = if phase == 7 && !w.intro_slides().is_empty() { Some(SlidePlayer { index: 0, t: 0.0 }) } else { None };
+		Runtime {
+			rs: rs,
+			w: w,
+			sl: sl,
+			is: is,
+			phase: phase,
+			cam: cam,
+			last_mouse: Vector2::zero(),
+			tutorial_step: None,
+			narrated_sel: None,
+			banner: None,
+			transition: None,
+			slides: slides,
+			scroll: None,
+			recovery: recovery,
+			hud_hidden: false,
+			shot_pending: false,
+			sim_time: 0.0,
+			throttled: false,
+			paused: false,
+			confirming_exit: false,
+			exited: false,
+			finished: false,
+			console: Console::default(),
+			rlau: rlau,
+			rl: rl,
+			thread: thread,
+			disp: self
 		}
 	}
 
 	fn is_pos_offscreen(&self, v: &Vector2, w: &World, gap: i32) -> bool {
 		let (cx, cy) = w.get_cpos();
 		let gap = gap as f32;
-		let (xmin, xmax) = (cx - gap, gap + cx + self.width as f32);
-		let (ymin, ymax) = (cy - gap, gap +cy + self.height as f32);
+		let (xmin, xmax) = (cx - gap, gap + cx + self.width as f32 / w.zoom());
+		let (ymin, ymax) = (cy - gap, gap +cy + self.height as f32 / w.zoom());
 		if v.x < xmin || v.x > xmax || v.y < ymin || v.y > ymax {
 			true
 		} else {
@@ -293,8 +927,8 @@ impl Display {
 	fn is_ipos_offscreen(&self, x: i32, y: i32, w: &World, gap: i32) -> bool {
 		let (cx, cy) = w.get_cpos();
 		let (cx, cy) = (cx as i32, cy as i32);
-		let (xmin, xmax) = (cx-gap, cx + self.width + gap);
-		let (ymin, ymax) = (cy-gap, cy + self.height + gap);
+		let (xmin, xmax) = (cx-gap, cx + (self.width as f32 / w.zoom()) as i32 + gap);
+		let (ymin, ymax) = (cy-gap, cy + (self.height as f32 / w.zoom()) as i32 + gap);
 		if x < xmin || x > xmax || y < ymin || y > ymax {
 			true
 		} else {
@@ -302,36 +936,274 @@ impl Display {
 		}
 	}
 
-	fn _draw_minimap(&self, d: &mut RaylibDrawHandle<'_>, world: &World) {
-		let total_side = self.width / 4;
-		let xoff = self.width - total_side;
+	/// Draw the objective tracker at the top right, below the minimap if `below` is the minimap's height.
+	fn _draw_objectives(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, w: &World, below: f32) {
+		if w.objectives().is_empty() {
+			return;
+		}
+		let text = w.objectives().iter().map(|o| match o.state {
+			ObjectiveState::Pending => format!("- {}", o.text),
+			ObjectiveState::Completed => format!("[green]+ {}[/green]", o.text),
+			ObjectiveState::Failed => format!("[red]x {}[/red]", o.text)
+		}).collect::<Vec<_>>().join("\n");
+		let (width, _) = self.hud_size();
+		let h = crate::text::measure_styled(rs, &text, self.text_size(LOG_FONT), OBJ_WIDTH - 12.0);
+		let rec = Rectangle::new(width as f32 - OBJ_WIDTH - 10.0, below + 10.0, OBJ_WIDTH, h + 8.0);
+		d.draw_rectangle_rec(rec, self.backdrop(0.6));
+		crate::text::draw_styled(d, rs, &text, Vector2::new(rec.x + 6.0, rec.y + 4.0), self.text_size(LOG_FONT), OBJ_WIDTH - 12.0, Color::WHITE, false);
+	}
+
+	/// Height of the minimap, in HUD co-ordinates.
+	fn minimap_height(&self, world: &World) -> f32 {
+		let (w, h) = world.map_size();
+		if w == 0 {
+			return 0.0;
+		}
+		(self.minimap_cell(world) * h as i32) as f32
+	}
+
+	/// Side of a cell of the minimap, in HUD co-ordinates; the map must be loaded.
+	fn minimap_cell(&self, world: &World) -> i32 {
+		let total_side = self.hud_size().0 / 4;
+		let w = world.map_size().0 as i32;
+		// Odd rows of hexagonal maps are shifted right by half a cell.
+		if world.grid() == Grid::Hex { 2*total_side / (2*w + 1) } else { total_side / w }
+	}
+
+	fn _draw_minimap(&self, d: &mut impl RaylibDraw, world: &World) {
+		let (width, _) = self.hud_size();
+		let total_side = width / 4;
+		let xoff = width - total_side;
 		let (w, h) = world.map_size();
-		let side: i32 = total_side / w as i32;
+		let side: i32 = self.minimap_cell(world);
+		let shift = |j: i32| if world.grid() == Grid::Hex { (j & 1) * side / 2 } else { 0 };
 		for i in 0..w as i32 {
 			for j in 0..h as i32 {
-				let rx: i32 = xoff + i*side;
+				let rx: i32 = xoff + i*side + shift(j);
 				let ry: i32 = j*side;
-				d.draw_rectangle(rx, ry, side, side, _tile_colour(i, j, world))
+				d.draw_rectangle(rx, ry, side, side, self.map_color(*_tile_colour(i, j, world)));
+				if !crate::world::is_tile_visible(world, i, j) {
+					d.draw_rectangle(rx, ry, side, side, self.theme.fog_color.fade(0.7));
+				}
 			}
 		}
 		for (_id, u) in &world.units {
+			if !crate::world::is_unit_visible(world, *_id) {
+				continue;
+			}
 			let (i,j) = (u.wpos.x as i32, u.wpos.y as i32);
-			let (cx, cy) = (xoff + i*side + side/2, j * side + side/2);
+			let (cx, cy) = (xoff + i*side + shift(j) + side/2, j * side + side/2);
 			if u.player {
-				d.draw_ellipse(cx, cy, (side/3) as f32, (side/3) as f32, PLAYER_TCOL);
+				d.draw_ellipse(cx, cy, (side/3) as f32, (side/3) as f32, self.map_color(PLAYER_TCOL));
 			} else {
-				d.draw_ellipse(cx, cy, (side/3) as f32, (side/3) as f32, ENEMY_TCOL);
+				d.draw_ellipse(cx, cy, (side/3) as f32, (side/3) as f32, self.map_color(ENEMY_TCOL));
+			}
+		}
+	}
+
+	/// Area of the top of the unit panel, enlarged to fit the portrait with the specified texture id, if any, and the left edge of its text, in HUD co-ordinates.
+	fn unit_panel(&self, rs: &ResourceSet, portrait: Option<u8>) -> (Rectangle, f32) {
+		let top = rs.get_ui_frame(0xf2).1;
+		let p = self.theme.portrait_size;
+		match portrait {
+			Some(_) if p > 0.0 => (Rectangle::new(0.0, 0.0, top.width + p + 10.0, top.height.max(p + 30.0)), XOFF + p + 10.0),
+			_ => (Rectangle::new(0.0, 0.0, top.width, top.height), XOFF)
+		}
+	}
+
+	/// Draw the portrait with the specified texture id in the unit panel, scaled to fit [Theme::portrait_size].
+	fn _draw_portrait(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, id: u8) {
+		let (tex, src) = rs.get_ui_frame(id);
+		let p = self.theme.portrait_size;
+		let k = (p / src.width).min(p / src.height);
+		let (w, h) = (src.width * k, src.height * k);
+		d.draw_texture_pro(tex, src, Rectangle::new(XOFF + 0.5*(p - w), 15.0 + 0.5*(p - h), w, h), Vector2::zero(), 0.0, Color::WHITE);
+	}
+
+	/// Area of the button ending the action of the selected unit or group, to the right of the top of the unit panel, in HUD co-ordinates; enlarged with the text scale.
+	fn wait_button(&self, rs: &ResourceSet, portrait: Option<u8>) -> Rectangle {
+		let top = self.unit_panel(rs, portrait).0;
+		let k = self.text_scale.max(1.0);
+		Rectangle::new(top.width + 8.0, 8.0, WAIT_WIDTH * k, WAIT_HEIGHT * k)
+	}
+
+	/// Area of the scrollable text of the unit information panel, beneath the top of the unit panel, in HUD co-ordinates.
+	fn info_rect(&self, rs: &ResourceSet, portrait: Option<u8>) -> Rectangle {
+		let (top, panel) = (self.unit_panel(rs, portrait).0, rs.get_ui_frame(0xf8).1);
+		Rectangle::new(XOFF, top.height + 20.0, panel.width - 2.0*XOFF, panel.height - 40.0)
+	}
+
+	/// Area of the battle log panel, in HUD co-ordinates.
+	fn log_rect(&self) -> Rectangle {
+		let (width, height) = self.hud_size();
+		Rectangle::new(width as f32 - LOG_WIDTH - 10.0, height as f32 - LOG_HEIGHT - 10.0, LOG_WIDTH, LOG_HEIGHT)
+	}
+
+	/// Area of the control hints bar showing `text`, at the bottom left of the screen, in HUD co-ordinates.
+	fn hints_rect(&self, rs: &ResourceSet, text: &str) -> Rectangle {
+		let (_, height) = self.hud_size();
+		let size = self.text_size(HINTS_FONT);
+		let s = raylib::core::text::measure_text_ex(rs.get_default_font_sized(size), text, size, 1.0);
+		Rectangle::new(0.0, height as f32 - s.y - 8.0, s.x + 16.0, s.y + 8.0)
+	}
+
+	/// Draw the control hints bar.
+	fn _draw_control_hints(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, text: &str) {
+		let rec = self.hints_rect(rs, text);
+		let size = self.text_size(HINTS_FONT);
+		d.draw_rectangle_rec(rec, self.backdrop(0.6));
+		d.draw_text_ex(rs.get_default_font_sized(size), text, Vector2::new(8.0, rec.y + 4.0), size, 1.0, Color::WHITE);
+	}
+
+	/// Draw the instruction of a step of a tutorial, the `n`th of `total`, centred near the bottom of the screen.
+	fn _draw_tutorial(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, step: &TutorialStep, n: usize, total: usize) {
+		let (width, height) = self.hud_size();
+		let tw = WINDOW_WIDTH.min(width as f32 - 2.0*XOFF);
+		let text = format!("{}\n[gray]Step {} of {}[/gray]", step.text, n, total);
+		let size = self.text_size(LOG_FONT);
+		let h = crate::text::measure_styled(rs, &text, size, tw - 12.0) + 8.0;
+		let rec = Rectangle::new(0.5*(width as f32 - tw), height as f32 - h - 40.0, tw, h);
+		d.draw_rectangle_rec(rec, self.backdrop(0.75));
+		d.draw_rectangle_lines_ex(rec, self.line_width(2), TUTORIAL_COLOR);
+		crate::text::draw_styled(d, rs, &text, Vector2::new(rec.x + 6.0, rec.y + 4.0), size, tw - 12.0, Color::WHITE, true);
+	}
+
+	/// Draw `text` clipped to `rec`, scrolled up by `scroll` pixels.
+	fn _draw_scrolled(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, text: &str, rec: Rectangle, size: f32, scroll: f32, col: Color) {
+		let k = self.ui_scale;
+		// raylib-rs' scissor mode guard does not implement RaylibDraw, so scissor mode is toggled directly.
+		unsafe { raylib::ffi::BeginScissorMode((rec.x*k) as i32, (rec.y*k) as i32, (rec.width*k) as i32, (rec.height*k) as i32) };
+		crate::text::draw_styled(d, rs, text, Vector2::new(rec.x, rec.y - scroll), size, rec.width, col, false);
+		unsafe { raylib::ffi::EndScissorMode() };
+	}
+
+	fn _draw_banner(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, b: &Banner) {
+		let (title, sub) = (self.text_size(40.0), self.text_size(24.0));
+		let (width, height) = self.hud_size();
+		let x = banner_offset(b.t / self.theme.banner_time, width as f32);
+		// 96 pixels high at the default text scale.
+		let hb = title + sub + 32.0;
+		let rec = Rectangle::new(x, 0.5*(height as f32 - hb), width as f32, hb);
+		match self.theme.banner_tex {
+			Some(id) => self._draw_panel(d, rs, id, rec),
+			None => d.draw_rectangle_rec(rec, self.theme.banner_color)
+		}
+		for (text, size, yoff) in [(&b.title, title, 12.0), (&b.sub, sub, title + 20.0)] {
+			let fnt = match self.theme.banner_font {
+				Some(id) => rs.get_font_sized(id, size),
+				None => rs.get_default_font_sized(size)
+			};
+			let s = raylib::core::text::measure_text_ex(fnt, text, size, 1.0);
+			let pos = Vector2::new(x + 0.5*(width as f32 - s.x), rec.y + yoff);
+			d.draw_text_ex(fnt, text, pos, size, 1.0, self.theme.banner_text_color);
+		}
+	}
+
+	/// Draw the texture with the specified id at (x, y), or its current frame if it is animated, see [ResourceSet::set_ui_anim].
+	fn _draw_ui(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, id: u8, x: i32, y: i32, tint: Color) {
+		let (tex, src) = rs.get_ui_frame(id);
+		d.draw_texture_rec(tex, src, Vector2::new(x as f32, y as f32), tint);
+	}
+
+	/// Draw the texture with the specified id stretched over `rec`, as a nine-patch if it has border insets, see [ResourceSet::set_nine_patch].
+	fn _draw_panel(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, id: u8, rec: Rectangle) {
+		let (tex, src) = rs.get_ui_frame(id);
+		match rs.nine_patch(id) {
+			Some((left, top, right, bottom)) => {
+				let info = NPatchInfo { source: src, left: left, top: top, right: right, bottom: bottom, layout: NPatchLayout::NPATCH_NINE_PATCH };
+				d.draw_texture_n_patch(tex, info, rec, Vector2::zero(), 0.0, Color::WHITE);
+			},
+			None => d.draw_texture_pro(tex, src, rec, Vector2::zero(), 0.0, Color::WHITE)
+		}
+	}
+
+	/// Area of a window drawn with the texture with the specified id, centred on the HUD.
+	/// Windows take the size of their texture, unless it is a nine-patch, in which case they are [WINDOW_WIDTH] wide (or as wide as the HUD allows) and as tall as their text needs.
+	fn window_rect(&self, id: u8, body: &str, rs: &ResourceSet) -> Rectangle {
+		let (width, height) = self.hud_size();
+		let (w, h) = match rs.nine_patch(id) {
+			Some((_, _, _, bottom)) => {
+				let w = WINDOW_WIDTH.min(width as f32 - 2.0*XOFF);
+				let text = crate::text::measure_styled(rs, body, self.text_size(23.0), w - 2.0*XOFF);
+				(w, (INTRO_OFF + text + (bottom as f32).max(XOFF)).min(height as f32 - 2.0*XOFF))
+			},
+			None => {
+				let src = rs.get_ui_frame(id).1;
+				(src.width, src.height)
+			}
+		};
+		Rectangle::new(0.5*(width as f32 - w), 0.5*(height as f32 - h), w, h)
+	}
+
+	/// Draw a slide of the intro slideshow over the whole screen, `t` seconds after it appeared: the image scaled to fit, above its caption.
+	fn _draw_slide(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, slide: &Slide, t: f32, scheme: InputScheme) {
+		let (width, height) = self.hud_size();
+		let (width, height) = (width as f32, height as f32);
+		d.draw_rectangle_rec(Rectangle::new(0.0, 0.0, width, height), Color::BLACK);
+		let a = slide_alpha(t, slide.duration);
+		let (tex, src) = rs.get_ui_frame(slide.tex);
+		let area = height - SLIDE_CAPTION;
+		let k = (width / src.width).min(area / src.height);
+		let (w, h) = (src.width * k, src.height * k);
+		d.draw_texture_pro(tex, src, Rectangle::new(0.5*(width - w), 0.5*(area - h), w, h), Vector2::zero(), 0.0, Color::WHITE.fade(a));
+		let tw = WINDOW_WIDTH.min(width - 2.0*XOFF);
+		crate::text::draw_styled(d, rs, &slide.caption, Vector2::new(0.5*(width - tw), area + 10.0), self.text_size(23.0), tw, Color::WHITE.fade(a), true);
+		d.draw_text(&hint(scheme, scheme.confirm_prompt(), "continue"), 10, height as i32 - self.text_px(10) - 10, self.text_px(10), self.hint_color());
+	}
+
+	/// Draw a scrolling text screen over the whole screen, the text scrolled up by `offset` pixels from the bottom.
+	fn _draw_scroll(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, text: &str, offset: f32, scheme: InputScheme) {
+		let (width, height) = self.hud_size();
+		let (width, height) = (width as f32, height as f32);
+		d.draw_rectangle_rec(Rectangle::new(0.0, 0.0, width, height), Color::BLACK);
+		let tw = WINDOW_WIDTH.min(width - 2.0*XOFF);
+		crate::text::draw_styled(d, rs, text, Vector2::new(0.5*(width - tw), height - offset), self.text_size(SCROLL_FONT), tw, Color::WHITE, true);
+		d.draw_text(&hint(scheme, scheme.hold_prompt(), "speed up"), 10, height as i32 - self.text_px(10) - 10, self.text_px(10), self.hint_color());
+	}
+
+	/// Cover the screen for a transition, `t` seconds in, see [Theme::transition].
+	fn _draw_transition(&self, d: &mut impl RaylibDraw, t: f32) {
+		let th = &self.theme;
+		let c = transition_cover(t, th.transition_time);
+		let col = Color { a: (th.transition_color.a as f32 * c) as u8, ..th.transition_color };
+		let (width, height) = self.hud_size();
+		let (width, height) = (width as f32, height as f32);
+		match th.transition {
+			Transition::Fade => d.draw_rectangle_rec(Rectangle::new(0.0, 0.0, width, height), col),
+			Transition::Iris => {
+				let r = 0.5 * width.hypot(height);
+				d.draw_ring(Vector2::new(0.5*width, 0.5*height), r * (1.0 - c), r + 1.0, 0.0, 360.0, 64, th.transition_color);
+			},
+			Transition::Slide => {
+				let x = if t < 0.5 * th.transition_time { 0.0 } else { width * (1.0 - c) };
+				d.draw_rectangle_rec(Rectangle::new(x, 0.0, width * c, height), th.transition_color);
 			}
 		}
 	}
 
-	fn _draw_window(&self, id: u8, title: &str, body: &str, rs: &ResourceSet, d: &mut RaylibDrawHandle<'_>) {
-		let tex = rs.get_texture(id);
-		let corner = Vector2::new(0.5*(self.width - tex.width()) as f32, 0.5*(self.height - tex.height()) as f32);
-		d.draw_texture_v(tex, corner, Color::WHITE);
-		let fnt = rs.get_default_font();
-		self._draw_text_centered(d, fnt, body, 23.0, 1.0, INTRO_OFF + corner.y);
-		self._draw_text_centered(d, fnt, title, 32.0, 1.0, TITLE_OFF + corner.y);
+	fn _draw_window(&self, id: u8, title: &str, body: &str, rs: &ResourceSet, d: &mut impl RaylibDraw) {
+		let rec = self.window_rect(id, body, rs);
+		self._draw_panel(d, rs, id, rec);
+		let bpos = Vector2::new(rec.x + XOFF, INTRO_OFF + rec.y);
+		crate::text::draw_styled(d, rs, body, bpos, self.text_size(23.0), rec.width - 2.0*XOFF, Color::BLACK, true);
+		let size = self.text_size(32.0);
+		self._draw_text_centered(d, rs.get_default_font_sized(size), title, size, 1.0, TITLE_OFF + rec.y);
+	}
+
+	/// Draw the victory or defeat window with the statistics of the battle beneath it.
+	fn _draw_results(&self, id: u8, title: &str, body: &str, rs: &ResourceSet, w: &World, d: &mut impl RaylibDraw) {
+		self._draw_window(id, title, body, rs, d);
+		let st = w.stats();
+		let text = format!("Turns taken: [b]{}[/b]\nDamage dealt: [b]{:.0}[/b]\nDamage received: [b]{:.0}[/b]\nUnits lost: [b]{}[/b]\nEnemies defeated: [b]{}[/b]\nXP gained: [yellow]{}[/yellow]\n[gray]{} to continue[/gray]",
+			st.turns, st.damage_dealt, st.damage_received, st.units_lost, st.enemies_defeated, st.xp_gained, w.input_scheme().confirm_prompt());
+		let (width, height) = self.hud_size();
+		let size = self.text_size(LOG_FONT);
+		let h = crate::text::measure_styled(rs, &text, size, RESULTS_WIDTH - 12.0) + 8.0;
+		let win = self.window_rect(id, body, rs);
+		let y = win.y + win.height + 10.0;
+		let rec = Rectangle::new(0.5*(width as f32 - RESULTS_WIDTH), y.min(height as f32 - h - 10.0), RESULTS_WIDTH, h);
+		d.draw_rectangle_rec(rec, self.backdrop(0.6));
+		crate::text::draw_styled(d, rs, &text, Vector2::new(rec.x + 6.0, rec.y + 4.0), size, RESULTS_WIDTH - 12.0, Color::WHITE, true);
 	}
 
 	fn _draw_tile(&self, w: &World, mut rec: Rectangle, tset: &Texture2D, tx: i32, ty: i32, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, n: i32) {
@@ -340,13 +1212,56 @@ impl Display {
 			let (rpos, pos) = crate::world::prep_tiledraw(w, tx, ty, n);
 			rec.x = rpos.x;
 			rec.y = rpos.y;
-			d.draw_texture_rec(tset, rec, pos, Color::WHITE);
+			// Raised tiles stand on a column of their own tile, drawn bottom up.
+			let step = w.elevation_rules().step as f32;
+			for k in (0..=crate::world::elevation_at(w, tx, ty)).rev() {
+				d.draw_texture_rec(tset, rec, pos + Vector2::new(0.0, k as f32 * step), self._tile_tint(w, tx, ty));
+			}
+		}
+	}
+
+	/// Draw the on-screen tiles of the tile layer with specified index, row by row.
+	fn _draw_layer(&self, w: &World, mut rec: Rectangle, tset: &Texture2D, layer: usize, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, n: i32) {
+		let (wi, hi) = w.map_size();
+		for ty in 0..hi as i32 {
+			for tx in 0..wi as i32 {
+				if let Some((rpos, pos)) = crate::world::prep_layer_tiledraw(w, layer, tx, ty, n) {
+					if self._is_rec_offscreen(w, pos, rec.width, rec.height) {
+						continue;
+					}
+					rec.x = rpos.x;
+					rec.y = rpos.y;
+					d.draw_texture_rec(tset, rec, pos, self._tile_tint(w, tx, ty));
+				}
+			}
+		}
+	}
+
+	/// Draw the tile layers of the specified kinds, kind by kind, each in order of addition.
+	fn _draw_layers(&self, w: &World, rs: &ResourceSet, kinds: &[LayerKind], d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>) {
+		let layers = w.tile_layers();
+		if !w.show_map() || layers.is_empty() {
+			return;
 		}
+		let tset = rs.get_texture(0xf0);
+		let tsize = w.get_tile_size();
+		let rec = Rectangle::new(0.0, 0.0, tsize.0 as f32, tsize.1 as f32);
+		let n = tset.width() / tsize.0;
+		for k in kinds {
+			for (i, _) in layers.iter().enumerate().filter(|(_, l)| *l == k) {
+				self._draw_layer(w, rec, tset, i, d, n);
+			}
+		}
+	}
+
+	/// Tint of the tile at the specified position: darkened if unseen under fog of war.
+	fn _tile_tint(&self, w: &World, tx: i32, ty: i32) -> Color {
+		if crate::world::is_tile_visible(w, tx, ty) { Color::WHITE } else { self.theme.fog_color }
 	}
 
-	fn _draw_text_centered(&self, d: &mut RaylibDrawHandle<'_>, fnt: &Font, text: &str, fntsize: f32, spacing: f32, yoff: f32) {
+	fn _draw_text_centered(&self, d: &mut impl RaylibDraw, fnt: &Font, text: &str, fntsize: f32, spacing: f32, yoff: f32) {
 		let s = raylib::core::text::measure_text_ex(fnt, text, fntsize, spacing);
-		let pos = Vector2::new(0.5*((self.width as f32) - s.x), yoff);
+		let pos = Vector2::new(0.5*((self.hud_size().0 as f32) - s.x), yoff);
 		d.draw_text_ex(fnt, text, pos, fntsize, spacing, Color::BLACK);
 	}
 
@@ -368,17 +1283,35 @@ impl Display {
 	}
 
 	#[inline]
-	fn _draw_world(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, w: &World, rs: &ResourceSet, is: &InputHandler, r: &Vector2, rlau: &mut RaylibAudio) {
-		if w.show_map() {
+	fn _draw_world(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, w: &World, rs: &ResourceSet, is: &InputHandler, r: &Vector2, rlau: &mut RaylibAudio, clean: bool) {
+		if w.show_map() && (w.grid() == Grid::Hex || w.has_elevation()) {
+			// Rows of hexagons overlap, as do raised tiles those behind them, so tiles are drawn row by row, back to front.
 			let tset = rs.get_texture(0xf0);
 			let tsize = w.get_tile_size();
 			let rec = Rectangle::new(0.0, 0.0, tsize.0 as f32, tsize.1 as f32);
 			let n = tset.width() / tsize.0;
-			let xl = self.width/tsize.0 + 1;
-			let yl = self.height/tsize.1 + 1;
+			let (wi, hi) = w.map_size();
+			for ty in 0..hi as i32 {
+				for tx in 0..wi as i32 {
+					let u = crate::world::wots(w, tx, ty);
+					let column = (crate::world::elevation_at(w, tx, ty) as i32 * w.elevation_rules().step) as f32;
+					if !self._is_rec_offscreen(w, Vector2::new(u.0 as f32, u.1 as f32), rec.width, rec.height + column) {
+						self._draw_tile(w, rec, tset, tx, ty, d, n);
+					}
+				}
+			}
+		} else if w.show_map() {
+			let tset = rs.get_texture(0xf0);
+			let tsize = w.get_tile_size();
+			let rec = Rectangle::new(0.0, 0.0, tsize.0 as f32, tsize.1 as f32);
+			let n = tset.width() / tsize.0;
+			// The screen is sampled a tile at a time, at the zoom of the camera.
+			let z = w.zoom();
+			let xl = (self.width as f32 / (z * tsize.0 as f32)) as i32 + 1;
+			let yl = (self.height as f32 / (z * tsize.1 as f32)) as i32 + 1;
 			for gx in -1..xl {
 				for gy in  -1..yl {
-					let wp = (((gx as f32) + 0.5) * tsize.0 as f32, ((gy as f32) + 0.5) * tsize.1 as f32);
+					let wp = (((gx as f32) + 0.5) * z * tsize.0 as f32, ((gy as f32) + 0.5) * z * tsize.1 as f32);
 					let (tx, ty) = crate::world::tile_at(w, wp.0, wp.1);
 					if gx == 0 {
 						self._draw_tile(w, rec, tset, tx-1, ty, d, n);
@@ -391,8 +1324,16 @@ impl Display {
 				}
 			}
 		}
+		self._draw_layers(w, rs, &[LayerKind::Ground, LayerKind::Decoration], d);
 		let mut renderables = vec![];
+		let ht = crate::world::tile_at(w, r.x, r.y);
+		let hover = crate::world::unit_at(w, ht.0, ht.1);
+		let marked = w.tutorial().map_or(&[][..], |t| t.highlights());
 		for (_id, sp) in &w.units {
+			// Enemy units out of sight are hidden under fog of war.
+			if !crate::world::is_unit_visible(w, *_id) {
+				continue;
+			}
 			let (tid, rec, pos, sif) = sp.prep_draw(w);
 			if self._is_rec_offscreen(w, pos, rec.width, rec.height) {
 				continue
@@ -403,13 +1344,27 @@ impl Display {
 					if !rlau.is_sound_playing(s) {rlau.play_sound(s)};
 				}
 			}
-			let rcol = if is.show && *_id == is.cur_id {Color::YELLOW} 
-						else if is.get_state() == 0 && is.is_frozen(&*_id) {Color::GRAY}
+			// Units that have acted are grayed out for the rest of the player turn.
+			let rcol = if !clean && matches!(is.get_state(), 0 | 1 | 4) && is.is_frozen(&*_id) {Color::GRAY}
 						else {sp.get_tint()};
-			renderables.push(Renderable::new_unit(rs.get_texture(tid), sp.wpos, pos, rec, rcol));
+			// Outline the selected unit, and the unit under the cursor if it can be acted upon.
+			let outline = if clean {None}
+						else if (is.show && *_id == is.cur_id) || is.group().contains(&*_id) {Some(Color::YELLOW)}
+						else if marked.contains(&Highlight::Unit(*_id)) {Some(TUTORIAL_COLOR)}
+						else if hover != Some(*_id) {None}
+						else if is.get_state() == 4 && !sp.player {Some(Color::RED)}
+						else if is.get_state() == 0 && sp.player && !is.is_frozen(&*_id) {Some(Color::WHITE)}
+						else {None};
+			let sh = sp.palette.and_then(|p| rs.get_palette_shader(p));
+			renderables.push(Renderable::new_unit(rs.get_texture(tid), sp.wpos, pos, rec, rcol, sh, outline));
 		}
-		for st in &w.statics {
-			let (tid, x, y) = st.prep_draw(w);
+		// Pickups are drawn like statics standing on their tile.
+		let statics = w.statics.iter().map(|st| (st.prep_draw(w), st.wx, st.wy));
+		let pickups = w.pickups().iter().map(|p| {
+			let (x, y) = crate::world::wots(w, p.tile.0, p.tile.1);
+			((p.tex_id, x, y), p.tile.0, p.tile.1)
+		});
+		for ((tid, x, y), wx, wy) in statics.chain(pickups) {
 			// if self.is_ipos_offscreen(x, y, w, RENDER_FILTER_GAP) {
 			// 	continue;
 			// }
@@ -421,7 +1376,7 @@ impl Display {
 				if self._is_rec_offscreen(w, pos, rec.width, rec.height) {
 					continue;
 				}
-				renderables.push(Renderable::new_static_texreg(tex, pos, st.wx, st.wy, *rec));
+				renderables.push(Renderable::new_static_texreg(tex, pos, wx, wy, *rec));
 			} else {
 				let tex = rs.get_texture(tid);
 				let (x,y) = (x, y -tex.height() + w.get_tile_size().1);
@@ -429,39 +1384,56 @@ impl Display {
 				if self._is_rec_offscreen(w, Vector2::new(x as f32,y as f32), tex.width() as f32, tex.height() as f32) {
 					continue;
 				}
-				renderables.push(Renderable::new_static(tex, x, y, st.wx, st.wy));
+				renderables.push(Renderable::new_static(tex, x, y, wx, wy));
 			}
 			//if BOX_STATICS {
 			//	d.draw_rectangle_lines(x, y, bw, bh, Color::WHITE);
 			//}
 		}
-		renderables.sort_by(Renderable::cmp);	// Sort draw by world position, render farthest first.
+		if w.grid() == Grid::Hex {
+			renderables.sort_by(Renderable::cmp_rows);
+		} else {
+			renderables.sort_by(Renderable::cmp);	// Sort draw by world position, render farthest first.
+		}
 		//println!("Number of renderables is {}", renderables.len());
-		let (mut bw, mut bh) = (0, 0);
 		for rd in renderables {
-			if let Some(rec) = rd.reg {
-				bw = rec.width as i32;
-				bh = rec.height as i32;
-				d.draw_texture_rec(rd.tex, rec, rd.spos, rd.tint)
-			} else {
-				bw = rd.tex.width();
-				bh = rd. tex.height();
-				d.draw_texture_v(rd.tex, rd.spos, rd.tint)
+			if let (Some(_), Some(sh)) = (rd.outline, rs.get_outline_shader()) {
+				rd.draw_outline(&mut d.begin_shader_mode(sh), OUTLINE_WIDTH * self.line_scale());
 			}
+			let (bw, bh) = match rd.shader {
+				Some(sh) => rd.draw(&mut d.begin_shader_mode(sh)),
+				None => rd.draw(d)
+			};
 			if BOX_STATICS && rd.is_static {
 				d.draw_rectangle_lines(rd.spos.x as i32, rd.spos.y as i32, bw, bh, Color::WHITE);
 			}
 		}
+		self._draw_layers(w, rs, &[LayerKind::Overlay], d);
 		for proj in &w.projectiles {
 			let (st, en) = proj._prep_draw(w);
 			//println!("Drawing line from: {:?}, to: {:?}", st, en);
 			d.draw_line_ex(st, en, 1.5, Color::BLACK);
 		}
+		if !clean {
+			let tw = w.get_tile_size().0;
+			let size = self.text_size(POPUP_FONT);
+			for p in &is.popups {
+				let k = p.kind as usize;
+				let fnt = match self.theme.popup_fonts[k] {
+					Some(id) => rs.get_font_sized(id, size),
+					None => rs.get_default_font_sized(size)
+				};
+				let u = crate::world::wots(w, p.tile.0, p.tile.1);
+				let s = raylib::core::text::measure_text_ex(fnt, &p.text, size, 1.0);
+				let pos = Vector2::new(u.0 as f32 + 0.5*(tw as f32 - s.x), u.1 as f32 - POPUP_RISE * p.t.value());
+				d.draw_text_ex(fnt, &p.text, pos, size, 1.0, self.theme.popup_colors[k].fade(1.0 - p.t.progress()));
+			}
+		}
 		// Select Tile.
-		if is.show {
+		if is.show && !clean {
 			let t = crate::world::tile_at(w,r.x, r.y);
 			let u = crate::world::wots(w,t.0, t.1);
-			d.draw_texture(rs.get_texture(0xf1), u.0, u.1, Color::WHITE);
+			self._draw_ui(d, rs, 0xf1, u.0, u.1, Color::WHITE);
 			if is.get_state() == 1 {
 				let (sx, ex, sy, ey) = is._boxrange();
 				for y in sy..=ey {
@@ -472,18 +1444,18 @@ impl Display {
 						}
 						if t != 0 {
 							let u = crate::world::wots(&w, x, y);
-							d.draw_texture(rs.get_texture(0xf2 + t), u.0, u.1, Color::WHITE);
+							self._draw_overlay(d, rs, 0xf2 + t, u.0, u.1, Color::WHITE);
 						}
 					}	
 				}
 			} else if is.get_state() == 4{
-				let (sx, ex, sy, ey) = is._atkrange();
+				let (sx, ex, sy, ey) = is._atkrange(w);
 				for y in sy..=ey {
 					for x in sx..=ex {
-						let v = is._inrange(x, y);
+						let v = is._inrange(w, x, y);
 						if v == 1 {
 							let u = crate::world::wots(w, x, y);
-							d.draw_texture(rs.get_texture(0xf4), u.0, u.1, Color::WHITE);
+							self._draw_overlay(d, rs, 0xf4, u.0, u.1, Color::WHITE);
 						} else if v == -1 {
 							let (tid, rec, pos) = w.units.get(&is.cur_id).unwrap()._stand_frame(w, x, y);
 							d.draw_texture_rec(rs.get_texture(tid), rec, pos, GRAYCOL);
@@ -492,30 +1464,1080 @@ impl Display {
 				} 
 			}
 		}
+		#[cfg(feature = "debug_tools")]
+		let revealed = crate::debug_tools::cheat(w, crate::debug_tools::Cheat::Reveal);
+		#[cfg(not(feature = "debug_tools"))]
+		let revealed = false;
+		if (is.show_danger || revealed) && !clean {
+			// Shade threatened tiles, more opaque where the threat is greater.
+			let m = crate::world::influence::compute(w, true);
+			let max = m.max_threat();
+			for (t, v) in m.threatened() {
+				let u = crate::world::wots(w, t.0, t.1);
+				self._draw_overlay(d, rs, 0xf4, u.0, u.1, Color::new(255, 255, 255, (60.0 + 160.0 * v / max) as u8));
+			}
+		}
+		if !clean {
+			// Tiles highlighted by a tutorial, and those of the units it highlights.
+			for h in marked {
+				let tile = match *h {
+					Highlight::Tile(t) => Some(t),
+					Highlight::Unit(u) => crate::world::unit_tile(w, u),
+					Highlight::Ui(_) => None
+				};
+				if let Some((x, y)) = tile {
+					let u = crate::world::wots(w, x, y);
+					self._draw_overlay(d, rs, 0xf4, u.0, u.1, TUTORIAL_COLOR);
+				}
+			}
+			// The tile under the gamepad cursor, while the player gives orders.
+			if w.input_scheme() == InputScheme::Gamepad && matches!(is.get_state(), 0 | 1 | 4) {
+				let u = crate::world::wots(w, is.cursor.0, is.cursor.1);
+				self._draw_overlay(d, rs, 0xf4, u.0, u.1, CURSOR_COLOR);
+			}
+		}
+		if (is.show_ai_debug || revealed) && !clean {
+			self._draw_ai_debug(d, w);
+		}
 	}
-}
 
-#[inline]
-fn _man_cam(cam: &mut Camera2D, w: &World) {
-	let (cx, cy) = w.get_cpos();
+	/// Draw the AI debug overlay: the score of every candidate tile (the chosen one in green), the path walked, and a line to the target.
+	/// Draw the debug console across the top of the screen: the latest lines of output, and the line being typed.
+	fn _draw_console(&self, d: &mut impl RaylibDraw, rs: &ResourceSet, c: &Console) {
+		let (width, _) = self.hud_size();
+		let size = self.text_size(CONSOLE_FONT);
+		let line = size + 2.0;
+		d.draw_rectangle_rec(Rectangle::new(0.0, 0.0, width as f32, line * (CONSOLE_LINES + 1) as f32 + 8.0), self.backdrop(0.8));
+		let fnt = rs.get_default_font_sized(size);
+		let skip = c.output.len().saturating_sub(CONSOLE_LINES);
+		for (i, l) in c.output.iter().skip(skip).enumerate() {
+			d.draw_text_ex(fnt, l, Vector2::new(6.0, 4.0 + i as f32 * line), size, 1.0, Color::LIGHTGRAY);
+		}
+		d.draw_text_ex(fnt, &format!("> {}_", c.input), Vector2::new(6.0, 4.0 + CONSOLE_LINES as f32 * line), size, 1.0, Color::WHITE);
+	}
+
+	fn _draw_ai_debug(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, w: &World) {
+		let (tw, th) = w.get_tile_size();
+		let centre = |(x, y): (i32, i32)| {
+			let u = crate::world::wots(w, x, y);
+			Vector2::new((u.0 + tw/2) as f32, (u.1 + th/2) as f32)
+		};
+		for plan in w.ai_plans() {
+			let start = match w.units.get(&plan.uid) {
+				Some(u) => (u.wpos.x.round() as i32, u.wpos.y.round() as i32),
+				None => continue
+			};
+			let end = plan.path.last().cloned();
+			for &(t, score) in &plan.scores {
+				let c = centre(t);
+				let col = if Some(t) == end {Color::GREEN} else {Color::LIGHTGRAY};
+				d.draw_text(&score.to_string(), c.x as i32 - 4, c.y as i32 - 5, self.text_px(10), col);
+			}
+			// Once the unit has set off, draw only the rest of the path.
+			let walked = plan.path.iter().position(|&t| t == start).map_or(0, |i| i + 1);
+			let mut prev = centre(start);
+			for &t in &plan.path[walked..] {
+				let c = centre(t);
+				d.draw_line_ex(prev, c, 2.0, Color::SKYBLUE);
+				prev = c;
+			}
+			if let Some(t) = plan.target.and_then(|id| w.units.get(&id)) {
+				let c = centre((t.wpos.x.round() as i32, t.wpos.y.round() as i32));
+				d.draw_line_ex(prev, c, 1.5, Color::RED);
+				d.draw_circle_lines(c.x as i32, c.y as i32, 6.0, Color::RED);
+			}
+		}
+	}
+
+	/// Draw the time left in the player turn, and in the bank, at the top centre of the screen; red in the last ten seconds.
+	fn _draw_countdown(&self, d: &mut RaylibMode2D<'_, RaylibDrawHandle<'_>>, t: &TurnTimer) {
+		let clock = |s: f32| { let s = s.ceil() as u32; format!("{}:{:02}", s / 60, s % 60) };
+		let text = match (t.turn_left(), t.bank_left()) {
+			(Some(a), Some(b)) => format!("{}  ({})", clock(a), clock(b)),
+			(Some(a), None) | (None, Some(a)) => clock(a),
+			(None, None) => return
+		};
+		let left = t.turn_left().unwrap_or(f32::INFINITY).min(t.bank_left().unwrap_or(f32::INFINITY));
+		let size = self.text_px(20);
+		let w = raylib::core::text::measure_text(&text, size);
+		let x = (self.width as f32 / self.ui_scale) as i32 / 2 - w / 2;
+		d.draw_rectangle(x - 6, 4, w + 12, size + 6, self.backdrop(0.5));
+		d.draw_text(&text, x, 7, size, if left <= 10.0 { Color::RED } else { Color::WHITE });
+	}
+}
+
+/// State of the animation viewer, see [Display::view_anims].
+#[derive(Default)]
+struct AnimViewer {
+	/// Index of the selected unit type, in order of ids.
+	ti: usize,
+	/// Index of the selected unit state.
+	st: usize,
+	frame: u8,
+	ftime: f32,
+	paused: bool,
+	onion: bool
+}
+
+/// A running game, returned by [Display::init].
+/// Lets the host application own the draw-update loop, e.g, to embed the game in an editor or launcher.
+pub struct Runtime {
+	// Field order matters: resources must be released before the audio device and window are closed.
+	rs: ResourceSet,
+	w: World,
+	sl: StateListener,
+	is: InputHandler,
+	phase: u8,
+	cam: Camera2D,
+	last_mouse: Vector2,
+	banner: Option<Banner>,
+	transition: Option<ScreenTransition>,
+	slides: Option<SlidePlayer>,
+	scroll: Option<ScrollPlayer>,
+	/// Autosave the player may resume from, while the prompt is shown.
+	recovery: Option<SnapshotInfo>,
+	/// Flag to hide all HUD elements and overlays.
+	hud_hidden: bool,
+	/// Flag set while a clean screenshot waits for a frame drawn without the HUD.
+	shot_pending: bool,
+	/// Time not yet simulated, with a fixed simulation rate.
+	sim_time: f32,
+	/// Flag set while the frame rate is lowered for an unfocused window.
+	throttled: bool,
+	/// Flag set while the game is paused for an unfocused window.
+	paused: bool,
+	/// Flag set while the exit confirmation is shown.
+	confirming_exit: bool,
+	/// Flag set once listeners have been notified of the exit.
+	exited: bool,
+	/// Step of the tutorial last published, if any.
+	tutorial_step: Option<usize>,
+	/// Unit selected (and the number of units selected with it) when last narrated, if any.
+	narrated_sel: Option<(u8, usize)>,
+	finished: bool,
+	console: Console,
+	rlau: RaylibAudio,
+	rl: RaylibHandle,
+	thread: RaylibThread,
+	disp: Display
+}
+
+impl Runtime {
+	/// Poll input, update and draw a single frame.
+	/// Returns false (without doing anything) once the window has been asked to close (and closing confirmed, if asked), or the results screen has been dismissed.
+	pub fn step(&mut self) -> bool {
+		if self.finished {
+			return false;
+		}
+		// A map loaded by a callback since the last frame.
+		if let Some(m) = self.w.take_loaded_map() {
+			self.sl.notify_map_loaded(&mut self.w, &m);
+			if self.disp.theme.transition_time > 0.0 {
+				self.transition = Some(ScreenTransition { from: None, t: 0.5 * self.disp.theme.transition_time });
+			}
+			if self.is.get_state() == 7 && !self.w.intro_slides().is_empty() {
+				self.slides = Some(SlidePlayer { index: 0, t: 0.0 });
+			}
+		}
+		if let Some(text) = self.w.take_scrolling_text() {
+			self.scroll = Some(ScrollPlayer { text: text, offset: 0.0, ending: false });
+		}
+		if self.rl.window_should_close() {
+			if self.disp.confirm_exit && !self.confirming_exit {
+				self.confirming_exit = true;
+			} else {
+				self.exit();
+				return false;
+			}
+		}
+		self.throttle();
+		if let Some(s) = self.is.detect_scheme(&mut self.w, &self.rl) {
+			self.sl.publish(&mut self.w, crate::event::EngineEvent::InputSchemeChanged(s));
+		}
+		self.auto_mute();
+		let paused = self.auto_pause();
+		let r = self.rl.get_mouse_position();
+		let clean = self.hud_hidden || self.shot_pending;
+		// The screen left is shown until a transition has covered it.
+		let shown = match &self.transition {
+			Some(ScreenTransition { from: Some(s), t }) if *t < 0.5 * self.disp.theme.transition_time => *s,
+			_ => self.is.get_state()
+		};
+		// Draw scope. All rendering occurs here.
+		{	
+			let mut d = self.rl.begin_drawing(&self.thread);
+			d.clear_background(self.disp.col);
+			// Camera scope.
+			{
+				_man_cam(&mut self.cam, &self.w);
+				let mut d = d.begin_mode2D(self.cam);
+				self.disp._draw_world(&mut d, &self.w, &self.rs, &self.is, &r, &mut self.rlau, clean);
+			}
+			// HUD Goes here.
+			{
+				let hud_cam = Camera2D {
+					target: Vector2::new(0.0,0.0),
+					offset: Vector2::new(0.0,0.0),
+					rotation: 0.0,
+					zoom: self.disp.ui_scale
+				};
+				let mut d = d.begin_mode2D(hud_cam);
+				if !clean {
+					d.draw_fps(0,0);
+				}
+				if self.is.is_thinking() && !clean {
+					let dots = 1 + (d.get_time() * 3.0) as usize % 3;
+					d.draw_text(&format!("Thinking{}", ".".repeat(dots)), 2, 22, self.disp.text_px(10), Color::LIGHTGRAY);
+				}
+				if let (Some(p), false) = (self.w.puzzle(), clean) {
+					let mut text = format!("Puzzle: step {}/{}", p.steps_done(), p.steps().len());
+					if p.steps().is_empty() {
+						text = format!("Puzzle: {} moves taken", p.moves_used());
+					}
+					if let Some(m) = p.moves_left() {
+						text.push_str(&format!(", {} left", m));
+					}
+					d.draw_text(&text, 2, 24 + self.disp.text_px(10), self.disp.text_px(10), Color::LIGHTGRAY);
+				}
+				if self.is.timer.is_enabled() && matches!(self.is.get_state(), 0 | 1 | 4) && !clean {
+					self.disp._draw_countdown(&mut d, &self.is.timer);
+				}
+
+				let panel = if !self.is.group().is_empty() {
+					Some(crate::world::_group_info(&self.w, self.is.group()))
+				} else if self.is.show {
+					let (h, mh) = crate::world::_unit_health(&self.w, self.is.cur_id);
+					Some((h, mh, format!("{}", self.is)))
+				} else {
+					None
+				};
+				if let (Some((h, mh, text)), false) = (panel, clean) {
+					let portrait = if self.is.group().is_empty() { crate::world::unit_portrait(&self.w, self.is.cur_id) } else { None };
+					let (top, x) = self.disp.unit_panel(&self.rs, portrait);
+					self.disp._draw_panel(&mut d, &self.rs, 0xf2, top);
+					if let (Some(id), true) = (portrait, self.disp.theme.portrait_size > 0.0) {
+						self.disp._draw_portrait(&mut d, &self.rs, id);
+					}
+					d.draw_rectangle_lines_ex(Rectangle { x: x, ..HPREC }, self.disp.line_width(3), Color::BLACK);
+					let hfrac = (h as f32) / (mh as f32);
+					let width = (116.0*(hfrac)) as i32;
+					let col = Color {
+						r: (255.0*(1.0-hfrac)) as u8,
+						g: (255.0*hfrac) as u8,
+						b: 0,
+						a: 255
+					};
+					d.draw_rectangle((x as i32)+2, 42, width, 15, col);
+					let size = self.disp.text_size(22.0);
+					d.draw_text_ex(self.rs.get_default_font_sized(size), &format!("HP: {} / {}", self.w.hp_rounding().apply(h), self.w.hp_rounding().apply(mh)), Vector2::new(x,64.0), size, 1.0, Color::BLACK);
+					d.draw_text_ex(self.rs.get_default_font_sized(size), &text, Vector2::new(x,20.0), size, 1.2, Color::BLACK);
+					if self.is.can_wait() {
+						let b = self.disp.wait_button(&self.rs, portrait);
+						let hover = b.check_collision_point_rec(r / self.disp.ui_scale);
+						d.draw_rectangle_rec(b, if hover { Color::RAYWHITE } else { Color::LIGHTGRAY });
+						d.draw_rectangle_lines_ex(b, self.disp.line_width(2), Color::BLACK);
+						let label = match crate::input::key_name(self.is.keys.wait) {
+							Some(k) => format!("Wait [{}]", k),
+							None => String::from("Wait")
+						};
+						let size = self.disp.text_size(20.0);
+						let fnt = self.rs.get_default_font_sized(size);
+						let s = raylib::core::text::measure_text_ex(fnt, &label, size, 1.0);
+						d.draw_text_ex(fnt, &label, Vector2::new(b.x + 0.5*(b.width - s.x), b.y + 0.5*(b.height - s.y)), size, 1.0, Color::BLACK);
+					}
+					if self.is.show && self.is.show_info {
+						self.disp._draw_ui(&mut d, &self.rs, 0xf8, 0, top.height as i32, Color::WHITE);
+						if let Some(text) = info_text(&self.w, &self.is, &self.disp.theme) {
+							self.disp._draw_scrolled(&mut d, &self.rs, &text, self.disp.info_rect(&self.rs, portrait), self.disp.text_size(22.0), self.is.info_scroll, Color::BLACK);
+						}
+						self.disp._draw_minimap(&mut d, &self.w);
+					}
+				}
+				if self.is.show_objectives && !clean {
+					let below = if self.is.show && self.is.show_info { self.disp.minimap_height(&self.w) } else { 0.0 };
+					self.disp._draw_objectives(&mut d, &self.rs, &self.w, below);
+				}
+				if self.is.show_log && !clean {
+					let rec = self.disp.log_rect();
+					d.draw_rectangle_rec(rec, self.disp.backdrop(0.6));
+					let text = self.is.log.iter().cloned().collect::<Vec<_>>().join("\n");
+					let inner = Rectangle::new(rec.x + 6.0, rec.y + 4.0, rec.width - 12.0, rec.height - 8.0);
+					self.disp._draw_scrolled(&mut d, &self.rs, &text, inner, self.disp.text_size(LOG_FONT), self.is.log_scroll, Color::WHITE);
+				}
+				let hints = if self.disp.control_hints && !clean && self.slides.is_none() && self.scroll.is_none() {
+					Some(crate::input::control_hints(TurnPhase::from_state(shown), &self.is.keys, self.w.input_scheme())).filter(|t| !t.is_empty())
+				} else {
+					None
+				};
+				if let Some(text) = &hints {
+					self.disp._draw_control_hints(&mut d, &self.rs, text);
+				}
+				if let (Some(t), true, false) = (self.w.tutorial(), matches!(shown, 0 | 1 | 4), clean) {
+					if let Some(s) = t.current() {
+						self.disp._draw_tutorial(&mut d, &self.rs, s, t.steps_done() + 1, t.steps().len());
+					}
+					for h in t.highlights() {
+						if let Some(rec) = ui_rect(&self.disp, &self.rs, &self.w, &self.is, h, hints.as_deref()) {
+							d.draw_rectangle_lines_ex(rec, self.disp.line_width(3), TUTORIAL_COLOR);
+						}
+					}
+				}
+				if let (Some(b), false) = (&self.banner, clean) {
+					self.disp._draw_banner(&mut d, &self.rs, b);
+				}
+				if self.console.open && !self.shot_pending {
+					self.disp._draw_console(&mut d, &self.rs, &self.console);
+				}
+				if self.confirming_exit {
+					self.disp._draw_window(0xf5, "Quit", "Are you sure? Progress since the last save will be lost.\n\n[b][Y][/b] Quit    [b][N][/b] Keep playing", &self.rs, &mut d);
+				} else if let Some(r) = &self.recovery {
+					let body = format!("The last session ended unexpectedly.\nResume from the autosave of turn {}?\n\n[b][Y][/b] Resume    [b][N][/b] Start over", r.turn);
+					self.disp._draw_window(0xf5, "Recover autosave", &body, &self.rs, &mut d);
+				} else if shown == 7 {
+					self.disp._draw_window(0xf5, self.w.map_title(), self.w.intro_text(), &self.rs, &mut d);
+				} else if shown == 5 {
+					self.disp._draw_results(0xf6, "Victory is thine", self.w.victory_text(), &self.rs, &self.w, &mut d);
+				} else if shown == 6 {
+					self.disp._draw_results(0xf6, "'Tis defeat", self.w.defeat_text(), &self.rs, &self.w, &mut d);
+				}
+				if let Some(sp) = &self.slides {
+					if let Some(slide) = self.w.intro_slides().get(sp.index) {
+						self.disp._draw_slide(&mut d, &self.rs, slide, sp.t, self.w.input_scheme());
+					}
+				}
+				if let Some(sc) = &self.scroll {
+					self.disp._draw_scroll(&mut d, &self.rs, &sc.text.text, sc.offset, self.w.input_scheme());
+				}
+				if let Some(tr) = &self.transition {
+					self.disp._draw_transition(&mut d, tr.t);
+				}
+			}
+			if let (Some(rec), false) = (self.is.drag_box(r), clean) {
+				d.draw_rectangle_rec(rec, Color::YELLOW.fade(0.15));
+				d.draw_rectangle_lines_ex(rec, self.disp.line_width(1), Color::YELLOW);
+			}
+			self.sl.notify_draw(&mut d, &self.w);
+		}
+		// Save screenshot. A clean screenshot of a frame with the HUD shown is taken after drawing the next frame without it.
+		if self.shot_pending || (self.rl.is_key_pressed(self.is.keys.screenshot) && self.rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)) {
+			if clean || !self.disp.clean_screenshots {
+				let p = self.disp.screenshot_path();
+				self.rl.take_screenshot(&self.thread, &p);
+				self.shot_pending = false;
+			} else {
+				self.shot_pending = true;
+			}
+		}
+		if self.rl.is_key_pressed(self.is.keys.toggle_hud) && !self.console.open {
+			self.hud_hidden = !self.hud_hidden;
+		}
+		if paused {
+			return true;
+		}
+		if self.rl.is_key_pressed(self.is.keys.toggle_console) {
+			self.console.toggle();
+			// Discard the character typed with the key.
+			while unsafe { raylib::ffi::GetCharPressed() } != 0 {}
+		} else if self.console.open {
+			self.console_input();
+		}
+		self.scroll_panels();
+		// Camera controls are always active, but for the keys typed into the console.
+		if !self.console.open {
+			_cam_control(&mut self.w, &self.rl, &self.is.keys);
+		}
+		self.w.update_tweens(self.rl.get_frame_time());
+		self.is.update_popups(&mut self.w, self.rl.get_frame_time());
+		self.is.tick_guard(self.rl.get_frame_time());
+		self.rs.advance_ui(self.rl.get_frame_time());
+		crate::schedule::run(&mut self.w, self.rl.get_frame_time());
+		// Dismissing the results screen ends the game, and hands control back to the host; after the scrolling text screen requested by the callback, if any.
+		if matches!(self.phase, 5 | 6) && !self.confirming_exit && self.scroll.is_none() && self.is.can_dismiss() && (self.rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) || gamepad_pressed(&self.rl, CONFIRM_BUTTON)) {
+			self.save_replay();
+			let o = if self.phase == 5 { Outcome::Victory } else { Outcome::Defeat };
+			self.sl.notify_results(&mut self.w, o);
+			self.w.save_profile();
+			match self.w.take_scrolling_text() {
+				Some(text) => self.scroll = Some(ScrollPlayer { text: text, offset: 0.0, ending: true }),
+				None => self.finished = true
+			}
+			return true;
+		}
+		// Input is not accepted, and orders are not carried out, while the exit confirmation, recovery prompt or phase banner is shown.
+		if self.confirming_exit {
+			if self.rl.is_key_pressed(KeyboardKey::KEY_Y) {
+				self.exit();
+				return false;
+			} else if self.rl.is_key_pressed(KeyboardKey::KEY_N) {
+				self.confirming_exit = false;
+			}
+		} else if self.recovery.is_some() {
+			if self.rl.is_key_pressed(KeyboardKey::KEY_Y) {
+				self.recovery = None;
+				self.recover();
+			} else if self.rl.is_key_pressed(KeyboardKey::KEY_N) {
+				self.recovery = None;
+			}
+		} else if self.scroll.is_some() {
+			self.advance_scroll();
+		} else if self.slides.is_some() {
+			self.advance_slides();
+		} else if let Some(tr) = &mut self.transition {
+			tr.t += self.rl.get_frame_time();
+			if tr.t >= self.disp.theme.transition_time {
+				self.transition = None;
+				self.sl.notify_transition_done(&mut self.w, TurnPhase::from_state(self.phase));
+			}
+		} else if let Some(b) = &mut self.banner {
+			b.t += self.rl.get_frame_time();
+			if b.t >= self.disp.theme.banner_time {
+				let p = b.phase;
+				self.banner = None;
+				self.sl.notify_banner_done(&mut self.w, p);
+			}
+		} else if self.disp.sim_fps > 0 && self.is.is_simulating() {
+			self.fixed_steps();
+		} else if !self.console.open || self.is.is_simulating() {
+			self.sim_time = 0.0;
+			if !self.click_wait() {
+				self.is.handle(&mut self.w, &self.rl, &mut self.sl, &mut self.rlau, &mut self.rs);
+			}
+		}
+		self.sl.notify_update(&mut self.w, self.rl.get_frame_time());
+		let step = self.w.tutorial().map(|t| t.steps_done());
+		if step != self.tutorial_step {
+			self.tutorial_step = step;
+			if let Some(i) = step {
+				self.sl.publish(&mut self.w, crate::event::EngineEvent::TutorialStep(i));
+			}
+		}
+		self.narrate_selection();
+		if self.is.get_state() != self.phase {
+			let prev = self.phase;
+			self.phase = self.is.get_state();
+			self.sl.notify_phase_change(&mut self.w, TurnPhase::from_state(self.phase));
+			if let Some(text) = crate::narration::describe_phase(&self.w, TurnPhase::from_state(prev), TurnPhase::from_state(self.phase)) {
+				self.sl.narrate(&mut self.w, &text);
+			}
+			if self.disp.theme.transition_time > 0.0 && (prev == 7 || matches!(self.phase, 5 | 6)) {
+				self.transition = Some(ScreenTransition { from: Some(prev), t: 0.0 });
+			}
+			self.show_banner(prev);
+			if let Some(a) = &self.disp.autosave {
+				if a.due(prev, self.phase, self.w.turn()) {
+					a.save(&self.w, TurnPhase::from_state(self.phase));
+				}
+			}
+		}
+		crate::world::mixer::tick(&mut self.w, self.rl.get_frame_time());
+		let centre = crate::world::tile_at(&self.w, 0.5 * self.rl.get_screen_width() as f32, 0.5 * self.rl.get_screen_height() as f32);
+		crate::world::ambient::update(&mut self.w, centre);
+		crate::utils::play_queued(&mut self.rs, &mut self.rlau, &mut self.w);
+		if let Some(a) = self.rs.get_music(self.w.bgm_id) {
+			self.rlau.update_music_stream(a);
+		}
+		let bgm = self.w.bgm_id;
+		for id in self.w.mixer.ambient.keys().filter(|id| **id != bgm) {
+			if let Some(a) = self.rs.get_music(*id) {
+				self.rlau.update_music_stream(a);
+			}
+		}
+		true
+	}
+
+	/// Pass the keys typed this frame to the open debug console.
+	fn console_input(&mut self) {
+		loop {
+			let c = unsafe { raylib::ffi::GetCharPressed() };
+			if c == 0 {
+				break;
+			}
+			match std::char::from_u32(c as u32) {
+				Some(ch) if !ch.is_control() => self.console.input.push(ch),
+				_ => {}
+			}
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+			self.console.input.pop();
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+			self.console.complete();
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_UP) {
+			self.console.recall(true);
+		} else if self.rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+			self.console.recall(false);
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+			self.console.submit(&mut self.w, &mut self.is);
+		} else if self.rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+			self.console.toggle();
+		}
+	}
+
+	/// Carry out orders in fixed steps of the simulation rate, for the time elapsed since the last frame.
+	fn fixed_steps(&mut self) {
+		let dt = 1.0 / self.disp.sim_fps as f32;
+		self.is.think(&mut self.w);
+		if self.is.is_thinking() {
+			self.sim_time = 0.0;
+			return;
+		}
+		self.sim_time += self.rl.get_frame_time();
+		let mut n = 0;
+		while self.sim_time >= dt && self.is.is_simulating() {
+			if n == MAX_SIM_STEPS {
+				self.sim_time = 0.0;
+				break;
+			}
+			self.is.simulate(&mut self.w, &mut self.sl, dt);
+			self.sim_time -= dt;
+			n += 1;
+		}
+		crate::world::update_visibility(&mut self.w);
+	}
+
+	/// Lower the target fps while the window is not focused, and restore it on focus.
+	fn throttle(&mut self) {
+		let bg = self.disp.background_fps;
+		let unfocused = bg > 0 && !self.rl.is_window_focused();
+		if unfocused != self.throttled {
+			self.throttled = unfocused;
+			self.rl.set_target_fps(if unfocused { bg } else { self.disp.fps });
+		}
+	}
+
+	/// Mute or unmute audio as the window loses or regains focus, if the game pauses or [mutes](World::set_mute_unfocused) while unfocused.
+	fn auto_mute(&mut self) {
+		let m = self.w.mixer();
+		let mute = (self.disp.pause_unfocused || m.mute_unfocused()) && (!self.rl.is_window_focused() || self.rl.is_window_minimized());
+		if mute != m.is_muted() {
+			self.w.set_muted(mute);
+			// Queued audio is not played while paused, so the volume is set at once.
+			self.rlau.set_master_volume(self.w.mixer().effective_master_volume());
+		}
+	}
+
+	/// Pause or resume the game as the window loses or regains focus, see [Display::set_pause_unfocused].
+	/// Returns true while paused, and on the frame the game resumes, so that the time spent away is not simulated.
+	fn auto_pause(&mut self) -> bool {
+		let away = self.disp.pause_unfocused && (!self.rl.is_window_focused() || self.rl.is_window_minimized());
+		if away == self.paused {
+			return away;
+		}
+		self.paused = away;
+		let music = self.rs.get_music(self.w.bgm_id);
+		if away {
+			if let Some(a) = music {
+				self.rlau.pause_music_stream(a);
+			}
+		} else {
+			if let Some(a) = music {
+				self.rlau.resume_music_stream(a);
+			}
+			self.sim_time = 0.0;
+		}
+		true
+	}
+
+	/// Returns true while the game is paused for an unfocused or minimized window.
+	pub fn is_paused(&self) -> bool {
+		self.paused
+	}
+
+	/// End the game, notifying listeners of the exit.
+	fn exit(&mut self) {
+		self.finished = true;
+		if !self.exited {
+			self.exited = true;
+			self.save_replay();
+			self.sl.notify_exit(&mut self.w);
+		}
+	}
+
+	/// Write the replay being recorded, if any, see [Display::record_replay].
+	fn save_replay(&self) {
+		if let (Some(r), Some(path)) = (&self.is.replay, &self.disp.replay_path) {
+			if let Err(e) = r.save(path) {
+				eprintln!("warning [display]: failed to save replay to {}: {}", path, e);
+			}
+		}
+	}
+
+	/// Returns the replay being recorded, if any, see [Display::record_replay].
+	pub fn replay(&self) -> Option<&Replay> {
+		self.is.replay.as_ref()
+	}
+
+	/// Restore the autosave.
+	fn recover(&mut self) {
+		if let Some(a) = &self.disp.autosave {
+			let path = a.path.clone();
+			self.restore_from(&path);
+		}
+	}
+
+	/// Restore the snapshot stored at `path` and resume the battle, switching the background music if it differs.
+	fn restore_from(&mut self, path: &str) -> bool {
+		let m = self.w.bgm_id;
+		let w = &mut self.w;
+		let r = std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|t| crate::save::restore_turn(w, &t));
+		match r {
+			Ok((p, acted)) => {
+				self.is.skip_intro();
+				self.is.resume(&mut self.w, p, &acted);
+				self.banner = None;
+				if self.w.bgm_id != m {
+					if let Some(a) = self.rs.get_music(m) {
+						self.rlau.stop_music_stream(a);
+					}
+					if let Some(a) = self.rs.get_music(self.w.bgm_id) {
+						self.rlau.play_music_stream(a);
+					}
+				}
+				true
+			},
+			Err(e) => {
+				eprintln!("warning [save]: failed to restore {}: {}", path, e);
+				false
+			}
+		}
+	}
+
+	/// Advance the slideshow shown before the intro text, skipping to the next slide on a click, SPACE, ENTER or the gamepad [confirm](CONFIRM_BUTTON) button, and past the last slide on ESCAPE or the gamepad [skip](SKIP_BUTTON) button.
+	fn advance_slides(&mut self) {
+		let sp = match &mut self.slides {
+			Some(sp) => sp,
+			None => return
+		};
+		sp.t += self.rl.get_frame_time();
+		let next = self.rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) || self.rl.is_key_pressed(KeyboardKey::KEY_SPACE) || self.rl.is_key_pressed(KeyboardKey::KEY_ENTER) || gamepad_pressed(&self.rl, CONFIRM_BUTTON);
+		if next || sp.t >= self.w.intro_slides().get(sp.index).map_or(0.0, |s| s.duration) {
+			sp.index += 1;
+			sp.t = 0.0;
+		}
+		// The slideshow also ends if the intro was skipped, e.g, by restoring a save.
+		if self.rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) || gamepad_pressed(&self.rl, SKIP_BUTTON) || sp.index >= self.w.intro_slides().len() || self.is.get_state() != 7 {
+			self.slides = None;
+			if self.disp.theme.transition_time > 0.0 {
+				self.transition = Some(ScreenTransition { from: None, t: 0.5 * self.disp.theme.transition_time });
+			}
+		}
+	}
+
+	/// Scroll the scrolling text screen shown, faster while SPACE, the left mouse button or the gamepad [confirm](CONFIRM_BUTTON) button is held, and close it once the text has left the screen, or on ESCAPE or the gamepad [skip](SKIP_BUTTON) button.
+	fn advance_scroll(&mut self) {
+		let sc = match &mut self.scroll {
+			Some(sc) => sc,
+			None => return
+		};
+		let fast = self.rl.is_key_down(KeyboardKey::KEY_SPACE) || self.rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) || gamepad_down(&self.rl, CONFIRM_BUTTON);
+		sc.offset += sc.text.speed * self.rl.get_frame_time() * if fast { SCROLL_FAST } else { 1.0 };
+		let (width, height) = self.disp.hud_size();
+		let tw = WINDOW_WIDTH.min(width as f32 - 2.0*XOFF);
+		let th = crate::text::measure_styled(&self.rs, &sc.text.text, self.disp.text_size(SCROLL_FONT), tw);
+		if self.rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) || gamepad_pressed(&self.rl, SKIP_BUTTON) || scroll_done(sc.offset, height as f32, th) {
+			self.finished = sc.ending;
+			self.scroll = None;
+		}
+	}
+
+	/// Show the banner for the phase just entered, if it starts a player or enemy phase.
+	/// Narrate the unit (or units) selected, once selected.
+	fn narrate_selection(&mut self) {
+		let sel = if matches!(self.is.get_state(), 1 | 4) { Some((self.is.cur_id, self.is.group().len())) } else { None };
+		if sel == self.narrated_sel {
+			return;
+		}
+		self.narrated_sel = sel;
+		if let Some(text) = sel.and_then(|(uid, n)| crate::narration::describe_selection(&self.w, uid, n)) {
+			self.sl.narrate(&mut self.w, &text);
+		}
+	}
+
+	fn show_banner(&mut self, prev: u8) {
+		let th = &self.disp.theme;
+		if th.banner_time <= 0.0 {
+			return;
+		}
+		let title = match (prev, self.phase) {
+			(3, 0) | (7, 0) => &th.player_phase_text,
+			(_, 3) => &th.enemy_phase_text,
+			_ => return
+		};
+		self.banner = Some(Banner {
+			phase: TurnPhase::from_state(self.phase),
+			title: title.clone(),
+			sub: format!("Turn {}", self.w.turn()),
+			t: 0.0
+		});
+	}
+
+	/// End the action of the selected unit or group if the Wait button of the unit panel is clicked. Returns true if it was, in which case the click is not passed on.
+	fn click_wait(&mut self) -> bool {
+		if self.hud_hidden || !self.is.can_wait() || !self.w.tutorial_allows(TutorialControl::Wait) || !self.rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+			return false;
+		}
+		let portrait = if self.is.group().is_empty() { crate::world::unit_portrait(&self.w, self.is.cur_id) } else { None };
+		if !self.disp.wait_button(&self.rs, portrait).check_collision_point_rec(self.rl.get_mouse_position() / self.disp.ui_scale) {
+			return false;
+		}
+		self.is.wait(&mut self.w);
+		// The tap on the button is not a tap on the map.
+		if let Some(t) = &mut self.is.touch {
+			t.cancel();
+		}
+		true
+	}
+
+	/// Scroll the unit information and battle log panels with the mouse wheel, or by dragging, while the cursor is over them.
+	fn scroll_panels(&mut self) {
+		let m = self.rl.get_mouse_position() / self.disp.ui_scale;
+		let mut dy = -self.rl.get_mouse_wheel_move() * SCROLL_STEP;
+		if self.rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+			dy += self.last_mouse.y - m.y;
+		}
+		self.last_mouse = m;
+		if dy == 0.0 {
+			return;
+		}
+		if self.is.show && self.is.show_info {
+			let rec = self.disp.info_rect(&self.rs, crate::world::unit_portrait(&self.w, self.is.cur_id));
+			if rec.check_collision_point_rec(m) {
+				let h = info_text(&self.w, &self.is, &self.disp.theme).map_or(0.0, |t| crate::text::measure_styled(&self.rs, &t, self.disp.text_size(22.0), rec.width));
+				self.is.info_scroll = (self.is.info_scroll + dy).clamp(0.0, (h - rec.height).max(0.0));
+			}
+		}
+		if self.is.show_log {
+			let rec = self.disp.log_rect();
+			if rec.check_collision_point_rec(m) {
+				let text = self.is.log.iter().cloned().collect::<Vec<_>>().join("\n");
+				let h = crate::text::measure_styled(&self.rs, &text, self.disp.text_size(LOG_FONT), rec.width - 12.0);
+				self.is.log_scroll = (self.is.log_scroll + dy).clamp(0.0, (h - rec.height + 8.0).max(0.0));
+			}
+		}
+	}
+
+	/// Poll input and draw a single frame of the animation viewer, see [Display::view_anims].
+	fn view_step(&mut self, v: &mut AnimViewer) -> bool {
+		if self.rl.window_should_close() {
+			return false;
+		}
+		let mut ids: Vec<u8> = self.w.unit_types.keys().cloned().collect();
+		ids.sort();
+		let n = ids.len();
+		let nst = 9;
+		let (ti, st) = (v.ti, v.st);
+		if n > 0 && self.rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+			v.ti = (v.ti + 1) % n;
+		}
+		if n > 0 && self.rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+			v.ti = (v.ti + n - 1) % n;
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+			v.st = (v.st + 1) % nst;
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_UP) {
+			v.st = (v.st + nst - 1) % nst;
+		}
+		if (v.ti, v.st) != (ti, st) {
+			v.frame = 0;
+			v.ftime = 0.0;
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+			v.paused = !v.paused;
+		}
+		if self.rl.is_key_pressed(KeyboardKey::KEY_O) {
+			v.onion = !v.onion;
+		}
+		let types = &self.w.unit_types;
+		let ut = ids.get(v.ti).and_then(|id| types.get(id));
+		let nf = ut.and_then(|u| u.anim_frame(v.st, 0)).map(|(nf, fr, _)| (nf.max(1), fr));
+		if let Some((nf, fr)) = nf {
+			if !v.paused {
+				v.ftime += self.rl.get_frame_time();
+				v.frame = (f32::floor(v.ftime * fr) as u32 % nf as u32) as u8;
+			} else if self.rl.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+				v.frame = (v.frame + 1) % nf;
+			} else if self.rl.is_key_pressed(KeyboardKey::KEY_COMMA) {
+				v.frame = (v.frame + nf - 1) % nf;
+			}
+		}
+		let mut d = self.rl.begin_drawing(&self.thread);
+		d.clear_background(self.disp.col);
+		match (ids.get(v.ti), ut) {
+			(Some(id), Some(ut)) => {
+				let tex = self.rs.get_texture(ut.tex_id());
+				let cam = Camera2D {
+					target: Vector2::new(0.0, 0.0),
+					offset: Vector2::new(0.5*self.disp.width as f32, 0.5*self.disp.height as f32),
+					rotation: 0.0,
+					zoom: VIEWER_ZOOM
+				};
+				let state = crate::world::usize_as_state(v.st);
+				let mut text = format!("{} [id:{}] ({}/{})\n", ut.name, id, v.ti + 1, n);
+				{
+					let mut d = d.begin_mode2D(cam);
+					if let Some((nf, _, rec)) = ut.anim_frame(v.st, v.frame) {
+						let pos = Vector2::new(-0.5*rec.width.abs(), -0.5*rec.height);
+						if v.onion && nf > 1 {
+							if let Some((_, _, prev)) = ut.anim_frame(v.st, (v.frame + nf - 1) % nf) {
+								d.draw_texture_rec(tex, prev, pos, Color::WHITE.fade(0.35));
+							}
+						}
+						d.draw_texture_rec(tex, rec, pos, Color::WHITE);
+						d.draw_rectangle_lines(pos.x as i32, pos.y as i32, rec.width.abs() as i32, rec.height as i32, Color::GRAY);
+						text.push_str(&format!("{:?}: frame {}/{}\nx: {}, y: {}, w: {}, h: {}", state, v.frame + 1, nf, rec.x, rec.y, rec.width, rec.height));
+					} else {
+						text.push_str(&format!("{:?}: not defined", state));
+					}
+				}
+				d.draw_text(&text, 20, 20, 20, Color::WHITE);
+			},
+			_ => d.draw_text("No unit types registered", 20, 20, 20, Color::WHITE)
+		}
+		let ctl = format!("[LEFT/RIGHT] type  [UP/DOWN] state  [SPACE] {}  [,/.] step  [O] onion-skin {}", if v.paused {"play"} else {"pause"}, if v.onion {"off"} else {"on"});
+		d.draw_text(&ctl, 20, self.disp.height - 30, 20, Color::WHITE);
+		true
+	}
+
+	/// Set the scale factor of HUD elements. See [Display::set_ui_scale].
+	pub fn set_ui_scale(&mut self, s: f32) {
+		self.disp.set_ui_scale(s);
+	}
+
+	/// Set the scale factor of font sizes. See [Display::set_text_scale].
+	pub fn set_text_scale(&mut self, s: f32) {
+		self.disp.set_text_scale(s);
+	}
+
+	/// Switch to or from the high-contrast look. See [Display::set_high_contrast].
+	pub fn set_high_contrast(&mut self, on: bool) {
+		self.disp.set_high_contrast(on);
+	}
+
+	/// Set the look of HUD elements, see [Display::set_theme].
+	pub fn set_theme(&mut self, t: Theme) {
+		self.disp.set_theme(t);
+	}
+
+	/// Hide or show all HUD elements (the fps counter, panels, banners) and overlays (tile highlights, outlines), e.g, for screenshots. Message windows are always shown.
+	pub fn set_hud_hidden(&mut self, hidden: bool) {
+		self.hud_hidden = hidden;
+	}
+
+	/// Returns true if the HUD is hidden, see [set_hud_hidden](Runtime::set_hud_hidden).
+	pub fn hud_hidden(&self) -> bool {
+		self.hud_hidden
+	}
+
+	/// Set the time limits of the player turn, see [Display::set_turn_timer]. The clocks restart at once.
+	pub fn set_turn_timer(&mut self, per_turn: f32, bank: f32, increment: f32, auto_wait: bool) {
+		self.disp.set_turn_timer(per_turn, bank, increment, auto_wait);
+		self.is.timer = self.disp.turn_timer;
+	}
+
+	/// Set the protection against accidental input, see [Display::set_input_guard].
+	pub fn set_input_guard(&mut self, debounce: f32, min_screen_time: f32) {
+		self.disp.set_input_guard(debounce, min_screen_time);
+		self.is.guard = self.disp.input_guard;
+	}
+
+	/// Set the gamepad rumble on feedback events, see [Display::set_rumble].
+	pub fn set_rumble(&mut self, on_hit: (f32, f32), on_select: (f32, f32)) {
+		self.disp.set_rumble(on_hit, on_select);
+		self.is.rumble = self.disp.rumble;
+	}
+
+	/// Read touch gestures or mouse clicks, see [Display::set_touch_input].
+	pub fn set_touch_input(&mut self, on: bool) {
+		self.disp.set_touch_input(on);
+		if on != self.is.touch.is_some() {
+			self.is.touch = if on { Some(TouchTracker::default()) } else { None };
+		}
+	}
+
+	/// Returns the time limits of the player turn, with the time left on each clock.
+	pub fn turn_timer(&self) -> &TurnTimer {
+		&self.is.timer
+	}
+
+	/// Show or hide the danger zone (also toggled by [KeyBindings::toggle_danger](crate::input::KeyBindings)), see [influence](crate::world::influence).
+	pub fn set_danger_zone(&mut self, on: bool) {
+		self.is.show_danger = on;
+	}
+
+	/// Show or hide the AI debug overlay (also toggled by [KeyBindings::toggle_ai_debug](crate::input::KeyBindings)), see [World::ai_plans].
+	pub fn set_ai_debug(&mut self, on: bool) {
+		self.is.show_ai_debug = on;
+	}
+
+	/// Hide the HUD in screenshots, see [Display::set_clean_screenshots].
+	pub fn set_clean_screenshots(&mut self, on: bool) {
+		self.disp.set_clean_screenshots(on);
+	}
+
+	/// Show or hide the control hints bar, see [Display::set_control_hints].
+	pub fn set_control_hints(&mut self, on: bool) {
+		self.disp.set_control_hints(on);
+	}
+
+	/// Pause the game while the window is not focused, see [Display::set_pause_unfocused].
+	pub fn set_pause_unfocused(&mut self, on: bool) {
+		self.disp.set_pause_unfocused(on);
+	}
+
+	/// Ask for confirmation when the window is closed, see [Display::set_confirm_exit].
+	pub fn set_confirm_exit(&mut self, on: bool) {
+		self.disp.set_confirm_exit(on);
+	}
+
+	/// Set the simulation rate, see [Display::set_sim_fps].
+	pub fn set_sim_fps(&mut self, fps: u32) {
+		self.disp.set_sim_fps(fps);
+		self.sim_time = 0.0;
+	}
+
+	/// Set the target fps while the window is not focused, see [Display::set_background_fps]. Takes effect on the next frame.
+	pub fn set_background_fps(&mut self, fps: u32) {
+		self.disp.set_background_fps(fps);
+		if self.throttled {
+			self.throttled = false;
+			let f = self.disp.fps;
+			self.rl.set_target_fps(f);
+		}
+	}
+
+	/// Set the time budget of the built-in AI, see [Display::set_ai_budget].
+	pub fn set_ai_budget(&mut self, ms: f32) {
+		self.disp.set_ai_budget(ms);
+		self.is.ai_budget = self.disp.ai_budget;
+	}
+
+	/// Set the directory screenshots are saved to, see [Display::set_screenshot_dir].
+	pub fn set_screenshot_dir(&mut self, dir: &str) {
+		self.disp.set_screenshot_dir(dir);
+	}
+
+	/// Enable autosaves of the running game, see [Display::set_autosave]. The recovery prompt, if needed, is shown on the next frame.
+	pub fn set_autosave(&mut self, path: &str, every: u32, before_enemy: bool) {
+		let a = Autosave { path: path.to_string(), every: every, before_enemy: before_enemy };
+		if self.disp.autosave.as_ref().map(|b| &b.path) != Some(&a.path) {
+			if let Some(b) = &self.disp.autosave {
+				let _ = std::fs::remove_file(b.lock_path());
+			}
+			self.recovery = a.begin_session();
+		}
+		self.disp.autosave = Some(a);
+	}
+
+	/// Save the battle to a slot, with a thumbnail of the last frame drawn. Returns false if the snapshot could not be written; a missing thumbnail only warns.
+	pub fn save_slot(&mut self, slots: &SaveSlots, slot: u32) -> bool {
+		if !slots.save_turn(&self.w, slot, &self.is.acted()) {
+			return false;
+		}
+		let mut img = self.rl.get_screen_data(&self.thread);
+		let h = (img.height() as i64 * THUMB_WIDTH as i64 / img.width().max(1) as i64) as i32;
+		img.resize(THUMB_WIDTH, h.max(1));
+		img.export_image(&slots.thumbnail_path(slot));
+		true
+	}
+
+	/// Restore a slot, discarding the battle in progress. Returns false (leaving the game unchanged) if the slot is empty or invalid.
+	pub fn load_slot(&mut self, slots: &SaveSlots, slot: u32) -> bool {
+		self.restore_from(&slots.path(slot))
+	}
+
+	/// Apply the ui and text scale, high-contrast mode, simulation and background frame rates, AI budget, auto-pause, volumes and muting and key bindings of `s`, e.g, from an options menu. The resolution, target fps and vsync take effect on the next launch.
+	pub fn apply_settings(&mut self, s: &Settings) {
+		self.disp.set_ui_scale(s.ui_scale);
+		self.disp.set_text_scale(s.text_scale);
+		self.disp.set_high_contrast(s.high_contrast);
+		self.set_sim_fps(s.sim_fps);
+		self.set_background_fps(s.background_fps);
+		self.set_ai_budget(s.ai_budget_ms);
+		self.disp.set_pause_unfocused(s.pause_unfocused);
+		self.is.keys = s.keys;
+		self.disp.mvolume = s.master_volume;
+		self.w.apply_audio_settings(s);
+	}
+
+	/// Save the volumes of the running game, and muting while unfocused, to the settings file at `path` (see [Settings::update_audio]), keeping its other settings, so that they persist to the next launch.
+	pub fn save_audio_settings(&self, path: &str) -> std::io::Result<()> {
+		let mut s = Settings::load(path)?;
+		s.update_audio(self.w.mixer());
+		s.save(path)
+	}
+
+	/// Returns the look of HUD elements.
+	pub fn theme(&self) -> &Theme {
+		&self.disp.theme
+	}
+
+	/// Borrow the world of the running game.
+	pub fn world(&self) -> &World {
+		&self.w
+	}
+
+	/// Mutably borrow the world of the running game.
+	pub fn world_mut(&mut self) -> &mut World {
+		&mut self.w
+	}
+
+	/// Report the memory used by the loaded resources of the running game, see [ResourceSet::report].
+	pub fn resource_report(&self) -> crate::utils::MemoryReport {
+		self.rs.report()
+	}
+
+	/// Close the window and release all resources, returning the world to the caller.
+	/// Listeners are notified of the exit (unless already notified), then the player profile is saved, and the autosave (if any) is marked as not needing recovery.
+	pub fn shutdown(mut self) -> World {
+		self.exit();
+		let Runtime { rs, mut w, sl, is, rlau, rl, disp, .. } = self;
+		w.save_profile();
+		// A clean shutdown, so there is nothing to recover on the next launch.
+		if let Some(a) = &disp.autosave {
+			let _ = std::fs::remove_file(a.lock_path());
+		}
+		drop(rs);
+		drop(sl);
+		drop(is);
+		drop(rlau);
+		drop(rl);
+		w
+	}
+}
+
+#[inline]
+fn _man_cam(cam: &mut Camera2D, w: &World) {
+	let (cx, cy) = w.get_cpos();
 	cam.target.x = cx;
 	cam.target.y = cy;
 	cam.offset.x = w.coff.0;
 	cam.offset.y = w.coff.1;
+	cam.zoom = w.zoom();
+}
+
+extern "C" {
+	// raylib draws its random values from the C library generator.
+	fn srand(seed: u32);
 }
 
 #[inline]
-fn _cam_control(w: &mut World, rl: &RaylibHandle) {
-	if rl.is_key_down(KeyboardKey::KEY_LEFT) {
+fn _cam_control(w: &mut World, rl: &RaylibHandle, keys: &KeyBindings) {
+	if [keys.cam_left, keys.cam_right, keys.cam_up, keys.cam_down].iter().any(|k| rl.is_key_down(*k)) {
+		w.tweens.pan = None;
+	}
+	if rl.is_key_down(keys.cam_left) {
 		w.cam_wx -= rl.get_frame_time() * 4.0;
 	}
-	if rl.is_key_down(KeyboardKey::KEY_RIGHT) {
+	if rl.is_key_down(keys.cam_right) {
 		w.cam_wx += rl.get_frame_time() * 4.0;
 	}
-	if rl.is_key_down(KeyboardKey::KEY_UP) {
+	if rl.is_key_down(keys.cam_up) {
 		w.cam_wy -= 4.0 * rl.get_frame_time();
 	}
-	if rl.is_key_down(KeyboardKey::KEY_DOWN) {
+	if rl.is_key_down(keys.cam_down) {
 		w.cam_wy += 4.0 * rl.get_frame_time();
 	}
 }
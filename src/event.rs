@@ -0,0 +1,279 @@
+//! Engine events, published on a single bus rather than through a callback per event.
+//! Rust code subscribes to events with [StateListener::subscribe](crate::utils::StateListener::subscribe), and FFI consumers poll them from a queue on the world,
+//! once enabled with [World::enable_event_queue](crate::world::World::enable_event_queue).
+//!
+//! Events are published as they happen, alongside the matching `on_*` callback of the [StateListener](crate::utils::StateListener), if any.
+//!
+//! Events (and [orders](crate::input::Order)) are displayed as single-line JSON objects, e.g, `{"event":"UnitDied","uid":3,"type_id":0}`, for external tools;
+//! subscribe [json_lines] to write every event to a file or stream.
+//! # Example
+//! ```
+//! use alesia::event::EngineEvent;
+//! use alesia::utils::StateListener;
+//! use alesia::world::World;
+//! let mut w = World::blank();
+//! w.enable_event_queue(true);
+//! let mut sl = StateListener::new();
+//! sl.subscribe(|_, e| if let EngineEvent::UnitDied { uid, .. } = e { println!("unit {} died", uid) });
+//! sl.notify_unit_death(&mut w, 3, 0);
+//! assert_eq!(w.poll_event(), Some(EngineEvent::UnitDied { uid: 3, type_id: 0 }));
+//! ```
+
+use crate::input::TurnPhase;
+use crate::input::Outcome;
+use crate::input::InputScheme;
+use crate::utils::ResType;
+use crate::world::World;
+use crate::world::pickup::PickupEffect;
+use std::fmt;
+use std::io::Write;
+
+/// Most events held by the queue of a world; the oldest events are dropped beyond this.
+pub const QUEUE_CAPACITY: usize = 1024;
+
+/// An event of the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineEvent {
+	/// The unit with the specified id, and type id, died.
+	UnitDied {
+		/// Id of the unit.
+		uid: u8,
+		/// Type id of the unit.
+		type_id: u8
+	},
+	/// A unit dealt damage to another.
+	UnitAttacked {
+		/// Id of the attacking unit.
+		attacker: u8,
+		/// Id of the unit attacked.
+		defender: u8,
+		/// Damage dealt.
+		damage: f32
+	},
+	/// A unit ended its move on a tile.
+	UnitMoved {
+		/// Id of the unit.
+		uid: u8,
+		/// The tile moved to.
+		tile: (i32, i32)
+	},
+	/// A unit collected a [pickup](crate::world::pickup).
+	PickupCollected {
+		/// Id of the unit.
+		uid: u8,
+		/// The effect applied.
+		effect: PickupEffect
+	},
+	/// The game entered a phase.
+	PhaseChanged(TurnPhase),
+	/// The banner announcing a phase has finished.
+	BannerDone(TurnPhase),
+	/// A transition between screens, into a phase, has finished.
+	TransitionDone(TurnPhase),
+	/// Time ran out in the player turn; true if the bank was spent, see [TurnTimer](crate::input::TurnTimer).
+	TurnTimedOut(bool),
+	/// A map was loaded, from the specified path.
+	MapLoaded(String),
+	/// A resource mapped on the [ResourceSet](crate::utils::ResourceSet) was loaded, or failed to load (in which case a placeholder is used, if any).
+	ResourceLoaded {
+		/// The id of the resource.
+		id: u8,
+		/// The kind of resource.
+		kind: ResType,
+		/// Whether the resource was loaded.
+		ok: bool
+	},
+	/// The results screen of a game with the specified outcome was dismissed.
+	ResultsDismissed(Outcome),
+	/// A gamepad should rumble, as configured with [Display::set_rumble](crate::display::Display::set_rumble); the game plays it with the gamepad API of its platform.
+	Rumble {
+		/// Strength, from 0 to 1.
+		strength: f32,
+		/// Duration in seconds.
+		duration: f32
+	},
+	/// The player switched input device, see [World::input_scheme](crate::world::World::input_scheme).
+	InputSchemeChanged(InputScheme),
+	/// The [tutorial](crate::tutorial) moved on to the step with the specified index, or began at step 0; the index is the number of steps once the tutorial is done.
+	TutorialStep(usize),
+	/// The game is about to exit.
+	Exit
+}
+
+/// Blittable form of an [EngineEvent], for the native API. The meaning of the fields depends on `kind`:
+///
+/// | Kind | Event | `uid` | `arg` | `value` | `x`, `y` |
+/// | ---- | ----- | ----- | ----- | ------- | -------- |
+/// | 0 | Unit died | unit | type id | - | - |
+/// | 1 | Unit attacked | attacker | defender | damage | - |
+/// | 2 | Unit moved | unit | - | - | tile |
+/// | 3 | Pickup collected | unit | pickup kind | pickup value | - |
+/// | 4 | Phase changed | - | phase | - | - |
+/// | 5 | Banner done | - | phase | - | - |
+/// | 6 | Turn timed out | - | 1 if the bank was spent | - | - |
+/// | 7 | Map loaded | - | - | - | - |
+/// | 8 | Resource loaded | - | resource id | - | kind (0 - texture, 1 - font, 2 - sound, 3 - music), 1 if loaded |
+/// | 9 | Results dismissed | - | 1 for victory, 2 for defeat | - | - |
+/// | 10 | Exit | - | - | - | - |
+/// | 11 | Transition done | - | phase | - | - |
+/// | 12 | Rumble | - | - | strength | duration in milliseconds, - |
+/// | 13 | Input scheme changed | - | scheme (0 - keyboard and mouse, 1 - gamepad, 2 - touch) | - | - |
+/// | 14 | Tutorial step | - | - | - | index of the step, - |
+///
+/// Unused fields are 0. The path of a loaded map is not included; read it from the JSON form (see `alsnPollEventJson`), or from the `on_map_loaded` callback.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RawEvent {
+	/// The kind of event.
+	pub kind: u8,
+	/// Id of the unit concerned.
+	pub uid: u8,
+	/// Second argument.
+	pub arg: u8,
+	/// Numeric value.
+	pub value: f32,
+	/// First co-ordinate.
+	pub x: i32,
+	/// Second co-ordinate.
+	pub y: i32
+}
+
+impl EngineEvent {
+	/// Returns the name of the event kind, e.g, "UnitDied".
+	pub fn name(&self) -> &'static str {
+		match self {
+			EngineEvent::UnitDied { .. } => "UnitDied",
+			EngineEvent::UnitAttacked { .. } => "UnitAttacked",
+			EngineEvent::UnitMoved { .. } => "UnitMoved",
+			EngineEvent::PickupCollected { .. } => "PickupCollected",
+			EngineEvent::PhaseChanged(_) => "PhaseChanged",
+			EngineEvent::BannerDone(_) => "BannerDone",
+			EngineEvent::TransitionDone(_) => "TransitionDone",
+			EngineEvent::TurnTimedOut(_) => "TurnTimedOut",
+			EngineEvent::MapLoaded(_) => "MapLoaded",
+			EngineEvent::ResourceLoaded { .. } => "ResourceLoaded",
+			EngineEvent::ResultsDismissed(_) => "ResultsDismissed",
+			EngineEvent::Rumble { .. } => "Rumble",
+			EngineEvent::InputSchemeChanged(_) => "InputSchemeChanged",
+			EngineEvent::TutorialStep(_) => "TutorialStep",
+			EngineEvent::Exit => "Exit"
+		}
+	}
+
+	/// Convert to the blittable form, see [RawEvent].
+	pub fn to_raw(&self) -> RawEvent {
+		let r = RawEvent::default();
+		match *self {
+			EngineEvent::UnitDied { uid, type_id } => RawEvent { kind: 0, uid: uid, arg: type_id, ..r },
+			EngineEvent::UnitAttacked { attacker, defender, damage } => RawEvent { kind: 1, uid: attacker, arg: defender, value: damage, ..r },
+			EngineEvent::UnitMoved { uid, tile } => RawEvent { kind: 2, uid: uid, x: tile.0, y: tile.1, ..r },
+			EngineEvent::PickupCollected { uid, effect } => {
+				let (k, v) = effect.to_raw();
+				RawEvent { kind: 3, uid: uid, arg: k, value: v, ..r }
+			},
+			EngineEvent::PhaseChanged(p) => RawEvent { kind: 4, arg: p as u8, ..r },
+			EngineEvent::BannerDone(p) => RawEvent { kind: 5, arg: p as u8, ..r },
+			EngineEvent::TurnTimedOut(b) => RawEvent { kind: 6, arg: b as u8, ..r },
+			EngineEvent::MapLoaded(_) => RawEvent { kind: 7, ..r },
+			EngineEvent::ResourceLoaded { id, kind, ok } => RawEvent { kind: 8, arg: id, x: kind as i32, y: ok as i32, ..r },
+			EngineEvent::ResultsDismissed(o) => RawEvent { kind: 9, arg: o.to_raw() as u8, ..r },
+			EngineEvent::Exit => RawEvent { kind: 10, ..r },
+			EngineEvent::TransitionDone(p) => RawEvent { kind: 11, arg: p as u8, ..r },
+			EngineEvent::Rumble { strength, duration } => RawEvent { kind: 12, value: strength, x: (duration * 1000.0).round() as i32, ..r },
+			EngineEvent::InputSchemeChanged(s) => RawEvent { kind: 13, arg: s as u8, ..r },
+			EngineEvent::TutorialStep(i) => RawEvent { kind: 14, x: i as i32, ..r }
+		}
+	}
+}
+
+impl fmt::Display for EngineEvent {
+	/// Writes the event as a JSON object, with the name of the event under "event", and its fields. Phases and outcomes are written by name, e.g, "EnemyTurn".
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{{\"event\":\"{}\"", self.name())?;
+		match self {
+			EngineEvent::UnitDied { uid, type_id } => write!(f, ",\"uid\":{},\"type_id\":{}", uid, type_id)?,
+			EngineEvent::UnitAttacked { attacker, defender, damage } => {
+				write!(f, ",\"attacker\":{},\"defender\":{},\"damage\":", attacker, defender)?;
+				json_num(f, *damage)?;
+			},
+			EngineEvent::UnitMoved { uid, tile } => write!(f, ",\"uid\":{},\"tile\":[{},{}]", uid, tile.0, tile.1)?,
+			EngineEvent::PickupCollected { uid, effect } => {
+				let (name, v) = match *effect {
+					PickupEffect::Heal(v) => ("heal", v),
+					PickupEffect::Boost(v) => ("boost", v),
+					PickupEffect::Gold(n) => ("gold", n as f32)
+				};
+				write!(f, ",\"uid\":{},\"effect\":\"{}\",\"value\":", uid, name)?;
+				json_num(f, v)?;
+			},
+			EngineEvent::PhaseChanged(p) | EngineEvent::BannerDone(p) | EngineEvent::TransitionDone(p) => write!(f, ",\"phase\":\"{:?}\"", p)?,
+			EngineEvent::TurnTimedOut(b) => write!(f, ",\"bank_spent\":{}", b)?,
+			EngineEvent::MapLoaded(path) => {
+				f.write_str(",\"path\":")?;
+				json_str(f, path)?;
+			},
+			EngineEvent::ResourceLoaded { id, kind, ok } => {
+				let k = match kind {
+					ResType::Tex => "texture",
+					ResType::Fnt => "font",
+					ResType::Snd => "sound",
+					ResType::Mus => "music"
+				};
+				write!(f, ",\"id\":{},\"kind\":\"{}\",\"ok\":{}", id, k, ok)?;
+			},
+			EngineEvent::ResultsDismissed(o) => write!(f, ",\"outcome\":\"{:?}\"", o)?,
+			EngineEvent::Rumble { strength, duration } => {
+				f.write_str(",\"strength\":")?;
+				json_num(f, *strength)?;
+				f.write_str(",\"duration\":")?;
+				json_num(f, *duration)?;
+			},
+			EngineEvent::InputSchemeChanged(s) => write!(f, ",\"scheme\":\"{:?}\"", s)?,
+			EngineEvent::TutorialStep(i) => write!(f, ",\"step\":{}", i)?,
+			EngineEvent::Exit => {}
+		}
+		f.write_str("}")
+	}
+}
+
+/// Returns a function to [subscribe](crate::utils::StateListener::subscribe), which writes every event to `out` as a line of JSON.
+/// Write errors are reported as warnings, and do not stop the game.
+/// # Example
+/// ```no_run
+/// use alesia::utils::StateListener;
+/// let mut sl = StateListener::new();
+/// sl.subscribe(alesia::event::json_lines(std::fs::File::create("events.jsonl").unwrap()));
+/// ```
+pub fn json_lines(mut out: impl Write + 'static) -> impl FnMut(&mut World, &EngineEvent) {
+	move |_, e| {
+		if let Err(err) = writeln!(out, "{}", e) {
+			eprintln!("warning [event]: failed to write event {}: {}", e.name(), err);
+		}
+	}
+}
+
+/// Write a number as JSON; values that are not finite are written as null.
+pub(crate) fn json_num(f: &mut fmt::Formatter, v: f32) -> fmt::Result {
+	if v.is_finite() {
+		write!(f, "{}", v)
+	} else {
+		f.write_str("null")
+	}
+}
+
+/// Write a string as JSON, escaping quotes, backslashes and control characters.
+fn json_str(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+	f.write_str("\"")?;
+	for c in s.chars() {
+		match c {
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+			c => write!(f, "{}", c)?
+		}
+	}
+	f.write_str("\"")
+}
@@ -3,18 +3,54 @@
 use raylib::audio::RaylibAudio;
 use crate::utils::ResourceSet;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use crate::world::World;
+use crate::world::Grid;
+use crate::world::PopupKind;
+use crate::world::BattleEvent;
+use crate::world::AiJob;
+use crate::world::ObjectiveState;
+use crate::world::voice::BarkKind;
+use crate::world::mixer::StingerKind;
+use crate::puzzle::PuzzleStep;
+use crate::tutorial::TutorialControl;
+use crate::tutorial::TutorialGoal;
+use crate::tween::Tween;
+use crate::tween::Easing;
+use crate::event::EngineEvent;
+use crate::touch::Gesture;
+use crate::touch::TouchTracker;
+use crate::replay::Replay;
 use raylib::RaylibHandle;
 use crate::utils::StateListener;
 use raylib::math::Vector2;
 use raylib::ffi::MouseButton;
 use raylib::ffi::KeyboardKey;
+use raylib::ffi::GamepadAxis;
+use raylib::ffi::GamepadButton;
 
 use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
 
 pub const DEBUG_ORDERS: bool = true;
+/// Number of lines kept in the battle log.
+const LOG_CAPACITY: usize = 64;
+/// Distance (in pixels) the mouse must be dragged to select units in a box, rather than click.
+const DRAG_THRESHOLD: f32 = 8.0;
+/// Time (in seconds) a popup is shown.
+const POPUP_TIME: f32 = 0.9;
 
+/// Damage dealt to a unit, health restored, or other text, shown rising from its tile.
 #[derive(Debug)]
+pub(crate) struct Popup {
+	pub(crate) tile: (i32, i32),
+	pub(crate) text: String,
+	pub(crate) kind: PopupKind,
+	pub(crate) t: Tween
+}
+
+#[derive(Debug, Clone, PartialEq)]
 /// An enum containing all possible orders followed by units.
 pub enum Order {
 	/// Order to move unit with id, to tile position.
@@ -44,6 +80,506 @@ impl Order {
 	}
 }
 
+impl fmt::Display for Order {
+	/// Writes the order as a JSON object, with the name of the variant under "order", e.g, `{"order":"MOVE","uid":1,"tile":[2,3]}`.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Order::MOVE(u, x, y) => write!(f, "{{\"order\":\"MOVE\",\"uid\":{},\"tile\":[{},{}]}}", u, x, y),
+			Order::ATTACK(u, t, x, y) => write!(f, "{{\"order\":\"ATTACK\",\"uid\":{},\"target\":{},\"tile\":[{},{}]}}", u, t, x, y),
+			Order::VICTORY => f.write_str("{\"order\":\"VICTORY\"}"),
+			Order::DEFEAT => f.write_str("{\"order\":\"DEFEAT\"}"),
+			Order::MutHealthR(u, v) => mut_health(f, "MutHealthR", *u, *v),
+			Order::MutHealthA(u, v) => mut_health(f, "MutHealthA", *u, *v)
+		}
+	}
+}
+
+fn mut_health(f: &mut fmt::Formatter, name: &str, uid: u8, v: f32) -> fmt::Result {
+	write!(f, "{{\"order\":\"{}\",\"uid\":{},\"value\":", name, uid)?;
+	crate::event::json_num(f, v)?;
+	f.write_str("}")
+}
+
+/// Phases of the game, as reported to the `on_phase_change` [callback](StateListener).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnPhase {
+	/// Player turn, no unit selected.
+	PlayerTurn = 0,
+	/// Player turn, unit selected; selecting movement tiles.
+	MoveSelect = 1,
+	/// Player units carry out their orders.
+	PlayerMoves = 2,
+	/// Enemy turn.
+	EnemyTurn = 3,
+	/// Player turn, movement selected; selecting attack tile.
+	AttackSelect = 4,
+	/// Player victory.
+	Victory = 5,
+	/// Player defeat.
+	Defeat = 6,
+	/// Level introduction.
+	Intro = 7
+}
+
+impl TurnPhase {
+	pub(crate) fn from_state(s: u8) -> TurnPhase {
+		match s {
+			0 => TurnPhase::PlayerTurn,
+			1 => TurnPhase::MoveSelect,
+			2 => TurnPhase::PlayerMoves,
+			3 => TurnPhase::EnemyTurn,
+			4 => TurnPhase::AttackSelect,
+			5 => TurnPhase::Victory,
+			6 => TurnPhase::Defeat,
+			_ => TurnPhase::Intro
+		}
+	}
+}
+
+/// Names of keys that may be bound to actions, as written in a [Settings](crate::utils::Settings) file.
+const KEY_NAMES: &[(&str, KeyboardKey)] = &[
+	("A", KeyboardKey::KEY_A), ("B", KeyboardKey::KEY_B), ("C", KeyboardKey::KEY_C), ("D", KeyboardKey::KEY_D),
+	("E", KeyboardKey::KEY_E), ("F", KeyboardKey::KEY_F), ("G", KeyboardKey::KEY_G), ("H", KeyboardKey::KEY_H),
+	("I", KeyboardKey::KEY_I), ("J", KeyboardKey::KEY_J), ("K", KeyboardKey::KEY_K), ("L", KeyboardKey::KEY_L),
+	("M", KeyboardKey::KEY_M), ("N", KeyboardKey::KEY_N), ("O", KeyboardKey::KEY_O), ("P", KeyboardKey::KEY_P),
+	("Q", KeyboardKey::KEY_Q), ("R", KeyboardKey::KEY_R), ("S", KeyboardKey::KEY_S), ("T", KeyboardKey::KEY_T),
+	("U", KeyboardKey::KEY_U), ("V", KeyboardKey::KEY_V), ("W", KeyboardKey::KEY_W), ("X", KeyboardKey::KEY_X),
+	("Y", KeyboardKey::KEY_Y), ("Z", KeyboardKey::KEY_Z),
+	("0", KeyboardKey::KEY_ZERO), ("1", KeyboardKey::KEY_ONE), ("2", KeyboardKey::KEY_TWO), ("3", KeyboardKey::KEY_THREE),
+	("4", KeyboardKey::KEY_FOUR), ("5", KeyboardKey::KEY_FIVE), ("6", KeyboardKey::KEY_SIX), ("7", KeyboardKey::KEY_SEVEN),
+	("8", KeyboardKey::KEY_EIGHT), ("9", KeyboardKey::KEY_NINE),
+	("F1", KeyboardKey::KEY_F1), ("F2", KeyboardKey::KEY_F2), ("F3", KeyboardKey::KEY_F3), ("F4", KeyboardKey::KEY_F4),
+	("F5", KeyboardKey::KEY_F5), ("F6", KeyboardKey::KEY_F6), ("F7", KeyboardKey::KEY_F7), ("F8", KeyboardKey::KEY_F8),
+	("F9", KeyboardKey::KEY_F9), ("F10", KeyboardKey::KEY_F10), ("F11", KeyboardKey::KEY_F11), ("F12", KeyboardKey::KEY_F12),
+	("LEFT", KeyboardKey::KEY_LEFT), ("RIGHT", KeyboardKey::KEY_RIGHT), ("UP", KeyboardKey::KEY_UP), ("DOWN", KeyboardKey::KEY_DOWN),
+	("SPACE", KeyboardKey::KEY_SPACE), ("ENTER", KeyboardKey::KEY_ENTER), ("TAB", KeyboardKey::KEY_TAB), ("BACKSPACE", KeyboardKey::KEY_BACKSPACE),
+	("ESCAPE", KeyboardKey::KEY_ESCAPE), ("INSERT", KeyboardKey::KEY_INSERT), ("DELETE", KeyboardKey::KEY_DELETE), ("HOME", KeyboardKey::KEY_HOME),
+	("END", KeyboardKey::KEY_END), ("PAGE_UP", KeyboardKey::KEY_PAGE_UP), ("PAGE_DOWN", KeyboardKey::KEY_PAGE_DOWN),
+	("COMMA", KeyboardKey::KEY_COMMA), ("PERIOD", KeyboardKey::KEY_PERIOD), ("MINUS", KeyboardKey::KEY_MINUS), ("EQUAL", KeyboardKey::KEY_EQUAL),
+	("SLASH", KeyboardKey::KEY_SLASH), ("SEMICOLON", KeyboardKey::KEY_SEMICOLON), ("APOSTROPHE", KeyboardKey::KEY_APOSTROPHE), ("GRAVE", KeyboardKey::KEY_GRAVE),
+	("LEFT_BRACKET", KeyboardKey::KEY_LEFT_BRACKET), ("RIGHT_BRACKET", KeyboardKey::KEY_RIGHT_BRACKET), ("BACKSLASH", KeyboardKey::KEY_BACKSLASH)
+];
+
+/// Returns the key with the given name (case-insensitive), e.g, "A", "7", "F5", "ENTER" or "PAGE_UP".
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+	KEY_NAMES.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, k)| *k)
+}
+
+/// Returns the name of a key, as accepted by [key_from_name], or None if the key cannot be bound.
+pub fn key_name(k: KeyboardKey) -> Option<&'static str> {
+	KEY_NAMES.iter().find(|(_, kk)| *kk == k).map(|(n, _)| *n)
+}
+
+/// Keys bound to player actions. Mouse controls are fixed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBindings {
+	/// End the player turn. Default is ENTER.
+	pub end_turn: KeyboardKey,
+	/// Deselect the current unit or group, discarding its orders. Default is E.
+	pub cancel: KeyboardKey,
+	/// End the action of the selected unit, or every unit of the selected group, without further orders, as does the Wait button of the unit panel. Default is W.
+	pub wait: KeyboardKey,
+	/// Show or hide the battle log. Default is L.
+	pub toggle_log: KeyboardKey,
+	/// Show or hide the objective tracker. Default is O.
+	pub toggle_objectives: KeyboardKey,
+	/// Pan the camera left. Default is LEFT.
+	pub cam_left: KeyboardKey,
+	/// Pan the camera right. Default is RIGHT.
+	pub cam_right: KeyboardKey,
+	/// Pan the camera up. Default is UP.
+	pub cam_up: KeyboardKey,
+	/// Pan the camera down. Default is DOWN.
+	pub cam_down: KeyboardKey,
+	/// Save a screenshot, while left control is held down. Default is S.
+	pub screenshot: KeyboardKey,
+	/// Hide or show all HUD elements and overlays, e.g, for screenshots. Default is H.
+	pub toggle_hud: KeyboardKey,
+	/// Show or hide the AI debug overlay. Default is F3.
+	pub toggle_ai_debug: KeyboardKey,
+	/// Show or hide the danger zone, i.e, the tiles enemy units could attack next turn. Default is D.
+	pub toggle_danger: KeyboardKey,
+	/// Open or close the [debug console](crate::console). Default is GRAVE, i.e, backtick.
+	pub toggle_console: KeyboardKey
+}
+
+impl Default for KeyBindings {
+	fn default() -> KeyBindings {
+		KeyBindings {
+			end_turn: KeyboardKey::KEY_ENTER,
+			cancel: KeyboardKey::KEY_E,
+			wait: KeyboardKey::KEY_W,
+			toggle_log: KeyboardKey::KEY_L,
+			toggle_objectives: KeyboardKey::KEY_O,
+			cam_left: KeyboardKey::KEY_LEFT,
+			cam_right: KeyboardKey::KEY_RIGHT,
+			cam_up: KeyboardKey::KEY_UP,
+			cam_down: KeyboardKey::KEY_DOWN,
+			screenshot: KeyboardKey::KEY_S,
+			toggle_hud: KeyboardKey::KEY_H,
+			toggle_ai_debug: KeyboardKey::KEY_F3,
+			toggle_danger: KeyboardKey::KEY_D,
+			toggle_console: KeyboardKey::KEY_GRAVE
+		}
+	}
+}
+
+impl KeyBindings {
+	/// Names of all actions, in the order of the fields.
+	pub const ACTIONS: [&'static str; 14] = ["end_turn", "cancel", "wait", "toggle_log", "toggle_objectives", "cam_left", "cam_right", "cam_up", "cam_down", "screenshot", "toggle_hud", "toggle_ai_debug", "toggle_danger", "toggle_console"];
+
+	fn slot(&mut self, action: &str) -> Option<&mut KeyboardKey> {
+		Some(match action {
+			"end_turn" => &mut self.end_turn,
+			"cancel" => &mut self.cancel,
+			"wait" => &mut self.wait,
+			"toggle_log" => &mut self.toggle_log,
+			"toggle_objectives" => &mut self.toggle_objectives,
+			"cam_left" => &mut self.cam_left,
+			"cam_right" => &mut self.cam_right,
+			"cam_up" => &mut self.cam_up,
+			"cam_down" => &mut self.cam_down,
+			"screenshot" => &mut self.screenshot,
+			"toggle_hud" => &mut self.toggle_hud,
+			"toggle_ai_debug" => &mut self.toggle_ai_debug,
+			"toggle_danger" => &mut self.toggle_danger,
+			"toggle_console" => &mut self.toggle_console,
+			_ => return None
+		})
+	}
+
+	/// Returns the key bound to an action (one of [ACTIONS](KeyBindings::ACTIONS)), if it exists.
+	pub fn get(&self, action: &str) -> Option<KeyboardKey> {
+		let mut c = *self;
+		c.slot(action).copied()
+	}
+
+	/// Bind `key` to an action. Returns false if there is no such action.
+	pub fn bind(&mut self, action: &str, key: KeyboardKey) -> bool {
+		match self.slot(action) {
+			Some(k) => {
+				*k = key;
+				true
+			},
+			None => false
+		}
+	}
+}
+
+/// Result of a victory check, see `bind_victory_check` of [StateListener].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+	/// The game goes on.
+	Continue,
+	/// The player has won.
+	Victory,
+	/// The player has lost.
+	Defeat
+}
+
+impl Outcome {
+	/// Convert the value returned by a C victory check callback, i.e, 1 for victory, 2 for defeat and anything else to continue.
+	pub fn from_raw(v: i32) -> Outcome {
+		match v {
+			1 => Outcome::Victory,
+			2 => Outcome::Defeat,
+			_ => Outcome::Continue
+		}
+	}
+
+	/// Inverse of [from_raw](Outcome::from_raw), i.e, 0 to continue, 1 for victory and 2 for defeat.
+	pub fn to_raw(self) -> i32 {
+		match self {
+			Outcome::Continue => 0,
+			Outcome::Victory => 1,
+			Outcome::Defeat => 2
+		}
+	}
+}
+
+/// Chess-clock style time limits of the player turn, see [Display::set_turn_timer](crate::display::Display::set_turn_timer).
+/// The clock runs only while the player may give orders; it stops while orders are carried out, and while the game is paused.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TurnTimer {
+	/// Seconds allowed for each turn, or 0 for no limit.
+	pub per_turn: f32,
+	/// Seconds allowed for all turns of the battle together, or 0 for no limit.
+	pub bank: f32,
+	/// Seconds added to the bank at the start of each turn.
+	pub increment: f32,
+	/// Carry out the orders given to the selected unit when time runs out, rather than discard them.
+	pub auto_wait: bool,
+	turn_left: f32,
+	bank_left: f32,
+	running: bool,
+	fired: bool
+}
+
+impl TurnTimer {
+	/// Constructor method. Negative or non-finite limits are taken as 0.
+	pub fn new(per_turn: f32, bank: f32, increment: f32, auto_wait: bool) -> TurnTimer {
+		let secs = |s: f32| if s.is_finite() { s.max(0.0) } else { 0.0 };
+		TurnTimer {
+			per_turn: secs(per_turn),
+			bank: secs(bank),
+			increment: secs(increment),
+			auto_wait: auto_wait,
+			turn_left: secs(per_turn),
+			bank_left: secs(bank),
+			running: false,
+			fired: false
+		}
+	}
+
+	/// Returns true if either limit is set.
+	pub fn is_enabled(&self) -> bool {
+		self.per_turn > 0.0 || self.bank > 0.0
+	}
+
+	/// Seconds left in this turn, if limited.
+	pub fn turn_left(&self) -> Option<f32> {
+		(self.per_turn > 0.0).then_some(self.turn_left)
+	}
+
+	/// Seconds left in the bank, if limited.
+	pub fn bank_left(&self) -> Option<f32> {
+		(self.bank > 0.0).then_some(self.bank_left)
+	}
+
+	/// Run the clock for `dt` seconds of the player turn, starting the turn if needed.
+	/// Returns true once, on the frame time runs out. Once the bank is spent, every later turn runs out at once.
+	pub fn tick(&mut self, dt: f32) -> bool {
+		if !self.is_enabled() {
+			return false;
+		}
+		if !self.running {
+			self.running = true;
+			self.fired = false;
+			self.turn_left = self.per_turn;
+			if self.bank > 0.0 && self.bank_left > 0.0 {
+				self.bank_left += self.increment;
+			}
+		}
+		if self.fired {
+			return false;
+		}
+		self.turn_left = (self.turn_left - dt).max(0.0);
+		if self.bank > 0.0 {
+			self.bank_left = (self.bank_left - dt).max(0.0);
+		}
+		self.fired = (self.per_turn > 0.0 && self.turn_left <= 0.0) || (self.bank > 0.0 && self.bank_left <= 0.0);
+		self.fired
+	}
+
+	/// End the running turn; the next [tick](TurnTimer::tick) starts a new one.
+	pub fn end_turn(&mut self) {
+		self.running = false;
+	}
+
+	/// Returns true if the bank has been spent.
+	pub fn is_bank_spent(&self) -> bool {
+		self.bank > 0.0 && self.bank_left <= 0.0
+	}
+}
+
+/// Protection against accidental input, see [Display::set_input_guard](crate::display::Display::set_input_guard).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputGuard {
+	/// Seconds after an action during which the same action is ignored, e.g, the second click of a double click; 0 disables. Default is 0.25.
+	/// Guarded actions are clicks on the same tile, and the end turn, cancel and wait keys.
+	pub debounce: f32,
+	/// Seconds the intro and results screens are shown before a click dismisses them; 0 (the default) disables.
+	pub min_screen_time: f32
+}
+
+impl Default for InputGuard {
+	fn default() -> InputGuard {
+		InputGuard { debounce: 0.25, min_screen_time: 0.0 }
+	}
+}
+
+impl InputGuard {
+	/// Constructor method. Times that are negative or not finite are taken as 0.
+	pub fn new(debounce: f32, min_screen_time: f32) -> InputGuard {
+		let secs = |t: f32| if t.is_finite() { t.max(0.0) } else { 0.0 };
+		InputGuard { debounce: secs(debounce), min_screen_time: secs(min_screen_time) }
+	}
+}
+
+/// Gamepad rumble on feedback events, published as [Rumble](EngineEvent::Rumble) events, see [Display::set_rumble](crate::display::Display::set_rumble).
+/// Each is a strength (0 to 1) and a duration (in seconds); a strength or duration of 0 disables it, as by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rumble {
+	/// Rumble when a player unit takes a hit, scaled by the fraction of its maximum health lost, up to the full strength.
+	pub on_hit: (f32, f32),
+	/// Rumble when a unit is selected.
+	pub on_select: (f32, f32)
+}
+
+impl Rumble {
+	/// Constructor method. Strengths are clamped between 0 and 1; values that are negative or not finite are taken as 0.
+	pub fn new(on_hit: (f32, f32), on_select: (f32, f32)) -> Rumble {
+		let v = |t: f32| if t.is_finite() { t.max(0.0) } else { 0.0 };
+		let r = |(s, d): (f32, f32)| (v(s).min(1.0), v(d));
+		Rumble { on_hit: r(on_hit), on_select: r(on_select) }
+	}
+
+	/// Returns the event to publish for a rumble of `strength` (times the configured strength) and duration, if enabled.
+	fn event((s, d): (f32, f32), strength: f32) -> Option<EngineEvent> {
+		(s > 0.0 && d > 0.0).then(|| EngineEvent::Rumble { strength: s * strength.clamp(0.0, 1.0), duration: d })
+	}
+}
+
+/// Gamepads read for input.
+pub const MAX_GAMEPADS: i32 = 4;
+/// Gamepad button confirming, e.g, dismissing the intro; A on Xbox controllers.
+pub const CONFIRM_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN;
+/// Gamepad button skipping, e.g, the intro slideshow; B on Xbox controllers.
+pub const SKIP_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT;
+/// Gamepad button cancelling, as the cancel key does; B on Xbox controllers.
+pub const CANCEL_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT;
+/// Gamepad button making the selected unit wait; X on Xbox controllers.
+pub const WAIT_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT;
+/// Gamepad button toggling unit information; Y on Xbox controllers.
+pub const INFO_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP;
+/// Gamepad button ending the player turn; START on Xbox controllers.
+pub const END_TURN_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT;
+/// Gamepad button moving the cursor to the previous player unit yet to act; LB on Xbox controllers.
+pub const PREV_UNIT_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1;
+/// Gamepad button moving the cursor to the next player unit yet to act; RB on Xbox controllers.
+pub const NEXT_UNIT_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1;
+/// Seconds a direction is held before the gamepad cursor repeats its step, and between repeated steps.
+const CURSOR_DELAY: f32 = 0.3;
+const CURSOR_REPEAT: f32 = 0.1;
+/// Distance (in pixels) from the edges of the screen the camera keeps the gamepad cursor.
+const CURSOR_MARGIN: f32 = 64.0;
+/// Distance (in pixels) the mouse moves in a frame before it counts as used.
+const MOUSE_SLOP: f32 = 4.0;
+/// Tilt of a gamepad stick before it counts as used.
+const STICK_DEADZONE: f32 = 0.5;
+
+/// Input device the player last used, which HUD hints name the controls of, e.g, "Click to continue" or "Press A to continue". See [World::input_scheme](crate::world::World::input_scheme).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputScheme {
+	/// Keyboard and mouse.
+	KeyboardMouse = 0,
+	/// A gamepad.
+	Gamepad = 1,
+	/// A touch screen.
+	Touch = 2
+}
+
+impl InputScheme {
+	/// Returns the scheme with the specified raw value, if any.
+	pub fn from_u8(v: u8) -> Option<InputScheme> {
+		match v {
+			0 => Some(InputScheme::KeyboardMouse),
+			1 => Some(InputScheme::Gamepad),
+			2 => Some(InputScheme::Touch),
+			_ => None
+		}
+	}
+
+	/// Returns the scheme to switch to, from `self`, given the devices used in a frame.
+	/// A touch always switches to touch, since platforms move the mouse along with touches; otherwise the current scheme is kept if its device was used, or none was.
+	pub fn next(self, keyboard_mouse: bool, gamepad: bool, touch: bool) -> InputScheme {
+		if touch {
+			return InputScheme::Touch;
+		}
+		let used = |s: InputScheme| match s {
+			InputScheme::KeyboardMouse => keyboard_mouse,
+			InputScheme::Gamepad => gamepad,
+			InputScheme::Touch => false
+		};
+		[self, InputScheme::Gamepad, InputScheme::KeyboardMouse].iter().cloned().find(|s| used(*s)).unwrap_or(self)
+	}
+
+	/// Prompt to confirm, as in "Click to continue".
+	pub fn confirm_prompt(self) -> &'static str {
+		match self {
+			InputScheme::KeyboardMouse => "Click",
+			InputScheme::Gamepad => "Press A",
+			InputScheme::Touch => "Tap"
+		}
+	}
+
+	/// Prompt to skip, as in "ESC to skip"; None if the scheme has no control to skip.
+	pub fn skip_prompt(self) -> Option<&'static str> {
+		match self {
+			InputScheme::KeyboardMouse => Some("ESC"),
+			InputScheme::Gamepad => Some("B"),
+			InputScheme::Touch => None
+		}
+	}
+
+	/// Prompt to hold a control down, as in "Hold SPACE to speed up".
+	pub fn hold_prompt(self) -> &'static str {
+		match self {
+			InputScheme::KeyboardMouse => "Hold SPACE",
+			InputScheme::Gamepad => "Hold A",
+			InputScheme::Touch => "Hold"
+		}
+	}
+}
+
+/// Returns the controls available in a phase, as shown by the control hints bar (see [Display::set_control_hints](crate::display::Display::set_control_hints)), e.g, "LMB: select, RMB: info, ENTER: end turn".
+/// Keys are named as bound in `keys`, and left out for touch input; with a gamepad, its buttons are named instead. The text is empty if the player has no controls in the phase.
+pub fn control_hints(phase: TurnPhase, keys: &KeyBindings, scheme: InputScheme) -> String {
+	if scheme == InputScheme::Gamepad {
+		let hints: &[&str] = match phase {
+			TurnPhase::PlayerTurn => &["A: select", "Y: info", "LB/RB: next unit", "START: end turn"],
+			TurnPhase::MoveSelect => &["A: move", "Y: info", "B: cancel", "X: wait"],
+			TurnPhase::AttackSelect => &["A: attack", "Y: info", "B: cancel", "X: wait"],
+			TurnPhase::Intro => &["A: continue"],
+			_ => &[]
+		};
+		return hints.join(", ");
+	}
+	let touch = scheme == InputScheme::Touch;
+	let (click, info) = if touch { ("Tap", "Long-press") } else { ("LMB", "RMB") };
+	let mut hints: Vec<(&str, &str)> = match phase {
+		TurnPhase::PlayerTurn => vec![(click, "select"), (info, "info")],
+		TurnPhase::MoveSelect => vec![(click, "move"), (info, "info")],
+		TurnPhase::AttackSelect => vec![(click, "attack"), (info, "info")],
+		TurnPhase::Intro => vec![(click, "continue")],
+		_ => vec![]
+	};
+	let bound: &[(KeyboardKey, &str)] = match phase {
+		TurnPhase::PlayerTurn => &[(keys.end_turn, "end turn")],
+		TurnPhase::MoveSelect | TurnPhase::AttackSelect => &[(keys.cancel, "cancel"), (keys.wait, "wait")],
+		_ => &[]
+	};
+	if !touch {
+		hints.extend(bound.iter().filter_map(|(k, a)| key_name(*k).map(|n| (n, *a))));
+	}
+	hints.iter().map(|(c, a)| format!("{}: {}", c, a)).collect::<Vec<_>>().join(", ")
+}
+
+/// Returns true if the specified button was pressed this frame on any gamepad.
+pub fn gamepad_pressed(rl: &RaylibHandle, button: GamepadButton) -> bool {
+	(0..MAX_GAMEPADS).any(|g| rl.is_gamepad_available(g) && rl.is_gamepad_button_pressed(g, button))
+}
+
+/// Returns true if the specified button is held down on any gamepad.
+pub fn gamepad_down(rl: &RaylibHandle, button: GamepadButton) -> bool {
+	(0..MAX_GAMEPADS).any(|g| rl.is_gamepad_available(g) && rl.is_gamepad_button_down(g, button))
+}
+
+/// Actions protected by [InputGuard::debounce].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Guarded {
+	Click,
+	EndTurn,
+	Cancel,
+	Wait
+}
+
+impl Guarded {
+	const COUNT: usize = 4;
+}
+
 /// Plain struct to store state variables related to user input.
 #[derive(Debug)]
 pub(crate) struct InputHandler {
@@ -52,8 +588,13 @@ pub(crate) struct InputHandler {
 	movn: u8,
 	movn_i: u8,
 	range: u8,
+	/// Attacks the selected unit has left, and whether it may move after attacking, see [Actions](crate::world::Actions).
+	attacks: u8,
+	move_after_attack: bool,
 	last_tile: (i32, i32),
 	cur_upos: (i32, i32),
+	/// Layout of the tiles of the world of the selected unit, for distances.
+	grid: Grid,
 	uname: String,
 	/// 0 - player turn.
 	/// 1 - player - unit selected, select movement tile.
@@ -64,14 +605,67 @@ pub(crate) struct InputHandler {
 	/// 6 - player defeat.
 	/// 7 - intro.
 	state: u8,
-	ovec: Vec<Order>,
+	pub(crate) ovec: Vec<Order>,
 	frozen: HashSet<u8>,
+	/// Player units selected together, in order of selection; the first leads group moves.
+	group: Vec<u8>,
+	/// Screen position where a drag to select units in a box began.
+	drag_from: Option<Vector2>,
 	isplrsel: bool,
 	/// Flag to show or hide UI.
 	pub show: bool,
 	/// Flag to show or unit type information.
 	pub show_info: bool,
-	log_timer: f32
+	/// Flag to show or hide the battle log.
+	pub show_log: bool,
+	/// Flag to show or hide the objective tracker.
+	pub show_objectives: bool,
+	/// Flag to show or hide the AI debug overlay, see [AiPlan](crate::world::AiPlan).
+	pub show_ai_debug: bool,
+	/// Flag to show or hide the danger zone, see [influence](crate::world::influence).
+	pub show_danger: bool,
+	/// Recent battle events, oldest first.
+	pub log: VecDeque<String>,
+	/// Scroll offset (in pixels) of the unit information panel.
+	pub info_scroll: f32,
+	/// Scroll offset (in pixels) of the battle log panel.
+	pub log_scroll: f32,
+	/// Keys bound to player actions.
+	pub keys: KeyBindings,
+	/// Flag to end the player turn on the next frame, as if the key were pressed.
+	force_end_turn: bool,
+	log_timer: f32,
+	/// Time (in milliseconds) the built-in AI may spend deciding per frame, or 0 to decide at once.
+	pub(crate) ai_budget: f32,
+	/// Decisions of the built-in AI yet to be made this enemy turn.
+	ai_job: Option<AiJob>,
+	/// Time limits of the player turn.
+	pub(crate) timer: TurnTimer,
+	/// The last action confirmed, yet to be checked against the [puzzle](crate::puzzle).
+	action: Option<PuzzleStep>,
+	/// Popups being shown.
+	pub(crate) popups: Vec<Popup>,
+	/// Replay being recorded, if any.
+	pub(crate) replay: Option<Replay>,
+	/// Flag set once the orders of the phase being carried out have been recorded.
+	recorded: bool,
+	/// Protection against accidental input.
+	pub(crate) guard: InputGuard,
+	/// Seconds since each [Guarded] action was last accepted, and the tile last clicked.
+	since: [f32; Guarded::COUNT],
+	last_click: Option<(i32, i32)>,
+	/// The state last seen, and for how many seconds it has been shown.
+	shown: (u8, f32),
+	/// Recognizer of touch gestures, if touch input is enabled, see [touch](crate::touch).
+	pub(crate) touch: Option<TouchTracker>,
+	/// Gamepad rumble on feedback events.
+	pub(crate) rumble: Rumble,
+	/// Mouse position on the last frame, to tell when the mouse is used.
+	last_mouse: Vector2,
+	/// Tile of the virtual cursor moved with a gamepad, which the confirm button clicks.
+	pub(crate) cursor: (i32, i32),
+	/// Seconds until the cursor steps again while a direction is held, or None if none is.
+	cursor_repeat: Option<f32>
 }
 
 impl InputHandler {
@@ -82,124 +676,630 @@ impl InputHandler {
 			movn: 0,
 			movn_i: 0,
 			range: 0,
+			attacks: 0,
+			move_after_attack: false,
 			uname: "".to_string(),
 			last_tile: (0, 0),
 			cur_upos: (0,0),
+			grid: Grid::Isometric,
 			state: 7,
 			ovec: vec![],
 			frozen: HashSet::new(),
+			group: Vec::new(),
+			drag_from: None,
 			isplrsel: false,
 			show: false,
 			show_info: false,
-			log_timer: 0.0
+			show_log: false,
+			show_objectives: true,
+			show_ai_debug: false,
+			show_danger: false,
+			log: VecDeque::new(),
+			info_scroll: 0.0,
+			log_scroll: 0.0,
+			keys: KeyBindings::default(),
+			force_end_turn: false,
+			ai_budget: 0.0,
+			ai_job: None,
+			timer: TurnTimer::default(),
+			action: None,
+			popups: vec![],
+			replay: None,
+			recorded: false,
+			log_timer: 0.0,
+			guard: InputGuard::default(),
+			since: [f32::INFINITY; Guarded::COUNT],
+			last_click: None,
+			shown: (7, 0.0),
+			touch: None,
+			rumble: Rumble::default(),
+			last_mouse: Vector2::zero(),
+			cursor: (0, 0),
+			cursor_repeat: None
 		}
 	}
 
-	/// Method invoked during game loop to handle key and mouse inputs.
-	pub fn handle(&mut self, w: &mut World, rl: &RaylibHandle, sl: &mut StateListener, rlau: &mut RaylibAudio, rs: &mut ResourceSet) {
-		if self.state == 2 || self.state == 3 {
-			self.log_timer += rl.get_frame_time();
-			let mut next_state = None;
-			self.ovec.retain(|o| {
-				if o.is_valid(w) {
-					crate::world::order_pending(o,w, &mut next_state)
-				} else {
-					false
-				}
-			});
-			let delta = rl.get_frame_time();
-
-			let mut torem = Vec::new();
-			for (_id, u) in &mut w.units {
-				u.update(&w.unit_types, delta);
-				if u.health <= 0.0 {
-					torem.push(*_id);
+	/// Returns true while orders are being carried out, i.e, during the player's moves or the enemy turn.
+	pub(crate) fn is_simulating(&self) -> bool {
+		self.state == 2 || self.state == 3
+	}
+
+	/// Returns true while the built-in AI is deciding the orders of the enemy turn, which are carried out once it is done.
+	pub(crate) fn is_thinking(&self) -> bool {
+		self.ai_job.is_some()
+	}
+
+	/// Let the built-in AI decide for units until the time budget of this frame is spent, or it is done.
+	pub(crate) fn think(&mut self, w: &mut World) {
+		if let Some(job) = &mut self.ai_job {
+			let start = Instant::now();
+			let budget = Duration::from_secs_f32(self.ai_budget.max(0.0) / 1000.0);
+			while job.step(w, &mut self.ovec) {
+				if self.ai_budget > 0.0 && start.elapsed() >= budget {
+					return;
 				}
 			}
-			for e in torem {let _ = &mut w.units.remove(&e);}
+			self.ai_job = None;
+		}
+	}
 
-			let mut torem = Vec::new();
-			for (i, p) in (&mut w.projectiles).iter_mut().enumerate() {
-				p.update(delta);
-				if p.reached {
-					torem.push(i)
+	/// Carry out pending orders and advance units and projectiles by `delta` seconds.
+	pub(crate) fn simulate(&mut self, w: &mut World, sl: &mut StateListener, delta: f32) {
+		crate::world::voice::tick(w, delta);
+		if self.is_thinking() {
+			return;
+		}
+		if let (Some(a), Some(p)) = (self.action.take(), &mut w.puzzle) {
+			p.act(a);
+		}
+		if let (Some(r), false) = (&mut self.replay, self.recorded) {
+			r.record(w, TurnPhase::from_state(self.state), &self.ovec);
+			self.recorded = true;
+		}
+		self.log_timer += delta;
+		let mut next_state = None;
+		let mut moved = Vec::new();
+		// Units standing with orders to move bark as they set off.
+		let mut standing: Vec<u8> = self.ovec.iter().filter_map(|o| match o {
+			Order::MOVE(u, ..) if !crate::world::is_unit_walking(w, *u) => Some(*u),
+			_ => None
+		}).collect();
+		standing.sort_unstable();
+		standing.dedup();
+		self.ovec.retain(|o| {
+			if o.is_valid(w) {
+				let pending = crate::world::order_pending(o,w, &mut next_state);
+				if let (false, Order::MOVE(u, ..)) = (pending, o) {
+					moved.push(*u);
+				}
+				pending
+			} else {
+				false
+			}
+		});
+		for u in standing {
+			if crate::world::is_unit_walking(w, u) {
+				crate::world::voice::bark(w, u, BarkKind::Move);
+			}
+		}
+		// Units collect pickups only where they end their move, not on tiles passed along the way.
+		for u in moved {
+			if !self.ovec.iter().any(|o| matches!(o, Order::MOVE(v, ..) if *v == u)) {
+				if let Some(t) = crate::world::unit_tile(w, u) {
+					sl.notify_move_complete(w, u, t);
 				}
+				crate::world::pickup::collect(w, u);
 			}
-			for e in torem {w.projectiles.remove(e);};
+		}
+		for ev in std::mem::take(&mut w.events) {
+			w.record_stats(&ev);
+			self.log_event(w, &ev);
+			match ev {
+				BattleEvent::Attack(a, d, dmg) => {
+					let text = w.hp_rounding().apply(dmg).to_string();
+					w.show_popup(d, &text, PopupKind::Damage);
+					// Hits taken by player units rumble harder the more health they cost.
+					let hit = if crate::world::is_unit_player_controlled(w, d) {
+						let mh = crate::world::_unit_health(w, d).1;
+						Rumble::event(self.rumble.on_hit, if mh > 0.0 { dmg / mh } else { 1.0 })
+					} else {
+						None
+					};
+					crate::world::voice::bark(w, a, BarkKind::Attack);
+					sl.notify_attack(w, a, d, dmg);
+					if let Some(e) = hit {
+						sl.publish(w, e);
+					}
+				},
+				BattleEvent::Pickup(u, e) => sl.notify_pickup(w, u, e),
+				_ => {}
+			}
+		}
+		// Popups are placed before units slain are removed.
+		self.take_popups(w);
+		let mut torem = Vec::new();
+		for (_id, u) in &mut w.units {
+			u.update(&w.unit_types, delta);
+			if u.health <= 0.0 {
+				torem.push(*_id);
+			}
+		}
+		for e in torem {
+			let tid = crate::world::get_type_id(w, e);
+			w.record_stats(&BattleEvent::Kill(e, tid));
+			crate::world::voice::bark(w, e, BarkKind::Death);
+			if w.units[&e].player {
+				w.play_stinger(StingerKind::UnitLost);
+			}
+			let _ = &mut w.units.remove(&e);
+			self.log_event(w, &BattleEvent::Kill(e, tid));
+			sl.notify_unit_death(w, e, tid);
+		}
+
+		let mut torem = Vec::new();
+		for (i, p) in (&mut w.projectiles).iter_mut().enumerate() {
+			p.update(delta);
+			if p.reached {
+				torem.push(i)
+			}
+		}
+		for e in torem {w.projectiles.remove(e);};
 
-			if self.ovec.len() == 0 && w.projectiles.len() == 0 {
-				self.state = 0;
+		if self.ovec.len() == 0 && w.projectiles.len() == 0 {
+			if self.state == 3 {
+				w.turn += 1;
 			}
-			if let Some(i) = next_state {
-				self.state = i;
+			self.state = 0;
+		}
+		if let Some(i) = next_state {
+			self.state = i;
+		}
+		if self.state == 0 {
+			let mut o = sl.check_victory(w);
+			if let Some(p) = &mut w.puzzle {
+				match p.settle(o == Outcome::Victory) {
+					ObjectiveState::Completed => o = Outcome::Victory,
+					ObjectiveState::Failed => o = Outcome::Defeat,
+					ObjectiveState::Pending => {}
+				}
 			}
-			if DEBUG_ORDERS && self.log_timer > 60.0 {
-				println!("Following orders are active {:?}", self.ovec);
-				self.log_timer = 0.0;
-				self.state = 0;
+			#[cfg(feature = "debug_tools")]
+			if let Some(f) = crate::debug_tools::take_outcome(w) {
+				o = f;
 			}
+			self.conclude(o);
+		}
+		if !self.is_simulating() {
+			self.recorded = false;
+		}
+		if DEBUG_ORDERS && self.log_timer > 60.0 {
+			println!("Following orders are active {:?}", self.ovec);
+			self.log_timer = 0.0;
+			self.state = 0;
+		}
+	}
+
+	/// Show the results screen if the game has been won or lost.
+	fn conclude(&mut self, o: Outcome) {
+		match o {
+			Outcome::Victory => self.state = 5,
+			Outcome::Defeat => self.state = 6,
+			Outcome::Continue => {}
+		}
+	}
+
+	/// Advance popups by `dt` seconds, removing the ones that have faded, and show those requested since.
+	pub(crate) fn update_popups(&mut self, w: &mut World, dt: f32) {
+		self.popups.retain_mut(|p| !p.t.update(dt));
+		self.take_popups(w);
+	}
+
+	/// Show the popups requested on the world, see [World::show_popup].
+	fn take_popups(&mut self, w: &mut World) {
+		for (uid, text, kind) in std::mem::take(&mut w.presentation.popups) {
+			if let Some(u) = w.units.get(&uid) {
+				let tile = (u.wpos.x.round() as i32, u.wpos.y.round() as i32);
+				self.popups.push(Popup { tile: tile, text: text, kind: kind, t: Tween::new(0.0, 1.0, POPUP_TIME, Easing::CubicOut) });
+			}
+		}
+	}
+
+	/// Advance the clocks of the [InputGuard] by `dt` seconds.
+	pub(crate) fn tick_guard(&mut self, dt: f32) {
+		for t in self.since.iter_mut() {
+			*t += dt;
+		}
+		if self.shown.0 == self.state {
+			self.shown.1 += dt;
+		} else {
+			self.shown = (self.state, 0.0);
+		}
+	}
+
+	/// Returns true if action `a` is accepted, i.e, it was not taken within the debounce time; clicks, on `tile`, are only ignored if on the same tile.
+	pub(crate) fn accept(&mut self, a: Guarded, tile: Option<(i32, i32)>) -> bool {
+		let i = a as usize;
+		if self.since[i] < self.guard.debounce && (tile.is_none() || tile == self.last_click) {
+			return false;
+		}
+		self.since[i] = 0.0;
+		if tile.is_some() {
+			self.last_click = tile;
+		}
+		true
+	}
+
+	/// Returns true if the intro or results screen has been shown long enough to be dismissed, see [InputGuard::min_screen_time].
+	pub(crate) fn can_dismiss(&self) -> bool {
+		self.shown.0 == self.state && self.shown.1 >= self.guard.min_screen_time
+	}
+
+	/// Method invoked during game loop to handle key and mouse inputs.
+	pub fn handle(&mut self, w: &mut World, rl: &RaylibHandle, sl: &mut StateListener, rlau: &mut RaylibAudio, rs: &mut ResourceSet) {
+		self.handle_input(w, rl, sl, rlau, rs);
+		self.sync_acted(w);
+	}
+
+	fn handle_input(&mut self, w: &mut World, rl: &RaylibHandle, sl: &mut StateListener, rlau: &mut RaylibAudio, rs: &mut ResourceSet) {
+		let gesture = self.touch_gesture(w, rl);
+		if self.is_simulating() {
+			self.think(w);
+			self.simulate(w, sl, rl.get_frame_time());
+			return;
+		}
+		#[cfg(feature = "debug_tools")]
+		if matches!(self.state, 0 | 1 | 4) {
+			if let Some(o) = crate::debug_tools::take_outcome(w) {
+				self.ovec.clear();
+				self.group.clear();
+				self.conclude(o);
+				return;
+			}
+		}
+		if matches!(self.state, 0 | 1 | 4) && self.timer.tick(rl.get_frame_time()) {
+			self.time_out(w, sl);
+			return;
+		}
+		if self.force_end_turn && self.state == 0 {
+			self.end_turn(w, sl, rlau, rs);
 			return;
 		}
-		if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
-			eprintln!("Click at {:?}", rl.get_mouse_position());
+		// A gamepad moves a virtual cursor, and its confirm button clicks the tile under it.
+		let pad = matches!(self.state, 0 | 1 | 4);
+		if pad {
+			self.gamepad_cursor(w, rl);
+		}
+		let pad_confirm = pad && gamepad_pressed(rl, CONFIRM_BUTTON);
+		// With touch input, a tap is a click.
+		let touch = self.touch.is_some();
+		let mpos = if pad_confirm {
+			let (x, y) = crate::world::tile_to_screen(w, self.cursor.0, self.cursor.1);
+			Vector2::new(x, y)
+		} else {
+			match gesture { Some(Gesture::Tap(p)) => p, _ => rl.get_mouse_position() }
+		};
+		let mut pressed = if pad_confirm { true } else if touch { matches!(gesture, Some(Gesture::Tap(_))) } else { rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) };
+		// A tutorial accepts clicks only where its step allows, and a step without a goal takes the click to move on.
+		let tutorial = if matches!(self.state, 0 | 1 | 4) { w.tutorial.as_deref().filter(|t| !t.is_done()) } else { None };
+		if let (Some(t), true) = (tutorial, pressed) {
+			let tile = crate::world::tile_at(w, mpos.x, mpos.y);
+			let goal = t.current().map(|s| s.goal);
+			pressed = goal != Some(TutorialGoal::Continue) && t.allows_tile(tile, crate::world::unit_at(w, tile.0, tile.1));
+			if goal == Some(TutorialGoal::Continue) && self.accept(Guarded::Click, None) {
+				w.tutorial_act(TutorialGoal::Continue);
+			}
+		}
+		// Units cannot be selected together in a puzzle, since each action is checked on its own, nor in a tutorial unless allowed.
+		let grouping = w.puzzle.is_none() && w.tutorial.iter().all(|t| t.allows_grouping());
+		let shift = grouping && (rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT));
+		let clicked = pressed && self.accept(Guarded::Click, Some(crate::world::tile_at(w, mpos.x, mpos.y)));
+		let given = self.ovec.len();
+		if clicked {
+			eprintln!("Click at {:?}", mpos);
 			if self.state == 0 {
-				self.select_unit(w, rl.get_mouse_position());
-				if self.state == 1{
-					rlau.play_sound(rs.get_sound(0xff));
+				// Dragging pans the camera with touch input, rather than selecting units in a box.
+				if !touch && !pad_confirm {
+					self.drag_from = Some(mpos);
+				}
+				let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
+				if shift {
+					self.toggle_group(w, mpos);
+				} else if !self.group.is_empty() && crate::world::unit_at(w, tx, ty).is_none() {
+					self.move_group(w, mpos);
+				} else {
+					self.group.clear();
+					self.select_unit(w, mpos);
+					if self.state == 1 {
+						w.tutorial_act(TutorialGoal::Select(self.cur_id));
+					}
+					if self.state == 1{
+						rlau.play_sound(rs.get_sound(0xff));
+						crate::world::voice::bark(w, self.cur_id, BarkKind::Select);
+						if let Some(e) = Rumble::event(self.rumble.on_select, 1.0) {
+							sl.publish(w, e);
+						}
+					}
 				}
  			} else if self.state == 1 {
- 				self.select_move_tile(w, rl.get_mouse_position());
+ 				self.select_move_tile(w, mpos);
  			} else if self.state == 4 {
- 				self.select_attack_tile(w, rl.get_mouse_position());
- 			} else if self.state == 7 {
+ 				self.select_attack_tile(w, mpos);
+ 			} else if self.state == 7 && self.can_dismiss() {
  				self.state = 0;
  				return;
  			}
 		}
-		if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+		if clicked {
+			self.tutorial_orders(w, given);
+		}
+		if self.state == 7 && self.can_dismiss() && gamepad_pressed(rl, CONFIRM_BUTTON) && self.accept(Guarded::Click, None) {
+			self.state = 0;
+			return;
+		}
+		if rl.is_mouse_button_released(MouseButton::MOUSE_LEFT_BUTTON) {
+			if let Some(from) = self.drag_from.take() {
+				if grouping && self.state <= 1 && from.distance_to(mpos) >= DRAG_THRESHOLD {
+					self.select_box(w, from, mpos, shift);
+				}
+			}
+		}
+		let info = rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) || matches!(gesture, Some(Gesture::LongPress(_))) || gamepad_pressed(rl, INFO_BUTTON);
+		if info && w.tutorial_allows(TutorialControl::Info) {
 			self.show_info = !self.show_info;
+			self.info_scroll = 0.0;
 		}
-		if rl.is_key_pressed(KeyboardKey::KEY_E) {
-			if self.ovec.len() != 0 {
-				self.frozen.remove(&self.cur_id);
-				self.ovec.clear();
+		if rl.is_key_pressed(self.keys.toggle_log) {
+			self.show_log = !self.show_log;
+		}
+		if rl.is_key_pressed(self.keys.toggle_objectives) {
+			self.show_objectives = !self.show_objectives;
+		}
+		if rl.is_key_pressed(self.keys.toggle_ai_debug) {
+			self.show_ai_debug = !self.show_ai_debug;
+		}
+		if rl.is_key_pressed(self.keys.toggle_danger) {
+			self.show_danger = !self.show_danger;
+		}
+		// Orders cannot be taken back in a puzzle.
+		if (rl.is_key_pressed(self.keys.cancel) || gamepad_pressed(rl, CANCEL_BUTTON)) && !(w.puzzle.is_some() && self.ovec.len() != 0) && w.tutorial_allows(TutorialControl::Cancel) && self.accept(Guarded::Cancel, None) {
+			self.cancel(w);
+		}
+		if (rl.is_key_pressed(self.keys.wait) || gamepad_pressed(rl, WAIT_BUTTON)) && w.tutorial_allows(TutorialControl::Wait) && self.accept(Guarded::Wait, None) {
+			self.wait(w);
+		}
+		if (rl.is_key_pressed(self.keys.end_turn) || gamepad_pressed(rl, END_TURN_BUTTON)) && self.state == 0 && w.tutorial_allows(TutorialControl::EndTurn) && self.accept(Guarded::EndTurn, None) {
+			self.end_turn(w, sl, rlau, rs);
+		}
+	}
+
+	/// Move the gamepad cursor with the left stick or the d-pad, stepping again while a direction is held, or to another unit yet to act with the shoulder buttons, and keep it on screen.
+	fn gamepad_cursor(&mut self, w: &mut World, rl: &RaylibHandle) {
+		let dt = rl.get_frame_time();
+		let mut dir = Vector2::zero();
+		for g in (0..MAX_GAMEPADS).filter(|g| rl.is_gamepad_available(*g)) {
+			dir.x += rl.get_gamepad_axis_movement(g, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
+			dir.y += rl.get_gamepad_axis_movement(g, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
+		}
+		let dpad = [
+			(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT, -1.0, 0.0), (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT, 1.0, 0.0),
+			(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP, 0.0, -1.0), (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN, 0.0, 1.0)
+		];
+		for (b, x, y) in dpad.iter().cloned() {
+			if gamepad_down(rl, b) {
+				dir.x += x;
+				dir.y += y;
 			}
-			self.reset();
 		}
-		if rl.is_key_pressed(KeyboardKey::KEY_ENTER) && self.state == 0 {
-			self.state = 3;
-			self.frozen.clear();
-			self.ovec.clear();
-			self.log_timer = 0.0;
-			// To check.
-			let m = w.bgm_id;
-			sl.notify_turn(w, &mut self.ovec);
-			// Switch music.
-			if w.bgm_id != m {
-				if let Some(a) = rs.get_music(m) {
-					rlau.stop_music_stream(a);
-				}
-				if let Some(a) = rs.get_music(w.bgm_id){
-					rlau.play_music_stream(a);
-				}
+		let moved = match self.cursor_repeat {
+			_ if dir.length() < STICK_DEADZONE => {
+				self.cursor_repeat = None;
+				false
+			},
+			Some(t) if t > dt => {
+				self.cursor_repeat = Some(t - dt);
+				false
+			},
+			held => {
+				self.cursor_repeat = Some(if held.is_some() { CURSOR_REPEAT } else { CURSOR_DELAY });
+				self.move_cursor(w, dir.x, dir.y)
 			}
-			for (i, u) in &w.units {
-				if let crate::world::TileType::Heal = crate::world::tile_type_at(&w, u.wpos.x as i32, u.wpos.y as i32)  {
-					self.ovec.push(Order::MutHealthR(*i, 0.25));
-				}
-				if let crate::world::TileType::Damage = crate::world::tile_type_at(&w, u.wpos.x as i32, u.wpos.y as i32)  {
-					self.ovec.push(Order::MutHealthR(*i, -0.35));
-				}
+		};
+		let cycled = self.state == 0 && ((gamepad_pressed(rl, NEXT_UNIT_BUTTON) && self.cycle_unit(w, true).is_some())
+			|| (gamepad_pressed(rl, PREV_UNIT_BUTTON) && self.cycle_unit(w, false).is_some()));
+		if moved || cycled {
+			self.show_cursor(w, rl.get_screen_width() as f32, rl.get_screen_height() as f32, cycled);
+		}
+	}
+
+	/// Step the gamepad cursor to the neighbouring tile drawn closest to the direction (dx, dy) on screen, staying on the map. Returns true if it moved.
+	pub(crate) fn move_cursor(&mut self, w: &World, dx: f32, dy: f32) -> bool {
+		let (mw, mh) = w.map_size();
+		let (cx, cy) = self.cursor;
+		let (sx, sy) = crate::world::tile_to_screen(w, cx, cy);
+		let len = (dx * dx + dy * dy).sqrt();
+		// Neighbours more than 60 degrees off the direction are never taken.
+		let mut best = (None, 0.5);
+		for (ox, oy) in (-1..=1).flat_map(|x| (-1..=1).map(move |y| (x, y))) {
+			let (tx, ty) = (cx + ox, cy + oy);
+			if (ox, oy) == (0, 0) || tx < 0 || ty < 0 || tx >= mw as i32 || ty >= mh as i32 {
+				continue;
+			}
+			let (nx, ny) = crate::world::tile_to_screen(w, tx, ty);
+			let (ex, ey) = (nx - sx, ny - sy);
+			let cos = (ex * dx + ey * dy) / (len * (ex * ex + ey * ey).sqrt());
+			if cos > best.1 {
+				best = (Some((tx, ty)), cos);
+			}
+		}
+		match best.0 {
+			Some(t) => {
+				self.cursor = t;
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Move the gamepad cursor to the player unit yet to act with the next id after the unit under it, or the previous id if not `forward`, wrapping around.
+	/// Returns the id of the unit, or None if every player unit has acted.
+	pub(crate) fn cycle_unit(&mut self, w: &World, forward: bool) -> Option<u8> {
+		let mut ids: Vec<u8> = w.units.iter().filter(|(id, u)| u.player && !self.frozen.contains(id)).map(|(id, _)| *id).collect();
+		ids.sort();
+		if !forward {
+			ids.reverse();
+		}
+		let cur = crate::world::unit_at(w, self.cursor.0, self.cursor.1);
+		let next = ids.iter().cloned().find(|id| cur.iter().all(|c| if forward { id > c } else { id < c })).or_else(|| ids.first().cloned())?;
+		self.cursor = crate::world::unit_tile(w, next)?;
+		Some(next)
+	}
+
+	/// Pan the camera so that the gamepad cursor is on a screen of specified size (in pixels), at least [CURSOR_MARGIN] from its edges, or at its centre if `centre`.
+	fn show_cursor(&self, w: &mut World, width: f32, height: f32, centre: bool) {
+		let (x, y) = crate::world::tile_to_screen(w, self.cursor.0, self.cursor.1);
+		let fit = |p: f32, size: f32| if centre { p - 0.5 * size } else { (p - CURSOR_MARGIN).min(0.0) + (p + CURSOR_MARGIN - size).max(0.0) };
+		let (dx, dy) = (fit(x + w.coff.0, width), fit(y + w.coff.1, height));
+		if dx != 0.0 || dy != 0.0 {
+			w.pan_screen(dx, dy);
+		}
+	}
+
+	/// Switch the [input scheme](InputScheme) of the world to the device used this frame, i.e, a key, mouse button, wheel or movement, gamepad button or stick, or touch.
+	/// Returns the new scheme, if it changed.
+	pub(crate) fn detect_scheme(&mut self, w: &mut World, rl: &RaylibHandle) -> Option<InputScheme> {
+		let m = rl.get_mouse_position();
+		let moved = m.distance_to(self.last_mouse) >= MOUSE_SLOP;
+		self.last_mouse = m;
+		let buttons = [MouseButton::MOUSE_LEFT_BUTTON, MouseButton::MOUSE_RIGHT_BUTTON, MouseButton::MOUSE_MIDDLE_BUTTON];
+		let km = moved || rl.get_mouse_wheel_move() != 0.0 || buttons.iter().any(|b| rl.is_mouse_button_pressed(*b)) || KEY_NAMES.iter().any(|(_, k)| rl.is_key_pressed(*k));
+		// Triggers are left out, as some platforms report them at -1 when released.
+		let sticks = [GamepadAxis::GAMEPAD_AXIS_LEFT_X, GamepadAxis::GAMEPAD_AXIS_LEFT_Y, GamepadAxis::GAMEPAD_AXIS_RIGHT_X, GamepadAxis::GAMEPAD_AXIS_RIGHT_Y];
+		let pad = (0..MAX_GAMEPADS).any(|g| rl.is_gamepad_available(g) && (
+			(1..=17).any(|b| unsafe { raylib::ffi::IsGamepadButtonPressed(g, b) }) || sticks.iter().any(|a| rl.get_gamepad_axis_movement(g, *a).abs() >= STICK_DEADZONE)
+		));
+		let s = w.input_scheme().next(km, pad, rl.get_touch_points_count() > 0);
+		if s == w.input_scheme() {
+			return None;
+		}
+		w.set_input_scheme(s);
+		Some(s)
+	}
+
+	/// Recognize a touch gesture from the points touching the screen, if touch input is enabled; drags pan and pinches zoom the camera at once.
+	fn touch_gesture(&mut self, w: &mut World, rl: &RaylibHandle) -> Option<Gesture> {
+		let t = self.touch.as_mut()?;
+		let n = rl.get_touch_points_count();
+		let points: Vec<Vector2> = if n > 0 {
+			(0..n.min(2)).map(|i| rl.get_touch_position(i)).collect()
+		} else if rl.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+			// Platforms that do not report touch points pass a touch on as the mouse.
+			vec![rl.get_mouse_position()]
+		} else {
+			vec![]
+		};
+		let g = t.update(rl.get_frame_time(), &points)?;
+		match g {
+			Gesture::Pan(d) => w.pan_screen(-d.x, -d.y),
+			Gesture::Pinch { factor, centre } => w.zoom_at(w.zoom() * factor, centre.x, centre.y),
+			_ => {}
+		}
+		Some(g)
+	}
+
+	/// End the player turn, and let the listener and built-in AI give the orders of the enemy turn.
+	fn end_turn(&mut self, w: &mut World, sl: &mut StateListener, rlau: &mut RaylibAudio, rs: &mut ResourceSet) {
+		self.force_end_turn = false;
+		self.timer.end_turn();
+		w.tutorial_act(TutorialGoal::EndTurn);
+		self.state = 3;
+		self.frozen.clear();
+		self.sync_acted(w);
+		self.group.clear();
+		self.ovec.clear();
+		self.log_timer = 0.0;
+		// To check.
+		let m = w.bgm_id;
+		sl.notify_turn(w, &mut self.ovec);
+		if self.ai_budget > 0.0 {
+			self.ai_job = Some(AiJob::new(w, &self.ovec));
+		} else {
+			crate::world::behavior_orders(w, &mut self.ovec);
+		}
+		// Switch music.
+		if w.bgm_id != m {
+			if let Some(a) = rs.get_music(m) {
+				rlau.stop_music_stream(a);
+			}
+			if let Some(a) = rs.get_music(w.bgm_id){
+				rlau.play_music_stream(a);
+			}
+		}
+		for (i, u) in &w.units {
+			if let crate::world::TileType::Heal = crate::world::tile_type_at(&w, u.wpos.x as i32, u.wpos.y as i32)  {
+				self.ovec.push(Order::MutHealthR(*i, 0.25));
+			}
+			if let crate::world::TileType::Damage = crate::world::tile_type_at(&w, u.wpos.x as i32, u.wpos.y as i32)  {
+				self.ovec.push(Order::MutHealthR(*i, -0.35));
 			}
 		}
 	}
 
+	/// End the player turn once its time has run out, carrying out the orders of the selected unit if the timer auto-waits, or discarding them.
+	fn time_out(&mut self, w: &mut World, sl: &mut StateListener) {
+		sl.notify_timeout(w, self.timer.is_bank_spent());
+		if self.timer.auto_wait && (self.state == 1 || self.state == 4) && self.isplrsel && !self.frozen.contains(&self.cur_id) {
+			self.confirm_move();
+		} else {
+			self.ovec.clear();
+			self.reset();
+		}
+		self.group.clear();
+		self.drag_from = None;
+		self.force_end_turn = true;
+	}
+
+	/// Carry out `orders` as the moves of player units, or as the enemy turn.
+	pub(crate) fn carry_out(&mut self, orders: Vec<Order>, enemy: bool) {
+		self.ovec = orders;
+		self.state = if enemy { 3 } else { 2 };
+	}
+
+	/// End the player turn on the next frame, as if the key were pressed.
+	pub(crate) fn request_end_turn(&mut self) {
+		self.force_end_turn = true;
+	}
+
+	/// Append a line describing `ev` to the battle log, discarding the oldest line if full.
+	pub(crate) fn log_event(&mut self, w: &World, ev: &BattleEvent) {
+		if self.log.len() == LOG_CAPACITY {
+			self.log.pop_front();
+		}
+		self.log.push_back(crate::world::describe_event(w, ev));
+	}
+
+	/// Take back the orders given to the selected unit, selecting it again as it stands in the world; or, if it has none, deselect the unit (or group).
+	pub(crate) fn cancel(&mut self, w: &World) {
+		let taken_back = !self.ovec.is_empty();
+		self.ovec.clear();
+		if taken_back && (self.state == 1 || self.state == 4) && w.units.contains_key(&self.cur_id) {
+			self.select(w, self.cur_id);
+			return;
+		}
+		self.group.clear();
+		self.reset();
+	}
+
 	fn reset(&mut self) {
 		self.state = 0;
 		self.show = false;
 		self.cur_id = 0;
 		self.movn = 0;
+		self.movn_i = 0;
 		self.range = 0;
+		self.attacks = 0;
+		self.move_after_attack = false;
+		self.last_tile = (0, 0);
+		self.cur_upos = (0, 0);
 		self.uname = String::from("");
 		self.isplrsel = false;
 		println!("Following orders were issued {:?}", self.ovec);
@@ -207,42 +1307,172 @@ impl InputHandler {
 
 	fn select_unit(&mut self, w: &World, mpos: Vector2) {
 		let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
-		for (id, u) in &w.units {
-			if (u.wpos.x as i32) == tx && (u.wpos.y as i32) == ty {
-				self.last_tile = (tx, ty);
-				self.cur_id = *id;
-				eprintln!("Selected unit id={}, at {:?}", self.cur_id, self.last_tile);
-				let t = crate::world::_guinfo(w, u);
-				self.movn = t.0;
-				self.movn_i = t.0;
-				self.range = t.1;
-				self.uname = t.2;
-				self.isplrsel = t.3;
-				self.show = true;
-				self.cur_upos = (tx, ty);
-				self.state = 1;
-				break;
+		// Enemy units hidden by fog of war cannot be selected.
+		if let Some(id) = crate::world::unit_at(w, tx, ty).filter(|id| crate::world::is_unit_visible(w, *id)) {
+			self.select(w, id);
+		}
+	}
+
+	/// Select the unit with specified id, which must exist, to give it orders.
+	pub(crate) fn select(&mut self, w: &World, id: u8) {
+		let u = &w.units[&id];
+		let tile = (u.wpos.x as i32, u.wpos.y as i32);
+		self.last_tile = tile;
+		self.cur_id = id;
+		eprintln!("Selected unit id={}, at {:?}", self.cur_id, self.last_tile);
+		let t = crate::world::_guinfo(w, u);
+		self.movn = t.0;
+		self.movn_i = t.0;
+		self.range = t.1;
+		let a = crate::world::unit_actions(w, id).unwrap_or_default();
+		self.attacks = a.attacks;
+		self.move_after_attack = a.move_after_attack;
+		self.uname = t.2;
+		self.isplrsel = t.3;
+		self.show = true;
+		self.info_scroll = 0.0;
+		self.cur_upos = tile;
+		self.cursor = tile;
+		self.grid = w.grid();
+		self.state = 1;
+	}
+
+	/// Add the player unit at `mpos` to the group, or remove it if already grouped. Units which have acted this turn cannot be grouped.
+	fn toggle_group(&mut self, w: &World, mpos: Vector2) {
+		let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
+		let id = match crate::world::unit_at(w, tx, ty) { Some(id) => id, None => return };
+		if !w.units[&id].player || self.frozen.contains(&id) {
+			return;
+		}
+		match self.group.iter().position(|g| *g == id) {
+			Some(i) => { self.group.remove(i); },
+			None => self.group.push(id)
+		}
+		self.reset();
+	}
+
+	/// Select the player units (that have not acted this turn) drawn inside the box with corners `a` and `b`, adding them to the group if `add`.
+	fn select_box(&mut self, w: &World, a: Vector2, b: Vector2, add: bool) {
+		let (x0, x1) = (a.x.min(b.x), a.x.max(b.x));
+		let (y0, y1) = (a.y.min(b.y), a.y.max(b.y));
+		if !add {
+			self.group.clear();
+		}
+		let mut ids: Vec<u8> = w.units.iter().filter(|(id, u)| u.player && !self.frozen.contains(id)).map(|(id, _)| *id).collect();
+		ids.sort();
+		for id in ids {
+			let (tx, ty) = crate::world::unit_tile(w, id).unwrap();
+			let (sx, sy) = crate::world::tile_to_screen(w, tx, ty);
+			if sx >= x0 && sx <= x1 && sy >= y0 && sy <= y1 && !self.group.contains(&id) {
+				self.group.push(id);
 			}
 		}
+		self.reset();
+	}
+
+	/// Move the group so that its first unit ends on the tile at `mpos`, keeping the formation. Nothing happens if some unit cannot reach its place.
+	fn move_group(&mut self, w: &World, mpos: Vector2) {
+		let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
+		if let Some(orders) = crate::world::group_move(w, &self.group, (tx, ty)) {
+			self.ovec.extend(orders);
+			self.frozen.extend(self.group.drain(..));
+			self.reset();
+			self.state = 2;
+		}
+	}
+
+	/// Check the orders given since the first `given`, by a click, against the tutorial, if any.
+	fn tutorial_orders(&self, w: &mut World, given: usize) {
+		for o in self.ovec.iter().skip(given) {
+			match *o {
+				Order::MOVE(u, x, y) => w.tutorial_act(TutorialGoal::Move(u, (x, y))),
+				Order::ATTACK(a, d, ..) => w.tutorial_act(TutorialGoal::Attack(a, d)),
+				_ => {}
+			}
+		}
+	}
+
+	/// Returns true if the selected unit, or group, may end its action with [InputHandler::wait].
+	pub(crate) fn can_wait(&self) -> bool {
+		!self.group.is_empty() || ((self.state == 1 || self.state == 4) && self.isplrsel && !self.frozen.contains(&self.cur_id))
+	}
+
+	/// End the action of the selected unit (carrying out the orders given so far), or of every unit of the selected group, confirmed by a popup over each.
+	pub(crate) fn wait(&mut self, w: &mut World) {
+		if !self.can_wait() {
+			return;
+		}
+		let ids = if self.group.is_empty() { vec![self.cur_id] } else { self.group.clone() };
+		for id in ids {
+			w.show_popup(id, "Wait", PopupKind::Status);
+			w.tutorial_act(TutorialGoal::Wait(id));
+		}
+		if !self.group.is_empty() {
+			self.frozen.extend(self.group.drain(..));
+		} else {
+			self.confirm_move();
+		}
+		self.sync_acted(w);
+	}
+
+	/// Returns true if the unit with specified id has acted this turn.
+	pub(crate) fn has_acted(&self, uid: u8) -> bool {
+		self.frozen.contains(&uid)
+	}
+
+	/// Returns the ids of the units that have acted this turn, in order.
+	pub(crate) fn acted(&self) -> Vec<u8> {
+		let mut ids: Vec<u8> = self.frozen.iter().cloned().collect();
+		ids.sort_unstable();
+		ids
+	}
+
+	/// Mirror the units that have acted this turn on the world, so that [save_state](crate::world::save_state) saves them.
+	fn sync_acted(&self, w: &mut World) {
+		w.acted = self.acted();
+	}
+
+	/// Returns the player units selected together, if any.
+	pub(crate) fn group(&self) -> &[u8] {
+		&self.group
+	}
+
+	/// Returns the screen rectangle of the selection box being dragged to `mpos`, if any.
+	pub(crate) fn drag_box(&self, mpos: Vector2) -> Option<raylib::math::Rectangle> {
+		let a = self.drag_from?;
+		if self.state > 1 || a.distance_to(mpos) < DRAG_THRESHOLD {
+			return None;
+		}
+		Some(raylib::math::Rectangle::new(a.x.min(mpos.x), a.y.min(mpos.y), (a.x - mpos.x).abs(), (a.y - mpos.y).abs()))
 	}
 
 	#[inline]
 	fn confirm_move(&mut self){
+		let target = self.ovec.iter().rev().find_map(|o| match o {
+			Order::ATTACK(a, d, ..) if *a == self.cur_id => Some(*d),
+			_ => None
+		});
+		self.action = Some(PuzzleStep { unit: self.cur_id, tile: self.last_tile, target: target });
 		self.frozen.insert(self.cur_id);
 		self.reset();
 		self.state = 2;
 	}
 
 	fn select_move_tile(&mut self, w: &World, mpos: Vector2) {
+		let t = crate::world::tile_at(w, mpos.x, mpos.y);
+		self.move_to(w, t);
+	}
+
+	/// Move the selected unit a step onto tile `(tx, ty)`, if next to its last tile and no cliff lies between them; or end its moves if the tile is its own.
+	pub(crate) fn move_to(&mut self, w: &World, (tx, ty): (i32, i32)) {
 		if self.isplrsel && !self.frozen.contains(&self.cur_id) {
-			let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
 			if !crate::world::tile_type_at(w, tx, ty).allowed() {
 				return;
 			}
 			for (i, u) in &w.units {
 				if (u.wpos.x as i32) == tx && (u.wpos.y as i32) == ty {
 					if *i == self.cur_id {
-						self.state = 4;
+						self.end_moves();
 					} /*else if crate::world::is_tile_atrange((tx,ty),self.last_tile,self.range){
 						self.ovec.push(Order::ATTACK(self.cur_id, *i, self.last_tile.0, self.last_tile.1));
 						self.confirm_move();
@@ -250,38 +1480,58 @@ impl InputHandler {
 					return;
 				}
 			}
-			if self.movn >= 1 && crate::world::is_tile_atrange((tx,ty), self.last_tile, 1){
+			if self.movn >= 1 && self.grid.distance((tx,ty), self.last_tile) == 1 && crate::world::can_step(w, self.last_tile, (tx, ty)) {
 				self.ovec.push(Order::MOVE(self.cur_id, tx, ty));
 				self.last_tile = (tx,ty);
 				self.movn -= 1;
 			}
 			if self.movn == 0 {
-				self.state = 4;
+				self.end_moves();
 			}
 		} else {
 			self.state = 0;
 		}
 	}
 
+	/// Select an attack tile once the selected unit is done moving, or confirm its orders if it has no attacks left.
+	fn end_moves(&mut self) {
+		if self.attacks > 0 {
+			self.state = 4;
+		} else {
+			self.confirm_move();
+		}
+	}
+
 	fn select_attack_tile(&mut self, w: &World, mpos: Vector2) {
-		let (tx, ty) = crate::world::tile_at(w, mpos.x, mpos.y);
-		if !crate::world::is_tile_atrange((tx, ty), self.last_tile, self.range) {
+		let t = crate::world::tile_at(w, mpos.x, mpos.y);
+		self.attack_at(w, t);
+	}
+
+	/// Attack the unit on tile `(tx, ty)` with the selected unit, if in range and not hidden by fog of war; otherwise confirm its orders.
+	pub(crate) fn attack_at(&mut self, w: &World, (tx, ty): (i32, i32)) {
+		if !crate::world::is_in_attack_range(w, self.last_tile, (tx, ty), self.range as i32) {
 			self.confirm_move();
 			return;
 		}
-		for (i, u) in &w.units {
-			if (u.wpos.x as i32) == tx && (u.wpos.y as i32) == ty {
-				if *i != self.cur_id {
-					self.ovec.push(Order::ATTACK(self.cur_id, *i, self.last_tile.0, self.last_tile.1));
-				}
-				break;
+		let target = crate::world::unit_at(w, tx, ty).filter(|i| *i != self.cur_id && crate::world::is_unit_visible(w, *i));
+		let i = match target {
+			Some(i) => i,
+			None => {
+				self.confirm_move();
+				return;
 			}
+		};
+		self.ovec.push(Order::ATTACK(self.cur_id, i, self.last_tile.0, self.last_tile.1));
+		self.attacks = self.attacks.saturating_sub(1);
+		if self.move_after_attack && self.movn > 0 {
+			self.state = 1;
+		} else if self.attacks == 0 {
+			self.confirm_move();
 		}
-		self.confirm_move();
 	}
 
 	pub fn tile_shade(&self, tx: i32, ty: i32) -> u8 {
-		let dst = i32::abs(tx - self.cur_upos.0) + i32::abs(ty - self.cur_upos.1);
+		let dst = self.grid.distance((tx, ty), self.cur_upos);
 		if dst == 0 {
 			0
 		} else if dst <= self.movn_i.into() {
@@ -297,19 +1547,39 @@ impl InputHandler {
 		self.state
 	}
 
+	/// Skip the level introduction, starting at the player turn.
+	pub(crate) fn skip_intro(&mut self) {
+		if self.state == 7 {
+			self.state = 0;
+		}
+	}
+
+	/// Resume a restored battle in the player turn, discarding any selection and pending orders, with the units in `acted` having acted; or at the start of the enemy turn.
+	pub(crate) fn resume(&mut self, w: &mut World, phase: TurnPhase, acted: &[u8]) {
+		self.ovec.clear();
+		self.action = None;
+		self.ai_job = None;
+		self.frozen.clear();
+		self.frozen.extend(acted.iter().cloned());
+		self.group.clear();
+		self.reset();
+		self.force_end_turn = phase == TurnPhase::EnemyTurn;
+		self.sync_acted(w);
+	}
+
 	pub fn _boxrange(&self) -> (i32, i32, i32, i32) {
 		let s = (self.movn_i + self.range) as i32;
 		(self.cur_upos.0 - s, self.cur_upos.0 + s, self.cur_upos.1 - s, self.cur_upos.1 + s)
 	}
 
-	pub fn _atkrange(&self) -> (i32, i32, i32, i32) {
-		let r = self.range as i32;
+	pub fn _atkrange(&self, w: &World) -> (i32, i32, i32, i32) {
+		let r = self.range as i32 + w.elevation_rules().range_bonus as i32;
 		(self.last_tile.0 - r, self.last_tile.0 + r, self.last_tile.1 - r, self.last_tile.1 + r)
 	}
 
-	pub fn _inrange(&self, x: i32, y: i32) -> i32 {
-		let dst = i32::abs(x - self.last_tile.0) + i32::abs(y - self.last_tile.1);
-		if dst == self.range.into() {
+	pub fn _inrange(&self, w: &World, x: i32, y: i32) -> i32 {
+		let dst = self.grid.distance((x, y), self.last_tile);
+		if crate::world::is_in_attack_range(w, self.last_tile, (x, y), self.range as i32) {
 			1
 		} else if dst == 0 {
 			-1
@@ -5,7 +5,62 @@ pub mod utils;
 pub mod display;
 pub mod world;
 pub mod input;
+pub mod text;
+pub mod save;
+pub mod replay;
+pub mod ai;
+pub mod puzzle;
+pub mod tutorial;
+pub mod narration;
+pub mod tween;
+pub mod schedule;
+pub mod event;
+pub mod console;
+pub mod touch;
+#[cfg(feature = "debug_tools")]
+pub mod debug_tools;
 pub mod napi;
+#[cfg(feature = "python")]
+pub mod pyapi;
+
+/// Capability bit, set if scripting (Python bindings, see the `python` feature) is compiled in.
+pub const CAP_SCRIPTING: u32 = 1;
+/// Capability bit, reserved for networking support. Never set by this version.
+pub const CAP_NETWORKING: u32 = 1 << 1;
+/// Capability bit, set if built for WebAssembly.
+pub const CAP_WASM: u32 = 1 << 2;
+/// Capability bit, set if the pointer based native API (the `legacy_ptr` feature) is compiled in.
+pub const CAP_LEGACY_PTR: u32 = 1 << 3;
+/// Capability bit, set if the .NET friendly native API (the `dotnet` feature) is compiled in.
+pub const CAP_DOTNET: u32 = 1 << 4;
+/// Capability bit, set if the cheats of the `debug_tools` feature are compiled in.
+pub const CAP_DEBUG_TOOLS: u32 = 1 << 5;
+
+/// Returns the version of the engine, e.g, "0.1.0".
+pub fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Returns the bitmask of capabilities (the `CAP_*` constants) compiled into the engine.
+pub fn capabilities() -> u32 {
+    let mut c = 0;
+    if cfg!(feature = "python") {
+        c |= CAP_SCRIPTING;
+    }
+    if cfg!(target_arch = "wasm32") {
+        c |= CAP_WASM;
+    }
+    if cfg!(feature = "legacy_ptr") {
+        c |= CAP_LEGACY_PTR;
+    }
+    if cfg!(feature = "dotnet") {
+        c |= CAP_DOTNET;
+    }
+    if cfg!(feature = "debug_tools") {
+        c |= CAP_DEBUG_TOOLS;
+    }
+    c
+}
 
 #[test]
 fn it_works() {
@@ -132,3 +187,2489 @@ fn load_map() {
     });
     d.begin(rs, w, sl);
 }
+
+#[test]
+fn napi_handles() {
+    use napi::handles::*;
+    let w = alshBlank_World();
+    let ut = alshNewUnitType(0, "Swordsman\0".as_ptr() as *const _, 10.0, 0.5, 2, 1, 1.5);
+    assert!(alshIsValid(w) && alshIsValid(ut));
+    assert!(!alshCreateStatic(ut, 0, 0, 0));
+    assert!(alshRegisterUnitType(w, ut, 0));
+    assert!(!alshIsValid(ut));
+    let mut uid = 0;
+    assert!(alshSpawnUnit(w, 0, 1, 1, -1, true, &mut uid));
+    assert!(!alshSpawnUnit(w, 1, 1, 1, -1, true, &mut uid));
+    assert!(alshRelease(w));
+    assert!(!alshRelease(w));
+    let w2 = alshBlank_World();
+    assert_ne!(w, w2);
+    assert!(!alshIsValid(w));
+}
+
+#[test]
+fn napi_last_error() {
+    use napi::error::*;
+    alsClearError();
+    assert_eq!(alsGetLastError(std::ptr::null_mut()), ERR_NONE);
+    assert_eq!(napi::alsnUnitCount(std::ptr::null()), 0);
+    let mut msg = std::ptr::null();
+    assert_eq!(alsGetLastError(&mut msg), ERR_NULL_POINTER);
+    assert!(!unsafe { std::ffi::CStr::from_ptr(msg) }.to_bytes().is_empty());
+    alsClearError();
+    assert_eq!(alsGetLastError(std::ptr::null_mut()), ERR_NONE);
+}
+
+#[test]
+fn reachable_tiles() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    world::spawn_unit(&mut w, 0, (1, 0), -1, false);
+    let mut t = world::reachable_tiles(&w, a);
+    t.sort();
+    assert_eq!(t, vec![(0, 1), (0, 2), (1, 1)]);
+    assert_eq!(world::tile_distance((0, 0), (2, 1)), 3);
+}
+
+#[cfg(feature = "dotnet")]
+#[test]
+fn napi_dotnet_strings() {
+    use napi::dotnet::*;
+    let name: Vec<u16> = "Bogenschütze\0".encode_utf16().collect();
+    let ut = alsdNewUnitType(0, name.as_ptr(), 10.0, 0.5, 2, 2, 1.5);
+    assert_eq!(alsdIsValid(ut), 1);
+    assert_eq!(alsdSetUnitInfo(ut, std::ptr::null()), 0);
+    let mut buf = [0u16; 8];
+    assert_eq!(alsdGetLastError(buf.as_mut_ptr(), 8), napi::error::ERR_NULL_POINTER);
+    assert_eq!(buf[7], 0);
+    assert_eq!(alsdRelease(ut), 1);
+}
+
+#[test]
+fn napi_map_texts() {
+    let w = world::World::blank();
+    let s = napi::alsnGetMapTitle(&w);
+    assert!(!s.is_null());
+    assert_eq!(unsafe { std::ffi::CStr::from_ptr(s) }.to_str().unwrap(), w.map_title());
+    napi::alsnFreeString(s);
+    assert!(napi::alsnGetDefeatText(std::ptr::null()).is_null());
+}
+
+#[test]
+fn napi_camera() {
+    let mut w = world::World::blank();
+    let (mut x, mut y) = (0.0, 0.0);
+    assert!(napi::alsnSetCamera(&mut w, 2.0, 3.0));
+    assert!(napi::alsnGetCamera(&w, &mut x, &mut y));
+    assert_eq!((x, y), (2.0, 3.0));
+    assert!(!napi::alsnSetCameraFollow(&mut w, 7, true));
+    assert!(napi::alsnSetCameraFollow(&mut w, 7, false));
+}
+
+#[test]
+fn tile_screen_roundtrip() {
+    let mut w = world::World::blank_o(300, 20, 96, 48);
+    w.set_cpos(1.5, -0.5);
+    for &(tx, ty) in [(0, 0), (3, 1), (2, 5), (7, 7)].iter() {
+        let (sx, sy) = world::tile_to_screen(&w, tx, ty);
+        assert_eq!(world::tile_at(&w, sx, sy), (tx, ty));
+    }
+}
+
+#[test]
+fn victory_check() {
+    use input::Outcome;
+    let w = world::World::blank();
+    let mut sl = utils::StateListener::new();
+    assert_eq!(sl.check_victory(&w), Outcome::Continue);
+    sl.bind_victory_check(|w| if w.units.is_empty() { Outcome::Defeat } else { Outcome::Continue });
+    assert_eq!(sl.check_victory(&w), Outcome::Defeat);
+    assert_eq!(Outcome::from_raw(1), Outcome::Victory);
+    assert_eq!(Outcome::from_raw(7), Outcome::Continue);
+}
+
+#[test]
+fn napi_audio() {
+    let mut w = world::World::blank();
+    assert!(napi::alsnPlaySound(&mut w, 1));
+    assert!(napi::alsnSetMusicVolume(&mut w, 0.5));
+    assert!(!napi::alsnSetSfxVolume(&mut w, 1.5));
+    assert!(!napi::alsnStopMusic(std::ptr::null_mut()));
+    assert_eq!(w.audio, vec![world::AudioCmd::PlaySound(1), world::AudioCmd::MusicVolume(0.5)]);
+}
+
+#[test]
+fn version_query() {
+    let v = unsafe { std::ffi::CStr::from_ptr(napi::alsnGetVersion()) };
+    assert_eq!(v.to_str().unwrap(), version());
+    assert_eq!(napi::alsnGetCapabilities() & CAP_NETWORKING, 0);
+    assert_eq!(capabilities() & CAP_LEGACY_PTR != 0, cfg!(feature = "legacy_ptr"));
+}
+
+#[test]
+fn damage_closure() {
+    let mut w = world::World::blank();
+    let mut hits = 0;
+    w.bind_damage_closure(move |_atk, _def| {
+        hits += 1;
+        hits as f32
+    });
+    if let world::DamageFunc::Handle(f) = &mut w.dmg_func {
+        assert_eq!(f(0, 0), 1.0);
+        assert_eq!(f(0, 0), 2.0);
+    } else {
+        panic!("closure not bound");
+    }
+}
+
+#[test]
+fn default_damage() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5);
+    ut.set_combat_stats(6.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, false);
+    let c = world::spawn_unit(&mut w, 0, (3, 0), -1, false);
+    assert_eq!(world::default_damage(&w, a, b), 4.5);
+    assert_eq!(world::default_damage(&w, a, c), 4.5 * 0.8);
+    assert_eq!(world::default_damage(&w, a, 200), 0.0);
+}
+
+#[test]
+fn unit_variant() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    ut.def_anim_muted((32,48), 10, (0,0), 8.0, false);
+    world::register_unit_type(&mut w, ut, 0);
+    assert!(world::register_unit_variant(&mut w, 0, 1, "Blademaster".to_string(), |u| u.set_stats(15.0, 0.5, 3, 1, 1.5)));
+    assert!(!world::register_unit_variant(&mut w, 9, 2, "Nobody".to_string(), |_| {}));
+    let v = &w.unit_types[&1];
+    assert_eq!((v.name.as_str(), v.max_health(), v.movement()), ("Blademaster", 15.0, 3));
+    let h = napi::handles::alshBlank_World();
+    assert_eq!(napi::handles::alshDeriveUnitType(h, 0, "Elite\0".as_ptr() as *const _), 0);
+}
+
+#[test]
+fn palette_shader_code() {
+    use raylib::prelude::Color;
+    let fs = utils::palette_fs(&[Color::new(255, 0, 0, 255), Color::new(0, 0, 255, 255)], &[Color::new(0, 255, 0, 255), Color::new(0, 0, 0, 255)]);
+    assert!(fs.contains("const vec3 SRC[2] = vec3[2](vec3(1.0000, 0.0000, 0.0000), vec3(0.0000, 0.0000, 1.0000));"));
+    assert!(fs.contains("const vec3 DST[2] = vec3[2](vec3(0.0000, 1.0000, 0.0000), vec3(0.0000, 0.0000, 0.0000));"));
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let u = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    assert!(napi::alsnSetUnitPalette(&mut w, u, 3, true));
+    assert_eq!(w.units[&u].palette, Some(3));
+}
+
+#[test]
+fn anim_speed() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    for _ in 0..9 {
+        ut.def_anim_muted((32,48), 10, (0,0), 4.0, false);
+    }
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, true);
+    assert!(napi::alsnSetUnitAnimSpeed(&mut w, a, 2.0));
+    assert!(!napi::alsnSetUnitAnimSpeed(&mut w, a, -1.0));
+    assert!(napi::alsnSetUnitAnimPaused(&mut w, b, true));
+    let uh = &w.unit_types;
+    for u in w.units.values_mut() {
+        u.update(uh, 0.5);
+    }
+    let f = |u: u8| w.units[&u].prep_draw(&w).1.x;
+    assert_eq!((f(a), f(b)), (128.0, 0.0));
+}
+
+#[test]
+fn idle_variation() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    for _ in 0..9 {
+        ut.def_anim_muted((32,48), 1, (0,0), 4.0, false);
+    }
+    ut.def_idle_anim((32,48), 4, (0,96), 4.0, false);
+    ut.set_idle_delay(1.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let step = |w: &mut world::World, dt: f32| {
+        let uh = &w.unit_types;
+        w.units.get_mut(&a).unwrap().update(uh, dt);
+        w.units[&a].prep_draw(w).1.y
+    };
+    assert_eq!(step(&mut w, 0.5), 0.0);
+    assert_eq!(step(&mut w, 0.5), 96.0);
+    assert_eq!(step(&mut w, 0.5), 96.0);
+    assert_eq!(step(&mut w, 0.5), 0.0);
+}
+
+#[test]
+fn directional_stand() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    for _ in 0..9 {
+        ut.def_anim_muted((32,48), 1, (0,0), 4.0, false);
+    }
+    ut.def_stand_anim(world::Facing::Left, (32,48), 1, (0,48), 4.0, false);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    assert_eq!(w.units[&a].prep_draw(&w).1.y, 0.0);
+    world::set_unit_state(&mut w, a, world::UnitState::WalkLeft);
+    world::set_unit_state(&mut w, a, world::UnitState::Stand);
+    assert_eq!(w.units[&a].facing, world::Facing::Left);
+    assert_eq!(w.units[&a].prep_draw(&w).1.y, 48.0);
+}
+
+#[test]
+fn anim_frames() {
+    let mut ut = world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    ut.def_anim_muted((32,48), 10, (0,48), 8.0, true);
+    let (nf, fr, rec) = ut.anim_frame(0, 2).unwrap();
+    assert_eq!((nf, fr), (10, 8.0));
+    assert_eq!((rec.x, rec.y, rec.width, rec.height), (64.0, 48.0, -32.0, 48.0));
+    assert!(ut.anim_frame(1, 0).is_none());
+}
+
+#[test]
+fn validate_animations() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(3, "Archer".to_string(), 10.0, 0.5, 2, 2, 1.5);
+    for i in 0..9 {
+        ut.def_anim_muted((32,48), 10, (0, 48*i), 6.0, false);
+    }
+    world::register_unit_type(&mut w, ut, 1);
+    assert!(world::validate_animations(&w, |_| Some((320, 432))).is_empty());
+    let msgs = world::validate_animations(&w, |_| Some((300, 400)));
+    assert_eq!(msgs.len(), 10);
+    assert!(msgs[0].contains("WalkDown: frame 9 of 10 exceeds texture width 300"));
+    assert!(world::validate_animations(&w, |_| None)[0].contains("texture id=3 has not been loaded"));
+}
+
+#[test]
+fn silent_wav() {
+    let v = utils::silent_wav();
+    assert_eq!(&v[0..4], b"RIFF");
+    assert_eq!(&v[8..16], b"WAVEfmt ");
+    let riff_len = u32::from_le_bytes([v[4], v[5], v[6], v[7]]) as usize;
+    let data_len = u32::from_le_bytes([v[40], v[41], v[42], v[43]]) as usize;
+    assert_eq!(riff_len + 8, v.len());
+    assert_eq!(data_len + 44, v.len());
+    assert!(v[44..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn font_atlas_index() {
+    let sizes = [16, 22, 32];
+    assert_eq!(utils::atlas_index(&sizes, 12.0), 0);
+    assert_eq!(utils::atlas_index(&sizes, 22.0), 1);
+    assert_eq!(utils::atlas_index(&sizes, 23.0), 2);
+    assert_eq!(utils::atlas_index(&sizes, 48.0), 2);
+    let ranges = [0x20, 0x7e, 0x400, 0x4ff];
+    assert_eq!(napi::read_ranges(ranges.as_ptr(), 2), vec![(0x20, 0x7e), (0x400, 0x4ff)]);
+}
+
+#[test]
+fn text_markup() {
+    use raylib::prelude::Color;
+    use text::Span;
+    let spans = text::parse("Atk [b][red]12[/red] vs[/b] {icon:7}\n[bogus] {icon:x}", Color::BLACK);
+    assert_eq!(spans, vec![
+        Span::Text("Atk ".to_string(), Color::BLACK, false),
+        Span::Text("12".to_string(), Color::RED, true),
+        Span::Text(" vs".to_string(), Color::BLACK, true),
+        Span::Text(" ".to_string(), Color::BLACK, false),
+        Span::Icon(7),
+        Span::Break,
+        Span::Text("[bogus] {icon:x}".to_string(), Color::BLACK, false),
+    ]);
+    assert_eq!(text::parse("[#10ff20]x", Color::BLACK)[0], Span::Text("x".to_string(), Color::new(0x10, 0xff, 0x20, 255), false));
+    // Fixed width font, 10 pixels per character.
+    let placed = text::layout(&text::parse("aa bb cc", Color::BLACK), |s| 10.0 * s.chars().count() as f32, 10.0, 55.0, false);
+    let pos: Vec<(f32, f32)> = placed.iter().map(|(_, p)| (p.x, p.y)).collect();
+    assert_eq!(pos, vec![(0.0, 0.0), (30.0, 0.0), (0.0, 12.0)]);
+    let placed = text::layout(&text::parse("aa bb cc", Color::BLACK), |s| 10.0 * s.chars().count() as f32, 10.0, 55.0, true);
+    assert_eq!((placed[0].1.x, placed[2].1.x), (2.5, 17.5));
+}
+
+#[test]
+fn battle_log() {
+    use std::rc::Rc;
+    use std::cell::Cell;
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Knight".to_string(), 10.0, 0.5, 2, 1, 1.5), 1);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 1, (1, 0), -1, false);
+    let mut is = input::InputHandler::new();
+    for _ in 0..70 {
+        is.log_event(&w, &world::BattleEvent::Attack(a, b, 2.5));
+    }
+    is.log_event(&w, &world::BattleEvent::Kill(b, 1));
+    assert_eq!(is.log.len(), 64);
+    assert_eq!(is.log[0], "Archer hits Knight for [red]2.5[/red]");
+    assert_eq!(is.log.back().unwrap(), "Knight is slain");
+    let total = Rc::new(Cell::new(0.0));
+    let t = total.clone();
+    let mut sl = utils::StateListener::new();
+    sl.bind_attack(move |_, _, _, dmg| t.set(t.get() + dmg));
+    sl.notify_attack(&mut w, a, b, 2.5);
+    sl.notify_attack(&mut w, a, b, 1.0);
+    assert_eq!(total.get(), 3.5);
+}
+
+#[test]
+fn objectives() {
+    use world::ObjectiveState;
+    let mut w = world::World::blank();
+    assert_eq!(w.add_objective("Rout the enemy"), 0);
+    let i = napi::alsnAddObjective(&mut w, "Keep the [b]Prince[/b] alive\0".as_ptr() as *const _);
+    assert_eq!(i, 1);
+    assert!(napi::alsnSetObjectiveState(&mut w, 0, 1));
+    assert!(!napi::alsnSetObjectiveState(&mut w, 0, 7));
+    assert!(!napi::alsnSetObjectiveState(&mut w, 5, 2));
+    assert!(w.set_objective_state(1, ObjectiveState::Failed));
+    let states: Vec<ObjectiveState> = w.objectives().iter().map(|o| o.state).collect();
+    assert_eq!(states, vec![ObjectiveState::Completed, ObjectiveState::Failed]);
+    assert_eq!(w.objectives()[1].text, "Keep the [b]Prince[/b] alive");
+    assert!(napi::alsnClearObjectives(&mut w));
+    assert!(w.objectives().is_empty());
+}
+
+#[test]
+fn phase_banner() {
+    assert_eq!(display::banner_offset(0.0, 800.0), -800.0);
+    assert_eq!(display::banner_offset(0.125, 800.0), -400.0);
+    assert_eq!(display::banner_offset(0.5, 800.0), 0.0);
+    assert_eq!(display::banner_offset(1.0, 800.0), 800.0);
+    let t = display::Theme::default();
+    let b = napi::banner_theme(&t, 3, -1, 0x000000ff, -1, 2.0).unwrap();
+    assert_eq!((b.banner_tex, b.banner_font, b.banner_time), (Some(3), None, 2.0));
+    assert_eq!(b.player_phase_text, "Player Phase");
+    assert!(napi::banner_theme(&t, 256, -1, 0, 0, 1.0).is_none());
+    assert!(napi::banner_theme(&t, -1, -1, 0, 0, -1.0).is_none());
+    let mut w = world::World::blank();
+    let mut sl = utils::StateListener::new();
+    sl.bind_banner_done(|w, p| if p == input::TurnPhase::PlayerTurn { w.add_objective("Banner done"); });
+    sl.notify_banner_done(&mut w, input::TurnPhase::PlayerTurn);
+    assert_eq!(w.objectives().len(), 1);
+    assert_eq!(w.turn(), 1);
+}
+
+#[test]
+fn battle_stats() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, false);
+    w.record_stats(&world::BattleEvent::Attack(a, b, 2.6));
+    w.record_stats(&world::BattleEvent::Attack(a, b, 2.6));
+    w.record_stats(&world::BattleEvent::Attack(b, a, 1.5));
+    w.record_stats(&world::BattleEvent::Kill(b, 0));
+    w.turn = 3;
+    let mut st = world::BattleStats::default();
+    assert!(napi::alsnGetBattleStats(&w, &mut st));
+    assert_eq!((st.turns, st.units_lost, st.enemies_defeated), (3, 0, 1));
+    assert_eq!((st.damage_dealt, st.damage_received), (5.2, 1.5));
+    assert_eq!(st.xp_gained, 5 + world::KILL_XP);
+    assert_eq!(input::Outcome::from_raw(input::Outcome::Defeat.to_raw()), input::Outcome::Defeat);
+}
+
+#[test]
+fn negative_damage_stats() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, false);
+    w.record_stats(&world::BattleEvent::Attack(a, b, 2.6));
+    w.bind_damage_closure(|_atk, _def| -3.0);
+    let dmg = match &mut w.dmg_func {
+        world::DamageFunc::Handle(f) => f(0, 0),
+        _ => panic!("closure not bound")
+    };
+    w.record_stats(&world::BattleEvent::Attack(a, b, dmg));
+    w.record_stats(&world::BattleEvent::Attack(b, a, dmg));
+    let st = w.stats();
+    assert_eq!((st.damage_dealt, st.damage_received, st.xp_gained), (2.6, 0.0, 2));
+}
+
+#[test]
+fn profile() {
+    let mut p = utils::Profile::parse("# comment\nwins = 3\nboss_beaten = 1\nbad line\n");
+    assert_eq!((p.get("wins"), p.get("missing")), (3, 0));
+    assert!(p.flag("boss_beaten"));
+    assert!(!p.set("two words", 1));
+    assert!(!p.set("a=b", 1));
+    assert_eq!(p.incr("wins", 2), 5);
+    assert_eq!(utils::Profile::parse(&p.to_text()).to_text(), p.to_text());
+    let path = std::env::temp_dir().join("alesia_profile_test.txt");
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+    let mut w = world::World::blank();
+    assert!(w.open_profile(path));
+    w.profile.set_flag("chapter1", true);
+    assert!(w.save_profile());
+    let mut w2 = world::World::blank();
+    assert!(w2.open_profile(path));
+    assert!(w2.profile.flag("chapter1"));
+    assert_eq!(napi::alsnGetProfileValue(&w2, b"chapter1\0".as_ptr() as *const std::os::raw::c_char), 1);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn settings() {
+    use raylib::ffi::KeyboardKey;
+    let s = utils::Settings::parse("[display] # window\nwidth = 1920\nvsync = false\nui_scale = 1.5\nlocale = \"fr # \\\"ca\\\"\"\n\n[audio]\nmusic_volume = 0.25\nsfx_volume = 2.0\n\n[keys]\nend_turn = \"space\"\ncam_left = \"A\"\nfly = \"F\"\nbogus\n");
+    assert_eq!((s.width, s.height, s.vsync, s.ui_scale), (1920, 720, false, 1.5));
+    assert_eq!(s.locale, "fr # \"ca\"");
+    assert_eq!((s.music_volume, s.sfx_volume), (0.25, 1.0));
+    assert_eq!((s.keys.end_turn, s.keys.cam_left, s.keys.cancel), (KeyboardKey::KEY_SPACE, KeyboardKey::KEY_A, KeyboardKey::KEY_E));
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    assert_eq!(input::key_name(KeyboardKey::KEY_PAGE_UP), Some("PAGE_UP"));
+    assert_eq!(s.keys.get("toggle_log"), Some(KeyboardKey::KEY_L));
+    assert_eq!(utils::Settings::load("/nonexistent/alesia.toml").unwrap(), utils::Settings::default());
+}
+
+#[test]
+fn frame_rate_settings() {
+    let s = utils::Settings::parse("[display]\nfps = 144\nsim_fps = 30\nbackground_fps = 5\n");
+    assert_eq!((s.fps, s.sim_fps, s.background_fps), (144, 30, 5));
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    let d = utils::Settings::default();
+    assert_eq!((d.sim_fps, d.background_fps), (0, 0));
+    assert_eq!(utils::Settings::parse("[display]\nsim_fps = -1\n").sim_fps, 0);
+}
+
+#[test]
+fn pause_unfocused_setting() {
+    assert!(!utils::Settings::default().pause_unfocused);
+    let s = utils::Settings::parse("[display]\npause_unfocused = true\n");
+    assert!(s.pause_unfocused);
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    assert!(!utils::Settings::parse("[display]\npause_unfocused = 1\n").pause_unfocused);
+}
+
+#[test]
+fn launch_args() {
+    let args = |v: &[&str]| utils::LaunchArgs::parse(v.iter().map(|s| s.to_string()));
+    let la = args(&["--map", "res/testmap.alw", "--windowed", "--seed=7", "--replay", "r.rep", "-v"]).unwrap();
+    assert_eq!(la.map.as_deref(), Some("res/testmap.alw"));
+    assert_eq!((la.windowed, la.seed, la.skip_intro), (true, Some(7), false));
+    assert_eq!((la.replay.as_deref(), &la.rest[..]), (Some("r.rep"), &["-v".to_string()][..]));
+    assert!(args(&["--seed", "x"]).is_err());
+    assert!(args(&["--map"]).is_err());
+    let mut d = display::Display::new_s(640, 480, "Args test");
+    let mut w = world::World::blank();
+    assert!(!args(&["--map", "res/missing.alw"]).unwrap().apply(&mut d, &mut w));
+    assert!(args(&["--windowed", "--seed", "3", "--skip-intro"]).unwrap().apply(&mut d, &mut w));
+    assert!(utils::Settings::parse("[display]\nfullscreen = true\n").fullscreen);
+}
+
+#[test]
+fn autosave() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (1, 2), -1, true);
+    w.units.get_mut(&a).unwrap().health = 4.5;
+    w.add_objective("Hold\nthe [b]gate[/b]");
+    w.turn = 4;
+    let text = save::snapshot(&w, input::TurnPhase::EnemyTurn);
+    assert_eq!(save::peek(&text).map(|i| i.turn), Some(4));
+    let mut w2 = world::World::blank();
+    assert!(save::restore(&mut w2, &text).is_err());
+    world::register_unit_type(&mut w2, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    assert_eq!(save::restore(&mut w2, &text), Ok(input::TurnPhase::EnemyTurn));
+    assert_eq!((w2.turn(), w2.units[&a].health, world::unit_tile(&w2, a)), (4, 4.5, Some((1, 2))));
+    assert_eq!(w2.objectives()[0].text, "Hold\nthe [b]gate[/b]");
+    assert!(save::restore(&mut w2, "alesia-save 1\nunit 1 0 x\n").is_err());
+    let dir = std::env::temp_dir();
+    let auto = save::Autosave { path: dir.join("alesia_autosave_test.sav").to_str().unwrap().to_string(), every: 2, before_enemy: true };
+    let _ = std::fs::remove_file(auto.lock_path());
+    assert!(auto.save(&w, input::TurnPhase::PlayerTurn));
+    assert!(auto.recoverable().is_none());
+    std::fs::write(auto.lock_path(), "").unwrap();
+    assert_eq!(auto.recoverable().map(|i| i.turn), Some(4));
+    assert!(auto.due(3, 0, 3) && !auto.due(3, 0, 4) && auto.due(0, 3, 4) && !auto.due(0, 1, 4));
+    let _ = std::fs::remove_file(auto.lock_path());
+    let _ = std::fs::remove_file(&auto.path);
+}
+
+#[test]
+fn save_slots() {
+    let dir = std::env::temp_dir().join("alesia_slots_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    let slots = save::SaveSlots::new(dir.to_str().unwrap());
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (3, 1), -1, true);
+    assert!(slots.list().is_empty());
+    w.turn = 2;
+    assert!(slots.save(&w, 3));
+    w.turn = 5;
+    assert!(slots.save(&w, 1));
+    std::fs::write(dir.join("slot9.sav"), "junk").unwrap();
+    let list = slots.list();
+    assert_eq!(list.iter().map(|i| (i.slot, i.header.turn)).collect::<Vec<_>>(), vec![(1, 5), (3, 2)]);
+    assert!(list[0].thumbnail.is_none() && list[0].header.time > 0);
+    let mut n = [0u32; 1];
+    let d = std::ffi::CString::new(dir.to_str().unwrap()).unwrap();
+    assert_eq!(napi::alsnListSlots(d.as_ptr(), n.as_mut_ptr(), 1), 2);
+    assert_eq!(n[0], 1);
+    world::despawn_unit(&mut w, a);
+    assert!(slots.load(&mut w, 3));
+    assert_eq!((w.turn(), world::unit_tile(&w, a)), (2, Some((3, 1))));
+    assert!(slots.delete(3) && !slots.delete(3));
+    assert!(!slots.load(&mut w, 3));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn data_dir() {
+    use std::path::PathBuf;
+    let env = |pairs: &'static [(&'static str, &'static str)]| move |k: &str| pairs.iter().find(|(n, _)| *n == k).map(|(_, v)| v.to_string());
+    assert_eq!(utils::resolve_data_dir("game", env(&[("ALESIA_DATA_DIR", "/tmp/x"), ("HOME", "/home/a")]), None, "linux"), PathBuf::from("/tmp/x"));
+    assert_eq!(utils::resolve_data_dir("game", env(&[("HOME", "/home/a")]), None, "linux"), PathBuf::from("/home/a/.local/share/game"));
+    assert_eq!(utils::resolve_data_dir("game", env(&[("HOME", "/home/a"), ("XDG_DATA_HOME", "/xdg")]), None, "linux"), PathBuf::from("/xdg/game"));
+    assert_eq!(utils::resolve_data_dir("game", env(&[("APPDATA", "C:/Users/a/AppData/Roaming")]), None, "windows"), PathBuf::from("C:/Users/a/AppData/Roaming/game"));
+    assert_eq!(utils::resolve_data_dir("game", env(&[("HOME", "/Users/a")]), None, "macos"), PathBuf::from("/Users/a/Library/Application Support/game"));
+    assert_eq!(utils::resolve_data_dir("game", env(&[]), None, "linux"), PathBuf::from("."));
+    let exe = std::env::temp_dir().join("alesia_portable_test");
+    std::fs::create_dir_all(&exe).unwrap();
+    std::fs::write(exe.join("portable"), "").unwrap();
+    assert_eq!(utils::resolve_data_dir("game", env(&[("HOME", "/home/a")]), Some(exe.clone()), "linux"), exe.join("data"));
+    let _ = std::fs::remove_dir_all(&exe);
+}
+
+#[test]
+fn hud_toggle_binding() {
+    use raylib::ffi::KeyboardKey;
+    assert_eq!(input::KeyBindings::default().toggle_hud, KeyboardKey::KEY_H);
+    let s = utils::Settings::parse("[keys]\ntoggle_hud = \"F2\"\n");
+    assert_eq!(s.keys.toggle_hud, KeyboardKey::KEY_F2);
+    assert!(s.to_toml().contains("toggle_hud = \"F2\""));
+}
+
+#[test]
+fn exit_callback() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    let calls = Rc::new(Cell::new(0));
+    let mut sl = utils::StateListener::new();
+    let c = calls.clone();
+    sl.bind_exit(move |w| {
+        w.bgm_id = 3;
+        c.set(c.get() + 1);
+    });
+    let mut w = world::World::blank();
+    sl.notify_exit(&mut w);
+    assert_eq!((calls.get(), w.bgm_id), (1, 3));
+    utils::StateListener::_new_raw().notify_exit(&mut w);
+}
+
+#[test]
+fn group_move() {
+    use input::Order;
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (3, 1), -1, false);
+    let steps = |o: Vec<Order>| o.iter().map(|o| match o { Order::MOVE(u, x, y) => (*u, *x, *y), _ => panic!("not a move") }).collect::<Vec<_>>();
+    let o = world::group_move(&w, &[a, b], (1, 1)).unwrap();
+    assert_eq!(steps(o), vec![(a, 0, 1), (a, 1, 1), (b, 1, 1), (b, 2, 1)]);
+    // The enemy blocks b's place, and the group cannot go beyond its movement.
+    assert!(world::group_move(&w, &[a, b], (2, 1)).is_none());
+    assert!(world::group_move(&w, &[a, b], (0, 3)).is_none());
+    assert!(world::group_move(&w, &[], (0, 1)).is_none());
+    let (h, mh, text) = world::_group_info(&w, &[a, b, e]);
+    assert_eq!((h, mh), (30.0, 30.0));
+    assert!(text.starts_with("Group of 3\n\nSwordsman x3"));
+}
+
+#[test]
+fn unit_behaviors() {
+    use input::Order;
+    use world::Behavior;
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let p = world::spawn_unit(&mut w, 0, (5, 0), -1, true);
+    let g = world::spawn_unit(&mut w, 0, (2, 0), -1, false);
+    let t = world::spawn_unit(&mut w, 0, (0, 4), -1, false);
+    assert!(world::set_behavior(&mut w, g, Some(Behavior::Guard { tile: (3, 0), radius: 2 })));
+    assert!(world::add_patrol_point(&mut w, t, (0, 6)));
+    assert!(world::add_patrol_point(&mut w, t, (0, 4)));
+    assert!(!world::set_behavior(&mut w, 99, Some(Behavior::Hold)));
+    let mut ovec = vec![Order::MOVE(p, 5, 1)];
+    world::behavior_orders(&mut w, &mut ovec);
+    let summary: Vec<String> = ovec.iter().map(|o| format!("{:?}", o)).collect();
+    // The guard closes in on the intruder and attacks; the patrol walks to its first waypoint and heads for the next.
+    assert_eq!(summary, vec![
+        format!("MOVE({}, 5, 1)", p),
+        format!("MOVE({}, 3, 0)", g), format!("MOVE({}, 4, 0)", g), format!("ATTACK({}, {}, 4, 0)", g, p),
+        format!("MOVE({}, 0, 5)", t), format!("MOVE({}, 0, 6)", t)
+    ]);
+    assert_eq!(w.units[&t].behavior, Some(Behavior::Patrol { waypoints: vec![(0, 6), (0, 4)], next: 1 }));
+    let text = save::snapshot(&w, input::TurnPhase::PlayerTurn);
+    world::set_behavior(&mut w, g, None);
+    save::restore(&mut w, &text).unwrap();
+    assert_eq!(w.units[&g].behavior, Some(Behavior::Guard { tile: (3, 0), radius: 2 }));
+    assert_eq!(w.units[&t].behavior, Some(Behavior::Patrol { waypoints: vec![(0, 6), (0, 4)], next: 1 }));
+}
+
+#[test]
+fn difficulty() {
+    use world::Difficulty;
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 1, 1.5);
+    ut.set_combat_stats(6.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let p = world::spawn_unit(&mut w, 0, (6, 0), -1, true);
+    let g = world::spawn_unit(&mut w, 0, (3, 0), -1, false);
+    world::set_behavior(&mut w, g, Some(world::Behavior::Guard { tile: (3, 0), radius: 2 }));
+    assert_eq!(w.difficulty(), Difficulty::NORMAL);
+    let mut ovec = vec![];
+    world::behavior_orders(&mut w, &mut ovec);
+    assert!(ovec.is_empty());
+    assert_eq!(Difficulty::preset("Hard"), Some(Difficulty::HARD));
+    assert!(!napi::alsnSetDifficulty(&mut w, 3));
+    assert!(napi::alsnSetDifficulty(&mut w, 2));
+    // The guard notices the intruder beyond its radius, and deals more damage.
+    world::behavior_orders(&mut w, &mut ovec);
+    assert_eq!(ovec.len(), 3);
+    assert_eq!(world::default_damage(&w, g, p), 4.5 * 0.8 * 1.25);
+    assert_eq!(world::default_damage(&w, p, g), 4.5 * 0.8 * 0.9);
+    w.set_difficulty(Difficulty { player_damage: 2.0, enemy_damage: f32::NAN, enemy_health: 0.0, aggression: 1.0 });
+    assert_eq!(w.difficulty(), Difficulty { player_damage: 2.0, ..Difficulty::NORMAL });
+    let text = save::snapshot(&w, input::TurnPhase::PlayerTurn);
+    w.set_difficulty(Difficulty::EASY);
+    save::restore(&mut w, &text).unwrap();
+    let mut d = Difficulty::default();
+    assert!(napi::alsnGetDifficulty(&w, &mut d));
+    assert_eq!(d.player_damage, 2.0);
+}
+
+#[test]
+fn ai_plans() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let p = world::spawn_unit(&mut w, 0, (5, 0), -1, true);
+    let g = world::spawn_unit(&mut w, 0, (2, 0), -1, false);
+    let h = world::spawn_unit(&mut w, 0, (0, 5), -1, false);
+    world::set_behavior(&mut w, g, Some(world::Behavior::Guard { tile: (3, 0), radius: 2 }));
+    world::set_behavior(&mut w, h, Some(world::Behavior::Hold));
+    let mut ovec = vec![];
+    world::behavior_orders(&mut w, &mut ovec);
+    let plans = w.ai_plans();
+    assert_eq!(plans.len(), 2);
+    assert_eq!((plans[0].uid, plans[0].target), (g, Some(p)));
+    assert_eq!(plans[0].path, vec![(3, 0), (4, 0)]);
+    // The chosen tile has the best score of all candidates.
+    assert_eq!(plans[0].scores.iter().map(|s| s.1).min(), Some(1));
+    assert!(plans[0].scores.contains(&((4, 0), 1)));
+    assert_eq!((plans[1].target, plans[1].path.len(), plans[1].scores.len()), (None, 0, 0));
+    assert_eq!(input::KeyBindings::default().get("toggle_ai_debug"), Some(raylib::ffi::KeyboardKey::KEY_F3));
+}
+
+#[test]
+fn behavior_trees() {
+    use ai::TreeSet;
+    let text = "[types]\n0 = \"root\"\n\n[node.root]\nkind = \"selector\"\nchildren = [\"flee\", \"fight\"]\n\n\
+        [node.flee]\nkind = \"sequence\"\nchildren = [\"hurt\", \"away\"]\n\n[node.hurt]\nkind = \"health_below\"\nratio = 0.5\n\n\
+        [node.away]\nkind = \"retreat\"\n\n[node.fight]\nkind = \"sequence\"\nchildren = [\"close\", \"hit\"]\n\n\
+        [node.close]\nkind = \"approach\"\n\n[node.hit]\nkind = \"attack\"  # the weakest in range\n";
+    let set = TreeSet::parse(text).unwrap();
+    assert!(TreeSet::parse("[node.a]\nkind = \"invert\"\nchildren = [\"a\"]\n").unwrap_err().contains("own descendants"));
+    assert!(TreeSet::parse("[node.a]\nkind = \"sequence\"\nchildren = [\"b\"]\n").unwrap_err().contains("undefined child b"));
+    assert!(TreeSet::parse("[node.a]\nkind = \"enemy_within\"\n").unwrap_err().contains("missing radius"));
+    assert!(set.tree("nope").is_none());
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let p = world::spawn_unit(&mut w, 0, (5, 0), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (2, 0), -1, false);
+    assert!(world::set_type_behavior_tree(&mut w, 0, set.tree("root")));
+    let mut ovec = vec![];
+    world::behavior_orders(&mut w, &mut ovec);
+    assert_eq!(w.ai_plans()[0].path, vec![(3, 0), (4, 0)]);
+    assert_eq!(w.ai_plans()[0].target, Some(p));
+    // Once hurt, the unit backs away from the player unit instead.
+    w.units.get_mut(&e).unwrap().health = 2.0;
+    world::behavior_orders(&mut w, &mut vec![]);
+    assert_eq!(w.ai_plans()[0].path, vec![(1, 0), (0, 0)]);
+    assert_eq!(w.ai_plans()[0].target, None);
+    // A tree assigned by the map takes precedence.
+    let path = std::env::temp_dir().join(format!("alesia_ai_{}.toml", std::process::id()));
+    std::fs::write(&path, "[types]\n0 = \"idle\"\n[node.idle]\nkind = \"wait\"\n").unwrap();
+    assert!(w.load_ai(path.to_str().unwrap()));
+    assert!(!w.load_ai("/nonexistent/ai.toml"));
+    world::behavior_orders(&mut w, &mut vec![]);
+    assert_eq!(w.ai_plans()[0].path, vec![]);
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn influence_map() {
+    use world::influence;
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 1, 2, 1.5);
+    ut.set_combat_stats(4.0, 1.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let p = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (6, 0), -1, false);
+    world::spawn_unit(&mut w, 0, (6, 2), -1, false);
+    assert_eq!(influence::strength(&w, p), 4.0);
+    w.units.get_mut(&e).unwrap().health = 5.0;
+    assert_eq!(influence::strength(&w, e), 2.0);
+    // Move one tile, then shoot two tiles.
+    assert!(influence::reach(&w, p).contains(&(3, 0)));
+    assert!(!influence::reach(&w, p).contains(&(4, 0)));
+    let m = influence::compute(&w, true);
+    assert_eq!(m.threat((4, 1)), 6.0);
+    assert_eq!(m.threat((3, 0)), 2.0);
+    assert_eq!(m.threat((0, 0)), 0.0);
+    assert_eq!(m.support((3, 0)), 4.0);
+    assert_eq!(m.max_threat(), 6.0);
+    assert_eq!(influence::compute(&w, false).threat((3, 0)), 4.0);
+    let (mut t, mut s) = (0.0, 0.0);
+    assert!(napi::alsnGetInfluence(&w, 4, 1, true, &mut t, &mut s));
+    assert_eq!((t, s), (6.0, 0.0));
+}
+
+#[test]
+fn time_sliced_ai() {
+    use world::Behavior;
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    world::spawn_unit(&mut w, 0, (5, 0), -1, true);
+    for x in 0..3 {
+        let e = world::spawn_unit(&mut w, 0, (x, 4), -1, false);
+        world::set_behavior(&mut w, e, Some(Behavior::Guard { tile: (x, 2), radius: 1 }));
+    }
+    let mut whole = vec![];
+    world::behavior_orders(&mut w, &mut whole);
+    let plans = w.ai_plans().to_vec();
+    // Deciding one unit at a time, as across frames, gives the same orders.
+    let mut sliced = vec![];
+    let mut job = world::AiJob::new(&mut w, &sliced);
+    for n in 1..=3 {
+        assert!(job.step(&mut w, &mut sliced));
+        assert_eq!(w.ai_plans().len(), n);
+    }
+    assert!(!job.step(&mut w, &mut sliced));
+    assert_eq!(format!("{:?}", sliced), format!("{:?}", whole));
+    assert_eq!(w.ai_plans(), &plans[..]);
+    let s = utils::Settings::parse("[display]\nai_budget_ms = 2.5\n");
+    assert_eq!(s.ai_budget_ms, 2.5);
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    assert_eq!(utils::Settings::parse("[display]\nai_budget_ms = -1\n").ai_budget_ms, 0.0);
+}
+
+#[test]
+fn turn_timer() {
+    use input::TurnTimer;
+    let mut t = TurnTimer::new(10.0, 25.0, 2.0, false);
+    assert_eq!(t.turn_left(), Some(10.0));
+    assert!(!t.tick(4.0));
+    assert_eq!(t.turn_left(), Some(6.0));
+    assert_eq!(t.bank_left(), Some(23.0));
+    // Expiry is reported once per turn.
+    assert!(t.tick(6.0));
+    assert!(!t.tick(1.0));
+    assert!(!t.is_bank_spent());
+    // The next turn starts with a full turn clock, and the increment added to the bank.
+    t.end_turn();
+    assert!(!t.tick(0.0));
+    assert_eq!((t.turn_left(), t.bank_left()), (Some(10.0), Some(19.0)));
+    assert!(!t.tick(9.0));
+    t.end_turn();
+    assert!(!t.tick(9.0));
+    assert!(t.tick(3.0));
+    assert!(t.is_bank_spent());
+    // Once the bank is spent, later turns run out at once.
+    t.end_turn();
+    assert!(t.tick(0.0));
+    let none = TurnTimer::new(0.0, -5.0, 1.0, true);
+    assert!(!none.is_enabled());
+    assert_eq!((none.turn_left(), none.bank_left()), (None, None));
+    assert!(!TurnTimer::default().tick(100.0));
+}
+
+#[test]
+fn puzzle() {
+    use puzzle::{Puzzle, PuzzleStep};
+    use world::ObjectiveState;
+    let step = |unit, x, y, target| PuzzleStep { unit: unit, tile: (x, y), target: target };
+    let mut p = Puzzle::parse("moves 3\n\nstep 1 2 2\n# Then strike.\nstep 2 4 3 9\n").unwrap();
+    assert_eq!(p.moves_left(), Some(3));
+    assert_eq!(p.act(step(1, 2, 2, None)), ObjectiveState::Pending);
+    assert_eq!(p.next_step(), Some(&step(2, 4, 3, Some(9))));
+    // Any other action fails the puzzle, and later actions are ignored.
+    assert_eq!(p.act(step(2, 4, 3, None)), ObjectiveState::Failed);
+    assert_eq!(p.act(step(2, 4, 3, Some(9))), ObjectiveState::Failed);
+    assert_eq!((p.steps_done(), p.moves_used()), (1, 2));
+    assert!(Puzzle::parse("step 1 2\n").is_err());
+    assert!(Puzzle::parse("# Nothing to solve.\n").is_err());
+    // Without steps, the puzzle is settled by the battle, once its moves are spent.
+    let mut free = Puzzle::new(vec![], Some(1));
+    assert_eq!(free.act(step(3, 0, 0, None)), ObjectiveState::Pending);
+    let mut won = free.clone();
+    assert_eq!(won.settle(true), ObjectiveState::Completed);
+    assert_eq!(free.settle(false), ObjectiveState::Failed);
+    // Progress is kept in snapshots.
+    let mut w = world::World::blank();
+    let path = std::env::temp_dir().join(format!("alesia_puzzle_{}.txt", std::process::id()));
+    std::fs::write(&path, "step 1 2 2\nstep 2 4 3 9\n").unwrap();
+    assert!(w.load_puzzle(path.to_str().unwrap()));
+    assert!(!w.load_puzzle("/nonexistent/puzzle.txt"));
+    w.puzzle.as_mut().unwrap().act(step(1, 2, 2, None));
+    let text = save::snapshot(&w, input::TurnPhase::PlayerTurn);
+    assert!(w.load_puzzle(path.to_str().unwrap()));
+    assert_eq!(w.puzzle().unwrap().steps_done(), 0);
+    save::restore(&mut w, &text).unwrap();
+    assert_eq!(w.puzzle().unwrap().steps_done(), 1);
+    assert_eq!(w.puzzle().unwrap().state(), ObjectiveState::Pending);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn map_loaded_hook() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let mut bytes = vec![0xfa, 0xde, 0x00, 0xff, 2, 2, 0, 0, 1, 2, 3, 4];
+    for s in ["Ford", "Hold the ford.", "Won", "Lost"].iter() {
+        bytes.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(s.as_bytes());
+    }
+    let path = std::env::temp_dir().join(format!("alesia_map_{}.alw", std::process::id()));
+    std::fs::write(&path, &bytes).unwrap();
+    let mut w = world::World::blank();
+    assert!(world::load_world(&mut w, path.to_str().unwrap()));
+    let seen = Rc::new(RefCell::new(None));
+    let mut sl = utils::StateListener::new();
+    let s = seen.clone();
+    sl.bind_map_loaded(move |w, m| {
+        *s.borrow_mut() = Some(m.clone());
+        w.set_difficulty(world::Difficulty::HARD);
+    });
+    // The display notifies the listener once, before the intro.
+    let m = w.take_loaded_map().unwrap();
+    sl.notify_map_loaded(&mut w, &m);
+    assert!(w.take_loaded_map().is_none());
+    let m = seen.borrow().clone().unwrap();
+    assert_eq!((m.title.as_str(), m.intro_text.as_str(), m.width, m.height), ("Ford", "Hold the ford.", 2, 2));
+    assert_eq!(m.path, path.to_str().unwrap());
+    assert_eq!(w.difficulty(), world::Difficulty::HARD);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn pickups() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use world::pickup::{self, PickupEffect};
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5);
+    ut.set_combat_stats(4.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (3, 0), -1, false);
+    pickup::place(&mut w, 0xe1, (0, 0), PickupEffect::Boost(0.5));
+    pickup::place(&mut w, 0xe1, (0, 0), PickupEffect::Heal(0.5));
+    assert_eq!(w.pickups().len(), 1);
+    assert!(!napi::alsnPlacePickup(&mut w, 0xe2, 3, 0, 7, 1.0));
+    assert!(napi::alsnPlacePickup(&mut w, 0xe2, 3, 0, 2, 40.0));
+    pickup::place(&mut w, 0xe1, (5, 5), PickupEffect::Boost(0.25));
+    // Healing is capped at the maximum health.
+    w.units.get_mut(&a).unwrap().health = 7.0;
+    let got = Rc::new(Cell::new(None));
+    let g = got.clone();
+    let mut sl = utils::StateListener::new();
+    sl.bind_pickup(move |_, uid, e| g.set(Some((uid, e))));
+    let e = pickup::collect(&mut w, a).unwrap();
+    sl.notify_pickup(&mut w, a, e);
+    assert_eq!(got.get(), Some((a, PickupEffect::Heal(0.5))));
+    assert_eq!(w.events[0], world::BattleEvent::Pickup(a, e));
+    assert_eq!(w.units[&a].health, 10.0);
+    assert!(pickup::at(&w, (0, 0)).is_none());
+    // Gold only counts for player units; enemies just take the pickup away.
+    assert_eq!(pickup::collect(&mut w, b), Some(PickupEffect::Gold(40)));
+    assert_eq!(w.profile.get("gold"), 0);
+    w.units.get_mut(&b).unwrap().wpos = raylib::math::Vector2::new(5.0, 5.0);
+    let dmg = world::default_damage(&w, b, a);
+    assert_eq!(pickup::collect(&mut w, b), Some(PickupEffect::Boost(0.25)));
+    assert_eq!(world::describe_event(&w, &w.events[2]), "Archer gains [green]25%[/green] attack");
+    assert!(world::default_damage(&w, b, a) > dmg);
+    pickup::place(&mut w, 0xe2, (0, 0), PickupEffect::Gold(15));
+    assert_eq!(pickup::collect(&mut w, a), Some(PickupEffect::Gold(15)));
+    assert_eq!(w.profile.get("gold"), 15);
+    pickup::place(&mut w, 0xe0, (2, 2), PickupEffect::Heal(0.25));
+    let text = save::snapshot(&w, input::TurnPhase::PlayerTurn);
+    assert!(napi::alsnRemovePickup(&mut w, 2, 2));
+    assert!(!napi::alsnRemovePickup(&mut w, 2, 2));
+    w.units.get_mut(&b).unwrap().boost = 0.0;
+    assert!(save::restore(&mut w, &text).is_ok());
+    assert_eq!(pickup::at(&w, (2, 2)).map(|p| (p.tex_id, p.effect)), Some((0xe0, PickupEffect::Heal(0.25))));
+    assert_eq!(w.units[&b].boost, 0.25);
+}
+
+#[test]
+fn tweens() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use tween::{Easing, Tween};
+    for e in (0..7).map(|v| Easing::from_u8(v).unwrap()) {
+        assert_eq!((e.apply(0.0), e.apply(1.0)), (0.0, 1.0));
+    }
+    assert!(Easing::from_u8(7).is_none());
+    assert!(Easing::BackOut.apply(0.8) > 1.0);
+    let mut w = world::World::blank();
+    let fired = Rc::new(Cell::new(0));
+    let f = fired.clone();
+    let a = w.tween_then(Tween::new(10.0, 20.0, 1.0, Easing::Linear), move |w, id| {
+        f.set(id);
+        w.profile.incr("tweens", 1);
+    });
+    let b = w.tween(Tween::new(0.0, 1.0, 5.0, Easing::QuadIn));
+    w.update_tweens(0.5);
+    assert_eq!(w.tween_value(a), Some(15.0));
+    assert!((w.tween_value(b).unwrap() - 0.01).abs() < 1e-6);
+    w.update_tweens(0.6);
+    assert_eq!((fired.get(), w.profile.get("tweens")), (a, 1));
+    assert_eq!(w.tween_value(a), None);
+    assert!(w.cancel_tween(b));
+    assert!(!w.cancel_tween(b));
+    // Camera pans end exactly on the target, and stop following units.
+    w.cam_follow = Some(1);
+    w.pan_camera(4.0, -2.0, 1.0, Easing::Smooth);
+    assert_eq!(w.cam_follow, None);
+    w.update_tweens(0.5);
+    assert_eq!((w.cam_wx, w.cam_wy), (2.0, -1.0));
+    w.update_tweens(0.75);
+    assert_eq!((w.cam_wx, w.cam_wy), (4.0, -2.0));
+    assert!(!w.is_panning());
+    extern "C" fn done(w: *mut world::World, id: u32, ctx: *mut std::ffi::c_void) {
+        unsafe { *(ctx as *mut u32) = id };
+        assert!(!w.is_null());
+    }
+    let mut got = 0u32;
+    assert_eq!(napi::alsnAddTween(&mut w, 0.0, 1.0, 1.0, 9, Some(done), std::ptr::null_mut()), 0);
+    let id = napi::alsnAddTween(&mut w, 0.0, 1.0, 0.5, 2, Some(done), &mut got as *mut u32 as *mut _);
+    let mut v = -1.0;
+    assert!(napi::alsnGetTweenValue(&w, id, &mut v));
+    assert_eq!(v, 0.0);
+    w.update_tweens(0.5);
+    assert_eq!(got, id);
+    assert!(!napi::alsnGetTweenValue(&w, id, &mut v));
+}
+
+#[test]
+fn scheduler() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let mut w = world::World::blank();
+    let seen = Rc::new(RefCell::new(vec![]));
+    let s = seen.clone();
+    let once = w.schedule_after(1.0, move |_, id| s.borrow_mut().push(id));
+    let s = seen.clone();
+    let every = w.schedule_every(0.5, move |w, id| {
+        s.borrow_mut().push(id);
+        // Repeating callbacks may cancel themselves, and schedule others.
+        if s.borrow().len() == 4 {
+            assert!(w.cancel_scheduled(id));
+            let s2 = s.clone();
+            w.schedule_after(0.0, move |_, id| s2.borrow_mut().push(id));
+        }
+    });
+    assert_eq!(w.scheduled_time_left(once), Some(1.0));
+    schedule::run(&mut w, 0.4);
+    assert!(seen.borrow().is_empty());
+    schedule::run(&mut w, 0.2);
+    assert_eq!(*seen.borrow(), vec![every]);
+    assert!((w.scheduled_time_left(every).unwrap() - 0.4).abs() < 1e-5);
+    schedule::run(&mut w, 0.45);
+    assert_eq!(*seen.borrow(), vec![every, once, every]);
+    assert_eq!(w.scheduled_time_left(once), None);
+    assert!(!w.cancel_scheduled(once));
+    schedule::run(&mut w, 0.5);
+    assert_eq!(seen.borrow().len(), 4);
+    assert_eq!(w.scheduled_time_left(every), None);
+    schedule::run(&mut w, 0.01);
+    assert_eq!(seen.borrow().len(), 5);
+    extern "C" fn tick(_: *mut world::World, _: u32, ctx: *mut std::ffi::c_void) {
+        unsafe { *(ctx as *mut u32) += 1 };
+    }
+    let mut n = 0u32;
+    assert_eq!(napi::alsnScheduleEvery(&mut w, 0.1, None, std::ptr::null_mut()), 0);
+    let id = napi::alsnScheduleEvery(&mut w, 0.1, Some(tick), &mut n as *mut u32 as *mut _);
+    // Callbacks run at most once per frame, however long it is.
+    schedule::run(&mut w, 0.35);
+    schedule::run(&mut w, 0.0);
+    assert_eq!(n, 2);
+    assert!(napi::alsnCancelScheduled(&mut w, id));
+    schedule::run(&mut w, 1.0);
+    assert_eq!(n, 2);
+}
+
+#[test]
+fn event_bus() {
+    use event::EngineEvent;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let mut w = world::World::blank();
+    let mut sl = utils::StateListener::new();
+    let seen = Rc::new(RefCell::new(vec![]));
+    let s = seen.clone();
+    let sub = sl.subscribe(move |_, e| s.borrow_mut().push(e.clone()));
+    // Events are not queued until the queue is enabled.
+    sl.notify_unit_death(&mut w, 3, 1);
+    assert_eq!(w.poll_event(), None);
+    w.enable_event_queue(true);
+    sl.notify_attack(&mut w, 1, 2, 4.5);
+    sl.notify_phase_change(&mut w, input::TurnPhase::EnemyTurn);
+    assert_eq!(*seen.borrow(), vec![
+        EngineEvent::UnitDied { uid: 3, type_id: 1 },
+        EngineEvent::UnitAttacked { attacker: 1, defender: 2, damage: 4.5 },
+        EngineEvent::PhaseChanged(input::TurnPhase::EnemyTurn)
+    ]);
+    assert!(sl.unsubscribe(sub));
+    assert!(!sl.unsubscribe(sub));
+    sl.notify_timeout(&mut w, true);
+    assert_eq!(seen.borrow().len(), 3);
+    let mut raw = event::RawEvent::default();
+    assert!(napi::alsnPollEvent(&mut w, &mut raw));
+    assert_eq!(raw, event::RawEvent { kind: 1, uid: 1, arg: 2, value: 4.5, x: 0, y: 0 });
+    assert_eq!(w.poll_event(), Some(EngineEvent::PhaseChanged(input::TurnPhase::EnemyTurn)));
+    assert_eq!(w.poll_event().map(|e| e.to_raw().kind), Some(6));
+    assert_eq!(w.poll_event(), None);
+    // The oldest events are dropped once the queue is full.
+    for i in 0..event::QUEUE_CAPACITY + 1 {
+        sl.notify_unit_death(&mut w, (i % 256) as u8, 0);
+    }
+    assert_eq!(w.poll_event(), Some(EngineEvent::UnitDied { uid: 1, type_id: 0 }));
+    assert!(napi::alsnEnableEventQueue(&mut w, false));
+    assert!(!napi::alsnPollEvent(&mut w, &mut raw));
+}
+
+#[test]
+fn event_json() {
+    use event::EngineEvent;
+    use input::Order;
+    assert_eq!(EngineEvent::UnitDied { uid: 3, type_id: 0 }.to_string(), r#"{"event":"UnitDied","uid":3,"type_id":0}"#);
+    assert_eq!(EngineEvent::UnitAttacked { attacker: 1, defender: 2, damage: f32::NAN }.to_string(), r#"{"event":"UnitAttacked","attacker":1,"defender":2,"damage":null}"#);
+    assert_eq!(EngineEvent::UnitMoved { uid: 1, tile: (-2, 5) }.to_string(), r#"{"event":"UnitMoved","uid":1,"tile":[-2,5]}"#);
+    assert_eq!(EngineEvent::PhaseChanged(input::TurnPhase::EnemyTurn).to_string(), r#"{"event":"PhaseChanged","phase":"EnemyTurn"}"#);
+    assert_eq!(EngineEvent::MapLoaded(String::from("maps\\a \"b\"\n")).to_string(), r#"{"event":"MapLoaded","path":"maps\\a \"b\"\n"}"#);
+    assert_eq!(EngineEvent::ResourceLoaded { id: 4, kind: utils::ResType::Snd, ok: false }.to_string(), r#"{"event":"ResourceLoaded","id":4,"kind":"sound","ok":false}"#);
+    assert_eq!(EngineEvent::Exit.to_string(), r#"{"event":"Exit"}"#);
+    assert_eq!(Order::ATTACK(1, 2, 3, 4).to_string(), r#"{"order":"ATTACK","uid":1,"target":2,"tile":[3,4]}"#);
+    assert_eq!(Order::MutHealthR(5, 0.25).to_string(), r#"{"order":"MutHealthR","uid":5,"value":0.25}"#);
+    assert_eq!(Order::VICTORY.to_string(), r#"{"order":"VICTORY"}"#);
+    let mut w = world::World::blank();
+    let mut sl = utils::StateListener::new();
+    let out = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    struct Shared(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+    impl std::io::Write for Shared {
+        fn write(&mut self, b: &[u8]) -> std::io::Result<usize> { self.0.borrow_mut().write(b) }
+        fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+    }
+    sl.subscribe(event::json_lines(Shared(out.clone())));
+    w.enable_event_queue(true);
+    sl.notify_timeout(&mut w, false);
+    sl.notify_results(&mut w, input::Outcome::Victory);
+    assert_eq!(String::from_utf8(out.borrow().clone()).unwrap(), "{\"event\":\"TurnTimedOut\",\"bank_spent\":false}\n{\"event\":\"ResultsDismissed\",\"outcome\":\"Victory\"}\n");
+    let s = napi::alsnPollEventJson(&mut w);
+    assert_eq!(unsafe { std::ffi::CStr::from_ptr(s) }.to_str().unwrap(), r#"{"event":"TurnTimedOut","bank_spent":false}"#);
+    napi::alsnFreeString(s);
+}
+
+#[test]
+fn debug_console() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, String::from("Scout"), 10.0, 0.2, 3, 1, 1.0), 2);
+    let mut is = input::InputHandler::new();
+    let mut c = console::Console::default();
+    let run = |c: &mut console::Console, w: &mut world::World, is: &mut input::InputHandler, line: &str| {
+        c.input = line.to_string();
+        c.submit(w, is);
+        c.output.back().cloned().unwrap_or_default()
+    };
+    assert_eq!(run(&mut c, &mut w, &mut is, "spawn 9 1 1"), "error: no unit type with id 9");
+    assert!(run(&mut c, &mut w, &mut is, "spawn 2 1 1").starts_with("spawned unit"));
+    let uid = world::unit_at(&w, 1, 1).unwrap();
+    assert!(w.units[&uid].player);
+    assert!(run(&mut c, &mut w, &mut is, "spawn 2 1 1 enemy").contains("taken by unit"));
+    assert_eq!(run(&mut c, &mut w, &mut is, "spawn 2 1"), "error: usage: spawn <type> <x> <y> [enemy]");
+    run(&mut c, &mut w, &mut is, &format!("health {} 25", uid));
+    assert_eq!(w.units[&uid].health, 10.0);
+    run(&mut c, &mut w, &mut is, &format!("teleport {} 4 -1", uid));
+    assert_eq!(world::unit_tile(&w, uid), Some((1, 1)));
+    run(&mut c, &mut w, &mut is, &format!("teleport {} 4 2", uid));
+    assert_eq!(world::unit_tile(&w, uid), Some((4, 2)));
+    run(&mut c, &mut w, &mut is, "reveal");
+    assert!(is.show_danger);
+    // The game begins with the intro, in which the turn cannot be ended.
+    assert!(run(&mut c, &mut w, &mut is, "end_turn").starts_with("error"));
+    assert!(run(&mut c, &mut w, &mut is, "fly").starts_with("error: unknown command"));
+    c.input = String::from("te");
+    c.complete();
+    assert_eq!(c.input, "teleport ");
+    c.input = String::from("h");
+    c.complete();
+    assert_eq!(c.input, "he");
+    assert_eq!(c.output.back().unwrap(), "health  help");
+    c.input.clear();
+    c.recall(true);
+    assert_eq!(c.input, "fly");
+    c.recall(true);
+    assert_eq!(c.input, "end_turn");
+    c.recall(false);
+    c.recall(false);
+    assert_eq!(c.input, "");
+}
+
+#[cfg(feature = "debug_tools")]
+#[test]
+fn debug_tools() {
+    use debug_tools::Cheat;
+    use input::Order;
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, String::from("Scout"), 10.0, 0.2, 2, 1, 0.0);
+    ut.set_combat_stats(2.0, 0.0);
+    world::register_unit_type(&mut w, ut, 2);
+    let p = world::spawn_unit(&mut w, 2, (1, 1), -1, true);
+    let e = world::spawn_unit(&mut w, 2, (2, 1), -0x38ffc328, false);
+    let attack = |w: &mut world::World| {
+        let mut next = None;
+        let o = Order::ATTACK(e, p, 2, 1);
+        while world::order_pending(&o, w, &mut next) {}
+        w.units[&p].health
+    };
+    debug_tools::set_cheat(&mut w, Cheat::GodMode, true);
+    assert_eq!(attack(&mut w), 10.0);
+    debug_tools::set_cheat(&mut w, Cheat::GodMode, false);
+    assert!(attack(&mut w) < 10.0);
+
+    let reach = world::reachable_tiles(&w, p).len();
+    debug_tools::set_cheat(&mut w, Cheat::InfiniteMovement, true);
+    assert!(world::reachable_tiles(&w, p).len() > reach);
+    // Enemy units move as usual.
+    assert_eq!(world::_guinfo(&w, &w.units[&e]).0, 2);
+    assert_eq!(world::_guinfo(&w, &w.units[&p]).0, debug_tools::INFINITE_MOVEMENT);
+    assert_eq!(Cheat::from_u8(3), None);
+
+    let mut is = input::InputHandler::new();
+    let mut sl = utils::StateListener::new();
+    // Units without animations cannot be updated, so end an empty game.
+    let mut empty = world::World::blank();
+    debug_tools::instant_win(&mut empty);
+    is.simulate(&mut empty, &mut sl, 0.0);
+    assert_eq!(is.get_state(), 5);
+
+    let mut c = console::Console::default();
+    c.input = String::from("reveal_all");
+    c.submit(&mut w, &mut is);
+    assert!(debug_tools::cheat(&w, Cheat::Reveal));
+    c.open = true;
+    c.input = String::from("lose");
+    c.submit(&mut w, &mut is);
+    assert!(!c.open);
+    assert_eq!(debug_tools::take_outcome(&mut w), Some(input::Outcome::Defeat));
+    assert_ne!(capabilities() & CAP_DEBUG_TOOLS, 0);
+}
+
+#[test]
+fn replay_determinism() {
+    use input::{Order, TurnPhase};
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, String::from("Scout"), 10.0, 3.0, 3, 1, 0.4);
+    ut.set_combat_stats(3.0, 1.0);
+    for i in 0..9 {
+        ut.def_anim_muted((32, 32), 2, (0, 32 * i), 4.0, false);
+    }
+    world::register_unit_type(&mut w, ut, 0);
+    let p = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (4, 1), -0x38ffc328, false);
+
+    // Play two turns with recording on, hashing the world at the end of each.
+    let mut is = input::InputHandler::new();
+    is.replay = Some(replay::Replay { sim_fps: 30, start: String::new(), phases: vec![] });
+    let mut sl = utils::StateListener::new();
+    let turns = vec![
+        (vec![Order::MOVE(p, 2, 1), Order::MOVE(p, 3, 1), Order::ATTACK(p, e, 3, 1)], vec![Order::ATTACK(e, p, 4, 1), Order::MutHealthR(e, 0.25)]),
+        (vec![Order::ATTACK(p, e, 3, 1)], vec![Order::MOVE(e, 4, 2)])
+    ];
+    let mut live = vec![];
+    for (player, enemy) in turns {
+        for (orders, en) in [(player, false), (enemy, true)] {
+            is.carry_out(orders, en);
+            while is.is_simulating() {
+                is.simulate(&mut w, &mut sl, 1.0 / 30.0);
+            }
+        }
+        live.push(world::state_hash(&w));
+    }
+    assert_eq!(w.turn(), 3);
+    assert!(w.units[&e].health < 10.0);
+
+    let r = is.replay.take().unwrap();
+    assert_eq!(r.phases.len(), 4);
+    assert_eq!(r.phases[1], (TurnPhase::EnemyTurn, vec![Order::ATTACK(e, p, 4, 1), Order::MutHealthR(e, 0.25)]));
+    let text = r.to_string();
+    assert!(text.starts_with("alesia-replay 1\nrate 30\n"));
+    let r = replay::Replay::parse(&text).unwrap();
+    assert_eq!(replay::run(&mut w, &r), Ok(live.clone()));
+    assert_eq!(replay::run(&mut w, &r), Ok(live));
+    assert_eq!(w.turn(), 3);
+
+    // A replay ending within a turn is hashed at the end.
+    let mut half = r.clone();
+    half.phases.truncate(1);
+    assert_eq!(replay::run(&mut w, &half).map(|h| h.len()), Ok(1));
+    assert!(replay::Replay::parse(&text.replace("ATTACK", "FLY")).unwrap_err().contains("invalid order"));
+}
+
+#[test]
+fn state_diff() {
+    use world::Divergence;
+    let setup = || {
+        let mut w = world::World::blank();
+        world::register_unit_type(&mut w, world::UnitType::new(0, String::from("Scout"), 10.0, 0.2, 3, 1, 1.0), 0);
+        let p = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+        let e = world::spawn_unit(&mut w, 0, (4, 2), -0x38ffc328, false);
+        (w, p, e)
+    };
+    let ((mut a, p, e), (mut b, ..)) = (setup(), setup());
+    assert_eq!(world::state_hash(&a), world::state_hash(&b));
+    assert!(world::diff(&a, &b).is_empty());
+
+    // A difference in rounding alone is found.
+    let ulp = f32::from_bits(0.3f32.to_bits() + 1);
+    a.units.get_mut(&p).unwrap().health = ulp;
+    b.units.get_mut(&p).unwrap().health = 0.3;
+    b.units.get_mut(&e).unwrap().wpos.x = 5.0;
+    let n = world::spawn_unit(&mut b, 0, (6, 6), -1, true);
+    world::pickup::place(&mut a, 0, (2, 2), world::pickup::PickupEffect::Gold(5));
+    assert_ne!(world::state_hash(&a), world::state_hash(&b));
+    let mut expected = vec![
+        (p, Divergence::UnitField(p, "health", ulp, 0.3)),
+        (e, Divergence::UnitField(e, "x", 4.0, 5.0)),
+        (n, Divergence::Unit(n, false))
+    ];
+    expected.sort_by_key(|(id, _)| *id);
+    let mut expected: Vec<Divergence> = expected.into_iter().map(|(_, d)| d).collect();
+    expected.insert(0, Divergence::Pickup((2, 2)));
+    assert_eq!(world::diff(&a, &b), expected);
+    let text = napi::alsnStateDiff(&a, &b);
+    let s = unsafe { std::ffi::CStr::from_ptr(text) }.to_str().unwrap().to_string();
+    napi::alsnFreeString(text);
+    assert!(s.lines().any(|l| l == format!("unit {} x 4 != 5", e)));
+    assert_eq!(napi::alsnStateHash(&a), world::state_hash(&a));
+}
+
+#[test]
+fn memory_report() {
+    use utils::{MemoryReport, ResourceUsage, ResType, ResourceSet};
+    // 64x64 RGBA8, then with all 7 mipmap levels down to 1x1.
+    assert_eq!(utils::texture_bytes(64, 64, 7, 1), 16384);
+    assert_eq!(utils::texture_bytes(64, 64, 7, 7), 4 * (4096 + 1024 + 256 + 64 + 16 + 4 + 1));
+    assert_eq!(utils::fmt_bytes(512), "512 B");
+    assert_eq!(utils::fmt_bytes(3 << 19), "1.50 MiB");
+
+    let mut rs = ResourceSet::new();
+    rs.map_texture(0, "tiles.png");
+    rs.set_memory_budget(1 << 20);
+    let r = rs.report();
+    assert!(r.entries.is_empty());
+    assert_eq!(r.budget, 1 << 20);
+    assert!(!r.over_budget());
+
+    let usage = |id, kind, bytes| ResourceUsage { id: id, kind: kind, path: String::new(), bytes: bytes, detail: String::new() };
+    let mut r = MemoryReport {
+        entries: vec![usage(0, ResType::Tex, 1 << 20), usage(1, ResType::Fnt, 1 << 10), usage(2, ResType::Mus, 8 << 20)],
+        budget: 1 << 20
+    };
+    assert_eq!(r.video_memory(), (1 << 20) + (1 << 10));
+    assert!(r.over_budget());
+    let text = r.to_string();
+    assert!(text.contains("total music: 8.00 MiB"));
+    assert!(text.ends_with("video memory: 1.00 MiB of 1.00 MiB budget, OVER BUDGET"));
+    r.budget = 0;
+    assert!(!r.over_budget());
+}
+
+#[test]
+fn texture_filters() {
+    use utils::TexFilter;
+    for v in 0..6 {
+        let f = TexFilter::from_u8(v).unwrap();
+        assert_eq!(f as u8, v);
+        assert_eq!(f.to_raylib() as u8, v);
+    }
+    assert_eq!(TexFilter::from_u8(6), None);
+
+    let rs = napi::handles::alshNewResourceSet();
+    let path = std::ffi::CString::new("tiles.png").unwrap();
+    assert!(napi::handles::alshMapTextureEx(rs, 0, path.as_ptr(), 0, true));
+    assert!(!napi::handles::alshMapTextureEx(rs, 1, path.as_ptr(), 6, false));
+    assert!(napi::handles::alshRelease(rs));
+}
+
+#[test]
+fn nine_patch() {
+    let mut rs = utils::ResourceSet::new();
+    rs.map_texture(0xf5, "window.png");
+    assert_eq!(rs.nine_patch(0xf5), None);
+    rs.set_nine_patch(0xf5, 12, 16, 12, -4);
+    assert_eq!(rs.nine_patch(0xf5), Some((12, 16, 12, 0)));
+
+    let h = napi::handles::alshNewResourceSet();
+    assert!(napi::handles::alshSetNinePatch(h, 0xf6, 8, 8, 8, 8));
+    assert!(napi::handles::alshRelease(h));
+    assert!(!napi::handles::alshSetNinePatch(h, 0xf6, 8, 8, 8, 8));
+}
+
+#[test]
+fn ui_anim() {
+    use raylib::math::Rectangle;
+    let mut rs = utils::ResourceSet::new();
+    assert_eq!(rs.ui_anim_frame(0xf1), None);
+    rs.set_ui_anim(0xf1, (32, 16), 4, 8.0);
+    assert_eq!(rs.ui_anim_frame(0xf1), Some(Rectangle::new(0.0, 0.0, 32.0, 16.0)));
+    rs.advance_ui(0.3);
+    assert_eq!(rs.ui_anim_frame(0xf1), Some(Rectangle::new(64.0, 0.0, 32.0, 16.0)));
+    // Loops back to the first frame after half a second.
+    rs.advance_ui(0.25);
+    assert_eq!(rs.ui_anim_frame(0xf1), Some(Rectangle::new(0.0, 0.0, 32.0, 16.0)));
+}
+
+#[test]
+fn screen_transitions() {
+    use display::{Transition, transition_cover};
+    use event::EngineEvent;
+    use input::TurnPhase;
+    assert_eq!(Transition::from_u8(1), Some(Transition::Iris));
+    assert_eq!(Transition::from_u8(3), None);
+    assert_eq!(transition_cover(0.0, 0.6), 0.0);
+    assert!((transition_cover(0.3, 0.6) - 1.0).abs() < 1e-6);
+    assert!(transition_cover(0.15, 0.6) > 0.0 && transition_cover(0.15, 0.6) < 1.0);
+    assert!(transition_cover(0.6, 0.6).abs() < 1e-6);
+    assert_eq!(transition_cover(0.1, 0.0), 0.0);
+    let t = napi::transition_theme(&display::Theme::default(), 2, 1.0, 0x112233ff).unwrap();
+    assert_eq!((t.transition, t.transition_time), (Transition::Slide, 1.0));
+    assert!(napi::transition_theme(&t, 3, 1.0, 0).is_none());
+    assert!(napi::transition_theme(&t, 0, -1.0, 0).is_none());
+
+    let mut w = world::World::blank();
+    w.enable_event_queue(true);
+    let mut sl = utils::StateListener::new();
+    sl.bind_transition_done(|w, p| if p == TurnPhase::PlayerTurn { w.add_objective("Transition done"); });
+    sl.notify_transition_done(&mut w, TurnPhase::PlayerTurn);
+    assert_eq!(w.objectives().len(), 1);
+    let e = w.poll_event().unwrap();
+    assert_eq!(e, EngineEvent::TransitionDone(TurnPhase::PlayerTurn));
+    assert_eq!(e.to_raw().kind, 11);
+    assert_eq!(e.to_string(), r#"{"event":"TransitionDone","phase":"PlayerTurn"}"#);
+}
+
+#[test]
+fn intro_slides() {
+    use display::slide_alpha;
+    assert_eq!(slide_alpha(0.0, 4.0), 0.0);
+    assert_eq!(slide_alpha(0.25, 4.0), 0.5);
+    assert_eq!(slide_alpha(2.0, 4.0), 1.0);
+    assert_eq!(slide_alpha(3.75, 4.0), 0.5);
+    assert_eq!(slide_alpha(1.0, 0.0), 1.0);
+
+    // A 1x1 map with no game objects, followed by a slideshow of two slides.
+    let mut map = vec![0xfa, 0xde, 0x00, 0xff, 1, 1, 0, 0, 0];
+    for s in ["Title", "Intro", "Won", "Lost"] {
+        map.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        map.extend_from_slice(s.as_bytes());
+    }
+    map.extend_from_slice(&[0x51, 0x1d, 2]);
+    for (tex, d, c) in [(7u8, 35u8, "Long ago"), (8, 20, "")] {
+        map.extend_from_slice(&[tex, d]);
+        map.extend_from_slice(&(c.len() as u16).to_be_bytes());
+        map.extend_from_slice(c.as_bytes());
+    }
+    let path = std::env::temp_dir().join(format!("alesia-slides-{}.map", std::process::id()));
+    std::fs::write(&path, &map).unwrap();
+    let mut w = world::World::blank();
+    w.add_intro_slide(1, "replaced by the map", 1.0);
+    assert!(world::load_world(&mut w, path.to_str().unwrap()));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(w.map_title(), "Title");
+    assert_eq!(w.intro_slides(), &[
+        world::Slide { tex: 7, caption: "Long ago".to_string(), duration: 3.5 },
+        world::Slide { tex: 8, caption: String::new(), duration: 2.0 }
+    ]);
+
+    let caption = std::ffi::CString::new("The end").unwrap();
+    assert!(napi::alsnAddIntroSlide(&mut w, 9, caption.as_ptr(), 1.5));
+    assert_eq!(w.intro_slides()[2].caption, "The end");
+    assert!(napi::alsnClearIntroSlides(&mut w));
+    assert!(w.intro_slides().is_empty());
+}
+
+#[test]
+fn scrolling_text() {
+    use display::scroll_done;
+    assert!(!scroll_done(0.0, 600.0, 200.0));
+    assert!(!scroll_done(799.0, 600.0, 200.0));
+    assert!(scroll_done(800.0, 600.0, 200.0));
+
+    let mut w = world::World::blank();
+    assert_eq!(w.scrolling_text(), None);
+    w.show_scrolling_text("Fin", 0.0);
+    assert_eq!(w.scrolling_text().map(|s| s.speed), Some(world::DEFAULT_SCROLL_SPEED));
+    let text = std::ffi::CString::new("[b]Credits[/b]\nEveryone").unwrap();
+    assert!(napi::alsnShowScrollingText(&mut w, text.as_ptr(), 60.0));
+    assert_eq!(w.scrolling_text(), Some(&world::ScrollText { text: "[b]Credits[/b]\nEveryone".to_string(), speed: 60.0 }));
+    assert!(!napi::alsnShowScrollingText(std::ptr::null_mut(), text.as_ptr(), 60.0));
+}
+
+#[test]
+fn unit_portraits() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    assert_eq!(ut.portrait(), None);
+    ut.set_portrait(Some(0x20));
+    let scout = ut.variant("Scout".to_string());
+    assert_eq!(scout.portrait(), Some(0x20));
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (2, 1), -1, true);
+    assert_eq!(world::unit_portrait(&w, a), Some(0x20));
+    assert!(napi::alsnSetUnitPortrait(&mut w, b, 0x21));
+    assert_eq!(world::unit_portrait(&w, b), Some(0x21));
+    assert_eq!(world::unit_portrait(&w, a), Some(0x20));
+    assert!(napi::alsnSetUnitPortrait(&mut w, b, -1));
+    assert_eq!(world::unit_portrait(&w, b), Some(0x20));
+    assert!(!napi::alsnSetUnitPortrait(&mut w, b, 256));
+    assert!(!napi::alsnSetUnitPortrait(&mut w, 99, 1));
+    assert_eq!(world::unit_portrait(&w, 99), None);
+    assert_eq!(display::Theme::default().portrait_size, 96.0);
+}
+
+#[test]
+fn unit_stat_panel() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 3, 1, 0.5);
+    ut.set_combat_stats(4.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let uid = world::spawn_unit(&mut w, 0, (1, 1), -1, false);
+    assert_eq!(world::unit_stats(&w, 99), None);
+    let st = world::unit_stats(&w, uid).unwrap();
+    assert_eq!((st.health, st.max_health, st.attack, st.movement, st.range), (10.0, 10.0, 4.0, 3, 1));
+    assert!(st.statuses.is_empty());
+    assert_eq!(display::stat_text(&st, &[], world::HpRounding::Ceil), "[b]Knight[/b] (enemy)\nHP: [b]10[/b] / 10\nAttack: [b]4.0[/b]\nDefense: [b]2.0[/b]\nMovement: [b]3[/b]\nRange: [b]1[/b]");
+
+    // Stats follow the unit, e.g, once boosted by a pickup.
+    w.units.get_mut(&uid).unwrap().boost = 0.5;
+    w.units.get_mut(&uid).unwrap().health = 7.0;
+    world::set_behavior(&mut w, uid, Some(world::Behavior::Hold));
+    let st = world::unit_stats(&w, uid).unwrap();
+    assert_eq!((st.health, st.attack), (7.0, 6.0));
+    assert_eq!(st.statuses, vec![world::UnitStatus::Boosted, world::UnitStatus::Holding]);
+    let text = display::stat_text(&st, &[Some(0x30), None], world::HpRounding::Ceil);
+    assert!(text.contains("Attack: [b]6.0[/b] [green](+50%)[/green]"));
+    assert!(text.ends_with("\n[yellow]{icon:48}Boosted  Holding[/yellow]"));
+    assert_eq!(world::UnitStatus::from_u8(4), Some(world::UnitStatus::Acted));
+    assert_eq!(world::UnitStatus::from_u8(world::UnitStatus::COUNT as u8), None);
+}
+
+#[test]
+fn find_path() {
+    use world::path;
+    let mut w = world::World::blank();
+    let ut = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    assert_eq!(path::find(&w, (0, 0), (0, 0)), Some(vec![]));
+    assert_eq!(path::find(&w, (0, 0), (0, 3)), Some(vec![(0, 1), (0, 2), (0, 3)]));
+    // Units block the way, and the goal, but not the start.
+    world::spawn_unit(&mut w, 0, (0, 2), -1, false);
+    let r = path::find(&w, (0, 0), (0, 3)).unwrap();
+    assert_eq!(r.len(), 5);
+    assert!(!r.contains(&(0, 2)));
+    assert_eq!(path::find(&w, (0, 0), (0, 2)), None);
+    assert_eq!(path::find(&w, (0, 0), (-1, 0)), None);
+    assert_eq!(world::unit_tile(&w, a), Some((0, 0)));
+
+    let mut out = [0i32; 4];
+    assert_eq!(napi::alsnFindPath(&w, 0, 0, 2, 0, out.as_mut_ptr(), 2), 2);
+    assert_eq!(out, [1, 0, 2, 0]);
+    assert_eq!(napi::alsnFindPath(&w, 0, 0, 0, 2, std::ptr::null_mut(), 0), -1);
+}
+
+#[test]
+fn hp_rounding() {
+    use world::HpRounding;
+    assert_eq!([HpRounding::Ceil.apply(7.3999996), HpRounding::Floor.apply(7.3999996), HpRounding::Round.apply(7.3999996)], [8, 7, 7]);
+    assert_eq!(HpRounding::Ceil.apply(0.1), 1);
+    assert_eq!(HpRounding::Round.apply(2.5), 3);
+    assert_eq!(HpRounding::from_name("Floor"), Some(HpRounding::Floor));
+    assert_eq!(HpRounding::from_u8(3), None);
+
+    let mut w = world::World::blank();
+    let ut = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    world::register_unit_type(&mut w, ut, 0);
+    let uid = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    w.units.get_mut(&uid).unwrap().health = 7.3999996;
+    assert_eq!(w.hp_rounding(), HpRounding::Ceil);
+    assert_eq!(world::unit_hp(&w, uid), Some((8, 10)));
+    assert!(napi::alsnSetHpRounding(&mut w, 1));
+    let (mut h, mut mh) = (0, 0);
+    assert!(napi::alsnGetUnitHp(&w, uid, &mut h, &mut mh));
+    assert_eq!((h, mh), (7, 10));
+    assert!(!napi::alsnSetHpRounding(&mut w, 3));
+    assert!(!napi::alsnGetUnitHp(&w, 99, &mut h, &mut mh));
+    assert_eq!(world::unit_hp(&w, 99), None);
+}
+
+#[test]
+fn popup_kinds() {
+    use world::PopupKind;
+    assert_eq!(PopupKind::from_u8(3), Some(PopupKind::Miss));
+    assert_eq!(PopupKind::from_u8(5), None);
+    assert_eq!(PopupKind::from_name("Crit"), Some(PopupKind::Crit));
+
+    let mut w = world::World::blank();
+    let ut = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    world::register_unit_type(&mut w, ut, 0);
+    let uid = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    w.units.get_mut(&uid).unwrap().health = 5.0;
+    let mut next = None;
+    world::order_pending(&input::Order::MutHealthA(uid, 2.5), &mut w, &mut next);
+    world::order_pending(&input::Order::MutHealthR(uid, -0.2), &mut w, &mut next);
+    let text = std::ffi::CString::new("POISONED").unwrap();
+    assert!(napi::alsnShowPopup(&mut w, uid, text.as_ptr(), 2));
+    assert!(!napi::alsnShowPopup(&mut w, uid, text.as_ptr(), 5));
+    assert!(!napi::alsnShowPopup(&mut w, 99, text.as_ptr(), 2));
+    w.show_popup(99, "MISS", PopupKind::Miss);
+
+    let mut is = input::InputHandler::new();
+    is.update_popups(&mut w, 0.0);
+    let shown: Vec<(&str, PopupKind)> = is.popups.iter().map(|p| (p.text.as_str(), p.kind)).collect();
+    assert_eq!(shown, vec![("+3", PopupKind::Heal), ("2", PopupKind::Damage), ("POISONED", PopupKind::Status)]);
+    assert!(is.popups.iter().all(|p| p.tile == (1, 1)));
+    assert!(w.presentation.popups.is_empty());
+}
+
+#[test]
+fn save_state() {
+    let mut map = vec![0xfa, 0xde, 0x00, 0xff, 2, 1, 0, 0, 3, 4];
+    for s in ["Title", "Intro", "Won", "Lost"] {
+        map.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        map.extend_from_slice(s.as_bytes());
+    }
+    let path = std::env::temp_dir().join(format!("alesia-state-{}.map", std::process::id()));
+    std::fs::write(&path, &map).unwrap();
+    let mut w = world::World::blank();
+    assert!(world::load_world(&mut w, path.to_str().unwrap()));
+    std::fs::remove_file(&path).unwrap();
+    let ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5);
+    world::register_unit_type(&mut w, ut.clone(), 0);
+    let a = world::spawn_unit(&mut w, 0, (1, 0), -1, true);
+    w.units.get_mut(&a).unwrap().health = 6.5;
+
+    let text = save::snapshot_turn(&w, input::TurnPhase::PlayerTurn, &[a, 99]);
+    assert!(text.starts_with("alesia-save 2\n"));
+    assert!(text.contains("\ntiles 2 1 0304\n"));
+    assert_eq!(save::restore_turn(&mut w, &text), Ok((input::TurnPhase::PlayerTurn, vec![a])));
+    assert_eq!(save::restore(&mut w, &text.replacen("alesia-save 2", "alesia-save 1", 1)), Ok(input::TurnPhase::PlayerTurn));
+    let mut other = world::World::blank();
+    world::register_unit_type(&mut other, ut, 0);
+    assert!(save::restore(&mut other, &text).is_err());
+
+    let file = std::env::temp_dir().join(format!("alesia-state-{}.sav", std::process::id()));
+    let file = file.to_str().unwrap();
+    // The units that have acted, as kept by the input handler, are saved with the rest.
+    w.acted = vec![a];
+    assert_eq!(world::save_state(&w, file), Ok(()));
+    w.units.get_mut(&a).unwrap().health = 1.0;
+    assert_eq!(world::load_state(&mut w, file), Ok(input::TurnPhase::PlayerTurn));
+    std::fs::remove_file(file).unwrap();
+    assert_eq!(w.units[&a].health, 6.5);
+    assert_eq!(w.resume, Some((input::TurnPhase::PlayerTurn, vec![a])));
+    assert!(world::load_state(&mut w, file).is_err());
+}
+
+#[test]
+fn unit_actions() {
+    use world::Actions;
+    let mut w = world::World::blank();
+    let mut duelist = world::UnitType::new(0, "Duelist".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    duelist.set_actions(Some(Actions { attacks: 2, move_after_attack: false }));
+    let knight = world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    world::register_unit_type(&mut w, duelist, 0);
+    world::register_unit_type(&mut w, knight, 1);
+    let d = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    let k = world::spawn_unit(&mut w, 1, (2, 1), -1, true);
+    assert_eq!(world::unit_actions(&w, d), Some(Actions { attacks: 2, move_after_attack: false }));
+    assert_eq!(world::unit_actions(&w, k), Some(Actions::DEFAULT));
+    assert!(napi::alsnSetDefaultActions(&mut w, 1, true));
+    assert_eq!(world::unit_actions(&w, k), Some(Actions { attacks: 1, move_after_attack: true }));
+    assert_eq!(world::unit_actions(&w, d).map(|a| a.attacks), Some(2));
+    assert_eq!(world::unit_actions(&w, 99), None);
+    assert_eq!(napi::actions_arg(-1, true), Some(None));
+    assert_eq!(napi::actions_arg(256, false), None);
+}
+
+#[test]
+fn ai_controller() {
+    use input::Order;
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Swordsman".to_string(), 10.0, 0.5, 2, 1, 1.5), 0);
+    let p = world::spawn_unit(&mut w, 0, (5, 0), -1, true);
+    let near = world::spawn_unit(&mut w, 0, (2, 0), -1, false);
+    let far = world::spawn_unit(&mut w, 0, (5, 9), -1, false);
+    let mut ovec = vec![];
+    world::behavior_orders(&mut w, &mut ovec);
+    assert!(ovec.is_empty());
+
+    // Units in sight advance on the player unit; those farther away stay put.
+    assert!(napi::alsnSetAiController(&mut w, true, 1.5));
+    world::behavior_orders(&mut w, &mut ovec);
+    assert_eq!(ovec, vec![Order::MOVE(near, 3, 0), Order::MOVE(near, 4, 0), Order::ATTACK(near, p, 4, 0)]);
+    ovec.clear();
+    w.set_ai_controller(Some(world::AiController::default()));
+    world::behavior_orders(&mut w, &mut ovec);
+    assert!(ovec.contains(&Order::MOVE(far, 5, 7)));
+    assert!(!napi::alsnSetAiController(&mut w, true, f32::NAN));
+    assert!(napi::alsnSetAiController(&mut w, false, 0.0));
+    assert_eq!(w.ai_controller(), None);
+}
+
+#[test]
+fn wait_action() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (4, 4), -1, false);
+    let mut is = input::InputHandler::new();
+    is.skip_intro();
+    assert!(!is.can_wait());
+    is.select(&w, e);
+    assert!(!is.can_wait());
+    is.select(&w, a);
+    assert!(is.can_wait());
+    is.wait(&mut w);
+    assert!(is.has_acted(a));
+    assert_eq!(w.acted, vec![a]);
+    assert!(!is.can_wait());
+    is.update_popups(&mut w, 0.0);
+    assert_eq!(is.popups.iter().map(|p| p.text.as_str()).collect::<Vec<_>>(), vec!["Wait"]);
+    is.select(&w, a);
+    assert!(!is.can_wait());
+}
+
+#[test]
+fn cancel_moves() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 3, 1, 0.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (2, 2), -1, true);
+    let mut is = input::InputHandler::new();
+    is.skip_intro();
+    is.select(&w, a);
+    let shade = is.tile_shade(5, 2);
+    is.move_to(&w, (3, 2));
+    is.move_to(&w, (4, 2));
+    assert_eq!(is._inrange(&w, 5, 2), 1);
+    // Taking back the moves selects the unit again from its tile.
+    is.cancel(&w);
+    assert_eq!(is.get_state(), 1);
+    assert_eq!(world::unit_tile(&w, a), Some((2, 2)));
+    assert_eq!(is._inrange(&w, 2, 2), -1);
+    assert_eq!(is._inrange(&w, 5, 2), 0);
+    assert_eq!(is.tile_shade(5, 2), shade);
+    is.move_to(&w, (2, 3));
+    is.move_to(&w, (2, 4));
+    is.move_to(&w, (2, 5));
+    assert_eq!(is.get_state(), 4);
+    is.cancel(&w);
+    assert_eq!(is.get_state(), 1);
+    // With no orders, the unit is deselected.
+    is.cancel(&w);
+    assert_eq!(is.get_state(), 0);
+    assert!(!is.has_acted(a));
+}
+
+#[test]
+fn fog_of_war() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Scout".to_string(), 10.0, 2.0, 2, 1, 0.5);
+    world::register_unit_type(&mut w, ut.variant("Knight".to_string()), 0);
+    ut.set_sight(Some(5));
+    world::register_unit_type(&mut w, ut, 1);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (4, 0), -1, false);
+    assert!(world::is_tile_visible(&w, 9, 9));
+    assert!(world::is_unit_visible(&w, e));
+    w.set_fog_of_war(true);
+    assert_eq!(world::unit_sight(&w, a), Some(3));
+    assert!(world::is_tile_visible(&w, 2, 1));
+    assert!(!world::is_tile_visible(&w, 2, 2));
+    assert!(!world::is_unit_visible(&w, e));
+    let s = world::spawn_unit(&mut w, 1, (0, 1), -1, true);
+    assert_eq!(world::unit_sight(&w, s), Some(5));
+    world::update_visibility(&mut w);
+    assert!(world::is_unit_visible(&w, e));
+    w.set_fog_of_war(false);
+    assert!(world::is_tile_visible(&w, 9, 9));
+    // Hidden enemies in range cannot be attacked.
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 1, 3, 0.5);
+    ut.set_sight(Some(1));
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (3, 0), -1, false);
+    w.set_fog_of_war(true);
+    let mut is = input::InputHandler::new();
+    is.skip_intro();
+    is.select(&w, a);
+    is.move_to(&w, (0, 0));
+    assert_eq!(is.get_state(), 4);
+    is.attack_at(&w, (3, 0));
+    assert!(is.has_acted(a));
+    assert!(!is.ovec.iter().any(|o| matches!(o, input::Order::ATTACK(_, d, ..) if d == &e)));
+}
+
+#[test]
+fn input_guard() {
+    use input::Guarded;
+    assert_eq!(input::InputGuard::new(-1.0, f32::NAN), input::InputGuard { debounce: 0.0, min_screen_time: 0.0 });
+    let mut is = input::InputHandler::new();
+    is.guard = input::InputGuard::new(0.25, 1.0);
+    // The intro cannot be dismissed until shown long enough.
+    is.tick_guard(0.5);
+    assert!(!is.can_dismiss());
+    is.tick_guard(0.6);
+    assert!(is.can_dismiss());
+    assert!(is.accept(Guarded::Click, Some((1, 1))));
+    assert!(!is.accept(Guarded::Click, Some((1, 1))));
+    assert!(is.accept(Guarded::Click, Some((2, 1))));
+    assert!(is.accept(Guarded::Wait, None));
+    assert!(!is.accept(Guarded::Wait, None));
+    is.tick_guard(0.3);
+    assert!(is.accept(Guarded::Wait, None));
+    assert!(is.accept(Guarded::Click, Some((2, 1))));
+}
+
+#[test]
+fn tile_layers() {
+    use world::LayerKind;
+    // A 2x2 map with an overlay, a slideshow of one slide, and a ground layer, after the game objects.
+    let mut map = vec![0xfa, 0xde, 0x00, 0xff, 2, 2, 0, 0, 1, 2, 3, 4];
+    for s in ["Title", "Intro", "Won", "Lost"].iter() {
+        map.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        map.extend_from_slice(s.as_bytes());
+    }
+    map.extend_from_slice(&[0x1a, 0xe7, 2, 9, 0xff, 0xff, 9]);
+    map.extend_from_slice(&[0x51, 0x1d, 1, 7, 10, 0, 0]);
+    map.extend_from_slice(&[0x1a, 0xe7, 0, 5, 5, 0xff, 0xff]);
+    let path = std::env::temp_dir().join(format!("alesia_layers_{}.alw", std::process::id()));
+    std::fs::write(&path, &map).unwrap();
+    let mut w = world::World::blank();
+    assert!(!w.add_tile_layer(LayerKind::Decoration, vec![1]));
+    assert!(world::load_world(&mut w, path.to_str().unwrap()));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(w.tile_layers(), vec![LayerKind::Overlay, LayerKind::Ground]);
+    assert_eq!(w.intro_slides().len(), 1);
+    assert_eq!(world::layer_tile_at(&w, 0, 1, 1), Some(9));
+    assert_eq!(world::layer_tile_at(&w, 1, 1, 0), Some(5));
+    assert_eq!(world::layer_tile_at(&w, 1, 2, 0), None);
+    assert_eq!(world::layer_tile_at(&w, 2, 0, 0), None);
+    assert!(world::prep_layer_tiledraw(&w, 0, 1, 0, 4).is_none());
+    assert!(!w.add_tile_layer(LayerKind::Decoration, vec![1, 2, 3]));
+    let tiles = [0xffu8, 6, 6, 0xff];
+    assert_eq!(napi::alsnAddTileLayer(&mut w, 1, tiles.as_ptr(), tiles.len()), 2);
+    assert_eq!(napi::alsnAddTileLayer(&mut w, 3, tiles.as_ptr(), tiles.len()), -1);
+    assert_eq!(world::LayerKind::from_name("Decoration"), Some(LayerKind::Decoration));
+    assert!(napi::alsnClearTileLayers(&mut w));
+    assert!(w.tile_layers().is_empty());
+}
+
+#[test]
+fn hex_grid() {
+    use world::Grid;
+    let mut w = world::World::blank_hex(10, 20, 64, 64);
+    assert_eq!(w.grid(), Grid::Hex);
+    assert_eq!(world::World::blank().grid(), Grid::Isometric);
+    // Odd rows are shifted right by half a tile.
+    assert_eq!(Grid::Hex.distance((2, 2), (3, 3)), 2);
+    assert_eq!(Grid::Hex.distance((2, 2), (2, 3)), 1);
+    assert_eq!(Grid::Hex.distance((2, 2), (1, 3)), 1);
+    assert_eq!(Grid::Hex.distance((2, 3), (3, 4)), 1);
+    assert_eq!(Grid::Hex.distance((0, 0), (0, 4)), 4);
+    assert_eq!(Grid::Isometric.distance((2, 2), (3, 3)), 2);
+    for t in Grid::Hex.neighbours((4, 5)) {
+        assert_eq!(Grid::Hex.distance((4, 5), t), 1);
+    }
+    assert_eq!(Grid::Hex.range((3, 3), 2).len(), 19);
+    assert_eq!(world::wots(&w, 1, 1), (10 + 64 + 32, 20 + 48));
+    for &(x, y) in [(0, 0), (3, 1), (2, 4), (-1, -1), (5, 7)].iter() {
+        let (sx, sy) = world::tile_to_screen(&w, x, y);
+        assert_eq!(world::tile_at(&w, sx, sy), (x, y));
+        assert_eq!(world::tile_at(&w, sx + 20.0, sy + 20.0), (x, y));
+    }
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 1, 2, 0.5);
+    for i in 0..9 {
+        ut.def_anim_muted((32, 32), 1, (0, 32 * i), 1.0, false);
+    }
+    world::register_unit_type(&mut w, ut, 0);
+    let uid = world::spawn_unit(&mut w, 0, (2, 3), -1, true);
+    assert_eq!(world::reachable_tiles(&w, uid).len(), 6);
+    assert_eq!(world::path::find(&w, (2, 3), (3, 5)).map(|r| r.len()), Some(2));
+    // Groups move by hex distance, along shortest routes.
+    let mut g = world::World::blank_hex(10, 20, 64, 64);
+    world::register_unit_type(&mut g, world::UnitType::new(0, "Knight".to_string(), 10.0, 2.0, 2, 1, 0.5), 0);
+    let (a, b) = (world::spawn_unit(&mut g, 0, (2, 2), -1, true), world::spawn_unit(&mut g, 0, (3, 2), -1, true));
+    let o = world::group_move(&g, &[a, b], (3, 4)).unwrap();
+    assert_eq!(o.len(), 4);
+    assert!(o.contains(&input::Order::MOVE(a, 3, 4)) && o.contains(&input::Order::MOVE(b, 4, 4)));
+    assert!(world::group_move(&g, &[a, b], (3, 5)).is_none());
+    assert_eq!(napi::alsnGridDistance(&w, 2, 2, 3, 3), 2);
+    assert_eq!(napi::alsnGetGrid(&w), 1);
+    // Diagonal steps are walked up or down, drifting sideways.
+    let mut is = input::InputHandler::new();
+    let mut sl = utils::StateListener::new();
+    is.carry_out(vec![input::Order::MOVE(uid, 3, 4)], false);
+    for _ in 0..600 {
+        if !is.is_simulating() {
+            break;
+        }
+        is.simulate(&mut w, &mut sl, 1.0 / 60.0);
+    }
+    assert_eq!(world::unit_tile(&w, uid), Some((3, 4)));
+}
+
+#[test]
+fn touch_gestures() {
+    use touch::{Gesture, TouchTracker};
+    use raylib::math::Vector2;
+    let mut t = TouchTracker::default();
+    let p = Vector2::new(100.0, 80.0);
+    // A drag pans, and is not a tap when lifted.
+    assert_eq!(t.update(0.05, &[p]), None);
+    assert_eq!(t.update(0.05, &[p + Vector2::new(20.0, 0.0)]), Some(Gesture::Pan(Vector2::new(20.0, 0.0))));
+    assert_eq!(t.update(0.05, &[p + Vector2::new(25.0, 5.0)]), Some(Gesture::Pan(Vector2::new(5.0, 5.0))));
+    assert_eq!(t.update(0.05, &[]), None);
+    // Holding still is a long-press, once.
+    assert_eq!(t.update(0.05, &[p]), None);
+    assert_eq!(t.update(0.3, &[p]), None);
+    assert_eq!(t.update(0.3, &[p]), Some(Gesture::LongPress(p)));
+    assert_eq!(t.update(0.3, &[p]), None);
+    assert_eq!(t.update(0.05, &[]), None);
+    // Fingers moving apart zoom in, about their middle; lifting one does not pan.
+    let (a, b) = (Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0));
+    assert_eq!(t.update(0.05, &[a, b]), None);
+    assert_eq!(t.update(0.05, &[a, Vector2::new(200.0, 0.0)]), Some(Gesture::Pinch { factor: 2.0, centre: Vector2::new(100.0, 0.0) }));
+    assert_eq!(t.update(0.05, &[Vector2::new(300.0, 0.0)]), None);
+    assert_eq!(t.update(0.05, &[]), None);
+    // A cancelled contact is ignored until lifted.
+    assert_eq!(t.update(0.05, &[p]), None);
+    t.cancel();
+    assert_eq!(t.update(0.05, &[]), None);
+
+    let mut w = world::World::blank_o(0, 0, 96, 48);
+    w.set_zoom(5.0);
+    assert_eq!(w.zoom(), world::MAX_ZOOM);
+    w.set_zoom(f32::NAN);
+    assert_eq!(w.zoom(), world::MAX_ZOOM);
+    let (sx, sy) = world::tile_to_screen(&w, 3, 2);
+    assert_eq!(world::tile_at(&w, sx, sy), (3, 2));
+    // The tile under the middle of a pinch stays there.
+    w.zoom_at(1.0, sx, sy);
+    assert_eq!(world::tile_at(&w, sx, sy), (3, 2));
+    w.pan_screen(-96.0, 0.0);
+    let (nx, ny) = world::tile_to_screen(&w, 3, 2);
+    assert!((nx - sx - 96.0).abs() < 0.01 && (ny - sy).abs() < 0.01);
+    assert!(napi::alsnSetZoom(&mut w, 0.75));
+    assert!(!napi::alsnSetZoom(&mut w, f32::INFINITY));
+    assert_eq!(napi::alsnGetZoom(&w), 0.75);
+}
+
+#[test]
+fn rumble() {
+    use event::EngineEvent;
+    use input::{Order, Rumble};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let r = Rumble::new((2.0, 0.3), (-1.0, f32::NAN));
+    assert_eq!(r.on_hit, (1.0, 0.3));
+    assert_eq!(r.on_select, (0.0, 0.0));
+    let e = EngineEvent::Rumble { strength: 0.5, duration: 0.25 };
+    assert_eq!(e.to_string(), r#"{"event":"Rumble","strength":0.5,"duration":0.25}"#);
+    assert_eq!(e.to_raw(), event::RawEvent { kind: 12, uid: 0, arg: 0, value: 0.5, x: 250, y: 0 });
+
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, String::from("Scout"), 10.0, 3.0, 3, 1, 0.4);
+    ut.set_combat_stats(3.0, 0.0);
+    for i in 0..9 {
+        ut.def_anim_muted((32, 32), 1, (0, 32 * i), 4.0, false);
+    }
+    world::register_unit_type(&mut w, ut, 0);
+    let p = world::spawn_unit(&mut w, 0, (1, 1), -1, true);
+    let e = world::spawn_unit(&mut w, 0, (2, 1), -0x38ffc328, false);
+    let mut sl = utils::StateListener::new();
+    let seen = Rc::new(RefCell::new(vec![]));
+    let s = seen.clone();
+    sl.subscribe(move |_, e| if let EngineEvent::Rumble { strength, .. } = e { s.borrow_mut().push(*strength) });
+    let mut attack = |w: &mut world::World, is: &mut input::InputHandler, a, d, x| {
+        is.carry_out(vec![Order::ATTACK(a, d, x, 1)], a == e);
+        while is.is_simulating() {
+            is.simulate(w, &mut sl, 1.0 / 30.0);
+        }
+    };
+    // Rumble is off by default.
+    let mut is = input::InputHandler::new();
+    attack(&mut w, &mut is, e, p, 2);
+    assert!(seen.borrow().is_empty());
+    // Only hits taken by player units rumble, scaled by the health lost.
+    is.rumble = r;
+    attack(&mut w, &mut is, p, e, 1);
+    assert!(seen.borrow().is_empty());
+    attack(&mut w, &mut is, e, p, 2);
+    let v = seen.borrow().clone();
+    assert_eq!(v.len(), 1);
+    assert!(v[0] > 0.0 && v[0] < 1.0);
+}
+
+#[test]
+fn save_world() {
+    // A 2x2 map with tile permissions, a static, three units with behaviors, two pickups, a slide and an overlay.
+    let mut map = vec![0xfa, 0xde, 0x00, 0xff, 2, 2, 0xda, 0xd7, 1, 3, 1, 4, 0, 1, 2, 3, 4];
+    for s in ["Ford", "Hold the ford.", "Won", "Lost"].iter() {
+        map.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        map.extend_from_slice(s.as_bytes());
+    }
+    for o in [[0, 7, 1, 0], [1, 0, 0, 0], [3, 0, 0, 0], [2, 0, 1, 1], [4, 2, 1, 1], [2, 0, 0, 1], [5, 0, 0, 1], [5, 0, 1, 0], [6, 50, 1, 0], [8, 30, 0, 1]].iter() {
+        map.extend_from_slice(&[0xfe, 0xed]);
+        map.extend_from_slice(o);
+    }
+    map.extend_from_slice(&[0x51, 0x1d, 1, 7, 10, 0, 2, b'G', b'o']);
+    map.extend_from_slice(&[0x1a, 0xe7, 2, 9, 0xff, 0xff, 9]);
+    let dir = std::env::temp_dir();
+    let src = dir.join(format!("alesia_src_{}.alw", std::process::id()));
+    let out = dir.join(format!("alesia_out_{}.alw", std::process::id()));
+    std::fs::write(&src, &map).unwrap();
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+    assert!(world::load_world(&mut w, src.to_str().unwrap()));
+    assert_eq!(world::save_world(&w, out.to_str().unwrap()), Ok(()));
+    assert_eq!(std::fs::read(&out).unwrap(), map);
+    // Objects off the tiles a byte can hold do not fit the format.
+    world::spawn_unit(&mut w, 0, (-1, 0), -1, true);
+    assert!(world::save_world(&w, out.to_str().unwrap()).is_err());
+    let path = std::ffi::CString::new(out.to_str().unwrap()).unwrap();
+    assert!(!napi::alsnSaveMap(&w, path.as_ptr()));
+    assert!(napi::alsnSaveMap(&world::World::blank(), path.as_ptr()));
+    let mut w = world::World::blank();
+    assert!(world::load_world(&mut w, out.to_str().unwrap()));
+    assert_eq!(w.map_size(), (0, 0));
+    std::fs::remove_file(&src).unwrap();
+    std::fs::remove_file(&out).unwrap();
+}
+
+#[test]
+fn input_scheme() {
+    use event::EngineEvent;
+    use input::InputScheme;
+    let km = InputScheme::KeyboardMouse;
+    // The last device used wins; the current scheme is kept while its device is in use, and touches move the mouse along.
+    assert_eq!(km.next(false, false, false), km);
+    assert_eq!(km.next(false, true, false), InputScheme::Gamepad);
+    assert_eq!(InputScheme::Gamepad.next(true, true, false), InputScheme::Gamepad);
+    assert_eq!(InputScheme::Gamepad.next(true, false, false), km);
+    assert_eq!(km.next(true, false, true), InputScheme::Touch);
+    assert_eq!(InputScheme::from_u8(3), None);
+    assert_eq!(display::hint(km, km.confirm_prompt(), "continue"), "Click to continue, ESC to skip");
+    assert_eq!(display::hint(InputScheme::Gamepad, InputScheme::Gamepad.hold_prompt(), "speed up"), "Hold A to speed up, B to skip");
+    assert_eq!(display::hint(InputScheme::Touch, InputScheme::Touch.confirm_prompt(), "continue"), "Tap to continue");
+
+    let mut w = world::World::blank();
+    assert_eq!(w.input_scheme(), km);
+    assert!(napi::alsnSetInputScheme(&mut w, 1));
+    assert!(!napi::alsnSetInputScheme(&mut w, 7));
+    assert_eq!(napi::alsnGetInputScheme(&w), 1);
+    assert_eq!(w.input_scheme(), InputScheme::Gamepad);
+    let e = EngineEvent::InputSchemeChanged(InputScheme::Touch);
+    assert_eq!(e.to_string(), r#"{"event":"InputSchemeChanged","scheme":"Touch"}"#);
+    assert_eq!(e.to_raw(), event::RawEvent { kind: 13, arg: 2, ..Default::default() });
+}
+
+#[test]
+fn ron_world() {
+    // The map of test save_world, in .alw and as text.
+    let mut map = vec![0xfa, 0xde, 0x00, 0xff, 2, 2, 0xda, 0xd7, 1, 3, 1, 4, 0, 1, 2, 3, 4];
+    for s in ["Ford", "Hold \"the\" ford.\n", "Won", "Lost"].iter() {
+        map.extend_from_slice(&(s.len() as u16).to_be_bytes());
+        map.extend_from_slice(s.as_bytes());
+    }
+    for o in [[0, 7, 1, 0], [1, 0, 0, 0], [3, 0, 0, 0], [2, 0, 1, 1], [4, 2, 1, 1], [2, 0, 0, 1], [5, 0, 0, 1], [5, 0, 1, 0], [6, 50, 1, 0], [8, 30, 0, 1]].iter() {
+        map.extend_from_slice(&[0xfe, 0xed]);
+        map.extend_from_slice(o);
+    }
+    map.extend_from_slice(&[0x51, 0x1d, 1, 7, 10, 0, 2, b'G', b'o']);
+    map.extend_from_slice(&[0x1a, 0xe7, 2, 9, 0xff, 0xff, 9]);
+    let text = r#"
+        /* Hand-written. */
+        Map(
+            width: 2, height: 2,
+            tiles: [
+                1, 2, // the ford
+                3, 4,
+            ],
+            prohibited: [3], heal: [4], damage: [],
+            title: "Ford", intro: "Hold \"the\" ford.\n", victory: "Won", defeat: "Lost",
+            statics: [(tex: 7, tile: (1, 0))],
+            units: [
+                (type_id: 0, tile: (0, 0), player: true, behavior: Hold),
+                (type_id: 0, tile: (1, 1), player: false, behavior: Guard(tile: (1, 1), radius: 2)),
+                (type_id: 0, tile: (0, 1), player: false, behavior: Patrol([(0, 1), (1, 0)])),
+            ],
+            pickups: [(tile: (1, 0), effect: Heal(0.5)), (tile: (0, 1), effect: Gold(30))],
+            slides: [(tex: 7, duration: 1.0, caption: "Go")],
+            layers: [(kind: Overlay, tiles: [9, 255, 255, 9])],
+        )
+    "#;
+    let dir = std::env::temp_dir();
+    let ron = dir.join(format!("alesia_map_{}.ron", std::process::id()));
+    let alw = dir.join(format!("alesia_map_{}.alw", std::process::id()));
+    let new = || {
+        let mut w = world::World::blank();
+        world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+        w
+    };
+    std::fs::write(&ron, text).unwrap();
+    let mut w = new();
+    assert!(world::load_world_ron(&mut w, ron.to_str().unwrap()));
+    assert_eq!(w.map_title(), "Ford");
+    // Written back out, as text and in .alw, the map is unchanged.
+    assert_eq!(world::save_world_ron(&w, ron.to_str().unwrap()), Ok(()));
+    let mut w = new();
+    assert!(world::load_world_ron(&mut w, ron.to_str().unwrap()));
+    assert_eq!(world::save_world(&w, alw.to_str().unwrap()), Ok(()));
+    assert_eq!(std::fs::read(&alw).unwrap(), map);
+    let saved = std::fs::read_to_string(&ron).unwrap();
+    assert_eq!(world::ron::to_string(&w), saved);
+
+    // Malformed text is reported by line, and leaves the world unchanged.
+    let mut w = new();
+    assert_eq!(world::ron::parse(&mut w, "Map(\n  width: 1,\n  height: 1\n  tiles: [0])"), Err("line 4: expected ')'".to_string()));
+    assert!(world::ron::parse(&mut w, "Map(width: 1, height: 1, tiles: [0], colour: 3)").unwrap_err().contains("unknown field colour"));
+    assert!(world::ron::parse(&mut w, "Map(width: 2, height: 1, tiles: [0])").is_err());
+    let bad = "Map(width: 1, height: 1, tiles: [0], title: \"X\", units: [(type_id: 5, tile: (0, 0), player: true)])";
+    assert!(world::ron::parse(&mut w, bad).unwrap_err().contains("unit type 5 is not registered"));
+    assert_eq!(w.map_size(), (0, 0));
+    assert!(world::ron::parse(&mut w, "(width: 1, height: 1, tiles: [0])").is_ok());
+    assert_eq!(w.map_size(), (1, 1));
+    let path = std::ffi::CString::new(ron.to_str().unwrap()).unwrap();
+    std::fs::write(&ron, "Map(").unwrap();
+    assert!(!napi::alsnLoadMapRon(&mut w, path.as_ptr()));
+    assert!(napi::alsnSaveMapRon(&w, path.as_ptr()));
+    assert!(napi::alsnLoadMapRon(&mut w, path.as_ptr()));
+    std::fs::remove_file(&ron).unwrap();
+    std::fs::remove_file(&alw).unwrap();
+}
+
+#[test]
+fn control_hints() {
+    use input::{control_hints, InputScheme, TurnPhase};
+    let mut keys = input::KeyBindings::default();
+    let km = InputScheme::KeyboardMouse;
+    assert_eq!(control_hints(TurnPhase::PlayerTurn, &keys, km), "LMB: select, RMB: info, ENTER: end turn");
+    assert_eq!(control_hints(TurnPhase::MoveSelect, &keys, km), "LMB: move, RMB: info, E: cancel, W: wait");
+    // Hints follow the bindings, and the device last used.
+    keys.bind("cancel", raylib::consts::KeyboardKey::KEY_X);
+    assert_eq!(control_hints(TurnPhase::AttackSelect, &keys, km), "LMB: attack, RMB: info, X: cancel, W: wait");
+    assert_eq!(control_hints(TurnPhase::AttackSelect, &keys, InputScheme::Touch), "Tap: attack, Long-press: info");
+    assert_eq!(control_hints(TurnPhase::Intro, &keys, InputScheme::Gamepad), "A: continue");
+    assert_eq!(control_hints(TurnPhase::EnemyTurn, &keys, km), "");
+}
+
+#[test]
+fn tutorial() {
+    use tutorial::{Highlight, Tutorial, TutorialControl, TutorialGoal, UiElement};
+    let text = "step Welcome.\nhighlight ui hints\nstep Attack the archer.\ngoal attack 1 7\nallow unit 2\nallow cancel\nhighlight tile 3 3\nstep End your turn.\ngoal end_turn\n";
+    let mut t = Tutorial::parse(text).unwrap();
+    assert_eq!(t.steps().len(), 3);
+    // A step without a goal is done by any click, and accepts nothing else.
+    assert!(t.highlights_ui(UiElement::ControlHints));
+    assert!(!t.allows_tile((0, 0), Some(1)) && !t.allows(TutorialControl::Info) && !t.allows_grouping());
+    assert!(!t.act(TutorialGoal::EndTurn));
+    assert!(t.act(TutorialGoal::Continue));
+    assert!(t.allows_tile((5, 5), Some(7)) && t.allows_tile((0, 0), Some(2)) && !t.allows_tile((0, 0), Some(3)));
+    assert!(t.allows(TutorialControl::Cancel) && !t.allows(TutorialControl::Wait));
+    assert_eq!(t.highlights(), &[Highlight::Tile((3, 3))]);
+    assert!(t.act(TutorialGoal::Attack(1, 7)));
+    assert!(t.allows(TutorialControl::EndTurn));
+    assert!(t.act(TutorialGoal::EndTurn));
+    // Once done, input is no longer restricted.
+    assert!(t.is_done() && t.allows_grouping() && t.allows(TutorialControl::Info) && t.highlights().is_empty());
+    assert!(Tutorial::parse("goal end_turn\n").is_err());
+    assert!(Tutorial::parse("step Go.\ngoal move 1 2\n").is_err());
+    assert!(Tutorial::parse("step Go.\nhighlight ui sky\n").is_err());
+    assert!(Tutorial::parse("# Nothing.\n").is_err());
+    let e = event::EngineEvent::TutorialStep(1);
+    assert_eq!(e.to_string(), "{\"event\":\"TutorialStep\",\"step\":1}");
+    assert_eq!((e.to_raw().kind, e.to_raw().x), (14, 1));
+    // Progress is kept in snapshots.
+    let mut w = world::World::blank();
+    let path = std::env::temp_dir().join(format!("alesia_tutorial_{}.txt", std::process::id()));
+    std::fs::write(&path, text).unwrap();
+    assert!(w.load_tutorial(path.to_str().unwrap()));
+    assert!(!w.load_tutorial("/nonexistent/tutorial.txt"));
+    w.tutorial_act(TutorialGoal::Continue);
+    assert!(w.tutorial_allows(TutorialControl::Cancel));
+    let snap = save::snapshot(&w, input::TurnPhase::PlayerTurn);
+    assert!(w.load_tutorial(path.to_str().unwrap()));
+    save::restore(&mut w, &snap).unwrap();
+    assert_eq!(w.tutorial().unwrap().steps_done(), 1);
+    w.set_tutorial(None);
+    assert!(w.tutorial_allows(TutorialControl::Wait));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn accessibility_settings() {
+    let s = utils::Settings::parse("[display]\ntext_scale = 1.5\nhigh_contrast = true\n");
+    assert_eq!((s.text_scale, s.high_contrast), (1.5, true));
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    // Invalid values leave the defaults in place.
+    let d = utils::Settings::parse("[display]\ntext_scale = 0\nhigh_contrast = 1\n");
+    assert_eq!((d.text_scale, d.high_contrast), (1.0, false));
+}
+
+#[test]
+fn elevation() {
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 2, 0.5);
+    ut.set_combat_stats(4.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let text = "Map(width: 4, height: 1, tiles: [0, 0, 0, 0], heights: [3, 1, 0, 0], units: [(type_id: 0, tile: (0, 0), player: true), (type_id: 0, tile: (3, 0), player: false)])";
+    assert!(world::ron::parse(&mut w, text).is_ok());
+    assert!(w.has_elevation());
+    assert_eq!(world::elevation_at(&w, 0, 0), 3);
+    assert_eq!(world::elevation_at(&w, 9, 0), 0);
+    let (a, b) = (world::unit_at(&w, 0, 0).unwrap(), world::unit_at(&w, 3, 0).unwrap());
+    // A cliff cannot be climbed or descended.
+    assert!(!world::can_step(&w, (0, 0), (1, 0)) && world::can_step(&w, (1, 0), (2, 0)));
+    assert!(!world::reachable_tiles(&w, a).contains(&(1, 0)));
+    assert!(world::path::find(&w, (0, 0), (2, 0)).is_none());
+    assert!(world::path::find(&w, (1, 0), (2, 0)).is_some());
+    // High ground reaches further, and hits harder.
+    assert!(world::is_in_attack_range(&w, (0, 0), (3, 0), 2));
+    assert!(!world::is_in_attack_range(&w, (3, 0), (0, 0), 2));
+    assert_eq!(world::height_advantage(&w, a, b), 3);
+    assert!(world::default_damage(&w, a, b) > world::default_damage(&w, b, a));
+    assert_eq!(napi::alsnGetHeightAdvantage(&w, b, a), -3);
+    // Raised tiles are drawn higher, and picked where drawn.
+    let raised = world::wots(&w, 0, 0);
+    let (sx, sy) = world::tile_to_screen(&w, 0, 0);
+    assert_eq!(world::tile_at(&w, sx, sy), (0, 0));
+    assert!(world::ron::to_string(&w).contains("heights"));
+    let mut w2 = world::World::blank();
+    world::register_unit_type(&mut w2, world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 2, 0.5), 0);
+    assert!(world::ron::parse(&mut w2, &world::ron::to_string(&w)).is_ok());
+    assert_eq!(world::elevation_at(&w2, 1, 0), 1);
+    assert!(!w.set_elevation(vec![1, 2]));
+    assert!(!napi::alsnSetElevation(&mut w, [1u8, 2].as_ptr(), 2));
+    assert!(w.set_elevation(vec![]));
+    assert_eq!(world::wots(&w, 0, 0).1 - raised.1, 3 * w.elevation_rules().step);
+    assert!(world::can_step(&w, (0, 0), (1, 0)));
+    assert!(world::ron::parse(&mut w2, "Map(width: 1, height: 1, tiles: [0], heights: [1, 2])").is_err());
+}
+
+#[test]
+fn narration() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use input::TurnPhase;
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5);
+    ut.set_combat_stats(4.0, 2.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (3, 0), -1, false);
+    w.units.get_mut(&a).unwrap().health = 6.6;
+    assert_eq!(narration::describe_selection(&w, a, 1).unwrap(), "Archer selected, 7 of 10 HP");
+    assert_eq!(narration::describe_selection(&w, a, 3).unwrap(), "3 units selected");
+    assert_eq!(narration::describe_unit(&w, b).unwrap(), "Enemy Archer, 10 of 10 HP");
+    assert_eq!(narration::describe_phase(&w, TurnPhase::PlayerMoves, TurnPhase::EnemyTurn).unwrap(), "Enemy phase begins");
+    assert_eq!(narration::describe_phase(&w, TurnPhase::EnemyTurn, TurnPhase::PlayerTurn).unwrap(), "Player phase begins, turn 1");
+    assert_eq!(narration::describe_phase(&w, TurnPhase::MoveSelect, TurnPhase::PlayerTurn), None);
+    assert_eq!(text::plain("[b]HP[/b] {icon:3}[red]7[/red]\nok"), "HP 7\nok");
+    // Events are narrated only while a callback is bound, in plain text.
+    let lines = Rc::new(RefCell::new(Vec::new()));
+    let l = lines.clone();
+    let mut sl = utils::StateListener::new();
+    assert!(!sl.narrates());
+    sl.bind_narration(move |_, text| l.borrow_mut().push(text.to_string()));
+    assert!(sl.narrates());
+    sl.notify_attack(&mut w, a, b, 2.5);
+    sl.publish(&mut w, event::EngineEvent::UnitMoved { uid: a, tile: (1, 0) });
+    sl.publish(&mut w, event::EngineEvent::Exit);
+    sl.narrate(&mut w, "");
+    assert_eq!(*lines.borrow(), vec!["Archer hits Archer for 2.5".to_string(), "Archer moves to 1, 0".to_string()]);
+    // Enemy units hidden by fog of war are not narrated.
+    let hidden = world::spawn_unit(&mut w, 0, (40, 40), -1, false);
+    w.set_fog_of_war(true);
+    assert!(!world::is_unit_visible(&w, hidden));
+    assert_eq!(narration::describe_event(&w, &event::EngineEvent::UnitMoved { uid: hidden, tile: (40, 41) }), None);
+}
+
+#[test]
+fn camera_follow() {
+    let mut w = world::World::blank();
+    world::register_unit_type(&mut w, world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 2, 0.5), 0);
+    let a = world::spawn_unit(&mut w, 0, (4, 2), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (0, 6), -1, false);
+    // The camera closes in on the followed unit over time, rather than jumping to it.
+    assert!(!w.follow_unit(9));
+    assert!(w.follow_unit(a));
+    w.update_tweens(0.1);
+    assert!(w.cam_wx > 0.0 && w.cam_wx < 4.0 && w.cam_wy > 0.0 && w.cam_wy < 2.0);
+    for _ in 0..100 {
+        w.update_tweens(0.1);
+    }
+    assert!((w.cam_wx - 4.0).abs() < 1e-3 && (w.cam_wy - 2.0).abs() < 1e-3);
+    w.set_camera_follow_rate(0.0);
+    assert!(w.follow_unit(b));
+    w.update_tweens(0.01);
+    assert_eq!((w.cam_wx, w.cam_wy), (0.0, 6.0));
+    // Panning to a tile stops following.
+    w.pan_to_tile(3, 3, 0.5);
+    assert_eq!(w.cam_follow, None);
+    w.update_tweens(0.5);
+    assert_eq!((w.cam_wx, w.cam_wy), (3.0, 3.0));
+    // Walking units are followed only once enabled.
+    world::set_unit_state(&mut w, a, world::UnitState::WalkDown);
+    w.update_tweens(0.1);
+    assert_eq!((w.cam_wx, w.cam_wy), (3.0, 3.0));
+    w.set_camera_auto_follow(true);
+    w.update_tweens(0.1);
+    assert_eq!((w.cam_wx, w.cam_wy), (4.0, 2.0));
+    w.units.remove(&b);
+    assert!(!napi::alsnSetCameraFollow(&mut w, b, true));
+    assert!(!napi::alsnSetCameraFollowRate(&mut w, -1.0));
+    assert!(napi::alsnPanToTile(&mut w, 1, 1, 0.0));
+}
+
+#[test]
+fn voice_barks() {
+    use world::voice::{self, BarkKind};
+    let mut w = world::World::blank();
+    let mut ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 2, 0.5);
+    for i in 0..9 {
+        ut.def_anim_muted((32, 32), 1, (0, 32 * i), 1.0, false);
+    }
+    ut.set_voice(BarkKind::Select, vec![3, 4], 2.0);
+    ut.set_voice(BarkKind::Move, vec![5], 10.0);
+    ut.set_voice(BarkKind::Death, vec![6], -1.0);
+    assert_eq!(ut.voice().cooldown(BarkKind::Death), 0.0);
+    world::register_unit_type(&mut w, ut, 0);
+    let a = world::spawn_unit(&mut w, 0, (0, 0), -1, true);
+    let b = world::spawn_unit(&mut w, 0, (1, 0), -1, true);
+    // Units of a type share the cooldown of each group.
+    assert!(matches!(voice::bark(&mut w, a, BarkKind::Select), Some(3) | Some(4)));
+    assert_eq!(voice::bark(&mut w, b, BarkKind::Select), None);
+    assert_eq!(voice::bark(&mut w, a, BarkKind::Attack), None);
+    assert_eq!(voice::bark(&mut w, a, BarkKind::Death), Some(6));
+    assert_eq!(voice::bark(&mut w, a, BarkKind::Death), Some(6));
+    assert_eq!(voice::bark(&mut w, 9, BarkKind::Death), None);
+    // A unit barks once as it sets off, not at every step.
+    w.audio.clear();
+    let mut is = input::InputHandler::new();
+    let mut sl = utils::StateListener::new();
+    is.carry_out(vec![input::Order::MOVE(b, 1, 1), input::Order::MOVE(b, 1, 2)], false);
+    for _ in 0..600 {
+        if !is.is_simulating() {
+            break;
+        }
+        is.simulate(&mut w, &mut sl, 1.0 / 60.0);
+    }
+    assert_eq!(world::unit_tile(&w, b), Some((1, 2)));
+    assert_eq!(w.audio, vec![world::AudioCmd::PlaySound(5)]);
+    // Cooldowns run out with the simulation.
+    assert_eq!(voice::bark(&mut w, b, BarkKind::Select), None);
+    is.simulate(&mut w, &mut sl, 1.5);
+    assert!(voice::bark(&mut w, b, BarkKind::Select).is_some());
+    assert_eq!(BarkKind::from_u8(4), None);
+    assert!(!napi::handles::alshSetVoice(0, 0, std::ptr::null(), 0, 0.0));
+}
+
+#[test]
+fn gamepad_cursor() {
+    use input::{control_hints, InputScheme, TurnPhase};
+    let mut w = world::World::blank();
+    let ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 2, 0.5);
+    world::register_unit_type(&mut w, ut, 0);
+    let text = "Map(width: 3, height: 3, tiles: [0, 0, 0, 0, 0, 0, 0, 0, 0], units: [(type_id: 0, tile: (0, 0), player: true), (type_id: 0, tile: (2, 1), player: true), (type_id: 0, tile: (1, 2), player: false)])";
+    assert!(world::ron::parse(&mut w, text).is_ok());
+    let mut is = input::InputHandler::new();
+    // The cursor steps to the neighbouring tile drawn in the direction of the stick, and stays on the map.
+    assert!(is.move_cursor(&w, 1.0, 1.0));
+    assert_eq!(is.cursor, (1, 0));
+    assert!(is.move_cursor(&w, 0.0, 1.0));
+    assert_eq!(is.cursor, (2, 1));
+    assert!(!is.move_cursor(&w, 1.0, 1.0));
+    assert!(is.move_cursor(&w, -1.0, 0.0));
+    assert_eq!(is.cursor, (1, 2));
+    // Shoulder buttons cycle through the player units, in order of id, wrapping around.
+    let (a, b) = (world::unit_at(&w, 0, 0).unwrap(), world::unit_at(&w, 2, 1).unwrap());
+    assert_eq!(is.cycle_unit(&w, true), Some(a.min(b)));
+    assert_eq!(is.cycle_unit(&w, true), Some(a.max(b)));
+    assert_eq!(is.cycle_unit(&w, true), Some(a.min(b)));
+    assert_eq!(is.cycle_unit(&w, false), Some(a.max(b)));
+    assert_eq!(is.cursor, world::unit_tile(&w, a.max(b)).unwrap());
+    let keys = input::KeyBindings::default();
+    assert_eq!(control_hints(TurnPhase::MoveSelect, &keys, InputScheme::Gamepad), "A: move, Y: info, B: cancel, X: wait");
+}
+
+#[test]
+fn stingers() {
+    use world::mixer::{self, StingerKind};
+    use world::AudioCmd;
+    let mut w = world::World::blank();
+    w.set_music_volume(0.8);
+    assert!(!w.play_stinger(StingerKind::LevelUp));
+    w.set_stinger(StingerKind::LevelUp, Some((9, 1.0)));
+    w.set_duck_level(0.5);
+    w.audio.clear();
+    // The music fades down while the stinger plays, then back up to the volume set.
+    assert!(w.play_stinger(StingerKind::LevelUp));
+    assert_eq!(w.audio, vec![AudioCmd::PlaySound(9)]);
+    mixer::tick(&mut w, 0.1);
+    assert!(w.mixer().is_ducking() && w.mixer().effective_music_volume() < 0.8);
+    for _ in 0..5 {
+        mixer::tick(&mut w, 0.1);
+    }
+    assert!((w.mixer().effective_music_volume() - 0.4).abs() < 1e-4);
+    // Changing the volume meanwhile keeps the music ducked.
+    w.set_music_volume(0.6);
+    assert_eq!(w.audio.last(), Some(&AudioCmd::MusicVolume(0.3)));
+    for _ in 0..10 {
+        mixer::tick(&mut w, 0.1);
+    }
+    assert!(!w.mixer().is_ducking());
+    assert_eq!(w.audio.last(), Some(&AudioCmd::MusicVolume(0.6)));
+    // Completing an objective plays its stinger, once.
+    w.set_stinger(StingerKind::ObjectiveComplete, Some((4, -2.0)));
+    assert_eq!(w.mixer().stinger(StingerKind::ObjectiveComplete), Some((4, 0.0)));
+    let i = w.add_objective("Hold the bridge");
+    w.audio.clear();
+    assert!(w.set_objective_state(i, world::ObjectiveState::Completed));
+    assert!(w.set_objective_state(i, world::ObjectiveState::Completed));
+    assert_eq!(w.audio, vec![AudioCmd::PlaySound(4)]);
+    assert!(!napi::alsnSetStinger(&mut w, 3, 0, 1.0));
+    assert!(napi::alsnSetStinger(&mut w, 2, 0, -1.0));
+    assert_eq!(w.mixer().stinger(StingerKind::ObjectiveComplete), None);
+}
+
+#[test]
+fn ambient_regions() {
+    use world::ambient::{self, AmbientRegion};
+    use world::AudioCmd;
+    let mut w = world::World::blank();
+    let text = "Map(width: 8, height: 1, tiles: [0, 0, 0, 0, 0, 0, 0, 0], ambient: [(track: 5, from: (0, 0), to: (1, 0), falloff: 4.0, volume: 0.8)])";
+    assert!(world::ron::parse(&mut w, text).is_ok());
+    let r = w.ambient_regions()[0];
+    assert_eq!((r.track, r.volume), (5, 0.8));
+    // The sound fades with distance from the area, and is silent beyond the fall-off.
+    assert_eq!(r.volume_at((1, 0)), 0.8);
+    assert!((r.volume_at((3, 0)) - 0.4).abs() < 1e-6);
+    assert_eq!(r.volume_at((5, 0)), 0.0);
+    assert!(world::ron::to_string(&w).contains("ambient: [\n        (track: 5, from: (0, 0), to: (1, 0), falloff: 4.0, volume: 0.8),"));
+    // Regions are kept in the ambient chunk of binary maps.
+    let alw = std::env::temp_dir().join(format!("alesia_ambient_{}.alw", std::process::id()));
+    assert_eq!(world::save_world(&w, alw.to_str().unwrap()), Ok(()));
+    let mut v = world::World::blank();
+    assert!(world::load_world(&mut v, alw.to_str().unwrap()));
+    let _ = std::fs::remove_file(&alw);
+    assert_eq!(v.ambient_regions(), w.ambient_regions());
+    // Tracks start as the camera nears, follow the volume of sound effects, and stop once out of earshot.
+    w.audio.clear();
+    ambient::update(&mut w, (7, 0));
+    assert!(w.audio.is_empty());
+    ambient::update(&mut w, (0, 0));
+    assert_eq!(w.audio, vec![AudioCmd::AmbientVolume(5, 0.8)]);
+    ambient::update(&mut w, (0, 0));
+    assert_eq!(w.audio.len(), 1);
+    w.set_sfx_volume(0.5);
+    ambient::update(&mut w, (0, 0));
+    assert_eq!(w.audio.last(), Some(&AudioCmd::AmbientVolume(5, 0.4)));
+    assert_eq!(w.mixer().ambient_volume(5), 0.4);
+    w.clear_ambient_regions();
+    ambient::update(&mut w, (0, 0));
+    assert_eq!(w.audio.last(), Some(&AudioCmd::AmbientVolume(5, 0.0)));
+    assert_eq!(w.mixer().ambient_volume(5), 0.0);
+    w.add_ambient_region(AmbientRegion::new(2, (3, 0), (3, 0), -1.0, 2.0));
+    assert_eq!((w.ambient_regions()[0].falloff, w.ambient_regions()[0].volume), (0.0, 1.0));
+    assert!(world::ron::parse(&mut w, "Map(width: 1, height: 1, tiles: [0], ambient: [(track: 1, from: (0, 0), to: (0, 0), falloff: -1)])").is_err());
+    assert!(!napi::alsnAddAmbientRegion(&mut w, 1, 0, 0, 0, 0, 1.0, 1.5));
+}
+
+#[test]
+fn audio_settings() {
+    use world::AudioCmd;
+    let s = utils::Settings::parse("[audio]\nmaster_volume = 0.5\nmusic_volume = 0.25\nmute_unfocused = true\n");
+    assert!(s.mute_unfocused && !utils::Settings::default().mute_unfocused);
+    assert_eq!(utils::Settings::parse(&s.to_toml()), s);
+    let mut w = world::World::blank();
+    w.apply_audio_settings(&s);
+    assert_eq!(w.audio, vec![AudioCmd::MasterVolume(0.5), AudioCmd::MusicVolume(0.25), AudioCmd::SfxVolume(1.0)]);
+    assert!(w.mixer().mute_unfocused());
+    // Muting keeps the volume set, which is restored on unmuting.
+    w.set_muted(true);
+    w.set_master_volume(0.75);
+    assert_eq!(w.audio.last(), Some(&AudioCmd::MasterVolume(0.0)));
+    w.set_muted(false);
+    assert_eq!(w.audio.last(), Some(&AudioCmd::MasterVolume(0.75)));
+    // Volumes changed during the game are kept in the settings, and saved with them.
+    w.set_sfx_volume(0.4);
+    w.set_mute_unfocused(false);
+    let mut t = utils::Settings::default();
+    t.width = 800;
+    t.update_audio(w.mixer());
+    assert_eq!((t.master_volume, t.music_volume, t.sfx_volume, t.mute_unfocused, t.width), (0.75, 0.25, 0.4, false, 800));
+    assert!(t.to_toml().contains("[audio]\nmaster_volume = 0.75\nmusic_volume = 0.25\nsfx_volume = 0.4\nmute_unfocused = false\n"));
+}
+
+#[test]
+fn combat_callbacks() {
+    use event::EngineEvent;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    let mut w = world::World::blank();
+    w.enable_event_queue(true);
+    let log = Rc::new(RefCell::new(vec![]));
+    let mut sl = utils::StateListener::new();
+    let l = log.clone();
+    sl.bind_unit_death(move |_, uid, tid| l.borrow_mut().push(format!("death {} {}", uid, tid)));
+    let l = log.clone();
+    sl.bind_attack(move |_, a, d, dmg| l.borrow_mut().push(format!("attack {} {} {}", a, d, dmg)));
+    let l = log.clone();
+    sl.bind_move_complete(move |_, uid, t| l.borrow_mut().push(format!("move {} {:?}", uid, t)));
+    sl.notify_move_complete(&mut w, 1, (3, -2));
+    sl.notify_attack(&mut w, 1, 2, 6.0);
+    sl.notify_unit_death(&mut w, 2, 0);
+    assert_eq!(*log.borrow(), vec!["move 1 (3, -2)", "attack 1 2 6", "death 2 0"]);
+    // The callback comes first, then the event is published as before.
+    assert_eq!(w.poll_event(), Some(EngineEvent::UnitMoved { uid: 1, tile: (3, -2) }));
+    extern "C" fn moved(_: *mut world::World, uid: u8, x: i32, y: i32, ctx: *mut std::ffi::c_void) {
+        unsafe { *(ctx as *mut (u8, i32, i32)) = (uid, x, y) };
+    }
+    let mut got = (0u8, 0, 0);
+    let mut raw = utils::StateListener::_new_raw();
+    raw._bind_rawmove_complete(Some(moved), &mut got as *mut (u8, i32, i32) as *mut _);
+    raw.notify_move_complete(&mut w, 4, (7, 9));
+    assert_eq!(got, (4, 7, 9));
+    let h = napi::handles::alshNewStateListener();
+    assert!(napi::handles::alshBindMoveComplete(h, Some(moved), std::ptr::null_mut()));
+    assert!(napi::handles::alshRelease(h));
+    assert!(!napi::handles::alshBindMoveComplete(h, None, std::ptr::null_mut()));
+}
@@ -6,25 +6,65 @@
 //! 2. If the function is a constructor for a struct, i.e, `Struct::new` then `alsNewStruct` is the chosen name
 //! 3. Destructors have names of the form `alsFreeStruct` where `Struct::drop` is invoked.
 //! 4. Functions exclusive to the Native API begin with `alsn` and are named in `CamelCase`.
+//! 5. Functions of the handle based API (see [handles]) begin with `alsh`, and otherwise follow the names of their pointer based counterparts.
+//! 6. Functions of the .NET friendly API (see `dotnet`, requires the `dotnet` feature) begin with `alsd`, and otherwise follow the names of their handle based counterparts.
+//!
+//! The pointer based constructors, destructors and consuming functions are only available with the `legacy_ptr` feature (enabled by default).
 //! 
 //! Wrapper functions will not panic unless underlying engine functions panic, i.e, in case of errors most wrapper functions will simply return (in case of void return type) or return a default value after printing appropriate error messages.
-//! *Exception*. Wrapper functions that require a const c_char* pointer panic if the CStr is not a valid Utf8 String.
 //! If the function returns a mutable pointer, then in case of an error a [NULL Pointer](std::ptr::null_mut) is returned.
+//! In either case, the error is also recorded for the calling thread, and can be retrieved with `alsGetLastError` (see [error]).
+//!
+//! # Querying the world from callbacks
+//! The following functions only read the World, and are safe to call with the borrowed pointer received by the turn (and other) callbacks:
+//! `alsIdList`, `alsVerifyUID`, `alsGetTypeID`, `alsTilePermAt`, `alsnGetWorldWidth`, `alsnGetWorldHeight`, `alsnUnitCount`, `alsnGetUnitHealth`, `alsnGetUnitTypeStats`,
+//! `alsnUnitRef`, `alsnGetUnitTile`, `alsnIsUnitPlayer`, `alsnIsTileVisible`, `alsnUnitAt`, `alsnTileDistance`, `alsnGridDistance`, `alsnIsTileAtRange`, `alsnGetElevation`, `alsnGetHeightAdvantage`, `alsnReachableTiles`, `alsnGetInputScheme` and the map text getters, e.g, `alsnGetMapTitle`.
+//! Orders are issued through the `alsnPush*Order` functions instead of mutating the World directly.
 
 use crate::input::Order;
+#[cfg(feature = "legacy_ptr")]
 use raylib::prelude::Color;
+#[cfg(feature = "legacy_ptr")]
 use crate::utils::CTurnHandle;
+#[cfg(feature = "legacy_ptr")]
 use crate::utils::CInitHandle;
+#[cfg(feature = "legacy_ptr")]
+use crate::utils::CInitCtxHandle;
+#[cfg(feature = "legacy_ptr")]
+use crate::utils::CTurnCtxHandle;
+#[cfg(feature = "legacy_ptr")]
+use crate::utils::{CUpdateHandle, CUnitDeathHandle, CAttackHandle, CPhaseHandle, CDrawHandle, CVictoryHandle, CResultsHandle, CExitHandle, CTimeoutHandle, CMapLoadedHandle, CPickupHandle, CNarrationHandle, CMoveCompleteHandle};
+use std::ffi::c_void;
+#[cfg(feature = "legacy_ptr")]
 use crate::utils::StateListener;
+#[cfg(feature = "legacy_ptr")]
 use crate::display::Display;
+use crate::display::Runtime;
+#[cfg(feature = "legacy_ptr")]
 use crate::world::UnitType;
 use crate::world;
 use crate::world::World;
+use crate::world::pickup;
+use crate::world::pickup::PickupEffect;
+use crate::tween::Tween;
+use crate::tween::Easing;
+use crate::tween::CTweenHandle;
+use crate::schedule::CScheduleHandle;
+use crate::event::RawEvent;
 use std::ffi::CStr;
+use std::ffi::CString;
 use std::os::raw::c_char;
 use crate::utils::ResourceSet;
+use crate::utils::Settings;
+use crate::save::SaveSlots;
 use std::ptr;
 
+pub mod handles;
+pub mod error;
+#[cfg(feature = "dotnet")]
+pub mod dotnet;
+
+#[cfg(feature = "legacy_ptr")]
 macro_rules! create_release {
 	($fname:ident, $stype:ident) => {
 		#[no_mangle]
@@ -43,18 +83,35 @@ macro_rules! create_release {
 macro_rules! check_nonnull {
 	($pname:ident, $msg: expr) => {
 		if $pname.is_null() {
-			eprintln!($msg);
+			error::report(error::ERR_NULL_POINTER, $msg);
 			return;
 		}
 	};
 	($pname:ident, $msg: expr, $ret: expr) => {
 		if $pname.is_null() {
-			eprintln!($msg);
+			error::report(error::ERR_NULL_POINTER, $msg);
 			return $ret;
 		}
 	};
 }
 
+/// Copy a (non-null) C string argument into a `String`, or report an error and return.
+macro_rules! cstr_arg {
+	($pname:ident, $what: expr) => {
+		cstr_arg!($pname, $what, ())
+	};
+	($pname:ident, $what: expr, $ret: expr) => {
+		match unsafe { CStr::from_ptr($pname) }.to_str() {
+			Ok(s) => s.to_owned(),
+			Err(_) => {
+				error::report(error::ERR_INVALID_STRING, concat!("fatal [napi]: ", $what, " is not UtfString"));
+				return $ret;
+			}
+		}
+	};
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[allow(missing_docs)]
 #[no_mangle]
 pub extern "C" fn alsNewResourceSet() -> *mut ResourceSet {
@@ -62,6 +119,7 @@ pub extern "C" fn alsNewResourceSet() -> *mut ResourceSet {
 	Box::into_raw(Box::new(rset))
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[allow(missing_docs)]
 #[no_mangle]
 pub extern "C" fn  alsBlank_World() -> *mut World {
@@ -69,18 +127,45 @@ pub extern "C" fn  alsBlank_World() -> *mut World {
 	Box::into_raw(Box::new(w))
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Create an empty world of hexagonal tiles, with specified origin and tile size, i.e, the width and height of a hexagon.
+pub extern "C" fn alsBlankHex_World(ox: i32, oy: i32, tx: i32, ty: i32) -> *mut World {
+	let w = World::blank_hex(ox, oy, tx, ty);
+	Box::into_raw(Box::new(w))
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[allow(missing_docs)]
 #[no_mangle]
 pub extern "C" fn alsNewUnitType(tid: u8, name: *const c_char, health: f32, mov_rate: f32, movt: u8, ran: u8, adur: f32) -> *mut world::UnitType {
 	check_nonnull!(name, "fatal [napi]: Pointer to UnitType display name String is NULL", ptr::null_mut());
-	//Convert String
-	let p = unsafe { CStr::from_ptr(name) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("UnitType display name is not UtfString");
+	let p = cstr_arg!(name, "UnitType display name", ptr::null_mut());
 	
 	let ut = world::UnitType::new(tid, p, health, mov_rate, movt, ran, adur);
 	Box::into_raw(Box::new(ut))
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Create a variant of the registered unit type with id `base`, inheriting its texture, animations and stats.
+/// The variant may be modified like any other unit type before being registered with `alsRegisterUnitType`.
+/// Returns NULL on NULL pointer, invalid name, or invalid type ID.
+pub extern "C" fn alsnDeriveUnitType(w: *const World, base: u8, name: *const c_char) -> *mut world::UnitType {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	check_nonnull!(name, "fatal [napi]: Pointer to UnitType display name String is NULL", ptr::null_mut());
+	let p = cstr_arg!(name, "UnitType display name", ptr::null_mut());
+	let w = unsafe { &*w };
+	match w.unit_types.get(&base) {
+		Some(b) => Box::into_raw(Box::new(b.variant(p))),
+		None => {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit type id={}", base));
+			ptr::null_mut()
+		}
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[allow(missing_docs)]
 #[no_mangle]
 pub extern "C" fn alsNewStateListener() -> *mut StateListener {
@@ -90,9 +175,13 @@ pub extern "C" fn alsNewStateListener() -> *mut StateListener {
 
 pub type DfuncType = extern "C" fn(u8, u8) -> f32;
 
+#[cfg(feature = "legacy_ptr")]
 create_release!(alsFreeResourceSet, ResourceSet);
+#[cfg(feature = "legacy_ptr")]
 create_release!(alsFreeWorld, World);
+#[cfg(feature = "legacy_ptr")]
 create_release!(alsFreeUnitType, UnitType);
+#[cfg(feature = "legacy_ptr")]
 create_release!(alsFreeStateListener, StateListener);
 
 #[no_mangle]
@@ -104,14 +193,13 @@ pub extern "C" fn alsnFreeVec(v: *mut Vec<u8>) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsMapTexture(rs: *mut ResourceSet, id: u8, path: *const c_char) {
 	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
 	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL");
-	//Copy String
-	let p = unsafe { CStr::from_ptr(path) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(path, "ResourceSet path");
     
     unsafe {
     	let r = &mut *rs;
@@ -119,6 +207,51 @@ pub extern "C" fn alsMapTexture(rs: *mut ResourceSet, id: u8, path: *const c_cha
     }
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Map a texture with the filter to use when it is scaled (0 - point, 1 - bilinear, 2 - trilinear, 3-5 - anisotropic 4x, 8x and 16x), and whether to generate mipmaps once loaded.
+/// Returns false on NULL pointer or invalid filter.
+pub extern "C" fn alsMapTextureEx(rs: *mut ResourceSet, id: u8, path: *const c_char, filter: u8, mipmaps: bool) -> bool {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL", false);
+	let p = cstr_arg!(path, "ResourceSet path", false);
+	match tex_filter_arg(filter) {
+		Some(f) => {
+			unsafe { (*rs).map_texture_ex(id, &p, f, mipmaps) };
+			true
+		},
+		None => false
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Make the texture with the specified id a nine-patch, with the given border insets in pixels, see `ResourceSet::set_nine_patch`. Returns false on NULL pointer.
+pub extern "C" fn alsSetNinePatch(rs: *mut ResourceSet, id: u8, left: i32, top: i32, right: i32, bottom: i32) -> bool {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", false);
+	unsafe { (*rs).set_nine_patch(id, left, top, right, bottom) };
+	true
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Animate the texture with the specified id, used for a UI role, with `frn` frames of size (fw, fh) laid out horizontally, at `fr` frames per second, see `ResourceSet::set_ui_anim`.
+/// Returns false on NULL pointer.
+pub extern "C" fn alsSetUiAnim(rs: *mut ResourceSet, id: u8, fw: u32, fh: u32, frn: u8, fr: f32) -> bool {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", false);
+	unsafe { (*rs).set_ui_anim(id, (fw, fh), frn, fr) };
+	true
+}
+
+pub(crate) fn tex_filter_arg(filter: u8) -> Option<crate::utils::TexFilter> {
+	let f = crate::utils::TexFilter::from_u8(filter);
+	if f.is_none() {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid texture filter {}", filter));
+	}
+	f
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsMapTextureRegion(rs: *mut ResourceSet, tid: u8, parent_id: u8, x: f32, y: f32, w: f32, h: f32) {
@@ -139,6 +272,7 @@ pub extern "C" fn alsCreateStatic(w: *mut World, tex_id: u8, cx: i32, cy: i32) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 /// FFI for `UnitType.def_anim_muted`. Tuples have been expanded into individual arguments.
 pub extern "C" fn alsDefAnim(u: *mut UnitType, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) {
@@ -148,6 +282,44 @@ pub extern "C" fn alsDefAnim(u: *mut UnitType, fw: u32, fh: u32, frn: u8, cfx: u
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// FFI for `UnitType.def_idle_anim`. Tuples have been expanded into individual arguments.
+pub extern "C" fn alsDefIdleAnim(u: *mut UnitType, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL");
+	unsafe {
+		(&mut *u).def_idle_anim((fw, fh), frn, (cfx,cfy), fr, flip);
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// FFI for `UnitType.def_stand_anim`. `facing` is 0, 1, 2 or 3 for down, left, up and right respectively.
+pub extern "C" fn alsDefStandAnim(u: *mut UnitType, facing: u8, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL");
+	let f = match world::u8_as_facing(facing) {
+		Some(f) => f,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid facing {}", facing));
+			return;
+		}
+	};
+	unsafe {
+		(&mut *u).def_stand_anim(f, (fw, fh), frn, (cfx,cfy), fr, flip);
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// FFI for `UnitType.set_idle_delay`.
+pub extern "C" fn alsSetIdleDelay(u: *mut UnitType, secs: f32) {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL");
+	unsafe {
+		(&mut *u).set_idle_delay(secs);
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 /// FFI for `UnitType.def_anim`. Tuples have been expanded into indivifual arguments.
 pub extern "C" fn alsDefAnimUnmuted(u: *mut UnitType, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool, snd: u8, lp: bool) {
@@ -157,6 +329,7 @@ pub extern "C" fn alsDefAnimUnmuted(u: *mut UnitType, fw: u32, fh: u32, frn: u8,
 	}	
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 /// Creates the display and begins the game.
 /// ResourceSet and World are deallocated when this method returns.
@@ -165,18 +338,17 @@ pub extern "C" fn alsBeginS_Display(sw: i32, sh: i32, t: *const c_char, rs: *mut
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL");
 	check_nonnull!(t, "fatal [napi]: Pointer to Display Title String is NULL");
 
-	// Allocate and Copy
-	let p = unsafe { CStr::from_ptr(t) };
-    let p = p.to_str().map(|s| s).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(t, "Display title");
 
 	unsafe {
 		let wb = Box::from_raw(w);
 		let rsb = Box::from_raw(rs);
-		let d = Display::new_s(sw, sh, p);
+		let d = Display::new_s(sw, sh, &p);
 		d.begin_s(*rsb, *wb);
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 /// Creates the display and begins the game.
 /// ResourceSet, World, and StateListener are deallocated when this method returns.
@@ -186,19 +358,82 @@ pub extern "C" fn alsBegin_Display(sw: i32, sh: i32, vsync: bool, fps: u32, t: *
 	check_nonnull!(t, "fatal [napi]: Pointer to Display Title String is NULL");
 	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
 
-	// Allocate and Copy
-	let p = unsafe { CStr::from_ptr(t) };
-    let p = p.to_str().map(|s| s).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(t, "Display title");
 
     unsafe {
 		let wb = Box::from_raw(w);
 		let rsb = Box::from_raw(rs);
-		let d = Display::new(sw, sh, fps, vsync, p, Color::BLACK, mvl);
+		let d = Display::new(sw, sh, fps, vsync, &p, Color::BLACK, mvl);
 		let s = Box::from_raw(sl);
 		d.begin(*rsb, *wb, *s);
 	}   
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Creates the display and returns a [Runtime], without entering the draw-update loop.
+/// ResourceSet, World, and StateListener are consumed. Returns NULL on error, in which case nothing is consumed.
+pub extern "C" fn alsInit_Display(sw: i32, sh: i32, vsync: bool, fps: u32, t: *const c_char, rs: *mut ResourceSet, w: *mut World, sl: *mut StateListener, mvl: f32) -> *mut Runtime {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", ptr::null_mut());
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	check_nonnull!(t, "fatal [napi]: Pointer to Display Title String is NULL", ptr::null_mut());
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL", ptr::null_mut());
+	let p = cstr_arg!(t, "Display title", ptr::null_mut());
+	unsafe {
+		let d = Display::new(sw, sh, fps, vsync, &p, Color::BLACK, mvl);
+		let rt = d.init(*Box::from_raw(rs), *Box::from_raw(w), *Box::from_raw(sl));
+		Box::into_raw(Box::new(rt))
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Variant of `alsInit_Display` that takes the resolution, fps, vsync, volumes, ui and text scale, high-contrast mode and key bindings from the settings file at `path` (see `Settings` in `alesia::utils`).
+/// A missing file gives the defaults. Returns NULL on error, in which case nothing is consumed.
+pub extern "C" fn alsInit_DisplayFromSettings(path: *const c_char, t: *const c_char, rs: *mut ResourceSet, w: *mut World, sl: *mut StateListener) -> *mut Runtime {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", ptr::null_mut());
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	check_nonnull!(path, "fatal [napi]: Pointer to Settings Path String is NULL", ptr::null_mut());
+	check_nonnull!(t, "fatal [napi]: Pointer to Display Title String is NULL", ptr::null_mut());
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL", ptr::null_mut());
+	let p = cstr_arg!(path, "Settings path", ptr::null_mut());
+	let title = cstr_arg!(t, "Display title", ptr::null_mut());
+	let s = match load_settings(&p) { Some(s) => s, None => return ptr::null_mut() };
+	unsafe {
+		let d = Display::from_settings(&s, &title);
+		let rt = d.init(*Box::from_raw(rs), *Box::from_raw(w), *Box::from_raw(sl));
+		Box::into_raw(Box::new(rt))
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Poll input, update and draw a single frame. Returns false once the window has been asked to close.
+pub extern "C" fn alsStep_Runtime(rt: *mut Runtime) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).step() }
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Close the window and release the runtime, returning the (owned) World; release it with `alsFreeWorld`.
+pub extern "C" fn alsShutdown_Runtime(rt: *mut Runtime) -> *mut World {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", ptr::null_mut());
+	let rt = unsafe { Box::from_raw(rt) };
+	Box::into_raw(Box::new(rt.shutdown()))
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Borrow the World of a running game. The pointer is valid until the runtime is shut down.
+/// # Safety
+/// **Under no circumstances must the reference returned be released by the callsite**.
+pub extern "C" fn alsWorld_Runtime(rt: *mut Runtime) -> *mut World {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", ptr::null_mut());
+	unsafe { (*rt).world_mut() }
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsRegisterUnitType(w: *mut World,u: *mut UnitType, id: u8) {
@@ -211,14 +446,13 @@ pub extern "C" fn alsRegisterUnitType(w: *mut World,u: *mut UnitType, id: u8) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsSetUnitInfo(u: *mut UnitType, s: *const c_char) {
 	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL");
 	check_nonnull!(s, "fatal [napi]: Pointer to information string is NULL");
-	//Copy String
-	let p = unsafe { CStr::from_ptr(s) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(s, "Information string");
     
     unsafe {
     	let r = &mut *u;
@@ -226,6 +460,112 @@ pub extern "C" fn alsSetUnitInfo(u: *mut UnitType, s: *const c_char) {
     }
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set the attack and defense strength of a unit type, used by the built-in damage formula.
+pub extern "C" fn alsSetCombatStats(u: *mut UnitType, attack: f32, defense: f32) {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL");
+	unsafe {
+		let r = &mut *u;
+		r.set_combat_stats(attack, defense);
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set the texture id of the portrait shown in the unit panel for units of a unit type, or negative for none. Returns false on NULL pointer or invalid id.
+pub extern "C" fn alsSetPortrait(u: *mut UnitType, tex: i32) -> bool {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL", false);
+	match opt_tex_arg(tex, "portrait") {
+		Some(p) => {
+			unsafe { (*u).set_portrait(p) };
+			true
+		},
+		None => false
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set the attacks per turn of player units of a unit type, and whether they may move after attacking; negative attacks to follow the world (see `alsnSetDefaultActions`).
+/// Returns false on NULL pointer or invalid number of attacks.
+pub extern "C" fn alsSetActions(u: *mut UnitType, attacks: i32, move_after_attack: bool) -> bool {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL", false);
+	match actions_arg(attacks, move_after_attack) {
+		Some(a) => {
+			unsafe { (*u).set_actions(a) };
+			true
+		},
+		None => false
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set how many tiles away units of a unit type see under fog of war; negative for their movement and range (the default).
+/// Returns false on NULL pointer or invalid sight.
+pub extern "C" fn alsSetSight(u: *mut UnitType, sight: i32) -> bool {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL", false);
+	match sight_arg(sight) {
+		Some(s) => {
+			unsafe { (*u).set_sight(s) };
+			true
+		},
+		None => false
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set the voice lines of a unit type for a moment (0 - selected, 1 - starting to move, 2 - attacking, 3 - dying) to `n` sound ids, one of which is played at random,
+/// and the seconds before the group may play again; `n` may be 0 to silence the group. Returns false on NULL pointer or invalid kind.
+pub extern "C" fn alsSetVoice(u: *mut UnitType, kind: u8, sounds: *const u8, n: usize, cooldown: f32) -> bool {
+	check_nonnull!(u, "fatal [napi]: Pointer to UnitType is NULL", false);
+	if n != 0 {
+		check_nonnull!(sounds, "fatal [napi]: Pointer to sound ids is NULL", false);
+	}
+	match bark_arg(kind) {
+		Some(k) => {
+			unsafe { (*u).set_voice(k, read_slice(sounds, n), cooldown) };
+			true
+		},
+		None => false
+	}
+}
+
+/// Kind of voice line of a unit type. Reports and returns None if out of range.
+pub(crate) fn bark_arg(kind: u8) -> Option<world::voice::BarkKind> {
+	let k = world::voice::BarkKind::from_u8(kind);
+	if k.is_none() {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid voice kind {}", kind));
+	}
+	k
+}
+
+/// Optional sight of a unit type, None if negative. Reports and returns None if out of range.
+pub(crate) fn sight_arg(sight: i32) -> Option<Option<u8>> {
+	match sight {
+		s if s < 0 => Some(None),
+		s if s <= 255 => Some(Some(s as u8)),
+		s => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid sight {}", s));
+			None
+		}
+	}
+}
+
+/// Optional actions of a unit type, None for negative attacks. Reports and returns None if out of range.
+pub(crate) fn actions_arg(attacks: i32, move_after_attack: bool) -> Option<Option<world::Actions>> {
+	match attacks {
+		a if a < 0 => Some(None),
+		a if a <= 255 => Some(Some(world::Actions { attacks: a as u8, move_after_attack: move_after_attack })),
+		a => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid number of attacks {}", a));
+			None
+		}
+	}
+}
+
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsBindDamageFunc(w: *mut World, f: DfuncType) {
@@ -235,14 +575,13 @@ pub extern "C" fn alsBindDamageFunc(w: *mut World, f: DfuncType) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsMapFont(rs: *mut ResourceSet, id: u8, path: *const c_char) {
 	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
 	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL");
-	//Copy String
-	let p = unsafe { CStr::from_ptr(path) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(path, "ResourceSet path");
     
     unsafe {
     	let r = &mut *rs;
@@ -250,6 +589,25 @@ pub extern "C" fn alsMapFont(rs: *mut ResourceSet, id: u8, path: *const c_char)
     }
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Map a font, generating an atlas for each of the `nsizes` pixel sizes in `sizes`.
+/// `ranges` holds `nranges` pairs of (first, last) codepoints to include; if `nranges` is 0, printable ASCII is used.
+pub extern "C" fn alsMapFontAtlas(rs: *mut ResourceSet, id: u8, path: *const c_char, sizes: *const i32, nsizes: usize, ranges: *const u32, nranges: usize) {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
+	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL");
+	check_nonnull!(sizes, "fatal [napi]: Pointer to font size array is NULL");
+	if nranges != 0 {
+		check_nonnull!(ranges, "fatal [napi]: Pointer to codepoint range array is NULL");
+	}
+	let p = cstr_arg!(path, "ResourceSet path");
+	unsafe {
+		let r = &mut *rs;
+		r.map_font_atlas(id, &p, &read_slice(sizes, nsizes), &read_ranges(ranges, nranges));
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsBindInit(sl: *mut StateListener, f: CInitHandle) {
@@ -260,6 +618,7 @@ pub extern "C" fn alsBindInit(sl: *mut StateListener, f: CInitHandle) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsBindTurn(sl: *mut StateListener, f: CTurnHandle) {
@@ -270,6 +629,197 @@ pub extern "C" fn alsBindTurn(sl: *mut StateListener, f: CTurnHandle) {
 	}
 }
 
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Variant of `alsBindInit` for callbacks that take a context pointer.
+/// `ctx` is passed back as is on every invocation; the call site retains ownership of it.
+pub extern "C" fn alsBindInitCtx(sl: *mut StateListener, f: CInitCtxHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawinit_ctx(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Variant of `alsBindTurn` for callbacks that take a context pointer.
+/// `ctx` is passed back as is on every invocation; the call site retains ownership of it.
+pub extern "C" fn alsBindTurnCtx(sl: *mut StateListener, f: CTurnCtxHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawturn_ctx(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked every frame, with the frame time in seconds. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindUpdate(sl: *mut StateListener, f: CUpdateHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawupdate(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when a unit dies, with the id and type id of the unit. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindUnitDeath(sl: *mut StateListener, f: CUnitDeathHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawunit_death(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when a unit attacks, with the ids of the attacker and target, and the damage dealt. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindAttack(sl: *mut StateListener, f: CAttackHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawattack(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when a unit completes a move, with the id of the unit and the coordinates of the tile it ended the move on. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindMoveComplete(sl: *mut StateListener, f: CMoveCompleteHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawmove_complete(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when a transition between screens has finished, with the phase entered. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindTransitionDone(sl: *mut StateListener, f: CPhaseHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawtransition_done(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when the banner announcing a new phase has left the screen. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindBannerDone(sl: *mut StateListener, f: CPhaseHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawbanner_done(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when the results screen is dismissed, with the outcome (1 - victory, 2 - defeat). The game has ended at this point.
+/// `ctx` is passed back as is.
+pub extern "C" fn alsBindResults(sl: *mut StateListener, f: CResultsHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawresults(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when the game enters a new phase. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindPhaseChange(sl: *mut StateListener, f: CPhaseHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawphase_change(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked every frame, after the world and HUD have been drawn. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindDraw(sl: *mut StateListener, f: CDrawHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawdraw(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked at the end of each turn; it returns 1 for victory, 2 for defeat and 0 to continue. `ctx` is passed back as is on every invocation.
+pub extern "C" fn alsBindVictoryCheck(sl: *mut StateListener, f: CVictoryHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawvictory_check(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked once the game is about to exit, e.g, to save the battle. `ctx` is passed back as is.
+pub extern "C" fn alsBindExit(sl: *mut StateListener, f: CExitHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawexit(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when time runs out in a player turn, with true if the bank has been spent. `ctx` is passed back as is.
+pub extern "C" fn alsBindTimeout(sl: *mut StateListener, f: CTimeoutHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawtimeout(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked once a map has been loaded, with its path, before the intro is shown, e.g, to place loot. `ctx` is passed back as is.
+pub extern "C" fn alsBindMapLoaded(sl: *mut StateListener, f: CMapLoadedHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawmap_loaded(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked when a unit collects a pickup, with the id of the unit, and the kind and value of the pickup (see `alsnPlacePickup`). `ctx` is passed back as is.
+pub extern "C" fn alsBindPickup(sl: *mut StateListener, f: CPickupHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawpickup(f, ctx)
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Bind a callback invoked with each line of narration, in plain text, e.g, "Archer selected, 7 of 10 HP", to pass on to text-to-speech. `ctx` is passed back as is.
+/// The string is valid only for the duration of the callback.
+pub extern "C" fn alsBindNarration(sl: *mut StateListener, f: CNarrationHandle, ctx: *mut c_void) {
+	check_nonnull!(sl, "fatal [napi]: Pointer to StateListener is NULL");
+	unsafe {
+		let sl = &mut *sl;
+		sl._bind_rawnarration(f, ctx)
+	}
+}
+
 #[no_mangle]
 /// Getter for the health of the unit with specified ID.
 /// Returns -1.0 on NULL pointer or invalid ID.
@@ -280,6 +830,7 @@ pub extern "C" fn alsnGetUnitHealth(w: *mut World, uid: u8) -> f32 {
 		if let Some(h) = w.units.get(&uid){
 			h.health
 		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
 			-1.0
 		}
 	}
@@ -308,8 +859,7 @@ pub extern "C" fn alsnGetUnitY(w: *const world::Unit) -> f32 {
 
 #[no_mangle]
 /// Get an immutable (i.e, `readonly`) reference to the unit with the specified id.
-/// # Paincs
-/// If the specified unit ID is invalid.
+/// Returns NULL if the specified unit ID is invalid.
 /// # Safety
 /// The function exposes an immutable reference to the [Unit](crate::world::Unit) instance corresponding to the id.
 /// **Under no circumstances must the reference returned be released by the callsite**.
@@ -318,8 +868,13 @@ pub extern "C" fn alsnUnitRef(w: *mut World, uid: u8) -> *const world::Unit {
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null());
 	unsafe {
 		let w = &*w;
-		let u = w.units.get(&uid).expect("Invalid unit ID");
-		u
+		match w.units.get(&uid) {
+			Some(u) => u,
+			None => {
+				error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+				ptr::null()
+			}
+		}
 	}
 }
 
@@ -364,7 +919,7 @@ pub extern "C" fn alsnPushVictoryOrder(i: *mut Vec<Order>) {
 }
 
 #[no_mangle]
-/// Push a victory order
+/// Push a defeat order
 pub extern "C" fn alsnPushDefeatOrder(i: *mut Vec<Order>) {
 	check_nonnull!(i, "fatal [napi]: Pointer to Order Vector is NULL");
 	unsafe {
@@ -374,7 +929,7 @@ pub extern "C" fn alsnPushDefeatOrder(i: *mut Vec<Order>) {
 }
 
 #[no_mangle]
-/// Push a victory order
+/// Push an order to modify unit health. If `is_rel` is true, `val` is relative to the unit's maximum health.
 pub extern "C" fn alsnPushMutHealthOrder(i: *mut Vec<Order>, uid: u8, val: f32, is_rel: bool) {
 	check_nonnull!(i, "fatal [napi]: Pointer to Order Vector is NULL");
 	unsafe {
@@ -388,11 +943,16 @@ pub extern "C" fn alsnPushMutHealthOrder(i: *mut Vec<Order>, uid: u8, val: f32,
 }
 
 #[no_mangle]
-#[allow(missing_docs)]
+/// Spawn a unit of the given type, and return its id.
+/// Returns 0 if the unit type id is invalid; since 0 is also a valid unit id, check `alsGetLastError` if in doubt.
 pub extern "C" fn alsSpawnUnit(w: *mut World, tid: u8, tx: i32, ty: i32, tint: i32, plr: bool) -> u8 {
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0x00);
 	unsafe {
 		let w = &mut *w;
+		if !w.unit_types.contains_key(&tid) {
+			error::report(error::ERR_INVALID_ID, &format!("fatal [napi]: Invalid unit type id={}", tid));
+			return 0x00;
+		}
 		crate::world::spawn_unit(w, tid, (tx, ty), tint, plr)
 	}
 }
@@ -407,23 +967,30 @@ pub extern "C" fn alsIdList(w: *const World) -> *mut Vec<u8> {
 #[no_mangle]
 /// Return length of byte vector.
 pub extern "C" fn alsnVecLen(u: *const Vec<u8>) -> usize {
+	check_nonnull!(u, "fatal [napi]: Vec_u8 pointer is NULL", 0);
 	unsafe{(&*u).len()}
 }
 
 #[no_mangle]
-/// Return byte value at index `elm` in `Vec<u8>`
+/// Return byte value at index `elm` in `Vec<u8>`, or 0 if `elm` is out of range.
 pub extern "C" fn alsnVecAt(u: *const Vec<u8>, elm: usize) -> u8 {
-	unsafe{(&*u)[elm]}
+	check_nonnull!(u, "fatal [napi]: Vec_u8 pointer is NULL", 0);
+	match unsafe{(&*u).get(elm)} {
+		Some(v) => *v,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Index {} out of range", elm));
+			0
+		}
+	}
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsMapSound(rs: *mut ResourceSet, id: u8, path: *const c_char) {
 	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
 	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL");
-	//Copy String
-	let p = unsafe { CStr::from_ptr(path) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(path, "ResourceSet path");
     
     unsafe {
     	let r = &mut *rs;
@@ -436,22 +1003,24 @@ pub extern "C" fn alsMapSound(rs: *mut ResourceSet, id: u8, path: *const c_char)
 pub extern "C" fn alsLoadMap(w: *mut World, fpath: *const c_char) -> bool {
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
 	check_nonnull!(fpath, "fatal [napi]: Level file path string is NULL", false);
-	let p = unsafe { CStr::from_ptr(fpath) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(fpath, "Level file path", false);
     unsafe {
     	let w = &mut *w;
-    	world::load_world(w, &p)
+    	let r = world::load_world(w, &p);
+    	if !r {
+    		error::report(error::ERR_OPERATION_FAILED, &format!("fatal [napi]: Failed to load level file {}", p));
+    	}
+    	r
     }
 }
 
+#[cfg(feature = "legacy_ptr")]
 #[no_mangle]
 #[allow(missing_docs)]
 pub extern "C" fn alsMapMusic(rs: *mut ResourceSet, id: u8, path: *const c_char) {
 	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
 	check_nonnull!(path, "fatal [napi]: Pointer to ResourceSet Path String is NULL");
-	//Copy String
-	let p = unsafe { CStr::from_ptr(path) };
-    let p = p.to_str().map(|s| s.to_owned()).expect("ResourceSet path is not UtfString");
+	let p = cstr_arg!(path, "ResourceSet path");
     
     unsafe {
     	let r = &mut *rs;
@@ -459,18 +1028,61 @@ pub extern "C" fn alsMapMusic(rs: *mut ResourceSet, id: u8, path: *const c_char)
     }
 }
 
-#[no_mangle]
-/// Checks if the specified unit ID is valid.
-pub extern "C" fn alsVerifyUID(w: *const World, uid: u8) -> bool {
-	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
-	unsafe {
-		let w = &*w;
-		world::is_uid_valid(w, uid)
+/// Read `n` hex colours from the array `p`, which must not be NULL unless `n` is zero.
+pub(crate) fn read_slice<T: Copy>(p: *const T, n: usize) -> Vec<T> {
+	if n == 0 {
+		return vec![];
 	}
+	unsafe { std::slice::from_raw_parts(p, n) }.to_vec()
 }
 
-#[no_mangle]
-#[allow(missing_docs)]
+pub(crate) fn read_ranges(p: *const u32, n: usize) -> Vec<(u32, u32)> {
+	read_slice(p, 2*n).chunks(2).map(|c| (c[0], c[1])).collect()
+}
+
+pub(crate) fn read_colors(p: *const i32, n: usize) -> Vec<raylib::prelude::Color> {
+	if n == 0 {
+		return vec![];
+	}
+	unsafe { std::slice::from_raw_parts(p, n) }.iter().map(|c| raylib::prelude::Color::get_color(*c)).collect()
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Set the team colours (hex colours) of unit sprites, which are replaced by palettes. See `alsMapPalette`.
+pub extern "C" fn alsSetTeamColors(rs: *mut ResourceSet, colors: *const i32, n: usize) {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
+	check_nonnull!(colors, "fatal [napi]: Pointer to colour array is NULL");
+	unsafe {
+		let r = &mut *rs;
+		r.set_team_colors(&read_colors(colors, n));
+	}
+}
+
+#[cfg(feature = "legacy_ptr")]
+#[no_mangle]
+/// Map a palette of `n` hex colours to the specified id; the i-th colour replaces the i-th team colour.
+pub extern "C" fn alsMapPalette(rs: *mut ResourceSet, id: u8, colors: *const i32, n: usize) {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL");
+	check_nonnull!(colors, "fatal [napi]: Pointer to colour array is NULL");
+	unsafe {
+		let r = &mut *rs;
+		r.map_palette(id, &read_colors(colors, n));
+	}
+}
+
+#[no_mangle]
+/// Checks if the specified unit ID is valid.
+pub extern "C" fn alsVerifyUID(w: *const World, uid: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &*w;
+		world::is_uid_valid(w, uid)
+	}
+}
+
+#[no_mangle]
+#[allow(missing_docs)]
 pub extern "C" fn alsTilePermAt(w: *const World, x: i32, y: i32) -> bool {
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
 	unsafe {
@@ -485,6 +1097,10 @@ pub extern "C" fn alsGetTypeID(w: *const World, uid: u8) -> u8 {
 	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0x00);
 	unsafe {
 		let w = &*w;
+		if !world::is_uid_valid(w, uid) {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			return 0x00;
+		}
 		world::get_type_id(w, uid)
 	}
 }
@@ -507,4 +1123,2018 @@ pub extern "C" fn alsnGetWorldHeight(w: *const World) -> usize {
 		let w = &*w;
 		w.map_size().1
 	}
-}
\ No newline at end of file
+}
+
+/// Plain struct to report the stats of a [UnitType] over FFI.
+#[repr(C)]
+pub struct UnitTypeStats {
+	/// The max health of units of this type.
+	pub max_health: f32,
+	/// The rate at which units of this type move in tiles per second.
+	pub mov_rate: f32,
+	/// The duration of attack state in seconds.
+	pub attack_dur: f32,
+	/// The number of tiles a unit of this type can move.
+	pub movement: u8,
+	/// The range of the unit's attack.
+	pub range: u8
+}
+
+#[no_mangle]
+/// Change the state (and hence the animation) of the unit with specified ID.
+/// `state` is the index of the [UnitState](crate::world::UnitState) variant, i.e, 0 for `WalkDown` through 8 for `Stand`.
+/// Returns false on NULL pointer, invalid ID or invalid state.
+pub extern "C" fn alsnSetUnitState(w: *mut World, uid: u8, state: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	let us = match world::usize_as_state(state as usize) {
+		Some(us) => us,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid unit state {}", state));
+			return false;
+		}
+	};
+	unsafe {
+		let w = &mut *w;
+		if !world::is_uid_valid(w, uid) {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			return false;
+		}
+		world::set_unit_state(w, uid, us);
+		true
+	}
+}
+
+#[no_mangle]
+/// Remove the unit with specified ID from the world.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnDespawnUnit(w: *mut World, uid: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		if !world::despawn_unit(w, uid) {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			return false;
+		}
+		true
+	}
+}
+
+#[no_mangle]
+/// Set the tint (hex colour) of the unit with specified ID.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetUnitTint(w: *mut World, uid: u8, tint: i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		if let Some(u) = w.units.get_mut(&uid) {
+			u.tint = tint;
+			true
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the palette of the unit with specified ID, or restore its original colours if `enable` is false.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetUnitPalette(w: *mut World, uid: u8, pid: u8, enable: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		if let Some(u) = w.units.get_mut(&uid) {
+			u.palette = if enable { Some(pid) } else { None };
+			true
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the texture id of the portrait of the unit with specified ID, overriding that of its type, or negative to show that of its type.
+/// Returns false on NULL pointer, invalid ID or invalid texture id.
+pub extern "C" fn alsnSetUnitPortrait(w: *mut World, uid: u8, tex: i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_unit_portrait(unsafe { &mut *w }, uid, tex)
+}
+
+/// Optional texture id, negative for none. Reports and returns None if out of range.
+pub(crate) fn opt_tex_arg(tex: i32, what: &str) -> Option<Option<u8>> {
+	match tex {
+		t if t < 0 => Some(None),
+		t if t <= 255 => Some(Some(t as u8)),
+		t => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid {} texture id={}", what, t));
+			None
+		}
+	}
+}
+
+pub(crate) fn set_unit_portrait(w: &mut World, uid: u8, tex: i32) -> bool {
+	let p = match opt_tex_arg(tex, "portrait") { Some(p) => p, None => return false };
+	match w.units.get_mut(&uid) {
+		Some(u) => {
+			u.portrait = p;
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the animation speed multiplier of the unit with specified ID (1.0 is normal speed).
+/// Returns false on NULL pointer, invalid ID or negative speed.
+pub extern "C" fn alsnSetUnitAnimSpeed(w: *mut World, uid: u8, speed: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if speed.is_nan() || speed < 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid animation speed {}", speed));
+		return false;
+	}
+	unsafe {
+		let w = &mut *w;
+		if let Some(u) = w.units.get_mut(&uid) {
+			u.anim_speed = speed;
+			true
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Pause or resume the animation of the unit with specified ID. Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetUnitAnimPaused(w: *mut World, uid: u8, paused: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		if let Some(u) = w.units.get_mut(&uid) {
+			u.anim_paused = paused;
+			true
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Make the unit with specified ID hold its position, attacking player units within range on the enemy turn. Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetUnitHold(w: *mut World, uid: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unit_behavior(unsafe { &mut *w }, uid, |w| world::set_behavior(w, uid, Some(world::Behavior::Hold)))
+}
+
+#[no_mangle]
+/// Make the unit with specified ID guard the tile (x, y), attacking player units which come within `radius` tiles of it on the enemy turn.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetUnitGuard(w: *mut World, uid: u8, x: i32, y: i32, radius: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unit_behavior(unsafe { &mut *w }, uid, |w| world::set_behavior(w, uid, Some(world::Behavior::Guard { tile: (x, y), radius: radius })))
+}
+
+#[no_mangle]
+/// Append the tile (x, y) to the patrol of the unit with specified ID, starting a patrol if it has another behavior. Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnAddUnitPatrolPoint(w: *mut World, uid: u8, x: i32, y: i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unit_behavior(unsafe { &mut *w }, uid, |w| world::add_patrol_point(w, uid, (x, y)))
+}
+
+#[no_mangle]
+/// Clear the standing behavior of the unit with specified ID. Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnClearUnitBehavior(w: *mut World, uid: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unit_behavior(unsafe { &mut *w }, uid, |w| world::set_behavior(w, uid, None))
+}
+
+#[no_mangle]
+/// Getter for the standing behavior of the unit with specified ID: 0 - none, 1 - hold, 2 - guard, 3 - patrol.
+/// Returns -1 on NULL pointer or invalid ID.
+pub extern "C" fn alsnGetUnitBehavior(w: *const World, uid: u8) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	match unsafe { &*w }.units.get(&uid) {
+		Some(u) => match u.behavior {
+			None => 0,
+			Some(world::Behavior::Hold) => 1,
+			Some(world::Behavior::Guard { .. }) => 2,
+			Some(world::Behavior::Patrol { .. }) => 3
+		},
+		None => {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			-1
+		}
+	}
+}
+
+/// Apply `f` to set the behavior of a unit, reporting an invalid ID.
+pub(crate) fn unit_behavior(w: &mut World, uid: u8, f: impl FnOnce(&mut World) -> bool) -> bool {
+	if f(w) {
+		true
+	} else {
+		error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+		false
+	}
+}
+
+#[no_mangle]
+/// Write the stats of the unit type with specified type ID into `out`.
+/// Returns false on NULL pointer or invalid type ID, in which case `out` is left untouched.
+pub extern "C" fn alsnGetUnitTypeStats(w: *const World, tid: u8, out: *mut UnitTypeStats) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out, "fatal [napi]: Pointer to UnitTypeStats is NULL", false);
+	unsafe {
+		let w = &*w;
+		if let Some(ut) = w.unit_types.get(&tid) {
+			*out = UnitTypeStats {
+				max_health: ut.max_health(),
+				mov_rate: ut.mov_rate(),
+				attack_dur: ut.attack_dur(),
+				movement: ut.movement(),
+				range: ut.range()
+			};
+			true
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit type id={}", tid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Returns the number of alive units in the world.
+pub extern "C" fn alsnUnitCount(w: *const World) -> usize {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	unsafe {
+		let w = &*w;
+		w.units.len()
+	}
+}
+#[no_mangle]
+/// Write the tile position of the unit with specified ID into `out_x` and `out_y`.
+/// Returns false on NULL pointer or invalid ID, in which case the outputs are left untouched.
+pub extern "C" fn alsnGetUnitTile(w: *const World, uid: u8, out_x: *mut i32, out_y: *mut i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_x, "fatal [napi]: Pointer to x co-ordinate is NULL", false);
+	check_nonnull!(out_y, "fatal [napi]: Pointer to y co-ordinate is NULL", false);
+	unsafe {
+		match world::unit_tile(&*w, uid) {
+			Some((x, y)) => {
+				*out_x = x;
+				*out_y = y;
+				true
+			},
+			None => {
+				error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+				false
+			}
+		}
+	}
+}
+
+#[no_mangle]
+/// Returns true if the unit with specified ID is controlled by the player. Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnIsUnitPlayer(w: *const World, uid: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { world::is_unit_player_controlled(&*w, uid) }
+}
+
+#[no_mangle]
+/// Write the ID of the unit standing on tile (`x`, `y`) into `out_uid`.
+/// Returns false on NULL pointer, or if there is no unit on the tile.
+pub extern "C" fn alsnUnitAt(w: *const World, x: i32, y: i32, out_uid: *mut u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_uid, "fatal [napi]: Pointer to unit id is NULL", false);
+	unsafe {
+		match world::unit_at(&*w, x, y) {
+			Some(uid) => {
+				*out_uid = uid;
+				true
+			},
+			None => false
+		}
+	}
+}
+
+#[no_mangle]
+/// Returns true if tile (`x`, `y`) is seen by a player unit, or fog of war is disabled (see `alsnSetFogOfWar`). Returns false on NULL pointer.
+pub extern "C" fn alsnIsTileVisible(w: *const World, x: i32, y: i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { world::is_tile_visible(&*w, x, y) }
+}
+
+#[no_mangle]
+/// Returns the number of steps between two tiles, i.e, their manhattan distance.
+pub extern "C" fn alsnTileDistance(x1: i32, y1: i32, x2: i32, y2: i32) -> i32 {
+	world::tile_distance((x1, y1), (x2, y2))
+}
+
+#[no_mangle]
+/// Returns the layout of the tiles of the world: 0 for isometric squares, 1 for hexagons. Returns -1 on NULL pointer.
+pub extern "C" fn alsnGetGrid(w: *const World) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	unsafe { (*w).grid() as i32 }
+}
+
+#[no_mangle]
+/// Returns the number of steps between two tiles on the grid of the world, i.e, their manhattan distance on isometric squares. Returns -1 on NULL pointer.
+pub extern "C" fn alsnGridDistance(w: *const World, x1: i32, y1: i32, x2: i32, y2: i32) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	unsafe { (*w).grid().distance((x1, y1), (x2, y2)) }
+}
+
+#[no_mangle]
+/// Returns true if the two tiles are exactly at range `r` of each other; this is the check the engine uses for attacks on flat isometric squares, see `alsnGridDistance`.
+/// On elevated maps, attackers on high ground reach further, see `alsnSetElevationRules`.
+pub extern "C" fn alsnIsTileAtRange(x1: i32, y1: i32, x2: i32, y2: i32, r: u8) -> bool {
+	world::tile_distance((x1, y1), (x2, y2)) == r as i32
+}
+
+#[no_mangle]
+/// Computes the tiles the unit with specified ID can move to this turn, and returns their number.
+/// Up to `cap` tiles are written into `out` as consecutive (x, y) pairs, i.e, `out` must have room for `2*cap` integers.
+/// `out` may be NULL (with `cap` 0) to query the number of tiles alone. Returns 0 on NULL World pointer or invalid ID.
+pub extern "C" fn alsnReachableTiles(w: *const World, uid: u8, out: *mut i32, cap: usize) -> usize {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	unsafe {
+		let w = &*w;
+		if !world::is_uid_valid(w, uid) {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			return 0;
+		}
+		let tiles = world::reachable_tiles(w, uid);
+		if !out.is_null() {
+			for (i, (x, y)) in tiles.iter().take(cap).enumerate() {
+				*out.add(2*i) = *x;
+				*out.add(2*i + 1) = *y;
+			}
+		}
+		tiles.len()
+	}
+}
+
+#[no_mangle]
+/// Computes the shortest route from tile (`sx`, `sy`) to tile (`gx`, `gy`), see [world::path::find], and returns its number of steps, or -1 if there is no route (or on NULL World pointer).
+/// Up to `cap` tiles stepped onto are written into `out` as consecutive (x, y) pairs, as with `alsnReachableTiles`.
+pub extern "C" fn alsnFindPath(w: *const World, sx: i32, sy: i32, gx: i32, gy: i32, out: *mut i32, cap: usize) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	let route = match world::path::find(unsafe { &*w }, (sx, sy), (gx, gy)) {
+		Some(r) => r,
+		None => return -1
+	};
+	if !out.is_null() {
+		for (i, (x, y)) in route.iter().take(cap).enumerate() {
+			unsafe {
+				*out.add(2*i) = *x;
+				*out.add(2*i + 1) = *y;
+			}
+		}
+	}
+	route.len() as i32
+}
+
+/// Copy a string into a newly allocated, null-terminated buffer owned by the caller.
+pub(crate) fn to_owned_cstr(s: &str) -> *mut c_char {
+	// Interior NUL bytes cannot be represented; the text is truncated at the first one.
+	let s = s.split('\0').next().unwrap_or("");
+	CString::new(s).map(|s| s.into_raw()).unwrap_or(ptr::null_mut())
+}
+
+#[no_mangle]
+/// Returns the title of the loaded map as a UTF-8 string. Release it with `alsnFreeString`. Returns NULL on NULL pointer.
+pub extern "C" fn alsnGetMapTitle(w: *const World) -> *mut c_char {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	unsafe { to_owned_cstr((*w).map_title()) }
+}
+
+#[no_mangle]
+/// Returns the introduction text of the loaded map as a UTF-8 string. Release it with `alsnFreeString`. Returns NULL on NULL pointer.
+pub extern "C" fn alsnGetIntroText(w: *const World) -> *mut c_char {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	unsafe { to_owned_cstr((*w).intro_text()) }
+}
+
+#[no_mangle]
+/// Returns the victory text of the loaded map as a UTF-8 string. Release it with `alsnFreeString`. Returns NULL on NULL pointer.
+pub extern "C" fn alsnGetVictoryText(w: *const World) -> *mut c_char {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	unsafe { to_owned_cstr((*w).victory_text()) }
+}
+
+#[no_mangle]
+/// Returns the defeat text of the loaded map as a UTF-8 string. Release it with `alsnFreeString`. Returns NULL on NULL pointer.
+pub extern "C" fn alsnGetDefeatText(w: *const World) -> *mut c_char {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	unsafe { to_owned_cstr((*w).defeat_text()) }
+}
+
+#[no_mangle]
+/// Returns the directory for the saves, settings and screenshots of the game named `app` (see `data_dir` in `alesia::utils`) as a UTF-8 string.
+/// Release it with `alsnFreeString`. Returns NULL on NULL pointer or invalid string.
+pub extern "C" fn alsnDataDir(app: *const c_char) -> *mut c_char {
+	check_nonnull!(app, "fatal [napi]: Pointer to App Name String is NULL", ptr::null_mut());
+	let a = cstr_arg!(app, "App name", ptr::null_mut());
+	to_owned_cstr(&crate::utils::data_dir(&a).to_string_lossy())
+}
+
+#[no_mangle]
+/// Free a string returned by the engine. Does nothing if `s` is NULL.
+/// # Safety
+/// `s` must have been returned by one of the engine string functions (e.g, `alsnGetMapTitle` or `alsnPollEventJson`), and must not be used afterwards.
+pub extern "C" fn alsnFreeString(s: *mut c_char) {
+	if s.is_null() {
+		return;
+	}
+	unsafe {
+		drop(CString::from_raw(s));
+	}
+}
+
+#[no_mangle]
+/// Set the camera position in world co-ordinates, i.e, tiles.
+/// The camera stops following a unit (see `alsnSetCameraFollow`) or panning (see `alsnPanCamera`) when moved explicitly.
+pub extern "C" fn alsnSetCamera(w: *mut World, x: f32, y: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		w.cam_follow = None;
+		w.tweens.pan = None;
+		w.set_cpos(x, y);
+	}
+	true
+}
+
+#[no_mangle]
+/// Write the camera position in world co-ordinates into `out_x` and `out_y`. Returns false on NULL pointer.
+pub extern "C" fn alsnGetCamera(w: *const World, out_x: *mut f32, out_y: *mut f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_x, "fatal [napi]: Pointer to x co-ordinate is NULL", false);
+	check_nonnull!(out_y, "fatal [napi]: Pointer to y co-ordinate is NULL", false);
+	unsafe {
+		let w = &*w;
+		*out_x = w.cam_wx;
+		*out_y = w.cam_wy;
+	}
+	true
+}
+
+#[no_mangle]
+/// Make the camera follow the unit with specified ID, closing in on it at the rate set with `alsnSetCameraFollowRate`, or stop following if `follow` is false.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnSetCameraFollow(w: *mut World, uid: u8, follow: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_camera_follow(unsafe { &mut *w }, uid, follow)
+}
+
+pub(crate) fn set_camera_follow(w: &mut World, uid: u8, follow: bool) -> bool {
+	if !follow {
+		w.cam_follow = None;
+		return true;
+	}
+	if !w.follow_unit(uid) {
+		error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+		return false;
+	}
+	true
+}
+
+#[no_mangle]
+/// Set the rate at which the camera closes in on the unit it follows, per second; 0 keeps the unit exactly in place. Default is 8.
+/// Returns false on NULL pointer, or if `rate` is negative or not finite.
+pub extern "C" fn alsnSetCameraFollowRate(w: *mut World, rate: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_camera_follow_rate(unsafe { &mut *w }, rate)
+}
+
+pub(crate) fn set_camera_follow_rate(w: &mut World, rate: f32) -> bool {
+	if !rate.is_finite() || rate < 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid camera follow rate {}", rate));
+		return false;
+	}
+	w.set_camera_follow_rate(rate);
+	true
+}
+
+#[no_mangle]
+/// Set whether the camera follows units while they walk, unless panning or following a unit; enemy units hidden by fog of war are not followed. Returns false on NULL pointer.
+pub extern "C" fn alsnSetCameraAutoFollow(w: *mut World, on: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_camera_auto_follow(on) };
+	true
+}
+
+#[no_mangle]
+/// Pan the camera smoothly to the tile (x, y) over `duration` seconds. The camera stops following a unit. Returns false on NULL pointer.
+pub extern "C" fn alsnPanToTile(w: *mut World, x: i32, y: i32, duration: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).pan_to_tile(x, y, duration) };
+	true
+}
+
+#[no_mangle]
+/// Pan the camera smoothly to the world co-ordinates (x, y) over `duration` seconds, with an easing curve (see `alsnAddTween`). The camera stops following a unit.
+/// Returns false on NULL pointer or invalid easing.
+pub extern "C" fn alsnPanCamera(w: *mut World, x: f32, y: f32, duration: f32, easing: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	pan_camera(unsafe { &mut *w }, x, y, duration, easing)
+}
+
+#[no_mangle]
+/// Set the zoom of the camera, clamped between 0.5 and 2.0; 1.0 draws tiles at their size. Returns false on NULL pointer, or if `zoom` is not finite.
+pub extern "C" fn alsnSetZoom(w: *mut World, zoom: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_zoom(unsafe { &mut *w }, zoom)
+}
+
+#[no_mangle]
+/// Returns the zoom of the camera, or 0 on NULL pointer.
+pub extern "C" fn alsnGetZoom(w: *const World) -> f32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0.0);
+	unsafe { (*w).zoom() }
+}
+
+pub(crate) fn set_zoom(w: &mut World, zoom: f32) -> bool {
+	if !zoom.is_finite() {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid zoom {}", zoom));
+		return false;
+	}
+	w.set_zoom(zoom);
+	true
+}
+
+#[no_mangle]
+/// Returns the input device the player last used (0 - keyboard and mouse, 1 - gamepad, 2 - touch), which HUD hints should name the controls of; 0 on NULL pointer.
+/// Changes are published as events of kind 13, see `alsnPollEvent`.
+pub extern "C" fn alsnGetInputScheme(w: *const World) -> u8 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	unsafe { (*w).input_scheme() as u8 }
+}
+
+#[no_mangle]
+/// Set the input scheme (see `alsnGetInputScheme`), e.g, to that of the platform before the game begins; it is switched again once the player uses another device.
+/// Returns false on NULL pointer, or invalid scheme.
+pub extern "C" fn alsnSetInputScheme(w: *mut World, scheme: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_input_scheme(unsafe { &mut *w }, scheme)
+}
+
+pub(crate) fn set_input_scheme(w: &mut World, scheme: u8) -> bool {
+	match crate::input::InputScheme::from_u8(scheme) {
+		Some(s) => {
+			w.set_input_scheme(s);
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid input scheme {}", scheme));
+			false
+		}
+	}
+}
+
+fn easing_arg(easing: u8) -> Option<Easing> {
+	let e = Easing::from_u8(easing);
+	if e.is_none() {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid easing {}", easing));
+	}
+	e
+}
+
+pub(crate) fn pan_camera(w: &mut World, x: f32, y: f32, duration: f32, easing: u8) -> bool {
+	match easing_arg(easing) {
+		Some(e) => {
+			w.pan_camera(x, y, duration, e);
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Add a tween of a value from `from` to `to` over `duration` seconds, advanced once per frame along with the engine.
+/// The easing curve is 0 - linear, 1 - quadratic in, 2 - quadratic out, 3 - quadratic in-out, 4 - cubic out, 5 - smoothstep, or 6 - back out (overshooting slightly).
+/// `f` (if not NULL) is invoked with the id of the tween once it is complete, and `ctx` is passed back as is.
+/// Returns the id of the tween (greater than 0), or 0 on NULL pointer or invalid easing.
+pub extern "C" fn alsnAddTween(w: *mut World, from: f32, to: f32, duration: f32, easing: u8, f: CTweenHandle, ctx: *mut c_void) -> u32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	add_tween(unsafe { &mut *w }, from, to, duration, easing, f.map(|f| move |w: &mut World, id| f(w, id, ctx)))
+}
+
+pub(crate) fn add_tween(w: &mut World, from: f32, to: f32, duration: f32, easing: u8, f: Option<impl FnMut(&mut World, u32) + 'static>) -> u32 {
+	let t = match easing_arg(easing) {
+		Some(e) => Tween::new(from, to, duration, e),
+		None => return 0
+	};
+	match f {
+		Some(f) => w.tween_then(t, f),
+		None => w.tween(t)
+	}
+}
+
+#[no_mangle]
+/// Write the current value of the tween with the specified id into `out`.
+/// Returns false on NULL pointer, or if the tween has completed or been cancelled.
+pub extern "C" fn alsnGetTweenValue(w: *const World, id: u32, out: *mut f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out, "fatal [napi]: Pointer to tween value is NULL", false);
+	match unsafe { &*w }.tween_value(id) {
+		Some(v) => {
+			unsafe { *out = v };
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Schedule `f` to be invoked once, with the id returned, after `secs` seconds of game time. `ctx` is passed back as is.
+/// Returns the id (greater than 0), or 0 on NULL pointer.
+pub extern "C" fn alsnScheduleAfter(w: *mut World, secs: f32, f: CScheduleHandle, ctx: *mut c_void) -> u32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	schedule(unsafe { &mut *w }, secs, false, f.map(|f| move |w: &mut World, id| f(w, id, ctx)))
+}
+
+#[no_mangle]
+/// Schedule `f` to be invoked every `secs` seconds of game time, with the id returned, until cancelled with `alsnCancelScheduled`. `ctx` is passed back as is.
+/// Returns the id (greater than 0), or 0 on NULL pointer.
+pub extern "C" fn alsnScheduleEvery(w: *mut World, secs: f32, f: CScheduleHandle, ctx: *mut c_void) -> u32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	schedule(unsafe { &mut *w }, secs, true, f.map(|f| move |w: &mut World, id| f(w, id, ctx)))
+}
+
+pub(crate) fn schedule(w: &mut World, secs: f32, every: bool, f: Option<impl FnMut(&mut World, u32) + 'static>) -> u32 {
+	match (f, every) {
+		(Some(f), false) => w.schedule_after(secs, f),
+		(Some(f), true) => w.schedule_every(secs, f),
+		(None, _) => {
+			error::report(error::ERR_NULL_POINTER, "fatal [napi]: Scheduled function pointer is NULL");
+			0
+		}
+	}
+}
+
+#[no_mangle]
+/// Cancel a scheduled callback; this may be done from the callback itself. Returns false on NULL pointer, or if there is no such callback, e.g, if it has already been invoked (and does not repeat).
+pub extern "C" fn alsnCancelScheduled(w: *mut World, id: u32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { &mut *w }.cancel_scheduled(id)
+}
+
+#[no_mangle]
+/// Enable (or disable, discarding events not yet polled) the queue of engine events on the world, to be read with `alsnPollEvent`.
+/// Returns false on NULL pointer.
+pub extern "C" fn alsnEnableEventQueue(w: *mut World, enable: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { &mut *w }.enable_event_queue(enable);
+	true
+}
+
+#[no_mangle]
+/// Write the oldest event in the queue of the world into `out`, removing it. See [RawEvent] for the layout.
+/// Returns false on NULL pointer, or if the queue is empty or disabled.
+pub extern "C" fn alsnPollEvent(w: *mut World, out: *mut RawEvent) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out, "fatal [napi]: Pointer to event is NULL", false);
+	match unsafe { &mut *w }.poll_event() {
+		Some(e) => {
+			unsafe { *out = e.to_raw() };
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Returns the oldest event in the queue of the world as a single-line JSON object (UTF-8), removing it; release it with `alsnFreeString`.
+/// Returns NULL on NULL pointer, or if the queue is empty or disabled.
+pub extern "C" fn alsnPollEventJson(w: *mut World) -> *mut c_char {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	match unsafe { &mut *w }.poll_event() {
+		Some(e) => to_owned_cstr(&e.to_string()),
+		None => ptr::null_mut()
+	}
+}
+
+#[no_mangle]
+/// Returns the hash of the state of the battle, see [crate::world::state_hash]. Returns 0 on NULL pointer.
+pub extern "C" fn alsnStateHash(w: *const World) -> u64 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	crate::world::state_hash(unsafe { &*w })
+}
+
+#[no_mangle]
+/// Returns the differences in the state of the battle between two worlds (see [crate::world::diff]), one per line (UTF-8), e.g, "unit 3 health 7.5 != 7.25"; release it with `alsnFreeString`.
+/// Returns an empty string if the states agree, or NULL on NULL pointer.
+pub extern "C" fn alsnStateDiff(a: *const World, b: *const World) -> *mut c_char {
+	check_nonnull!(a, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	check_nonnull!(b, "fatal [napi]: Pointer to World is NULL", ptr::null_mut());
+	to_owned_cstr(&diff_text(unsafe { &*a }, unsafe { &*b }))
+}
+
+pub(crate) fn diff_text(a: &World, b: &World) -> String {
+	crate::world::diff(a, b).iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+#[no_mangle]
+/// Remove the tween with the specified id, without invoking its callback. Returns false on NULL pointer, or if the tween has completed or been cancelled.
+pub extern "C" fn alsnCancelTween(w: *mut World, id: u32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { &mut *w }.cancel_tween(id)
+}
+
+#[no_mangle]
+/// Returns the version of the engine as a null-terminated string, e.g, "0.1.0".
+/// # Safety
+/// The string is static, and **must not be released by the callsite**.
+pub extern "C" fn alsnGetVersion() -> *const c_char {
+	concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[no_mangle]
+/// Returns the bitmask of capabilities compiled into the engine, see the `CAP_*` constants of the crate root.
+pub extern "C" fn alsnGetCapabilities() -> u32 {
+	crate::capabilities()
+}
+
+#[cfg(feature = "debug_tools")]
+#[no_mangle]
+/// Turn a cheat on or off: 0 - god mode, 1 - infinite movement, 2 - reveal. See [crate::debug_tools].
+/// Returns false on NULL pointer or invalid cheat.
+pub extern "C" fn alsnSetCheat(w: *mut World, cheat: u8, on: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_cheat(unsafe { &mut *w }, cheat, on)
+}
+
+#[cfg(feature = "debug_tools")]
+pub(crate) fn set_cheat(w: &mut World, cheat: u8, on: bool) -> bool {
+	match crate::debug_tools::Cheat::from_u8(cheat) {
+		Some(c) => {
+			crate::debug_tools::set_cheat(w, c, on);
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid cheat {}", cheat));
+			false
+		}
+	}
+}
+
+#[cfg(feature = "debug_tools")]
+#[no_mangle]
+/// Win (`victory` true) or lose the game once the player turn is idle, see [crate::debug_tools::instant_win].
+/// Returns false on NULL pointer.
+pub extern "C" fn alsnForceOutcome(w: *mut World, victory: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	force_outcome(unsafe { &mut *w }, victory);
+	true
+}
+
+#[cfg(feature = "debug_tools")]
+pub(crate) fn force_outcome(w: &mut World, victory: bool) {
+	if victory {
+		crate::debug_tools::instant_win(w);
+	} else {
+		crate::debug_tools::instant_lose(w);
+	}
+}
+
+#[no_mangle]
+/// Open the player profile stored at `path` (created on first save if it does not exist), saving and replacing the current one.
+/// Returns false on NULL pointer, invalid string, or if the file could not be read.
+pub extern "C" fn alsnOpenProfile(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Profile Path String is NULL", false);
+	let p = cstr_arg!(path, "Profile path", false);
+	let ok = unsafe { (*w).open_profile(&p) };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to open profile {}", p));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Returns the value of `key` in the player profile, 0 if it has not been set, or on NULL pointer or invalid string.
+pub extern "C" fn alsnGetProfileValue(w: *const World, key: *const c_char) -> i64 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	check_nonnull!(key, "fatal [napi]: Pointer to Profile key is NULL", 0);
+	let k = cstr_arg!(key, "Profile key", 0);
+	unsafe { (*w).profile.get(&k) }
+}
+
+#[no_mangle]
+/// Set the value of `key` in the player profile. Returns false on NULL pointer, invalid string, or if the key is empty or contains whitespace or '='.
+pub extern "C" fn alsnSetProfileValue(w: *mut World, key: *const c_char, v: i64) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(key, "fatal [napi]: Pointer to Profile key is NULL", false);
+	let k = cstr_arg!(key, "Profile key", false);
+	let ok = unsafe { (*w).profile.set(&k, v) };
+	if !ok {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid profile key {:?}", k));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Save the player profile now, rather than at the end of the game. Returns false on NULL pointer, or if it could not be written.
+pub extern "C" fn alsnSaveProfile(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	let ok = unsafe { (*w).save_profile() };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, "warning [napi]: Failed to save profile");
+	}
+	ok
+}
+
+#[no_mangle]
+/// Save a snapshot of the battle (see `alesia::save`) to `path`, e.g, from a callback. Returns false on NULL pointer, invalid string, or if it could not be written.
+pub extern "C" fn alsnSaveBattle(w: *const World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Save Path String is NULL", false);
+	let p = cstr_arg!(path, "Save path", false);
+	let ok = unsafe { (*w).save_battle(&p) };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to save battle to {}", p));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Save the map of the world (see `alesia::world::save_world`) to `path`, as a `.alw` file which `alsLoadMap` reads, e.g, from an editor.
+/// Returns false on NULL pointer, invalid string, if the map does not fit the format, or if it could not be written.
+pub extern "C" fn alsnSaveMap(w: *const World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Map Path String is NULL", false);
+	let p = cstr_arg!(path, "Map path", false);
+	save_map(unsafe { &*w }, &p)
+}
+
+pub(crate) fn save_map(w: &World, path: &str) -> bool {
+	match world::save_world(w, path) {
+		Ok(()) => true,
+		Err(e) => {
+			error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: {}", e));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Load the map from the text file at `path` (see `alesia::world::load_world_ron`), in the human-readable format that can be edited by hand, in place of `alsLoadMap`.
+/// Returns false (leaving the world unchanged) on NULL pointer, invalid string, or if the file could not be read or is malformed.
+pub extern "C" fn alsnLoadMapRon(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Map Path String is NULL", false);
+	let p = cstr_arg!(path, "Map path", false);
+	load_map_ron(unsafe { &mut *w }, &p)
+}
+
+pub(crate) fn load_map_ron(w: &mut World, path: &str) -> bool {
+	let r = world::load_world_ron(w, path);
+	if !r {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load map {}", path));
+	}
+	r
+}
+
+#[no_mangle]
+/// Save the map of the world (see `alesia::world::save_world_ron`) to `path`, as text which `alsnLoadMapRon` reads.
+/// Returns false on NULL pointer, invalid string, or if the file could not be written.
+pub extern "C" fn alsnSaveMapRon(w: *const World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Map Path String is NULL", false);
+	let p = cstr_arg!(path, "Map path", false);
+	save_map_ron(unsafe { &*w }, &p)
+}
+
+pub(crate) fn save_map_ron(w: &World, path: &str) -> bool {
+	match world::save_world_ron(w, path) {
+		Ok(()) => true,
+		Err(e) => {
+			error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: {}", e));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Restore the snapshot stored at `path` before the game begins; the map must be loaded and unit types registered as when it was saved.
+/// Returns false (leaving the world unchanged) on NULL pointer, invalid string, or if the snapshot could not be read or is invalid.
+pub extern "C" fn alsnLoadBattle(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Save Path String is NULL", false);
+	let p = cstr_arg!(path, "Save path", false);
+	let ok = unsafe { (*w).load_battle(&p) };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load battle from {}", p));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Autosave the running game to `path` every `every` player turns (never if 0), and as the enemy phase begins if `before_enemy` is set.
+/// If the previous session did not shut down normally, the player is asked whether to resume from the autosave. Returns false on NULL pointer or invalid string.
+pub extern "C" fn alsnSetAutosave(rt: *mut Runtime, path: *const c_char, every: u32, before_enemy: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Autosave Path String is NULL", false);
+	let p = cstr_arg!(path, "Autosave path", false);
+	unsafe { (*rt).set_autosave(&p, every, before_enemy) };
+	true
+}
+
+#[no_mangle]
+/// Save the running game to a slot in the directory `dir` (see `SaveSlots` in `alesia::save`), with a thumbnail of the last frame drawn.
+/// Returns false on NULL pointer, invalid string, or if it could not be written.
+pub extern "C" fn alsnSaveSlot(rt: *mut Runtime, dir: *const c_char, slot: u32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(dir, "fatal [napi]: Pointer to Save Directory String is NULL", false);
+	let d = cstr_arg!(dir, "Save directory", false);
+	let ok = unsafe { (*rt).save_slot(&SaveSlots::new(&d), slot) };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to save slot {} in {}", slot, d));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Restore a slot in the directory `dir` into the running game. Returns false (leaving the game unchanged) on NULL pointer, invalid string, or if the slot is empty or invalid.
+pub extern "C" fn alsnLoadSlot(rt: *mut Runtime, dir: *const c_char, slot: u32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(dir, "fatal [napi]: Pointer to Save Directory String is NULL", false);
+	let d = cstr_arg!(dir, "Save directory", false);
+	let ok = unsafe { (*rt).load_slot(&SaveSlots::new(&d), slot) };
+	if !ok {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load slot {} in {}", slot, d));
+	}
+	ok
+}
+
+#[no_mangle]
+/// Write the numbers of the used slots in the directory `dir`, in order, into `out` (of length `cap`; may be NULL if `cap` is 0).
+/// Returns the number of used slots, which may exceed `cap`, or 0 on NULL pointer or invalid string.
+pub extern "C" fn alsnListSlots(dir: *const c_char, out: *mut u32, cap: usize) -> usize {
+	check_nonnull!(dir, "fatal [napi]: Pointer to Save Directory String is NULL", 0);
+	let d = cstr_arg!(dir, "Save directory", 0);
+	let slots = SaveSlots::new(&d).list();
+	if cap > 0 {
+		check_nonnull!(out, "fatal [napi]: Pointer to slot buffer is NULL", 0);
+		for (i, s) in slots.iter().take(cap).enumerate() {
+			unsafe { *out.add(i) = s.slot };
+		}
+	}
+	slots.len()
+}
+
+#[no_mangle]
+/// Returns the map title of a slot as a UTF-8 string (release it with `alsnFreeString`), and writes its turn number and time of saving (in seconds since the UNIX epoch) into `turn` and `time`, unless NULL.
+/// The thumbnail, if any, is `<dir>/slot<slot>.png`. Returns NULL if the slot is empty, or on NULL pointer or invalid string.
+pub extern "C" fn alsnGetSlotInfo(dir: *const c_char, slot: u32, turn: *mut u32, time: *mut u64) -> *mut c_char {
+	check_nonnull!(dir, "fatal [napi]: Pointer to Save Directory String is NULL", ptr::null_mut());
+	let d = cstr_arg!(dir, "Save directory", ptr::null_mut());
+	match SaveSlots::new(&d).info(slot) {
+		Some(i) => {
+			unsafe {
+				if !turn.is_null() {
+					*turn = i.header.turn;
+				}
+				if !time.is_null() {
+					*time = i.header.time;
+				}
+			}
+			to_owned_cstr(&i.header.title)
+		},
+		None => ptr::null_mut()
+	}
+}
+
+#[no_mangle]
+/// Delete a slot in the directory `dir`, and its thumbnail. Returns false if the slot is empty or could not be deleted, or on NULL pointer or invalid string.
+pub extern "C" fn alsnDeleteSlot(dir: *const c_char, slot: u32) -> bool {
+	check_nonnull!(dir, "fatal [napi]: Pointer to Save Directory String is NULL", false);
+	let d = cstr_arg!(dir, "Save directory", false);
+	SaveSlots::new(&d).delete(slot)
+}
+
+#[no_mangle]
+/// Write the statistics of the battle so far into `out`. Returns false on NULL pointer.
+pub extern "C" fn alsnGetBattleStats(w: *const World, out: *mut world::BattleStats) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out, "fatal [napi]: Pointer to BattleStats is NULL", false);
+	unsafe {
+		*out = (*w).stats();
+	}
+	true
+}
+
+#[no_mangle]
+/// Load a behavior tree file (see `alesia::ai`) for the current map, assigning trees to unit types. Returns false on NULL pointer, or if the file could not be read or is malformed.
+pub extern "C" fn alsnLoadAi(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Behavior tree file path string is NULL", false);
+	let p = cstr_arg!(path, "Behavior tree file path", false);
+	load_ai(unsafe { &mut *w }, &p)
+}
+
+pub(crate) fn load_ai(w: &mut World, path: &str) -> bool {
+	let r = w.load_ai(path);
+	if !r {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load behavior tree file {}", path));
+	}
+	r
+}
+
+#[no_mangle]
+/// Load a puzzle file (see `alesia::puzzle`) for the current map, replacing the previous puzzle. Returns false on NULL pointer, or if the file could not be read or is malformed.
+pub extern "C" fn alsnLoadPuzzle(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Puzzle file path string is NULL", false);
+	let p = cstr_arg!(path, "Puzzle file path", false);
+	load_puzzle(unsafe { &mut *w }, &p)
+}
+
+pub(crate) fn load_puzzle(w: &mut World, path: &str) -> bool {
+	let r = w.load_puzzle(path);
+	if !r {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load puzzle file {}", path));
+	}
+	r
+}
+
+#[no_mangle]
+/// Remove the puzzle of the current map, so the battle is played freely. Returns false on NULL pointer.
+pub extern "C" fn alsnClearPuzzle(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_puzzle(None) };
+	true
+}
+
+#[no_mangle]
+/// Getter for the state of the puzzle of the current map: 0 - pending, 1 - solved, 2 - failed.
+/// Returns -1 on NULL pointer, or if there is no puzzle.
+pub extern "C" fn alsnGetPuzzleState(w: *const World) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	unsafe { &*w }.puzzle().map_or(-1, |p| p.state() as i32)
+}
+
+#[no_mangle]
+/// Load a tutorial file (see `alesia::tutorial`) for the current map, replacing the previous tutorial. Returns false on NULL pointer, or if the file could not be read or is malformed.
+pub extern "C" fn alsnLoadTutorial(w: *mut World, path: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Tutorial file path string is NULL", false);
+	let p = cstr_arg!(path, "Tutorial file path", false);
+	load_tutorial(unsafe { &mut *w }, &p)
+}
+
+pub(crate) fn load_tutorial(w: &mut World, path: &str) -> bool {
+	let r = w.load_tutorial(path);
+	if !r {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load tutorial file {}", path));
+	}
+	r
+}
+
+#[no_mangle]
+/// Remove the tutorial of the current map, so input is no longer restricted. Returns false on NULL pointer.
+pub extern "C" fn alsnClearTutorial(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_tutorial(None) };
+	true
+}
+
+#[no_mangle]
+/// Getter for the number of steps of the tutorial of the current map done, i.e, the index of the step shown; equal to the number of steps once the tutorial is done.
+/// Returns -1 on NULL pointer, or if there is no tutorial.
+pub extern "C" fn alsnGetTutorialStep(w: *const World) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	unsafe { &*w }.tutorial().map_or(-1, |t| t.steps_done() as i32)
+}
+
+#[no_mangle]
+/// Place a pickup drawn with texture `tex` on a tile, replacing any pickup already there. The kind of pickup is 0 - heal (`value` is the fraction of maximum health restored), 1 - boost (`value` is the fraction by which attack strength is raised), or 2 - gold (`value` is the amount).
+/// Returns false on NULL pointer, or invalid kind or value.
+pub extern "C" fn alsnPlacePickup(w: *mut World, tex: u8, x: i32, y: i32, kind: u8, value: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	place_pickup(unsafe { &mut *w }, tex, x, y, kind, value)
+}
+
+pub(crate) fn place_pickup(w: &mut World, tex: u8, x: i32, y: i32, kind: u8, value: f32) -> bool {
+	match PickupEffect::from_raw(kind, value) {
+		Some(e) => {
+			pickup::place(w, tex, (x, y), e);
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid pickup kind={}, value={}", kind, value));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Remove the pickup on a tile without applying it. Returns false on NULL pointer, or if there is no pickup on the tile.
+pub extern "C" fn alsnRemovePickup(w: *mut World, x: i32, y: i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	pickup::remove(unsafe { &mut *w }, (x, y)).is_some()
+}
+
+#[no_mangle]
+/// Set the behavior tree of a registered unit type to the tree rooted at node `root` of a behavior tree file.
+/// Returns false on NULL pointer or invalid type ID, or if the file could not be read, is malformed, or does not define `root`.
+pub extern "C" fn alsnSetUnitTypeAi(w: *mut World, type_id: u8, path: *const c_char, root: *const c_char) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Behavior tree file path string is NULL", false);
+	check_nonnull!(root, "fatal [napi]: Behavior tree root string is NULL", false);
+	let p = cstr_arg!(path, "Behavior tree file path", false);
+	let r = cstr_arg!(root, "Behavior tree root", false);
+	set_type_ai(unsafe { &mut *w }, type_id, &p, &r)
+}
+
+pub(crate) fn set_type_ai(w: &mut World, type_id: u8, path: &str, root: &str) -> bool {
+	if !w.unit_types.contains_key(&type_id) {
+		error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit type id={}", type_id));
+		return false;
+	}
+	let tree = match crate::ai::TreeSet::load(path) {
+		Ok(set) => set.tree(root),
+		Err(e) => {
+			error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load behavior tree file {}: {}", path, e));
+			return false;
+		}
+	};
+	match tree {
+		Some(t) => world::set_type_behavior_tree(w, type_id, Some(t)),
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Behavior tree file {} does not define node {}", path, root));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Clear the behavior tree of a registered unit type. Returns false on NULL pointer or invalid type ID.
+pub extern "C" fn alsnClearUnitTypeAi(w: *mut World, type_id: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	clear_type_ai(unsafe { &mut *w }, type_id)
+}
+
+pub(crate) fn clear_type_ai(w: &mut World, type_id: u8) -> bool {
+	let r = world::set_type_behavior_tree(w, type_id, None);
+	if !r {
+		error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit type id={}", type_id));
+	}
+	r
+}
+
+#[no_mangle]
+/// Write the threat and support on a tile, as seen by player units if `player` is true or enemy units otherwise, into `out_threat` and `out_support` (see `alesia::world::influence`).
+/// The influence map is computed on every call. Returns false on NULL pointer.
+pub extern "C" fn alsnGetInfluence(w: *const World, x: i32, y: i32, player: bool, out_threat: *mut f32, out_support: *mut f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_threat, "fatal [napi]: Pointer to threat is NULL", false);
+	check_nonnull!(out_support, "fatal [napi]: Pointer to support is NULL", false);
+	let m = world::influence::compute(unsafe { &*w }, player);
+	unsafe {
+		*out_threat = m.threat((x, y));
+		*out_support = m.support((x, y));
+	}
+	true
+}
+
+#[no_mangle]
+/// Write the health and maximum health of the unit with specified ID, rounded to whole numbers as set by `alsnSetHpRounding`, into `out_hp` and `out_max`.
+/// Returns false on NULL pointer or invalid ID.
+pub extern "C" fn alsnGetUnitHp(w: *const World, uid: u8, out_hp: *mut i32, out_max: *mut i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unit_hp(unsafe { &*w }, uid, out_hp, out_max)
+}
+
+pub(crate) fn unit_hp(w: &World, uid: u8, out_hp: *mut i32, out_max: *mut i32) -> bool {
+	check_nonnull!(out_hp, "fatal [napi]: Pointer to health is NULL", false);
+	check_nonnull!(out_max, "fatal [napi]: Pointer to maximum health is NULL", false);
+	match world::unit_hp(w, uid) {
+		Some((h, mh)) => {
+			unsafe {
+				*out_hp = h;
+				*out_max = mh;
+			}
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Enable (or disable) the built-in AI for enemy units with neither a behavior nor a behavior tree, advancing on player units within `aggressiveness` times their movement and range (infinity for any distance).
+/// Returns false on NULL pointer or invalid aggressiveness, i.e, negative or NaN.
+pub extern "C" fn alsnSetAiController(w: *mut World, enabled: bool, aggressiveness: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_ai_controller(unsafe { &mut *w }, enabled, aggressiveness)
+}
+
+pub(crate) fn set_ai_controller(w: &mut World, enabled: bool, aggressiveness: f32) -> bool {
+	if aggressiveness.is_nan() || aggressiveness < 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid aggressiveness {}", aggressiveness));
+		return false;
+	}
+	w.set_ai_controller(if enabled { Some(world::AiController { aggressiveness: aggressiveness }) } else { None });
+	true
+}
+
+#[no_mangle]
+/// Enable (or disable) fog of war: tiles out of sight of every player unit are darkened, and enemy units on them hidden. Returns false on NULL pointer.
+pub extern "C" fn alsnSetFogOfWar(w: *mut World, enabled: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_fog_of_war(enabled) };
+	true
+}
+
+#[no_mangle]
+/// Set the attacks per turn of player units, and whether they may move after attacking, for unit types that do not set their own. Returns false on NULL pointer.
+pub extern "C" fn alsnSetDefaultActions(w: *mut World, attacks: u8, move_after_attack: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_actions(world::Actions { attacks: attacks, move_after_attack: move_after_attack }) };
+	true
+}
+
+#[no_mangle]
+/// Set how health is rounded to whole numbers wherever it is shown: 0 - up (the default), 1 - down, 2 - to the nearest. Returns false on NULL pointer or invalid rounding.
+pub extern "C" fn alsnSetHpRounding(w: *mut World, mode: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_hp_rounding(unsafe { &mut *w }, mode)
+}
+
+pub(crate) fn set_hp_rounding(w: &mut World, mode: u8) -> bool {
+	match world::HpRounding::from_u8(mode) {
+		Some(r) => {
+			w.set_hp_rounding(r);
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid health rounding {}", mode));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the difficulty of the battle to a preset: 0 - easy, 1 - normal, 2 - hard. Returns false on NULL pointer or invalid preset.
+pub extern "C" fn alsnSetDifficulty(w: *mut World, preset: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	set_difficulty_preset(unsafe { &mut *w }, preset)
+}
+
+pub(crate) fn set_difficulty_preset(w: &mut World, preset: u8) -> bool {
+	match world::Difficulty::from_raw(preset) {
+		Some(d) => {
+			w.set_difficulty(d);
+			true
+		},
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid difficulty preset {}", preset));
+			false
+		}
+	}
+}
+
+#[no_mangle]
+/// Set custom difficulty multipliers for the battle, see `World.set_difficulty`. Returns false on NULL pointer.
+pub extern "C" fn alsnSetCustomDifficulty(w: *mut World, d: *const world::Difficulty) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(d, "fatal [napi]: Pointer to Difficulty is NULL", false);
+	unsafe {
+		(*w).set_difficulty(*d);
+	}
+	true
+}
+
+#[no_mangle]
+/// Write the difficulty multipliers of the battle into `out`. Returns false on NULL pointer.
+pub extern "C" fn alsnGetDifficulty(w: *const World, out: *mut world::Difficulty) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out, "fatal [napi]: Pointer to Difficulty is NULL", false);
+	unsafe {
+		*out = (*w).difficulty();
+	}
+	true
+}
+
+#[no_mangle]
+/// Set the height level of every tile, given as `n` levels in row-major order; `n` must equal the number of tiles, or be 0 to flatten the map.
+/// Returns false on NULL pointer, if no map is loaded or on length mismatch.
+pub extern "C" fn alsnSetElevation(w: *mut World, heights: *const u8, n: usize) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if n != 0 {
+		check_nonnull!(heights, "fatal [napi]: Pointer to heights is NULL", false);
+	}
+	set_elevation(unsafe { &mut *w }, read_slice(heights, n))
+}
+
+pub(crate) fn set_elevation(w: &mut World, heights: Vec<u8>) -> bool {
+	let n = heights.len();
+	if !w.set_elevation(heights) {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: {} height levels do not match the map", n));
+		return false;
+	}
+	true
+}
+
+#[no_mangle]
+/// Returns the height level of the tile at (x, y); 0 for flat maps and out-of-bounds tiles, -1 on NULL pointer.
+pub extern "C" fn alsnGetElevation(w: *const World, x: i32, y: i32) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	unsafe { world::elevation_at(&*w, x, y) as i32 }
+}
+
+#[no_mangle]
+/// Set how elevation affects movement, range and damage. See [world::ElevationRules]. Returns false on NULL pointer.
+pub extern "C" fn alsnSetElevationRules(w: *mut World, r: *const world::ElevationRules) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(r, "fatal [napi]: Pointer to ElevationRules is NULL", false);
+	unsafe {
+		(*w).set_elevation_rules(*r);
+	}
+	true
+}
+
+#[no_mangle]
+/// Returns the number of levels the unit with ID `atk` stands above the unit with ID `def`; negative if below, 0 on NULL pointer or invalid ID.
+pub extern "C" fn alsnGetHeightAdvantage(w: *const World, atk: u8, def: u8) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", 0);
+	unsafe {
+		let w = &*w;
+		if !world::is_uid_valid(w, atk) || !world::is_uid_valid(w, def) {
+			return 0;
+		}
+		world::height_advantage(w, atk, def)
+	}
+}
+
+#[no_mangle]
+/// Add a slide, showing the texture with id `tex` above `caption` for `duration` seconds, to the slideshow shown before the intro text.
+/// Returns false on NULL pointer or invalid string.
+pub extern "C" fn alsnAddIntroSlide(w: *mut World, tex: u8, caption: *const c_char, duration: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(caption, "fatal [napi]: Pointer to slide caption is NULL", false);
+	let c = cstr_arg!(caption, "Slide caption", false);
+	unsafe { (*w).add_intro_slide(tex, &c, duration) };
+	true
+}
+
+#[no_mangle]
+/// Remove all slides of the slideshow shown before the intro text, including those defined by the map. Returns false on NULL pointer.
+pub extern "C" fn alsnClearIntroSlides(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_intro_slides(Vec::new()) };
+	true
+}
+
+#[no_mangle]
+/// Show a screen of `text` scrolling up at `speed` pixels per second (or the default speed if not positive), e.g, credits or an epilogue.
+/// Called from the `on_results` callback, the screen is shown once the results screen is dismissed, and the game ends after it. Returns false on NULL pointer or invalid string.
+pub extern "C" fn alsnShowScrollingText(w: *mut World, text: *const c_char, speed: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(text, "fatal [napi]: Pointer to scrolling text is NULL", false);
+	let t = cstr_arg!(text, "Scrolling text", false);
+	unsafe { (*w).show_scrolling_text(&t, speed) };
+	true
+}
+
+#[no_mangle]
+/// Show `text` rising from the tile of the unit with specified ID, in the style of `kind` (0 - damage, 1 - heal, 2 - status, 3 - miss, 4 - crit), e.g, "MISS" from the attack callback.
+/// Returns false on NULL pointer, invalid string, invalid ID or invalid kind.
+pub extern "C" fn alsnShowPopup(w: *mut World, uid: u8, text: *const c_char, kind: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(text, "fatal [napi]: Pointer to popup text is NULL", false);
+	let t = cstr_arg!(text, "Popup text", false);
+	show_popup(unsafe { &mut *w }, uid, &t, kind)
+}
+
+pub(crate) fn show_popup(w: &mut World, uid: u8, text: &str, kind: u8) -> bool {
+	let k = match world::PopupKind::from_u8(kind) {
+		Some(k) => k,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid popup kind {}", kind));
+			return false;
+		}
+	};
+	if !w.units.contains_key(&uid) {
+		error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit id={}", uid));
+		return false;
+	}
+	w.show_popup(uid, text, k);
+	true
+}
+
+#[no_mangle]
+/// Stack a layer of `n` tiles (row by row, 255 for none) over the tiles of the map, drawn as `kind`: 0 - ground, 1 - decoration (both under units), 2 - overlay (over units).
+/// Returns the index of the layer, or -1 on NULL pointer, invalid kind, or if no map is loaded or `n` is not the number of tiles of the map.
+pub extern "C" fn alsnAddTileLayer(w: *mut World, kind: u8, tiles: *const u8, n: usize) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	check_nonnull!(tiles, "fatal [napi]: Pointer to tile array is NULL", -1);
+	add_tile_layer(unsafe { &mut *w }, kind, read_slice(tiles, n))
+}
+
+pub(crate) fn add_tile_layer(w: &mut World, kind: u8, tiles: Vec<u8>) -> i32 {
+	let k = match world::LayerKind::from_u8(kind) {
+		Some(k) => k,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid layer kind {}", kind));
+			return -1;
+		}
+	};
+	if !w.add_tile_layer(k, tiles) {
+		error::report(error::ERR_INVALID_ARGUMENT, "warning [napi]: Tile layer does not match the map");
+		return -1;
+	}
+	w.tile_layers().len() as i32 - 1
+}
+
+#[no_mangle]
+/// Remove all layers of tiles stacked over the map, including those loaded with it. Returns false on NULL pointer.
+pub extern "C" fn alsnClearTileLayers(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).clear_tile_layers() };
+	true
+}
+
+#[no_mangle]
+/// Add an area of the map, the rectangle of tiles with opposite corners (x0, y0) and (x1, y1), looping the music track `track` at `volume` (in the range [0, 1]) while the camera is on it,
+/// fading out `falloff` tiles away from it. Returns false on NULL pointer, volume out of range, or negative fall-off.
+pub extern "C" fn alsnAddAmbientRegion(w: *mut World, track: u8, x0: i32, y0: i32, x1: i32, y1: i32, falloff: f32, volume: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if !(0.0..=1.0).contains(&volume) || falloff.is_nan() || falloff < 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid ambient volume {} or fall-off {}", volume, falloff));
+		return false;
+	}
+	unsafe { (*w).add_ambient_region(world::ambient::AmbientRegion::new(track, (x0, y0), (x1, y1), falloff, volume)) };
+	true
+}
+
+#[no_mangle]
+/// Remove all ambient regions of the map, including those loaded with it. Returns false on NULL pointer.
+pub extern "C" fn alsnClearAmbientRegions(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).clear_ambient_regions() };
+	true
+}
+
+#[no_mangle]
+/// Add a pending objective to the objective tracker. Returns its index, or -1 on NULL pointer or invalid string.
+pub extern "C" fn alsnAddObjective(w: *mut World, text: *const c_char) -> i32 {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", -1);
+	check_nonnull!(text, "fatal [napi]: Pointer to objective text is NULL", -1);
+	let t = cstr_arg!(text, "Objective text", -1);
+	unsafe {
+		let w = &mut *w;
+		w.add_objective(&t) as i32
+	}
+}
+
+#[no_mangle]
+/// Set the state (0 - pending, 1 - completed, 2 - failed) of the objective with specified index.
+/// Returns false on NULL pointer, invalid index or invalid state.
+pub extern "C" fn alsnSetObjectiveState(w: *mut World, idx: u32, state: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	let st = match world::ObjectiveState::from_raw(state) {
+		Some(s) => s,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid objective state {}", state));
+			return false;
+		}
+	};
+	unsafe {
+		let w = &mut *w;
+		if !w.set_objective_state(idx as usize, st) {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid objective index={}", idx));
+			return false;
+		}
+	}
+	true
+}
+
+#[no_mangle]
+/// Remove all objectives. Returns false on NULL pointer.
+pub extern "C" fn alsnClearObjectives(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		w.clear_objectives();
+	}
+	true
+}
+
+#[no_mangle]
+/// Play the sound with specified id once. Returns false on NULL pointer.
+pub extern "C" fn alsnPlaySound(w: *mut World, id: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		w.play_sound(id);
+	}
+	true
+}
+
+#[no_mangle]
+/// Stop the background music. It resumes only when the music id is changed. Returns false on NULL pointer.
+pub extern "C" fn alsnStopMusic(w: *mut World) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe {
+		let w = &mut *w;
+		w.stop_music();
+	}
+	true
+}
+
+#[no_mangle]
+/// Set the master volume in the range [0, 1]. Returns false on NULL pointer or volume out of range.
+pub extern "C" fn alsnSetMasterVolume(w: *mut World, v: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if !(0.0..=1.0).contains(&v) {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid volume {}", v));
+		return false;
+	}
+	unsafe { (*w).set_master_volume(v) };
+	true
+}
+
+#[no_mangle]
+/// Mute all audio while the window is not focused or minimized, without pausing the game. Returns false on NULL pointer.
+pub extern "C" fn alsnSetMuteUnfocused(w: *mut World, on: bool) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	unsafe { (*w).set_mute_unfocused(on) };
+	true
+}
+
+#[no_mangle]
+/// Set the volume of background music in the range [0, 1]. Returns false on NULL pointer or volume out of range.
+pub extern "C" fn alsnSetMusicVolume(w: *mut World, v: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if !(0.0..=1.0).contains(&v) {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid volume {}", v));
+		return false;
+	}
+	unsafe {
+		let w = &mut *w;
+		w.set_music_volume(v);
+	}
+	true
+}
+
+#[no_mangle]
+/// Set the volume of sound effects in the range [0, 1]. Returns false on NULL pointer or volume out of range.
+pub extern "C" fn alsnSetSfxVolume(w: *mut World, v: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if !(0.0..=1.0).contains(&v) {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid volume {}", v));
+		return false;
+	}
+	unsafe {
+		let w = &mut *w;
+		w.set_sfx_volume(v);
+	}
+	true
+}
+
+#[no_mangle]
+/// Set the stinger of kind `kind` (0 - level-up, 1 - unit lost, 2 - objective complete) to the sound with specified id, `length` seconds long, for which the background music is ducked.
+/// A negative length removes the stinger. Returns false on NULL pointer or invalid kind.
+pub extern "C" fn alsnSetStinger(w: *mut World, kind: u8, sound_id: u8, length: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	let k = match stinger_arg(kind) { Some(k) => k, None => return false };
+	unsafe { (*w).set_stinger(k, Some((sound_id, length)).filter(|_| length >= 0.0)) };
+	true
+}
+
+#[no_mangle]
+/// Play the stinger of kind `kind`, ducking the background music until it ends. Returns false on NULL pointer, invalid kind, or if no stinger of the kind is set.
+pub extern "C" fn alsnPlayStinger(w: *mut World, kind: u8) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	let k = match stinger_arg(kind) { Some(k) => k, None => return false };
+	unsafe { (*w).play_stinger(k) }
+}
+
+#[no_mangle]
+/// Set the fraction of its volume, in the range [0, 1], the background music is ducked to while a stinger plays. Returns false on NULL pointer or level out of range.
+pub extern "C" fn alsnSetDuckLevel(w: *mut World, level: f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	if !(0.0..=1.0).contains(&level) {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid duck level {}", level));
+		return false;
+	}
+	unsafe { (*w).set_duck_level(level) };
+	true
+}
+
+/// Convert a raw stinger kind, reporting it if invalid.
+fn stinger_arg(kind: u8) -> Option<world::mixer::StingerKind> {
+	let k = world::mixer::StingerKind::from_u8(kind);
+	if k.is_none() {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid stinger kind {}", kind));
+	}
+	k
+}
+
+#[no_mangle]
+/// Save screenshots of a running game to the directory `dir`, rather than the working directory. Returns false on NULL pointer or invalid string.
+pub extern "C" fn alsnSetScreenshotDir(rt: *mut Runtime, dir: *const c_char) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(dir, "fatal [napi]: Pointer to Screenshot Directory String is NULL", false);
+	let d = cstr_arg!(dir, "Screenshot directory", false);
+	unsafe { (*rt).set_screenshot_dir(&d) };
+	true
+}
+
+#[no_mangle]
+/// Ask for confirmation when the window of a running game is closed. Returns false on NULL pointer.
+pub extern "C" fn alsnSetConfirmExit(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_confirm_exit(on) };
+	true
+}
+
+#[no_mangle]
+/// Report the memory used by every loaded resource of a running game, with totals, as text, see `ResourceSet::report`.
+/// Release it with `alsnFreeString`. Returns NULL on NULL pointer.
+pub extern "C" fn alsnResourceReport(rt: *const Runtime) -> *mut c_char {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", ptr::null_mut());
+	to_owned_cstr(&unsafe { (*rt).resource_report() }.to_string())
+}
+
+#[no_mangle]
+/// Set the video memory, in bytes, that textures and fonts should fit in once loaded, or 0 for no budget; a warning is printed if it is exceeded. Returns false on NULL pointer.
+pub extern "C" fn alsnSetMemoryBudget(rs: *mut ResourceSet, bytes: u64) -> bool {
+	check_nonnull!(rs, "fatal [napi]: Pointer to ResourceSet is NULL", false);
+	unsafe { (*rs).set_memory_budget(bytes as usize) };
+	true
+}
+
+#[no_mangle]
+/// Set the milliseconds the built-in AI of a running game may spend deciding per frame, or 0 to decide at once. Returns false on NULL pointer.
+pub extern "C" fn alsnSetAiBudget(rt: *mut Runtime, ms: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_ai_budget(ms) };
+	true
+}
+
+#[no_mangle]
+/// Set the time limits (in seconds, 0 for none) of the player turn of a running game, chess-clock style. Returns false on NULL pointer.
+pub extern "C" fn alsnSetTurnTimer(rt: *mut Runtime, per_turn: f32, bank: f32, increment: f32, auto_wait: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_turn_timer(per_turn, bank, increment, auto_wait) };
+	true
+}
+
+#[no_mangle]
+/// Ignore an action repeated within `debounce` seconds (e.g, a double click) in a running game, and keep the intro and results screens shown for at least `min_screen_time` seconds.
+/// Returns false on NULL pointer.
+pub extern "C" fn alsnSetInputGuard(rt: *mut Runtime, debounce: f32, min_screen_time: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_input_guard(debounce, min_screen_time) };
+	true
+}
+
+#[no_mangle]
+/// Rumble a gamepad when a player unit takes a hit, and when a unit is selected, in a running game; each with a strength (0 to 1) and duration in seconds, 0 to disable.
+/// Rumbles are published as events of kind 12 (see `alsnPollEvent`), for the game to play with its platform's gamepad API. Returns false on NULL pointer.
+pub extern "C" fn alsnSetRumble(rt: *mut Runtime, hit_strength: f32, hit_duration: f32, select_strength: f32, select_duration: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_rumble((hit_strength, hit_duration), (select_strength, select_duration)) };
+	true
+}
+
+#[no_mangle]
+/// Read touch gestures (tap to select, drag to pan, pinch to zoom, long-press for unit information) in a running game if `on`, or mouse clicks otherwise.
+/// Returns false on NULL pointer.
+pub extern "C" fn alsnSetTouchInput(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_touch_input(on) };
+	true
+}
+
+#[no_mangle]
+/// Write the seconds left in the player turn and in the bank of a running game, or -1 if unlimited. Returns false on NULL pointer.
+pub extern "C" fn alsnGetTurnTimer(rt: *const Runtime, turn_left: *mut f32, bank_left: *mut f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(turn_left, "fatal [napi]: Pointer to Turn Time is NULL", false);
+	check_nonnull!(bank_left, "fatal [napi]: Pointer to Bank Time is NULL", false);
+	unsafe {
+		let t = (*rt).turn_timer();
+		*turn_left = t.turn_left().unwrap_or(-1.0);
+		*bank_left = t.bank_left().unwrap_or(-1.0);
+	}
+	true
+}
+
+#[no_mangle]
+/// Show or hide the danger zone of a running game. Returns false on NULL pointer.
+pub extern "C" fn alsnSetDangerZone(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_danger_zone(on) };
+	true
+}
+
+#[no_mangle]
+/// Show or hide the AI debug overlay of a running game. Returns false on NULL pointer.
+pub extern "C" fn alsnSetAiDebug(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_ai_debug(on) };
+	true
+}
+
+#[no_mangle]
+/// Pause a running game, and mute its audio, while its window is not focused or minimized. Returns false on NULL pointer.
+pub extern "C" fn alsnSetPauseUnfocused(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_pause_unfocused(on) };
+	true
+}
+
+#[no_mangle]
+/// Set the rate at which a running game carries out orders, or 0 for once per frame. Returns false on NULL pointer.
+pub extern "C" fn alsnSetSimFps(rt: *mut Runtime, fps: u32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_sim_fps(fps) };
+	true
+}
+
+#[no_mangle]
+/// Set the target fps of a running game while its window is not focused, or 0 to disable throttling. Returns false on NULL pointer.
+pub extern "C" fn alsnSetBackgroundFps(rt: *mut Runtime, fps: u32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_background_fps(fps) };
+	true
+}
+
+#[no_mangle]
+/// Hide or show all HUD elements and overlays of a running game. Returns false on NULL pointer.
+pub extern "C" fn alsnSetHudHidden(rt: *mut Runtime, hidden: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_hud_hidden(hidden) };
+	true
+}
+
+#[no_mangle]
+/// Hide the HUD in screenshots of a running game, even while it is shown. Returns false on NULL pointer.
+pub extern "C" fn alsnSetCleanScreenshots(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_clean_screenshots(on) };
+	true
+}
+
+#[no_mangle]
+/// Show or hide the control hints bar of a running game (see `alesia::display::Display::set_control_hints`). Returns false on NULL pointer.
+pub extern "C" fn alsnSetControlHints(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_control_hints(on) };
+	true
+}
+
+#[no_mangle]
+/// Set the scale factor of HUD elements of a running game. Returns false on NULL pointer or non-positive scale.
+pub extern "C" fn alsnSetUiScale(rt: *mut Runtime, scale: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	if scale.is_nan() || scale <= 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid ui scale {}", scale));
+		return false;
+	}
+	unsafe { (*rt).set_ui_scale(scale) };
+	true
+}
+
+#[no_mangle]
+/// Set the scale factor of font sizes of a running game (see `alesia::display::Display::set_text_scale`). Returns false on NULL pointer or non-positive scale.
+pub extern "C" fn alsnSetTextScale(rt: *mut Runtime, scale: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	if scale.is_nan() || scale <= 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid text scale {}", scale));
+		return false;
+	}
+	unsafe { (*rt).set_text_scale(scale) };
+	true
+}
+
+#[no_mangle]
+/// Switch a running game to or from the high-contrast look (see `alesia::display::Display::set_high_contrast`). Returns false on NULL pointer.
+pub extern "C" fn alsnSetHighContrast(rt: *mut Runtime, on: bool) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	unsafe { (*rt).set_high_contrast(on) };
+	true
+}
+
+/// Load the settings file at `path` (the defaults if it does not exist), or None (after reporting the error) if it could not be read.
+pub(crate) fn load_settings(path: &str) -> Option<Settings> {
+	match Settings::load(path) {
+		Ok(s) => Some(s),
+		Err(e) => {
+			error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to read settings {}: {}", path, e));
+			None
+		}
+	}
+}
+
+#[no_mangle]
+/// Apply the ui and text scale, high-contrast mode, volumes and key bindings of the settings file at `path` to a running game. Returns false on NULL pointer, invalid string, or if the file could not be read.
+pub extern "C" fn alsnApplySettings(rt: *mut Runtime, path: *const c_char) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Settings Path String is NULL", false);
+	let p = cstr_arg!(path, "Settings path", false);
+	match load_settings(&p) {
+		Some(s) => {
+			unsafe { (*rt).apply_settings(&s) };
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Save the volumes of a running game, and muting while unfocused, to the settings file at `path`, keeping its other settings. Returns false on NULL pointer, invalid string, or if the file could not be read or written.
+pub extern "C" fn alsnSaveAudioSettings(rt: *mut Runtime, path: *const c_char) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	check_nonnull!(path, "fatal [napi]: Pointer to Settings Path String is NULL", false);
+	let p = cstr_arg!(path, "Settings path", false);
+	save_audio_settings(unsafe { &*rt }, &p)
+}
+
+pub(crate) fn save_audio_settings(rt: &Runtime, path: &str) -> bool {
+	match rt.save_audio_settings(path) {
+		Ok(()) => true,
+		Err(e) => {
+			error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to save settings {}: {}", path, e));
+			false
+		}
+	}
+}
+
+/// Copy of `t` with the specified phase banner settings, or None (after reporting the error) if they are out of range.
+pub(crate) fn banner_theme(t: &crate::display::Theme, tex: i32, font: i32, bg: i32, fg: i32, time: f32) -> Option<crate::display::Theme> {
+	let id = |v: i32| if v < 0 { Some(None) } else if v <= 255 { Some(Some(v as u8)) } else { None };
+	match (id(tex), id(font)) {
+		(Some(tex), Some(font)) if time >= 0.0 => Some(crate::display::Theme {
+			banner_tex: tex,
+			banner_font: font,
+			banner_color: raylib::prelude::Color::get_color(bg),
+			banner_text_color: raylib::prelude::Color::get_color(fg),
+			banner_time: time,
+			..t.clone()
+		}),
+		_ => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid banner theme tex={}, font={}, time={}", tex, font, time));
+			None
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the look of the phase banner of a running game: texture and font ids (negative for none, i.e, a plain strip and the default font),
+/// background and text colours (hex), and the seconds it takes to cross the screen (0 disables it).
+/// Returns false on NULL pointer or invalid arguments.
+pub extern "C" fn alsnSetBannerTheme(rt: *mut Runtime, tex: i32, font: i32, bg: i32, fg: i32, time: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	let rt = unsafe { &mut *rt };
+	match banner_theme(rt.theme(), tex, font, bg, fg, time) {
+		Some(t) => {
+			rt.set_theme(t);
+			true
+		},
+		None => false
+	}
+}
+
+/// Copy of `t` with the specified transition settings, or None (after reporting the error) if they are out of range.
+pub(crate) fn transition_theme(t: &crate::display::Theme, kind: u8, time: f32, color: i32) -> Option<crate::display::Theme> {
+	match crate::display::Transition::from_u8(kind) {
+		Some(k) if time >= 0.0 => Some(crate::display::Theme {
+			transition: k,
+			transition_time: time,
+			transition_color: raylib::prelude::Color::get_color(color),
+			..t.clone()
+		}),
+		_ => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid transition kind={}, time={}", kind, time));
+			None
+		}
+	}
+}
+
+#[no_mangle]
+/// Set the transitions between screens of a running game: the effect (0 - fade, 1 - iris, 2 - slide), the seconds a transition takes (0 disables them), and the colour (hex) covering the screen.
+/// Returns false on NULL pointer or invalid arguments.
+pub extern "C" fn alsnSetTransition(rt: *mut Runtime, kind: u8, time: f32, color: i32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	let rt = unsafe { &mut *rt };
+	match transition_theme(rt.theme(), kind, time, color) {
+		Some(t) => {
+			rt.set_theme(t);
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Set the side of unit portraits in the unit panel of a running game, in HUD pixels (0 hides them). Returns false on NULL pointer or negative size.
+pub extern "C" fn alsnSetPortraitSize(rt: *mut Runtime, size: f32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	set_portrait_size(unsafe { &mut *rt }, size)
+}
+
+pub(crate) fn set_portrait_size(rt: &mut Runtime, size: f32) -> bool {
+	if size.is_nan() || size < 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid portrait size {}", size));
+		return false;
+	}
+	let t = crate::display::Theme { portrait_size: size, ..rt.theme().clone() };
+	rt.set_theme(t);
+	true
+}
+
+#[no_mangle]
+/// Set the icon shown before a unit status (0 - boosted, 1 - holding, 2 - guarding, 3 - patrolling, 4 - acted) in the unit information panel of a running game,
+/// or negative to show the status by name only. Returns false on NULL pointer or invalid arguments.
+pub extern "C" fn alsnSetStatusIcon(rt: *mut Runtime, status: u8, tex: i32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	set_status_icon(unsafe { &mut *rt }, status, tex)
+}
+
+pub(crate) fn set_status_icon(rt: &mut Runtime, status: u8, tex: i32) -> bool {
+	let s = match world::UnitStatus::from_u8(status) {
+		Some(s) => s,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid unit status {}", status));
+			return false;
+		}
+	};
+	let icon = match opt_tex_arg(tex, "status icon") { Some(i) => i, None => return false };
+	let mut t = rt.theme().clone();
+	t.status_icons[s as usize] = icon;
+	rt.set_theme(t);
+	true
+}
+
+#[no_mangle]
+/// Set the colour (in 0xRRGGBBAA format) and font id (negative for the default font) of popups of a kind (0 - damage, 1 - heal, 2 - status, 3 - miss, 4 - crit) in a running game.
+/// Returns false on NULL pointer or invalid arguments.
+pub extern "C" fn alsnSetPopupStyle(rt: *mut Runtime, kind: u8, color: i32, font: i32) -> bool {
+	check_nonnull!(rt, "fatal [napi]: Pointer to Runtime is NULL", false);
+	set_popup_style(unsafe { &mut *rt }, kind, color, font)
+}
+
+pub(crate) fn set_popup_style(rt: &mut Runtime, kind: u8, color: i32, font: i32) -> bool {
+	let k = match world::PopupKind::from_u8(kind) {
+		Some(k) => k,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid popup kind {}", kind));
+			return false;
+		}
+	};
+	let f = match font {
+		f if f < 0 => None,
+		f if f <= 255 => Some(f as u8),
+		f => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid popup font id={}", f));
+			return false;
+		}
+	};
+	let mut t = rt.theme().clone();
+	t.popup_colors[k as usize] = raylib::prelude::Color::get_color(color);
+	t.popup_fonts[k as usize] = f;
+	rt.set_theme(t);
+	true
+}
+
+#[no_mangle]
+/// Write the world position of the tile at screen position (`sx`, `sy`) into `out_tx` and `out_ty`. Returns false on NULL pointer.
+/// The camera position is taken into account, i.e, mouse co-ordinates can be passed as is.
+pub extern "C" fn alsnTileAt(w: *const World, sx: f32, sy: f32, out_tx: *mut i32, out_ty: *mut i32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_tx, "fatal [napi]: Pointer to x co-ordinate is NULL", false);
+	check_nonnull!(out_ty, "fatal [napi]: Pointer to y co-ordinate is NULL", false);
+	unsafe {
+		let (tx, ty) = world::tile_at(&*w, sx, sy);
+		*out_tx = tx;
+		*out_ty = ty;
+	}
+	true
+}
+
+#[no_mangle]
+/// Write the screen position of the centre of tile (`tx`, `ty`) into `out_sx` and `out_sy`. Returns false on NULL pointer.
+/// Inverse of `alsnTileAt`, i.e, the camera position is taken into account.
+pub extern "C" fn alsnTileToScreen(w: *const World, tx: i32, ty: i32, out_sx: *mut f32, out_sy: *mut f32) -> bool {
+	check_nonnull!(w, "fatal [napi]: Pointer to World is NULL", false);
+	check_nonnull!(out_sx, "fatal [napi]: Pointer to x co-ordinate is NULL", false);
+	check_nonnull!(out_sy, "fatal [napi]: Pointer to y co-ordinate is NULL", false);
+	unsafe {
+		let (sx, sy) = world::tile_to_screen(&*w, tx, ty);
+		*out_sx = sx;
+		*out_sy = sy;
+	}
+	true
+}
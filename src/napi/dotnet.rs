@@ -0,0 +1,1009 @@
+//! .NET (C#) friendly variants of the [handle based API](super::handles). Requires the `dotnet` feature.
+//!
+//! Functions in this module only use blittable types, so that they can be bound with plain `DllImport`s without custom marshalling:
+//! 1. Flags (arguments and return values) are `i32`, where 0 is false and any other value is true.
+//! 2. Strings are null-terminated UTF-16 (i.e, C# `string` with `CharSet.Unicode`).
+//! 3. Callbacks use the `system` calling convention (`stdcall` on 32-bit Windows), i.e, the default for C# delegates.
+//!
+//! Handles returned by these functions are ordinary handles, and may be used with the rest of the handle based API.
+
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use crate::input::Order;
+use crate::input::TurnPhase;
+use crate::input::Outcome;
+use crate::utils::StateListener;
+use crate::world::World;
+use crate::world::Difficulty;
+use crate::world::ElevationRules;
+use crate::event::RawEvent;
+use super::error;
+use super::handles;
+use super::handles::Handle;
+
+/// C# delegate type for `on_init` [callback](StateListener).
+pub type CsInitHandle = Option<extern "system" fn(*mut c_void)>;
+/// C# delegate type for `on_turn` [callback](StateListener). Ownership is the same as for [CTurnHandle](crate::utils::CTurnHandle).
+pub type CsTurnHandle = Option<extern "system" fn(*mut World, *mut Vec<Order>, *mut c_void)>;
+/// C# delegate type for `on_update` [callback](StateListener).
+pub type CsUpdateHandle = Option<extern "system" fn(*mut World, f32, *mut c_void)>;
+/// C# delegate type for `on_unit_death` [callback](StateListener).
+pub type CsUnitDeathHandle = Option<extern "system" fn(*mut World, u8, u8, *mut c_void)>;
+/// C# delegate type for `on_attack` [callback](StateListener).
+pub type CsAttackHandle = Option<extern "system" fn(*mut World, u8, u8, f32, *mut c_void)>;
+/// C# delegate type for `on_move_complete` [callback](StateListener), receiving the id of the unit and the coordinates of the tile.
+pub type CsMoveCompleteHandle = Option<extern "system" fn(*mut World, u8, i32, i32, *mut c_void)>;
+/// C# delegate type for `on_results` [callback](StateListener).
+pub type CsResultsHandle = Option<extern "system" fn(*mut World, i32, *mut c_void)>;
+/// C# delegate type for `on_phase_change` [callback](StateListener).
+pub type CsPhaseHandle = Option<extern "system" fn(*mut World, TurnPhase, *mut c_void)>;
+/// C# delegate type for `on_draw` [callback](StateListener). The World must not be modified.
+pub type CsDrawHandle = Option<extern "system" fn(*const World, *mut c_void)>;
+/// C# delegate type for `on_victory_check` [callback](StateListener). Returns 1 for victory, 2 for defeat and 0 to continue.
+pub type CsVictoryHandle = Option<extern "system" fn(*const World, *mut c_void) -> i32>;
+/// C# delegate type for `on_exit` [callback](StateListener).
+pub type CsExitHandle = Option<extern "system" fn(*mut World, *mut c_void)>;
+/// C# delegate type for `on_timeout` [callback](StateListener). The flag is 1 if the bank has been spent.
+pub type CsTimeoutHandle = Option<extern "system" fn(*mut World, i32, *mut c_void)>;
+/// C# delegate type for `on_map_loaded` [callback](StateListener), receiving the path of the map as a UTF-16 string, valid only for the duration of the callback.
+pub type CsMapLoadedHandle = Option<extern "system" fn(*mut World, *const u16, *mut c_void)>;
+/// C# delegate type for the callback of a tween, see `alsdAddTween`.
+pub type CsTweenHandle = Option<extern "system" fn(*mut World, u32, *mut c_void)>;
+/// C# delegate type for scheduled callbacks, see `alsdScheduleAfter`.
+pub type CsScheduleHandle = Option<extern "system" fn(*mut World, u32, *mut c_void)>;
+/// C# delegate type for `on_pickup` [callback](StateListener), receiving the id of the unit, and the kind and value of the pickup.
+pub type CsPickupHandle = Option<extern "system" fn(*mut World, u8, u8, f32, *mut c_void)>;
+/// C# delegate type for `on_narration` [callback](StateListener), receiving a line of narration as a UTF-16 string, valid only for the duration of the callback.
+pub type CsNarrationHandle = Option<extern "system" fn(*mut World, *const u16, *mut c_void)>;
+
+fn from_wide(p: *const u16, what: &str) -> Option<String> {
+	if p.is_null() {
+		error::report(error::ERR_NULL_POINTER, &format!("fatal [napi]: Pointer to {} is NULL", what));
+		return None;
+	}
+	let s = unsafe {
+		let mut n = 0;
+		while *p.add(n) != 0 {
+			n += 1;
+		}
+		std::slice::from_raw_parts(p, n)
+	};
+	match String::from_utf16(s) {
+		Ok(s) => Some(s),
+		Err(_) => {
+			error::report(error::ERR_INVALID_STRING, &format!("fatal [napi]: {} is not valid UTF-16", what));
+			None
+		}
+	}
+}
+
+/// Call a handle based function with the Utf8 equivalent of a UTF-16 string argument.
+fn with_utf8<R>(p: *const u16, what: &str, default: R, f: impl FnOnce(*const c_char) -> R) -> R {
+	match from_wide(p, what) {
+		Some(s) => {
+			let mut b = s.into_bytes();
+			b.push(0);
+			f(b.as_ptr() as *const c_char)
+		},
+		None => default
+	}
+}
+
+#[no_mangle]
+/// Writes the message of the last error of the calling thread into `buf` as a null-terminated UTF-16 string, truncated to `cap` units (including the terminator).
+/// Returns the error code, see `alsGetLastError`.
+pub extern "system" fn alsdGetLastError(buf: *mut u16, cap: i32) -> i32 {
+	let mut msg = std::ptr::null();
+	let code = error::alsGetLastError(&mut msg);
+	write_wide(&unsafe { std::ffi::CStr::from_ptr(msg) }.to_string_lossy(), buf, cap);
+	code
+}
+
+/// Write a string into `buf` as a null-terminated UTF-16 string, truncated to `cap` units (including the terminator). Returns the number of units written, excluding the terminator.
+fn write_wide(s: &str, buf: *mut u16, cap: i32) -> i32 {
+	if buf.is_null() || cap <= 0 {
+		return 0;
+	}
+	let mut n = 0;
+	for u in s.encode_utf16().take(cap as usize - 1) {
+		unsafe { *buf.add(n) = u };
+		n += 1;
+	}
+	unsafe { *buf.add(n) = 0 };
+	n as i32
+}
+
+#[no_mangle]
+/// Variant of `alshIsValid`.
+pub extern "system" fn alsdIsValid(h: Handle) -> i32 {
+	handles::alshIsValid(h) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshRelease`.
+pub extern "system" fn alsdRelease(h: Handle) -> i32 {
+	handles::alshRelease(h) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshNewUnitType` with a UTF-16 display name.
+pub extern "system" fn alsdNewUnitType(tid: u8, name: *const u16, health: f32, mov_rate: f32, movt: u8, ran: u8, adur: f32) -> Handle {
+	with_utf8(name, "UnitType display name String", 0, |p| handles::alshNewUnitType(tid, p, health, mov_rate, movt, ran, adur))
+}
+
+#[no_mangle]
+/// Variant of `alshDeriveUnitType` with a UTF-16 display name.
+pub extern "system" fn alsdDeriveUnitType(w: Handle, base: u8, name: *const u16) -> Handle {
+	with_utf8(name, "UnitType display name String", 0, |p| handles::alshDeriveUnitType(w, base, p))
+}
+
+#[no_mangle]
+/// Variant of `alshAddIntroSlide` with a UTF-16 caption.
+pub extern "system" fn alsdAddIntroSlide(w: Handle, tex: u8, caption: *const u16, duration: f32) -> i32 {
+	with_utf8(caption, "Slide caption", 0, |p| handles::alshAddIntroSlide(w, tex, p, duration) as i32)
+}
+
+#[no_mangle]
+/// Variant of `alshShowScrollingText` with UTF-16 text.
+pub extern "system" fn alsdShowScrollingText(w: Handle, text: *const u16, speed: f32) -> i32 {
+	with_utf8(text, "Scrolling text", 0, |p| handles::alshShowScrollingText(w, p, speed) as i32)
+}
+
+#[no_mangle]
+/// Variant of `alshShowPopup` with UTF-16 text.
+pub extern "system" fn alsdShowPopup(w: Handle, uid: u8, text: *const u16, kind: u8) -> i32 {
+	with_utf8(text, "Popup text", 0, |p| handles::alshShowPopup(w, uid, p, kind) as i32)
+}
+
+#[no_mangle]
+/// Variant of `alshClearTileLayers`.
+pub extern "system" fn alsdClearTileLayers(w: Handle) -> i32 {
+	handles::alshClearTileLayers(w) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshAddObjective` with UTF-16 text.
+pub extern "system" fn alsdAddObjective(w: Handle, text: *const u16) -> i32 {
+	with_utf8(text, "Objective text", -1, |p| handles::alshAddObjective(w, p))
+}
+
+#[no_mangle]
+/// Variant of `alsnSaveBattle` with a UTF-16 path, for use from callbacks.
+pub extern "system" fn alsdSaveBattle(w: *const World, path: *const u16) -> i32 {
+	with_utf8(path, "Save Path String", false, |p| crate::napi::alsnSaveBattle(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSaveMap` with a UTF-16 path.
+pub extern "system" fn alsdSaveMap(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Map Path String", false, |p| handles::alshSaveMap(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadMapRon` with a UTF-16 path.
+pub extern "system" fn alsdLoadMapRon(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Map Path String", false, |p| handles::alshLoadMapRon(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSaveMapRon` with a UTF-16 path.
+pub extern "system" fn alsdSaveMapRon(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Map Path String", false, |p| handles::alshSaveMapRon(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadBattle` with a UTF-16 path.
+pub extern "system" fn alsdLoadBattle(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Save Path String", false, |p| handles::alshLoadBattle(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetAutosave` with a UTF-16 path.
+pub extern "system" fn alsdSetAutosave(rt: Handle, path: *const u16, every: u32, before_enemy: i32) -> i32 {
+	with_utf8(path, "Autosave Path String", false, |p| handles::alshSetAutosave(rt, p, every, before_enemy != 0)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetScreenshotDir` with a UTF-16 directory.
+pub extern "system" fn alsdSetScreenshotDir(rt: Handle, dir: *const u16) -> i32 {
+	with_utf8(dir, "Screenshot Directory String", false, |d| handles::alshSetScreenshotDir(rt, d)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetConfirmExit` with a 32-bit boolean.
+pub extern "system" fn alsdSetConfirmExit(rt: Handle, on: i32) -> i32 {
+	handles::alshSetConfirmExit(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetAiBudget`.
+pub extern "system" fn alsdSetAiBudget(rt: Handle, ms: f32) -> i32 {
+	handles::alshSetAiBudget(rt, ms) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetDangerZone` with a 32-bit boolean.
+pub extern "system" fn alsdSetDangerZone(rt: Handle, on: i32) -> i32 {
+	handles::alshSetDangerZone(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetInputGuard`.
+pub extern "system" fn alsdSetInputGuard(rt: Handle, debounce: f32, min_screen_time: f32) -> i32 {
+	handles::alshSetInputGuard(rt, debounce, min_screen_time) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetRumble`.
+pub extern "system" fn alsdSetRumble(rt: Handle, hit_strength: f32, hit_duration: f32, select_strength: f32, select_duration: f32) -> i32 {
+	handles::alshSetRumble(rt, hit_strength, hit_duration, select_strength, select_duration) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetTouchInput` with a 32-bit boolean.
+pub extern "system" fn alsdSetTouchInput(rt: Handle, on: i32) -> i32 {
+	handles::alshSetTouchInput(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetTurnTimer` with a 32-bit boolean.
+pub extern "system" fn alsdSetTurnTimer(rt: Handle, per_turn: f32, bank: f32, increment: f32, auto_wait: i32) -> i32 {
+	handles::alshSetTurnTimer(rt, per_turn, bank, increment, auto_wait != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshGetTurnTimer`.
+pub extern "system" fn alsdGetTurnTimer(rt: Handle, turn_left: *mut f32, bank_left: *mut f32) -> i32 {
+	handles::alshGetTurnTimer(rt, turn_left, bank_left) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetAiDebug` with a 32-bit boolean.
+pub extern "system" fn alsdSetAiDebug(rt: Handle, on: i32) -> i32 {
+	handles::alshSetAiDebug(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetPauseUnfocused` with a 32-bit boolean.
+pub extern "system" fn alsdSetPauseUnfocused(rt: Handle, on: i32) -> i32 {
+	handles::alshSetPauseUnfocused(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetSimFps`.
+pub extern "system" fn alsdSetSimFps(rt: Handle, fps: u32) -> i32 {
+	handles::alshSetSimFps(rt, fps) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetBackgroundFps`.
+pub extern "system" fn alsdSetBackgroundFps(rt: Handle, fps: u32) -> i32 {
+	handles::alshSetBackgroundFps(rt, fps) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetHudHidden` with a 32-bit boolean.
+pub extern "system" fn alsdSetHudHidden(rt: Handle, hidden: i32) -> i32 {
+	handles::alshSetHudHidden(rt, hidden != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCleanScreenshots` with a 32-bit boolean.
+pub extern "system" fn alsdSetCleanScreenshots(rt: Handle, on: i32) -> i32 {
+	handles::alshSetCleanScreenshots(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetControlHints` with a 32-bit boolean.
+pub extern "system" fn alsdSetControlHints(rt: Handle, on: i32) -> i32 {
+	handles::alshSetControlHints(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetHighContrast` with a 32-bit boolean.
+pub extern "system" fn alsdSetHighContrast(rt: Handle, on: i32) -> i32 {
+	handles::alshSetHighContrast(rt, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSaveSlot` with a UTF-16 directory.
+pub extern "system" fn alsdSaveSlot(rt: Handle, dir: *const u16, slot: u32) -> i32 {
+	with_utf8(dir, "Save Directory String", false, |d| handles::alshSaveSlot(rt, d, slot)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadSlot` with a UTF-16 directory.
+pub extern "system" fn alsdLoadSlot(rt: Handle, dir: *const u16, slot: u32) -> i32 {
+	with_utf8(dir, "Save Directory String", false, |d| handles::alshLoadSlot(rt, d, slot)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alsnListSlots` with a UTF-16 directory.
+pub extern "system" fn alsdListSlots(dir: *const u16, out: *mut u32, cap: usize) -> usize {
+	with_utf8(dir, "Save Directory String", 0, |d| crate::napi::alsnListSlots(d, out, cap))
+}
+
+#[no_mangle]
+/// Variant of `alsnGetSlotInfo` with a UTF-16 directory, without the map title. Returns 0 if the slot is empty.
+pub extern "system" fn alsdGetSlotInfo(dir: *const u16, slot: u32, turn: *mut u32, time: *mut u64) -> i32 {
+	with_utf8(dir, "Save Directory String", false, |d| {
+		let t = crate::napi::alsnGetSlotInfo(d, slot, turn, time);
+		crate::napi::alsnFreeString(t);
+		!t.is_null()
+	}) as i32
+}
+
+#[no_mangle]
+/// Variant of `alsnDeleteSlot` with a UTF-16 directory.
+pub extern "system" fn alsdDeleteSlot(dir: *const u16, slot: u32) -> i32 {
+	with_utf8(dir, "Save Directory String", false, |d| crate::napi::alsnDeleteSlot(d, slot)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshOpenProfile` with a UTF-16 path.
+pub extern "system" fn alsdOpenProfile(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Profile Path String", false, |p| handles::alshOpenProfile(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alsnGetProfileValue` with a UTF-16 key, for use from callbacks.
+pub extern "system" fn alsdGetProfileValue(w: *const World, key: *const u16) -> i64 {
+	with_utf8(key, "Profile key", 0, |k| crate::napi::alsnGetProfileValue(w, k))
+}
+
+#[no_mangle]
+/// Variant of `alsnSetProfileValue` with a UTF-16 key, for use from callbacks.
+pub extern "system" fn alsdSetProfileValue(w: *mut World, key: *const u16, v: i64) -> i32 {
+	with_utf8(key, "Profile key", false, |k| crate::napi::alsnSetProfileValue(w, k, v)) as i32
+}
+
+#[no_mangle]
+/// Creates a StateListener for callbacks bound with the `alsdBind*` functions.
+pub extern "system" fn alsdNewStateListener() -> Handle {
+	handles::register_listener(StateListener::new())
+}
+
+#[no_mangle]
+/// Variant of `alshMapTexture` with a UTF-16 path.
+pub extern "system" fn alsdMapTexture(rs: Handle, id: u8, path: *const u16) -> i32 {
+	with_utf8(path, "ResourceSet Path String", false, |p| handles::alshMapTexture(rs, id, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapTextureRegion`.
+pub extern "system" fn alsdMapTextureRegion(rs: Handle, tid: u8, parent_id: u8, x: f32, y: f32, w: f32, h: f32) -> i32 {
+	handles::alshMapTextureRegion(rs, tid, parent_id, x, y, w, h) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapFont` with a UTF-16 path.
+pub extern "system" fn alsdMapFont(rs: Handle, id: u8, path: *const u16) -> i32 {
+	with_utf8(path, "ResourceSet Path String", false, |p| handles::alshMapFont(rs, id, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapFontAtlas` with a UTF-16 path.
+pub extern "system" fn alsdMapFontAtlas(rs: Handle, id: u8, path: *const u16, sizes: *const i32, nsizes: i32, ranges: *const u32, nranges: i32) -> i32 {
+	with_utf8(path, "ResourceSet Path String", false, |p| handles::alshMapFontAtlas(rs, id, p, sizes, nsizes.max(0) as usize, ranges, nranges.max(0) as usize)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapSound` with a UTF-16 path.
+pub extern "system" fn alsdMapSound(rs: Handle, id: u8, path: *const u16) -> i32 {
+	with_utf8(path, "ResourceSet Path String", false, |p| handles::alshMapSound(rs, id, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapMusic` with a UTF-16 path.
+pub extern "system" fn alsdMapMusic(rs: Handle, id: u8, path: *const u16) -> i32 {
+	with_utf8(path, "ResourceSet Path String", false, |p| handles::alshMapMusic(rs, id, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetTeamColors`.
+pub extern "system" fn alsdSetTeamColors(rs: Handle, colors: *const i32, n: i32) -> i32 {
+	handles::alshSetTeamColors(rs, colors, n.max(0) as usize) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshMapPalette`.
+pub extern "system" fn alsdMapPalette(rs: Handle, id: u8, colors: *const i32, n: i32) -> i32 {
+	handles::alshMapPalette(rs, id, colors, n.max(0) as usize) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshDefAnim`.
+pub extern "system" fn alsdDefAnim(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: i32) -> i32 {
+	handles::alshDefAnim(u, fw, fh, frn, cfx, cfy, fr, flip != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshDefAnimUnmuted`.
+pub extern "system" fn alsdDefAnimUnmuted(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: i32, snd: u8, lp: i32) -> i32 {
+	handles::alshDefAnimUnmuted(u, fw, fh, frn, cfx, cfy, fr, flip != 0, snd, lp != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshDefIdleAnim`.
+pub extern "system" fn alsdDefIdleAnim(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: i32) -> i32 {
+	handles::alshDefIdleAnim(u, fw, fh, frn, cfx, cfy, fr, flip != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshDefStandAnim`.
+pub extern "system" fn alsdDefStandAnim(u: Handle, facing: u8, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: i32) -> i32 {
+	handles::alshDefStandAnim(u, facing, fw, fh, frn, cfx, cfy, fr, flip != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetIdleDelay`.
+pub extern "system" fn alsdSetIdleDelay(u: Handle, secs: f32) -> i32 {
+	handles::alshSetIdleDelay(u, secs) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetUnitInfo` with a UTF-16 string.
+pub extern "system" fn alsdSetUnitInfo(u: Handle, s: *const u16) -> i32 {
+	with_utf8(s, "information string", false, |p| handles::alshSetUnitInfo(u, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetPortrait`.
+pub extern "system" fn alsdSetPortrait(u: Handle, tex: i32) -> i32 {
+	handles::alshSetPortrait(u, tex) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetActions`.
+pub extern "system" fn alsdSetActions(u: Handle, attacks: i32, move_after_attack: i32) -> i32 {
+	handles::alshSetActions(u, attacks, move_after_attack != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetVoice`.
+pub extern "system" fn alsdSetVoice(u: Handle, kind: u8, sounds: *const u8, n: usize, cooldown: f32) -> i32 {
+	handles::alshSetVoice(u, kind, sounds, n, cooldown) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetSight`.
+pub extern "system" fn alsdSetSight(u: Handle, sight: i32) -> i32 {
+	handles::alshSetSight(u, sight) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetUnitPortrait`.
+pub extern "system" fn alsdSetUnitPortrait(w: Handle, uid: u8, tex: i32) -> i32 {
+	handles::alshSetUnitPortrait(w, uid, tex) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCombatStats`.
+pub extern "system" fn alsdSetCombatStats(u: Handle, attack: f32, defense: f32) -> i32 {
+	handles::alshSetCombatStats(u, attack, defense) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshRegisterUnitType`.
+pub extern "system" fn alsdRegisterUnitType(w: Handle, u: Handle, id: u8) -> i32 {
+	handles::alshRegisterUnitType(w, u, id) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshCreateStatic`.
+pub extern "system" fn alsdCreateStatic(w: Handle, tex_id: u8, cx: i32, cy: i32) -> i32 {
+	handles::alshCreateStatic(w, tex_id, cx, cy) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSpawnUnit`.
+pub extern "system" fn alsdSpawnUnit(w: Handle, tid: u8, tx: i32, ty: i32, tint: i32, plr: i32, out_uid: *mut u8) -> i32 {
+	handles::alshSpawnUnit(w, tid, tx, ty, tint, plr != 0, out_uid) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetUnitHold`.
+pub extern "system" fn alsdSetUnitHold(w: Handle, uid: u8) -> i32 {
+	handles::alshSetUnitHold(w, uid) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetUnitGuard`.
+pub extern "system" fn alsdSetUnitGuard(w: Handle, uid: u8, x: i32, y: i32, radius: u8) -> i32 {
+	handles::alshSetUnitGuard(w, uid, x, y, radius) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshAddUnitPatrolPoint`.
+pub extern "system" fn alsdAddUnitPatrolPoint(w: Handle, uid: u8, x: i32, y: i32) -> i32 {
+	handles::alshAddUnitPatrolPoint(w, uid, x, y) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshClearUnitBehavior`.
+pub extern "system" fn alsdClearUnitBehavior(w: Handle, uid: u8) -> i32 {
+	handles::alshClearUnitBehavior(w, uid) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadAi` with a UTF-16 path.
+pub extern "system" fn alsdLoadAi(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Behavior tree file path string", false, |p| handles::alshLoadAi(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetUnitTypeAi` with UTF-16 strings.
+pub extern "system" fn alsdSetUnitTypeAi(w: Handle, type_id: u8, path: *const u16, root: *const u16) -> i32 {
+	with_utf8(path, "Behavior tree file path string", false, |p| {
+		with_utf8(root, "Behavior tree root string", false, |r| handles::alshSetUnitTypeAi(w, type_id, p, r))
+	}) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshClearUnitTypeAi`.
+pub extern "system" fn alsdClearUnitTypeAi(w: Handle, type_id: u8) -> i32 {
+	handles::alshClearUnitTypeAi(w, type_id) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshGetInfluence` with a 32-bit boolean.
+pub extern "system" fn alsdGetInfluence(w: Handle, x: i32, y: i32, player: i32, out_threat: *mut f32, out_support: *mut f32) -> i32 {
+	handles::alshGetInfluence(w, x, y, player != 0, out_threat, out_support) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadPuzzle` with a UTF-16 path.
+pub extern "system" fn alsdLoadPuzzle(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Puzzle file path string", false, |p| handles::alshLoadPuzzle(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshClearPuzzle`.
+pub extern "system" fn alsdClearPuzzle(w: Handle) -> i32 {
+	handles::alshClearPuzzle(w) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadTutorial` with a UTF-16 path.
+pub extern "system" fn alsdLoadTutorial(w: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Tutorial file path string", false, |p| handles::alshLoadTutorial(w, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshClearTutorial`.
+pub extern "system" fn alsdClearTutorial(w: Handle) -> i32 {
+	handles::alshClearTutorial(w) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshPanCamera`.
+pub extern "system" fn alsdPanCamera(w: Handle, x: f32, y: f32, duration: f32, easing: u8) -> i32 {
+	handles::alshPanCamera(w, x, y, duration, easing) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshPanToTile`.
+pub extern "system" fn alsdPanToTile(w: Handle, x: i32, y: i32, duration: f32) -> i32 {
+	handles::alshPanToTile(w, x, y, duration) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCameraFollow`.
+pub extern "system" fn alsdSetCameraFollow(w: Handle, uid: u8, follow: i32) -> i32 {
+	handles::alshSetCameraFollow(w, uid, follow != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCameraFollowRate`.
+pub extern "system" fn alsdSetCameraFollowRate(w: Handle, rate: f32) -> i32 {
+	handles::alshSetCameraFollowRate(w, rate) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCameraAutoFollow`.
+pub extern "system" fn alsdSetCameraAutoFollow(w: Handle, on: i32) -> i32 {
+	handles::alshSetCameraAutoFollow(w, on != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetZoom`.
+pub extern "system" fn alsdSetZoom(w: Handle, zoom: f32) -> i32 {
+	handles::alshSetZoom(w, zoom) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetInputScheme`.
+pub extern "system" fn alsdSetInputScheme(w: Handle, scheme: u8) -> i32 {
+	handles::alshSetInputScheme(w, scheme) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshAddTween` with a delegate.
+pub extern "system" fn alsdAddTween(w: Handle, from: f32, to: f32, duration: f32, easing: u8, f: CsTweenHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	handles::with_world(w, |w| crate::napi::add_tween(w, from, to, duration, easing, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Variant of `alshGetTweenValue`.
+pub extern "system" fn alsdGetTweenValue(w: Handle, id: u32, out: *mut f32) -> i32 {
+	handles::alshGetTweenValue(w, id, out) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshCancelTween`.
+pub extern "system" fn alsdCancelTween(w: Handle, id: u32) -> i32 {
+	handles::alshCancelTween(w, id) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshScheduleAfter` with a delegate.
+pub extern "system" fn alsdScheduleAfter(w: Handle, secs: f32, f: CsScheduleHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	handles::with_world(w, |w| crate::napi::schedule(w, secs, false, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Variant of `alshScheduleEvery` with a delegate.
+pub extern "system" fn alsdScheduleEvery(w: Handle, secs: f32, f: CsScheduleHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	handles::with_world(w, |w| crate::napi::schedule(w, secs, true, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Variant of `alshCancelScheduled`.
+pub extern "system" fn alsdCancelScheduled(w: Handle, id: u32) -> i32 {
+	handles::alshCancelScheduled(w, id) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshEnableEventQueue`.
+pub extern "system" fn alsdEnableEventQueue(w: Handle, enable: i32) -> i32 {
+	handles::alshEnableEventQueue(w, enable != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshPollEvent`.
+pub extern "system" fn alsdPollEvent(w: Handle, out: *mut RawEvent) -> i32 {
+	handles::alshPollEvent(w, out) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshPollEventJson`, writing the event into `buf` as a null-terminated UTF-16 string, truncated to `cap` units (including the terminator).
+/// Returns the number of units written, excluding the terminator, or 0 if the queue is empty or disabled. The event is removed even if truncated; 512 units are enough for any event but a map loaded from a long path.
+pub extern "system" fn alsdPollEventJson(w: Handle, buf: *mut u16, cap: i32) -> i32 {
+	if buf.is_null() || cap <= 0 {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to event buffer is NULL");
+		return 0;
+	}
+	match handles::with_world(w, |w| w.poll_event()) {
+		Some(Some(e)) => write_wide(&e.to_string(), buf, cap),
+		_ => 0
+	}
+}
+
+#[no_mangle]
+/// Variant of `alshStateDiff`, writing the differences into `buf` as a null-terminated UTF-16 string, truncated to `cap` units (including the terminator).
+/// Returns the number of units written, excluding the terminator; 0 if the states agree, or either handle is invalid.
+pub extern "system" fn alsdStateDiff(a: Handle, b: Handle, buf: *mut u16, cap: i32) -> i32 {
+	if buf.is_null() || cap <= 0 {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to diff buffer is NULL");
+		return 0;
+	}
+	match handles::state_diff(a, b) {
+		Some(s) => write_wide(&s, buf, cap),
+		None => 0
+	}
+}
+
+#[no_mangle]
+/// Variant of `alshPlacePickup`.
+pub extern "system" fn alsdPlacePickup(w: Handle, tex: u8, x: i32, y: i32, kind: u8, value: f32) -> i32 {
+	handles::alshPlacePickup(w, tex, x, y, kind, value) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshRemovePickup`.
+pub extern "system" fn alsdRemovePickup(w: Handle, x: i32, y: i32) -> i32 {
+	handles::alshRemovePickup(w, x, y) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshGetUnitHp`.
+pub extern "system" fn alsdGetUnitHp(w: Handle, uid: u8, out_hp: *mut i32, out_max: *mut i32) -> i32 {
+	handles::alshGetUnitHp(w, uid, out_hp, out_max) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetAiController`.
+pub extern "system" fn alsdSetAiController(w: Handle, enabled: i32, aggressiveness: f32) -> i32 {
+	handles::alshSetAiController(w, enabled != 0, aggressiveness) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetFogOfWar`.
+pub extern "system" fn alsdSetFogOfWar(w: Handle, enabled: i32) -> i32 {
+	handles::alshSetFogOfWar(w, enabled != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshIsTileVisible`.
+pub extern "system" fn alsdIsTileVisible(w: Handle, x: i32, y: i32) -> i32 {
+	handles::alshIsTileVisible(w, x, y) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetDefaultActions`.
+pub extern "system" fn alsdSetDefaultActions(w: Handle, attacks: u8, move_after_attack: i32) -> i32 {
+	handles::alshSetDefaultActions(w, attacks, move_after_attack != 0) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetHpRounding`.
+pub extern "system" fn alsdSetHpRounding(w: Handle, mode: u8) -> i32 {
+	handles::alshSetHpRounding(w, mode) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetDifficulty`.
+pub extern "system" fn alsdSetDifficulty(w: Handle, preset: u8) -> i32 {
+	handles::alshSetDifficulty(w, preset) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetCustomDifficulty`.
+pub extern "system" fn alsdSetCustomDifficulty(w: Handle, d: *const Difficulty) -> i32 {
+	handles::alshSetCustomDifficulty(w, d) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshGetDifficulty`.
+pub extern "system" fn alsdGetDifficulty(w: Handle, out: *mut Difficulty) -> i32 {
+	handles::alshGetDifficulty(w, out) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetElevation`.
+pub extern "system" fn alsdSetElevation(w: Handle, heights: *const u8, n: usize) -> i32 {
+	handles::alshSetElevation(w, heights, n) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetElevationRules`.
+pub extern "system" fn alsdSetElevationRules(w: Handle, r: *const ElevationRules) -> i32 {
+	handles::alshSetElevationRules(w, r) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshLoadMap` with a UTF-16 path.
+pub extern "system" fn alsdLoadMap(w: Handle, fpath: *const u16) -> i32 {
+	with_utf8(fpath, "Level file path string", false, |p| handles::alshLoadMap(w, p)) as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when display initialization is complete. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindInit(sl: Handle, f: CsInitHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_init(move || f(ctx)),
+		None => sl.bind_init(|| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when the player turn ends. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindTurn(sl: Handle, f: CsTurnHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_turn(move |w, o| f(w, o, ctx)),
+		None => sl.bind_turn(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked every frame. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindUpdate(sl: Handle, f: CsUpdateHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_update(move |w, dt| f(w, dt, ctx)),
+		None => sl.bind_update(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when a unit dies. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindUnitDeath(sl: Handle, f: CsUnitDeathHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_unit_death(move |w, uid, tid| f(w, uid, tid, ctx)),
+		None => sl.bind_unit_death(|_, _, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when a unit attacks. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindAttack(sl: Handle, f: CsAttackHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_attack(move |w, a, d, dmg| f(w, a, d, dmg, ctx)),
+		None => sl.bind_attack(|_, _, _, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when a unit completes a move. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindMoveComplete(sl: Handle, f: CsMoveCompleteHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_move_complete(move |w, uid, (x, y)| f(w, uid, x, y, ctx)),
+		None => sl.bind_move_complete(|_, _, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when a transition between screens has finished. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindTransitionDone(sl: Handle, f: CsPhaseHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_transition_done(move |w, p| f(w, p, ctx)),
+		None => sl.bind_transition_done(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when the phase banner has left the screen. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindBannerDone(sl: Handle, f: CsPhaseHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_banner_done(move |w, p| f(w, p, ctx)),
+		None => sl.bind_banner_done(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when the results screen is dismissed. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindResults(sl: Handle, f: CsResultsHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_results(move |w, o| f(w, o.to_raw(), ctx)),
+		None => sl.bind_results(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when the game enters a new phase. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindPhaseChange(sl: Handle, f: CsPhaseHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_phase_change(move |w, p| f(w, p, ctx)),
+		None => sl.bind_phase_change(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked every frame after the world and HUD have been drawn. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindDraw(sl: Handle, f: CsDrawHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_draw(move |_, w| f(w, ctx)),
+		None => sl.bind_draw(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked at the end of each turn. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindVictoryCheck(sl: Handle, f: CsVictoryHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_victory_check(move |w| Outcome::from_raw(f(w, ctx))),
+		None => sl.bind_victory_check(|_| Outcome::Continue)
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked once the game is about to exit. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindExit(sl: Handle, f: CsExitHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_exit(move |w| f(w, ctx)),
+		None => sl.bind_exit(|_| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when time runs out in a player turn. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindTimeout(sl: Handle, f: CsTimeoutHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_timeout(move |w, bank| f(w, bank as i32, ctx)),
+		None => sl.bind_timeout(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked once a map has been loaded. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindMapLoaded(sl: Handle, f: CsMapLoadedHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_map_loaded(move |w, m| {
+			let path: Vec<u16> = m.path.encode_utf16().chain(std::iter::once(0)).collect();
+			f(w, path.as_ptr(), ctx)
+		}),
+		None => sl.bind_map_loaded(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked when a unit collects a pickup. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindPickup(sl: Handle, f: CsPickupHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_pickup(move |w, uid, e| {
+			let (kind, value) = e.to_raw();
+			f(w, uid, kind, value, ctx)
+		}),
+		None => sl.bind_pickup(|_, _, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Bind a callback invoked with each line of narration. Only for listeners created with `alsdNewStateListener`.
+pub extern "system" fn alsdBindNarration(sl: Handle, f: CsNarrationHandle, ctx: *mut c_void) -> i32 {
+	handles::with_listener(sl, |sl| match f {
+		Some(f) => sl.bind_narration(move |w, text| {
+			let t: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+			f(w, t.as_ptr(), ctx)
+		}),
+		None => sl.bind_narration(|_, _| ())
+	}).is_some() as i32
+}
+
+#[no_mangle]
+/// Variant of `alshBegin_Display` with a UTF-16 title.
+pub extern "system" fn alsdBegin_Display(sw: i32, sh: i32, vsync: i32, fps: u32, t: *const u16, rs: Handle, w: Handle, sl: Handle, mvl: f32) -> i32 {
+	with_utf8(t, "Display Title String", false, |p| handles::alshBegin_Display(sw, sh, vsync != 0, fps, p, rs, w, sl, mvl)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshInit_Display` with a UTF-16 title.
+pub extern "system" fn alsdInit_Display(sw: i32, sh: i32, vsync: i32, fps: u32, t: *const u16, rs: Handle, w: Handle, sl: Handle, mvl: f32) -> Handle {
+	with_utf8(t, "Display Title String", 0, |p| handles::alshInit_Display(sw, sh, vsync != 0, fps, p, rs, w, sl, mvl))
+}
+
+#[no_mangle]
+/// Variant of `alshInit_DisplayFromSettings` with UTF-16 strings.
+pub extern "system" fn alsdInit_DisplayFromSettings(path: *const u16, t: *const u16, rs: Handle, w: Handle, sl: Handle) -> Handle {
+	with_utf8(path, "Settings Path String", 0, |p| with_utf8(t, "Display Title String", 0, |t| handles::alshInit_DisplayFromSettings(p, t, rs, w, sl)))
+}
+
+#[no_mangle]
+/// Variant of `alshApplySettings` with a UTF-16 path.
+pub extern "system" fn alsdApplySettings(rt: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Settings Path String", false, |p| handles::alshApplySettings(rt, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSaveAudioSettings` with a UTF-16 path.
+pub extern "system" fn alsdSaveAudioSettings(rt: Handle, path: *const u16) -> i32 {
+	with_utf8(path, "Settings Path String", false, |p| handles::alshSaveAudioSettings(rt, p)) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetPortraitSize`.
+pub extern "system" fn alsdSetPortraitSize(rt: Handle, size: f32) -> i32 {
+	handles::alshSetPortraitSize(rt, size) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetStatusIcon`.
+pub extern "system" fn alsdSetStatusIcon(rt: Handle, status: u8, tex: i32) -> i32 {
+	handles::alshSetStatusIcon(rt, status, tex) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetPopupStyle`.
+pub extern "system" fn alsdSetPopupStyle(rt: Handle, kind: u8, color: i32, font: i32) -> i32 {
+	handles::alshSetPopupStyle(rt, kind, color, font) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetTransition`.
+pub extern "system" fn alsdSetTransition(rt: Handle, kind: u8, time: f32, color: i32) -> i32 {
+	handles::alshSetTransition(rt, kind, time, color) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshSetBannerTheme`.
+pub extern "system" fn alsdSetBannerTheme(rt: Handle, tex: i32, font: i32, bg: i32, fg: i32, time: f32) -> i32 {
+	handles::alshSetBannerTheme(rt, tex, font, bg, fg, time) as i32
+}
+
+#[no_mangle]
+/// Variant of `alshStep_Runtime`.
+pub extern "system" fn alsdStep_Runtime(rt: Handle) -> i32 {
+	handles::alshStep_Runtime(rt) as i32
+}
@@ -0,0 +1,62 @@
+//! Thread-local error reporting for the native API.
+//!
+//! Wrapper functions that fail record an error code and message for the calling thread, in addition to printing the message.
+//! The record is only overwritten by subsequent failures, i.e, successful calls do not clear it; use `alsClearError` for that.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// No error has occurred since the last call to `alsClearError`.
+pub const ERR_NONE: i32 = 0;
+/// A pointer argument was NULL.
+pub const ERR_NULL_POINTER: i32 = 1;
+/// A string argument was not a valid Utf8 String.
+pub const ERR_INVALID_STRING: i32 = 2;
+/// An ID (unit, unit type, etc.) argument did not refer to an existing object.
+pub const ERR_INVALID_ID: i32 = 3;
+/// A handle was invalid, stale, or referred to an object of the wrong type.
+pub const ERR_INVALID_HANDLE: i32 = 4;
+/// Any other invalid argument, e.g, an out of range index.
+pub const ERR_INVALID_ARGUMENT: i32 = 5;
+/// An underlying engine operation failed, e.g, loading a level file.
+pub const ERR_OPERATION_FAILED: i32 = 6;
+
+struct LastError {
+	code: i32,
+	msg: CString
+}
+
+thread_local! {
+	static LAST_ERROR: RefCell<LastError> = RefCell::new(LastError { code: ERR_NONE, msg: CString::default() });
+}
+
+/// Print `msg` and record it as the last error of the calling thread.
+pub(crate) fn report(code: i32, msg: &str) {
+	eprintln!("{}", msg);
+	// Interior NUL bytes cannot be represented; strip them rather than drop the message.
+	let msg = CString::new(msg.replace('\0', "")).unwrap_or_default();
+	LAST_ERROR.with(|e| *e.borrow_mut() = LastError { code, msg });
+}
+
+#[no_mangle]
+/// Returns the code of the last error that occurred on the calling thread (one of the `ERR_*` constants), `ERR_NONE` if there has been none.
+/// If `msg` is not NULL, a pointer to the corresponding message is written to it.
+/// # Safety
+/// The message is owned by the engine, and remains valid until the next failing call or `alsClearError` on the same thread.
+/// **Under no circumstances must the message be released by the callsite**.
+pub extern "C" fn alsGetLastError(msg: *mut *const c_char) -> i32 {
+	LAST_ERROR.with(|e| {
+		let e = e.borrow();
+		if !msg.is_null() {
+			unsafe { *msg = e.msg.as_ptr() };
+		}
+		e.code
+	})
+}
+
+#[no_mangle]
+/// Clear the last error of the calling thread.
+pub extern "C" fn alsClearError() {
+	LAST_ERROR.with(|e| *e.borrow_mut() = LastError { code: ERR_NONE, msg: CString::default() });
+}
@@ -0,0 +1,1419 @@
+//! Handle based alternative to the raw pointer API.
+//!
+//! Engine objects created through this module are owned by an internal (per-thread) registry, and are referred to by opaque integer [Handle]s.
+//! Every handle carries a generation counter, so a handle that has been released (or consumed by the engine, e.g, by `alshBegin_Display`) is
+//! recognized as stale, and functions simply return `false` (or the null handle `0`) after printing an error message, instead of invoking undefined behaviour.
+//!
+//! Handles are only valid on the thread which created them.
+//! Callbacks invoked by the engine still receive borrowed `World` pointers; the `alsn` query functions remain usable with those.
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use raylib::prelude::Color;
+use crate::display::Display;
+use crate::display::Runtime;
+use crate::utils::CInitHandle;
+use crate::utils::CTurnHandle;
+use crate::utils::CInitCtxHandle;
+use crate::utils::CTurnCtxHandle;
+use crate::utils::{CUpdateHandle, CUnitDeathHandle, CAttackHandle, CPhaseHandle, CDrawHandle, CVictoryHandle, CResultsHandle, CExitHandle, CTimeoutHandle, CMapLoadedHandle, CPickupHandle, CNarrationHandle, CMoveCompleteHandle};
+use crate::utils::ResourceSet;
+use crate::utils::StateListener;
+use crate::save::SaveSlots;
+use crate::world;
+use crate::world::UnitType;
+use crate::world::World;
+use crate::tween::CTweenHandle;
+use crate::schedule::CScheduleHandle;
+use crate::event::RawEvent;
+use super::DfuncType;
+use super::error;
+
+/// Opaque handle to an engine object. The null handle is `0`.
+pub type Handle = u64;
+
+enum Object {
+	ResourceSet(ResourceSet),
+	World(Box<World>),
+	UnitType(UnitType),
+	StateListener(StateListener),
+	Runtime(Box<Runtime>)
+}
+
+impl Object {
+	fn kind(&self) -> &'static str {
+		match self {
+			Object::ResourceSet(_) => "ResourceSet",
+			Object::World(_) => "World",
+			Object::UnitType(_) => "UnitType",
+			Object::StateListener(_) => "StateListener",
+			Object::Runtime(_) => "Runtime"
+		}
+	}
+}
+
+struct Slot {
+	gen: u32,
+	obj: Option<Object>
+}
+
+/// Registry of all live objects. Slots are recycled, and each recycling bumps the generation of the slot.
+struct HandleTable {
+	slots: Vec<Slot>,
+	free: Vec<usize>
+}
+
+thread_local! {
+	static TABLE: RefCell<HandleTable> = const { RefCell::new(HandleTable { slots: Vec::new(), free: Vec::new() }) };
+}
+
+impl HandleTable {
+	fn insert(&mut self, obj: Object) -> Handle {
+		let idx = match self.free.pop() {
+			Some(i) => {
+				self.slots[i].obj = Some(obj);
+				i
+			},
+			None => {
+				self.slots.push(Slot { gen: 1, obj: Some(obj) });
+				self.slots.len() - 1
+			}
+		};
+		((self.slots[idx].gen as u64) << 32) | (idx as u64 + 1)
+	}
+
+	fn slot(&mut self, h: Handle) -> Option<&mut Slot> {
+		let idx = (h & 0xffff_ffff) as usize;
+		let gen = (h >> 32) as u32;
+		if idx == 0 {
+			return None;
+		}
+		match self.slots.get_mut(idx - 1) {
+			Some(s) if s.gen == gen && s.obj.is_some() => Some(s),
+			_ => None
+		}
+	}
+
+	fn get(&mut self, h: Handle) -> Option<&mut Object> {
+		self.slot(h).and_then(|s| s.obj.as_mut())
+	}
+
+	fn take(&mut self, h: Handle) -> Option<Object> {
+		let idx = (h & 0xffff_ffff) as usize;
+		let s = self.slot(h)?;
+		let obj = s.obj.take();
+		s.gen = s.gen.wrapping_add(1).max(1);
+		self.free.push(idx - 1);
+		obj
+	}
+
+	/// Take the object out without invalidating the handle for good; the handle is invalid until [HandleTable::restore] is called.
+	fn lend(&mut self, h: Handle) -> Option<Object> {
+		self.slot(h).and_then(|s| s.obj.take())
+	}
+
+	fn restore(&mut self, h: Handle, obj: Object) {
+		let idx = (h & 0xffff_ffff) as usize;
+		self.slots[idx - 1].obj = Some(obj);
+	}
+}
+
+fn register(obj: Object) -> Handle {
+	TABLE.with(|t| t.borrow_mut().insert(obj))
+}
+
+macro_rules! with_handle {
+	($h:expr, $variant:ident, |$v:ident| $body:expr) => {
+		TABLE.with(|t| {
+			let mut t = t.borrow_mut();
+			match t.get($h) {
+				Some(Object::$variant($v)) => Some($body),
+				Some(o) => {
+					error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Handle {:#x} refers to a {}, expected {}", $h, o.kind(), stringify!($variant)));
+					None
+				},
+				None => {
+					error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale {} handle {:#x}", stringify!($variant), $h));
+					None
+				}
+			}
+		})
+	};
+}
+
+macro_rules! take_handle {
+	($t:expr, $h:expr, $variant:ident) => {
+		match $t.get($h) {
+			Some(Object::$variant(_)) => match $t.take($h) {
+				Some(Object::$variant(v)) => Some(v),
+				_ => None
+			},
+			_ => {
+				error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale {} handle {:#x}", stringify!($variant), $h));
+				None
+			}
+		}
+	};
+}
+
+#[cfg(feature = "dotnet")]
+pub(super) fn register_listener(sl: StateListener) -> Handle {
+	register(Object::StateListener(sl))
+}
+
+#[cfg(feature = "dotnet")]
+/// Apply `f` to the World referred to by the handle; None (after reporting an error) if the handle is invalid.
+pub(super) fn with_world<R>(w: Handle, f: impl FnOnce(&mut World) -> R) -> Option<R> {
+	with_handle!(w, World, |w| f(w))
+}
+
+#[cfg(feature = "dotnet")]
+/// Apply `f` to the StateListener referred to by the handle; None (after reporting an error) if the handle is invalid.
+pub(super) fn with_listener<R>(sl: Handle, f: impl FnOnce(&mut StateListener) -> R) -> Option<R> {
+	with_handle!(sl, StateListener, |sl| f(sl))
+}
+
+fn to_string(p: *const c_char, what: &str) -> Option<String> {
+	if p.is_null() {
+		error::report(error::ERR_NULL_POINTER, &format!("fatal [napi]: Pointer to {} is NULL", what));
+		return None;
+	}
+	match unsafe { CStr::from_ptr(p) }.to_str() {
+		Ok(s) => Some(s.to_owned()),
+		Err(_) => {
+			error::report(error::ERR_INVALID_STRING, &format!("fatal [napi]: {} is not UtfString", what));
+			None
+		}
+	}
+}
+
+#[no_mangle]
+/// Returns true if the handle refers to a live object.
+pub extern "C" fn alshIsValid(h: Handle) -> bool {
+	TABLE.with(|t| t.borrow_mut().get(h).is_some())
+}
+
+#[no_mangle]
+/// Release the object referred to by the handle. Returns false if the handle is invalid or stale.
+pub extern "C" fn alshRelease(h: Handle) -> bool {
+	// Drop outside the borrow, since destructors of engine objects may be arbitrarily complex.
+	let obj = TABLE.with(|t| t.borrow_mut().take(h));
+	if obj.is_none() {
+		error::report(error::ERR_INVALID_HANDLE, &format!("warning [napi]: Attempt to release invalid or stale handle {:#x}", h));
+	}
+	obj.is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsNewResourceSet`.
+pub extern "C" fn alshNewResourceSet() -> Handle {
+	register(Object::ResourceSet(ResourceSet::new()))
+}
+
+#[no_mangle]
+/// Handle variant of `alsBlank_World`.
+pub extern "C" fn alshBlank_World() -> Handle {
+	register(Object::World(Box::new(World::blank())))
+}
+
+#[no_mangle]
+/// Handle variant of `alsBlankHex_World`.
+pub extern "C" fn alshBlankHex_World(ox: i32, oy: i32, tx: i32, ty: i32) -> Handle {
+	register(Object::World(Box::new(World::blank_hex(ox, oy, tx, ty))))
+}
+
+#[no_mangle]
+/// Handle variant of `alsNewUnitType`. Returns the null handle if `name` is NULL or not a valid Utf8 String.
+pub extern "C" fn alshNewUnitType(tid: u8, name: *const c_char, health: f32, mov_rate: f32, movt: u8, ran: u8, adur: f32) -> Handle {
+	match to_string(name, "UnitType display name String") {
+		Some(p) => register(Object::UnitType(UnitType::new(tid, p, health, mov_rate, movt, ran, adur))),
+		None => 0
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnDeriveUnitType`. Returns the null handle on failure.
+pub extern "C" fn alshDeriveUnitType(w: Handle, base: u8, name: *const c_char) -> Handle {
+	let p = match to_string(name, "UnitType display name String") { Some(p) => p, None => return 0 };
+	match with_handle!(w, World, |w| w.unit_types.get(&base).map(|b| b.variant(p))) {
+		Some(Some(u)) => register(Object::UnitType(u)),
+		Some(None) => {
+			error::report(error::ERR_INVALID_ID, &format!("warning [napi]: Invalid unit type id={}", base));
+			0
+		},
+		None => 0
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsNewStateListener`.
+pub extern "C" fn alshNewStateListener() -> Handle {
+	register(Object::StateListener(StateListener::_new_raw()))
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapTexture`.
+pub extern "C" fn alshMapTexture(rs: Handle, id: u8, path: *const c_char) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	with_handle!(rs, ResourceSet, |r| r.map_texture(id, &p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetNinePatch`.
+pub extern "C" fn alshSetNinePatch(rs: Handle, id: u8, left: i32, top: i32, right: i32, bottom: i32) -> bool {
+	with_handle!(rs, ResourceSet, |r| r.set_nine_patch(id, left, top, right, bottom)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetUiAnim`.
+pub extern "C" fn alshSetUiAnim(rs: Handle, id: u8, fw: u32, fh: u32, frn: u8, fr: f32) -> bool {
+	with_handle!(rs, ResourceSet, |r| r.set_ui_anim(id, (fw, fh), frn, fr)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapTextureEx`.
+pub extern "C" fn alshMapTextureEx(rs: Handle, id: u8, path: *const c_char, filter: u8, mipmaps: bool) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	let f = match crate::napi::tex_filter_arg(filter) { Some(f) => f, None => return false };
+	with_handle!(rs, ResourceSet, |r| r.map_texture_ex(id, &p, f, mipmaps)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapTextureRegion`.
+pub extern "C" fn alshMapTextureRegion(rs: Handle, tid: u8, parent_id: u8, x: f32, y: f32, w: f32, h: f32) -> bool {
+	with_handle!(rs, ResourceSet, |r| r.map_texture_region(tid, parent_id, x, y, w, h)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapFont`.
+pub extern "C" fn alshMapFont(rs: Handle, id: u8, path: *const c_char) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	with_handle!(rs, ResourceSet, |r| r.map_font(id, &p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapFontAtlas`.
+pub extern "C" fn alshMapFontAtlas(rs: Handle, id: u8, path: *const c_char, sizes: *const i32, nsizes: usize, ranges: *const u32, nranges: usize) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	if sizes.is_null() || (nranges != 0 && ranges.is_null()) {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to font size or codepoint range array is NULL");
+		return false;
+	}
+	let (sz, rg) = (super::read_slice(sizes, nsizes), super::read_ranges(ranges, nranges));
+	with_handle!(rs, ResourceSet, |r| r.map_font_atlas(id, &p, &sz, &rg)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapSound`.
+pub extern "C" fn alshMapSound(rs: Handle, id: u8, path: *const c_char) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	with_handle!(rs, ResourceSet, |r| r.map_sound(id, &p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapMusic`.
+pub extern "C" fn alshMapMusic(rs: Handle, id: u8, path: *const c_char) -> bool {
+	let p = match to_string(path, "ResourceSet Path String") { Some(p) => p, None => return false };
+	with_handle!(rs, ResourceSet, |r| r.map_music(id, &p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetMemoryBudget`.
+pub extern "C" fn alshSetMemoryBudget(rs: Handle, bytes: u64) -> bool {
+	with_handle!(rs, ResourceSet, |r| r.set_memory_budget(bytes as usize)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsDefAnim`.
+pub extern "C" fn alshDefAnim(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) -> bool {
+	with_handle!(u, UnitType, |ut| ut.def_anim_muted((fw, fh), frn, (cfx, cfy), fr, flip)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsDefIdleAnim`.
+pub extern "C" fn alshDefIdleAnim(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) -> bool {
+	with_handle!(u, UnitType, |ut| ut.def_idle_anim((fw, fh), frn, (cfx, cfy), fr, flip)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsDefStandAnim`.
+pub extern "C" fn alshDefStandAnim(u: Handle, facing: u8, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) -> bool {
+	let f = match world::u8_as_facing(facing) {
+		Some(f) => f,
+		None => {
+			error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid facing {}", facing));
+			return false;
+		}
+	};
+	with_handle!(u, UnitType, |ut| ut.def_stand_anim(f, (fw, fh), frn, (cfx, cfy), fr, flip)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetIdleDelay`.
+pub extern "C" fn alshSetIdleDelay(u: Handle, secs: f32) -> bool {
+	with_handle!(u, UnitType, |ut| ut.set_idle_delay(secs)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsDefAnimUnmuted`.
+pub extern "C" fn alshDefAnimUnmuted(u: Handle, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool, snd: u8, lp: bool) -> bool {
+	with_handle!(u, UnitType, |ut| ut.def_anim((fw, fh), frn, (cfx, cfy), fr, flip, snd, lp)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetTeamColors`.
+pub extern "C" fn alshSetTeamColors(rs: Handle, colors: *const i32, n: usize) -> bool {
+	if colors.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to colour array is NULL");
+		return false;
+	}
+	let c = super::read_colors(colors, n);
+	with_handle!(rs, ResourceSet, |r| r.set_team_colors(&c)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsMapPalette`.
+pub extern "C" fn alshMapPalette(rs: Handle, id: u8, colors: *const i32, n: usize) -> bool {
+	if colors.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to colour array is NULL");
+		return false;
+	}
+	let c = super::read_colors(colors, n);
+	with_handle!(rs, ResourceSet, |r| r.map_palette(id, &c)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetUnitInfo`.
+pub extern "C" fn alshSetUnitInfo(u: Handle, s: *const c_char) -> bool {
+	let p = match to_string(s, "information string") { Some(p) => p, None => return false };
+	with_handle!(u, UnitType, |ut| ut.set_info(p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetPortrait`.
+pub extern "C" fn alshSetPortrait(u: Handle, tex: i32) -> bool {
+	let p = match super::opt_tex_arg(tex, "portrait") { Some(p) => p, None => return false };
+	with_handle!(u, UnitType, |ut| ut.set_portrait(p)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetActions`.
+pub extern "C" fn alshSetActions(u: Handle, attacks: i32, move_after_attack: bool) -> bool {
+	let a = match super::actions_arg(attacks, move_after_attack) { Some(a) => a, None => return false };
+	with_handle!(u, UnitType, |ut| ut.set_actions(a)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetVoice`.
+pub extern "C" fn alshSetVoice(u: Handle, kind: u8, sounds: *const u8, n: usize, cooldown: f32) -> bool {
+	if n != 0 && sounds.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to sound ids is NULL");
+		return false;
+	}
+	let k = match super::bark_arg(kind) { Some(k) => k, None => return false };
+	let s = super::read_slice(sounds, n);
+	with_handle!(u, UnitType, |ut| ut.set_voice(k, s, cooldown)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetSight`.
+pub extern "C" fn alshSetSight(u: Handle, sight: i32) -> bool {
+	let s = match super::sight_arg(sight) { Some(s) => s, None => return false };
+	with_handle!(u, UnitType, |ut| ut.set_sight(s)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetUnitPortrait`.
+pub extern "C" fn alshSetUnitPortrait(w: Handle, uid: u8, tex: i32) -> bool {
+	with_handle!(w, World, |w| super::set_unit_portrait(w, uid, tex)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsSetCombatStats`.
+pub extern "C" fn alshSetCombatStats(u: Handle, attack: f32, defense: f32) -> bool {
+	with_handle!(u, UnitType, |ut| ut.set_combat_stats(attack, defense)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsRegisterUnitType`.
+/// The unit type handle is consumed (and hence stale) if registration succeeds.
+pub extern "C" fn alshRegisterUnitType(w: Handle, u: Handle, id: u8) -> bool {
+	TABLE.with(|t| {
+		let mut t = t.borrow_mut();
+		if !matches!(t.get(w), Some(Object::World(_))) {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale World handle {:#x}", w));
+			return false;
+		}
+		let ut = match take_handle!(t, u, UnitType) { Some(ut) => ut, None => return false };
+		if let Some(Object::World(w)) = t.get(w) {
+			world::register_unit_type(w, ut, id);
+		}
+		true
+	})
+}
+
+#[no_mangle]
+/// Handle variant of `alsCreateStatic`.
+pub extern "C" fn alshCreateStatic(w: Handle, tex_id: u8, cx: i32, cy: i32) -> bool {
+	with_handle!(w, World, |w| world::create_static(w, tex_id, (cx, cy))).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsSpawnUnit`. The id of the spawned unit is written to `out_uid` (if not NULL).
+/// Returns false if the world handle is invalid, or if no unit type with id `tid` has been registered.
+pub extern "C" fn alshSpawnUnit(w: Handle, tid: u8, tx: i32, ty: i32, tint: i32, plr: bool, out_uid: *mut u8) -> bool {
+	let r = with_handle!(w, World, |w| {
+		if w.unit_types.contains_key(&tid) {
+			Some(world::spawn_unit(w, tid, (tx, ty), tint, plr))
+		} else {
+			error::report(error::ERR_INVALID_ID, &format!("fatal [napi]: Invalid unit type id={}", tid));
+			None
+		}
+	});
+	match r.flatten() {
+		Some(uid) => {
+			if !out_uid.is_null() {
+				unsafe { *out_uid = uid };
+			}
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetUnitHold`.
+pub extern "C" fn alshSetUnitHold(w: Handle, uid: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::unit_behavior(w, uid, |w| world::set_behavior(w, uid, Some(world::Behavior::Hold)))).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetUnitGuard`.
+pub extern "C" fn alshSetUnitGuard(w: Handle, uid: u8, x: i32, y: i32, radius: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::unit_behavior(w, uid, |w| world::set_behavior(w, uid, Some(world::Behavior::Guard { tile: (x, y), radius: radius })))).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnAddUnitPatrolPoint`.
+pub extern "C" fn alshAddUnitPatrolPoint(w: Handle, uid: u8, x: i32, y: i32) -> bool {
+	with_handle!(w, World, |w| crate::napi::unit_behavior(w, uid, |w| world::add_patrol_point(w, uid, (x, y)))).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearUnitBehavior`.
+pub extern "C" fn alshClearUnitBehavior(w: Handle, uid: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::unit_behavior(w, uid, |w| world::set_behavior(w, uid, None))).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadAi`.
+pub extern "C" fn alshLoadAi(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Behavior tree file path string") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| crate::napi::load_ai(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadPuzzle`.
+pub extern "C" fn alshLoadPuzzle(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Puzzle file path string") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| crate::napi::load_puzzle(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearPuzzle`.
+pub extern "C" fn alshClearPuzzle(w: Handle) -> bool {
+	with_handle!(w, World, |w| w.set_puzzle(None)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadTutorial`.
+pub extern "C" fn alshLoadTutorial(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Tutorial file path string") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| crate::napi::load_tutorial(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearTutorial`.
+pub extern "C" fn alshClearTutorial(w: Handle) -> bool {
+	with_handle!(w, World, |w| w.set_tutorial(None)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnPanCamera`.
+pub extern "C" fn alshPanCamera(w: Handle, x: f32, y: f32, duration: f32, easing: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::pan_camera(w, x, y, duration, easing)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnPanToTile`.
+pub extern "C" fn alshPanToTile(w: Handle, x: i32, y: i32, duration: f32) -> bool {
+	with_handle!(w, World, |w| w.pan_to_tile(x, y, duration)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetCameraFollow`.
+pub extern "C" fn alshSetCameraFollow(w: Handle, uid: u8, follow: bool) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_camera_follow(w, uid, follow)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetCameraFollowRate`.
+pub extern "C" fn alshSetCameraFollowRate(w: Handle, rate: f32) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_camera_follow_rate(w, rate)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetCameraAutoFollow`.
+pub extern "C" fn alshSetCameraAutoFollow(w: Handle, on: bool) -> bool {
+	with_handle!(w, World, |w| w.set_camera_auto_follow(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetZoom`.
+pub extern "C" fn alshSetZoom(w: Handle, zoom: f32) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_zoom(w, zoom)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetZoom`. Returns 0 on invalid handle.
+pub extern "C" fn alshGetZoom(w: Handle) -> f32 {
+	with_handle!(w, World, |w| w.zoom()).unwrap_or(0.0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetInputScheme`. Returns 0 on invalid handle.
+pub extern "C" fn alshGetInputScheme(w: Handle) -> u8 {
+	with_handle!(w, World, |w| w.input_scheme() as u8).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetInputScheme`.
+pub extern "C" fn alshSetInputScheme(w: Handle, scheme: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_input_scheme(w, scheme)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnAddTween`.
+pub extern "C" fn alshAddTween(w: Handle, from: f32, to: f32, duration: f32, easing: u8, f: CTweenHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	with_handle!(w, World, |w| crate::napi::add_tween(w, from, to, duration, easing, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetTweenValue`.
+pub extern "C" fn alshGetTweenValue(w: Handle, id: u32, out: *mut f32) -> bool {
+	if out.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to tween value is NULL");
+		return false;
+	}
+	match with_handle!(w, World, |w| w.tween_value(id)) {
+		Some(Some(v)) => {
+			unsafe { *out = v };
+			true
+		},
+		_ => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnCancelTween`.
+pub extern "C" fn alshCancelTween(w: Handle, id: u32) -> bool {
+	with_handle!(w, World, |w| w.cancel_tween(id)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnScheduleAfter`.
+pub extern "C" fn alshScheduleAfter(w: Handle, secs: f32, f: CScheduleHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	with_handle!(w, World, |w| crate::napi::schedule(w, secs, false, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnScheduleEvery`.
+pub extern "C" fn alshScheduleEvery(w: Handle, secs: f32, f: CScheduleHandle, ctx: *mut c_void) -> u32 {
+	let f = f.map(|f| move |w: &mut World, id| f(w, id, ctx));
+	with_handle!(w, World, |w| crate::napi::schedule(w, secs, true, f)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnCancelScheduled`.
+pub extern "C" fn alshCancelScheduled(w: Handle, id: u32) -> bool {
+	with_handle!(w, World, |w| w.cancel_scheduled(id)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnEnableEventQueue`.
+pub extern "C" fn alshEnableEventQueue(w: Handle, enable: bool) -> bool {
+	with_handle!(w, World, |w| w.enable_event_queue(enable)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnPollEvent`.
+pub extern "C" fn alshPollEvent(w: Handle, out: *mut RawEvent) -> bool {
+	if out.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to event is NULL");
+		return false;
+	}
+	match with_handle!(w, World, |w| w.poll_event()) {
+		Some(Some(e)) => {
+			unsafe { *out = e.to_raw() };
+			true
+		},
+		_ => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnPollEventJson`.
+pub extern "C" fn alshPollEventJson(w: Handle) -> *mut c_char {
+	match with_handle!(w, World, |w| w.poll_event()) {
+		Some(Some(e)) => crate::napi::to_owned_cstr(&e.to_string()),
+		_ => std::ptr::null_mut()
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnStateHash`.
+pub extern "C" fn alshStateHash(w: Handle) -> u64 {
+	with_handle!(w, World, |w| crate::world::state_hash(w)).unwrap_or(0)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnStateDiff`.
+pub extern "C" fn alshStateDiff(a: Handle, b: Handle) -> *mut c_char {
+	match state_diff(a, b) {
+		Some(s) => crate::napi::to_owned_cstr(&s),
+		None => std::ptr::null_mut()
+	}
+}
+
+/// Returns the differences between the worlds with the specified handles, one per line, or None if either handle is invalid.
+pub(super) fn state_diff(a: Handle, b: Handle) -> Option<String> {
+	if a == b {
+		return with_handle!(a, World, |_w| String::new());
+	}
+	// The first world is lent out of the table, so that both worlds may be borrowed at once.
+	let wa = match TABLE.with(|t| t.borrow_mut().lend(a)) {
+		Some(Object::World(w)) => w,
+		Some(o) => {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Handle {:#x} refers to a {}, expected World", a, o.kind()));
+			TABLE.with(|t| t.borrow_mut().restore(a, o));
+			return None;
+		},
+		None => {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale World handle {:#x}", a));
+			return None;
+		}
+	};
+	let d = with_handle!(b, World, |wb| crate::napi::diff_text(&wa, wb));
+	TABLE.with(|t| t.borrow_mut().restore(a, Object::World(wa)));
+	d
+}
+
+#[no_mangle]
+/// Handle variant of `alsnPlacePickup`.
+pub extern "C" fn alshPlacePickup(w: Handle, tex: u8, x: i32, y: i32, kind: u8, value: f32) -> bool {
+	with_handle!(w, World, |w| crate::napi::place_pickup(w, tex, x, y, kind, value)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnRemovePickup`.
+pub extern "C" fn alshRemovePickup(w: Handle, x: i32, y: i32) -> bool {
+	with_handle!(w, World, |w| crate::world::pickup::remove(w, (x, y)).is_some()).unwrap_or(false)
+}
+
+#[cfg(feature = "debug_tools")]
+#[no_mangle]
+/// Handle variant of `alsnSetCheat`.
+pub extern "C" fn alshSetCheat(w: Handle, cheat: u8, on: bool) -> bool {
+	with_handle!(w, World, |w| super::set_cheat(w, cheat, on)).unwrap_or(false)
+}
+
+#[cfg(feature = "debug_tools")]
+#[no_mangle]
+/// Handle variant of `alsnForceOutcome`.
+pub extern "C" fn alshForceOutcome(w: Handle, victory: bool) -> bool {
+	with_handle!(w, World, |w| super::force_outcome(w, victory)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetUnitTypeAi`.
+pub extern "C" fn alshSetUnitTypeAi(w: Handle, type_id: u8, path: *const c_char, root: *const c_char) -> bool {
+	let p = match to_string(path, "Behavior tree file path string") { Some(p) => p, None => return false };
+	let r = match to_string(root, "Behavior tree root string") { Some(r) => r, None => return false };
+	with_handle!(w, World, |w| crate::napi::set_type_ai(w, type_id, &p, &r)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearUnitTypeAi`.
+pub extern "C" fn alshClearUnitTypeAi(w: Handle, type_id: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::clear_type_ai(w, type_id)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetInfluence`.
+pub extern "C" fn alshGetInfluence(w: Handle, x: i32, y: i32, player: bool, out_threat: *mut f32, out_support: *mut f32) -> bool {
+	if out_threat.is_null() || out_support.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to threat or support is NULL");
+		return false;
+	}
+	match with_handle!(w, World, |w| world::influence::compute(w, player)) {
+		Some(m) => {
+			unsafe {
+				*out_threat = m.threat((x, y));
+				*out_support = m.support((x, y));
+			}
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetUnitHp`.
+pub extern "C" fn alshGetUnitHp(w: Handle, uid: u8, out_hp: *mut i32, out_max: *mut i32) -> bool {
+	with_handle!(w, World, |w| crate::napi::unit_hp(w, uid, out_hp, out_max)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetAiController`.
+pub extern "C" fn alshSetAiController(w: Handle, enabled: bool, aggressiveness: f32) -> bool {
+	with_handle!(w, World, |w| super::set_ai_controller(w, enabled, aggressiveness)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetFogOfWar`.
+pub extern "C" fn alshSetFogOfWar(w: Handle, enabled: bool) -> bool {
+	with_handle!(w, World, |w| w.set_fog_of_war(enabled)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnIsTileVisible`.
+pub extern "C" fn alshIsTileVisible(w: Handle, x: i32, y: i32) -> bool {
+	with_handle!(w, World, |w| world::is_tile_visible(w, x, y)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetGrid`. Returns -1 on invalid handle.
+pub extern "C" fn alshGetGrid(w: Handle) -> i32 {
+	with_handle!(w, World, |w| w.grid() as i32).unwrap_or(-1)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGridDistance`. Returns -1 on invalid handle.
+pub extern "C" fn alshGridDistance(w: Handle, x1: i32, y1: i32, x2: i32, y2: i32) -> i32 {
+	with_handle!(w, World, |w| w.grid().distance((x1, y1), (x2, y2))).unwrap_or(-1)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetDefaultActions`.
+pub extern "C" fn alshSetDefaultActions(w: Handle, attacks: u8, move_after_attack: bool) -> bool {
+	with_handle!(w, World, |w| w.set_actions(world::Actions { attacks: attacks, move_after_attack: move_after_attack })).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetHpRounding`.
+pub extern "C" fn alshSetHpRounding(w: Handle, mode: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_hp_rounding(w, mode)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetDifficulty`.
+pub extern "C" fn alshSetDifficulty(w: Handle, preset: u8) -> bool {
+	with_handle!(w, World, |w| crate::napi::set_difficulty_preset(w, preset)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetCustomDifficulty`.
+pub extern "C" fn alshSetCustomDifficulty(w: Handle, d: *const world::Difficulty) -> bool {
+	if d.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to Difficulty is NULL");
+		return false;
+	}
+	let d = unsafe { *d };
+	with_handle!(w, World, |w| w.set_difficulty(d)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetDifficulty`.
+pub extern "C" fn alshGetDifficulty(w: Handle, out: *mut world::Difficulty) -> bool {
+	if out.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to Difficulty is NULL");
+		return false;
+	}
+	match with_handle!(w, World, |w| w.difficulty()) {
+		Some(d) => {
+			unsafe { *out = d };
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetElevation`.
+pub extern "C" fn alshSetElevation(w: Handle, heights: *const u8, n: usize) -> bool {
+	if n != 0 && heights.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to heights is NULL");
+		return false;
+	}
+	let h = super::read_slice(heights, n);
+	with_handle!(w, World, |w| super::set_elevation(w, h)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetElevationRules`.
+pub extern "C" fn alshSetElevationRules(w: Handle, r: *const world::ElevationRules) -> bool {
+	if r.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to ElevationRules is NULL");
+		return false;
+	}
+	let r = unsafe { *r };
+	with_handle!(w, World, |w| w.set_elevation_rules(r)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsLoadMap`.
+pub extern "C" fn alshLoadMap(w: Handle, fpath: *const c_char) -> bool {
+	let p = match to_string(fpath, "Level file path string") { Some(p) => p, None => return false };
+	let r = with_handle!(w, World, |w| world::load_world(w, &p));
+	if r == Some(false) {
+		error::report(error::ERR_OPERATION_FAILED, &format!("fatal [napi]: Failed to load level file {}", p));
+	}
+	r.unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindDamageFunc`.
+pub extern "C" fn alshBindDamageFunc(w: Handle, f: DfuncType) -> bool {
+	with_handle!(w, World, |w| w.dmg_func = world::DamageFunc::CHandle(f)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnAddTileLayer`.
+pub extern "C" fn alshAddTileLayer(w: Handle, kind: u8, tiles: *const u8, n: usize) -> i32 {
+	if tiles.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to tile array is NULL");
+		return -1;
+	}
+	let t = super::read_slice(tiles, n);
+	with_handle!(w, World, |w| super::add_tile_layer(w, kind, t)).unwrap_or(-1)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearTileLayers`.
+pub extern "C" fn alshClearTileLayers(w: Handle) -> bool {
+	with_handle!(w, World, |w| w.clear_tile_layers()).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnAddObjective`, for objectives known before the game starts.
+pub extern "C" fn alshAddObjective(w: Handle, text: *const c_char) -> i32 {
+	let t = match to_string(text, "Objective text") { Some(t) => t, None => return -1 };
+	with_handle!(w, World, |w| w.add_objective(&t) as i32).unwrap_or(-1)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnAddIntroSlide`.
+pub extern "C" fn alshAddIntroSlide(w: Handle, tex: u8, caption: *const c_char, duration: f32) -> bool {
+	let c = match to_string(caption, "Slide caption") { Some(c) => c, None => return false };
+	with_handle!(w, World, |w| w.add_intro_slide(tex, &c, duration)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnClearIntroSlides`.
+pub extern "C" fn alshClearIntroSlides(w: Handle) -> bool {
+	with_handle!(w, World, |w| w.set_intro_slides(Vec::new())).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnShowScrollingText`.
+pub extern "C" fn alshShowScrollingText(w: Handle, text: *const c_char, speed: f32) -> bool {
+	let t = match to_string(text, "Scrolling text") { Some(t) => t, None => return false };
+	with_handle!(w, World, |w| w.show_scrolling_text(&t, speed)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnShowPopup`.
+pub extern "C" fn alshShowPopup(w: Handle, uid: u8, text: *const c_char, kind: u8) -> bool {
+	let t = match to_string(text, "Popup text") { Some(t) => t, None => return false };
+	with_handle!(w, World, |w| super::show_popup(w, uid, &t, kind)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSaveMap`.
+pub extern "C" fn alshSaveMap(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Map Path String") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| super::save_map(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadMapRon`.
+pub extern "C" fn alshLoadMapRon(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Map Path String") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| super::load_map_ron(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSaveMapRon`.
+pub extern "C" fn alshSaveMapRon(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Map Path String") { Some(p) => p, None => return false };
+	with_handle!(w, World, |w| super::save_map_ron(w, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadBattle`.
+pub extern "C" fn alshLoadBattle(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Save Path String") { Some(p) => p, None => return false };
+	let r = with_handle!(w, World, |w| w.load_battle(&p));
+	if r == Some(false) {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load battle from {}", p));
+	}
+	r.unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnOpenProfile`.
+pub extern "C" fn alshOpenProfile(w: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Profile Path String") { Some(p) => p, None => return false };
+	let r = with_handle!(w, World, |w| w.open_profile(&p));
+	if r == Some(false) {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to open profile {}", p));
+	}
+	r.unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindInit`.
+pub extern "C" fn alshBindInit(sl: Handle, f: CInitHandle) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawinit(f)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindTurn`.
+pub extern "C" fn alshBindTurn(sl: Handle, f: CTurnHandle) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawturn(f)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindInitCtx`.
+pub extern "C" fn alshBindInitCtx(sl: Handle, f: CInitCtxHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawinit_ctx(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindTurnCtx`.
+pub extern "C" fn alshBindTurnCtx(sl: Handle, f: CTurnCtxHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawturn_ctx(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindUpdate`.
+pub extern "C" fn alshBindUpdate(sl: Handle, f: CUpdateHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawupdate(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindUnitDeath`.
+pub extern "C" fn alshBindUnitDeath(sl: Handle, f: CUnitDeathHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawunit_death(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindAttack`.
+pub extern "C" fn alshBindAttack(sl: Handle, f: CAttackHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawattack(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindMoveComplete`.
+pub extern "C" fn alshBindMoveComplete(sl: Handle, f: CMoveCompleteHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawmove_complete(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindTransitionDone`.
+pub extern "C" fn alshBindTransitionDone(sl: Handle, f: CPhaseHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawtransition_done(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindBannerDone`.
+pub extern "C" fn alshBindBannerDone(sl: Handle, f: CPhaseHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawbanner_done(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindResults`.
+pub extern "C" fn alshBindResults(sl: Handle, f: CResultsHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawresults(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindPhaseChange`.
+pub extern "C" fn alshBindPhaseChange(sl: Handle, f: CPhaseHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawphase_change(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindDraw`.
+pub extern "C" fn alshBindDraw(sl: Handle, f: CDrawHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawdraw(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindVictoryCheck`.
+pub extern "C" fn alshBindVictoryCheck(sl: Handle, f: CVictoryHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawvictory_check(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindExit`.
+pub extern "C" fn alshBindExit(sl: Handle, f: CExitHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawexit(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindTimeout`.
+pub extern "C" fn alshBindTimeout(sl: Handle, f: CTimeoutHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawtimeout(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindMapLoaded`.
+pub extern "C" fn alshBindMapLoaded(sl: Handle, f: CMapLoadedHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawmap_loaded(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindPickup`.
+pub extern "C" fn alshBindPickup(sl: Handle, f: CPickupHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawpickup(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBindNarration`.
+pub extern "C" fn alshBindNarration(sl: Handle, f: CNarrationHandle, ctx: *mut c_void) -> bool {
+	with_handle!(sl, StateListener, |sl| sl._bind_rawnarration(f, ctx)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsBegin_Display`. Creates the display and begins the game.
+/// The ResourceSet, World and StateListener handles are consumed (and released when this function returns).
+/// Returns false without consuming any handle if one of them is invalid.
+pub extern "C" fn alshBegin_Display(sw: i32, sh: i32, vsync: bool, fps: u32, t: *const c_char, rs: Handle, w: Handle, sl: Handle, mvl: f32) -> bool {
+	let title = match to_string(t, "Display Title String") { Some(p) => p, None => return false };
+	match take_for_display(rs, w, sl, "alshBegin_Display") {
+		Some((rs, w, sl)) => {
+			let d = Display::new(sw, sh, fps, vsync, &title, Color::BLACK, mvl);
+			d.begin(rs, w, sl);
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsInit_Display`. Returns a Runtime handle, or the null handle on error, in which case no handle is consumed.
+pub extern "C" fn alshInit_Display(sw: i32, sh: i32, vsync: bool, fps: u32, t: *const c_char, rs: Handle, w: Handle, sl: Handle, mvl: f32) -> Handle {
+	let title = match to_string(t, "Display Title String") { Some(p) => p, None => return 0 };
+	match take_for_display(rs, w, sl, "alshInit_Display") {
+		Some((rs, w, sl)) => {
+			let d = Display::new(sw, sh, fps, vsync, &title, Color::BLACK, mvl);
+			register(Object::Runtime(Box::new(d.init(rs, w, sl))))
+		},
+		None => 0
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsInit_DisplayFromSettings`. Returns a Runtime handle, or the null handle on error, in which case no handle is consumed.
+pub extern "C" fn alshInit_DisplayFromSettings(path: *const c_char, t: *const c_char, rs: Handle, w: Handle, sl: Handle) -> Handle {
+	let path = match to_string(path, "Settings Path String") { Some(p) => p, None => return 0 };
+	let title = match to_string(t, "Display Title String") { Some(p) => p, None => return 0 };
+	let s = match super::load_settings(&path) { Some(s) => s, None => return 0 };
+	match take_for_display(rs, w, sl, "alshInit_DisplayFromSettings") {
+		Some((rs, w, sl)) => register(Object::Runtime(Box::new(Display::from_settings(&s, &title).init(rs, w, sl)))),
+		None => 0
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsStep_Runtime`. Returns false if the handle is invalid, or once the window has been asked to close.
+pub extern "C" fn alshStep_Runtime(rt: Handle) -> bool {
+	// The runtime is lent out of the table for the duration of the frame, since callbacks may call back into this module.
+	let mut r = match TABLE.with(|t| t.borrow_mut().lend(rt)) {
+		Some(Object::Runtime(r)) => r,
+		Some(o) => {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Handle {:#x} refers to a {}, expected Runtime", rt, o.kind()));
+			TABLE.with(|t| t.borrow_mut().restore(rt, o));
+			return false;
+		},
+		None => {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale Runtime handle {:#x}", rt));
+			return false;
+		}
+	};
+	let b = r.step();
+	TABLE.with(|t| t.borrow_mut().restore(rt, Object::Runtime(r)));
+	b
+}
+
+#[no_mangle]
+/// Handle variant of `alsWorld_Runtime`. Returns NULL if the handle is invalid.
+/// The pointer is valid until the runtime is shut down.
+pub extern "C" fn alshWorld_Runtime(rt: Handle) -> *mut World {
+	with_handle!(rt, Runtime, |r| r.world_mut() as *mut World).unwrap_or(std::ptr::null_mut())
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetScreenshotDir`.
+pub extern "C" fn alshSetScreenshotDir(rt: Handle, dir: *const c_char) -> bool {
+	let d = match to_string(dir, "Screenshot Directory String") { Some(d) => d, None => return false };
+	with_handle!(rt, Runtime, |r| r.set_screenshot_dir(&d)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetConfirmExit`.
+pub extern "C" fn alshSetConfirmExit(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_confirm_exit(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnResourceReport`. Returns NULL if the handle is invalid.
+pub extern "C" fn alshResourceReport(rt: Handle) -> *mut c_char {
+	with_handle!(rt, Runtime, |r| crate::napi::to_owned_cstr(&r.resource_report().to_string())).unwrap_or(std::ptr::null_mut())
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetAiBudget`.
+pub extern "C" fn alshSetAiBudget(rt: Handle, ms: f32) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_ai_budget(ms)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetDangerZone`.
+pub extern "C" fn alshSetDangerZone(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_danger_zone(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetTurnTimer`.
+pub extern "C" fn alshSetTurnTimer(rt: Handle, per_turn: f32, bank: f32, increment: f32, auto_wait: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_turn_timer(per_turn, bank, increment, auto_wait)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetInputGuard`.
+pub extern "C" fn alshSetInputGuard(rt: Handle, debounce: f32, min_screen_time: f32) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_input_guard(debounce, min_screen_time)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetRumble`.
+pub extern "C" fn alshSetRumble(rt: Handle, hit_strength: f32, hit_duration: f32, select_strength: f32, select_duration: f32) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_rumble((hit_strength, hit_duration), (select_strength, select_duration))).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetTouchInput`.
+pub extern "C" fn alshSetTouchInput(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_touch_input(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnGetTurnTimer`.
+pub extern "C" fn alshGetTurnTimer(rt: Handle, turn_left: *mut f32, bank_left: *mut f32) -> bool {
+	if turn_left.is_null() || bank_left.is_null() {
+		error::report(error::ERR_NULL_POINTER, "fatal [napi]: Pointer to turn or bank time is NULL");
+		return false;
+	}
+	match with_handle!(rt, Runtime, |r| (r.turn_timer().turn_left(), r.turn_timer().bank_left())) {
+		Some((t, b)) => {
+			unsafe {
+				*turn_left = t.unwrap_or(-1.0);
+				*bank_left = b.unwrap_or(-1.0);
+			}
+			true
+		},
+		None => false
+	}
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetAiDebug`.
+pub extern "C" fn alshSetAiDebug(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_ai_debug(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetPauseUnfocused`.
+pub extern "C" fn alshSetPauseUnfocused(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_pause_unfocused(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetSimFps`.
+pub extern "C" fn alshSetSimFps(rt: Handle, fps: u32) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_sim_fps(fps)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetBackgroundFps`.
+pub extern "C" fn alshSetBackgroundFps(rt: Handle, fps: u32) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_background_fps(fps)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetHudHidden`.
+pub extern "C" fn alshSetHudHidden(rt: Handle, hidden: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_hud_hidden(hidden)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetCleanScreenshots`.
+pub extern "C" fn alshSetCleanScreenshots(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_clean_screenshots(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetControlHints`.
+pub extern "C" fn alshSetControlHints(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_control_hints(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetUiScale`.
+pub extern "C" fn alshSetUiScale(rt: Handle, scale: f32) -> bool {
+	if scale.is_nan() || scale <= 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid ui scale {}", scale));
+		return false;
+	}
+	with_handle!(rt, Runtime, |r| r.set_ui_scale(scale)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetTextScale`.
+pub extern "C" fn alshSetTextScale(rt: Handle, scale: f32) -> bool {
+	if scale.is_nan() || scale <= 0.0 {
+		error::report(error::ERR_INVALID_ARGUMENT, &format!("warning [napi]: Invalid text scale {}", scale));
+		return false;
+	}
+	with_handle!(rt, Runtime, |r| r.set_text_scale(scale)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetHighContrast`.
+pub extern "C" fn alshSetHighContrast(rt: Handle, on: bool) -> bool {
+	with_handle!(rt, Runtime, |r| r.set_high_contrast(on)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnApplySettings`.
+pub extern "C" fn alshApplySettings(rt: Handle, path: *const c_char) -> bool {
+	let s = match to_string(path, "Settings Path String").and_then(|p| super::load_settings(&p)) { Some(s) => s, None => return false };
+	with_handle!(rt, Runtime, |r| r.apply_settings(&s)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSaveAudioSettings`.
+pub extern "C" fn alshSaveAudioSettings(rt: Handle, path: *const c_char) -> bool {
+	let p = match to_string(path, "Settings Path String") { Some(p) => p, None => return false };
+	with_handle!(rt, Runtime, |r| super::save_audio_settings(r, &p)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetAutosave`.
+pub extern "C" fn alshSetAutosave(rt: Handle, path: *const c_char, every: u32, before_enemy: bool) -> bool {
+	let p = match to_string(path, "Autosave Path String") { Some(p) => p, None => return false };
+	with_handle!(rt, Runtime, |r| r.set_autosave(&p, every, before_enemy)).is_some()
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSaveSlot`.
+pub extern "C" fn alshSaveSlot(rt: Handle, dir: *const c_char, slot: u32) -> bool {
+	let d = match to_string(dir, "Save Directory String") { Some(d) => d, None => return false };
+	let r = with_handle!(rt, Runtime, |r| r.save_slot(&SaveSlots::new(&d), slot));
+	if r == Some(false) {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to save slot {} in {}", slot, d));
+	}
+	r.unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnLoadSlot`.
+pub extern "C" fn alshLoadSlot(rt: Handle, dir: *const c_char, slot: u32) -> bool {
+	let d = match to_string(dir, "Save Directory String") { Some(d) => d, None => return false };
+	let r = with_handle!(rt, Runtime, |r| r.load_slot(&SaveSlots::new(&d), slot));
+	if r == Some(false) {
+		error::report(error::ERR_OPERATION_FAILED, &format!("warning [napi]: Failed to load slot {} in {}", slot, d));
+	}
+	r.unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetBannerTheme`.
+pub extern "C" fn alshSetBannerTheme(rt: Handle, tex: i32, font: i32, bg: i32, fg: i32, time: f32) -> bool {
+	with_handle!(rt, Runtime, |r| match super::banner_theme(r.theme(), tex, font, bg, fg, time) {
+		Some(t) => {
+			r.set_theme(t);
+			true
+		},
+		None => false
+	}).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetPortraitSize`.
+pub extern "C" fn alshSetPortraitSize(rt: Handle, size: f32) -> bool {
+	with_handle!(rt, Runtime, |r| super::set_portrait_size(r, size)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetStatusIcon`.
+pub extern "C" fn alshSetStatusIcon(rt: Handle, status: u8, tex: i32) -> bool {
+	with_handle!(rt, Runtime, |r| super::set_status_icon(r, status, tex)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetPopupStyle`.
+pub extern "C" fn alshSetPopupStyle(rt: Handle, kind: u8, color: i32, font: i32) -> bool {
+	with_handle!(rt, Runtime, |r| super::set_popup_style(r, kind, color, font)).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsnSetTransition`.
+pub extern "C" fn alshSetTransition(rt: Handle, kind: u8, time: f32, color: i32) -> bool {
+	with_handle!(rt, Runtime, |r| match super::transition_theme(r.theme(), kind, time, color) {
+		Some(t) => {
+			r.set_theme(t);
+			true
+		},
+		None => false
+	}).unwrap_or(false)
+}
+
+#[no_mangle]
+/// Handle variant of `alsShutdown_Runtime`. Consumes the runtime handle, and returns a handle to its World.
+pub extern "C" fn alshShutdown_Runtime(rt: Handle) -> Handle {
+	match TABLE.with(|t| take_handle!(t.borrow_mut(), rt, Runtime)) {
+		Some(r) => register(Object::World(Box::new(r.shutdown()))),
+		None => 0
+	}
+}
+
+fn take_for_display(rs: Handle, w: Handle, sl: Handle, fname: &str) -> Option<(ResourceSet, World, StateListener)> {
+	TABLE.with(|tb| {
+		let mut tb = tb.borrow_mut();
+		let ok = matches!(tb.get(rs), Some(Object::ResourceSet(_)))
+			&& matches!(tb.get(w), Some(Object::World(_)))
+			&& matches!(tb.get(sl), Some(Object::StateListener(_)));
+		if !ok {
+			error::report(error::ERR_INVALID_HANDLE, &format!("fatal [napi]: Invalid or stale handle passed to {}", fname));
+			return None;
+		}
+		Some((take_handle!(tb, rs, ResourceSet)?, *take_handle!(tb, w, World)?, take_handle!(tb, sl, StateListener)?))
+	})
+}
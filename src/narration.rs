@@ -0,0 +1,68 @@
+//! Plain-text narration of game events and changes of focus, e.g, "Archer selected, 7 of 10 HP" or "Enemy phase begins", for games to pass on to the text-to-speech of their platform.
+//! Bind a function to [StateListener::bind_narration](crate::utils::StateListener::bind_narration) to receive each line as it happens; narration is only composed while one is bound.
+//!
+//! Battle events read as they do in the battle log, without markup. Enemy units hidden by fog of war are not narrated.
+//! # Example
+//! ```
+//! use alesia::utils::StateListener;
+//! use alesia::world::World;
+//! let mut w = World::blank();
+//! let mut sl = StateListener::new();
+//! sl.bind_narration(|_, text| println!("{}", text));
+//! sl.narrate(&mut w, "Welcome");
+//! ```
+
+use crate::event::EngineEvent;
+use crate::input::TurnPhase;
+use crate::world::BattleEvent;
+use crate::world::World;
+
+/// Describe an event in a line of narration, or None if it is not narrated, e.g, a hidden enemy unit moving.
+/// Changes of phase are narrated by the display, which knows the phase left, see [describe_phase].
+pub fn describe_event(w: &World, e: &EngineEvent) -> Option<String> {
+	let visible = |uid: u8| crate::world::is_unit_visible(w, uid);
+	let ev = match *e {
+		EngineEvent::UnitAttacked { attacker, defender, damage } if visible(attacker) || visible(defender) => BattleEvent::Attack(attacker, defender, damage),
+		EngineEvent::UnitDied { uid, type_id } => BattleEvent::Kill(uid, type_id),
+		EngineEvent::PickupCollected { uid, effect } if visible(uid) => BattleEvent::Pickup(uid, effect),
+		EngineEvent::UnitMoved { uid, tile } if visible(uid) => return Some(format!("{} moves to {}, {}", unit_name(w, uid)?, tile.0, tile.1)),
+		EngineEvent::TurnTimedOut(_) => return Some("Time is up".to_string()),
+		EngineEvent::TutorialStep(i) => return w.tutorial().and_then(|t| t.steps().get(i)).map(|s| crate::text::plain(&s.text)),
+		EngineEvent::MapLoaded(_) if !w.map_title().is_empty() => return Some(w.map_title().to_string()),
+		_ => return None
+	};
+	Some(crate::text::plain(&crate::world::describe_event(w, &ev)))
+}
+
+/// Describe the game entering phase `next` from phase `prev`, as the phase banner does, e.g, "Enemy phase begins"; None if the change is not narrated, e.g, a unit being selected.
+pub fn describe_phase(w: &World, prev: TurnPhase, next: TurnPhase) -> Option<String> {
+	match (prev, next) {
+		(TurnPhase::EnemyTurn, TurnPhase::PlayerTurn) | (TurnPhase::Intro, TurnPhase::PlayerTurn) => Some(format!("Player phase begins, turn {}", w.turn())),
+		(_, TurnPhase::EnemyTurn) => Some("Enemy phase begins".to_string()),
+		(_, TurnPhase::Victory) => Some("Victory".to_string()),
+		(_, TurnPhase::Defeat) => Some("Defeat".to_string()),
+		_ => None
+	}
+}
+
+/// Describe the unit with specified id, with its side and health rounded as the world [rounds](crate::world::HpRounding) it, e.g, "Enemy Archer, 7 of 10 HP". None if the unit does not exist.
+pub fn describe_unit(w: &World, uid: u8) -> Option<String> {
+	let st = crate::world::unit_stats(w, uid)?;
+	let r = w.hp_rounding();
+	let side = if st.player { "" } else { "Enemy " };
+	Some(format!("{}{}, {} of {} HP", side, st.name, r.apply(st.health), r.apply(st.max_health)))
+}
+
+/// Describe the selection of the unit with specified id, e.g, "Archer selected, 7 of 10 HP", or of `n` units together, if more than one.
+pub fn describe_selection(w: &World, uid: u8, n: usize) -> Option<String> {
+	if n > 1 {
+		return Some(format!("{} units selected", n));
+	}
+	let st = crate::world::unit_stats(w, uid)?;
+	let r = w.hp_rounding();
+	Some(format!("{} selected, {} of {} HP", st.name, r.apply(st.health), r.apply(st.max_health)))
+}
+
+fn unit_name(w: &World, uid: u8) -> Option<String> {
+	crate::world::unit_stats(w, uid).map(|st| st.name)
+}
@@ -0,0 +1,166 @@
+//! Tactics puzzles, i.e, battles where the player must find a fixed sequence of actions, or win within a limited number of moves.
+//! A puzzle is defined in a plain text file, one record per line; blank lines and lines starting with `#` are ignored.
+//!
+//! | Record | Meaning |
+//! | ------ | ------- |
+//! | `moves <n>` | at most `n` actions may be taken |
+//! | `step <unit> <x> <y>` | the next action: the unit with id `unit` ends its move on (`x`, `y`), without attacking |
+//! | `step <unit> <x> <y> <target>` | the next action: the unit with id `unit` ends its move on (`x`, `y`), and attacks the unit with id `target` |
+//!
+//! An action is the move (and attack) of a player unit, checked against the puzzle once confirmed; waiting in place counts as an action too.
+//! Any action other than the next step fails the puzzle, and taking the last step solves it.
+//! A puzzle without steps is solved by winning the battle, and failed once its moves have been spent without winning.
+//! A solved puzzle ends the battle in victory, and a failed one in defeat.
+//!
+//! While a puzzle is loaded (see [World::load_puzzle](crate::world::World::load_puzzle)), orders cannot be cancelled once given, and units cannot be selected together.
+//! # Example
+//! ```
+//! use alesia::puzzle::{Puzzle, PuzzleStep};
+//! use alesia::world::ObjectiveState;
+//! let mut p = Puzzle::parse("# Knight takes the archer.\nmoves 2\nstep 1 3 4\nstep 2 5 4 7\n").unwrap();
+//! assert_eq!(p.act(PuzzleStep { unit: 1, tile: (3, 4), target: None }), ObjectiveState::Pending);
+//! assert_eq!(p.act(PuzzleStep { unit: 2, tile: (5, 4), target: Some(7) }), ObjectiveState::Completed);
+//! ```
+
+use crate::world::ObjectiveState;
+
+/// An action of a player unit: the tile it ends its move on, and the unit it attacks, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PuzzleStep {
+	/// Id of the unit taking the action.
+	pub unit: u8,
+	/// Tile the unit ends its move on.
+	pub tile: (i32, i32),
+	/// Id of the unit attacked, if any.
+	pub target: Option<u8>
+}
+
+/// A puzzle definition, with the progress of the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+	steps: Vec<PuzzleStep>,
+	moves: Option<u32>,
+	done: usize,
+	used: u32,
+	state: ObjectiveState
+}
+
+impl Puzzle {
+	/// Constructor method. Returns a puzzle with the specified sequence of steps (possibly empty), and limit on the number of actions, if any.
+	pub fn new(steps: Vec<PuzzleStep>, moves: Option<u32>) -> Puzzle {
+		Puzzle {
+			steps: steps,
+			moves: moves,
+			done: 0,
+			used: 0,
+			state: ObjectiveState::Pending
+		}
+	}
+
+	/// Parse a puzzle definition. Returns an error message if malformed.
+	pub fn parse(text: &str) -> Result<Puzzle, String> {
+		let mut p = Puzzle::new(vec![], None);
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let f: Vec<&str> = line.split_whitespace().collect();
+			let bad = || format!("malformed record on line {}: {}", i + 1, line);
+			match (f[0], f.len()) {
+				("moves", 2) => p.moves = Some(f[1].parse().map_err(|_| bad())?),
+				("step", 4) | ("step", 5) => p.steps.push(PuzzleStep {
+					unit: f[1].parse().map_err(|_| bad())?,
+					tile: (f[2].parse().map_err(|_| bad())?, f[3].parse().map_err(|_| bad())?),
+					target: match f.get(4) {
+						Some(t) => Some(t.parse().map_err(|_| bad())?),
+						None => None
+					}
+				}),
+				_ => return Err(bad())
+			}
+		}
+		if p.steps.is_empty() && p.moves.is_none() {
+			return Err("puzzle has neither steps nor a limit on moves".to_string());
+		}
+		Ok(p)
+	}
+
+	/// Read and parse the puzzle definition at `path`.
+	pub fn load(path: &str) -> Result<Puzzle, String> {
+		let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+		Puzzle::parse(&text)
+	}
+
+	/// Returns the sequence of steps.
+	pub fn steps(&self) -> &[PuzzleStep] {
+		&self.steps
+	}
+
+	/// Returns the number of steps taken.
+	pub fn steps_done(&self) -> usize {
+		self.done
+	}
+
+	/// Returns the next step to take, if any.
+	pub fn next_step(&self) -> Option<&PuzzleStep> {
+		self.steps.get(self.done)
+	}
+
+	/// Returns the number of actions that may still be taken, if limited.
+	pub fn moves_left(&self) -> Option<u32> {
+		self.moves.map(|m| m.saturating_sub(self.used))
+	}
+
+	/// Returns the number of actions taken.
+	pub fn moves_used(&self) -> u32 {
+		self.used
+	}
+
+	/// Returns whether the puzzle is yet to be solved, solved (`Completed`) or failed.
+	pub fn state(&self) -> ObjectiveState {
+		self.state
+	}
+
+	/// Check an action against the puzzle, and return its state afterwards. Actions are ignored once the puzzle is solved or failed.
+	pub fn act(&mut self, a: PuzzleStep) -> ObjectiveState {
+		if self.state != ObjectiveState::Pending {
+			return self.state;
+		}
+		if self.moves_left() == Some(0) {
+			self.state = ObjectiveState::Failed;
+			return self.state;
+		}
+		self.used += 1;
+		if let Some(s) = self.next_step() {
+			if *s == a {
+				self.done += 1;
+				if self.done == self.steps.len() {
+					self.state = ObjectiveState::Completed;
+				}
+			} else {
+				self.state = ObjectiveState::Failed;
+			}
+		}
+		self.state
+	}
+
+	/// Settle a pending puzzle once the player turn has ended, given whether the battle has been won: solved if won, and failed if the moves have been spent.
+	pub(crate) fn settle(&mut self, won: bool) -> ObjectiveState {
+		if self.state == ObjectiveState::Pending {
+			if won {
+				self.state = ObjectiveState::Completed;
+			} else if self.moves_left() == Some(0) {
+				self.state = ObjectiveState::Failed;
+			}
+		}
+		self.state
+	}
+
+	/// Restore the progress of the player, e.g, from a save.
+	pub(crate) fn set_progress(&mut self, done: usize, used: u32, state: ObjectiveState) {
+		self.done = done.min(self.steps.len());
+		self.used = used;
+		self.state = state;
+	}
+}
@@ -0,0 +1,1273 @@
+//! Python bindings for the engine, built with [pyo3](https://pyo3.rs) when the `python` feature is enabled.
+//!
+//! The module is exported as `alesia`, and exposes the following classes:
+//! 1. `ResourceSet`, `World`, `UnitType`, `StateListener` and `Display`, which wrap their Rust counterparts.
+//! 2. `WorldView` and `OrderList`, which are handed to the turn callback.
+//!
+//! and the functions `data_dir`, and `list_slots` and `delete_slot` for save slots; with the `debug_tools` feature, also `set_cheat` and `force_outcome`, see [crate::debug_tools].
+//!
+//! Objects that are consumed by the engine (e.g, a `UnitType` once registered, or a `World` once the display begins) are *spent*;
+//! using them afterwards raises a `ValueError` instead of silently doing nothing.
+//! # Example
+//! ```python
+//! import alesia
+//! rs = alesia.ResourceSet()
+//! rs.map_texture(0, "res/tile.png")
+//! w = alesia.World()
+//! ut = alesia.UnitType(2, "Swordsperson", 10.0, 0.2, 2, 1, 3.0)
+//! w.register_unit_type(ut, 0)
+//! sl = alesia.StateListener()
+//! sl.bind_turn(lambda view, orders: orders.push_victory())
+//! alesia.Display(1296, 800, "Python test").begin(rs, w, sl)
+//! ```
+
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::IntoPyDict;
+use pyo3::types::PyTuple;
+use std::ptr;
+use std::collections::HashMap;
+use crate::input::Order;
+use crate::input::InputScheme;
+use crate::utils::ResourceSet;
+use crate::utils::StateListener;
+use crate::utils::Settings;
+use crate::utils::TexFilter;
+use crate::save::SaveSlots;
+use crate::ai::TreeSet;
+use crate::display::Display;
+use crate::world;
+use crate::world::World;
+use crate::world::UnitType;
+use crate::world::pickup::PickupEffect;
+use crate::world::voice::BarkKind;
+use crate::world::mixer::StingerKind;
+use crate::tween::Tween;
+use crate::tween::Easing;
+use crate::event::EngineEvent;
+
+fn spent(what: &str) -> PyErr {
+	PyValueError::new_err(format!("{} has already been consumed by the engine", what))
+}
+
+/// Python wrapper for [ResourceSet].
+#[pyclass(name = "ResourceSet", unsendable)]
+pub struct PyResourceSet {
+	inner: Option<ResourceSet>
+}
+
+#[pymethods]
+impl PyResourceSet {
+	#[new]
+	fn new() -> Self {
+		PyResourceSet {
+			inner: Some(ResourceSet::new())
+		}
+	}
+
+	fn map_texture(&mut self, id: u8, path: &str) -> PyResult<()> {
+		self.get()?.map_texture(id, path);
+		Ok(())
+	}
+
+	#[args(filter = "1", mipmaps = "false")]
+	fn map_texture_ex(&mut self, id: u8, path: &str, filter: u8, mipmaps: bool) -> PyResult<()> {
+		let f = TexFilter::from_u8(filter).ok_or_else(|| PyValueError::new_err(format!("Invalid texture filter {}", filter)))?;
+		self.get()?.map_texture_ex(id, path, f, mipmaps);
+		Ok(())
+	}
+
+	fn set_nine_patch(&mut self, id: u8, left: i32, top: i32, right: i32, bottom: i32) -> PyResult<()> {
+		self.get()?.set_nine_patch(id, left, top, right, bottom);
+		Ok(())
+	}
+
+	fn set_ui_anim(&mut self, id: u8, fw: u32, fh: u32, frn: u8, fr: f32) -> PyResult<()> {
+		self.get()?.set_ui_anim(id, (fw, fh), frn, fr);
+		Ok(())
+	}
+
+	fn map_texture_region(&mut self, id: u8, tid: u8, x: f32, y: f32, w: f32, h: f32) -> PyResult<()> {
+		self.get()?.map_texture_region(id, tid, x, y, w, h);
+		Ok(())
+	}
+
+	fn map_font(&mut self, id: u8, path: &str) -> PyResult<()> {
+		self.get()?.map_font(id, path);
+		Ok(())
+	}
+
+	fn map_font_atlas(&mut self, id: u8, path: &str, sizes: Vec<i32>, ranges: Vec<(u32, u32)>) -> PyResult<()> {
+		self.get()?.map_font_atlas(id, path, &sizes, &ranges);
+		Ok(())
+	}
+
+	fn map_sound(&mut self, id: u8, path: &str) -> PyResult<()> {
+		self.get()?.map_sound(id, path);
+		Ok(())
+	}
+
+	fn map_music(&mut self, id: u8, path: &str) -> PyResult<()> {
+		self.get()?.map_music(id, path);
+		Ok(())
+	}
+
+	fn set_memory_budget(&mut self, bytes: usize) -> PyResult<()> {
+		self.get()?.set_memory_budget(bytes);
+		Ok(())
+	}
+}
+
+impl PyResourceSet {
+	fn get(&mut self) -> PyResult<&mut ResourceSet> {
+		self.inner.as_mut().ok_or_else(|| spent("ResourceSet"))
+	}
+}
+
+/// Python wrapper for [UnitType].
+#[pyclass(name = "UnitType", unsendable)]
+pub struct PyUnitType {
+	inner: Option<UnitType>
+}
+
+#[pymethods]
+impl PyUnitType {
+	#[new]
+	fn new(tex_id: u8, name: String, max_health: f32, mov_rate: f32, movement: u8, range: u8, attack_dur: f32) -> Self {
+		PyUnitType {
+			inner: Some(UnitType::new(tex_id, name, max_health, mov_rate, movement, range, attack_dur))
+		}
+	}
+
+	/// See [UnitType::def_anim_muted].
+	fn def_anim_muted(&mut self, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool) -> PyResult<()> {
+		self.get()?.def_anim_muted((fw, fh), frn, (cfx, cfy), fr, flip);
+		Ok(())
+	}
+
+	/// See [UnitType::def_anim].
+	fn def_anim(&mut self, fw: u32, fh: u32, frn: u8, cfx: u32, cfy: u32, fr: f32, flip: bool, snd: u8, lp: bool) -> PyResult<()> {
+		self.get()?.def_anim((fw, fh), frn, (cfx, cfy), fr, flip, snd, lp);
+		Ok(())
+	}
+
+	fn set_info(&mut self, text: String) -> PyResult<()> {
+		self.get()?.set_info(text);
+		Ok(())
+	}
+
+	/// See [UnitType::set_portrait].
+	fn set_portrait(&mut self, tex: Option<u8>) -> PyResult<()> {
+		self.get()?.set_portrait(tex);
+		Ok(())
+	}
+
+	/// Set the attacks per turn of units of this type, and whether they may move after attacking; None for attacks to follow the world. See [UnitType::set_actions].
+	#[args(move_after_attack = "false")]
+	fn set_actions(&mut self, attacks: Option<u8>, move_after_attack: bool) -> PyResult<()> {
+		self.get()?.set_actions(attacks.map(|a| world::Actions { attacks: a, move_after_attack: move_after_attack }));
+		Ok(())
+	}
+
+	/// Set the voice lines for a moment (0 - selected, 1 - starting to move, 2 - attacking, 3 - dying), one of which is played at random, and the seconds before they may play again. See [world::voice].
+	#[args(cooldown = "0.0")]
+	fn set_voice(&mut self, kind: u8, sounds: Vec<u8>, cooldown: f32) -> PyResult<()> {
+		let k = BarkKind::from_u8(kind).ok_or_else(|| PyValueError::new_err(format!("Invalid voice kind {}", kind)))?;
+		self.get()?.set_voice(k, sounds, cooldown);
+		Ok(())
+	}
+
+	/// Set how many tiles away units of this type see under fog of war; None for their movement and range. See [UnitType::set_sight].
+	fn set_sight(&mut self, sight: Option<u8>) -> PyResult<()> {
+		self.get()?.set_sight(sight);
+		Ok(())
+	}
+}
+
+impl PyUnitType {
+	fn get(&mut self) -> PyResult<&mut UnitType> {
+		self.inner.as_mut().ok_or_else(|| spent("UnitType"))
+	}
+}
+
+/// Python wrapper for [World].
+#[pyclass(name = "World", unsendable)]
+pub struct PyWorld {
+	inner: Option<World>
+}
+
+#[pymethods]
+impl PyWorld {
+	/// Create a blank world. If origin and tile size are not specified, see [World::blank]. If `hex` is true, tiles are hexagons, see [World::blank_hex].
+	#[new]
+	#[args(origin = "None", tile_size = "None", hex = "false")]
+	fn new(origin: Option<(i32, i32)>, tile_size: Option<(i32, i32)>, hex: bool) -> Self {
+		let w = match (origin, tile_size) {
+			_ if hex => {
+				let (ox, oy) = origin.unwrap_or((0, 0));
+				let (tx, ty) = tile_size.unwrap_or((96, 48));
+				World::blank_hex(ox, oy, tx, ty)
+			},
+			(None, None) => World::blank(),
+			(o, t) => {
+				let (ox, oy) = o.unwrap_or((0, 0));
+				let (tx, ty) = t.unwrap_or((96, 48));
+				World::blank_o(ox, oy, tx, ty)
+			}
+		};
+		PyWorld {
+			inner: Some(w)
+		}
+	}
+
+	/// See [world::load_world].
+	fn load_map(&mut self, path: &str) -> PyResult<bool> {
+		Ok(world::load_world(self.get()?, path))
+	}
+
+	/// See [world::save_world]. Returns false if the map could not be saved.
+	fn save_map(&mut self, path: &str) -> PyResult<bool> {
+		match world::save_world(self.get()?, path) {
+			Ok(()) => Ok(true),
+			Err(e) => {
+				eprintln!("warning [pyapi]: {}", e);
+				Ok(false)
+			}
+		}
+	}
+
+	/// See [world::load_world_ron].
+	fn load_map_ron(&mut self, path: &str) -> PyResult<bool> {
+		Ok(world::load_world_ron(self.get()?, path))
+	}
+
+	/// See [world::save_world_ron]. Returns false if the map could not be saved.
+	fn save_map_ron(&mut self, path: &str) -> PyResult<bool> {
+		match world::save_world_ron(self.get()?, path) {
+			Ok(()) => Ok(true),
+			Err(e) => {
+				eprintln!("warning [pyapi]: {}", e);
+				Ok(false)
+			}
+		}
+	}
+
+	fn create_static(&mut self, tex_id: u8, x: i32, y: i32) -> PyResult<()> {
+		world::create_static(self.get()?, tex_id, (x, y));
+		Ok(())
+	}
+
+	/// Register a unit type. The `UnitType` object is spent after this call.
+	fn register_unit_type(&mut self, mut ut: PyRefMut<PyUnitType>, id: u8) -> PyResult<()> {
+		let w = self.get()?;
+		let u = ut.inner.take().ok_or_else(|| spent("UnitType"))?;
+		world::register_unit_type(w, u, id);
+		Ok(())
+	}
+
+	/// Spawn a unit and return its id. See [world::spawn_unit].
+	#[args(tint = "-1", player = "true")]
+	fn spawn_unit(&mut self, type_id: u8, x: i32, y: i32, tint: i32, player: bool) -> PyResult<u8> {
+		let w = self.get()?;
+		if !w.unit_types.contains_key(&type_id) {
+			return Err(PyValueError::new_err(format!("Invalid unit type id={}", type_id)));
+		}
+		Ok(world::spawn_unit(w, type_id, (x, y), tint, player))
+	}
+
+	/// Make a unit hold its position. See [world::Behavior].
+	fn set_hold(&mut self, uid: u8) -> PyResult<()> {
+		self.behavior(uid, Some(world::Behavior::Hold))
+	}
+
+	/// Make a unit guard the tile (x, y) within `radius` tiles. See [world::Behavior].
+	fn set_guard(&mut self, uid: u8, x: i32, y: i32, radius: u8) -> PyResult<()> {
+		self.behavior(uid, Some(world::Behavior::Guard { tile: (x, y), radius: radius }))
+	}
+
+	/// Make a unit patrol between `waypoints`, a list of (x, y) tiles. See [world::Behavior].
+	fn set_patrol(&mut self, uid: u8, waypoints: Vec<(i32, i32)>) -> PyResult<()> {
+		self.behavior(uid, Some(world::Behavior::Patrol { waypoints: waypoints, next: 0 }))
+	}
+
+	fn clear_behavior(&mut self, uid: u8) -> PyResult<()> {
+		self.behavior(uid, None)
+	}
+
+	/// Set the portrait of a unit, overriding that of its type, or None to show that of its type. See [UnitType::set_portrait].
+	fn set_unit_portrait(&mut self, uid: u8, tex: Option<u8>) -> PyResult<()> {
+		match self.get()?.units.get_mut(&uid) {
+			Some(u) => u.portrait = tex,
+			None => return Err(PyValueError::new_err(format!("Invalid unit id={}", uid)))
+		}
+		Ok(())
+	}
+
+	/// Returns the shortest route from `start` to `goal` as a list of (x, y) tiles, or None if there is none. See [world::path::find].
+	fn find_path(&mut self, start: (i32, i32), goal: (i32, i32)) -> PyResult<Option<Vec<(i32, i32)>>> {
+		Ok(world::path::find(self.get()?, start, goal))
+	}
+
+	/// See [World::load_ai].
+	fn load_ai(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.load_ai(path))
+	}
+
+	/// See [World::load_puzzle].
+	fn load_puzzle(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.load_puzzle(path))
+	}
+
+	/// Remove the puzzle of the current map, so the battle is played freely.
+	fn clear_puzzle(&mut self) -> PyResult<()> {
+		self.get()?.set_puzzle(None);
+		Ok(())
+	}
+
+	/// See [World::load_tutorial].
+	fn load_tutorial(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.load_tutorial(path))
+	}
+
+	/// Remove the tutorial of the current map, so input is no longer restricted.
+	fn clear_tutorial(&mut self) -> PyResult<()> {
+		self.get()?.set_tutorial(None);
+		Ok(())
+	}
+
+	/// Place a pickup drawn with texture `tex` on the tile (x, y), replacing any pickup already there.
+	/// `kind` is 0 - heal (`value` is the fraction of maximum health restored), 1 - boost (`value` is the fraction by which attack is raised), or 2 - gold (`value` is the amount). See [world::pickup].
+	fn place_pickup(&mut self, tex: u8, x: i32, y: i32, kind: u8, value: f32) -> PyResult<()> {
+		let e = PickupEffect::from_raw(kind, value).ok_or_else(|| PyValueError::new_err(format!("Invalid pickup kind={}, value={}", kind, value)))?;
+		world::pickup::place(self.get()?, tex, (x, y), e);
+		Ok(())
+	}
+
+	/// Remove the pickup on the tile (x, y) without applying it. Returns false if there was none.
+	fn remove_pickup(&mut self, x: i32, y: i32) -> PyResult<bool> {
+		Ok(world::pickup::remove(self.get()?, (x, y)).is_some())
+	}
+
+	/// Pan the camera smoothly to the world co-ordinates (x, y) over `duration` seconds. See [Easing] for the values of `easing`.
+	#[args(easing = "5")]
+	fn pan_camera(&mut self, x: f32, y: f32, duration: f32, easing: u8) -> PyResult<()> {
+		let e = Easing::from_u8(easing).ok_or_else(|| PyValueError::new_err(format!("Invalid easing {}", easing)))?;
+		self.get()?.pan_camera(x, y, duration, e);
+		Ok(())
+	}
+
+	/// See [World::pan_to_tile].
+	#[args(duration = "0.5")]
+	fn pan_to_tile(&mut self, x: i32, y: i32, duration: f32) -> PyResult<()> {
+		self.get()?.pan_to_tile(x, y, duration);
+		Ok(())
+	}
+
+	/// Make the camera follow the unit with specified id, or stop following if `uid` is None. See [World::follow_unit].
+	fn follow_unit(&mut self, uid: Option<u8>) -> PyResult<()> {
+		let w = self.get()?;
+		match uid {
+			Some(uid) if !w.follow_unit(uid) => Err(PyValueError::new_err(format!("Invalid unit id={}", uid))),
+			Some(_) => Ok(()),
+			None => {
+				w.cam_follow = None;
+				Ok(())
+			}
+		}
+	}
+
+	/// See [World::set_camera_follow_rate].
+	fn set_camera_follow_rate(&mut self, rate: f32) -> PyResult<()> {
+		self.get()?.set_camera_follow_rate(rate);
+		Ok(())
+	}
+
+	/// See [World::set_camera_auto_follow].
+	fn set_camera_auto_follow(&mut self, on: bool) -> PyResult<()> {
+		self.get()?.set_camera_auto_follow(on);
+		Ok(())
+	}
+
+	/// See [World::set_zoom].
+	fn set_zoom(&mut self, zoom: f32) -> PyResult<()> {
+		if !zoom.is_finite() {
+			return Err(PyValueError::new_err(format!("Invalid zoom {}", zoom)));
+		}
+		self.get()?.set_zoom(zoom);
+		Ok(())
+	}
+
+	/// See [World::zoom].
+	fn zoom(&mut self) -> PyResult<f32> {
+		Ok(self.get()?.zoom())
+	}
+
+	/// Set the input scheme (0 - keyboard and mouse, 1 - gamepad, 2 - touch), see [World::set_input_scheme].
+	fn set_input_scheme(&mut self, scheme: u8) -> PyResult<()> {
+		let sc = InputScheme::from_u8(scheme).ok_or_else(|| PyValueError::new_err(format!("Invalid input scheme {}", scheme)))?;
+		self.get()?.set_input_scheme(sc);
+		Ok(())
+	}
+
+	/// See [World::input_scheme].
+	fn input_scheme(&mut self) -> PyResult<u8> {
+		Ok(self.get()?.input_scheme() as u8)
+	}
+
+	/// Add a tween of a value from `start` to `end` over `duration` seconds, advanced once per frame along with the engine, returning its id. See [crate::tween].
+	/// `on_complete` (if not None) is called as `on_complete(view: WorldView, id: int)` once the tween is complete; exceptions raised by it are printed.
+	#[args(easing = "0", on_complete = "None")]
+	fn tween(&mut self, start: f32, end: f32, duration: f32, easing: u8, on_complete: Option<PyObject>) -> PyResult<u32> {
+		let e = Easing::from_u8(easing).ok_or_else(|| PyValueError::new_err(format!("Invalid easing {}", easing)))?;
+		let t = Tween::new(start, end, duration, e);
+		let w = self.get()?;
+		Ok(match on_complete {
+			Some(f) => w.tween_then(t, id_callback(f, "tween")),
+			None => w.tween(t)
+		})
+	}
+
+	/// Call `f(view: WorldView, id: int)` once, after `secs` seconds of game time, returning the id. See [crate::schedule].
+	fn schedule_after(&mut self, secs: f32, f: PyObject) -> PyResult<u32> {
+		Ok(self.get()?.schedule_after(secs, id_callback(f, "scheduled")))
+	}
+
+	/// Call `f(view: WorldView, id: int)` every `secs` seconds of game time until cancelled, returning the id.
+	fn schedule_every(&mut self, secs: f32, f: PyObject) -> PyResult<u32> {
+		Ok(self.get()?.schedule_every(secs, id_callback(f, "scheduled")))
+	}
+
+	/// Cancel a scheduled callable. Returns false if it has already been called (and does not repeat), or been cancelled.
+	fn cancel_scheduled(&mut self, id: u32) -> PyResult<bool> {
+		Ok(self.get()?.cancel_scheduled(id))
+	}
+
+	/// Remove a tween without calling its `on_complete`. Returns false if it has completed or been cancelled.
+	fn cancel_tween(&mut self, id: u32) -> PyResult<bool> {
+		Ok(self.get()?.cancel_tween(id))
+	}
+
+	/// Enable (or disable) the queue of engine events, read with `WorldView.poll_event`. See [crate::event].
+	fn enable_event_queue(&mut self, enable: bool) -> PyResult<()> {
+		self.get()?.enable_event_queue(enable);
+		Ok(())
+	}
+
+	/// Returns the hash of the state of the battle. See [world::state_hash].
+	fn state_hash(&self) -> PyResult<u64> {
+		Ok(world::state_hash(self.inner.as_ref().ok_or_else(|| spent("World"))?))
+	}
+
+	/// Returns the differences in the state of the battle from another World, as a list of strings, e.g, "unit 3 health 7.5 != 7.25". See [world::diff].
+	fn diff(&self, other: PyRef<PyWorld>) -> PyResult<Vec<String>> {
+		let a = self.inner.as_ref().ok_or_else(|| spent("World"))?;
+		let b = other.inner.as_ref().ok_or_else(|| spent("World"))?;
+		Ok(world::diff(a, b).iter().map(|d| d.to_string()).collect())
+	}
+
+	/// Set the behavior tree of a registered unit type to the tree rooted at node `root` of the file at `path`, or clear it if `path` is None. See [crate::ai].
+	fn set_type_ai(&mut self, type_id: u8, path: Option<&str>, root: Option<&str>) -> PyResult<()> {
+		let tree = match path {
+			Some(p) => {
+				let set = TreeSet::load(p).map_err(|e| PyValueError::new_err(format!("Failed to load behavior tree file {}: {}", p, e)))?;
+				let root = root.ok_or_else(|| PyValueError::new_err("Missing root node"))?;
+				Some(set.tree(root).ok_or_else(|| PyValueError::new_err(format!("Undefined node {}", root)))?)
+			},
+			None => None
+		};
+		if !world::set_type_behavior_tree(self.get()?, type_id, tree) {
+			return Err(PyValueError::new_err(format!("Invalid unit type id={}", type_id)));
+		}
+		Ok(())
+	}
+
+	/// Add a slide to the slideshow shown before the intro text. See [World::add_intro_slide].
+	fn add_intro_slide(&mut self, tex: u8, caption: &str, duration: f32) -> PyResult<()> {
+		self.get()?.add_intro_slide(tex, caption, duration);
+		Ok(())
+	}
+
+	/// Remove all slides of the slideshow shown before the intro text.
+	fn clear_intro_slides(&mut self) -> PyResult<()> {
+		self.get()?.set_intro_slides(Vec::new());
+		Ok(())
+	}
+
+	/// Show a screen of text scrolling up, e.g, credits or an epilogue, at `speed` pixels per second (the default speed if not positive). See [World::show_scrolling_text].
+	#[args(speed = "0.0")]
+	fn show_scrolling_text(&mut self, text: &str, speed: f32) -> PyResult<()> {
+		self.get()?.show_scrolling_text(text, speed);
+		Ok(())
+	}
+
+	/// Stack a layer of tiles (row by row, 255 for none) over the tiles of the map, drawn as `kind`: "ground", "decoration" or "overlay". See [World::add_tile_layer].
+	fn add_tile_layer(&mut self, kind: &str, tiles: Vec<u8>) -> PyResult<()> {
+		let k = world::LayerKind::from_name(kind).ok_or_else(|| PyValueError::new_err(format!("Invalid layer kind {:?}", kind)))?;
+		if !self.get()?.add_tile_layer(k, tiles) {
+			return Err(PyValueError::new_err("Tile layer does not match the map"));
+		}
+		Ok(())
+	}
+
+	/// See [World::clear_tile_layers].
+	fn clear_tile_layers(&mut self) -> PyResult<()> {
+		self.get()?.clear_tile_layers();
+		Ok(())
+	}
+
+	/// Add an area of the map, the rectangle of tiles with opposite corners `from` and `to`, looping a music track while the camera is on it. See [world::ambient].
+	#[args(volume = "1.0")]
+	fn add_ambient_region(&mut self, track: u8, from: (i32, i32), to: (i32, i32), falloff: f32, volume: f32) -> PyResult<()> {
+		if !(0.0..=1.0).contains(&volume) || falloff.is_nan() || falloff < 0.0 {
+			return Err(PyValueError::new_err(format!("Invalid ambient volume {} or fall-off {}", volume, falloff)));
+		}
+		self.get()?.add_ambient_region(world::ambient::AmbientRegion::new(track, from, to, falloff, volume));
+		Ok(())
+	}
+
+	/// See [World::clear_ambient_regions].
+	fn clear_ambient_regions(&mut self) -> PyResult<()> {
+		self.get()?.clear_ambient_regions();
+		Ok(())
+	}
+
+	/// Show `text` rising from the tile of a unit, in the style of `kind`: "damage", "heal", "status", "miss" or "crit". See [World::show_popup].
+	#[args(kind = "\"status\"")]
+	fn show_popup(&mut self, uid: u8, text: &str, kind: &str) -> PyResult<()> {
+		let k = world::PopupKind::from_name(kind).ok_or_else(|| PyValueError::new_err(format!("Invalid popup kind {:?}", kind)))?;
+		let w = self.get()?;
+		if !w.units.contains_key(&uid) {
+			return Err(PyValueError::new_err(format!("Invalid unit id={}", uid)));
+		}
+		w.show_popup(uid, text, k);
+		Ok(())
+	}
+
+	/// Enable or disable the built-in AI for enemy units without a behavior, advancing on player units within `aggressiveness` times their movement and range. See [World::set_ai_controller].
+	#[args(aggressiveness = "f32::INFINITY")]
+	fn set_ai_controller(&mut self, enabled: bool, aggressiveness: f32) -> PyResult<()> {
+		if aggressiveness.is_nan() || aggressiveness < 0.0 {
+			return Err(PyValueError::new_err(format!("Invalid aggressiveness {}", aggressiveness)));
+		}
+		self.get()?.set_ai_controller(if enabled { Some(world::AiController { aggressiveness: aggressiveness }) } else { None });
+		Ok(())
+	}
+
+	/// See [World::set_fog_of_war].
+	fn set_fog_of_war(&mut self, enabled: bool) -> PyResult<()> {
+		self.get()?.set_fog_of_war(enabled);
+		Ok(())
+	}
+
+	/// See [world::is_tile_visible].
+	fn is_tile_visible(&mut self, x: i32, y: i32) -> PyResult<bool> {
+		Ok(world::is_tile_visible(self.get()?, x, y))
+	}
+
+	/// Returns true if the tiles of the world are hexagons, see [World::blank_hex].
+	fn is_hex(&mut self) -> PyResult<bool> {
+		Ok(self.get()?.grid() == world::Grid::Hex)
+	}
+
+	/// Returns the number of steps between two tiles on the grid of the world. See [Grid::distance](world::Grid::distance).
+	fn grid_distance(&mut self, a: (i32, i32), b: (i32, i32)) -> PyResult<i32> {
+		Ok(self.get()?.grid().distance(a, b))
+	}
+
+	/// Set the attacks per turn of player units, and whether they may move after attacking, for unit types that do not set their own. See [World::set_actions].
+	#[args(move_after_attack = "false")]
+	fn set_actions(&mut self, attacks: u8, move_after_attack: bool) -> PyResult<()> {
+		self.get()?.set_actions(world::Actions { attacks: attacks, move_after_attack: move_after_attack });
+		Ok(())
+	}
+
+	/// Set the difficulty to a preset: "easy", "normal" or "hard". See [world::Difficulty].
+	fn set_difficulty(&mut self, name: &str) -> PyResult<()> {
+		let d = world::Difficulty::preset(name).ok_or_else(|| PyValueError::new_err(format!("Invalid difficulty {:?}", name)))?;
+		self.get()?.set_difficulty(d);
+		Ok(())
+	}
+
+	/// Set how health is rounded to whole numbers wherever it is shown: "ceil", "floor" or "round". See [World::set_hp_rounding].
+	fn set_hp_rounding(&mut self, name: &str) -> PyResult<()> {
+		let r = world::HpRounding::from_name(name).ok_or_else(|| PyValueError::new_err(format!("Invalid health rounding {:?}", name)))?;
+		self.get()?.set_hp_rounding(r);
+		Ok(())
+	}
+
+	/// Set custom difficulty multipliers. See [world::Difficulty].
+	fn set_custom_difficulty(&mut self, player_damage: f32, enemy_damage: f32, enemy_health: f32, aggression: f32) -> PyResult<()> {
+		self.get()?.set_difficulty(world::Difficulty { player_damage: player_damage, enemy_damage: enemy_damage, enemy_health: enemy_health, aggression: aggression });
+		Ok(())
+	}
+
+	/// Returns the difficulty multipliers as (player_damage, enemy_damage, enemy_health, aggression).
+	fn difficulty(&mut self) -> PyResult<(f32, f32, f32, f32)> {
+		let d = self.get()?.difficulty();
+		Ok((d.player_damage, d.enemy_damage, d.enemy_health, d.aggression))
+	}
+
+	/// Set the height level of every tile in row-major order; an empty list flattens the map.
+	fn set_elevation(&mut self, heights: Vec<u8>) -> PyResult<()> {
+		if self.get()?.set_elevation(heights) {
+			Ok(())
+		} else {
+			Err(PyValueError::new_err("Height levels do not match the map"))
+		}
+	}
+
+	/// Returns the height level of the tile at (x, y).
+	fn elevation_at(&mut self, x: i32, y: i32) -> PyResult<u8> {
+		Ok(world::elevation_at(self.get()?, x, y))
+	}
+
+	/// Set how elevation affects movement, range and damage. See [world::ElevationRules].
+	fn set_elevation_rules(&mut self, step: i32, max_climb: u8, range_bonus: u8, damage_bonus: f32) -> PyResult<()> {
+		self.get()?.set_elevation_rules(world::ElevationRules { step: step, max_climb: max_climb, range_bonus: range_bonus, damage_bonus: damage_bonus });
+		Ok(())
+	}
+
+	/// Returns the number of levels unit `atk` stands above unit `def`.
+	fn height_advantage(&mut self, atk: u8, def: u8) -> PyResult<i32> {
+		Ok(world::height_advantage(self.get()?, atk, def))
+	}
+
+	/// Set the stinger for an event (0 - level-up, 1 - unit lost, 2 - objective complete) to a sound `length` seconds long, or remove it if `sound_id` is None. See [world::mixer].
+	#[args(length = "0.0")]
+	fn set_stinger(&mut self, kind: u8, sound_id: Option<u8>, length: f32) -> PyResult<()> {
+		let k = StingerKind::from_u8(kind).ok_or_else(|| PyValueError::new_err(format!("Invalid stinger kind {}", kind)))?;
+		self.get()?.set_stinger(k, sound_id.map(|id| (id, length)));
+		Ok(())
+	}
+
+	/// Play the stinger for an event, ducking the background music until it ends. Returns false if none is set.
+	fn play_stinger(&mut self, kind: u8) -> PyResult<bool> {
+		let k = StingerKind::from_u8(kind).ok_or_else(|| PyValueError::new_err(format!("Invalid stinger kind {}", kind)))?;
+		Ok(self.get()?.play_stinger(k))
+	}
+
+	/// Set the fraction of its volume the background music is ducked to while a stinger plays.
+	fn set_duck_level(&mut self, level: f32) -> PyResult<()> {
+		self.get()?.set_duck_level(level);
+		Ok(())
+	}
+
+	fn set_bgm(&mut self, id: u8) -> PyResult<()> {
+		self.get()?.set_bgm(id);
+		Ok(())
+	}
+
+	/// Restore a save slot in `dir` before the game begins, see [SaveSlots::load].
+	fn load_slot(&mut self, dir: &str, slot: u32) -> PyResult<bool> {
+		Ok(SaveSlots::new(dir).load(self.get()?, slot))
+	}
+
+	/// See [World::load_battle].
+	fn load_battle(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.load_battle(path))
+	}
+
+	fn save_battle(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.save_battle(path))
+	}
+
+	/// See [World::open_profile].
+	fn open_profile(&mut self, path: &str) -> PyResult<bool> {
+		Ok(self.get()?.open_profile(path))
+	}
+
+	fn profile_value(&mut self, key: &str) -> PyResult<i64> {
+		Ok(self.get()?.profile.get(key))
+	}
+
+	fn set_profile_value(&mut self, key: &str, v: i64) -> PyResult<()> {
+		if !self.get()?.profile.set(key, v) {
+			return Err(PyValueError::new_err(format!("Invalid profile key {:?}", key)));
+		}
+		Ok(())
+	}
+}
+
+impl PyWorld {
+	fn get(&mut self) -> PyResult<&mut World> {
+		self.inner.as_mut().ok_or_else(|| spent("World"))
+	}
+
+	fn behavior(&mut self, uid: u8, b: Option<world::Behavior>) -> PyResult<()> {
+		if !world::set_behavior(self.get()?, uid, b) {
+			return Err(PyValueError::new_err(format!("Invalid unit id={}", uid)));
+		}
+		Ok(())
+	}
+}
+
+/// Read-only view into the world, valid only for the duration of the turn callback.
+#[pyclass(name = "WorldView", unsendable)]
+pub struct PyWorldView {
+	ptr: *mut World
+}
+
+impl PyWorldView {
+	fn get(&self) -> PyResult<&World> {
+		if self.ptr.is_null() {
+			return Err(PyValueError::new_err("WorldView used outside of the turn callback"));
+		}
+		unsafe { Ok(&*self.ptr) }
+	}
+
+	/// Only for scheduling callables and polling events; the view is otherwise read-only.
+	fn get_mut(&mut self) -> PyResult<&mut World> {
+		if self.ptr.is_null() {
+			return Err(PyValueError::new_err("WorldView used outside of the turn callback"));
+		}
+		unsafe { Ok(&mut *self.ptr) }
+	}
+}
+
+/// Call `f` with the arguments made by `args` from a view of the world `w`, printing exceptions raised by it as those of the `what` callback.
+/// The view is invalidated once the call returns, so that references kept by the script cannot outlive the callback.
+fn call_with_view<A: IntoPy<Py<PyTuple>>>(f: &PyObject, w: &mut World, what: &str, args: impl FnOnce(Python, Py<PyWorldView>) -> PyResult<A>) {
+	Python::with_gil(|py| {
+		let r = (|| -> PyResult<()> {
+			let view = Py::new(py, PyWorldView { ptr: w as *mut World })?;
+			let res = args(py, view.clone_ref(py)).and_then(|a| f.call1(py, a));
+			view.borrow_mut(py).ptr = ptr::null_mut();
+			res.map(|_| ())
+		})();
+		if let Err(e) = r {
+			eprintln!("warning [pyapi]: exception in {} callback", what);
+			e.print(py);
+		}
+	});
+}
+
+/// Wrap a callable `f(view: WorldView, id: int)`, printing exceptions raised by it.
+fn id_callback(f: PyObject, what: &'static str) -> impl FnMut(&mut World, u32) {
+	move |w, id| call_with_view(&f, w, what, |_, view| Ok((view, id)))
+}
+
+#[pymethods]
+impl PyWorldView {
+	fn profile_value(&self, key: &str) -> PyResult<i64> {
+		Ok(self.get()?.profile.get(key))
+	}
+
+	/// Ids of all alive units.
+	fn units(&self) -> PyResult<Vec<u8>> {
+		Ok(world::id_list(self.get()?))
+	}
+
+	fn unit_health(&self, uid: u8) -> PyResult<f32> {
+		self.unit(uid).map(|u| u.health)
+	}
+
+	/// Returns the health and maximum health of a unit, rounded as shown. See [world::unit_hp].
+	fn unit_hp(&self, uid: u8) -> PyResult<(i32, i32)> {
+		world::unit_hp(self.get()?, uid).ok_or_else(|| PyValueError::new_err(format!("Invalid unit id={}", uid)))
+	}
+
+	fn unit_pos(&self, uid: u8) -> PyResult<(f32, f32)> {
+		self.unit(uid).map(|u| (u.wpos.x, u.wpos.y))
+	}
+
+	fn unit_type(&self, uid: u8) -> PyResult<u8> {
+		let w = self.get()?;
+		self.unit(uid)?;
+		Ok(world::get_type_id(w, uid))
+	}
+
+	fn is_player(&self, uid: u8) -> PyResult<bool> {
+		self.unit(uid).map(|u| u.player)
+	}
+
+	fn map_size(&self) -> PyResult<(usize, usize)> {
+		Ok(self.get()?.map_size())
+	}
+
+	fn tile_allowed(&self, x: i32, y: i32) -> PyResult<bool> {
+		Ok(world::tile_type_at(self.get()?, x, y).allowed())
+	}
+
+	/// Returns the influence map seen by player units if `player` is true, or by enemy units otherwise, as a dict of (x, y) to (threat, support).
+	/// See [world::influence].
+	fn influence(&self, player: bool) -> PyResult<HashMap<(i32, i32), (f32, f32)>> {
+		let m = world::influence::compute(self.get()?, player);
+		let mut out = HashMap::new();
+		for (t, v) in m.threatened() {
+			out.insert(t, (v, m.support(t)));
+		}
+		for t in m.supported() {
+			out.entry(t).or_insert((0.0, m.support(t)));
+		}
+		Ok(out)
+	}
+
+	/// State of the puzzle of the current map (0 - pending, 1 - solved, 2 - failed), or None if there is no puzzle. See [crate::puzzle].
+	fn puzzle_state(&self) -> PyResult<Option<u8>> {
+		Ok(self.get()?.puzzle().map(|p| p.state() as u8))
+	}
+
+	/// Number of steps of the tutorial of the current map done, or None if there is no tutorial. See [crate::tutorial].
+	fn tutorial_step(&self) -> PyResult<Option<usize>> {
+		Ok(self.get()?.tutorial().map(|t| t.steps_done()))
+	}
+
+	/// Call `f(view: WorldView, id: int)` once, after `secs` seconds of game time, returning the id. See `World.schedule_after`.
+	fn schedule_after(&mut self, secs: f32, f: PyObject) -> PyResult<u32> {
+		Ok(self.get_mut()?.schedule_after(secs, id_callback(f, "scheduled")))
+	}
+
+	/// Call `f(view: WorldView, id: int)` every `secs` seconds of game time until cancelled, returning the id.
+	fn schedule_every(&mut self, secs: f32, f: PyObject) -> PyResult<u32> {
+		Ok(self.get_mut()?.schedule_every(secs, id_callback(f, "scheduled")))
+	}
+
+	/// Cancel a scheduled callable, e.g, from the callable itself. Returns false if it has already been called (and does not repeat), or been cancelled.
+	fn cancel_scheduled(&mut self, id: u32) -> PyResult<bool> {
+		Ok(self.get_mut()?.cancel_scheduled(id))
+	}
+
+	/// Returns the current value of a tween, or None once it has completed or been cancelled. See `World.tween`.
+	fn tween_value(&self, id: u32) -> PyResult<Option<f32>> {
+		Ok(self.get()?.tween_value(id))
+	}
+
+	/// Returns the oldest event in the queue as (name, data), removing it, or None if the queue is empty or disabled. See `StateListener.subscribe` for the data.
+	fn poll_event(&mut self, py: Python) -> PyResult<Option<(&'static str, PyObject)>> {
+		Ok(self.get_mut()?.poll_event().map(|e| (e.name(), event_data(py, &e))))
+	}
+
+	/// Returns the hash of the state of the battle. See [world::state_hash].
+	fn state_hash(&self) -> PyResult<u64> {
+		Ok(world::state_hash(self.get()?))
+	}
+
+	/// Returns the pickups lying on tiles, as a list of (x, y, kind, value). See [world::pickup].
+	fn pickups(&self) -> PyResult<Vec<(i32, i32, u8, f32)>> {
+		Ok(self.get()?.pickups().iter().map(|p| {
+			let (kind, value) = p.effect.to_raw();
+			(p.tile.0, p.tile.1, kind, value)
+		}).collect())
+	}
+	/// Input device the player last used (0 - keyboard and mouse, 1 - gamepad, 2 - touch), for HUD hints. See [World::input_scheme].
+	fn input_scheme(&self) -> PyResult<u8> {
+		Ok(self.get()?.input_scheme() as u8)
+	}
+}
+
+impl PyWorldView {
+	fn unit(&self, uid: u8) -> PyResult<&world::Unit> {
+		self.get()?.units.get(&uid).ok_or_else(|| PyValueError::new_err(format!("Invalid unit id={}", uid)))
+	}
+}
+
+/// Returns the fields of an event as a dict, e.g, {"uid": 3, "type_id": 0} for "UnitDied".
+fn event_data(py: Python, e: &EngineEvent) -> PyObject {
+	let d: Vec<(&str, PyObject)> = match e {
+		EngineEvent::UnitDied { uid, type_id } => vec![("uid", uid.to_object(py)), ("type_id", type_id.to_object(py))],
+		EngineEvent::UnitAttacked { attacker, defender, damage } => vec![("attacker", attacker.to_object(py)), ("defender", defender.to_object(py)), ("damage", damage.to_object(py))],
+		EngineEvent::UnitMoved { uid, tile } => vec![("uid", uid.to_object(py)), ("tile", tile.to_object(py))],
+		EngineEvent::PickupCollected { uid, effect } => {
+			let (kind, value) = effect.to_raw();
+			vec![("uid", uid.to_object(py)), ("kind", kind.to_object(py)), ("value", value.to_object(py))]
+		},
+		EngineEvent::PhaseChanged(p) | EngineEvent::BannerDone(p) | EngineEvent::TransitionDone(p) => vec![("phase", (*p as u8).to_object(py))],
+		EngineEvent::TurnTimedOut(b) => vec![("bank_spent", b.to_object(py))],
+		EngineEvent::MapLoaded(path) => vec![("path", path.to_object(py))],
+		EngineEvent::ResourceLoaded { id, kind, ok } => vec![("id", id.to_object(py)), ("kind", (*kind as u8).to_object(py)), ("ok", ok.to_object(py))],
+		EngineEvent::ResultsDismissed(o) => vec![("outcome", o.to_raw().to_object(py))],
+		EngineEvent::Rumble { strength, duration } => vec![("strength", strength.to_object(py)), ("duration", duration.to_object(py))],
+		EngineEvent::InputSchemeChanged(sc) => vec![("scheme", (*sc as u8).to_object(py))],
+		EngineEvent::TutorialStep(i) => vec![("step", i.to_object(py))],
+		EngineEvent::Exit => vec![]
+	};
+	d.into_py_dict(py).to_object(py)
+}
+
+/// Order list to push orders into, valid only for the duration of the turn callback.
+#[pyclass(name = "OrderList", unsendable)]
+pub struct PyOrderList {
+	ptr: *mut Vec<Order>
+}
+
+impl PyOrderList {
+	fn push(&mut self, o: Order) -> PyResult<()> {
+		if self.ptr.is_null() {
+			return Err(PyValueError::new_err("OrderList used outside of the turn callback"));
+		}
+		unsafe { (*self.ptr).push(o) };
+		Ok(())
+	}
+}
+
+#[pymethods]
+impl PyOrderList {
+	fn push_move(&mut self, uid: u8, tx: i32, ty: i32) -> PyResult<()> {
+		self.push(Order::MOVE(uid, tx, ty))
+	}
+
+	fn push_attack(&mut self, uid: u8, target: u8, tx: i32, ty: i32) -> PyResult<()> {
+		self.push(Order::ATTACK(uid, target, tx, ty))
+	}
+
+	fn push_victory(&mut self) -> PyResult<()> {
+		self.push(Order::VICTORY)
+	}
+
+	fn push_defeat(&mut self) -> PyResult<()> {
+		self.push(Order::DEFEAT)
+	}
+
+	#[args(relative = "false")]
+	fn push_mut_health(&mut self, uid: u8, val: f32, relative: bool) -> PyResult<()> {
+		if relative {
+			self.push(Order::MutHealthR(uid, val))
+		} else {
+			self.push(Order::MutHealthA(uid, val))
+		}
+	}
+}
+
+/// Python wrapper for [StateListener].
+#[pyclass(name = "StateListener", unsendable)]
+pub struct PyStateListener {
+	inner: Option<StateListener>
+}
+
+#[pymethods]
+impl PyStateListener {
+	#[new]
+	fn new() -> Self {
+		PyStateListener {
+			inner: Some(StateListener::new())
+		}
+	}
+
+	/// Bind a callable `f(view: WorldView, orders: OrderList)` invoked when the player turn ends.
+	/// Exceptions raised by the callable are printed, and do not stop the game.
+	fn bind_turn(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_turn(move |w, o| {
+			let o = o as *mut Vec<Order>;
+			let mut orders: Option<Py<PyOrderList>> = None;
+			call_with_view(&f, w, "turn", |py, view| {
+				let list = Py::new(py, PyOrderList { ptr: o })?;
+				orders = Some(list.clone_ref(py));
+				Ok((view, list))
+			});
+			// Invalidate the order list as well, so that references kept by the script cannot outlive the callback.
+			if let Some(list) = orders {
+				Python::with_gil(|py| list.borrow_mut(py).ptr = ptr::null_mut());
+			}
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, info: dict)` invoked once a map has been loaded, before the intro is shown.
+	/// `info` has the keys "path", "title", "intro", "victory", "defeat", "width" and "height". Exceptions raised by the callable are printed.
+	fn bind_map_loaded(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_map_loaded(move |w, m| {
+			call_with_view(&f, w, "map loaded", |py, view| {
+				let info = [
+					("path", m.path.to_object(py)),
+					("title", m.title.to_object(py)),
+					("intro", m.intro_text.to_object(py)),
+					("victory", m.victory_text.to_object(py)),
+					("defeat", m.defeat_text.to_object(py)),
+					("width", m.width.to_object(py)),
+					("height", m.height.to_object(py))
+				].into_py_dict(py).to_object(py);
+				Ok((view, info))
+			});
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, bank_spent: bool)` invoked when time runs out in a player turn, before the turn ends.
+	/// Exceptions raised by the callable are printed.
+	fn bind_timeout(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_timeout(move |w, bank| {
+			call_with_view(&f, w, "timeout", |_, view| Ok((view, bank)));
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, uid: int, type_id: int)` invoked when a unit dies, after it has been removed from the world.
+	/// Exceptions raised by the callable are printed.
+	fn bind_unit_death(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_unit_death(move |w, uid, tid| {
+			call_with_view(&f, w, "unit death", |_, view| Ok((view, uid, tid)));
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, attacker: int, defender: int, damage: float)` invoked when a unit attacks, once damage has been dealt.
+	/// Exceptions raised by the callable are printed.
+	fn bind_attack(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_attack(move |w, a, d, dmg| {
+			call_with_view(&f, w, "attack", |_, view| Ok((view, a, d, dmg)));
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, uid: int, tile: (int, int))` invoked when a unit ends its move on a tile.
+	/// Exceptions raised by the callable are printed.
+	fn bind_move_complete(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_move_complete(move |w, uid, t| {
+			call_with_view(&f, w, "move complete", |_, view| Ok((view, uid, t)));
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, uid: int, kind: int, value: float)` invoked when a unit collects a pickup, see `World.place_pickup`.
+	/// Exceptions raised by the callable are printed.
+	fn bind_pickup(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_pickup(move |w, uid, e| {
+			call_with_view(&f, w, "pickup", |_, view| {
+				let (kind, value) = e.to_raw();
+				Ok((view, uid, kind, value))
+			});
+		});
+		Ok(())
+	}
+
+	/// Bind a callable `f(view: WorldView, text: str)` invoked with each line of narration, e.g, "Archer selected, 7 of 10 HP", to pass on to text-to-speech. See [crate::narration].
+	/// Exceptions raised by the callable are printed.
+	fn bind_narration(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_narration(move |w, text| {
+			call_with_view(&f, w, "narration", |_, view| Ok((view, text)));
+		});
+		Ok(())
+	}
+
+	/// Subscribe a callable `f(view: WorldView, name: str, data: dict)` to every engine event, returning an id to unsubscribe with. See [crate::event].
+	/// `name` is the name of the event (e.g, "UnitDied"), and `data` holds its fields (e.g, "uid" and "type_id"). Exceptions raised by the callable are printed.
+	fn subscribe(&mut self, f: PyObject) -> PyResult<u32> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		Ok(sl.subscribe(move |w, e| {
+			call_with_view(&f, w, "event", |py, view| Ok((view, e.name(), event_data(py, e))));
+		}))
+	}
+
+	/// Remove a subscribed callable. Returns false if there is no such subscription.
+	fn unsubscribe(&mut self, id: u32) -> PyResult<bool> {
+		Ok(self.inner.as_mut().ok_or_else(|| spent("StateListener"))?.unsubscribe(id))
+	}
+
+	/// Bind a callable `f(view: WorldView)` invoked once the game is about to exit, e.g, to save progress.
+	/// Exceptions raised by the callable are printed.
+	fn bind_exit(&mut self, f: PyObject) -> PyResult<()> {
+		let sl = self.inner.as_mut().ok_or_else(|| spent("StateListener"))?;
+		sl.bind_exit(move |w| {
+			call_with_view(&f, w, "exit", |_, view| Ok((view,)));
+		});
+		Ok(())
+	}
+}
+
+/// Python wrapper for [Display].
+#[pyclass(name = "Display", unsendable)]
+pub struct PyDisplay {
+	inner: Option<Display>
+}
+
+#[pymethods]
+impl PyDisplay {
+	#[new]
+	fn new(width: i32, height: i32, title: &str) -> Self {
+		PyDisplay {
+			inner: Some(Display::new_s(width, height, title))
+		}
+	}
+
+	/// Create a display with the resolution, fps, vsync, volumes, ui scale and key bindings of the settings file at `path`, or the defaults if it does not exist.
+	#[staticmethod]
+	fn from_settings(path: &str, title: &str) -> PyResult<Self> {
+		let s = Settings::load(path).map_err(|e| PyValueError::new_err(format!("Failed to read settings {}: {}", path, e)))?;
+		Ok(PyDisplay {
+			inner: Some(Display::from_settings(&s, title))
+		})
+	}
+
+	/// See [Display::set_screenshot_dir].
+	fn set_screenshot_dir(&mut self, dir: &str) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_screenshot_dir(dir);
+		Ok(())
+	}
+
+	/// See [Display::set_confirm_exit].
+	fn set_confirm_exit(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_confirm_exit(on);
+		Ok(())
+	}
+
+	/// See [Display::set_pause_unfocused].
+	fn set_pause_unfocused(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_pause_unfocused(on);
+		Ok(())
+	}
+
+	/// See [Display::set_sim_fps].
+	fn set_sim_fps(&mut self, fps: u32) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_sim_fps(fps);
+		Ok(())
+	}
+
+	/// See [Display::set_ai_budget].
+	fn set_ai_budget(&mut self, ms: f32) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_ai_budget(ms);
+		Ok(())
+	}
+
+	/// See [Display::set_turn_timer].
+	#[args(bank = "0.0", increment = "0.0", auto_wait = "false")]
+	fn set_turn_timer(&mut self, per_turn: f32, bank: f32, increment: f32, auto_wait: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_turn_timer(per_turn, bank, increment, auto_wait);
+		Ok(())
+	}
+
+	/// See [Display::set_input_guard].
+	#[args(min_screen_time = "0.0")]
+	fn set_input_guard(&mut self, debounce: f32, min_screen_time: f32) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_input_guard(debounce, min_screen_time);
+		Ok(())
+	}
+
+	/// See [Display::set_rumble]. Each of `on_hit` and `on_select` is a tuple of strength and duration.
+	#[args(on_select = "(0.0, 0.0)")]
+	fn set_rumble(&mut self, on_hit: (f32, f32), on_select: (f32, f32)) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_rumble(on_hit, on_select);
+		Ok(())
+	}
+
+	/// See [Display::set_touch_input].
+	fn set_touch_input(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_touch_input(on);
+		Ok(())
+	}
+
+	/// See [Display::set_background_fps].
+	fn set_background_fps(&mut self, fps: u32) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_background_fps(fps);
+		Ok(())
+	}
+
+	/// See [Display::set_clean_screenshots].
+	fn set_clean_screenshots(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_clean_screenshots(on);
+		Ok(())
+	}
+
+	/// See [Display::set_control_hints].
+	fn set_control_hints(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_control_hints(on);
+		Ok(())
+	}
+
+	/// See [Display::set_text_scale].
+	fn set_text_scale(&mut self, s: f32) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_text_scale(s);
+		Ok(())
+	}
+
+	/// See [Display::set_high_contrast].
+	fn set_high_contrast(&mut self, on: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_high_contrast(on);
+		Ok(())
+	}
+
+	/// See [Display::set_autosave].
+	#[args(before_enemy = "true")]
+	fn set_autosave(&mut self, path: &str, every: u32, before_enemy: bool) -> PyResult<()> {
+		self.inner.as_mut().ok_or_else(|| spent("Display"))?.set_autosave(path, every, before_enemy);
+		Ok(())
+	}
+
+	/// Begin the draw-update loop. Blocks until the window is closed.
+	/// The display, resource set, world and state listener are all spent after this call.
+	#[args(sl = "None")]
+	fn begin(&mut self, mut rs: PyRefMut<PyResourceSet>, mut w: PyRefMut<PyWorld>, sl: Option<PyRefMut<PyStateListener>>) -> PyResult<()> {
+		// Validate every argument before taking any, so that a bad one leaves the others usable.
+		self.inner.as_ref().ok_or_else(|| spent("Display"))?;
+		rs.inner.as_ref().ok_or_else(|| spent("ResourceSet"))?;
+		w.inner.as_ref().ok_or_else(|| spent("World"))?;
+		if let Some(s) = &sl {
+			s.inner.as_ref().ok_or_else(|| spent("StateListener"))?;
+		}
+		let sl = sl.and_then(|mut s| s.inner.take()).unwrap_or_else(StateListener::new);
+		let (rs, w) = (rs.inner.take().unwrap(), w.inner.take().unwrap());
+		self.inner.take().unwrap().begin(rs, w, sl);
+		Ok(())
+	}
+}
+
+/// Directory for the saves, settings and screenshots of the game named `app`. See [data_dir](crate::utils::data_dir).
+#[pyfunction]
+fn data_dir(app: &str) -> String {
+	crate::utils::data_dir(app).to_string_lossy().into_owned()
+}
+
+/// Metadata of the used save slots in `dir`, as (slot, map title, time, turn, thumbnail path or None) tuples. See [SaveSlots::list].
+#[pyfunction]
+fn list_slots(dir: &str) -> Vec<(u32, String, u64, u32, Option<String>)> {
+	SaveSlots::new(dir).list().into_iter().map(|i| (i.slot, i.header.title, i.header.time, i.header.turn, i.thumbnail)).collect()
+}
+
+/// Delete a save slot in `dir`. See [SaveSlots::delete].
+#[pyfunction]
+fn delete_slot(dir: &str, slot: u32) -> bool {
+	SaveSlots::new(dir).delete(slot)
+}
+
+/// Run `f` on the world of a World or WorldView.
+#[cfg(feature = "debug_tools")]
+fn with_any_world<R>(w: &PyAny, f: impl FnOnce(&mut World) -> R) -> PyResult<R> {
+	if let Ok(mut v) = w.extract::<PyRefMut<PyWorldView>>() {
+		return Ok(f(v.get_mut()?));
+	}
+	let mut v: PyRefMut<PyWorld> = w.extract()?;
+	Ok(f(v.get()?))
+}
+
+/// Turn a cheat on or off on a World or WorldView: 0 - god mode, 1 - infinite movement, 2 - reveal. See [crate::debug_tools].
+#[cfg(feature = "debug_tools")]
+#[pyfunction]
+fn set_cheat(w: &PyAny, cheat: u8, on: bool) -> PyResult<()> {
+	let c = crate::debug_tools::Cheat::from_u8(cheat).ok_or_else(|| PyValueError::new_err(format!("Invalid cheat {}", cheat)))?;
+	with_any_world(w, |w| crate::debug_tools::set_cheat(w, c, on))
+}
+
+/// Win (`victory` True) or lose the game once the player turn is idle. See [crate::debug_tools::instant_win].
+#[cfg(feature = "debug_tools")]
+#[pyfunction]
+fn force_outcome(w: &PyAny, victory: bool) -> PyResult<()> {
+	with_any_world(w, |w| crate::napi::force_outcome(w, victory))
+}
+
+/// Module initializer for the `alesia` python extension.
+#[pymodule]
+fn alesia(_py: Python, m: &PyModule) -> PyResult<()> {
+	m.add_class::<PyResourceSet>()?;
+	m.add_class::<PyUnitType>()?;
+	m.add_class::<PyWorld>()?;
+	m.add_class::<PyWorldView>()?;
+	m.add_class::<PyOrderList>()?;
+	m.add_class::<PyStateListener>()?;
+	m.add_class::<PyDisplay>()?;
+	m.add_function(wrap_pyfunction!(data_dir, m)?)?;
+	m.add_function(wrap_pyfunction!(list_slots, m)?)?;
+	m.add_function(wrap_pyfunction!(delete_slot, m)?)?;
+	#[cfg(feature = "debug_tools")]
+	{
+		m.add_function(wrap_pyfunction!(set_cheat, m)?)?;
+		m.add_function(wrap_pyfunction!(force_outcome, m)?)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,205 @@
+//! Replays of a battle, i.e, the orders carried out in every phase from a [snapshot](crate::save) of the battle, for checking that the simulation is deterministic.
+//! A game records a replay with [Display::record_replay](crate::display::Display::record_replay); [run] then carries out its orders headlessly, and returns a [hash](crate::world::state_hash) of the world at the end of every turn.
+//! Hashes that differ between platforms, builds or runs point to the first turn at which the simulations diverged.
+//!
+//! Orders are recorded as they are carried out, whether given by the player, the built-in AI or a listener; so a replay does not depend on the AI or scripts.
+//! Orders are carried out at the fixed simulation rate of the game (see [Display::set_sim_fps](crate::display::Display::set_sim_fps)), which should be set when recording.
+//!
+//! Replays are stored as plain text, starting with the line `alesia-replay 1`, then the simulation rate, the snapshot, and the orders of each phase, one per line.
+//! # Example
+//! ```
+//! use alesia::world::{self, World, UnitType};
+//! use alesia::input::{Order, TurnPhase};
+//! use alesia::replay::Replay;
+//! let mut w = World::blank();
+//! let mut ut = UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 3, 0.5);
+//! for i in 0..9 {
+//!     ut.def_anim_muted((32, 32), 1, (0, 32 * i), 1.0, false);
+//! }
+//! world::register_unit_type(&mut w, ut, 0);
+//! let uid = world::spawn_unit(&mut w, 0, (2, 3), -1, true);
+//! let mut r = Replay::new(&w, 60);
+//! r.push(TurnPhase::PlayerMoves, vec![Order::MOVE(uid, 2, 4)]);
+//! r.push(TurnPhase::EnemyTurn, vec![]);
+//! let r = Replay::parse(&r.to_string()).unwrap();
+//! let hashes = alesia::replay::run(&mut w, &r).unwrap();
+//! assert_eq!(hashes.len(), 1);
+//! assert_eq!(world::unit_tile(&w, uid), Some((2, 4)));
+//! assert_eq!(hashes, alesia::replay::run(&mut w, &r).unwrap());
+//! ```
+
+use crate::input::InputHandler;
+use crate::input::Order;
+use crate::input::TurnPhase;
+use crate::utils::StateListener;
+use crate::world;
+use crate::world::World;
+use std::fmt;
+
+/// First line of every replay.
+const HEADER: &str = "alesia-replay 1";
+/// Most simulation steps a phase may take to carry out its orders, before the replay is given up on.
+const MAX_STEPS: u32 = 1 << 20;
+
+/// The orders carried out in every phase of a battle, from a snapshot of the battle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+	/// Simulation steps per second.
+	pub sim_fps: u32,
+	/// Snapshot of the battle before the first phase, see [save](crate::save).
+	pub start: String,
+	/// The orders carried out in each phase, in order; the phase is [PlayerMoves](TurnPhase::PlayerMoves) or [EnemyTurn](TurnPhase::EnemyTurn).
+	pub phases: Vec<(TurnPhase, Vec<Order>)>
+}
+
+impl Replay {
+	/// Constructor method. Begin a replay from the current state of the battle, carried out at `sim_fps` steps per second.
+	pub fn new(w: &World, sim_fps: u32) -> Replay {
+		Replay {
+			sim_fps: sim_fps,
+			start: crate::save::snapshot(w, TurnPhase::PlayerTurn),
+			phases: Vec::new()
+		}
+	}
+
+	/// Add the orders carried out in a phase. Orders of the enemy turn should be added with [EnemyTurn](TurnPhase::EnemyTurn), and any other orders with [PlayerMoves](TurnPhase::PlayerMoves).
+	pub fn push(&mut self, phase: TurnPhase, orders: Vec<Order>) {
+		let phase = if phase == TurnPhase::EnemyTurn { phase } else { TurnPhase::PlayerMoves };
+		self.phases.push((phase, orders));
+	}
+
+	/// Parse a replay from text. Returns an error message, with the line number, if the text is malformed.
+	pub fn parse(text: &str) -> Result<Replay, String> {
+		let mut lines = text.lines().enumerate();
+		if lines.next().map(|(_, l)| l) != Some(HEADER) {
+			return Err(String::from("not a replay"));
+		}
+		let sim_fps = header_number(lines.next(), "rate")? as u32;
+		let n = header_number(lines.next(), "snapshot")?;
+		let mut start = String::new();
+		for _ in 0..n {
+			let (_, l) = lines.next().ok_or("snapshot ends early")?;
+			start.push_str(l);
+			start.push('\n');
+		}
+		let mut phases = Vec::new();
+		while let Some((i, l)) = lines.next() {
+			let (phase, n) = match l.split_once(' ') {
+				Some(("player", n)) => (TurnPhase::PlayerMoves, n),
+				Some(("enemy", n)) => (TurnPhase::EnemyTurn, n),
+				_ => return Err(format!("line {}: expected player or enemy, and a number of orders", i + 1))
+			};
+			let n: usize = n.parse().map_err(|_| format!("line {}: invalid number of orders {:?}", i + 1, n))?;
+			let mut orders = Vec::with_capacity(n);
+			for _ in 0..n {
+				let (i, l) = lines.next().ok_or("orders end early")?;
+				orders.push(parse_order(l).ok_or_else(|| format!("line {}: invalid order {:?}", i + 1, l))?);
+			}
+			phases.push((phase, orders));
+		}
+		Ok(Replay { sim_fps: sim_fps, start: start, phases: phases })
+	}
+
+	/// Read a replay from the file at `path`.
+	pub fn load(path: &str) -> Result<Replay, String> {
+		let text = std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+		Replay::parse(&text)
+	}
+
+	/// Write the replay to the file at `path`, replacing it at once.
+	pub fn save(&self, path: &str) -> std::io::Result<()> {
+		crate::save::write_atomic(path, &self.to_string())
+	}
+
+	/// Record the orders of a phase about to be carried out, beginning from the current state of the battle if nothing has been recorded.
+	pub(crate) fn record(&mut self, w: &World, phase: TurnPhase, orders: &[Order]) {
+		if self.start.is_empty() {
+			self.start = crate::save::snapshot(w, phase);
+		}
+		self.push(phase, orders.to_vec());
+	}
+}
+
+impl fmt::Display for Replay {
+	/// Writes the replay as text, see [Replay::parse].
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "{}\nrate {}\nsnapshot {}", HEADER, self.sim_fps, self.start.lines().count())?;
+		for l in self.start.lines() {
+			writeln!(f, "{}", l)?;
+		}
+		for (p, orders) in &self.phases {
+			writeln!(f, "{} {}", if *p == TurnPhase::EnemyTurn { "enemy" } else { "player" }, orders.len())?;
+			for o in orders {
+				match o {
+					Order::MOVE(u, x, y) => writeln!(f, "MOVE {} {} {}", u, x, y)?,
+					Order::ATTACK(u, t, x, y) => writeln!(f, "ATTACK {} {} {} {}", u, t, x, y)?,
+					Order::VICTORY => writeln!(f, "VICTORY")?,
+					Order::DEFEAT => writeln!(f, "DEFEAT")?,
+					Order::MutHealthR(u, v) => writeln!(f, "MutHealthR {} {}", u, v)?,
+					Order::MutHealthA(u, v) => writeln!(f, "MutHealthA {} {}", u, v)?
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Parse a line of the form `key n`.
+fn header_number(line: Option<(usize, &str)>, key: &str) -> Result<usize, String> {
+	let (i, l) = line.ok_or_else(|| format!("missing {}", key))?;
+	match l.split_once(' ') {
+		Some((k, n)) if k == key => n.parse().map_err(|_| format!("line {}: invalid {} {:?}", i + 1, key, n)),
+		_ => Err(format!("line {}: expected {}", i + 1, key))
+	}
+}
+
+fn parse_order(line: &str) -> Option<Order> {
+	let args: Vec<&str> = line.split(' ').collect();
+	Some(match args.as_slice() {
+		["MOVE", u, x, y] => Order::MOVE(u.parse().ok()?, x.parse().ok()?, y.parse().ok()?),
+		["ATTACK", u, t, x, y] => Order::ATTACK(u.parse().ok()?, t.parse().ok()?, x.parse().ok()?, y.parse().ok()?),
+		["VICTORY"] => Order::VICTORY,
+		["DEFEAT"] => Order::DEFEAT,
+		["MutHealthR", u, v] => Order::MutHealthR(u.parse().ok()?, v.parse().ok()?),
+		["MutHealthA", u, v] => Order::MutHealthA(u.parse().ok()?, v.parse().ok()?),
+		_ => return None
+	})
+}
+
+/// Restore the snapshot of a replay into the world, and carry out its orders headlessly, phase by phase, at its simulation rate (60 steps per second if 0).
+/// The world must be set up as it was when the replay was recorded, i.e, with the same map loaded and unit types registered (with their animations).
+///
+/// Returns the [hash](world::state_hash) of the world at the end of every turn, i.e, after each enemy turn, and at the end of the replay if it ends within a turn.
+/// The replay stops early once the battle is won or lost (by a [VICTORY](Order::VICTORY) or [DEFEAT](Order::DEFEAT) order).
+/// Returns an error message if the snapshot cannot be restored, or the orders of a phase are not done within a reasonable number of steps.
+pub fn run(w: &mut World, r: &Replay) -> Result<Vec<u64>, String> {
+	crate::save::restore(w, &r.start)?;
+	let dt = 1.0 / if r.sim_fps > 0 { r.sim_fps } else { 60 } as f32;
+	let mut is = InputHandler::new();
+	let mut sl = StateListener::new();
+	let mut hashes = Vec::new();
+	let mut ended = true;
+	for (i, (p, orders)) in r.phases.iter().enumerate() {
+		is.carry_out(orders.clone(), *p == TurnPhase::EnemyTurn);
+		let mut n = 0;
+		while is.is_simulating() {
+			if n == MAX_STEPS {
+				return Err(format!("orders of phase {} were not done after {} steps", i + 1, n));
+			}
+			is.simulate(w, &mut sl, dt);
+			n += 1;
+		}
+		let over = matches!(is.get_state(), 5 | 6);
+		ended = *p == TurnPhase::EnemyTurn;
+		if ended || over {
+			hashes.push(world::state_hash(w));
+		}
+		if over {
+			return Ok(hashes);
+		}
+	}
+	if !ended {
+		hashes.push(world::state_hash(w));
+	}
+	Ok(hashes)
+}
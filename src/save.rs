@@ -0,0 +1,447 @@
+//! Snapshots of a battle in progress, for save games and autosaves.
+//! A snapshot holds the turn, camera, music, statistics, objectives, puzzle and tutorial progress, pickups, tiles of the map, and units (with their behaviors and boosts, and whether they have acted in the turn) of the [World];
+//! the rest of the map, static objects and unit types are not saved, so a snapshot must be restored into a world set up as it was when the battle began, i.e, with the same map loaded and unit types registered.
+//! See also [world::save_state] and [world::load_state].
+//!
+//! Snapshots are stored as plain text, one record per line, starting with the line `alesia-save 2`. Snapshots of version 1, which lack the tiles and the units that have acted, are restored as well.
+//! # Example
+//! ```
+//! use alesia::world::{self, World, UnitType};
+//! use alesia::input::TurnPhase;
+//! let mut w = World::blank();
+//! world::register_unit_type(&mut w, UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+//! let uid = world::spawn_unit(&mut w, 0, (2, 3), -1, true);
+//! let text = alesia::save::snapshot(&w, TurnPhase::PlayerTurn);
+//! world::despawn_unit(&mut w, uid);
+//! assert_eq!(alesia::save::restore(&mut w, &text), Ok(TurnPhase::PlayerTurn));
+//! assert_eq!(world::unit_tile(&w, uid), Some((2, 3)));
+//! ```
+
+use crate::input::TurnPhase;
+use crate::world;
+use crate::world::World;
+use crate::world::Objective;
+use crate::world::ObjectiveState;
+use crate::world::BattleStats;
+use crate::world::Difficulty;
+use crate::world::Behavior;
+use crate::world::pickup::Pickup;
+use crate::world::pickup::PickupEffect;
+use raylib::math::Vector2;
+
+/// First line of every snapshot.
+const HEADER: &str = "alesia-save 2";
+/// First line of snapshots written before the tiles and the units that have acted were saved.
+const HEADER_V1: &str = "alesia-save 1";
+
+fn escape(s: &str) -> String {
+	s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+	let mut out = String::new();
+	let mut chars = s.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => match chars.next() {
+				Some('n') => out.push('\n'),
+				Some(e) => out.push(e),
+				None => out.push('\\')
+			},
+			c => out.push(c)
+		}
+	}
+	out
+}
+
+/// Seconds since the UNIX epoch.
+pub(crate) fn now() -> u64 {
+	std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Returns the snapshot of a battle as text. `phase` is the phase the battle resumes at; either the player turn, or the enemy turn (which then begins as soon as the battle is resumed).
+pub fn snapshot(w: &World, phase: TurnPhase) -> String {
+	snapshot_turn(w, phase, &[])
+}
+
+/// Returns the snapshot of a battle as text, with the units that have acted in the player turn.
+pub(crate) fn snapshot_turn(w: &World, phase: TurnPhase, acted: &[u8]) -> String {
+	let phase = if phase == TurnPhase::EnemyTurn { 3 } else { 0 };
+	let st = w.stats();
+	let mut out = format!("{}\ntitle {}\ntime {}\nturn {}\nphase {}\ncamera {} {}\nbgm {}\n", HEADER, escape(w.map_title()), now(), w.turn(), phase, w.cam_wx, w.cam_wy, w.bgm_id);
+	out.push_str(&format!("stats {} {} {} {} {}\n", st.damage_dealt, st.damage_received, st.units_lost, st.enemies_defeated, st.xp_gained));
+	let d = w.difficulty();
+	out.push_str(&format!("difficulty {} {} {} {}\n", d.player_damage, d.enemy_damage, d.enemy_health, d.aggression));
+	for o in w.objectives() {
+		out.push_str(&format!("objective {} {}\n", o.state as u8, escape(&o.text)));
+	}
+	if let Some(p) = w.puzzle() {
+		out.push_str(&format!("puzzle {} {} {}\n", p.steps_done(), p.moves_used(), p.state() as u8));
+	}
+	if let Some(t) = w.tutorial() {
+		out.push_str(&format!("tutorial {}\n", t.steps_done()));
+	}
+	for p in w.pickups() {
+		let (kind, value) = p.effect.to_raw();
+		out.push_str(&format!("pickup {} {} {} {} {}\n", p.tex_id, p.tile.0, p.tile.1, kind, value));
+	}
+	if w.show_map() {
+		let (mw, mh) = w.map_size();
+		out.push_str(&format!("tiles {} {} ", mw, mh));
+		for t in w.map_tiles() {
+			out.push_str(&format!("{:02x}", t));
+		}
+		out.push('\n');
+	}
+	let mut ids: Vec<&u8> = w.units.keys().collect();
+	ids.sort();
+	for id in ids {
+		let u = &w.units[id];
+		out.push_str(&format!("unit {} {} {} {} {} {} {} {} {}\n", id, world::get_type_id(w, *id), u.health, u.wpos.x, u.wpos.y, u.tint, u.player as u8, u.palette.map_or(-1, |p| p as i32), u.facing as u8));
+		if u.boost != 0.0 {
+			out.push_str(&format!("boost {} {}\n", id, u.boost));
+		}
+		match &u.behavior {
+			Some(Behavior::Hold) => out.push_str(&format!("behavior {} hold\n", id)),
+			Some(Behavior::Guard { tile, radius }) => out.push_str(&format!("behavior {} guard {} {} {}\n", id, tile.0, tile.1, radius)),
+			Some(Behavior::Patrol { waypoints, next }) => {
+				out.push_str(&format!("behavior {} patrol {}", id, next));
+				for (x, y) in waypoints {
+					out.push_str(&format!(" {} {}", x, y));
+				}
+				out.push('\n');
+			},
+			None => {}
+		}
+	}
+	if phase == 0 && !acted.is_empty() {
+		out.push_str("acted");
+		for id in acted {
+			out.push_str(&format!(" {}", id));
+		}
+		out.push('\n');
+	}
+	out
+}
+
+/// Header fields of a snapshot, read without restoring it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotInfo {
+	/// Title of the map.
+	pub title: String,
+	/// Time at which the snapshot was taken, in seconds since the UNIX epoch.
+	pub time: u64,
+	/// Number of the turn.
+	pub turn: u32
+}
+
+/// Read the header of a snapshot. Returns None if the text is not a snapshot.
+pub fn peek(text: &str) -> Option<SnapshotInfo> {
+	let mut lines = text.lines();
+	if !matches!(lines.next()?, HEADER | HEADER_V1) {
+		return None;
+	}
+	let mut info = SnapshotInfo::default();
+	for line in lines {
+		match line.split_once(' ') {
+			Some(("title", t)) => info.title = unescape(t),
+			Some(("time", t)) => info.time = t.parse().ok()?,
+			Some(("turn", t)) => info.turn = t.parse().ok()?,
+			_ => break
+		}
+	}
+	Some(info)
+}
+
+/// Restore a snapshot into the world, replacing its units, pickups, objectives, statistics, difficulty, puzzle and tutorial progress, turn, camera and music.
+/// Returns the phase the battle resumes at, or an error message (leaving the world unchanged) if the snapshot is malformed or refers to unregistered unit types.
+pub fn restore(w: &mut World, text: &str) -> Result<TurnPhase, String> {
+	restore_turn(w, text).map(|(p, _)| p)
+}
+
+/// Restore a snapshot into the world, returning the phase the battle resumes at and the units that have acted in the player turn.
+pub(crate) fn restore_turn(w: &mut World, text: &str) -> Result<(TurnPhase, Vec<u8>), String> {
+	let mut lines = text.lines().enumerate();
+	match lines.next() {
+		Some((_, HEADER)) | Some((_, HEADER_V1)) => {},
+		_ => return Err("not a save file".to_string())
+	}
+	let mut phase = TurnPhase::PlayerTurn;
+	let (mut turn, mut cam, mut bgm) = (w.turn(), (w.cam_wx, w.cam_wy), w.bgm_id);
+	let mut stats = BattleStats::default();
+	let mut difficulty = w.difficulty();
+	let mut objectives = vec![];
+	let mut puzzle = None;
+	let mut tutorial = None;
+	let mut units = vec![];
+	let mut behaviors = vec![];
+	let mut pickups = vec![];
+	let mut boosts = vec![];
+	let mut tiles = None;
+	let mut acted = vec![];
+	for (i, line) in lines {
+		let bad = || format!("malformed line {}: {}", i + 1, line);
+		let (tag, rest) = line.split_once(' ').unwrap_or((line, ""));
+		let f: Vec<&str> = rest.split(' ').collect();
+		let num = |k: usize| f.get(k).and_then(|s| s.parse::<f32>().ok()).filter(|v| v.is_finite()).ok_or_else(bad);
+		let int = |k: usize| f.get(k).and_then(|s| s.parse::<i64>().ok()).ok_or_else(bad);
+		let byte = |k: usize| int(k).and_then(|v| if (0..=255).contains(&v) { Ok(v as u8) } else { Err(bad()) });
+		match tag {
+			"title" | "time" | "" => {},
+			"turn" => turn = int(0).and_then(|v| if v >= 1 && v <= u32::MAX as i64 { Ok(v as u32) } else { Err(bad()) })?,
+			"phase" => phase = if byte(0)? == 3 { TurnPhase::EnemyTurn } else { TurnPhase::PlayerTurn },
+			"camera" => cam = (num(0)?, num(1)?),
+			"bgm" => bgm = byte(0)?,
+			"stats" => stats = BattleStats {
+				turns: 0,
+				damage_dealt: num(0)?,
+				damage_received: num(1)?,
+				units_lost: int(2)? as u32,
+				enemies_defeated: int(3)? as u32,
+				xp_gained: int(4)? as u32
+			},
+			"difficulty" => difficulty = Difficulty { player_damage: num(0)?, enemy_damage: num(1)?, enemy_health: num(2)?, aggression: num(3)? },
+			"objective" => {
+				let (st, text) = rest.split_once(' ').unwrap_or((rest, ""));
+				let state = st.parse::<u8>().ok().and_then(ObjectiveState::from_raw).ok_or_else(bad)?;
+				objectives.push(Objective { text: unescape(text), state: state });
+			},
+			"puzzle" => {
+				let count = |k: usize| int(k).and_then(|v| if (0..=u32::MAX as i64).contains(&v) { Ok(v as u32) } else { Err(bad()) });
+				puzzle = Some((count(0)? as usize, count(1)?, ObjectiveState::from_raw(byte(2)?).ok_or_else(bad)?));
+			},
+			"tutorial" => tutorial = Some(rest.trim().parse::<usize>().map_err(|_| bad())?),
+			"unit" => {
+				let tid = byte(1)?;
+				if !w.unit_types.contains_key(&tid) {
+					return Err(format!("unit type {} is not registered (line {})", tid, i + 1));
+				}
+				let palette = match int(7)? { -1 => None, _ => Some(byte(7)?) };
+				let facing = world::u8_as_facing(byte(8)?).ok_or_else(bad)?;
+				units.push((byte(0)?, tid, num(2)?, Vector2::new(num(3)?, num(4)?), int(5)? as i32, int(6)? != 0, palette, facing));
+			},
+			"behavior" => {
+				let pos = |k: usize| int(k).and_then(|v| if v >= i32::MIN as i64 && v <= i32::MAX as i64 { Ok(v as i32) } else { Err(bad()) });
+				let b = match f.get(1) {
+					Some(&"hold") => Behavior::Hold,
+					Some(&"guard") => Behavior::Guard { tile: (pos(2)?, pos(3)?), radius: byte(4)? },
+					Some(&"patrol") if f.len() % 2 == 1 => Behavior::Patrol {
+						waypoints: (3..f.len()).step_by(2).map(|k| Ok((pos(k)?, pos(k + 1)?))).collect::<Result<_, String>>()?,
+						next: int(2)? as usize
+					},
+					_ => return Err(bad())
+				};
+				behaviors.push((byte(0)?, b));
+			},
+			"pickup" => {
+				let pos = |k: usize| int(k).and_then(|v| if v >= i32::MIN as i64 && v <= i32::MAX as i64 { Ok(v as i32) } else { Err(bad()) });
+				let effect = PickupEffect::from_raw(byte(3)?, num(4)?).ok_or_else(bad)?;
+				pickups.push(Pickup { tex_id: byte(0)?, tile: (pos(1)?, pos(2)?), effect: effect });
+			},
+			"boost" => boosts.push((byte(0)?, num(1)?)),
+			"tiles" => {
+				let hex = f.get(2).copied().unwrap_or("");
+				if (int(0)?, int(1)?) != (w.map_size().0 as i64, w.map_size().1 as i64) || !w.show_map() || hex.len() != 2 * w.map_tiles().len() {
+					return Err(format!("saved on another map (line {})", i + 1));
+				}
+				let t = (0..hex.len()).step_by(2).map(|k| hex.get(k..k + 2).and_then(|b| u8::from_str_radix(b, 16).ok())).collect::<Option<Vec<u8>>>();
+				tiles = Some(t.ok_or_else(bad)?);
+			},
+			"acted" => acted = (0..f.len()).map(byte).collect::<Result<_, String>>()?,
+			_ => return Err(bad())
+		}
+	}
+	w.units.clear();
+	w.projectiles.clear();
+	w.events.clear();
+	w.cam_follow = None;
+	w.tweens.pan = None;
+	for (id, tid, health, wpos, tint, player, palette, facing) in units {
+		w.units.insert(id, world::Unit::restored(tid, tint, wpos, player, health, palette, facing));
+	}
+	for (id, b) in behaviors {
+		world::set_behavior(w, id, Some(b));
+	}
+	for (id, b) in boosts {
+		if let Some(u) = w.units.get_mut(&id) {
+			u.boost = b;
+		}
+	}
+	w.pickups = pickups;
+	if let Some(t) = tiles {
+		w.set_map_tiles(t);
+	}
+	w.turn = turn;
+	w.cam_wx = cam.0;
+	w.cam_wy = cam.1;
+	w.set_bgm(bgm);
+	w.stats = stats;
+	w.set_difficulty(difficulty);
+	w.objectives = objectives;
+	if let (Some(p), Some((done, used, state))) = (&mut w.puzzle, puzzle) {
+		p.set_progress(done, used, state);
+	}
+	if let (Some(t), Some(done)) = (&mut w.tutorial, tutorial) {
+		t.set_progress(done);
+	}
+	acted.retain(|id| w.units.contains_key(id));
+	Ok((phase, acted))
+}
+
+/// Write text to a file without leaving it half-written if the game crashes, by writing a temporary file and renaming it.
+pub fn write_atomic(path: &str, text: &str) -> std::io::Result<()> {
+	write_atomic_bytes(path, text.as_bytes())
+}
+
+/// Write bytes to a file at once, see [write_atomic].
+pub(crate) fn write_atomic_bytes(path: &str, bytes: &[u8]) -> std::io::Result<()> {
+	let tmp = format!("{}.tmp", path);
+	std::fs::write(&tmp, bytes)?;
+	std::fs::rename(&tmp, path)
+}
+
+/// Autosave options of the [Display](crate::display::Display), see [Display::set_autosave](crate::display::Display::set_autosave).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Autosave {
+	/// Path of the autosave file.
+	pub path: String,
+	/// Number of turns between autosaves at the start of the player turn; 0 to disable.
+	pub every: u32,
+	/// Flag to also autosave as the enemy phase begins.
+	pub before_enemy: bool
+}
+
+impl Autosave {
+	/// Path of the file marking a session in progress; it is removed when the game shuts down normally.
+	pub fn lock_path(&self) -> String {
+		format!("{}.lock", self.path)
+	}
+
+	/// Returns the header of the autosave, if the previous session ended abnormally (i.e, did not shut down) and left one behind.
+	pub fn recoverable(&self) -> Option<SnapshotInfo> {
+		if !std::path::Path::new(&self.lock_path()).exists() {
+			return None;
+		}
+		std::fs::read_to_string(&self.path).ok().and_then(|t| peek(&t))
+	}
+
+	/// Mark a session in progress, returning the header of the autosave if the previous session has to be recovered.
+	pub(crate) fn begin_session(&self) -> Option<SnapshotInfo> {
+		let r = self.recoverable();
+		if let Err(e) = std::fs::write(self.lock_path(), "") {
+			eprintln!("warning [save]: failed to create {}: {}", self.lock_path(), e);
+		}
+		r
+	}
+
+	/// Returns true if the battle should be saved upon entering the phase `phase` from `prev`, on turn number `turn`.
+	pub(crate) fn due(&self, prev: u8, phase: u8, turn: u32) -> bool {
+		match (prev, phase) {
+			(3, 0) => turn.saturating_sub(1).checked_rem(self.every) == Some(0),
+			(_, 3) => self.before_enemy,
+			_ => false
+		}
+	}
+
+	/// Save a snapshot of the battle, warning on failure.
+	pub(crate) fn save(&self, w: &World, phase: TurnPhase) -> bool {
+		match write_atomic(&self.path, &snapshot(w, phase)) {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("warning [save]: failed to write autosave {}: {}", self.path, e);
+				false
+			}
+		}
+	}
+}
+
+/// Width of save slot thumbnails, in pixels; the height keeps the aspect ratio of the screen.
+pub const THUMB_WIDTH: i32 = 192;
+
+/// Metadata of a save slot, see [SaveSlots::list].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotInfo {
+	/// Number of the slot.
+	pub slot: u32,
+	/// Title of the map, time of saving and turn number.
+	pub header: SnapshotInfo,
+	/// Path of the thumbnail (a PNG image of the screen when the game was saved), if there is one.
+	pub thumbnail: Option<String>
+}
+
+/// Numbered save slots in a directory, for save and load menus.
+/// Slot `n` is stored as `slot<n>.sav`, with its thumbnail in `slot<n>.png`. Thumbnails are taken by [Runtime::save_slot](crate::display::Runtime::save_slot).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SaveSlots {
+	dir: String
+}
+
+impl SaveSlots {
+	/// Save slots in the directory `dir`, which is created on the first save.
+	pub fn new(dir: &str) -> SaveSlots {
+		SaveSlots { dir: dir.to_string() }
+	}
+
+	/// Path of the snapshot of a slot.
+	pub fn path(&self, slot: u32) -> String {
+		format!("{}/slot{}.sav", self.dir, slot)
+	}
+
+	/// Path of the thumbnail of a slot.
+	pub fn thumbnail_path(&self, slot: u32) -> String {
+		format!("{}/slot{}.png", self.dir, slot)
+	}
+
+	/// Save a snapshot of the battle to a slot, without a thumbnail (removing any old one). Returns false if it could not be written.
+	pub fn save(&self, w: &World, slot: u32) -> bool {
+		self.save_turn(w, slot, &[])
+	}
+
+	/// Save a snapshot of the battle to a slot, with the units that have acted in the turn.
+	pub(crate) fn save_turn(&self, w: &World, slot: u32, acted: &[u8]) -> bool {
+		let _ = std::fs::remove_file(self.thumbnail_path(slot));
+		let r = std::fs::create_dir_all(&self.dir).and_then(|_| write_atomic(&self.path(slot), &snapshot_turn(w, TurnPhase::PlayerTurn, acted)));
+		match r {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("warning [save]: failed to write slot {} in {}: {}", slot, self.dir, e);
+				false
+			}
+		}
+	}
+
+	/// Restore a slot before the game begins, see [World::load_battle]. Returns false if the slot is empty or invalid.
+	pub fn load(&self, w: &mut World, slot: u32) -> bool {
+		w.load_battle(&self.path(slot))
+	}
+
+	/// Returns the metadata of a slot, or None if it is empty (or not a valid snapshot).
+	pub fn info(&self, slot: u32) -> Option<SlotInfo> {
+		let header = peek(&std::fs::read_to_string(self.path(slot)).ok()?)?;
+		let thumb = self.thumbnail_path(slot);
+		Some(SlotInfo {
+			slot: slot,
+			header: header,
+			thumbnail: if std::path::Path::new(&thumb).is_file() { Some(thumb) } else { None }
+		})
+	}
+
+	/// Returns the metadata of all used slots, in order of slot number.
+	pub fn list(&self) -> Vec<SlotInfo> {
+		let mut slots: Vec<u32> = match std::fs::read_dir(&self.dir) {
+			Ok(rd) => rd.filter_map(|e| {
+				let name = e.ok()?.file_name().into_string().ok()?;
+				name.strip_prefix("slot")?.strip_suffix(".sav")?.parse().ok()
+			}).collect(),
+			Err(_) => vec![]
+		};
+		slots.sort_unstable();
+		slots.into_iter().filter_map(|s| self.info(s)).collect()
+	}
+
+	/// Delete a slot and its thumbnail. Returns false if the slot is empty, or could not be deleted.
+	pub fn delete(&self, slot: u32) -> bool {
+		let _ = std::fs::remove_file(self.thumbnail_path(slot));
+		std::fs::remove_file(self.path(slot)).is_ok()
+	}
+}
@@ -0,0 +1,96 @@
+//! Callbacks run by the engine loop after a delay, or repeatedly at an interval.
+//! Games schedule callbacks with [World::schedule_after] and [World::schedule_every], e.g, from the turn or update callbacks of a [StateListener](crate::utils::StateListener), instead of counting frame times themselves.
+//!
+//! Scheduled callbacks run once per frame at most, before the `on_update` callback; time does not pass for them while the game is paused.
+//! A repeating callback that falls behind (e.g, with an interval shorter than a frame) runs on the next frame, and keeps its interval from then on.
+
+use crate::world::World;
+use std::collections::VecDeque;
+use std::ffi::c_void;
+
+type TaskFn = Box<dyn FnMut(&mut World, u32)>;
+/// Type alias for nullable C ABI function pointer for callbacks scheduled through the native API, receiving the id returned when scheduled.
+/// # Safety
+/// The context pointer is owned by the call site, and is passed back as is.
+pub type CScheduleHandle = Option<extern "C" fn(*mut World, u32, *mut c_void)>;
+
+struct Task {
+	id: u32,
+	left: f32,
+	period: Option<f32>,
+	f: TaskFn
+}
+
+/// Callbacks scheduled on a world, identified by ids starting at 1.
+#[derive(Default)]
+pub(crate) struct Scheduler {
+	next: u32,
+	tasks: Vec<Task>,
+	/// Tasks due this frame, yet to run.
+	due: VecDeque<Task>,
+	/// The id of the task running, and whether it has been cancelled by its own callback.
+	running: Option<(u32, bool)>
+}
+
+impl Scheduler {
+	/// Schedule `f` to run after `secs` seconds, and then every `period` seconds if specified. Durations that are negative (or not finite) are taken as 0. Returns the id of the task.
+	pub(crate) fn add(&mut self, secs: f32, period: Option<f32>, f: TaskFn) -> u32 {
+		let clean = |t: f32| if t.is_finite() { t.max(0.0) } else { 0.0 };
+		self.next = self.next.checked_add(1).unwrap_or(1);
+		self.tasks.push(Task { id: self.next, left: clean(secs), period: period.map(clean), f: f });
+		self.next
+	}
+
+	/// Cancel the task with the specified id. Returns false if there is no such task, e.g, if it has already run (and does not repeat).
+	pub(crate) fn cancel(&mut self, id: u32) -> bool {
+		if let Some(i) = self.tasks.iter().position(|t| t.id == id) {
+			self.tasks.remove(i);
+			return true;
+		}
+		if let Some(i) = self.due.iter().position(|t| t.id == id) {
+			self.due.remove(i);
+			return true;
+		}
+		match &mut self.running {
+			Some((r, cancelled)) if *r == id && !*cancelled => {
+				*cancelled = true;
+				true
+			},
+			_ => false
+		}
+	}
+
+	/// Returns the time left (in seconds) before the task with the specified id runs next, if it is scheduled.
+	pub(crate) fn time_left(&self, id: u32) -> Option<f32> {
+		match self.running {
+			Some((r, false)) if r == id => return Some(0.0),
+			_ => {}
+		}
+		self.tasks.iter().chain(self.due.iter()).find(|t| t.id == id).map(|t| t.left.max(0.0))
+	}
+}
+
+/// Run the callbacks due after `dt` seconds.
+pub(crate) fn run(w: &mut World, dt: f32) {
+	let s = &mut w.schedule;
+	let dt = if dt.is_finite() { dt.max(0.0) } else { 0.0 };
+	let mut i = 0;
+	while i < s.tasks.len() {
+		s.tasks[i].left -= dt;
+		if s.tasks[i].left <= 0.0 {
+			let t = s.tasks.remove(i);
+			s.due.push_back(t);
+		} else {
+			i += 1;
+		}
+	}
+	while let Some(mut t) = w.schedule.due.pop_front() {
+		w.schedule.running = Some((t.id, false));
+		(t.f)(w, t.id);
+		let cancelled = matches!(w.schedule.running.take(), Some((_, true)));
+		if let (Some(p), false) = (t.period, cancelled) {
+			t.left = (t.left + p).max(0.0);
+			w.schedule.tasks.push(t);
+		}
+	}
+}
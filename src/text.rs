@@ -0,0 +1,251 @@
+//! Styled, word-wrapped text for HUD panels and message windows.
+//! Strings may contain a tiny markup language:
+//! * `[red]...[/red]` - draw the enclosed text in a colour; either a name (red, green, blue, yellow, orange, purple, white, black, gray) or hex, e.g, `[#ff8000]`.
+//! * `[b]...[/b]` - draw the enclosed text in bold.
+//! * `{icon:3}` - draw the texture (or texture region) with id 3 inline, scaled to the height of the text.
+//!
+//! Unknown tags are drawn verbatim. Closing tags close the innermost open tag, regardless of their name.
+//! # Example
+//! ```
+//! use alesia::text::{parse, Span};
+//! use raylib::prelude::Color;
+//! let spans = parse("HP [red]12[/red] {icon:3}", Color::BLACK);
+//! assert_eq!(spans[1], Span::Text("12".to_string(), Color::RED, false));
+//! assert_eq!(spans[3], Span::Icon(3));
+//! ```
+
+use raylib::prelude::Color;
+use raylib::prelude::RaylibDraw;
+use raylib::prelude::RaylibTexture2D;
+use raylib::math::Rectangle;
+use raylib::math::Vector2;
+use crate::utils::ResourceSet;
+
+/// Spacing between characters of styled text.
+const SPACING: f32 = 1.0;
+/// Height of a line of styled text, relative to the font size.
+const LINE_SPACING: f32 = 1.2;
+/// Horizontal padding around inline icons, in pixels.
+const ICON_PAD: f32 = 2.0;
+
+/// A run of text with uniform style, an inline icon, or a line break.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Span {
+	/// Text, its colour, and whether it is bold.
+	Text(String, Color, bool),
+	/// Texture (or texture region) id of an inline icon.
+	Icon(u8),
+	/// Explicit line break.
+	Break
+}
+
+fn named_color(name: &str) -> Option<Color> {
+	Some(match name {
+		"red" => Color::RED,
+		"green" => Color::DARKGREEN,
+		"blue" => Color::BLUE,
+		"yellow" => Color::GOLD,
+		"orange" => Color::ORANGE,
+		"purple" => Color::PURPLE,
+		"white" => Color::WHITE,
+		"black" => Color::BLACK,
+		"gray" | "grey" => Color::GRAY,
+		_ => {
+			let hex = name.strip_prefix('#')?;
+			if hex.len() != 6 {
+				return None;
+			}
+			let v = u32::from_str_radix(hex, 16).ok()?;
+			Color::new((v >> 16) as u8, (v >> 8) as u8, v as u8, 255)
+		}
+	})
+}
+
+enum Tag {
+	Color(Color),
+	Bold
+}
+
+/// Parse markup into spans, with `base` as the colour of unstyled text.
+pub fn parse(src: &str, base: Color) -> Vec<Span> {
+	let mut spans = vec![];
+	let mut stack: Vec<Tag> = vec![];
+	let mut buf = String::new();
+	let style = |stack: &Vec<Tag>| {
+		let col = stack.iter().rev().find_map(|t| match t { Tag::Color(c) => Some(*c), _ => None }).unwrap_or(base);
+		(col, stack.iter().any(|t| matches!(t, Tag::Bold)))
+	};
+	let mut rest = src;
+	while let Some(c) = rest.chars().next() {
+		let (tag, len) = match c {
+			'[' => match rest.find(']') {
+				Some(end) => {
+					let name = &rest[1..end];
+					if name.starts_with('/') {
+						(Some(None), end + 1)
+					} else if name == "b" {
+						(Some(Some(Tag::Bold)), end + 1)
+					} else {
+						match named_color(name) {
+							Some(col) => (Some(Some(Tag::Color(col))), end + 1),
+							None => (None, 1)
+						}
+					}
+				},
+				None => (None, 1)
+			},
+			'{' => match (rest.find('}'), rest.strip_prefix("{icon:")) {
+				(Some(end), Some(_)) => match rest[6..end].trim().parse::<u8>() {
+					Ok(id) => {
+						if !buf.is_empty() {
+							let (col, bold) = style(&stack);
+							spans.push(Span::Text(std::mem::take(&mut buf), col, bold));
+						}
+						spans.push(Span::Icon(id));
+						rest = &rest[end+1..];
+						continue;
+					},
+					Err(_) => (None, 1)
+				},
+				_ => (None, 1)
+			},
+			'\n' => {
+				if !buf.is_empty() {
+					let (col, bold) = style(&stack);
+					spans.push(Span::Text(std::mem::take(&mut buf), col, bold));
+				}
+				spans.push(Span::Break);
+				rest = &rest[1..];
+				continue;
+			},
+			_ => (None, c.len_utf8())
+		};
+		match tag {
+			Some(t) => {
+				if !buf.is_empty() {
+					let (col, bold) = style(&stack);
+					spans.push(Span::Text(std::mem::take(&mut buf), col, bold));
+				}
+				match t {
+					Some(t) => stack.push(t),
+					None => { stack.pop(); }
+				}
+			},
+			None => buf.push_str(&rest[..len])
+		}
+		rest = &rest[len..];
+	}
+	if !buf.is_empty() {
+		let (col, bold) = style(&stack);
+		spans.push(Span::Text(buf, col, bold));
+	}
+	spans
+}
+
+/// Returns the text of markup without its tags and icons, e.g, for [narration](crate::narration). Line breaks are kept.
+pub fn plain(src: &str) -> String {
+	let mut s = String::new();
+	for sp in parse(src, Color::BLACK) {
+		match sp {
+			Span::Text(t, ..) => s.push_str(&t),
+			Span::Break => s.push('\n'),
+			Span::Icon(_) => {}
+		}
+	}
+	s
+}
+
+/// Break spans into words and icons, and position them (relative to the top-left corner) so that no line exceeds `max_width`.
+/// `measure` returns the width of a piece of text; icons are `size` pixels wide. If `center` is set, each line is centred.
+/// A single word wider than `max_width` is placed on its own line, and overflows it.
+pub fn layout(spans: &[Span], measure: impl Fn(&str) -> f32, size: f32, max_width: f32, center: bool) -> Vec<(Span, Vector2)> {
+	let mut out: Vec<(Span, Vector2)> = vec![];
+	let (mut x, mut y) = (0.0f32, 0.0f32);
+	let mut line_start = 0;
+	let mut line_width = 0.0f32;
+	let line_height = size * LINE_SPACING;
+	let finish_line = |out: &mut Vec<(Span, Vector2)>, start: usize, width: f32| {
+		if center {
+			let dx = 0.5 * (max_width - width).max(0.0);
+			for (_, p) in out[start..].iter_mut() {
+				p.x += dx;
+			}
+		}
+	};
+	for span in spans {
+		match span {
+			Span::Break => {
+				finish_line(&mut out, line_start, line_width);
+				line_start = out.len();
+				x = 0.0;
+				line_width = 0.0;
+				y += line_height;
+			},
+			Span::Icon(_) => {
+				let w = size + 2.0*ICON_PAD;
+				if x > 0.0 && x + w > max_width {
+					finish_line(&mut out, line_start, line_width);
+					line_start = out.len();
+					x = 0.0;
+					y += line_height;
+				}
+				out.push((span.clone(), Vector2::new(x + ICON_PAD, y)));
+				x += w;
+				line_width = x;
+			},
+			Span::Text(s, col, bold) => {
+				for word in s.split_inclusive(' ') {
+					let trimmed = word.trim_end_matches(' ');
+					let w = measure(trimmed);
+					if x > 0.0 && x + w > max_width {
+						finish_line(&mut out, line_start, line_width);
+						line_start = out.len();
+						x = 0.0;
+						y += line_height;
+					}
+					out.push((Span::Text(word.to_string(), *col, *bold), Vector2::new(x, y)));
+					line_width = x + w;
+					x += if trimmed.len() == word.len() { w } else { measure(word) };
+				}
+			}
+		}
+	}
+	finish_line(&mut out, line_start, line_width);
+	out
+}
+
+/// Height of styled text drawn with the default font, wrapped to `max_width`.
+pub fn measure_styled(rs: &ResourceSet, text: &str, size: f32, max_width: f32) -> f32 {
+	let fnt = rs.get_default_font_sized(size);
+	let measure = |s: &str| raylib::core::text::measure_text_ex(fnt, s, size, SPACING).x;
+	layout(&parse(text, Color::BLACK), measure, size, max_width, false).last().map_or(0.0, |(_, p)| p.y + size * LINE_SPACING)
+}
+
+/// Draw styled text using the default font, wrapped to `max_width`, with its top-left corner at `pos`.
+pub fn draw_styled(d: &mut impl RaylibDraw, rs: &ResourceSet, text: &str, pos: Vector2, size: f32, max_width: f32, base: Color, center: bool) {
+	let fnt = rs.get_default_font_sized(size);
+	let measure = |s: &str| raylib::core::text::measure_text_ex(fnt, s, size, SPACING).x;
+	for (span, p) in layout(&parse(text, base), measure, size, max_width, center) {
+		let p = pos + p;
+		match span {
+			Span::Text(s, col, bold) => {
+				d.draw_text_ex(fnt, &s, p, size, SPACING, col);
+				if bold {
+					d.draw_text_ex(fnt, &s, p + Vector2::new(1.0, 0.0), size, SPACING, col);
+				}
+			},
+			Span::Icon(id) => {
+				let (tex, src) = if rs.is_texture_region(id) {
+					let (tex, rec) = rs.get_texture_region(id);
+					(tex, *rec)
+				} else {
+					let tex = rs.get_texture(id);
+					(tex, Rectangle::new(0.0, 0.0, tex.width() as f32, tex.height() as f32))
+				};
+				let dst = Rectangle::new(p.x, p.y, size, size);
+				d.draw_texture_pro(tex, src, dst, Vector2::zero(), 0.0, Color::WHITE);
+			},
+			Span::Break => {}
+		}
+	}
+}
@@ -0,0 +1,127 @@
+//! Touch input: gestures recognized from the points touching the screen, once enabled with [Display::set_touch_input](crate::display::Display::set_touch_input).
+//!
+//! | Gesture | Effect |
+//! | ------- | ------ |
+//! | Tap | As a left click, i.e, select a unit, or the tile to move to or attack. |
+//! | Drag | Pan the camera. |
+//! | Pinch | Zoom the camera, about the middle of the fingers, see [World::set_zoom](crate::world::World::set_zoom). |
+//! | Long-press | As a right click, i.e, show or hide unit information. |
+//!
+//! While touch input is enabled, the left mouse button is read as a single finger, where the platform does not report touch points; so gestures may be tried with a mouse.
+//! Buttons of the HUD, e.g, Wait, are pressed as before; a contact beginning on them is not passed on.
+//! # Example
+//! ```
+//! use alesia::touch::{Gesture, TouchTracker};
+//! use raylib::math::Vector2;
+//! let mut t = TouchTracker::default();
+//! let p = Vector2::new(40.0, 30.0);
+//! assert_eq!(t.update(0.1, &[p]), None);
+//! assert_eq!(t.update(0.1, &[]), Some(Gesture::Tap(p)));
+//! ```
+
+use raylib::math::Vector2;
+
+/// Distance (in pixels) a finger moves before a contact is a drag rather than a tap.
+pub const TAP_SLOP: f32 = 12.0;
+/// Time (in seconds) a finger is held still before a contact is a long-press.
+pub const LONG_PRESS_TIME: f32 = 0.5;
+
+/// A gesture recognized from touch points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+	/// A finger was lifted soon after touching the screen, without moving, at the specified position.
+	Tap(Vector2),
+	/// A finger has been held still at the specified position.
+	LongPress(Vector2),
+	/// A finger moved by the specified distance (in pixels) since the last frame.
+	Pan(Vector2),
+	/// Two fingers moved apart (or together) since the last frame.
+	Pinch {
+		/// Ratio of the distance between the fingers to that on the last frame.
+		factor: f32,
+		/// Point midway between the fingers.
+		centre: Vector2
+	}
+}
+
+/// State of the fingers touching the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Contact {
+	/// No finger touches the screen.
+	Up,
+	/// A finger touches the screen where it began, and for how long.
+	Press(Vector2, f32),
+	/// A finger is dragged, last seen at the specified position.
+	Drag(Vector2),
+	/// Two fingers touch the screen, the specified distance apart.
+	Pinch(f32),
+	/// The contact has been handled, and is ignored until all fingers are lifted.
+	Spent
+}
+
+/// Recognizes gestures from the points touching the screen, frame by frame.
+#[derive(Debug, Clone, Copy)]
+pub struct TouchTracker {
+	contact: Contact
+}
+
+impl Default for TouchTracker {
+	fn default() -> TouchTracker {
+		TouchTracker { contact: Contact::Up }
+	}
+}
+
+impl TouchTracker {
+	/// Update with the points touching the screen this frame, `dt` seconds after the last. Returns the gesture recognized, if any.
+	/// Only the first two points are read.
+	pub fn update(&mut self, dt: f32, points: &[Vector2]) -> Option<Gesture> {
+		match (points, self.contact) {
+			([], Contact::Press(p, _)) => {
+				self.contact = Contact::Up;
+				Some(Gesture::Tap(p))
+			},
+			([], _) => {
+				self.contact = Contact::Up;
+				None
+			},
+			([a, b, ..], c) => {
+				let d = a.distance_to(*b);
+				self.contact = Contact::Pinch(d);
+				match c {
+					Contact::Pinch(last) if last > 0.0 && d > 0.0 => Some(Gesture::Pinch { factor: d / last, centre: (*a + *b) * 0.5 }),
+					_ => None
+				}
+			},
+			([p], Contact::Up) => {
+				self.contact = Contact::Press(*p, 0.0);
+				None
+			},
+			([p], Contact::Press(s, held)) => {
+				if p.distance_to(s) >= TAP_SLOP {
+					self.contact = Contact::Drag(*p);
+					Some(Gesture::Pan(*p - s))
+				} else if held + dt >= LONG_PRESS_TIME {
+					self.contact = Contact::Spent;
+					Some(Gesture::LongPress(s))
+				} else {
+					self.contact = Contact::Press(s, held + dt);
+					None
+				}
+			},
+			([p], Contact::Drag(last)) => {
+				self.contact = Contact::Drag(*p);
+				Some(Gesture::Pan(*p - last))
+			},
+			// Lifting one finger of a pinch does not start a drag.
+			([_], Contact::Pinch(_)) | ([_], Contact::Spent) => {
+				self.contact = Contact::Spent;
+				None
+			}
+		}
+	}
+
+	/// Ignore the fingers touching the screen until they are lifted, e.g, once a button of the HUD has been pressed.
+	pub fn cancel(&mut self) {
+		self.contact = Contact::Spent;
+	}
+}
@@ -0,0 +1,276 @@
+//! Scripted tutorials, i.e, battles where the player is walked through a sequence of instructions, with input restricted to what each asks for.
+//! A tutorial is defined in a plain text file, one record per line; blank lines and lines starting with `#` are ignored.
+//! Each step begins with a `step` record, and is followed by the records of the step.
+//!
+//! | Record | Meaning |
+//! | ------ | ------- |
+//! | `step <text>` | begin a step, showing the instruction `text` (which may be [styled](crate::text)) |
+//! | `goal select <unit>` | the step is done once the unit with id `unit` is selected |
+//! | `goal move <unit> <x> <y>` | the step is done once the unit is ordered onto (`x`, `y`) |
+//! | `goal attack <unit> <target>` | the step is done once the unit is ordered to attack the unit with id `target` |
+//! | `goal wait <unit>` | the step is done once the unit waits |
+//! | `goal end_turn` | the step is done once the player turn ends |
+//! | `allow tile <x> <y>` | clicks on (`x`, `y`) are accepted |
+//! | `allow unit <id>` | clicks on the unit with id `id` are accepted |
+//! | `allow <control>` | the control is accepted: `cancel`, `wait`, `end_turn` or `info` (the unit information panel) |
+//! | `allow all` | input is not restricted during the step |
+//! | `highlight tile <x> <y>` | the tile is highlighted |
+//! | `highlight unit <id>` | the unit is highlighted |
+//! | `highlight ui <element>` | an element of the HUD is highlighted: `panel` (the unit panel), `wait` (the Wait button), `log`, `minimap` or `hints` (the control hints bar) |
+//!
+//! A step without a goal is done once the player clicks (or taps) anywhere.
+//! During a step, clicks are accepted only on the tiles and units allowed, and those of the goal, i.e, the unit to select, the tile to move onto, or the unit to attack;
+//! keys and buttons are accepted only if allowed, or needed for the goal. Camera controls, and toggles of the HUD, are always accepted.
+//! Units cannot be selected together, unless the step allows all input. Once the last step is done, input is no longer restricted.
+//!
+//! While a tutorial is loaded (see [World::load_tutorial](crate::world::World::load_tutorial)), the instruction of the current step is shown at the bottom of the screen,
+//! and a [TutorialStep](crate::event::EngineEvent::TutorialStep) event is published as each step begins.
+//! # Example
+//! ```
+//! use alesia::tutorial::{Tutorial, TutorialControl, TutorialGoal};
+//! let mut t = Tutorial::parse("# Moving units.\nstep Click the [b]knight[/b].\ngoal select 1\nstep Move it forward.\ngoal move 1 3 4\nallow tile 2 4\n").unwrap();
+//! assert!(!t.allows_tile((2, 4), None));
+//! assert!(t.act(TutorialGoal::Select(1)));
+//! assert!(t.allows_tile((2, 4), None) && !t.allows(TutorialControl::Cancel));
+//! assert!(t.act(TutorialGoal::Move(1, (3, 4))));
+//! assert!(t.is_done());
+//! ```
+
+/// What the player must do to finish a step of a tutorial; also, the actions of the player checked against it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialGoal {
+	/// Click (or tap) anywhere.
+	Continue,
+	/// Select the unit with the specified id.
+	Select(u8),
+	/// Order the unit with the specified id onto the tile.
+	Move(u8, (i32, i32)),
+	/// Order the unit with the specified id to attack the second.
+	Attack(u8, u8),
+	/// End the action of the unit with the specified id, see [KeyBindings::wait](crate::input::KeyBindings).
+	Wait(u8),
+	/// End the player turn.
+	EndTurn
+}
+
+/// Controls that may be restricted by a tutorial, besides clicks on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TutorialControl {
+	/// Deselect the current unit, or take back its orders.
+	Cancel,
+	/// End the action of the selected unit, with the key or the Wait button.
+	Wait,
+	/// End the player turn.
+	EndTurn,
+	/// Show or hide the unit information panel.
+	Info
+}
+
+/// Elements of the HUD that may be highlighted by a tutorial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiElement {
+	/// The unit panel, i.e, the portrait, name and health of the selected unit.
+	UnitPanel,
+	/// The Wait button of the unit panel.
+	WaitButton,
+	/// The battle log.
+	Log,
+	/// The minimap.
+	Minimap,
+	/// The control hints bar, see [Display::set_control_hints](crate::display::Display::set_control_hints).
+	ControlHints
+}
+
+/// Something highlighted during a step of a tutorial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+	/// The tile at the specified co-ordinates.
+	Tile((i32, i32)),
+	/// The unit with the specified id.
+	Unit(u8),
+	/// An element of the HUD, highlighted while shown.
+	Ui(UiElement)
+}
+
+/// A step of a tutorial: an instruction, what finishes it, and the input accepted meanwhile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TutorialStep {
+	/// The instruction shown.
+	pub text: String,
+	/// What the player must do to finish the step.
+	pub goal: TutorialGoal,
+	/// Tiles clicks are accepted on, besides that of the goal.
+	pub tiles: Vec<(i32, i32)>,
+	/// Units clicks are accepted on, besides those of the goal.
+	pub units: Vec<u8>,
+	/// Controls accepted, besides those of the goal.
+	pub controls: Vec<TutorialControl>,
+	/// Flag to accept all input during the step.
+	pub free: bool,
+	/// Tiles, units and elements of the HUD highlighted.
+	pub highlights: Vec<Highlight>
+}
+
+impl TutorialStep {
+	/// Constructor method. Returns a step showing `text`, done once `goal` is met, accepting only the input of the goal.
+	pub fn new(text: &str, goal: TutorialGoal) -> TutorialStep {
+		TutorialStep {
+			text: text.to_string(),
+			goal: goal,
+			tiles: vec![],
+			units: vec![],
+			controls: vec![],
+			free: false,
+			highlights: vec![]
+		}
+	}
+}
+
+/// A tutorial definition, with the progress of the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tutorial {
+	steps: Vec<TutorialStep>,
+	done: usize
+}
+
+impl Tutorial {
+	/// Constructor method. Returns a tutorial with the specified steps.
+	pub fn new(steps: Vec<TutorialStep>) -> Tutorial {
+		Tutorial {
+			steps: steps,
+			done: 0
+		}
+	}
+
+	/// Parse a tutorial definition. Returns an error message if malformed.
+	pub fn parse(text: &str) -> Result<Tutorial, String> {
+		let mut steps: Vec<TutorialStep> = vec![];
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			let bad = || format!("malformed record on line {}: {}", i + 1, line);
+			if let Some(t) = line.strip_prefix("step") {
+				if !t.is_empty() && !t.starts_with(char::is_whitespace) {
+					return Err(bad());
+				}
+				steps.push(TutorialStep::new(t.trim(), TutorialGoal::Continue));
+				continue;
+			}
+			let s = steps.last_mut().ok_or_else(|| format!("record before the first step on line {}: {}", i + 1, line))?;
+			let f: Vec<&str> = line.split_whitespace().collect();
+			let id = |k: usize| f[k].parse::<u8>().map_err(|_| bad());
+			let tile = |k: usize| -> Result<(i32, i32), String> { Ok((f[k].parse().map_err(|_| bad())?, f[k + 1].parse().map_err(|_| bad())?)) };
+			match (f[0], f.get(1).copied(), f.len()) {
+				("goal", Some("select"), 3) => s.goal = TutorialGoal::Select(id(2)?),
+				("goal", Some("move"), 5) => s.goal = TutorialGoal::Move(id(2)?, tile(3)?),
+				("goal", Some("attack"), 4) => s.goal = TutorialGoal::Attack(id(2)?, id(3)?),
+				("goal", Some("wait"), 3) => s.goal = TutorialGoal::Wait(id(2)?),
+				("goal", Some("end_turn"), 2) => s.goal = TutorialGoal::EndTurn,
+				("allow", Some("tile"), 4) => s.tiles.push(tile(2)?),
+				("allow", Some("unit"), 3) => s.units.push(id(2)?),
+				("allow", Some("all"), 2) => s.free = true,
+				("allow", Some(c), 2) => s.controls.push(match c {
+					"cancel" => TutorialControl::Cancel,
+					"wait" => TutorialControl::Wait,
+					"end_turn" => TutorialControl::EndTurn,
+					"info" => TutorialControl::Info,
+					_ => return Err(bad())
+				}),
+				("highlight", Some("tile"), 4) => s.highlights.push(Highlight::Tile(tile(2)?)),
+				("highlight", Some("unit"), 3) => s.highlights.push(Highlight::Unit(id(2)?)),
+				("highlight", Some("ui"), 3) => s.highlights.push(Highlight::Ui(match f[2] {
+					"panel" => UiElement::UnitPanel,
+					"wait" => UiElement::WaitButton,
+					"log" => UiElement::Log,
+					"minimap" => UiElement::Minimap,
+					"hints" => UiElement::ControlHints,
+					_ => return Err(bad())
+				})),
+				_ => return Err(bad())
+			}
+		}
+		if steps.is_empty() {
+			return Err("tutorial has no steps".to_string());
+		}
+		Ok(Tutorial::new(steps))
+	}
+
+	/// Read and parse the tutorial definition at `path`.
+	pub fn load(path: &str) -> Result<Tutorial, String> {
+		let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+		Tutorial::parse(&text)
+	}
+
+	/// Returns the sequence of steps.
+	pub fn steps(&self) -> &[TutorialStep] {
+		&self.steps
+	}
+
+	/// Returns the number of steps done.
+	pub fn steps_done(&self) -> usize {
+		self.done
+	}
+
+	/// Returns the step being shown, if any.
+	pub fn current(&self) -> Option<&TutorialStep> {
+		self.steps.get(self.done)
+	}
+
+	/// Returns true once every step is done.
+	pub fn is_done(&self) -> bool {
+		self.done >= self.steps.len()
+	}
+
+	/// Check an action of the player against the goal of the current step, moving on to the next step if met. Returns true if it was.
+	pub fn act(&mut self, a: TutorialGoal) -> bool {
+		if self.current().map(|s| s.goal) != Some(a) {
+			return false;
+		}
+		self.done += 1;
+		true
+	}
+
+	/// Returns true if a click on `tile` (where the unit with id `unit` stands, if any) is accepted in the current step.
+	pub fn allows_tile(&self, tile: (i32, i32), unit: Option<u8>) -> bool {
+		let s = match self.current() { Some(s) => s, None => return true };
+		let goal = match s.goal {
+			TutorialGoal::Select(u) | TutorialGoal::Attack(_, u) => unit == Some(u),
+			TutorialGoal::Move(_, t) => tile == t,
+			_ => false
+		};
+		s.free || goal || s.tiles.contains(&tile) || unit.iter().any(|u| s.units.contains(u))
+	}
+
+	/// Returns true if the control is accepted in the current step.
+	pub fn allows(&self, c: TutorialControl) -> bool {
+		let s = match self.current() { Some(s) => s, None => return true };
+		let goal = match s.goal {
+			TutorialGoal::Wait(_) => c == TutorialControl::Wait,
+			TutorialGoal::EndTurn => c == TutorialControl::EndTurn,
+			_ => false
+		};
+		s.free || goal || s.controls.contains(&c)
+	}
+
+	/// Returns true if units may be selected together in the current step, i.e, once done, or if it accepts all input.
+	pub fn allows_grouping(&self) -> bool {
+		self.current().iter().all(|s| s.free)
+	}
+
+	/// Returns the highlights of the current step.
+	pub fn highlights(&self) -> &[Highlight] {
+		self.current().map_or(&[], |s| &s.highlights)
+	}
+
+	/// Returns true if the current step highlights the element of the HUD.
+	pub fn highlights_ui(&self, e: UiElement) -> bool {
+		self.highlights().contains(&Highlight::Ui(e))
+	}
+
+	/// Restore the progress of the player, e.g, from a save.
+	pub(crate) fn set_progress(&mut self, done: usize) {
+		self.done = done.min(self.steps.len());
+	}
+}
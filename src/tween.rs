@@ -0,0 +1,169 @@
+//! Tweens, i.e, values animated from a start to an end value over a duration, along an easing curve.
+//! The engine uses tweens for camera pans (see [World::pan_camera]), the phase banner and the popups rising from units.
+//! Games may animate their own values with [World::tween], which are advanced once per frame along with the engine, and read back with [World::tween_value].
+//! # Example
+//! ```
+//! use alesia::tween::{Tween, Easing};
+//! let mut t = Tween::new(0.0, 10.0, 2.0, Easing::Linear);
+//! assert!(!t.update(1.0));
+//! assert_eq!(t.value(), 5.0);
+//! assert!(t.update(1.5));
+//! assert_eq!(t.value(), 10.0);
+//! ```
+
+use crate::world::World;
+use std::ffi::c_void;
+
+/// Easing curves, mapping the progress of a tween in [0, 1] to the fraction of the change in value.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+	/// Constant rate.
+	Linear = 0,
+	/// Starts slow, and accelerates.
+	QuadIn = 1,
+	/// Starts fast, and decelerates.
+	QuadOut = 2,
+	/// Starts and ends slow.
+	QuadInOut = 3,
+	/// Starts fast, and decelerates more sharply than [QuadOut](Easing::QuadOut).
+	CubicOut = 4,
+	/// Smoothstep; starts and ends slow, more gently than [QuadInOut](Easing::QuadInOut).
+	Smooth = 5,
+	/// Overshoots the end value slightly, and settles back.
+	BackOut = 6
+}
+
+impl Easing {
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<Easing> {
+		match v {
+			0 => Some(Easing::Linear),
+			1 => Some(Easing::QuadIn),
+			2 => Some(Easing::QuadOut),
+			3 => Some(Easing::QuadInOut),
+			4 => Some(Easing::CubicOut),
+			5 => Some(Easing::Smooth),
+			6 => Some(Easing::BackOut),
+			_ => None
+		}
+	}
+
+	/// Returns the eased fraction at progress `t`, which is clamped to [0, 1]. Every curve maps 0 to 0 and 1 to 1.
+	pub fn apply(self, t: f32) -> f32 {
+		let t = t.clamp(0.0, 1.0);
+		match self {
+			Easing::Linear => t,
+			Easing::QuadIn => t * t,
+			Easing::QuadOut => t * (2.0 - t),
+			Easing::QuadInOut => if t < 0.5 { 2.0 * t * t } else { 1.0 - 2.0 * (1.0 - t) * (1.0 - t) },
+			Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+			Easing::Smooth => t * t * (3.0 - 2.0 * t),
+			Easing::BackOut => {
+				let (c, u) = (1.70158, t - 1.0);
+				1.0 + u * u * ((c + 1.0) * u + c)
+			}
+		}
+	}
+}
+
+/// A value animated from `from` to `to` over `duration` seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tween {
+	/// Start value.
+	pub from: f32,
+	/// End value.
+	pub to: f32,
+	/// Duration in seconds.
+	pub duration: f32,
+	/// Easing curve.
+	pub easing: Easing,
+	elapsed: f32
+}
+
+impl Tween {
+	/// Constructor method. A tween with a duration that is not positive (or not finite) is complete at once.
+	pub fn new(from: f32, to: f32, duration: f32, easing: Easing) -> Tween {
+		Tween {
+			from: from,
+			to: to,
+			duration: if duration.is_finite() { duration.max(0.0) } else { 0.0 },
+			easing: easing,
+			elapsed: 0.0
+		}
+	}
+
+	/// Advance the tween by `dt` seconds. Returns true once complete.
+	pub fn update(&mut self, dt: f32) -> bool {
+		if dt.is_finite() && dt > 0.0 {
+			self.elapsed = (self.elapsed + dt).min(self.duration);
+		}
+		self.is_done()
+	}
+
+	/// Returns the progress of the tween in [0, 1], before easing.
+	pub fn progress(&self) -> f32 {
+		if self.duration > 0.0 { self.elapsed / self.duration } else { 1.0 }
+	}
+
+	/// Returns the current value.
+	pub fn value(&self) -> f32 {
+		self.from + (self.to - self.from) * self.easing.apply(self.progress())
+	}
+
+	/// Returns true once the tween has run for its duration.
+	pub fn is_done(&self) -> bool {
+		self.elapsed >= self.duration
+	}
+}
+
+type TweenDone = Box<dyn FnMut(&mut World, u32)>;
+/// Type alias for nullable C ABI function pointer invoked once a tween added through the native API is complete, with the id of the tween.
+/// # Safety
+/// The context pointer is owned by the call site, and is passed back as is.
+pub type CTweenHandle = Option<extern "C" fn(*mut World, u32, *mut c_void)>;
+
+/// Tweens added by the game, identified by ids starting at 1, with the functions invoked once they are complete.
+#[derive(Default)]
+pub(crate) struct TweenSet {
+	next: u32,
+	active: Vec<(u32, Tween, Option<TweenDone>)>,
+	/// Camera pan in progress, if any, see [World::pan_camera].
+	pub(crate) pan: Option<(Tween, Tween)>
+}
+
+impl TweenSet {
+	/// Add a tween, returning its id.
+	pub(crate) fn add(&mut self, t: Tween, on_complete: Option<TweenDone>) -> u32 {
+		self.next = self.next.checked_add(1).unwrap_or(1);
+		self.active.push((self.next, t, on_complete));
+		self.next
+	}
+
+	/// Returns the tween with the specified id, if it has not yet completed or been cancelled.
+	pub(crate) fn get(&self, id: u32) -> Option<&Tween> {
+		self.active.iter().find(|(i, ..)| *i == id).map(|(_, t, _)| t)
+	}
+
+	/// Remove the tween with the specified id without invoking its function. Returns false if there is no such tween.
+	pub(crate) fn cancel(&mut self, id: u32) -> bool {
+		let n = self.active.len();
+		self.active.retain(|(i, ..)| *i != id);
+		self.active.len() != n
+	}
+
+	/// Advance all tweens by `dt` seconds, removing the ones that completed and returning their ids and functions, in order of addition.
+	pub(crate) fn advance(&mut self, dt: f32) -> Vec<(u32, Option<TweenDone>)> {
+		let mut done = vec![];
+		let mut i = 0;
+		while i < self.active.len() {
+			if self.active[i].1.update(dt) {
+				let (id, _, f) = self.active.remove(i);
+				done.push((id, f));
+			} else {
+				i += 1;
+			}
+		}
+		done
+	}
+}
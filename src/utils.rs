@@ -2,19 +2,98 @@
 
 use raylib::math::Rectangle;
 use raylib::audio::Music;
+use raylib::audio::RaylibAudio;
 use crate::input::Order;
+use crate::input::TurnPhase;
+use crate::input::Outcome;
+use crate::input::KeyBindings;
+use crate::world::pickup::PickupEffect;
+use crate::event::EngineEvent;
+use crate::display::Display;
+use raylib::drawing::RaylibDrawHandle;
 use raylib::text::Font;
+use raylib::text::FontLoadEx;
 use raylib::RaylibThread;
 use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
 use raylib::RaylibHandle;
 use raylib::prelude::Texture2D;
+use raylib::prelude::RaylibTexture2D;
 use raylib::prelude::Sound;
+use raylib::prelude::Wave;
+use raylib::prelude::Image;
+use raylib::prelude::Shader;
+use raylib::prelude::Color;
 
-enum ResType {
-	Tex,
-	Fnt,
-	Snd,
-	Mus
+/// Kinds of resources mapped on a [ResourceSet].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResType {
+	/// Texture.
+	Tex = 0,
+	/// Font.
+	Fnt = 1,
+	/// Sound.
+	Snd = 2,
+	/// Music track.
+	Mus = 3
+}
+
+/// Filtering of a texture when scaled, see [ResourceSet::map_texture_ex].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TexFilter {
+	/// Nearest pixel, for pixel art.
+	Point = 0,
+	/// Bilinear, the default.
+	Bilinear = 1,
+	/// Trilinear, i.e, bilinear blended between mipmap levels; needs mipmaps.
+	Trilinear = 2,
+	/// Anisotropic, with 4 samples.
+	Anisotropic4x = 3,
+	/// Anisotropic, with 8 samples.
+	Anisotropic8x = 4,
+	/// Anisotropic, with 16 samples.
+	Anisotropic16x = 5
+}
+
+impl TexFilter {
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<TexFilter> {
+		match v {
+			0 => Some(TexFilter::Point),
+			1 => Some(TexFilter::Bilinear),
+			2 => Some(TexFilter::Trilinear),
+			3 => Some(TexFilter::Anisotropic4x),
+			4 => Some(TexFilter::Anisotropic8x),
+			5 => Some(TexFilter::Anisotropic16x),
+			_ => None
+		}
+	}
+
+	pub(crate) fn to_raylib(self) -> raylib::consts::TextureFilter {
+		use raylib::consts::TextureFilter::*;
+		match self {
+			TexFilter::Point => TEXTURE_FILTER_POINT,
+			TexFilter::Bilinear => TEXTURE_FILTER_BILINEAR,
+			TexFilter::Trilinear => TEXTURE_FILTER_TRILINEAR,
+			TexFilter::Anisotropic4x => TEXTURE_FILTER_ANISOTROPIC_4X,
+			TexFilter::Anisotropic8x => TEXTURE_FILTER_ANISOTROPIC_8X,
+			TexFilter::Anisotropic16x => TEXTURE_FILTER_ANISOTROPIC_16X
+		}
+	}
+}
+
+/// A looping frame animation of a UI texture, see [ResourceSet::set_ui_anim].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct UiAnim {
+	frame_width: u32,
+	frame_height: u32,
+	nframes: u8,
+	frame_rate: f32
 }
 
 /// Struct for storing, and managing resources such as textures and cues.
@@ -28,11 +107,25 @@ pub struct ResourceSet {
 	to_load: Vec<(u8, ResType, String)>,
 	texs: HashMap<u8, Texture2D>,
 	texrec: HashMap<u8, (u8, Rectangle)>,
+	tex_opts: HashMap<u8, (TexFilter, bool)>,
+	nine_patches: HashMap<u8, (i32, i32, i32, i32)>,
+	ui_anims: HashMap<u8, UiAnim>,
+	/// Seconds elapsed, for animated UI textures.
+	ui_clock: f32,
 	fonts: HashMap<u8, Font>,
+	font_specs: HashMap<u8, (Vec<i32>, Vec<i32>)>,
+	font_atlases: HashMap<u8, Vec<(i32, Font)>>,
 	sounds: HashMap<u8, Sound>, 
 	tracks: HashMap<u8, Music>,
+	team_colors: Vec<Color>,
+	palettes: HashMap<u8, Vec<Color>>,
+	palette_shaders: HashMap<u8, Shader>,
+	outline_shader: Option<Shader>,
+	placeholder_tex: Option<Texture2D>,
+	silent_snd: Option<Sound>,
 	deftex: u8,
-	deffont: u8
+	deffont: u8,
+	memory_budget: usize
 }
 
 ///#TODO: Remove in Release
@@ -49,11 +142,24 @@ impl ResourceSet {
 			to_load: vec![],
 			texs: HashMap::new(),
 			texrec: HashMap::new(),
+			tex_opts: HashMap::new(),
+			nine_patches: HashMap::new(),
+			ui_anims: HashMap::new(),
+			ui_clock: 0.0,
 			fonts: HashMap::new(),
+			font_specs: HashMap::new(),
+			font_atlases: HashMap::new(),
 			sounds: HashMap::new(),
 			tracks: HashMap::new(),
+			team_colors: vec![],
+			palettes: HashMap::new(),
+			palette_shaders: HashMap::new(),
+			outline_shader: None,
+			placeholder_tex: None,
+			silent_snd: None,
 			deftex: 0,
-			deffont: 0
+			deffont: 0,
+			memory_budget: 0
 		}
 	}
 
@@ -66,12 +172,21 @@ impl ResourceSet {
 	/// * 244 ('0xf4') - the 'attack tile' image that is shown for tiles that the unit can attack but not move to.
 	/// * 248 ('0xf8') - the unit type information window.
 	/// * 245 ('0xf5') - the text box on which introduction text is rendered.
+	/// * 224-226 ('0xe0'-'0xe2') - the healing, boost and gold pickups placed by the map, see [MAP_TEXTURES](crate::world::pickup::MAP_TEXTURES).
 	/// The method does not load textures, but stores id-path mappings so that they may later be loaded once an OpenGL context is available.
 	pub fn map_texture(&mut self, id: u8, path: &str) {
 		self.deftex = id;
 		self.to_load.push((id, ResType::Tex, path.to_string()));
 	}
 
+	/// Map a texture to an internal unsigned byte identifier, like [map_texture](ResourceSet::map_texture), with the filter to use when it is scaled,
+	/// e.g, [Point](TexFilter::Point) for pixel art, and whether to generate mipmaps once loaded, for textures drawn much smaller than their size.
+	/// Textures mapped otherwise keep the raylib defaults, i.e, bilinear filtering and no mipmaps.
+	pub fn map_texture_ex(&mut self, id: u8, path: &str, filter: TexFilter, mipmaps: bool) {
+		self.map_texture(id, path);
+		self.tex_opts.insert(id, (filter, mipmaps));
+	}
+
 	/// Map a region of a texture to an internal unsigned byte identifier.
 	/// *id* - the unsigned byte identifier for the texture region.
 	/// *tid* - the texture id, from which the region is to be extracted.
@@ -85,6 +200,55 @@ impl ResourceSet {
 		self.texrec.insert(id, (tid, Rectangle{x: x, y: y, width: w, height: h}));
 	}
 
+	/// Make the texture with the specified id a nine-patch, with the given border insets in pixels (negative insets are taken as 0).
+	/// Corners are drawn unscaled, edges are stretched along their length, and the centre fills the rest, so that one texture fits panels of any size.
+	/// Windows (e.g, the introduction text box) drawn with a nine-patch texture are sized to fit their text rather than the texture; the banner texture is drawn as a nine-patch as well.
+	pub fn set_nine_patch(&mut self, id: u8, left: i32, top: i32, right: i32, bottom: i32) {
+		self.nine_patches.insert(id, (left.max(0), top.max(0), right.max(0), bottom.max(0)));
+	}
+
+	/// Return the border insets (left, top, right, bottom) of the texture with the specified id, if it is a nine-patch.
+	pub fn nine_patch(&self, id: u8) -> Option<(i32, i32, i32, i32)> {
+		self.nine_patches.get(&id).copied()
+	}
+
+	/// Animate the texture with the specified id, used for a UI role (e.g, the 'select tile' image, panels or the banner), looping over its frames.
+	/// * `fs` - size of an animation frame.
+	/// * `frn` - number of frames, laid out horizontally from the top-left corner of the texture.
+	/// * `fr` - frame rate of the animation.
+	///
+	/// The animation is advanced by the engine every frame, and wherever the texture is drawn for its role, only the current frame is drawn.
+	pub fn set_ui_anim(&mut self, id: u8, fs: (u32, u32), frn: u8, fr: f32) {
+		self.ui_anims.insert(id, UiAnim {
+			frame_width: fs.0,
+			frame_height: fs.1,
+			nframes: frn.max(1),
+			frame_rate: fr
+		});
+	}
+
+	/// Advance animated UI textures by `dt` seconds.
+	pub(crate) fn advance_ui(&mut self, dt: f32) {
+		self.ui_clock += dt;
+	}
+
+	/// Region of the current frame of the texture with the specified id, if it is animated, see [set_ui_anim](ResourceSet::set_ui_anim).
+	pub(crate) fn ui_anim_frame(&self, id: u8) -> Option<Rectangle> {
+		self.ui_anims.get(&id).map(|a| {
+			let i = (self.ui_clock * a.frame_rate).max(0.0) as u64 % a.nframes as u64;
+			Rectangle::new((i as u32 * a.frame_width) as f32, 0.0, a.frame_width as f32, a.frame_height as f32)
+		})
+	}
+
+	/// Return the texture with the specified id, used for a UI role, and the region of it to draw: the current frame if it is animated, else the whole texture.
+	/// # Panics
+	/// If resources have not been loaded yet.
+	pub fn get_ui_frame(&self, id: u8) -> (&Texture2D, Rectangle) {
+		let tex = self.get_texture(id);
+		let rec = self.ui_anim_frame(id).unwrap_or_else(|| Rectangle::new(0.0, 0.0, tex.width() as f32, tex.height() as f32));
+		(tex, rec)
+	}
+
 	/// Check if the given id belongs to a texture region.
 	pub fn is_texture_region(&self, id: u8) -> bool {
 		self.texrec.contains_key(&id)
@@ -97,6 +261,18 @@ impl ResourceSet {
 		self.to_load.push((id, ResType::Fnt, path.to_string()));
 	}
 
+	/// Map a font to an internal unsigned byte identifier, generating a separate atlas for each of the specified pixel sizes.
+	/// *ranges* - inclusive (first, last) codepoint ranges to include in the atlases; if empty, printable ASCII is used.
+	/// Text drawn with [get_font_sized](ResourceSet::get_font_sized) uses the closest atlas, which keeps large text crisp.
+	pub fn map_font_atlas(&mut self, id: u8, path: &str, sizes: &[i32], ranges: &[(u32, u32)]) {
+		let mut sizes: Vec<i32> = sizes.iter().copied().filter(|s| *s > 0).collect();
+		sizes.sort_unstable();
+		sizes.dedup();
+		let codepoints = ranges.iter().flat_map(|(a, b)| *a..=*b).map(|c| c as i32).collect();
+		self.font_specs.insert(id, (sizes, codepoints));
+		self.map_font(id, path);
+	}
+
 	/// Return the texture (if it exists) with the specified id.
 	pub fn get_texture(&self, id: u8) -> &Texture2D {
 		match self.texs.get(&id) {
@@ -106,25 +282,42 @@ impl ResourceSet {
 	}
 
 	/// Return the texture region (if it exists) with the specified id.
+	/// If the texture of the region has not been loaded, the [default texture](ResourceSet::get_default_texture) is returned in its place.
 	/// # Panics
-	/// If the texture region, or texture does not exists, method panics.
+	/// If the texture region does not exist, method panics.
 	pub fn get_texture_region(&self, id: u8) -> (&Texture2D, &Rectangle) {
 		match self.texrec.get(&id) {
-			Some((tid, rec)) => {
-				(self.texs.get(tid).expect(&format!("Texture[tid={}] does not exist for corresponding texture region[id={}]", tid, id)), rec)
-			},
+			Some((tid, rec)) => (self.get_texture(*tid), rec),
 			_ => panic!("Invalid texture region id={}", id)
 		}
 	}
 
+	fn find_font(&self, id: u8, size: f32) -> Option<&Font> {
+		match self.font_atlases.get(&id) {
+			Some(v) if !v.is_empty() => {
+				let sizes: Vec<i32> = v.iter().map(|(s, _)| *s).collect();
+				Some(&v[atlas_index(&sizes, size)].1)
+			},
+			_ => self.fonts.get(&id)
+		}
+	}
+
 	/// Return the font (if it exists) with the specified id.
 	pub fn get_font(&self, id: u8) -> &Font {
-		match self.fonts.get(&id) {
+		match self.find_font(id, f32::INFINITY) {
 			Some(f) => f,
 			_ => self.get_default_font()
 		}
 	}
 
+	/// Return the font (if it exists) with the specified id, using the atlas best suited for drawing text of the given size.
+	pub fn get_font_sized(&self, id: u8, size: f32) -> &Font {
+		match self.find_font(id, size) {
+			Some(f) => f,
+			_ => self.get_default_font_sized(size)
+		}
+	}
+
 	/// Set the default texture of the resource set.
 	/// The default texture is returned whenever a texture id is not registered or loaded.
 	/// If not set, the last texture mapped is considered as the default texture.
@@ -134,11 +327,13 @@ impl ResourceSet {
 	}
 
 	/// Get the default texture.
+	/// If the default texture has failed to load, a built-in magenta checkerboard is returned instead.
 	/// # Panics
-	/// If the texture has failed to load, or not been loaded.
+	/// If resources have not been loaded yet.
 	pub fn get_default_texture(&self) -> &Texture2D {
-		match self.texs.get(&self.deftex) {
-			Some(tex) => tex,
+		match (self.texs.get(&self.deftex), &self.placeholder_tex) {
+			(Some(tex), _) => tex,
+			(_, Some(tex)) => tex,
 			_ => panic!("Default texture [ID={}] has not been loaded", self.deftex),
 		}
 	}
@@ -147,7 +342,14 @@ impl ResourceSet {
 	/// # Panics
 	/// If the font has failed to laod, or not been loaded.
 	pub fn get_default_font(&self) -> &Font {
-		match self.fonts.get(&self.deffont) {
+		self.get_default_font_sized(f32::INFINITY)
+	}
+
+	/// Get the default font, using the atlas best suited for drawing text of the given size.
+	/// # Panics
+	/// If the font has failed to laod, or not been loaded.
+	pub fn get_default_font_sized(&self, size: f32) -> &Font {
+		match self.find_font(self.deffont, size) {
 			Some(f) => f,
 			_ => panic!("Default font [ID={}] has not been loaded", self.deffont)
 		}
@@ -162,9 +364,13 @@ impl ResourceSet {
 	}
 
 	/// Return the sound (if it exists) with the specified id.
+	/// If the sound has not been loaded, a built-in silent sound is returned instead; failures to load are reported once, when resources are loaded.
+	/// # Panics
+	/// If resources have not been loaded yet.
 	pub fn get_sound(&self, id: u8) -> &Sound {
-		match self.sounds.get(&id) {
-			Some(snd) => snd,
+		match (self.sounds.get(&id), &self.silent_snd) {
+			(Some(snd), _) => snd,
+			(_, Some(snd)) => snd,
 			_ => panic!("Sound [ID={}] has not been loaded", id)
 		}
 	}
@@ -179,39 +385,861 @@ impl ResourceSet {
 	pub fn get_music(&mut self, id: u8) -> Option<&mut Music> {
 		self.tracks.get_mut(&id)
 	}
+
+	/// Set the team colours, i.e, the colours in unit sprites that are replaced by [palettes](ResourceSet::map_palette).
+	/// Pixels must match a team colour exactly (up to rounding) to be replaced.
+	pub fn set_team_colors(&mut self, colors: &[Color]) {
+		self.team_colors = colors.to_vec();
+	}
+
+	/// Map a palette to an internal unsigned byte identifier. The i-th colour of the palette replaces the i-th team colour
+	/// in sprites of units with this palette; see [Unit::palette](crate::world::Unit::palette).
+	/// Like other resources, palettes are only compiled (into shaders) once initialization is complete.
+	pub fn map_palette(&mut self, id: u8, colors: &[Color]) {
+		self.palettes.insert(id, colors.to_vec());
+	}
+
+	/// Return the size (width, height) of the loaded texture with the specified id, if any.
+	pub fn texture_size(&self, id: u8) -> Option<(i32, i32)> {
+		self.texs.get(&id).map(|t| (t.width(), t.height()))
+	}
+
+	/// Return the shader (if it exists) for the palette with the specified id.
+	pub fn get_palette_shader(&self, id: u8) -> Option<&Shader> {
+		self.palette_shaders.get(&id)
+	}
+
+	/// Return the shader used to draw unit outlines, once resources have been loaded.
+	pub(crate) fn get_outline_shader(&self) -> Option<&Shader> {
+		self.outline_shader.as_ref()
+	}
+
+	/// Set the video memory, in bytes, that loaded textures and fonts should fit in, or 0 for no budget (the default).
+	/// A warning listing the largest resources is printed if the budget is exceeded once resources have been loaded; see [report](ResourceSet::report).
+	pub fn set_memory_budget(&mut self, bytes: usize) {
+		self.memory_budget = bytes;
+	}
+
+	/// Report the memory used by every loaded texture, font, sound and music track, largest first.
+	/// Textures and font atlases are counted as video memory, with their mipmaps; sounds as the samples held in memory;
+	/// music tracks are streamed, so the size of their file is reported instead, which does not count towards the budget.
+	/// Built-in placeholders are not included.
+	pub fn report(&self) -> MemoryReport {
+		let path = |id: u8, kind: ResType| self.to_load.iter().rev().find(|(i, k, _)| *i == id && *k == kind).map(|(_, _, p)| p.clone()).unwrap_or_default();
+		let mut entries = Vec::new();
+		for (id, t) in self.texs.iter() {
+			entries.push(ResourceUsage {
+				id: *id,
+				kind: ResType::Tex,
+				path: path(*id, ResType::Tex),
+				bytes: texture_bytes(t.width, t.height, t.format, t.mipmaps),
+				detail: format!("{}x{}, {} mipmap level(s)", t.width, t.height, t.mipmaps)
+			});
+		}
+		let fonts = self.fonts.iter().map(|(id, f)| (*id, None, f));
+		let atlases = self.font_atlases.iter().flat_map(|(id, v)| v.iter().map(move |(sz, f)| (*id, Some(*sz), f)));
+		for (id, size, f) in fonts.chain(atlases) {
+			let t = &f.texture;
+			entries.push(ResourceUsage {
+				id: id,
+				kind: ResType::Fnt,
+				path: path(id, ResType::Fnt),
+				bytes: texture_bytes(t.width, t.height, t.format, t.mipmaps),
+				detail: match size {
+					Some(sz) => format!("{}px atlas, {}x{}, {} glyphs", sz, t.width, t.height, f.charsCount),
+					None => format!("atlas {}x{}, {} glyphs", t.width, t.height, f.charsCount)
+				}
+			});
+		}
+		for (id, snd) in self.sounds.iter() {
+			let st = &snd.stream;
+			entries.push(ResourceUsage {
+				id: *id,
+				kind: ResType::Snd,
+				path: path(*id, ResType::Snd),
+				bytes: snd.sampleCount as usize * (st.sampleSize as usize / 8),
+				detail: format!("{} Hz, {}-bit, {} channel(s)", st.sampleRate, st.sampleSize, st.channels)
+			});
+		}
+		for id in self.tracks.keys() {
+			let p = path(*id, ResType::Mus);
+			entries.push(ResourceUsage {
+				id: *id,
+				kind: ResType::Mus,
+				bytes: std::fs::metadata(&p).map(|m| m.len() as usize).unwrap_or(0),
+				path: p,
+				detail: String::from("streamed, file size")
+			});
+		}
+		entries.sort_by_key(|e| (std::cmp::Reverse(e.bytes), e.kind as u8, e.id));
+		MemoryReport { entries: entries, budget: self.memory_budget }
+	}
+}
+
+/// Memory used by a loaded resource, see [ResourceSet::report].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceUsage {
+	/// The id of the resource.
+	pub id: u8,
+	/// The kind of resource.
+	pub kind: ResType,
+	/// The path the resource was loaded from.
+	pub path: String,
+	/// Bytes used; for music tracks, the size of the file.
+	pub bytes: usize,
+	/// Dimensions, format, etc. of the resource.
+	pub detail: String
+}
+
+/// Memory used by the loaded resources of a [ResourceSet], largest first, and the budget for video memory.
+/// Displays as a table with a line per resource, followed by the totals.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryReport {
+	/// Memory used by each resource.
+	pub entries: Vec<ResourceUsage>,
+	/// Video memory budget, in bytes, or 0 for none.
+	pub budget: usize
+}
+
+impl MemoryReport {
+	/// Total bytes used by resources of a kind.
+	pub fn total_of(&self, kind: ResType) -> usize {
+		self.entries.iter().filter(|e| e.kind == kind).map(|e| e.bytes).sum()
+	}
+
+	/// Total bytes of video memory, i.e, used by textures and fonts.
+	pub fn video_memory(&self) -> usize {
+		self.total_of(ResType::Tex) + self.total_of(ResType::Fnt)
+	}
+
+	/// Returns true if there is a budget, and textures and fonts exceed it.
+	pub fn over_budget(&self) -> bool {
+		self.budget > 0 && self.video_memory() > self.budget
+	}
+}
+
+impl std::fmt::Display for MemoryReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		for e in &self.entries {
+			writeln!(f, "{:<7} {:>3}  {:>10}  {} ({})", res_name(e.kind), e.id, fmt_bytes(e.bytes), e.path, e.detail)?;
+		}
+		for k in [ResType::Tex, ResType::Fnt, ResType::Snd, ResType::Mus] {
+			writeln!(f, "total {}: {}", res_name(k), fmt_bytes(self.total_of(k)))?;
+		}
+		write!(f, "video memory: {}", fmt_bytes(self.video_memory()))?;
+		if self.budget > 0 {
+			write!(f, " of {} budget{}", fmt_bytes(self.budget), if self.over_budget() { ", OVER BUDGET" } else { "" })?;
+		}
+		Ok(())
+	}
+}
+
+fn res_name(k: ResType) -> &'static str {
+	match k {
+		ResType::Tex => "texture",
+		ResType::Fnt => "font",
+		ResType::Snd => "sound",
+		ResType::Mus => "music"
+	}
+}
+
+/// Format a size in bytes, e.g, "1.50 MiB".
+pub(crate) fn fmt_bytes(n: usize) -> String {
+	if n < 1024 {
+		return format!("{} B", n);
+	}
+	let mut v = n as f64 / 1024.0;
+	for unit in ["KiB", "MiB"] {
+		if v < 1024.0 {
+			return format!("{:.2} {}", v, unit);
+		}
+		v /= 1024.0;
+	}
+	format!("{:.2} GiB", v)
+}
+
+/// Bytes taken by a texture of the given size and raylib pixel format, including all mipmap levels.
+pub(crate) fn texture_bytes(w: i32, h: i32, format: i32, mipmaps: i32) -> usize {
+	let (mut w, mut h) = (w, h);
+	let mut n = 0;
+	for _ in 0..mipmaps.max(1) {
+		n += unsafe { raylib::ffi::GetPixelDataSize(w, h, format) }.max(0) as usize;
+		w = (w / 2).max(1);
+		h = (h / 2).max(1);
+	}
+	n
 }
 
-/// Load all resources from the set.
+/// Load all resources from the set, returning the id and kind of each resource mapped, and whether it was loaded.
+/// Resources that fail to load are skipped with a warning; missing textures and sounds are then substituted by built-in placeholders.
 /// ## Panics
-/// If any mapped resource fails to load, then this function panics.
-pub fn load_all(rs: &mut ResourceSet, rl: &mut RaylibHandle, rthread: &RaylibThread) {
+/// If a mapped font fails to load, then this function panics.
+pub fn load_all(rs: &mut ResourceSet, rl: &mut RaylibHandle, rthread: &RaylibThread) -> Vec<(u8, ResType, bool)> {
+	let img = Image::gen_image_checked(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, PLACEHOLDER_SIZE / 2, PLACEHOLDER_SIZE / 2, Color::MAGENTA, Color::BLACK);
+	match rl.load_texture_from_image(rthread, &img) {
+		Ok(tex) => rs.placeholder_tex = Some(tex),
+		Err(e) => eprintln!("warning [resources]: failed to generate placeholder texture: {}", e)
+	}
+	let wav = silent_wav();
+	let wave = unsafe { Wave::from_raw(raylib::ffi::LoadWaveFromMemory(".wav\0".as_ptr() as *const _, wav.as_ptr(), wav.len() as i32)) };
+	match Sound::load_sound_from_wave(&wave) {
+		Ok(snd) => rs.silent_snd = Some(snd),
+		Err(e) => eprintln!("warning [resources]: failed to generate silent sound: {}", e)
+	}
+	let mut loaded = Vec::with_capacity(rs.to_load.len());
 	for (id, rtyp, path) in rs.to_load.iter() {
-		match rtyp{
-			ResType::Tex => {
-				let ermsg = format!("warning [resources]: failed to load texture id={}, from {}", *id, path);
-				let tex = rl.load_texture(rthread, path).expect(&ermsg);
-				rs.texs.insert(*id, tex);
+		let ok = match rtyp {
+			ResType::Tex => match rl.load_texture(rthread, path) {
+				Ok(mut tex) => {
+					if let Some((filter, mipmaps)) = rs.tex_opts.get(id) {
+						if *mipmaps {
+							tex.gen_texture_mipmaps();
+						}
+						tex.set_texture_filter(rthread, filter.to_raylib());
+					}
+					rs.texs.insert(*id, tex);
+					true
+				},
+				Err(_) => { eprintln!("warning [resources]: failed to load texture id={}, from {}", *id, path); false }
 			},
 			ResType::Fnt => {
 				let ermsg = format!("warning [resources]: failed to load font id={}, from {}", *id, path);
-				let f = rl.load_font(rthread, path).expect(&ermsg);
-				rs.fonts.insert(*id, f);
+				match rs.font_specs.get(id) {
+					Some((sizes, cps)) if !sizes.is_empty() => {
+						let mut v = Vec::with_capacity(sizes.len());
+						for sz in sizes {
+							let chars = if cps.is_empty() { FontLoadEx::Default(95) } else { FontLoadEx::Chars(cps) };
+							let f = rl.load_font_ex(rthread, path, *sz, chars).expect(&ermsg);
+							v.push((*sz, f));
+						}
+						rs.font_atlases.insert(*id, v);
+					},
+					_ => {
+						let f = rl.load_font(rthread, path).expect(&ermsg);
+						rs.fonts.insert(*id, f);
+					}
+				}
+				true
+			},
+			ResType::Snd => match Sound::load_sound(path) {
+				Ok(snd) => { rs.sounds.insert(*id, snd); true },
+				Err(_) => { eprintln!("warning [resources]: failed to load sound id={}, from {}", *id, path); false }
+			},
+			ResType::Mus => match Music::load_music_stream(rthread, path) {
+				Ok(mus) => { rs.tracks.insert(*id, mus); true },
+				Err(_) => { eprintln!("warning [resources]: failed to load music track id={}, from {}", *id, path); false }
+			}
+		};
+		loaded.push((*id, *rtyp, ok));
+	}
+	for (id, pal) in rs.palettes.iter() {
+		let n = usize::min(pal.len(), rs.team_colors.len());
+		if n == 0 {
+			eprintln!("warning [resources]: palette id={} has no colours to swap", *id);
+			continue;
+		}
+		let code = palette_fs(&rs.team_colors[..n], &pal[..n]);
+		let sh = rl.load_shader_from_memory(rthread, None, Some(&code));
+		rs.palette_shaders.insert(*id, sh);
+	}
+	rs.outline_shader = Some(rl.load_shader_from_memory(rthread, None, Some(OUTLINE_FS)));
+	let report = rs.report();
+	if report.over_budget() {
+		eprintln!("warning [resources]: textures and fonts take {} of video memory, over the budget of {}; largest:", fmt_bytes(report.video_memory()), fmt_bytes(report.budget));
+		for e in report.entries.iter().filter(|e| matches!(e.kind, ResType::Tex | ResType::Fnt)).take(5) {
+			eprintln!("    {} id={} ({}): {}", res_name(e.kind), e.id, e.path, fmt_bytes(e.bytes));
+		}
+	}
+	loaded
+}
+
+/// Index of the atlas to use for text of the given size, from the (sorted, non-empty) list of atlas sizes.
+/// The smallest atlas at least as large as the text is preferred, since downscaling blurs less than upscaling.
+pub(crate) fn atlas_index(sizes: &[i32], size: f32) -> usize {
+	sizes.iter().position(|s| *s as f32 >= size).unwrap_or(sizes.len() - 1)
+}
+
+/// Width and height of the placeholder texture, in pixels.
+const PLACEHOLDER_SIZE: i32 = 32;
+
+/// Generate an in-memory 16-bit mono WAV file containing a few milliseconds of silence.
+pub(crate) fn silent_wav() -> Vec<u8> {
+	const RATE: u32 = 22050;
+	const SAMPLES: u32 = 64;
+	let data_len = SAMPLES * 2;
+	let mut v = Vec::with_capacity(44 + data_len as usize);
+	v.extend_from_slice(b"RIFF");
+	v.extend_from_slice(&(36 + data_len).to_le_bytes());
+	v.extend_from_slice(b"WAVEfmt ");
+	v.extend_from_slice(&16u32.to_le_bytes());
+	v.extend_from_slice(&1u16.to_le_bytes()); // PCM
+	v.extend_from_slice(&1u16.to_le_bytes()); // mono
+	v.extend_from_slice(&RATE.to_le_bytes());
+	v.extend_from_slice(&(RATE * 2).to_le_bytes());
+	v.extend_from_slice(&2u16.to_le_bytes());
+	v.extend_from_slice(&16u16.to_le_bytes());
+	v.extend_from_slice(b"data");
+	v.extend_from_slice(&data_len.to_le_bytes());
+	v.resize(44 + data_len as usize, 0);
+	v
+}
+
+/// Fragment shader that draws the silhouette of a sprite in the tint colour.
+const OUTLINE_FS: &str = r#"#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+uniform sampler2D texture0;
+out vec4 finalColor;
+void main() {
+	float a = texture(texture0, fragTexCoord).a;
+	finalColor = vec4(fragColor.rgb, a*fragColor.a);
+}
+"#;
+
+fn glsl_vec3(c: &Color) -> String {
+	format!("vec3({:.4}, {:.4}, {:.4})", c.r as f32 / 255.0, c.g as f32 / 255.0, c.b as f32 / 255.0)
+}
+
+/// Generate the fragment shader that replaces each of the colours in `src` with the corresponding colour of `dst`.
+pub(crate) fn palette_fs(src: &[Color], dst: &[Color]) -> String {
+	let list = |v: &[Color]| v.iter().map(glsl_vec3).collect::<Vec<_>>().join(", ");
+	format!(r#"#version 330
+in vec2 fragTexCoord;
+in vec4 fragColor;
+uniform sampler2D texture0;
+uniform vec4 colDiffuse;
+out vec4 finalColor;
+const vec3 SRC[{n}] = vec3[{n}]({src});
+const vec3 DST[{n}] = vec3[{n}]({dst});
+void main() {{
+	vec4 t = texture(texture0, fragTexCoord);
+	for (int i = 0; i < {n}; i++) {{
+		if (distance(t.rgb, SRC[i]) < 0.004) {{
+			t.rgb = DST[i];
+			break;
+		}}
+	}}
+	finalColor = t*colDiffuse*fragColor;
+}}
+"#, n = src.len(), src = list(src), dst = list(dst))
+}
+
+/// Persistent store of named flags and counters, e.g, campaign state like "talked to the blacksmith", that survives between battles.
+/// The profile of the [World](crate::world::World) is saved by the engine when the results screen is dismissed and when the game shuts down.
+/// Profiles are stored as plain text, one `key = value` pair per line; lines starting with `#` are ignored.
+/// # Example
+/// ```
+/// use alesia::utils::Profile;
+/// let mut p = Profile::new();
+/// p.set_flag("met_blacksmith", true);
+/// p.incr("battles_won", 1);
+/// assert!(p.flag("met_blacksmith"));
+/// assert_eq!(p.get("battles_won"), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profile {
+	values: HashMap<String, i64>,
+	path: Option<String>,
+	dirty: bool
+}
+
+impl Profile {
+	/// Create an empty profile, that is not backed by a file.
+	pub fn new() -> Profile {
+		Profile::default()
+	}
+
+	/// Open the profile stored at `path`, or create an empty one (saved to `path`) if the file does not exist.
+	/// Malformed lines are skipped with a warning.
+	pub fn open(path: &str) -> std::io::Result<Profile> {
+		let text = match std::fs::read_to_string(path) {
+			Ok(t) => t,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+			Err(e) => return Err(e)
+		};
+		let mut p = Profile::parse(&text);
+		p.path = Some(path.to_string());
+		Ok(p)
+	}
+
+	/// Parse a profile from text, in the format described [above](Profile).
+	pub fn parse(text: &str) -> Profile {
+		let mut p = Profile::new();
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+			match line.split_once('=').map(|(k, v)| (k.trim(), v.trim().parse::<i64>())) {
+				Some((k, Ok(v))) if Profile::valid_key(k) => { p.values.insert(k.to_string(), v); },
+				_ => eprintln!("warning [profile]: skipping malformed line {}: {}", i + 1, line)
+			}
+		}
+		p
+	}
+
+	/// Returns the profile as text, with keys sorted.
+	pub fn to_text(&self) -> String {
+		let mut keys: Vec<&String> = self.values.keys().collect();
+		keys.sort();
+		keys.iter().map(|k| format!("{} = {}\n", k, self.values[*k])).collect()
+	}
+
+	fn valid_key(k: &str) -> bool {
+		!k.is_empty() && !k.starts_with('#') && !k.contains(|c: char| c == '=' || c.is_whitespace())
+	}
+
+	/// Returns the value of `key`, or 0 if it has not been set.
+	pub fn get(&self, key: &str) -> i64 {
+		self.values.get(key).copied().unwrap_or(0)
+	}
+
+	/// Set the value of `key`. Returns false (ignoring the value) if the key is empty, or contains whitespace or '='.
+	pub fn set(&mut self, key: &str, v: i64) -> bool {
+		if !Profile::valid_key(key) {
+			eprintln!("warning [profile]: invalid key {:?}", key);
+			return false;
+		}
+		if self.values.insert(key.to_string(), v) != Some(v) {
+			self.dirty = true;
+		}
+		true
+	}
+
+	/// Returns true if the flag `key` is set, i.e, has a non-zero value.
+	pub fn flag(&self, key: &str) -> bool {
+		self.get(key) != 0
+	}
+
+	/// Set or clear the flag `key`.
+	pub fn set_flag(&mut self, key: &str, on: bool) -> bool {
+		self.set(key, on as i64)
+	}
+
+	/// Add `delta` to the counter `key`, returning the new value.
+	pub fn incr(&mut self, key: &str, delta: i64) -> i64 {
+		let v = self.get(key).saturating_add(delta);
+		self.set(key, v);
+		self.get(key)
+	}
+
+	/// Returns true if `key` has been set.
+	pub fn contains(&self, key: &str) -> bool {
+		self.values.contains_key(key)
+	}
+
+	/// Remove `key` from the profile.
+	pub fn remove(&mut self, key: &str) {
+		if self.values.remove(key).is_some() {
+			self.dirty = true;
+		}
+	}
+
+	/// Returns the path the profile is saved to, if any.
+	pub fn path(&self) -> Option<&str> {
+		self.path.as_deref()
+	}
+
+	/// Write the profile to its file, if it has one and has changed since it was opened or last saved.
+	pub fn save(&mut self) -> std::io::Result<()> {
+		if let (Some(p), true) = (&self.path, self.dirty) {
+			std::fs::write(p, self.to_text())?;
+			self.dirty = false;
+		}
+		Ok(())
+	}
+}
+
+/// User options for the display and audio, stored in a TOML file. See [Display::from_settings](crate::display::Display::from_settings).
+/// Only a subset of TOML is understood: the tables `[display]`, `[audio]` and `[keys]`, holding integers, floats, booleans and basic strings, and `#` comments.
+/// Unknown keys and invalid values are skipped with a warning, leaving the default in place.
+/// ```toml
+/// [display]
+/// width = 1280
+/// height = 720
+/// fps = 60
+/// sim_fps = 30
+/// background_fps = 10
+/// ai_budget_ms = 4.0
+/// vsync = true
+/// fullscreen = false
+/// pause_unfocused = true
+/// ui_scale = 1.25
+/// text_scale = 1.5
+/// high_contrast = false
+/// locale = "en"
+///
+/// [audio]
+/// master_volume = 0.8
+/// music_volume = 0.5
+/// sfx_volume = 1.0
+/// mute_unfocused = true
+///
+/// [keys]
+/// end_turn = "SPACE"
+/// ```
+/// Key names are those accepted by [key_from_name](crate::input::key_from_name), and actions those listed in [KeyBindings::ACTIONS].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+	/// Width of the window in pixels.
+	pub width: i32,
+	/// Height of the window in pixels.
+	pub height: i32,
+	/// Target fps.
+	pub fps: u32,
+	/// Rate at which orders are carried out and units updated, or 0 to update once per frame.
+	pub sim_fps: u32,
+	/// Target fps while the window is not focused, or 0 to keep the target fps.
+	pub background_fps: u32,
+	/// Milliseconds the built-in AI may spend deciding per frame, or 0 to decide at once.
+	pub ai_budget_ms: f32,
+	/// Flag to enable or disable vsync.
+	pub vsync: bool,
+	/// Flag to start in fullscreen mode.
+	pub fullscreen: bool,
+	/// Flag to pause the game and mute audio while the window is not focused or minimized.
+	pub pause_unfocused: bool,
+	/// Scale factor of HUD elements.
+	pub ui_scale: f32,
+	/// Scale factor of font sizes, see [Display::set_text_scale](crate::display::Display::set_text_scale).
+	pub text_scale: f32,
+	/// Flag to use the high-contrast look, see [Display::set_high_contrast](crate::display::Display::set_high_contrast).
+	pub high_contrast: bool,
+	/// Language of the game's text, e.g, "en". The engine only stores it, for use by the game.
+	pub locale: String,
+	/// Master volume, in the range [0, 1].
+	pub master_volume: f32,
+	/// Volume of background music, in the range [0, 1].
+	pub music_volume: f32,
+	/// Volume of sound effects, in the range [0, 1].
+	pub sfx_volume: f32,
+	/// Flag to mute audio while the window is not focused or minimized, without pausing, see [World::set_mute_unfocused](crate::world::World::set_mute_unfocused).
+	pub mute_unfocused: bool,
+	/// Keys bound to player actions.
+	pub keys: KeyBindings
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			width: 1280,
+			height: 720,
+			fps: 60,
+			sim_fps: 0,
+			background_fps: 0,
+			ai_budget_ms: 0.0,
+			vsync: true,
+			fullscreen: false,
+			pause_unfocused: false,
+			ui_scale: 1.0,
+			text_scale: 1.0,
+			high_contrast: false,
+			locale: "en".to_string(),
+			master_volume: 1.0,
+			music_volume: 1.0,
+			sfx_volume: 1.0,
+			mute_unfocused: false,
+			keys: KeyBindings::default()
+		}
+	}
+}
+
+/// Strip a trailing comment from a line of TOML, ignoring '#' inside strings.
+pub(crate) fn toml_strip_comment(line: &str) -> &str {
+	let mut quoted = false;
+	let mut escaped = false;
+	for (i, c) in line.char_indices() {
+		match c {
+			'\\' if quoted => { escaped = !escaped; continue; },
+			'"' if !escaped => quoted = !quoted,
+			'#' if !quoted => return &line[..i],
+			_ => {}
+		}
+		escaped = false;
+	}
+	line
+}
+
+/// Parse a basic TOML string, i.e, `"..."` with `\"` and `\\` escapes.
+pub(crate) fn toml_string(v: &str) -> Option<String> {
+	let inner = v.strip_prefix('"')?.strip_suffix('"')?;
+	let mut out = String::new();
+	let mut chars = inner.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => match chars.next()? {
+				'n' => out.push('\n'),
+				't' => out.push('\t'),
+				e @ ('"' | '\\') => out.push(e),
+				_ => return None
+			},
+			'"' => return None,
+			c => out.push(c)
+		}
+	}
+	Some(out)
+}
+
+impl Settings {
+	/// Load settings from the file at `path`, or the defaults if it does not exist.
+	pub fn load(path: &str) -> std::io::Result<Settings> {
+		match std::fs::read_to_string(path) {
+			Ok(t) => Ok(Settings::parse(&t)),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Settings::default()),
+			Err(e) => Err(e)
+		}
+	}
+
+	/// Parse settings from TOML text, in the format described [above](Settings).
+	pub fn parse(text: &str) -> Settings {
+		let mut s = Settings::default();
+		let mut table = String::new();
+		for (i, line) in text.lines().enumerate() {
+			let line = toml_strip_comment(line).trim();
+			if line.is_empty() {
+				continue;
+			}
+			if let Some(t) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+				table = t.trim().to_string();
+				continue;
+			}
+			let ok = match line.split_once('=') {
+				Some((k, v)) => s.set(&table, k.trim(), v.trim()),
+				None => false
+			};
+			if !ok {
+				eprintln!("warning [settings]: skipping invalid line {}: {}", i + 1, line);
+			}
+		}
+		s
+	}
+
+	fn set(&mut self, table: &str, key: &str, v: &str) -> bool {
+		let int = || v.parse::<i64>().ok();
+		let float = || v.parse::<f32>().ok().filter(|f| f.is_finite());
+		let volume = || float().filter(|f| (0.0..=1.0).contains(f));
+		let boolean = || match v { "true" => Some(true), "false" => Some(false), _ => None };
+		match (table, key) {
+			("display", "width") => int().filter(|&n| n > 0 && n <= i32::MAX as i64).map(|n| self.width = n as i32).is_some(),
+			("display", "height") => int().filter(|&n| n > 0 && n <= i32::MAX as i64).map(|n| self.height = n as i32).is_some(),
+			("display", "fps") => int().filter(|&n| n >= 0 && n <= u32::MAX as i64).map(|n| self.fps = n as u32).is_some(),
+			("display", "sim_fps") => int().filter(|&n| n >= 0 && n <= u32::MAX as i64).map(|n| self.sim_fps = n as u32).is_some(),
+			("display", "background_fps") => int().filter(|&n| n >= 0 && n <= u32::MAX as i64).map(|n| self.background_fps = n as u32).is_some(),
+			("display", "ai_budget_ms") => float().filter(|&f| f >= 0.0).map(|f| self.ai_budget_ms = f).is_some(),
+			("display", "vsync") => boolean().map(|b| self.vsync = b).is_some(),
+			("display", "fullscreen") => boolean().map(|b| self.fullscreen = b).is_some(),
+			("display", "pause_unfocused") => boolean().map(|b| self.pause_unfocused = b).is_some(),
+			("display", "ui_scale") => float().filter(|&f| f > 0.0).map(|f| self.ui_scale = f).is_some(),
+			("display", "text_scale") => float().filter(|&f| f > 0.0).map(|f| self.text_scale = f).is_some(),
+			("display", "high_contrast") => boolean().map(|b| self.high_contrast = b).is_some(),
+			("display", "locale") => toml_string(v).map(|l| self.locale = l).is_some(),
+			("audio", "master_volume") => volume().map(|f| self.master_volume = f).is_some(),
+			("audio", "music_volume") => volume().map(|f| self.music_volume = f).is_some(),
+			("audio", "sfx_volume") => volume().map(|f| self.sfx_volume = f).is_some(),
+			("audio", "mute_unfocused") => boolean().map(|b| self.mute_unfocused = b).is_some(),
+			("keys", action) => match toml_string(v).and_then(|n| crate::input::key_from_name(&n)) {
+				Some(k) => self.keys.bind(action, k),
+				None => false
+			},
+			_ => false
+		}
+	}
+
+	/// Returns the settings as TOML text, in the format described [above](Settings).
+	pub fn to_toml(&self) -> String {
+		let esc = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\t', "\\t");
+		let mut out = format!("[display]\nwidth = {}\nheight = {}\nfps = {}\nsim_fps = {}\nbackground_fps = {}\nai_budget_ms = {:?}\nvsync = {}\nfullscreen = {}\npause_unfocused = {}\nui_scale = {:?}\ntext_scale = {:?}\nhigh_contrast = {}\nlocale = \"{}\"\n\n",
+			self.width, self.height, self.fps, self.sim_fps, self.background_fps, self.ai_budget_ms, self.vsync, self.fullscreen, self.pause_unfocused, self.ui_scale, self.text_scale, self.high_contrast, esc(&self.locale));
+		out.push_str(&format!("[audio]\nmaster_volume = {:?}\nmusic_volume = {:?}\nsfx_volume = {:?}\nmute_unfocused = {}\n\n[keys]\n",
+			self.master_volume, self.music_volume, self.sfx_volume, self.mute_unfocused));
+		for a in KeyBindings::ACTIONS.iter() {
+			if let Some(n) = self.keys.get(a).and_then(crate::input::key_name) {
+				out.push_str(&format!("{} = \"{}\"\n", a, n));
+			}
+		}
+		out
+	}
+
+	/// Update the audio settings to those of a world's [mixer](crate::world::mixer), e.g, as changed from an options menu during a game, so that saving the settings keeps them.
+	pub fn update_audio(&mut self, m: &crate::world::mixer::Mixer) {
+		self.master_volume = m.master_volume();
+		self.music_volume = m.music_volume();
+		self.sfx_volume = m.sfx_volume();
+		self.mute_unfocused = m.mute_unfocused();
+	}
+
+	/// Write the settings to the file at `path`.
+	pub fn save(&self, path: &str) -> std::io::Result<()> {
+		std::fs::write(path, self.to_toml())
+	}
+}
+
+/// Common command-line flags for debug launches, see [LaunchArgs::parse].
+/// # Example
+/// ```
+/// use alesia::utils::LaunchArgs;
+/// let args = LaunchArgs::parse(["--seed", "42", "--skip-intro", "--lang=fr"].iter().map(|s| s.to_string())).unwrap();
+/// assert_eq!(args.seed, Some(42));
+/// assert!(args.skip_intro);
+/// assert_eq!(args.rest, vec!["--lang=fr".to_string()]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchArgs {
+	/// `--map PATH` - map file to load instead of the game's own; `.alw`, or text for [load_world_ron](crate::world::load_world_ron).
+	pub map: Option<String>,
+	/// `--windowed` - do not start in fullscreen mode, regardless of settings.
+	pub windowed: bool,
+	/// `--seed N` - seed of the random number generator, for reproducible runs.
+	pub seed: Option<u32>,
+	/// `--skip-intro` - start at the player turn, without the level introduction.
+	pub skip_intro: bool,
+	/// `--replay PATH` - replay file for the game to load, see [Replay::load](crate::replay::Replay::load). The engine only parses it; it is not used by [apply](LaunchArgs::apply).
+	pub replay: Option<String>,
+	/// Arguments that were not recognised, in order, left for the game to handle.
+	pub rest: Vec<String>
+}
+
+impl LaunchArgs {
+	/// Parse flags from `args`, which should not include the program name. Flags that take a value accept both `--flag VALUE` and `--flag=VALUE`.
+	/// Returns an error message if a flag is missing its value, or the seed is not a valid number.
+	pub fn parse(args: impl IntoIterator<Item = String>) -> Result<LaunchArgs, String> {
+		let mut la = LaunchArgs::default();
+		let mut args = args.into_iter();
+		while let Some(a) = args.next() {
+			let (flag, inline) = match a.split_once('=') {
+				Some((f, v)) if f.starts_with("--") => (f.to_string(), Some(v.to_string())),
+				_ => (a.clone(), None)
+			};
+			let mut value = |name: &str| inline.clone().or_else(|| args.next()).ok_or(format!("missing value for {}", name));
+			match flag.as_str() {
+				"--map" => la.map = Some(value("--map")?),
+				"--replay" => la.replay = Some(value("--replay")?),
+				"--seed" => {
+					let v = value("--seed")?;
+					la.seed = Some(v.parse::<u32>().map_err(|_| format!("invalid seed {:?}", v))?);
+				},
+				"--windowed" if inline.is_none() => la.windowed = true,
+				"--skip-intro" if inline.is_none() => la.skip_intro = true,
+				_ => la.rest.push(a)
+			}
+		}
+		Ok(la)
+	}
+
+	/// Parse the arguments of the running program. On error, a warning is printed and the defaults are returned.
+	pub fn from_env() -> LaunchArgs {
+		LaunchArgs::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+			eprintln!("warning [args]: {}", e);
+			LaunchArgs::default()
+		})
+	}
+
+	/// Apply the flags to the display and world, i.e, load the map and set the windowed, seed and skip-intro options.
+	/// Returns false if the map does not exist, or could not be loaded.
+	pub fn apply(&self, d: &mut Display, w: &mut crate::world::World) -> bool {
+		if self.windowed {
+			d.set_fullscreen(false);
+		}
+		if let Some(s) = self.seed {
+			d.set_seed(s);
+		}
+		if self.skip_intro {
+			d.set_skip_intro(true);
+		}
+		match &self.map {
+			// load_world panics if the file cannot be opened.
+			Some(m) if !std::path::Path::new(m).is_file() => {
+				eprintln!("warning [args]: map file {} not found", m);
+				false
+			},
+			Some(m) => crate::world::load_world(w, m),
+			None => true
+		}
+	}
+}
+
+/// Environment variable that overrides the data directory, see [data_dir].
+pub const DATA_DIR_VAR: &str = "ALESIA_DATA_DIR";
+
+/// Returns the directory for the saves, settings and screenshots of the game named `app`, which is, in order of preference:
+/// 1. the value of the environment variable `ALESIA_DATA_DIR`, if set;
+/// 2. the directory `data` next to the executable, if a file named `portable` is next to it, for portable installs;
+/// 3. `%APPDATA%\<app>` on Windows, `~/Library/Application Support/<app>` on macOS, and `$XDG_DATA_HOME/<app>` (by default `~/.local/share/<app>`) elsewhere;
+/// 4. the working directory, if none of the above can be determined.
+///
+/// The directory is not created; see [data_file].
+pub fn data_dir(app: &str) -> std::path::PathBuf {
+	let exe_dir = std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf()));
+	resolve_data_dir(app, |k| std::env::var(k).ok().filter(|v| !v.is_empty()), exe_dir, std::env::consts::OS)
+}
+
+pub(crate) fn resolve_data_dir(app: &str, var: impl Fn(&str) -> Option<String>, exe_dir: Option<std::path::PathBuf>, os: &str) -> std::path::PathBuf {
+	use std::path::PathBuf;
+	if let Some(d) = var(DATA_DIR_VAR) {
+		return PathBuf::from(d);
+	}
+	if let Some(d) = exe_dir.filter(|d| d.join("portable").is_file()) {
+		return d.join("data");
+	}
+	let base = match os {
+		"windows" => var("APPDATA").map(PathBuf::from),
+		"macos" => var("HOME").map(|h| PathBuf::from(h).join("Library").join("Application Support")),
+		_ => var("XDG_DATA_HOME").map(PathBuf::from).or_else(|| var("HOME").map(|h| PathBuf::from(h).join(".local").join("share")))
+	};
+	match base {
+		Some(b) => b.join(app),
+		None => {
+			eprintln!("warning [utils]: could not determine the data directory, using the working directory");
+			PathBuf::from(".")
+		}
+	}
+}
+
+/// Returns the path of the file `name` in the [data directory](data_dir) of `app`, creating the directory if it does not exist.
+pub fn data_file(app: &str, name: &str) -> String {
+	let dir = data_dir(app);
+	if let Err(e) = std::fs::create_dir_all(&dir) {
+		eprintln!("warning [utils]: failed to create {}: {}", dir.display(), e);
+	}
+	dir.join(name).to_string_lossy().into_owned()
+}
+
+/// Carry out all audio requests queued on the world.
+pub(crate) fn play_queued(rs: &mut ResourceSet, rlau: &mut RaylibAudio, w: &mut crate::world::World) {
+	use crate::world::AudioCmd;
+	let ambient = &w.mixer.ambient;
+	for cmd in w.audio.drain(..) {
+		match cmd {
+			AudioCmd::PlaySound(id) => match rs.sounds.get(&id) {
+				Some(s) => rlau.play_sound(s),
+				None => eprintln!("warning [resources]: sound id={} has not been loaded", id)
+			},
+			AudioCmd::StopMusic => if let Some(a) = rs.tracks.get_mut(&w.bgm_id) {
+				rlau.stop_music_stream(a);
+			},
+			// Tracks of ambient regions playing keep their own volume.
+			AudioCmd::MusicVolume(v) => for (_, a) in rs.tracks.iter_mut().filter(|(id, _)| !ambient.contains_key(id)) {
+				rlau.set_music_volume(a, v);
 			},
-			ResType::Snd => {
-				let ermsg = format!("warning [resources]: failed to load sound id={}, from {}", *id, path);
-				let snd = Sound::load_sound(path).expect(&ermsg);
-				rs.sounds.insert(*id, snd);
+			AudioCmd::SfxVolume(v) => for s in rs.sounds.values() {
+				rlau.set_sound_volume(s, v);
 			},
-			ResType::Mus => {
-				let ermsg = format!("warning [resources]: failed to load music track id={}, from {}", *id, path);
-				let snd = Music::load_music_stream(rthread, path).expect(&ermsg);
-				rs.tracks.insert(*id, snd);
+			AudioCmd::MasterVolume(v) => rlau.set_master_volume(v),
+			AudioCmd::AmbientVolume(id, v) => if let Some(a) = rs.tracks.get_mut(&id) {
+				if v <= 0.0 {
+					rlau.stop_music_stream(a);
+				} else {
+					if !rlau.is_music_playing(a) {
+						rlau.play_music_stream(a);
+					}
+					rlau.set_music_volume(a, v);
+				}
 			}
 		}
 	}
 }
 
-type InitHandle = fn();
+type InitHandle = Box<dyn Fn()>;
 /// Type alias for nullable C ABI function pointer for `on_init` [callback](StateListener). 
 pub type CInitHandle = Option<extern "C" fn()>; 
 type TurnHandle = Box<dyn FnMut(&mut crate::world::World, &mut Vec<Order>)>;
@@ -220,17 +1248,143 @@ type TurnHandle = Box<dyn FnMut(&mut crate::world::World, &mut Vec<Order>)>;
 /// The call site retains ownership of all non-primitive parameters. 
 /// **Under no circumstances must the references be released within this callback**
 pub type CTurnHandle = Option<extern "C" fn(*mut crate::world::World, *mut Vec<Order>)>;
+/// Type alias for nullable C ABI function pointer for `on_init` [callback](StateListener), which receives the context pointer supplied at bind time.
+pub type CInitCtxHandle = Option<extern "C" fn(*mut c_void)>;
+/// Type alias for nullable C ABI function pointer for `on_turn` [callback](StateListener), which receives the context pointer supplied at bind time.
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CTurnCtxHandle = Option<extern "C" fn(*mut crate::world::World, *mut Vec<Order>, *mut c_void)>;
+type UpdateHandle = Box<dyn FnMut(&mut crate::world::World, f32)>;
+/// Type alias for nullable C ABI function pointer for `on_update` [callback](StateListener).
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CUpdateHandle = Option<extern "C" fn(*mut crate::world::World, f32, *mut c_void)>;
+type UnitDeathHandle = Box<dyn FnMut(&mut crate::world::World, u8, u8)>;
+/// Type alias for nullable C ABI function pointer for `on_unit_death` [callback](StateListener).
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CUnitDeathHandle = Option<extern "C" fn(*mut crate::world::World, u8, u8, *mut c_void)>;
+type AttackHandle = Box<dyn FnMut(&mut crate::world::World, u8, u8, f32)>;
+/// Type alias for nullable C ABI function pointer for `on_attack` [callback](StateListener).
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CAttackHandle = Option<extern "C" fn(*mut crate::world::World, u8, u8, f32, *mut c_void)>;
+type ResultsHandle = Box<dyn FnMut(&mut crate::world::World, Outcome)>;
+/// Type alias for nullable C ABI function pointer for `on_results` [callback](StateListener). The outcome is 1 for victory and 2 for defeat.
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CResultsHandle = Option<extern "C" fn(*mut crate::world::World, i32, *mut c_void)>;
+type PhaseHandle = Box<dyn FnMut(&mut crate::world::World, TurnPhase)>;
+/// Type alias for nullable C ABI function pointer for `on_phase_change` [callback](StateListener).
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CPhaseHandle = Option<extern "C" fn(*mut crate::world::World, TurnPhase, *mut c_void)>;
+type DrawHandle = Box<dyn FnMut(&mut RaylibDrawHandle<'_>, &crate::world::World)>;
+/// Type alias for nullable C ABI function pointer for `on_draw` [callback](StateListener).
+/// The callback is invoked between `BeginDrawing` and `EndDrawing`, after the world and HUD have been drawn, so raylib draw calls may be issued from it.
+/// # Safety
+/// The World is borrowed immutably; **under no circumstances must it be modified or released within this callback**.
+pub type CDrawHandle = Option<extern "C" fn(*const crate::world::World, *mut c_void)>;
+type VictoryHandle = Box<dyn FnMut(&crate::world::World) -> Outcome>;
+/// Type alias for nullable C ABI function pointer for `on_victory_check` [callback](StateListener).
+/// The callback returns 1 for victory, 2 for defeat, and 0 to continue the game.
+/// # Safety
+/// Same as [CDrawHandle].
+pub type CVictoryHandle = Option<extern "C" fn(*const crate::world::World, *mut c_void) -> i32>;
+type ExitHandle = Box<dyn FnMut(&mut crate::world::World)>;
+/// Type alias for nullable C ABI function pointer for `on_exit` [callback](StateListener).
+/// # Safety
+/// Same as [CTurnHandle]. The context pointer is owned by the call site, and is passed back as is.
+pub type CExitHandle = Option<extern "C" fn(*mut crate::world::World, *mut c_void)>;
+type MapLoadedHandle = Box<dyn FnMut(&mut crate::world::World, &crate::world::MapInfo)>;
+/// Type alias for nullable C ABI function pointer for `on_map_loaded` [callback](StateListener), receiving the path of the map as a C string.
+/// The other metadata of the map may be read through the world, e.g, with `alsnGetMapTitle`.
+/// # Safety
+/// Same as [CExitHandle]. The string is valid only for the duration of the callback.
+pub type CMapLoadedHandle = Option<extern "C" fn(*mut crate::world::World, *const c_char, *mut c_void)>;
+type TimeoutHandle = Box<dyn FnMut(&mut crate::world::World, bool)>;
+/// Type alias for nullable C ABI function pointer for `on_timeout` [callback](StateListener).
+/// The flag is true if the bank of the [turn timer](crate::input::TurnTimer) has been spent, rather than the time of the turn.
+/// # Safety
+/// Same as [CExitHandle].
+pub type CTimeoutHandle = Option<extern "C" fn(*mut crate::world::World, bool, *mut c_void)>;
+type PickupHandle = Box<dyn FnMut(&mut crate::world::World, u8, PickupEffect)>;
+/// Type alias for nullable C ABI function pointer for `on_pickup` [callback](StateListener), receiving the id of the unit, and the raw kind and value of the [effect](PickupEffect::to_raw).
+/// # Safety
+/// Same as [CExitHandle].
+pub type CPickupHandle = Option<extern "C" fn(*mut crate::world::World, u8, u8, f32, *mut c_void)>;
+type NarrationHandle = Box<dyn FnMut(&mut crate::world::World, &str)>;
+/// Type alias for nullable C ABI function pointer for `on_narration` [callback](StateListener), receiving a line of [narration](crate::narration) as a C string.
+/// # Safety
+/// Same as [CMapLoadedHandle].
+pub type CNarrationHandle = Option<extern "C" fn(*mut crate::world::World, *const c_char, *mut c_void)>;
+type MoveCompleteHandle = Box<dyn FnMut(&mut crate::world::World, u8, (i32, i32))>;
+/// Type alias for nullable C ABI function pointer for `on_move_complete` [callback](StateListener), receiving the id of the unit and the coordinates of the tile moved to.
+/// # Safety
+/// Same as [CExitHandle].
+pub type CMoveCompleteHandle = Option<extern "C" fn(*mut crate::world::World, u8, i32, i32, *mut c_void)>;
+type EventHandle = Box<dyn FnMut(&mut crate::world::World, &EngineEvent)>;
 
 
 /// Plain struct to store callbacks for the following events:
 /// 1. Display initialization.
 /// 2. Player turn end.
+/// 3. Every frame, i.e, update (with frame time) and draw.
+/// 4. Unit death.
+/// 5. Change of [phase](TurnPhase).
+/// 6. End of each turn, to check for victory or defeat.
+/// 7. Attacks, once damage has been dealt.
+/// 8. End of the phase banner shown on a change of phase.
+/// 9. Dismissal of the results screen, at the end of the game.
+/// 10. Exit, before the window is closed.
+/// 11. Time running out in a player turn, see [TurnTimer](crate::input::TurnTimer).
+/// 12. A map having been [loaded](crate::world::load_world), before the intro is shown.
+/// 13. A unit collecting a [pickup](crate::world::pickup).
+/// 14. End of a transition between screens, see [Theme::transition](crate::display::Theme::transition).
+/// 15. [Narration](crate::narration) of events and changes of focus, in plain text.
+/// 16. A unit completing a move, i.e, ending it on a tile.
+///
+/// Each of these but narration is also published as an [EngineEvent] to the functions [subscribed](StateListener::subscribe) to the listener, and to the event queue of the world, if enabled.
 pub struct StateListener {
 	raw: bool,
 	on_init: Option<InitHandle>,
 	on_init_raw: CInitHandle,
 	on_turn: Option<TurnHandle>,
-	on_turn_raw: CTurnHandle
+	on_turn_raw: CTurnHandle,
+	on_init_ctx: (CInitCtxHandle, *mut c_void),
+	on_turn_ctx: (CTurnCtxHandle, *mut c_void),
+	on_update: Option<UpdateHandle>,
+	on_update_raw: (CUpdateHandle, *mut c_void),
+	on_unit_death: Option<UnitDeathHandle>,
+	on_unit_death_raw: (CUnitDeathHandle, *mut c_void),
+	on_attack: Option<AttackHandle>,
+	on_attack_raw: (CAttackHandle, *mut c_void),
+	on_phase_change: Option<PhaseHandle>,
+	on_phase_change_raw: (CPhaseHandle, *mut c_void),
+	on_banner_done: Option<PhaseHandle>,
+	on_banner_done_raw: (CPhaseHandle, *mut c_void),
+	on_transition_done: Option<PhaseHandle>,
+	on_transition_done_raw: (CPhaseHandle, *mut c_void),
+	on_results: Option<ResultsHandle>,
+	on_results_raw: (CResultsHandle, *mut c_void),
+	on_draw: Option<DrawHandle>,
+	on_draw_raw: (CDrawHandle, *mut c_void),
+	on_victory_check: Option<VictoryHandle>,
+	on_victory_check_raw: (CVictoryHandle, *mut c_void),
+	on_exit: Option<ExitHandle>,
+	on_exit_raw: (CExitHandle, *mut c_void),
+	on_timeout: Option<TimeoutHandle>,
+	on_timeout_raw: (CTimeoutHandle, *mut c_void),
+	on_map_loaded: Option<MapLoadedHandle>,
+	on_map_loaded_raw: (CMapLoadedHandle, *mut c_void),
+	on_pickup: Option<PickupHandle>,
+	on_pickup_raw: (CPickupHandle, *mut c_void),
+	on_narration: Option<NarrationHandle>,
+	on_narration_raw: (CNarrationHandle, *mut c_void),
+	on_move_complete: Option<MoveCompleteHandle>,
+	on_move_complete_raw: (CMoveCompleteHandle, *mut c_void),
+	subscribers: Vec<(u32, EventHandle)>,
+	next_sub: u32
 }
 
 impl StateListener {
@@ -241,7 +1395,41 @@ impl StateListener {
 			on_init: None,
 			on_init_raw: None,
 			on_turn: None,
-			on_turn_raw: None
+			on_turn_raw: None,
+			on_init_ctx: (None, ptr::null_mut()),
+			on_turn_ctx: (None, ptr::null_mut()),
+			on_update: None,
+			on_update_raw: (None, ptr::null_mut()),
+			on_unit_death: None,
+			on_unit_death_raw: (None, ptr::null_mut()),
+			on_attack: None,
+			on_attack_raw: (None, ptr::null_mut()),
+			on_phase_change: None,
+			on_phase_change_raw: (None, ptr::null_mut()),
+			on_banner_done: None,
+			on_banner_done_raw: (None, ptr::null_mut()),
+			on_transition_done: None,
+			on_transition_done_raw: (None, ptr::null_mut()),
+			on_results: None,
+			on_results_raw: (None, ptr::null_mut()),
+			on_draw: None,
+			on_draw_raw: (None, ptr::null_mut()),
+			on_victory_check: None,
+			on_victory_check_raw: (None, ptr::null_mut()),
+			on_exit: None,
+			on_exit_raw: (None, ptr::null_mut()),
+			on_timeout: None,
+			on_timeout_raw: (None, ptr::null_mut()),
+			on_map_loaded: None,
+			on_map_loaded_raw: (None, ptr::null_mut()),
+			on_pickup: None,
+			on_pickup_raw: (None, ptr::null_mut()),
+			on_narration: None,
+			on_narration_raw: (None, ptr::null_mut()),
+			on_move_complete: None,
+			on_move_complete_raw: (None, ptr::null_mut()),
+			subscribers: Vec::new(),
+			next_sub: 0
 		}
 	}
 
@@ -253,16 +1441,50 @@ impl StateListener {
 			on_init: None,
 			on_init_raw: None,
 			on_turn: None,
-			on_turn_raw: None
+			on_turn_raw: None,
+			on_init_ctx: (None, ptr::null_mut()),
+			on_turn_ctx: (None, ptr::null_mut()),
+			on_update: None,
+			on_update_raw: (None, ptr::null_mut()),
+			on_unit_death: None,
+			on_unit_death_raw: (None, ptr::null_mut()),
+			on_attack: None,
+			on_attack_raw: (None, ptr::null_mut()),
+			on_phase_change: None,
+			on_phase_change_raw: (None, ptr::null_mut()),
+			on_banner_done: None,
+			on_banner_done_raw: (None, ptr::null_mut()),
+			on_transition_done: None,
+			on_transition_done_raw: (None, ptr::null_mut()),
+			on_results: None,
+			on_results_raw: (None, ptr::null_mut()),
+			on_draw: None,
+			on_draw_raw: (None, ptr::null_mut()),
+			on_victory_check: None,
+			on_victory_check_raw: (None, ptr::null_mut()),
+			on_exit: None,
+			on_exit_raw: (None, ptr::null_mut()),
+			on_timeout: None,
+			on_timeout_raw: (None, ptr::null_mut()),
+			on_map_loaded: None,
+			on_map_loaded_raw: (None, ptr::null_mut()),
+			on_pickup: None,
+			on_pickup_raw: (None, ptr::null_mut()),
+			on_narration: None,
+			on_narration_raw: (None, ptr::null_mut()),
+			on_move_complete: None,
+			on_move_complete_raw: (None, ptr::null_mut()),
+			subscribers: Vec::new(),
+			next_sub: 0
 		}
 	}
 
 	/// Bind a function for callback when display is initialized.
-	pub fn bind_init(&mut self, f: InitHandle) {
+	pub fn bind_init(&mut self, f: impl Fn() + 'static) {
 		if self.raw {
 			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
 		}
-		self.on_init = Some(f);
+		self.on_init = Some(Box::new(f));
 	}
 
 	/// Bind a function for callback when player turn ends.
@@ -273,30 +1495,349 @@ impl StateListener {
 		self.on_turn = Some(Box::new(f));
 	}
 
-	/// FFI Internal
-	pub fn _bind_rawinit(&mut self, f: CInitHandle) {
-		if !self.raw {
-			eprintln!("warning [state_listener]: C fp bound to state listener!");
+	/// Bind a function for callback every frame, with the frame time in seconds.
+	pub fn bind_update(&mut self, f: impl FnMut(&mut crate::world::World, f32) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
 		}
-		self.on_init_raw = f;
+		self.on_update = Some(Box::new(f));
 	}
 
-	/// FFI Internal
-	pub fn _bind_rawturn(&mut self, f: CTurnHandle) {
-		if !self.raw {
-			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+	/// Bind a function for callback when a unit dies, with the id and type id of the unit.
+	/// The unit has already been removed from the world when the callback is invoked.
+	pub fn bind_unit_death(&mut self, f: impl FnMut(&mut crate::world::World, u8, u8) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
 		}
-		self.on_turn_raw = f;
+		self.on_unit_death = Some(Box::new(f));
 	}
 
-	/// Notify this listener that display initialization has been completed.
-	pub fn notify_init(&self) {
+	/// Bind a function for callback when a unit attacks, with the ids of the attacker and target, and the damage dealt.
+	/// The target may already have zero health, in which case it is removed, and reported dead, right after.
+	pub fn bind_attack(&mut self, f: impl FnMut(&mut crate::world::World, u8, u8, f32) + 'static) {
 		if self.raw {
-			if let Some(f) = self.on_init_raw {
-				f();
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_attack = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when the game enters a new phase.
+	pub fn bind_phase_change(&mut self, f: impl FnMut(&mut crate::world::World, TurnPhase) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_phase_change = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when the banner announcing a new phase has left the screen. Input is ignored while the banner is shown.
+	pub fn bind_banner_done(&mut self, f: impl FnMut(&mut crate::world::World, TurnPhase) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_banner_done = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when a transition between screens has finished, with the phase entered. Input is ignored during the transition.
+	pub fn bind_transition_done(&mut self, f: impl FnMut(&mut crate::world::World, TurnPhase) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_transition_done = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when the player dismisses the results screen, with the outcome of the game.
+	/// The game has ended at this point; [Runtime::step](crate::display::Runtime::step) returns false from then on.
+	pub fn bind_results(&mut self, f: impl FnMut(&mut crate::world::World, Outcome) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_results = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback every frame, after the world and HUD have been drawn.
+	pub fn bind_draw(&mut self, f: impl FnMut(&mut RaylibDrawHandle<'_>, &crate::world::World) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_draw = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback at the end of each turn (once all orders have been carried out), to decide whether the game has been won or lost.
+	pub fn bind_victory_check(&mut self, f: impl FnMut(&crate::world::World) -> Outcome + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_victory_check = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback once the game is about to exit, i.e, the window has been closed (and closing confirmed, if asked), or the game is shut down.
+	/// The world may still be saved at this point.
+	pub fn bind_exit(&mut self, f: impl FnMut(&mut crate::world::World) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_exit = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when time runs out in a player turn, before the turn is ended.
+	/// The flag is true if the bank of the turn timer has been spent, e.g, to declare defeat in competitive modes.
+	pub fn bind_timeout(&mut self, f: impl FnMut(&mut crate::world::World, bool) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_timeout = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback once a map has been loaded, with its metadata, e.g, to place loot or adjust the difficulty before the intro is shown.
+	pub fn bind_map_loaded(&mut self, f: impl FnMut(&mut crate::world::World, &crate::world::MapInfo) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_map_loaded = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when a unit collects a pickup, with the id of the unit and the effect applied.
+	pub fn bind_pickup(&mut self, f: impl FnMut(&mut crate::world::World, u8, PickupEffect) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_pickup = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback when a unit completes a move, with the id of the unit and the tile it ended the move on.
+	/// Units moving along a path report only the last tile, before collecting any pickup on it.
+	pub fn bind_move_complete(&mut self, f: impl FnMut(&mut crate::world::World, u8, (i32, i32)) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_move_complete = Some(Box::new(f));
+	}
+
+	/// Bind a function for callback with each line of [narration](crate::narration), e.g, to pass on to text-to-speech.
+	pub fn bind_narration(&mut self, f: impl FnMut(&mut crate::world::World, &str) + 'static) {
+		if self.raw {
+			eprintln!("warning [state_listener]: rust fp bound to raw listener!");
+		}
+		self.on_narration = Some(Box::new(f));
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawinit(&mut self, f: CInitHandle) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state listener!");
+		}
+		self.on_init_ctx = (None, ptr::null_mut());
+		self.on_init_raw = f;
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawturn(&mut self, f: CTurnHandle) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_turn_ctx = (None, ptr::null_mut());
+		self.on_turn_raw = f;
+	}
+
+	/// FFI Internal. Replaces any callback bound using `_bind_rawinit`.
+	pub fn _bind_rawinit_ctx(&mut self, f: CInitCtxHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state listener!");
+		}
+		self.on_init_raw = None;
+		self.on_init_ctx = (f, ctx);
+	}
+
+	/// FFI Internal. Replaces any callback bound using `_bind_rawturn`.
+	pub fn _bind_rawturn_ctx(&mut self, f: CTurnCtxHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_turn_raw = None;
+		self.on_turn_ctx = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawupdate(&mut self, f: CUpdateHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_update_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawunit_death(&mut self, f: CUnitDeathHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_unit_death_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawattack(&mut self, f: CAttackHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_attack_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawphase_change(&mut self, f: CPhaseHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_phase_change_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawbanner_done(&mut self, f: CPhaseHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_banner_done_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawtransition_done(&mut self, f: CPhaseHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_transition_done_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawresults(&mut self, f: CResultsHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_results_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawdraw(&mut self, f: CDrawHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_draw_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawvictory_check(&mut self, f: CVictoryHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_victory_check_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawexit(&mut self, f: CExitHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_exit_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawtimeout(&mut self, f: CTimeoutHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_timeout_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawmap_loaded(&mut self, f: CMapLoadedHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_map_loaded_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawpickup(&mut self, f: CPickupHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_pickup_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawmove_complete(&mut self, f: CMoveCompleteHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_move_complete_raw = (f, ctx);
+	}
+
+	/// FFI Internal
+	pub fn _bind_rawnarration(&mut self, f: CNarrationHandle, ctx: *mut c_void) {
+		if !self.raw {
+			eprintln!("warning [state_listener]: C fp bound to state_listener!");
+		}
+		self.on_narration_raw = (f, ctx);
+	}
+
+	/// Subscribe a function to every [EngineEvent] published through this listener, after the matching callback (if any). Returns an id to [unsubscribe](StateListener::unsubscribe) with.
+	/// Functions may be subscribed to raw listeners as well.
+	pub fn subscribe(&mut self, f: impl FnMut(&mut crate::world::World, &EngineEvent) + 'static) -> u32 {
+		self.next_sub = self.next_sub.checked_add(1).unwrap_or(1);
+		self.subscribers.push((self.next_sub, Box::new(f)));
+		self.next_sub
+	}
+
+	/// Remove the function subscribed with the specified id. Returns false if there is no such function.
+	pub fn unsubscribe(&mut self, id: u32) -> bool {
+		let n = self.subscribers.len();
+		self.subscribers.retain(|(i, _)| *i != id);
+		self.subscribers.len() != n
+	}
+
+	/// Publish an event to the subscribed functions, in order of subscription, and then to the event queue of the world, if enabled.
+	/// Events with a matching callback are published by the `notify_*` methods themselves.
+	pub fn publish(&mut self, w: &mut crate::world::World, e: EngineEvent) {
+		for (_, f) in self.subscribers.iter_mut() {
+			f(w, &e);
+		}
+		if self.narrates() {
+			if let Some(text) = crate::narration::describe_event(w, &e) {
+				self.narrate(w, &text);
+			}
+		}
+		w.push_event(e);
+	}
+
+	/// Returns true if a narration callback is bound, see [StateListener::bind_narration].
+	pub fn narrates(&self) -> bool {
+		if self.raw { self.on_narration_raw.0.is_some() } else { self.on_narration.is_some() }
+	}
+
+	/// Pass a line of [narration](crate::narration) to the narration callback, if any. Empty lines are not passed on.
+	pub fn narrate(&mut self, w: &mut crate::world::World, text: &str) {
+		if text.is_empty() {
+			return;
+		}
+		if self.raw {
+			if let (Some(f), ctx) = self.on_narration_raw {
+				let t = CString::new(text).unwrap_or_default();
+				f(w, t.as_ptr(), ctx);
 			}
 		} else {
-			if let Some(f) = self.on_init {
+			if let Some(f) = &mut self.on_narration {
+				f(w, text);
+			}
+		}
+	}
+
+	/// Notify this listener that display initialization has been completed.
+	pub fn notify_init(&self) {
+		if self.raw {
+			if let Some(f) = self.on_init_raw {
+				f();
+			} else if let (Some(f), ctx) = self.on_init_ctx {
+				f(ctx);
+			}
+		} else {
+			if let Some(f) = &self.on_init {
 				f();
 			}
 		}
@@ -307,6 +1848,8 @@ impl StateListener {
 		if self.raw {
 			if let Some(f) = self.on_turn_raw {
 				f(w,ih);
+			} else if let (Some(f), ctx) = self.on_turn_ctx {
+				f(w,ih,ctx);
 			}
 		} else {
 			if let Some(f) = &mut self.on_turn {
@@ -314,6 +1857,202 @@ impl StateListener {
 			}
 		}
 	}
+
+	/// Notify this listener that a frame has elapsed.
+	pub fn notify_update(&mut self, w: &mut crate::world::World, delta: f32) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_update_raw {
+				f(w, delta, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_update {
+				f(w, delta);
+			}
+		}
+	}
+
+	/// Notify this listener that the unit with id `uid` and type id `tid` has died.
+	pub fn notify_unit_death(&mut self, w: &mut crate::world::World, uid: u8, tid: u8) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_unit_death_raw {
+				f(w, uid, tid, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_unit_death {
+				f(w, uid, tid);
+			}
+		}
+		self.publish(w, EngineEvent::UnitDied { uid: uid, type_id: tid });
+	}
+
+	/// Notify this listener that the unit with id `atk` dealt `dmg` damage to the unit with id `def`.
+	pub fn notify_attack(&mut self, w: &mut crate::world::World, atk: u8, def: u8, dmg: f32) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_attack_raw {
+				f(w, atk, def, dmg, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_attack {
+				f(w, atk, def, dmg);
+			}
+		}
+		self.publish(w, EngineEvent::UnitAttacked { attacker: atk, defender: def, damage: dmg });
+	}
+
+	/// Notify this listener that the banner announcing phase `p` has finished.
+	pub fn notify_banner_done(&mut self, w: &mut crate::world::World, p: TurnPhase) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_banner_done_raw {
+				f(w, p, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_banner_done {
+				f(w, p);
+			}
+		}
+		self.publish(w, EngineEvent::BannerDone(p));
+	}
+
+	/// Notify this listener that a transition between screens, into phase `p`, has finished.
+	pub fn notify_transition_done(&mut self, w: &mut crate::world::World, p: TurnPhase) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_transition_done_raw {
+				f(w, p, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_transition_done {
+				f(w, p);
+			}
+		}
+		self.publish(w, EngineEvent::TransitionDone(p));
+	}
+
+	/// Notify this listener that the results screen of a game with outcome `o` has been dismissed.
+	pub fn notify_results(&mut self, w: &mut crate::world::World, o: Outcome) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_results_raw {
+				f(w, o.to_raw(), ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_results {
+				f(w, o);
+			}
+		}
+		self.publish(w, EngineEvent::ResultsDismissed(o));
+	}
+
+	/// Notify this listener that the game is about to exit.
+	pub fn notify_exit(&mut self, w: &mut crate::world::World) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_exit_raw {
+				f(w, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_exit {
+				f(w);
+			}
+		}
+		self.publish(w, EngineEvent::Exit);
+	}
+
+	/// Notify this listener that time has run out in the player turn, `bank` being true if the bank has been spent.
+	pub fn notify_timeout(&mut self, w: &mut crate::world::World, bank: bool) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_timeout_raw {
+				f(w, bank, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_timeout {
+				f(w, bank);
+			}
+		}
+		self.publish(w, EngineEvent::TurnTimedOut(bank));
+	}
+
+	/// Notify this listener that the map described by `m` has been loaded.
+	pub fn notify_map_loaded(&mut self, w: &mut crate::world::World, m: &crate::world::MapInfo) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_map_loaded_raw {
+				let path = CString::new(m.path.as_str()).unwrap_or_default();
+				f(w, path.as_ptr(), ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_map_loaded {
+				f(w, m);
+			}
+		}
+		self.publish(w, EngineEvent::MapLoaded(m.path.clone()));
+	}
+
+	/// Notify this listener that the unit with id `uid` collected a pickup with effect `e`.
+	pub fn notify_pickup(&mut self, w: &mut crate::world::World, uid: u8, e: PickupEffect) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_pickup_raw {
+				let (k, v) = e.to_raw();
+				f(w, uid, k, v, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_pickup {
+				f(w, uid, e);
+			}
+		}
+		self.publish(w, EngineEvent::PickupCollected { uid: uid, effect: e });
+	}
+
+	/// Notify this listener that the unit with id `uid` ended its move on tile `t`.
+	pub fn notify_move_complete(&mut self, w: &mut crate::world::World, uid: u8, t: (i32, i32)) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_move_complete_raw {
+				f(w, uid, t.0, t.1, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_move_complete {
+				f(w, uid, t);
+			}
+		}
+		self.publish(w, EngineEvent::UnitMoved { uid: uid, tile: t });
+	}
+
+	/// Notify this listener that the game has entered phase `p`.
+	pub fn notify_phase_change(&mut self, w: &mut crate::world::World, p: TurnPhase) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_phase_change_raw {
+				f(w, p, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_phase_change {
+				f(w, p);
+			}
+		}
+		self.publish(w, EngineEvent::PhaseChanged(p));
+	}
+
+	/// Ask this listener whether the game has been won or lost. Returns [Outcome::Continue] if no check has been bound.
+	pub fn check_victory(&mut self, w: &crate::world::World) -> Outcome {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_victory_check_raw {
+				return Outcome::from_raw(f(w, ctx));
+			}
+		} else {
+			if let Some(f) = &mut self.on_victory_check {
+				return f(w);
+			}
+		}
+		Outcome::Continue
+	}
+
+	/// Notify this listener that the frame is being drawn.
+	pub fn notify_draw(&mut self, d: &mut RaylibDrawHandle<'_>, w: &crate::world::World) {
+		if self.raw {
+			if let (Some(f), ctx) = self.on_draw_raw {
+				f(w, ctx);
+			}
+		} else {
+			if let Some(f) = &mut self.on_draw {
+				f(d, w);
+			}
+		}
+	}
 }
 
 
@@ -6,10 +6,40 @@ use std::fmt::Formatter;
 use std::io::Read;
 use std::fs::File;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::cell::OnceCell;
 use raylib::math::Vector2;
 use raylib::math::Rectangle;
 use raylib::prelude::Color;
 use crate::input::Order;
+use crate::input::TurnPhase;
+use crate::input::InputScheme;
+use crate::utils::Profile;
+use crate::ai::BehaviorTree;
+use crate::ai::TreeSet;
+use crate::puzzle::Puzzle;
+use crate::tutorial::Tutorial;
+use crate::tutorial::TutorialControl;
+use crate::tutorial::TutorialGoal;
+use crate::tween::Tween;
+use crate::tween::TweenSet;
+use crate::tween::Easing;
+use crate::schedule::Scheduler;
+use crate::event::EngineEvent;
+use std::collections::VecDeque;
+use pickup::Pickup;
+use pickup::PickupEffect;
+use mixer::Mixer;
+use ambient::AmbientRegion;
+use mixer::StingerKind;
+
+pub mod influence;
+pub mod path;
+pub mod pickup;
+pub mod voice;
+pub mod mixer;
+pub mod ambient;
+pub mod ron;
 
 const EPS: f32 = 0.1;
 const CONTROL_PT: Vector2 = Vector2 {
@@ -38,23 +68,16 @@ impl TileType {
 	}
 }
 
-/// Pointer to damage calculating function;
+/// Damage calculating function or closure;
 /// *`atk` - the unit type id of the attacking unit.
 /// *`def` - the unit type id of the defending unit.
 pub enum DamageFunc {
-	Handle(fn (atk: u8, def: u8) -> f32),
+	/// The built-in formula, see [default_damage].
+	Builtin,
+	Handle(Box<dyn FnMut(u8, u8) -> f32>),
 	CHandle(extern "C" fn (atk: u8, def: u8) -> f32)
 }
 
-impl DamageFunc {
-	fn invoke(&self, atk: u8, def: u8) -> f32 {
-		match self {
-			DamageFunc::Handle(r) => r(atk, def),
-			DamageFunc::CHandle(r) => r(atk, def)
-		}
-	}
-}
-
 pub(crate) struct Projectile {
 	target: Vector2,
 	ctrlpt: Vector2,
@@ -119,6 +142,58 @@ impl Projectile {
 	}
 }
 
+/// Metadata of a map file, as passed to the `on_map_loaded` [callback](crate::utils::StateListener) once [loaded](load_world).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapInfo {
+	/// Path the map was loaded from.
+	pub path: String,
+	/// Title of the map.
+	pub title: String,
+	/// Text shown before the map begins.
+	pub intro_text: String,
+	/// Text shown when the player wins.
+	pub victory_text: String,
+	/// Text shown when the player loses.
+	pub defeat_text: String,
+	/// Width of the map, in tiles.
+	pub width: usize,
+	/// Height of the map, in tiles.
+	pub height: usize
+}
+
+/// A slide of the slideshow shown before the intro text, see [World::set_intro_slides].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slide {
+	/// Id of the texture shown, scaled to fit the screen.
+	pub tex: u8,
+	/// Text shown beneath the image.
+	pub caption: String,
+	/// Seconds the slide is shown, fading in and out, unless skipped.
+	pub duration: f32
+}
+
+/// A screen of text scrolling up, e.g, credits or an epilogue, see [World::show_scrolling_text].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollText {
+	/// The text shown, which may be [styled](crate::text).
+	pub text: String,
+	/// Pixels scrolled per second.
+	pub speed: f32
+}
+
+/// Pixels per second a [ScrollText] scrolls, unless specified.
+pub const DEFAULT_SCROLL_SPEED: f32 = 40.0;
+
+/// Presentation of the battle to the player: slides shown before the intro text, the scrolling text screen requested, if any, how health is rounded and the popups requested; boxed to keep the world small.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Presentation {
+	pub(crate) slides: Vec<Slide>,
+	pub(crate) scroll: Option<ScrollText>,
+	pub(crate) hp_rounding: HpRounding,
+	/// Popups requested since last shown: the unit, text and kind.
+	pub(crate) popups: Vec<(u8, String, PopupKind)>
+}
+
 #[derive(Debug)]
 /// Plain struct to store map data
 struct TileMap {
@@ -142,10 +217,124 @@ struct TileMap {
  	/// Text shown when player loses.
  	defeat_text: String,
 
+	/// Layers of tiles stacked over the tiles of the map, in order of addition.
+	layers: Vec<TileLayer>,
+	/// Elevation of each tile, row by row; empty if the map is flat.
+	heights: Vec<u8>,
+	/// Areas with looping ambient sounds, see [ambient].
+	ambient: Vec<AmbientRegion>,
+
 	/// Flag to show or hide map.	
 	show: bool
 }
 
+/// Least zoom of the camera, see [World::set_zoom].
+pub const MIN_ZOOM: f32 = 0.5;
+/// Greatest zoom of the camera, see [World::set_zoom].
+pub const MAX_ZOOM: f32 = 2.0;
+/// Default rate at which the camera closes in on the unit it follows, per second, see [World::set_camera_follow_rate].
+pub const CAM_FOLLOW_RATE: f32 = 8.0;
+
+/// Tile id of the empty tiles of a [tile layer](World::add_tile_layer), through which lower layers show.
+pub const EMPTY_TILE: u8 = 0xff;
+
+/// Where a layer of tiles is drawn, see [World::add_tile_layer].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayerKind {
+	/// Drawn over the tiles of the map, under decoration, e.g, roads.
+	Ground = 0,
+	/// Drawn over ground layers, under units and statics, e.g, grass and rubble.
+	Decoration = 1,
+	/// Drawn over units and statics, e.g, treetops.
+	Overlay = 2
+}
+
+impl LayerKind {
+	/// Convert from the raw value used by the native API and world files. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<LayerKind> {
+		match v {
+			0 => Some(LayerKind::Ground),
+			1 => Some(LayerKind::Decoration),
+			2 => Some(LayerKind::Overlay),
+			_ => None
+		}
+	}
+
+	/// Returns the kind with the specified name, ignoring case: "ground", "decoration" or "overlay".
+	pub fn from_name(name: &str) -> Option<LayerKind> {
+		match name.to_ascii_lowercase().as_str() {
+			"ground" => Some(LayerKind::Ground),
+			"decoration" => Some(LayerKind::Decoration),
+			"overlay" => Some(LayerKind::Overlay),
+			_ => None
+		}
+	}
+}
+
+/// A layer of tiles over the tiles of the map; tiles are row by row, as those of the map.
+#[derive(Debug)]
+struct TileLayer {
+	kind: LayerKind,
+	tiles: Vec<u8>
+}
+
+/// Layout of the tiles of a world, see [World::blank_hex].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grid {
+	/// Isometric squares; a tile has four neighbours, along x and y.
+	Isometric = 0,
+	/// Pointy-top hexagons in rows, odd rows shifted right by half a tile; a tile has six neighbours.
+	/// Tiles are numbered by column and row, so that maps stay rectangular.
+	Hex = 1
+}
+
+impl Grid {
+	/// Returns the number of steps a unit takes to walk between two tiles, ignoring what stands in the way.
+	pub fn distance(self, t1: (i32, i32), t2: (i32, i32)) -> i32 {
+		match self {
+			Grid::Isometric => tile_distance(t1, t2),
+			Grid::Hex => {
+				let (q1, r1) = hex_axial(t1);
+				let (q2, r2) = hex_axial(t2);
+				let (dq, dr) = (q1 - q2, r1 - r2);
+				(dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+			}
+		}
+	}
+
+	/// Returns the tiles next to a tile, i.e, a step away.
+	pub fn neighbours(self, (x, y): (i32, i32)) -> Vec<(i32, i32)> {
+		match self {
+			Grid::Isometric => vec![(x+1, y), (x-1, y), (x, y+1), (x, y-1)],
+			Grid::Hex => {
+				// Columns of the rows above and below are shifted by half a tile.
+				let s = y & 1;
+				vec![(x+1, y), (x-1, y), (x+s, y-1), (x+s-1, y-1), (x+s, y+1), (x+s-1, y+1)]
+			}
+		}
+	}
+
+	/// Returns the tiles within `r` steps of a tile, including the tile.
+	pub fn range(self, (x, y): (i32, i32), r: i32) -> Vec<(i32, i32)> {
+		let mut v = Vec::new();
+		for ty in y-r..=y+r {
+			for tx in x-r..=x+r {
+				if self.distance((x, y), (tx, ty)) <= r {
+					v.push((tx, ty));
+				}
+			}
+		}
+		v
+	}
+}
+
+/// Axial co-ordinates of a hexagonal tile, from its column and row.
+fn hex_axial((x, y): (i32, i32)) -> (i32, i32) {
+	(x - (y - (y & 1)) / 2, y)
+}
+
 impl TileMap {
 	fn empty() -> TileMap {
 		TileMap {
@@ -157,13 +346,288 @@ impl TileMap {
 			intro_text: String::new(),
 			victory_text: String::new(),
 			defeat_text: String::new(),
+			layers: vec![],
+			heights: vec![],
+			ambient: vec![],
 			show: false
 		}
 	}
 }
 
-fn no_dmg(_: u8, _: u8) -> f32 {
-	0.0
+/// Audio request made through the world, e.g, from a callback. Requests are carried out by the display at the end of the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AudioCmd {
+	/// Play the sound with specified id.
+	PlaySound(u8),
+	/// Stop the background music.
+	StopMusic,
+	/// Set the volume of all music tracks, in the range [0, 1].
+	MusicVolume(f32),
+	/// Set the volume of all sounds, in the range [0, 1].
+	SfxVolume(f32),
+	/// Set the master volume, in the range [0, 1].
+	MasterVolume(f32),
+	/// Set the volume of the track of [ambient regions](ambient) with specified id, in the range [0, 1], starting it if not playing, or stopping it at 0.
+	AmbientVolume(u8, f32)
+}
+
+/// Notable event of a battle, recorded by the world and reported through [StateListener](crate::utils::StateListener) callbacks and the battle log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BattleEvent {
+	/// The unit with the first id dealt damage to the unit with the second id.
+	Attack(u8, u8, f32),
+	/// The unit with the specified id, and type id, died.
+	Kill(u8, u8),
+	/// The unit with the specified id collected a [pickup].
+	Pickup(u8, PickupEffect)
+}
+
+/// Describe a battle event in a line of the battle log. Units are named by their unit type.
+pub fn describe_event(w: &World, ev: &BattleEvent) -> String {
+	let uname = |uid: &u8| w.units.get(uid).and_then(|u| w.unit_types.get(&u.type_id)).map_or("?", |ut| ut.name.as_str());
+	match ev {
+		BattleEvent::Attack(a, d, dmg) => format!("{} hits {} for [red]{:.1}[/red]", uname(a), uname(d), dmg),
+		BattleEvent::Kill(_, tid) => format!("{} is slain", w.unit_types.get(tid).map_or("?", |ut| ut.name.as_str())),
+		BattleEvent::Pickup(u, e) => match e {
+			PickupEffect::Heal(f) => format!("{} recovers [green]{:.0}%[/green] health", uname(u), f * 100.0),
+			PickupEffect::Boost(f) => format!("{} gains [green]{:.0}%[/green] attack", uname(u), f * 100.0),
+			PickupEffect::Gold(n) => format!("{} finds [yellow]{}[/yellow] gold", uname(u), n)
+		}
+	}
+}
+
+/// Experience gained by the player for each enemy unit defeated, in addition to one point per (whole) point of damage dealt.
+pub const KILL_XP: u32 = 10;
+
+/// Statistics of the current battle, collected from [battle events](BattleEvent) and shown on the results screen.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BattleStats {
+	/// Number of turns taken, including the current one.
+	pub turns: u32,
+	/// Total damage dealt by player units.
+	pub damage_dealt: f32,
+	/// Total damage received by player units.
+	pub damage_received: f32,
+	/// Number of player units that died.
+	pub units_lost: u32,
+	/// Number of enemy units that died.
+	pub enemies_defeated: u32,
+	/// Experience gained by the player, see [KILL_XP].
+	pub xp_gained: u32
+}
+
+/// Difficulty of a battle, see [World::set_difficulty]. Every field is a multiplier; [Difficulty::NORMAL] leaves the game unchanged.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+	/// Multiplier for damage dealt by player units, under the [built-in damage formula](default_damage).
+	pub player_damage: f32,
+	/// Multiplier for damage dealt by enemy units, under the built-in damage formula.
+	pub enemy_damage: f32,
+	/// Multiplier for the effective health of enemy units; all damage they take is divided by it.
+	pub enemy_health: f32,
+	/// Multiplier for the alertness of enemy units with a [Behavior]: it scales the radius of guards, and above 1 patrolling units
+	/// chase player units within `(aggression - 1) * movement` tiles beyond their range.
+	pub aggression: f32
+}
+
+impl Difficulty {
+	/// Player units deal more damage, enemy units deal less, and are less alert.
+	pub const EASY: Difficulty = Difficulty { player_damage: 1.25, enemy_damage: 0.75, enemy_health: 0.8, aggression: 0.75 };
+	/// The default difficulty.
+	pub const NORMAL: Difficulty = Difficulty { player_damage: 1.0, enemy_damage: 1.0, enemy_health: 1.0, aggression: 1.0 };
+	/// Player units deal less damage, enemy units deal more, are tougher, and more alert.
+	pub const HARD: Difficulty = Difficulty { player_damage: 0.9, enemy_damage: 1.25, enemy_health: 1.25, aggression: 1.5 };
+
+	/// Returns the preset with the specified name ("easy", "normal" or "hard", ignoring case), if any.
+	pub fn preset(name: &str) -> Option<Difficulty> {
+		match name.to_ascii_lowercase().as_str() {
+			"easy" => Some(Difficulty::EASY),
+			"normal" => Some(Difficulty::NORMAL),
+			"hard" => Some(Difficulty::HARD),
+			_ => None
+		}
+	}
+
+	/// Returns the preset with the specified index: 0 easy, 1 normal, or 2 hard.
+	pub fn from_raw(v: u8) -> Option<Difficulty> {
+		[Difficulty::EASY, Difficulty::NORMAL, Difficulty::HARD].get(v as usize).cloned()
+	}
+}
+
+/// How the elevation of tiles (see [World::set_elevation]) is drawn, and bears on movement and attacks. See [World::set_elevation_rules].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElevationRules {
+	/// Pixels a tile is raised on screen per level of elevation. Default is 8.
+	pub step: i32,
+	/// Most levels a unit may climb or descend in a step; steeper rises are cliffs, which block movement. Default is 1.
+	pub max_climb: u8,
+	/// Tiles added to the attack range of a unit standing higher than its target. Default is 1.
+	pub range_bonus: u8,
+	/// Fraction by which the [built-in damage formula](default_damage) raises damage per level the attacker stands above the defender, and lowers it per level below, down to half. Default is 0.1.
+	pub damage_bonus: f32
+}
+
+impl Default for ElevationRules {
+	fn default() -> ElevationRules {
+		ElevationRules {
+			step: 8,
+			max_climb: 1,
+			range_bonus: 1,
+			damage_bonus: 0.1
+		}
+	}
+}
+
+impl Default for Difficulty {
+	fn default() -> Difficulty {
+		Difficulty::NORMAL
+	}
+}
+
+/// The actions a player unit may take in its turn, set for all units with [World::set_actions], and for the units of a type with [UnitType::set_actions].
+/// [Actions::DEFAULT] lets a unit move up to its movement, and then attack once. The built-in AI gives each enemy unit one move and one attack regardless.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Actions {
+	/// Attacks per turn, from the tile the unit stands on; a unit with none only moves.
+	pub attacks: u8,
+	/// Flag to let a unit move on after attacking, with the movement it has left (and attack again, if it has attacks left).
+	pub move_after_attack: bool
+}
+
+impl Actions {
+	/// Move, then attack once.
+	pub const DEFAULT: Actions = Actions { attacks: 1, move_after_attack: false };
+}
+
+impl Default for Actions {
+	fn default() -> Actions {
+		Actions::DEFAULT
+	}
+}
+
+/// State of an [Objective].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectiveState {
+	/// Not yet decided.
+	Pending = 0,
+	/// Achieved by the player.
+	Completed = 1,
+	/// Can no longer be achieved.
+	Failed = 2
+}
+
+impl ObjectiveState {
+	/// Convert a raw value (as used by the native API), or None if out of range.
+	pub fn from_raw(v: u8) -> Option<ObjectiveState> {
+		match v {
+			0 => Some(ObjectiveState::Pending),
+			1 => Some(ObjectiveState::Completed),
+			2 => Some(ObjectiveState::Failed),
+			_ => None
+		}
+	}
+}
+
+/// Kinds of text rising from the tile of a unit, each drawn in its own colour and font, see [Theme::popup_colors](crate::display::Theme::popup_colors).
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+	/// Damage dealt, by an attack or otherwise.
+	Damage = 0,
+	/// Health restored.
+	Heal = 1,
+	/// A status applied or removed, e.g, "POISONED".
+	Status = 2,
+	/// An attack that missed.
+	Miss = 3,
+	/// A critical hit.
+	Crit = 4
+}
+
+impl PopupKind {
+	/// Number of kinds.
+	pub const COUNT: usize = 5;
+
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<PopupKind> {
+		match v {
+			0 => Some(PopupKind::Damage),
+			1 => Some(PopupKind::Heal),
+			2 => Some(PopupKind::Status),
+			3 => Some(PopupKind::Miss),
+			4 => Some(PopupKind::Crit),
+			_ => None
+		}
+	}
+
+	/// Returns the kind with the specified name, ignoring case: "damage", "heal", "status", "miss" or "crit".
+	pub fn from_name(name: &str) -> Option<PopupKind> {
+		match name.to_ascii_lowercase().as_str() {
+			"damage" => Some(PopupKind::Damage),
+			"heal" => Some(PopupKind::Heal),
+			"status" => Some(PopupKind::Status),
+			"miss" => Some(PopupKind::Miss),
+			"crit" => Some(PopupKind::Crit),
+			_ => None
+		}
+	}
+}
+
+/// How health (and damage) is rounded to whole numbers for display, see [World::set_hp_rounding].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HpRounding {
+	/// Round up, so that units with any health left show at least 1. The default.
+	#[default]
+	Ceil = 0,
+	/// Round down.
+	Floor = 1,
+	/// Round to the nearest whole number, halves away from zero.
+	Round = 2
+}
+
+impl HpRounding {
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<HpRounding> {
+		match v {
+			0 => Some(HpRounding::Ceil),
+			1 => Some(HpRounding::Floor),
+			2 => Some(HpRounding::Round),
+			_ => None
+		}
+	}
+
+	/// Returns the rounding with the specified name, ignoring case: "ceil", "floor" or "round".
+	pub fn from_name(name: &str) -> Option<HpRounding> {
+		match name.to_ascii_lowercase().as_str() {
+			"ceil" => Some(HpRounding::Ceil),
+			"floor" => Some(HpRounding::Floor),
+			"round" => Some(HpRounding::Round),
+			_ => None
+		}
+	}
+
+	/// Round a value of health.
+	pub fn apply(&self, hp: f32) -> i32 {
+		match self {
+			HpRounding::Ceil => hp.ceil() as i32,
+			HpRounding::Floor => hp.floor() as i32,
+			HpRounding::Round => hp.round() as i32
+		}
+	}
+}
+
+/// A goal of the current level, listed by the objective tracker of the HUD.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Objective {
+	/// Description of the objective; may contain [markup](crate::text).
+	pub text: String,
+	/// Whether the objective is pending, completed or failed.
+	pub state: ObjectiveState
 }
 
 /// Plain struct to contain sprites, tilemap, gameobjects etc.
@@ -182,14 +646,75 @@ pub struct World {
 	tilemap: TileMap,
 	/// Size of isometric tiles in pixels
 	tile_size: (i32, i32),
+	/// Layout of the tiles, see [Grid].
+	grid: Grid,
 	/// Camera abscissa in world-co-ordinates.
 	pub cam_wx: f32,
 	/// Camera ordinate in world-co-ordinates.
 	pub cam_wy: f32,
 	/// Camera offset
 	pub coff: (f32, f32),
+	/// Camera zoom, see [World::set_zoom].
+	zoom: f32,
+	/// Input device the player last used, see [World::input_scheme].
+	input_scheme: InputScheme,
+	/// The id of the unit followed by the camera, if any. The followed unit is kept at the camera offset, see [World::follow_unit].
+	pub cam_follow: Option<u8>,
+	/// Rate at which the camera closes in on the unit it follows, per second; 0 keeps the unit exactly in place.
+	cam_follow_rate: f32,
+	/// Flag to follow walking units with the camera, see [World::set_camera_auto_follow].
+	cam_auto_follow: bool,
 	/// The internal identifier of the music currently playing in the background.
 	pub bgm_id: u8,
+	/// Persistent flags and counters of the player, see [Profile].
+	pub profile: Profile,
+	/// Audio requests yet to be carried out.
+	pub(crate) audio: Vec<AudioCmd>,
+	/// Battle events that have not yet been reported.
+	pub(crate) events: Vec<BattleEvent>,
+	/// Seconds left before each group of voice lines of a unit type may bark again, see [voice].
+	pub(crate) bark_cooldowns: HashMap<(u8, voice::BarkKind), f32>,
+	/// Volumes and stingers, see [mixer].
+	pub(crate) mixer: Mixer,
+	pub(crate) objectives: Vec<Objective>,
+	/// Number of the current turn, starting at 1.
+	pub(crate) turn: u32,
+	pub(crate) stats: BattleStats,
+	/// Phase to resume at, and the units that have acted that turn, once a restored battle begins.
+	pub(crate) resume: Option<(TurnPhase, Vec<u8>)>,
+	/// Units that have acted in the player turn, in order of id, as kept by the input handler.
+	pub(crate) acted: Vec<u8>,
+	pub(crate) difficulty: Difficulty,
+	/// Actions of units whose type does not set its own, see [World::set_actions].
+	pub(crate) actions: Actions,
+	/// Built-in AI of enemy units without a behavior, if enabled, see [World::set_ai_controller].
+	pub(crate) ai_controller: Option<AiController>,
+	/// Tiles seen by player units, if fog of war is enabled, see [World::set_fog_of_war].
+	pub(crate) fog: Option<HashSet<(i32, i32)>>,
+	pub(crate) ai_plans: Vec<AiPlan>,
+	/// Behavior trees assigned to unit types by the map, see [World::load_ai].
+	pub(crate) ai_trees: HashMap<u8, BehaviorTree>,
+	/// The puzzle of the current map, if any, see [World::load_puzzle].
+	pub(crate) puzzle: Option<Box<Puzzle>>,
+	/// The tutorial of the current map, if any, see [World::load_tutorial].
+	pub(crate) tutorial: Option<Box<Tutorial>>,
+	/// Pickups lying on tiles, in order of placement.
+	pub(crate) pickups: Vec<Pickup>,
+	/// Path of the map loaded since listeners were last notified, if any.
+	pub(crate) loaded_map: Option<String>,
+	/// Tweens added by the game (see [World::tween]), and the camera pan.
+	pub(crate) tweens: Box<TweenSet>,
+	/// Callbacks scheduled by the game, see [World::schedule_after].
+	pub(crate) schedule: Box<Scheduler>,
+	/// Slides shown before the intro text, the scrolling text screen requested and how health is rounded, see [World::set_intro_slides], [World::show_scrolling_text] and [World::set_hp_rounding].
+	pub(crate) presentation: Box<Presentation>,
+	/// Events published since last polled, if the queue is enabled, see [World::enable_event_queue].
+	pub(crate) event_queue: Option<VecDeque<EngineEvent>>,
+	/// Cheats set on the world, see [debug_tools](crate::debug_tools).
+	#[cfg(feature = "debug_tools")]
+	pub(crate) cheats: crate::debug_tools::Cheats,
+	/// Rules of tile elevation, see [World::set_elevation_rules].
+	pub(crate) elevation: ElevationRules,
 	/// The function pointer for damage function
 	pub(crate) dmg_func: DamageFunc,
 }
@@ -211,20 +736,7 @@ impl World {
 	/// Create an empty world struct.
 	/// Tile size is 96x48.
 	pub fn blank() -> World {
-		World {
-			statics: vec![],
-			projectiles: vec![],
-			unit_types: HashMap::new(),
-			units: HashMap::new(),
-			origin: (0,0),
-			tile_size: (96, 48),
-			tilemap: TileMap::empty(),
-			cam_wx: 0.0,
-			cam_wy: 0.0,
-			coff: (0.0, 0.0),
-			bgm_id: 0,
-			dmg_func: DamageFunc::Handle(no_dmg),
-		}
+		World::blank_o(0, 0, 96, 48)
 	}
 
 	/// Create an empty world with specified origin and tile size
@@ -240,13 +752,59 @@ impl World {
 			units: HashMap::new(),
 			origin: (ox,oy),
 			tile_size: (tx,ty),
+			grid: Grid::Isometric,
 			tilemap: TileMap::empty(),
 			cam_wx: 0.0,
 			cam_wy: 0.0,
 			coff: (0.0, 0.0),
+			zoom: 1.0,
+			input_scheme: InputScheme::KeyboardMouse,
+			cam_follow: None,
+			cam_follow_rate: CAM_FOLLOW_RATE,
+			cam_auto_follow: false,
 			bgm_id: 0,
-			dmg_func: DamageFunc::Handle(no_dmg),
-		}	
+			profile: Profile::new(),
+			audio: vec![],
+			events: vec![],
+			bark_cooldowns: HashMap::new(),
+			mixer: Mixer::default(),
+			objectives: vec![],
+			turn: 1,
+			stats: BattleStats::default(),
+			resume: None,
+			acted: vec![],
+			difficulty: Difficulty::NORMAL,
+			actions: Actions::DEFAULT,
+			ai_controller: None,
+			fog: None,
+			ai_plans: vec![],
+			ai_trees: HashMap::new(),
+			puzzle: None,
+			tutorial: None,
+			pickups: vec![],
+			loaded_map: None,
+			tweens: Box::default(),
+			schedule: Box::default(),
+			presentation: Box::default(),
+			event_queue: None,
+			#[cfg(feature = "debug_tools")]
+			cheats: Default::default(),
+			elevation: ElevationRules::default(),
+			dmg_func: DamageFunc::Builtin,
+		}
+	}
+
+	/// Create an empty world of hexagonal tiles (see [Grid::Hex]) with specified origin and tile size, i.e, the width and height of a hexagon.
+	/// Rows of hexagons overlap by a quarter of their height.
+	pub fn blank_hex(ox: i32, oy: i32, tx: i32, ty: i32) -> World {
+		let mut w = World::blank_o(ox, oy, tx, ty);
+		w.grid = Grid::Hex;
+		w
+	}
+
+	/// Returns the layout of the tiles of the world.
+	pub fn grid(&self) -> Grid {
+		self.grid
 	}
 
 	/// Get camera position in screen co-ordinates
@@ -261,10 +819,49 @@ impl World {
 
 	/// Set camera position in world co-ordinates
 	pub fn set_cpos(&mut self, x: f32, y: f32) {
-		self.cam_wy = x;
+		self.cam_wx = x;
 		self.cam_wy = y;
 	}
 
+	/// Returns the zoom of the camera; 1.0 draws tiles at their size.
+	pub fn zoom(&self) -> f32 {
+		self.zoom
+	}
+
+	/// Set the zoom of the camera, clamped between [MIN_ZOOM] and [MAX_ZOOM]; the top left corner of the screen stays in place. Default is 1.0.
+	pub fn set_zoom(&mut self, zoom: f32) {
+		if !zoom.is_finite() {
+			eprintln!("warning [world]: ignoring zoom {}", zoom);
+			return;
+		}
+		self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+	}
+
+	/// Set the zoom of the camera (see [World::set_zoom]), keeping the point at screen position (`sx`, `sy`) in place, e.g, the middle of a pinch.
+	pub fn zoom_at(&mut self, zoom: f32, sx: f32, sy: f32) {
+		let old = self.zoom;
+		self.set_zoom(zoom);
+		let k = 1.0 / old - 1.0 / self.zoom;
+		_pan(self, sx * k, sy * k);
+	}
+
+	/// Move the camera by a distance on screen (in pixels), e.g, that of a drag, stopping any [camera pan](World::pan_camera).
+	pub fn pan_screen(&mut self, dx: f32, dy: f32) {
+		self.tweens.pan = None;
+		_pan(self, dx / self.zoom, dy / self.zoom);
+	}
+
+	/// Returns the input device the player last used, which HUD hints should name the controls of. Default is keyboard and mouse.
+	/// The running game switches it as soon as another device is used, publishing an [InputSchemeChanged](EngineEvent::InputSchemeChanged) event.
+	pub fn input_scheme(&self) -> InputScheme {
+		self.input_scheme
+	}
+
+	/// Set the input scheme, e.g, to that of the platform before the game begins; it is switched again once the player uses another device.
+	pub fn set_input_scheme(&mut self, scheme: InputScheme) {
+		self.input_scheme = scheme;
+	}
+
 	/// Returns the size of the tilemap as a tuple (width, height)
 	pub fn map_size(&self) -> (usize, usize) {
 		return (self.tilemap.map_width, self.tilemap.map_height)
@@ -275,14 +872,607 @@ impl World {
 		return self.tilemap.show;
 	}
 
+	/// Stack a layer of tiles over the tiles of the map, drawn with the same tileset; layers of a kind are drawn in order of addition.
+	/// Tiles are row by row, as many as those of the map; [EMPTY_TILE] leaves a tile empty. Layers do not change where units may move.
+	/// Returns false (with a warning) if no map is loaded, or the number of tiles differs from that of the map.
+	pub fn add_tile_layer(&mut self, kind: LayerKind, tiles: Vec<u8>) -> bool {
+		if !self.tilemap.show || tiles.len() != self.tilemap.map_tiles.len() {
+			eprintln!("warning [world]: tile layer of {} tiles does not match the map ({} tiles)", tiles.len(), self.tilemap.map_tiles.len());
+			return false;
+		}
+		self.tilemap.layers.push(TileLayer { kind: kind, tiles: tiles });
+		true
+	}
+
+	/// Remove all layers of tiles stacked over the map, including those loaded with it.
+	pub fn clear_tile_layers(&mut self) {
+		self.tilemap.layers.clear();
+	}
+
+	/// Set the elevation of the tiles of the map, row by row, as many as its tiles; or flatten the map if empty. Raised tiles are drawn higher, on a column of their own tile,
+	/// and units may not step across cliffs, i.e, rises steeper than the [ElevationRules] allow; see [elevation_at].
+	/// Returns false (with a warning) if no map is loaded, or the number of heights differs from that of the tiles of the map.
+	pub fn set_elevation(&mut self, heights: Vec<u8>) -> bool {
+		if !heights.is_empty() && (!self.tilemap.show || heights.len() != self.tilemap.map_tiles.len()) {
+			eprintln!("warning [world]: elevation of {} tiles does not match the map ({} tiles)", heights.len(), self.tilemap.map_tiles.len());
+			return false;
+		}
+		self.tilemap.heights = heights;
+		true
+	}
+
+	/// Returns true if any tile of the map is raised.
+	pub fn has_elevation(&self) -> bool {
+		self.tilemap.heights.iter().any(|h| *h > 0)
+	}
+
+	/// Set how elevation is drawn, and bears on movement and attacks. A negative step is replaced by 0, and a damage bonus that is negative or not finite by 0.
+	pub fn set_elevation_rules(&mut self, r: ElevationRules) {
+		self.elevation = ElevationRules {
+			step: r.step.max(0),
+			damage_bonus: if r.damage_bonus.is_finite() { r.damage_bonus.max(0.0) } else { 0.0 },
+			..r
+		};
+	}
+
+	/// Returns the rules of elevation.
+	pub fn elevation_rules(&self) -> ElevationRules {
+		self.elevation
+	}
+
+	/// Returns the kinds of the layers of tiles stacked over the map, in order of addition; a layer is referred to by its index in this list.
+	pub fn tile_layers(&self) -> Vec<LayerKind> {
+		self.tilemap.layers.iter().map(|l| l.kind).collect()
+	}
+
+	/// Add an area with a looping ambient sound to the map, see [ambient]. Regions are replaced when another map is loaded.
+	pub fn add_ambient_region(&mut self, r: AmbientRegion) {
+		self.tilemap.ambient.push(r);
+	}
+
+	/// Returns the areas of the map with looping ambient sounds, in order of addition.
+	pub fn ambient_regions(&self) -> &[AmbientRegion] {
+		&self.tilemap.ambient
+	}
+
+	/// Remove all ambient regions of the map; their sounds stop on the next frame.
+	pub fn clear_ambient_regions(&mut self) {
+		self.tilemap.ambient.clear();
+	}
+
+	/// Returns the tiles of the map, row by row.
+	pub(crate) fn map_tiles(&self) -> &[u8] {
+		&self.tilemap.map_tiles
+	}
+
+	/// Replace the tiles of the map, which must have as many tiles.
+	pub(crate) fn set_map_tiles(&mut self, tiles: Vec<u8>) {
+		debug_assert_eq!(tiles.len(), self.tilemap.map_tiles.len());
+		self.tilemap.map_tiles = tiles;
+	}
+
 	/// Set the music id for the background music.
 	pub fn set_bgm(&mut self, id: u8) {
 		self.bgm_id = id;
 	}
 
+	/// Play the sound with specified id once. Ids without a loaded sound are ignored.
+	pub fn play_sound(&mut self, id: u8) {
+		self.audio.push(AudioCmd::PlaySound(id));
+	}
+
+	/// Stop the background music. It resumes only when the music id is changed.
+	pub fn stop_music(&mut self) {
+		self.audio.push(AudioCmd::StopMusic);
+	}
+
+	/// Returns the audio mixer, with the volumes set and the stingers, see [mixer].
+	pub fn mixer(&self) -> &Mixer {
+		&self.mixer
+	}
+
+	/// Set the master volume, clamped to [0, 1]. While muted, audio stays silent.
+	pub fn set_master_volume(&mut self, v: f32) {
+		self.mixer.master_volume = v.clamp(0.0, 1.0);
+		self.audio.push(AudioCmd::MasterVolume(self.mixer.effective_master_volume()));
+	}
+
+	/// Mute or unmute all audio, keeping the volumes set, e.g, while the game is in the background.
+	pub fn set_muted(&mut self, on: bool) {
+		self.mixer.muted = on;
+		self.audio.push(AudioCmd::MasterVolume(self.mixer.effective_master_volume()));
+	}
+
+	/// Mute all audio while the window is not focused or minimized, unmuting once it is focused again, without pausing the game (see [Display::set_pause_unfocused](crate::display::Display::set_pause_unfocused)). Default is false.
+	pub fn set_mute_unfocused(&mut self, on: bool) {
+		self.mixer.mute_unfocused = on;
+	}
+
+	/// Set the master, music and sound effects volumes, and muting while unfocused, of `s`.
+	pub fn apply_audio_settings(&mut self, s: &crate::utils::Settings) {
+		self.set_master_volume(s.master_volume);
+		self.set_music_volume(s.music_volume);
+		self.set_sfx_volume(s.sfx_volume);
+		self.set_mute_unfocused(s.mute_unfocused);
+	}
+
+	/// Set the volume of background music, clamped to [0, 1]. While a stinger plays, the music stays ducked.
+	pub fn set_music_volume(&mut self, v: f32) {
+		self.mixer.music_volume = v.clamp(0.0, 1.0);
+		self.audio.push(AudioCmd::MusicVolume(self.mixer.effective_music_volume()));
+	}
+
+	/// Set the volume of sound effects, clamped to [0, 1].
+	pub fn set_sfx_volume(&mut self, v: f32) {
+		self.mixer.sfx_volume = v.clamp(0.0, 1.0);
+		self.audio.push(AudioCmd::SfxVolume(self.mixer.sfx_volume));
+	}
+
+	/// Set the stinger of `kind` to the sound with specified id, `length` seconds long, for which the music stays ducked; None removes it.
+	/// Negative or non-finite lengths are replaced by 0.
+	pub fn set_stinger(&mut self, kind: StingerKind, stinger: Option<(u8, f32)>) {
+		self.mixer.stingers[kind as usize] = stinger.map(|(id, len)| (id, if len.is_finite() { len.max(0.0) } else { 0.0 }));
+	}
+
+	/// Set the fraction of its volume the background music is ducked to while a stinger plays, clamped to [0, 1]; 1 leaves it as is.
+	pub fn set_duck_level(&mut self, level: f32) {
+		self.mixer.duck_level = level.clamp(0.0, 1.0);
+	}
+
+	/// Play the stinger of `kind`, ducking the background music until it ends. Stingers playing together duck the music until the last ends.
+	/// Returns false if no stinger of the kind is set.
+	pub fn play_stinger(&mut self, kind: StingerKind) -> bool {
+		match self.mixer.stinger(kind) {
+			Some((id, len)) => {
+				self.mixer.hold = self.mixer.hold.max(len);
+				self.play_sound(id);
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Returns the number of the current turn, starting at 1. A turn ends once the enemy has carried out its orders.
+	pub fn turn(&self) -> u32 {
+		self.turn
+	}
+
+	/// Open the player profile stored at `path` (see [Profile::open]), replacing the current profile, which is saved first.
+	/// Returns false if the file exists but could not be read.
+	pub fn open_profile(&mut self, path: &str) -> bool {
+		self.save_profile();
+		match Profile::open(path) {
+			Ok(p) => {
+				self.profile = p;
+				true
+			},
+			Err(e) => {
+				eprintln!("warning [profile]: failed to open {}: {}", path, e);
+				false
+			}
+		}
+	}
+
+	/// Save the player profile to its file, if it has changed. Returns false if it could not be written.
+	pub fn save_profile(&mut self) -> bool {
+		match self.profile.save() {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("warning [profile]: failed to save {}: {}", self.profile.path().unwrap_or("?"), e);
+				false
+			}
+		}
+	}
+
+	/// Save a snapshot of the battle (see [save](crate::save)) to the file at `path`, to be resumed at the player turn. Returns false if it could not be written.
+	/// See [save_state] for the error.
+	pub fn save_battle(&self, path: &str) -> bool {
+		match save_state(self, path) {
+			Ok(()) => true,
+			Err(e) => {
+				eprintln!("warning [save]: {}", e);
+				false
+			}
+		}
+	}
+
+	/// Restore the snapshot stored at `path` (see [save](crate::save)), before the game begins. The level introduction is then skipped.
+	/// Returns false (leaving the world unchanged) if it could not be read, or is invalid. See [load_state] for the error.
+	pub fn load_battle(&mut self, path: &str) -> bool {
+		match load_state(self, path) {
+			Ok(_) => true,
+			Err(e) => {
+				eprintln!("warning [save]: {}", e);
+				false
+			}
+		}
+	}
+
+	/// Returns the statistics of the battle so far.
+	pub fn stats(&self) -> BattleStats {
+		BattleStats { turns: self.turn, ..self.stats }
+	}
+
+	/// Update the battle statistics with an event. The units involved must not have been removed yet.
+	pub(crate) fn record_stats(&mut self, ev: &BattleEvent) {
+		let is_player = |uid: &u8| self.units.get(uid).map(|u| u.player);
+		match ev {
+			BattleEvent::Attack(a, d, dmg) => {
+				// Damage functions may return negative values; those never count against the totals.
+				let dmg = dmg.max(0.0);
+				match (is_player(a), is_player(d)) {
+					(Some(true), Some(false)) => {
+						self.stats.xp_gained += (self.stats.damage_dealt + dmg) as u32 - self.stats.damage_dealt as u32;
+						self.stats.damage_dealt += dmg;
+					},
+					(Some(false), Some(true)) => self.stats.damage_received += dmg,
+					_ => {}
+				}
+			},
+			BattleEvent::Kill(uid, _) => match is_player(uid) {
+				Some(true) => self.stats.units_lost += 1,
+				Some(false) => {
+					self.stats.enemies_defeated += 1;
+					self.stats.xp_gained += KILL_XP;
+				},
+				None => {}
+			},
+			BattleEvent::Pickup(..) => {}
+		}
+	}
+
+	/// Add a pending objective, returning its index.
+	pub fn add_objective(&mut self, text: &str) -> usize {
+		self.objectives.push(Objective { text: text.to_string(), state: ObjectiveState::Pending });
+		self.objectives.len() - 1
+	}
+
+	/// Set the state of the objective with specified index; completing it plays the [stinger](mixer) of an objective complete. Returns false if there is no such objective.
+	pub fn set_objective_state(&mut self, idx: usize, state: ObjectiveState) -> bool {
+		match self.objectives.get_mut(idx) {
+			Some(o) => {
+				let done = state == ObjectiveState::Completed && o.state != ObjectiveState::Completed;
+				o.state = state;
+				if done {
+					self.play_stinger(StingerKind::ObjectiveComplete);
+				}
+				true
+			},
+			None => false
+		}
+	}
+
+	/// Remove all objectives.
+	pub fn clear_objectives(&mut self) {
+		self.objectives.clear();
+	}
+
+	/// Returns the objectives of the current level, in the order they were added.
+	pub fn objectives(&self) -> &[Objective] {
+		&self.objectives
+	}
+
+	/// Set the difficulty of the battle. Multipliers that are negative or not finite are replaced by 1.
+	pub fn set_difficulty(&mut self, d: Difficulty) {
+		let fix = |v: f32| if v.is_finite() && v >= 0.0 { v } else { 1.0 };
+		self.difficulty = Difficulty {
+			player_damage: fix(d.player_damage),
+			enemy_damage: fix(d.enemy_damage),
+			enemy_health: if d.enemy_health > 0.0 { fix(d.enemy_health) } else { 1.0 },
+			aggression: fix(d.aggression)
+		};
+	}
+
+	/// Returns the difficulty of the battle.
+	pub fn difficulty(&self) -> Difficulty {
+		self.difficulty
+	}
+
+	/// Enable the built-in AI for enemy units with neither a [Behavior] nor a [BehaviorTree], or disable it with None (the default), in which case such units only act on orders of the `on_turn` callback.
+	/// Units given orders by the callback are left to them.
+	pub fn set_ai_controller(&mut self, c: Option<AiController>) {
+		self.ai_controller = c;
+	}
+
+	/// Returns the built-in AI of enemy units without a behavior, if enabled.
+	pub fn ai_controller(&self) -> Option<AiController> {
+		self.ai_controller
+	}
+
+	/// Enable (or disable) fog of war: tiles out of [sight](unit_sight) of every player unit are darkened, and enemy units on them hidden. Disabled by default.
+	/// Visibility is updated every frame by the game, or by [update_visibility].
+	pub fn set_fog_of_war(&mut self, enabled: bool) {
+		if enabled != self.fog.is_some() {
+			self.fog = if enabled { Some(HashSet::new()) } else { None };
+			update_visibility(self);
+		}
+	}
+
+	/// Returns true if fog of war is enabled.
+	pub fn fog_of_war(&self) -> bool {
+		self.fog.is_some()
+	}
+
+	/// Set the actions of player units in their turn, for unit types that do not set their own (see [UnitType::set_actions]).
+	pub fn set_actions(&mut self, a: Actions) {
+		self.actions = a;
+	}
+
+	/// Returns the actions of player units in their turn, for unit types that do not set their own.
+	pub fn actions(&self) -> Actions {
+		self.actions
+	}
+
+	/// Set how health is rounded to whole numbers wherever it is shown, i.e, the HUD, popups and [unit_hp]. Health itself is not rounded.
+	pub fn set_hp_rounding(&mut self, r: HpRounding) {
+		self.presentation.hp_rounding = r;
+	}
+
+	/// Returns how health is rounded to whole numbers for display.
+	pub fn hp_rounding(&self) -> HpRounding {
+		self.presentation.hp_rounding
+	}
+
+	/// Show `text` rising from the tile of the unit with specified id, in the style of `kind`, e.g, "POISONED" when a script applies a status, or "MISS" from the attack callback.
+	/// Damage dealt by attacks, and health restored or lost by orders, tiles and pickups, are shown by the engine. Popups of units that no longer exist are dropped.
+	pub fn show_popup(&mut self, uid: u8, text: &str, kind: PopupKind) {
+		self.presentation.popups.push((uid, text.to_string(), kind));
+	}
+
+	/// Show the change in health of a unit as a popup, if any: restored health as [Heal](PopupKind::Heal), lost health as [Damage](PopupKind::Damage).
+	pub(crate) fn health_popup(&mut self, uid: u8, delta: f32) {
+		let v = self.presentation.hp_rounding.apply(delta.abs());
+		if delta > 0.0 {
+			self.show_popup(uid, &format!("+{}", v), PopupKind::Heal);
+		} else if delta < 0.0 {
+			self.show_popup(uid, &v.to_string(), PopupKind::Damage);
+		}
+	}
+
+	/// Load a [behavior tree file](crate::ai) for the current map, replacing the trees assigned to unit types by the previous one.
+	/// Returns false (keeping the previous trees) if the file could not be read or is malformed.
+	pub fn load_ai(&mut self, path: &str) -> bool {
+		match TreeSet::load(path) {
+			Ok(set) => {
+				self.ai_trees = set.type_trees().into_iter().collect();
+				true
+			},
+			Err(e) => {
+				eprintln!("warning [ai]: failed to load {}: {}", path, e);
+				false
+			}
+		}
+	}
+
+	/// Load a [puzzle](crate::puzzle) for the current map, replacing the previous one.
+	/// Returns false (keeping the previous puzzle) if the file could not be read or is malformed.
+	pub fn load_puzzle(&mut self, path: &str) -> bool {
+		match Puzzle::load(path) {
+			Ok(p) => {
+				self.puzzle = Some(Box::new(p));
+				true
+			},
+			Err(e) => {
+				eprintln!("warning [puzzle]: failed to load {}: {}", path, e);
+				false
+			}
+		}
+	}
+
+	/// Set the [puzzle](crate::puzzle) of the current map, or None to play the battle freely.
+	pub fn set_puzzle(&mut self, p: Option<Puzzle>) {
+		self.puzzle = p.map(Box::new);
+	}
+
+	/// Returns the puzzle of the current map, with the progress of the player, if any.
+	pub fn puzzle(&self) -> Option<&Puzzle> {
+		self.puzzle.as_deref()
+	}
+
+	/// Load a [tutorial](crate::tutorial) for the current map, replacing the previous one.
+	/// Returns false (keeping the previous tutorial) if the file could not be read or is malformed.
+	pub fn load_tutorial(&mut self, path: &str) -> bool {
+		match Tutorial::load(path) {
+			Ok(t) => {
+				self.tutorial = Some(Box::new(t));
+				true
+			},
+			Err(e) => {
+				eprintln!("warning [tutorial]: failed to load {}: {}", path, e);
+				false
+			}
+		}
+	}
+
+	/// Set the [tutorial](crate::tutorial) of the current map, or None to play without one.
+	pub fn set_tutorial(&mut self, t: Option<Tutorial>) {
+		self.tutorial = t.map(Box::new);
+	}
+
+	/// Returns the tutorial of the current map, with the progress of the player, if any.
+	pub fn tutorial(&self) -> Option<&Tutorial> {
+		self.tutorial.as_deref()
+	}
+
+	/// Returns true if the tutorial, if any, accepts the control in its current step.
+	pub(crate) fn tutorial_allows(&self, c: TutorialControl) -> bool {
+		self.tutorial.iter().all(|t| t.allows(c))
+	}
+
+	/// Check an action of the player against the tutorial, if any, see [Tutorial::act].
+	pub(crate) fn tutorial_act(&mut self, a: TutorialGoal) {
+		if let Some(t) = &mut self.tutorial {
+			t.act(a);
+		}
+	}
+
+	/// Returns the pickups lying on tiles, see [pickup].
+	pub fn pickups(&self) -> &[Pickup] {
+		&self.pickups
+	}
+
+	/// Pan the camera smoothly to the world co-ordinates (x, y) over `duration` seconds. The camera stops following a unit, and the pan is cancelled if the camera is moved explicitly.
+	pub fn pan_camera(&mut self, x: f32, y: f32, duration: f32, easing: Easing) {
+		self.cam_follow = None;
+		self.tweens.pan = Some((Tween::new(self.cam_wx, x, duration, easing), Tween::new(self.cam_wy, y, duration, easing)));
+	}
+
+	/// Pan the camera smoothly to the tile (x, y) over `duration` seconds, as [World::pan_camera] does, e.g, to show a scripted event.
+	pub fn pan_to_tile(&mut self, x: i32, y: i32, duration: f32) {
+		self.pan_camera(x as f32, y as f32, duration, Easing::Smooth);
+	}
+
+	/// Make the camera follow the unit with specified id, closing in on it at the [follow rate](World::set_camera_follow_rate), until the camera is moved explicitly or the unit dies.
+	/// Any camera pan is cancelled. Returns false if there is no such unit.
+	pub fn follow_unit(&mut self, uid: u8) -> bool {
+		if !self.units.contains_key(&uid) {
+			return false;
+		}
+		self.cam_follow = Some(uid);
+		self.tweens.pan = None;
+		true
+	}
+
+	/// Set the rate at which the camera closes in on the unit it follows, per second, i.e, it covers about two thirds of the distance in `1/rate` seconds.
+	/// 0 keeps the unit exactly in place; negative or non-finite rates are replaced by 0. Default is [CAM_FOLLOW_RATE].
+	pub fn set_camera_follow_rate(&mut self, rate: f32) {
+		self.cam_follow_rate = if rate.is_finite() { rate.max(0.0) } else { 0.0 };
+	}
+
+	/// Set whether the camera follows units while they walk, e.g, enemies in their turn, unless it is panning or following a unit. Enemy units hidden by fog of war are not followed. Default is false.
+	pub fn set_camera_auto_follow(&mut self, on: bool) {
+		self.cam_auto_follow = on;
+	}
+
+	/// Returns true if the camera follows units while they walk, see [World::set_camera_auto_follow].
+	pub fn camera_auto_follow(&self) -> bool {
+		self.cam_auto_follow
+	}
+
+	/// Returns true while the camera is panning, see [World::pan_camera].
+	pub fn is_panning(&self) -> bool {
+		self.tweens.pan.is_some()
+	}
+
+	/// Add a tween, advanced once per frame along with the engine, returning its id. See [tween](crate::tween).
+	pub fn tween(&mut self, t: Tween) -> u32 {
+		self.tweens.add(t, None)
+	}
+
+	/// Add a tween, and a function invoked with its id once it is complete, returning its id.
+	pub fn tween_then(&mut self, t: Tween, f: impl FnMut(&mut World, u32) + 'static) -> u32 {
+		self.tweens.add(t, Some(Box::new(f)))
+	}
+
+	/// Returns the current value of the tween with the specified id, or None once it has completed or been cancelled.
+	pub fn tween_value(&self, id: u32) -> Option<f32> {
+		self.tweens.get(id).map(|t| t.value())
+	}
+
+	/// Remove the tween with the specified id without invoking its function. Returns false if there is no such tween.
+	pub fn cancel_tween(&mut self, id: u32) -> bool {
+		self.tweens.cancel(id)
+	}
+
+	/// Advance the camera pan (or the camera following a unit) and tweens by `dt` seconds, invoking the functions of the tweens that completed.
+	pub(crate) fn update_tweens(&mut self, dt: f32) {
+		if let Some((tx, ty)) = &mut self.tweens.pan {
+			let done = tx.update(dt) & ty.update(dt);
+			let (x, y) = (tx.value(), ty.value());
+			self.set_cpos(x, y);
+			if done {
+				self.tweens.pan = None;
+			}
+		} else {
+			self.follow(dt);
+		}
+		for (id, f) in self.tweens.advance(dt) {
+			if let Some(mut f) = f {
+				f(self, id);
+			}
+		}
+	}
+
+	/// Close in on the unit followed by the camera, or the walking unit with the least id if following walking units, by `dt` seconds at the follow rate.
+	fn follow(&mut self, dt: f32) {
+		let uid = match self.cam_follow {
+			Some(uid) if !self.units.contains_key(&uid) => {
+				self.cam_follow = None;
+				return;
+			},
+			Some(uid) => uid,
+			None if self.cam_auto_follow => {
+				let walking = self.units.iter().filter(|(_, u)| u.state.is_walking()).map(|(id, _)| *id);
+				match walking.filter(|id| is_unit_visible(self, *id)).min() {
+					Some(uid) => uid,
+					None => return
+				}
+			},
+			None => return
+		};
+		let p = self.units[&uid].wpos;
+		let k = if self.cam_follow_rate > 0.0 { 1.0 - (-self.cam_follow_rate * dt).exp() } else { 1.0 };
+		self.set_cpos(self.cam_wx + (p.x - self.cam_wx) * k, self.cam_wy + (p.y - self.cam_wy) * k);
+	}
+
+	/// Schedule `f` to be invoked once, with the id returned, after `secs` seconds of game time. See [schedule](crate::schedule).
+	pub fn schedule_after(&mut self, secs: f32, f: impl FnMut(&mut World, u32) + 'static) -> u32 {
+		self.schedule.add(secs, None, Box::new(f))
+	}
+
+	/// Schedule `f` to be invoked every `secs` seconds of game time, with the id returned, until cancelled.
+	pub fn schedule_every(&mut self, secs: f32, f: impl FnMut(&mut World, u32) + 'static) -> u32 {
+		self.schedule.add(secs, Some(secs), Box::new(f))
+	}
+
+	/// Cancel a scheduled callback; this may be done from the callback itself. Returns false if there is no such callback, e.g, if it has already been invoked (and does not repeat).
+	pub fn cancel_scheduled(&mut self, id: u32) -> bool {
+		self.schedule.cancel(id)
+	}
+
+	/// Returns the time (in seconds) before the scheduled callback with the specified id is next invoked, or None if it is not scheduled.
+	pub fn scheduled_time_left(&self, id: u32) -> Option<f32> {
+		self.schedule.time_left(id)
+	}
+
+	/// Enable (or disable, discarding events not yet polled) the queue of [events](crate::event) published to this world, e.g, for FFI consumers that cannot subscribe functions.
+	/// The queue holds at most [QUEUE_CAPACITY](crate::event::QUEUE_CAPACITY) events, dropping the oldest beyond this.
+	pub fn enable_event_queue(&mut self, enable: bool) {
+		match (enable, self.event_queue.is_some()) {
+			(true, false) => self.event_queue = Some(VecDeque::new()),
+			(false, true) => self.event_queue = None,
+			_ => {}
+		}
+	}
+
+	/// Returns the oldest event in the queue, removing it, or None if the queue is empty or disabled.
+	pub fn poll_event(&mut self) -> Option<EngineEvent> {
+		self.event_queue.as_mut()?.pop_front()
+	}
+
+	/// Push an event to the queue, if enabled.
+	pub(crate) fn push_event(&mut self, e: EngineEvent) {
+		if let Some(q) = &mut self.event_queue {
+			if q.len() >= crate::event::QUEUE_CAPACITY {
+				q.pop_front();
+			}
+			q.push_back(e);
+		}
+	}
+
+	/// Returns what the built-in AI decided for each unit with a [Behavior] during the last enemy turn, in order of unit ids.
+	pub fn ai_plans(&self) -> &[AiPlan] {
+		&self.ai_plans
+	}
+
 	/// Set the damage calculation function.
 	pub fn bind_damage_func(&mut self, f: fn(u8, u8) -> f32) {
-		self.dmg_func = DamageFunc::Handle(f);
+		self.dmg_func = DamageFunc::Handle(Box::new(f));
+	}
+
+	/// Set the damage calculation closure, which may capture state, e.g, a rules table or an RNG.
+	pub fn bind_damage_closure(&mut self, f: impl FnMut(u8, u8) -> f32 + 'static) {
+		self.dmg_func = DamageFunc::Handle(Box::new(f));
 	}
 
 	/// Get the text to be displayed before starting gameplay.
@@ -290,6 +1480,42 @@ impl World {
 		&self.tilemap.intro_text
 	}
 
+	/// Set the slides shown before the intro text, replacing those defined by the map, if any; an empty list shows the intro text at once.
+	/// The slideshow is shown when the game begins, or once a map is loaded. Clicking, SPACE or ENTER skips to the next slide, and ESCAPE skips the slideshow.
+	pub fn set_intro_slides(&mut self, slides: Vec<Slide>) {
+		self.presentation.slides = slides;
+	}
+
+	/// Add a slide to the end of the slideshow shown before the intro text, see [set_intro_slides](World::set_intro_slides).
+	pub fn add_intro_slide(&mut self, tex: u8, caption: &str, duration: f32) {
+		self.presentation.slides.push(Slide { tex: tex, caption: caption.to_string(), duration: duration.max(0.0) });
+	}
+
+	/// Get the slides shown before the intro text.
+	pub fn intro_slides(&self) -> &[Slide] {
+		&self.presentation.slides
+	}
+
+	/// Show a screen of text scrolling up over the game, e.g, credits or an epilogue, at `speed` pixels per second ([DEFAULT_SCROLL_SPEED] if not positive).
+	/// The screen is shown from the next frame; if requested by the `on_results` callback, it is shown once the results screen is dismissed, and the game ends after it.
+	/// Holding SPACE or the left mouse button scrolls faster, and ESCAPE skips the screen. Replaces any screen requested but not yet shown.
+	///
+	/// The text is shown as given; there is no string table, so localized text must be looked up by the caller.
+	pub fn show_scrolling_text(&mut self, text: &str, speed: f32) {
+		let speed = if speed > 0.0 { speed } else { DEFAULT_SCROLL_SPEED };
+		self.presentation.scroll = Some(ScrollText { text: text.to_string(), speed: speed });
+	}
+
+	/// Get the scrolling text screen requested, but not yet shown, if any.
+	pub fn scrolling_text(&self) -> Option<&ScrollText> {
+		self.presentation.scroll.as_ref()
+	}
+
+	/// Take the scrolling text screen requested, to be shown.
+	pub(crate) fn take_scrolling_text(&mut self) -> Option<ScrollText> {
+		self.presentation.scroll.take()
+	}
+
 	/// Get the title of the map loaded.
 	pub fn map_title(&self) -> &str {
 		&self.tilemap.title
@@ -304,6 +1530,20 @@ impl World {
 	pub fn defeat_text(&self) -> &str {
 		&self.tilemap.defeat_text
 	}
+
+	/// Returns the metadata of the map loaded since listeners were last notified, if any, so they are notified once.
+	pub(crate) fn take_loaded_map(&mut self) -> Option<MapInfo> {
+		let path = self.loaded_map.take()?;
+		Some(MapInfo {
+			path: path,
+			title: self.tilemap.title.clone(),
+			intro_text: self.tilemap.intro_text.clone(),
+			victory_text: self.tilemap.victory_text.clone(),
+			defeat_text: self.tilemap.defeat_text.clone(),
+			width: self.tilemap.map_width,
+			height: self.tilemap.map_height
+		})
+	}
 }
 
 /// Plain struct to specify the texture, world co-ordinates, and size of a static image
@@ -325,6 +1565,7 @@ impl StaticTex {
 	}
 }
 
+#[derive(Clone)]
 struct AnimInfo {
 	frame_width: u32,
 	frame_height: u32,
@@ -337,6 +1578,7 @@ struct AnimInfo {
 }
 
 /// A struct to specify a Unit-Type. Contains details common to all units of a given type.
+#[derive(Clone)]
 pub struct UnitType {
 	tex_id: u8,
 	max_health: f32,
@@ -347,12 +1589,32 @@ pub struct UnitType {
 	pub name: String,
 	/// Info-string to be displayed. for this unit type.
 	pub info: Option<String>,
+	/// Texture id of the portrait shown in the unit panel, if any.
+	portrait: Option<u8>,
+	/// Actions of units of this type in their turn, if not those of the world.
+	actions: Option<Actions>,
+	/// Tiles seen by units of this type under fog of war, if not their movement and range.
+	sight: Option<u8>,
 	/// The maximum number of tiles units of this type can move in a turn.
 	movement: u8,
 	/// The range of the unit.
 	range: u8,
+	/// Attack strength, used by the built-in damage formula.
+	attack: f32,
+	/// Defense strength, used by the built-in damage formula.
+	defense: f32,
+	/// Behavior tree deciding the turns of enemy units of this type, see [crate::ai].
+	pub(crate) ai: Option<BehaviorTree>,
 	/// Animation related info
 	anim: Vec<AnimInfo>,
+	/// Idle variations of the Stand animation.
+	idle_anim: Vec<AnimInfo>,
+	/// Stand animations for each [Facing], used instead of the Stand animation if defined.
+	stand_anim: [Option<AnimInfo>; 4],
+	/// Seconds of standing after which an idle variation is played.
+	idle_delay: f32,
+	/// Voice lines of units of this type, see [voice].
+	voice: voice::VoiceSet,
 }
 
 ///#TODO: Remove in release
@@ -377,15 +1639,53 @@ impl UnitType {
 			tex_id: tex_id,
 			name: name,
 			info: None,
+			portrait: None,
+			actions: None,
+			sight: None,
 			anim: vec![],
+			idle_anim: vec![],
+			stand_anim: [None, None, None, None],
+			idle_delay: 5.0,
+			voice: voice::VoiceSet::default(),
+			ai: None,
 			max_health: max_health,
 			mov_rate: mov_rate,
 			movement: movement,
 			range: range,
+			attack: 0.0,
+			defense: 0.0,
 			attack_dur: attack_dur
 		}
 	}
 
+	/// Create a variant of this unit type with the specified name, e.g, a promoted class or an elite unit.
+	/// The variant inherits the texture, animations, stats and info of this unit type, which may then be overridden.
+	pub fn variant(&self, name: String) -> UnitType {
+		let mut u = self.clone();
+		u.name = name;
+		u
+	}
+
+	/// Set the texture of this unit type. Useful for variants, whose sprite sheets share the layout of their base.
+	pub fn set_texture(&mut self, tex_id: u8) {
+		self.tex_id = tex_id;
+	}
+
+	/// Set the stats of this unit type; see [UnitType::new] for the meaning of each.
+	pub fn set_stats(&mut self, max_health: f32, mov_rate: f32, movement: u8, range: u8, attack_dur: f32) {
+		self.max_health = max_health;
+		self.mov_rate = mov_rate;
+		self.movement = movement;
+		self.range = range;
+		self.attack_dur = attack_dur;
+	}
+
+	/// Set the attack and defense strength of this unit type, used by the [built-in](default_damage) damage formula. Both are zero by default.
+	pub fn set_combat_stats(&mut self, attack: f32, defense: f32) {
+		self.attack = attack;
+		self.defense = defense;
+	}
+
 	/// Define an animation for this unit without an accompanying sound.
 	/// All unit animations must be defined in the exact order as enum variants of [`Unit State`]
 	///
@@ -436,10 +1736,144 @@ impl UnitType {
 		});
 	}
 
+	/// Define an idle variation (e.g, blinking, or banner waving) of the Stand animation; arguments are as for [UnitType::def_anim_muted].
+	/// After standing for the [idle delay](UnitType::set_idle_delay), units play one of the variations at random, once, before returning to the Stand animation.
+	pub fn def_idle_anim(&mut self, fs: (u32, u32), frn: u8, cf: (u32, u32), fr: f32, flip: bool) {
+		self.idle_anim.push(AnimInfo {
+			frame_width: fs.0,
+			frame_height: fs.1,
+			frame_rate: fr,
+			sfr_x: cf.0,
+			sfr_y: cf.1,
+			nframes: frn,
+			flip: flip,
+			snd_info: None
+		});
+	}
+
+	/// Define the Stand animation for units facing the specified direction; arguments are as for [UnitType::def_anim_muted].
+	/// Directions without such an animation fall back to the Stand animation.
+	pub fn def_stand_anim(&mut self, facing: Facing, fs: (u32, u32), frn: u8, cf: (u32, u32), fr: f32, flip: bool) {
+		self.stand_anim[facing as usize] = Some(AnimInfo {
+			frame_width: fs.0,
+			frame_height: fs.1,
+			frame_rate: fr,
+			sfr_x: cf.0,
+			sfr_y: cf.1,
+			nframes: frn,
+			flip: flip,
+			snd_info: None
+		});
+	}
+
+	/// Set the number of seconds units must stand still before an idle variation is played. Default is 5 seconds.
+	pub fn set_idle_delay(&mut self, secs: f32) {
+		self.idle_delay = secs;
+	}
+
+	/// Set the behavior tree deciding the turns of enemy units of this type (see [crate::ai]), or clear it with None.
+	pub fn set_behavior_tree(&mut self, tree: Option<BehaviorTree>) {
+		self.ai = tree;
+	}
+
 	/// Set the info string of this unit type.
 	pub fn set_info(&mut self, text: String) {
 		self.info = Some(text);
 	}
+
+	/// Set the texture of the portrait shown in the unit panel for units of this type, or None to show no portrait.
+	/// The whole texture (or the current frame of its [UI animation](crate::utils::ResourceSet::set_ui_anim)) is scaled to the portrait size of the [Theme](crate::display::Theme).
+	pub fn set_portrait(&mut self, tex: Option<u8>) {
+		self.portrait = tex;
+	}
+
+	/// Texture id of the portrait of units of this type, if any.
+	pub fn portrait(&self) -> Option<u8> {
+		self.portrait
+	}
+
+	/// Set the actions of player units of this type in their turn, e.g, two attacks for a duelist, or moving after attacking for cavalry; None to follow [World::set_actions].
+	pub fn set_actions(&mut self, a: Option<Actions>) {
+		self.actions = a;
+	}
+
+	/// The actions of units of this type in their turn, if set.
+	pub fn actions(&self) -> Option<Actions> {
+		self.actions
+	}
+
+	/// Set how many tiles away units of this type see under fog of war; None for their movement and range.
+	pub fn set_sight(&mut self, sight: Option<u8>) {
+		self.sight = sight;
+	}
+
+	/// The number of tiles units of this type see under fog of war, if set.
+	pub fn sight(&self) -> Option<u8> {
+		self.sight
+	}
+
+	/// Set the voice lines of units of this type for a [moment](voice::BarkKind), one of which is played at random, and the seconds before the group may bark again. See [voice].
+	pub fn set_voice(&mut self, kind: voice::BarkKind, sounds: Vec<u8>, cooldown: f32) {
+		self.voice.set(kind, sounds, cooldown);
+	}
+
+	/// The voice lines of units of this type.
+	pub fn voice(&self) -> &voice::VoiceSet {
+		&self.voice
+	}
+
+	/// The maximum health of units of this type.
+	pub fn max_health(&self) -> f32 {
+		self.max_health
+	}
+
+	/// The rate at which units of this type move, in tiles per second.
+	pub fn mov_rate(&self) -> f32 {
+		self.mov_rate
+	}
+
+	/// The internal identifier of the texture of this unit type.
+	pub(crate) fn tex_id(&self) -> u8 {
+		self.tex_id
+	}
+
+	/// Frame count, frame rate and texture rectangle of the specified frame of the animation of the state with index `st`,
+	/// or `None` if the animation has not been defined.
+	pub(crate) fn anim_frame(&self, st: usize, frame: u8) -> Option<(u8, f32, Rectangle)> {
+		let aif = self.anim.get(st)?;
+		let rec = Rectangle {
+			height: (aif.frame_height as f32),
+			width: if aif.flip {-1.0} else {1.0} * (aif.frame_width) as f32,
+			x: (aif.sfr_x + (frame as u32)*aif.frame_width) as f32,
+			y: (aif.sfr_y as f32)
+		};
+		Some((aif.nframes, aif.frame_rate, rec))
+	}
+
+	/// The duration of the attack state in seconds.
+	pub fn attack_dur(&self) -> f32 {
+		self.attack_dur
+	}
+
+	/// The maximum number of tiles units of this type can move in a turn.
+	pub fn movement(&self) -> u8 {
+		self.movement
+	}
+
+	/// The range of the unit's attack.
+	pub fn range(&self) -> u8 {
+		self.range
+	}
+
+	/// The attack strength of units of this type.
+	pub fn attack(&self) -> f32 {
+		self.attack
+	}
+
+	/// The defense strength of units of this type.
+	pub fn defense(&self) -> f32 {
+		self.defense
+	}
 }
 
 /// An enum of all possible states of a unit. Every UnitState has a corresponding animation.
@@ -472,6 +1906,165 @@ impl UnitState {
 			_ => false
 		}
 	}
+
+	fn is_walking(&self) -> bool {
+		matches!(self, UnitState::WalkDown | UnitState::WalkLeft | UnitState::WalkUp | UnitState::WalkRight)
+	}
+
+	/// The direction a unit faces in this state, `None` for [UnitState::Stand].
+	pub fn facing(&self) -> Option<Facing> {
+		match self {
+			UnitState::WalkDown | UnitState::AttackDown => Some(Facing::Down),
+			UnitState::WalkLeft | UnitState::AttackLeft => Some(Facing::Left),
+			UnitState::WalkUp | UnitState::AttackUp => Some(Facing::Up),
+			UnitState::WalkRight | UnitState::AttackRight => Some(Facing::Right),
+			UnitState::Stand => None
+		}
+	}
+}
+
+/// Standing behavior of a unit, carried out by the built-in AI during the enemy turn for enemy units given no orders by the `on_turn` [callback](crate::utils::StateListener).
+/// See [set_behavior].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Behavior {
+	/// Stay put, attacking the weakest player unit within range.
+	Hold,
+	/// Attack player units which come within `radius` tiles of `tile`, without leaving that area; return to `tile` when there are none.
+	Guard {
+		/// The tile guarded.
+		tile: (i32, i32),
+		/// Distance (in tiles) from the guarded tile within which intruders are attacked.
+		radius: u8
+	},
+	/// Walk between waypoints in turn, attacking player units within range along the way.
+	Patrol {
+		/// Tiles walked between, in order, looping back to the first.
+		waypoints: Vec<(i32, i32)>,
+		/// Index of the waypoint headed for.
+		next: usize
+	}
+}
+
+/// Turn of enemy units with neither a [Behavior] nor a [BehaviorTree], carried out by the built-in AI once enabled with [World::set_ai_controller], so that simple games need no `on_turn` [callback](crate::utils::StateListener).
+/// A unit attacks the weakest player unit in range if there is one; otherwise it advances on the nearest player unit, and attacks it once in range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AiController {
+	/// How far a unit looks for player units to advance on, as a multiple of its movement and range, further scaled by [Difficulty::aggression]; a unit with none in sight stays put.
+	/// 1 advances only on player units it could attack this turn, and infinity on every player unit.
+	pub aggressiveness: f32
+}
+
+impl Default for AiController {
+	fn default() -> AiController {
+		AiController { aggressiveness: f32::INFINITY }
+	}
+}
+
+/// A condition of a unit, shown in its stat panel. See [UnitStats].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitStatus {
+	/// The attack strength of the unit is raised, see [Unit::boost].
+	Boosted = 0,
+	/// The unit holds its position, see [Behavior::Hold].
+	Holding = 1,
+	/// The unit guards a tile, see [Behavior::Guard].
+	Guarding = 2,
+	/// The unit patrols between waypoints, see [Behavior::Patrol].
+	Patrolling = 3,
+	/// The unit has acted this turn. Known only to the input handler, so never set by [unit_stats].
+	Acted = 4
+}
+
+impl UnitStatus {
+	/// Number of statuses.
+	pub const COUNT: usize = 5;
+
+	/// Convert from the raw value used by the native API. Returns None if out of range.
+	pub fn from_u8(v: u8) -> Option<UnitStatus> {
+		match v {
+			0 => Some(UnitStatus::Boosted),
+			1 => Some(UnitStatus::Holding),
+			2 => Some(UnitStatus::Guarding),
+			3 => Some(UnitStatus::Patrolling),
+			4 => Some(UnitStatus::Acted),
+			_ => None
+		}
+	}
+
+	/// Returns the name of the status, as shown in the stat panel.
+	pub fn name(&self) -> &'static str {
+		match self {
+			UnitStatus::Boosted => "Boosted",
+			UnitStatus::Holding => "Holding",
+			UnitStatus::Guarding => "Guarding",
+			UnitStatus::Patrolling => "Patrolling",
+			UnitStatus::Acted => "Acted"
+		}
+	}
+}
+
+/// Live stats of a unit, taking boosts and cheats into account, as shown in its stat panel. See [unit_stats].
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitStats {
+	/// Display name of the unit type.
+	pub name: String,
+	/// Whether the unit belongs to the player.
+	pub player: bool,
+	/// Current health.
+	pub health: f32,
+	/// Maximum health.
+	pub max_health: f32,
+	/// Attack strength, raised by the boost of the unit.
+	pub attack: f32,
+	/// Fraction by which the attack strength is raised, see [Unit::boost].
+	pub boost: f32,
+	/// Defense strength.
+	pub defense: f32,
+	/// Tiles the unit may move per turn.
+	pub movement: u8,
+	/// Range of the unit's attack.
+	pub range: u8,
+	/// Conditions of the unit, in the order of [UnitStatus].
+	pub statuses: Vec<UnitStatus>
+}
+
+/// What the built-in AI decided for a unit with a [Behavior] during the last enemy turn, shown by the AI debug overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiPlan {
+	/// Id of the unit.
+	pub uid: u8,
+	/// Id of the player unit it attacks, if any.
+	pub target: Option<u8>,
+	/// Tiles it walks through, in order.
+	pub path: Vec<(i32, i32)>,
+	/// Candidate end tiles considered, with their scores: the distance (in tiles) to the goal, lower being better.
+	pub scores: Vec<((i32, i32), i32)>
+}
+
+/// The direction a unit faces, i.e, that of its last movement or attack.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facing {
+	/// Towards increasing y-co-ordinate.
+	Down = 0,
+	/// Towards decreasing x-co-ordinate.
+	Left = 1,
+	/// Towards decreasing y-co-ordinate.
+	Up = 2,
+	/// Towards increasing x-co-ordinate.
+	Right = 3
+}
+
+/// Inverse of `Facing as u8`. Returns `None` for values that do not correspond to a direction.
+pub(crate) fn u8_as_facing(i: u8) -> Option<Facing> {
+	match i {
+		0 => Some(Facing::Down),
+		1 => Some(Facing::Left),
+		2 => Some(Facing::Up),
+		3 => Some(Facing::Right),
+		_ => None
+	}
 }
 
 fn state_as_usize(u: &UnitState) -> usize {
@@ -488,6 +2081,22 @@ fn state_as_usize(u: &UnitState) -> usize {
 	}
 }
 
+/// Inverse of `state_as_usize`. Returns `None` for indices that do not correspond to a state.
+pub(crate) fn usize_as_state(i: usize) -> Option<UnitState> {
+	match i {
+		0 => Some(UnitState::WalkDown),
+		1 => Some(UnitState::WalkLeft),
+		2 => Some(UnitState::WalkUp),
+		3 => Some(UnitState::WalkRight),
+		4 => Some(UnitState::AttackDown),
+		5 => Some(UnitState::AttackLeft),
+		6 => Some(UnitState::AttackUp),
+		7 => Some(UnitState::AttackRight),
+		8 => Some(UnitState::Stand),
+		_ => None
+	}
+}
+
 /// Plain struct to represent a unit in the world.
 #[derive(Debug)]
 pub struct Unit {
@@ -506,11 +2115,40 @@ pub struct Unit {
 	stime: f32,
 	frame: u8,
 	busy: bool,
+	/// Steps along x per step along y while walking up or down, i.e, -1 or 1 for diagonal steps between hexagonal tiles, else 0.
+	slant: f32,
 	/// Flag to mark whether the unit belongs to player or enemy.
-	pub player: bool
+	pub player: bool,
+	/// The id of the palette swapped into the sprite's team colours, if any. See [ResourceSet::map_palette](crate::utils::ResourceSet::map_palette).
+	pub palette: Option<u8>,
+	/// Multiplier for the animation rate of the sprite, e.g, for slowed or hasted units. Does not affect movement.
+	pub anim_speed: f32,
+	/// Flag to freeze the animation of the sprite at its current frame.
+	pub anim_paused: bool,
+	/// The direction the unit faces.
+	pub facing: Facing,
+	/// The idle variation being played, if any.
+	idle: Option<usize>,
+	/// Counter for animation time spent standing, to trigger idle variations.
+	itime: f32,
+	/// Standing behavior of the unit, if any. See [Behavior].
+	pub behavior: Option<Behavior>,
+	/// Fraction by which the attack strength of the unit is raised, e.g, by a [pickup]. Used by the built-in damage formula.
+	pub boost: f32,
+	/// Texture id of the portrait of the unit, overriding that of its type, if any. See [UnitType::set_portrait].
+	pub portrait: Option<u8>
 }
 
 impl Unit {
+	/// A unit restored from a [snapshot](crate::save).
+	pub(crate) fn restored(tid: u8, tint: i32, wpos: Vector2, plr: bool, health: f32, palette: Option<u8>, facing: Facing) -> Unit {
+		Unit {
+			palette: palette,
+			facing: facing,
+			..Unit::new(tid, tint, wpos, plr, health)
+		}
+	}
+
 	fn new(tid: u8, tint: i32, wpos: Vector2, plr: bool, health: f32) -> Unit {
 		Unit {
 			type_id: tid,
@@ -523,15 +2161,33 @@ impl Unit {
 			ftime: 0.0,
 			stime: 0.0,
 			busy: false,
+			slant: 0.0,
+			palette: None,
+			anim_speed: 1.0,
+			anim_paused: false,
+			facing: Facing::Down,
+			idle: None,
+			itime: 0.0,
+			behavior: None,
+			boost: 0.0,
+			portrait: None
 		}
 	}
 
 	#[inline]
 	fn get_anim_info<'a>(&self, w: &'a World) -> (&'a UnitType, &'a AnimInfo) {
 		let ut = w.unit_types.get(&self.type_id).expect(&format!("fatal [draw]: Unit type id {} does not exist", self.type_id));
-		let idx = state_as_usize(&self.state);
-		let aif = &ut.anim[idx];
-		return (ut, aif)
+		return (ut, self.anim_of(ut))
+	}
+
+	/// The animation currently played, i.e, that of the unit state, or an idle variation.
+	#[inline]
+	fn anim_of<'a>(&self, ut: &'a UnitType) -> &'a AnimInfo {
+		match self.idle {
+			Some(i) if self.state.is_idle() => &ut.idle_anim[i],
+			_ if self.state.is_idle() => ut.stand_anim[self.facing as usize].as_ref().unwrap_or(&ut.anim[state_as_usize(&self.state)]),
+			_ => &ut.anim[state_as_usize(&self.state)]
+		}
 	}
 
 	/// Prepare the unit for rendering.
@@ -569,22 +2225,32 @@ impl Unit {
 
 	/// Update function of the Unit.
 	pub fn update(&mut self, uh: &HashMap<u8,UnitType>, delta: f32) {
-		self.ftime += delta;
+		let adelta = if self.anim_paused {0.0} else {delta * self.anim_speed.max(0.0)};
+		self.ftime += adelta;
 		let ut = uh.get(&self.type_id).expect(&format!("fatal [draw]: Unit type id {} does not exist", self.type_id));
 		if !self.state.is_idle() {
 			self.stime += delta;
+		} else if self.idle.is_none() && !ut.idle_anim.is_empty() {
+			self.itime += adelta;
+			if self.itime >= ut.idle_delay {
+				self.idle = Some(raylib::core::misc::get_random_value::<i32>(0, ut.idle_anim.len() as i32 - 1) as usize);
+				self.itime = 0.0;
+				self.ftime = 0.0;
+			}
 		}
-		let aif = &ut.anim[state_as_usize(&self.state)];
+		let aif = self.anim_of(ut);
 		self.frame = f32::floor(self.ftime * aif.frame_rate) as u8;
 		if self.frame >= aif.nframes.into() {
 			self.frame = 0;
 			self.ftime = 0.0;
+			// Idle variations are played once.
+			self.idle = None;
 		}
 		let ds = delta * ut.mov_rate;
 		match self.state {
-			UnitState::WalkDown => {self.wpos.y += ds},
+			UnitState::WalkDown => {self.wpos.y += ds; self.wpos.x += ds * self.slant},
 			UnitState::WalkLeft => {self.wpos.x -= ds},
-			UnitState::WalkUp => {self.wpos.y -= ds},
+			UnitState::WalkUp => {self.wpos.y -= ds; self.wpos.x += ds * self.slant},
 			UnitState::WalkRight => {self.wpos.x += ds},
 			_ => ()
 		};
@@ -596,26 +2262,86 @@ impl Unit {
 	}
 }
 
-/// Convert world co-ordinates into screen co-ordinates for rendering purposes.
+/// Convert world co-ordinates into screen co-ordinates for rendering purposes, raised by the elevation of the tile.
 pub fn wots(w: &World,xw: i32, yw: i32) -> (i32, i32) {
-	return (w.origin.0 + (xw-yw)*w.tile_size.0/2, w.origin.1 + (xw+yw)*w.tile_size.1/2)
+	let lift = elevation_at(w, xw, yw) as i32 * w.elevation.step;
+	if w.grid == Grid::Hex {
+		return (w.origin.0 + xw*w.tile_size.0 + (yw & 1)*w.tile_size.0/2, w.origin.1 + yw*w.tile_size.1*3/4 - lift);
+	}
+	return (w.origin.0 + (xw-yw)*w.tile_size.0/2, w.origin.1 + (xw+yw)*w.tile_size.1/2 - lift)
 }
 
+/// Convert the world position of a unit or projectile to screen co-ordinates, raised by the elevation beneath it, blended between tiles.
 fn wots_v(w: &World, v: Vector2) -> Vector2 {
 	let v2 = wots_f(w, v.x, v.y);
-	return Vector2::new(v2.0, v2.1);
+	return Vector2::new(v2.0, v2.1 - elevation_f(w, v.x, v.y) * w.elevation.step as f32);
+}
+
+/// Returns the elevation at a world position between tiles, blending those of the four tiles around it.
+fn elevation_f(w: &World, xw: f32, yw: f32) -> f32 {
+	if w.tilemap.heights.is_empty() {
+		return 0.0;
+	}
+	let (x0, y0) = (xw.floor(), yw.floor());
+	let (fx, fy) = (xw - x0, yw - y0);
+	let e = |dx: i32, dy: i32| elevation_at(w, x0 as i32 + dx, y0 as i32 + dy) as f32;
+	let top = e(0, 0) + (e(1, 0) - e(0, 0)) * fx;
+	let bottom = e(0, 1) + (e(1, 1) - e(0, 1)) * fx;
+	top + (bottom - top) * fy
 }
 
-/// Convert world co-ordinates to screen co-ordinates.
+/// Convert world co-ordinates to screen co-ordinates, ignoring elevation.
 fn wots_f(w: &World, xw: f32, yw: f32) -> (f32, f32) {
+	if w.grid == Grid::Hex {
+		// Between rows, the half tile shift of odd rows is blended in.
+		let r = f32::floor(yw);
+		let odd = |r: f32| (r as i32 & 1) as f32;
+		let shift = 0.5 * (odd(r) + (odd(r + 1.0) - odd(r)) * (yw - r));
+		return ((w.origin.0 as f32) + (xw + shift)*(w.tile_size.0 as f32), (w.origin.1 as f32) + yw*(0.75*w.tile_size.1 as f32));
+	}
 	return ((w.origin.0 as f32) + (xw-yw)*(0.5*w.tile_size.0 as f32), (w.origin.1 as f32) + (xw+yw)*(0.5*w.tile_size.1 as f32))
 }
 
-/// Get the world position of the virtual tile at given screen position.
+/// Get the screen position of the centre of the tile at given world position, i.e, the inverse of [tile_at].
+pub fn tile_to_screen(w: &World, tx: i32, ty: i32) -> (f32, f32) {
+	let (sx, sy) = wots_f(w, tx as f32, ty as f32);
+	let sy = sy - (elevation_at(w, tx, ty) as i32 * w.elevation.step) as f32;
+	let cpos = w.get_cpos();
+	((sx - cpos.0 + 0.5*w.tile_size.0 as f32) * w.zoom, (sy - cpos.1 + 0.5*w.tile_size.1 as f32) * w.zoom)
+}
+
+/// Move the camera by a distance in screen co-ordinates at no zoom, i.e, the inverse of [wots_f] without the origin.
+fn _pan(w: &mut World, dx: f32, dy: f32) {
+	let (tw, th) = (w.tile_size.0 as f32, w.tile_size.1 as f32);
+	if w.grid == Grid::Hex {
+		w.cam_wx += dx / tw;
+		w.cam_wy += dy / (0.75 * th);
+	} else {
+		w.cam_wx += dx / tw + dy / th;
+		w.cam_wy += dy / th - dx / tw;
+	}
+}
+
+/// Get the world position of the virtual tile at given screen position; on elevated maps, the highest tile whose top covers the position.
 pub fn tile_at(w: &World, x: f32, y: f32) -> (i32, i32) {
 	let cpos = w.get_cpos();
-	let x = x + cpos.0 - w.origin.0 as f32;
-	let y = y + cpos.1 - w.origin.1 as f32;
+	let x = x / w.zoom + cpos.0 - w.origin.0 as f32;
+	let y = y / w.zoom + cpos.1 - w.origin.1 as f32;
+	let top = w.tilemap.heights.iter().cloned().max().unwrap_or(0);
+	for h in (1..=top).rev() {
+		let t = flat_tile_at(w, x, y + (h as i32 * w.elevation.step) as f32);
+		if elevation_at(w, t.0, t.1) == h {
+			return t;
+		}
+	}
+	flat_tile_at(w, x, y)
+}
+
+/// Returns the tile at a position relative to the origin, in screen co-ordinates at no zoom, ignoring elevation.
+fn flat_tile_at(w: &World, x: f32, y: f32) -> (i32, i32) {
+	if w.grid == Grid::Hex {
+		return hex_at(x/w.tile_size.0 as f32 - 0.5, y/w.tile_size.1 as f32 - 0.5);
+	}
 	let tx = f32::floor(x/w.tile_size.0 as f32);
 	let ty = f32::floor(y/w.tile_size.1 as f32);
 	let tix = x - (tx + 0.5)*w.tile_size.0 as f32;
@@ -637,6 +2363,25 @@ pub fn tile_at(w: &World, x: f32, y: f32) -> (i32, i32) {
 	}
 }
 
+/// Returns the hexagonal tile at a position, in tile widths and heights from the centre of tile (0, 0).
+fn hex_at(x: f32, y: f32) -> (i32, i32) {
+	// Axial co-ordinates, in a regular hexagon of unit size.
+	let (x, y) = (x * f32::sqrt(3.0), y * 2.0);
+	let q = x / f32::sqrt(3.0) - y / 3.0;
+	let r = y * 2.0 / 3.0;
+	// Round to the nearest hexagon, in cube co-ordinates.
+	let s = -q - r;
+	let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+	let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+	if dq > dr && dq > ds {
+		rq = -rr - rs;
+	} else if dr > ds {
+		rr = -rq - rs;
+	}
+	let (q, r) = (rq as i32, rr as i32);
+	(q + (r - (r & 1)) / 2, r)
+}
+
 /// Add a static image/texture of given size to the world at the specified location.
 /// Statics are rendered in insertion/creation order.
 pub fn create_static(w: &mut World, tex_id: u8, co_ords: (i32,i32)) {
@@ -648,10 +2393,74 @@ pub fn create_static(w: &mut World, tex_id: u8, co_ords: (i32,i32)) {
 	w.statics.push(s);
 }
 
-/// Register a unit type into the world.
-/// Only registered types can have units.
-pub fn register_unit_type(w: &mut World, u: UnitType, id: u8) {
-	w.unit_types.insert(id, u);
+/// Register a unit type into the world.
+/// Only registered types can have units.
+pub fn register_unit_type(w: &mut World, u: UnitType, id: u8) {
+	w.unit_types.insert(id, u);
+}
+
+/// Set the behavior tree of the registered unit type with specified id, see [UnitType::set_behavior_tree]. Returns false if there is no such type.
+pub fn set_type_behavior_tree(w: &mut World, type_id: u8, tree: Option<BehaviorTree>) -> bool {
+	match w.unit_types.get_mut(&type_id) {
+		Some(ut) => {
+			ut.set_behavior_tree(tree);
+			true
+		},
+		None => false
+	}
+}
+
+/// Register a variant of the unit type with id `base` under `id`, see [UnitType::variant].
+/// `f` applies overrides to the variant before it is registered.
+/// Returns false if no unit type with id `base` has been registered.
+pub fn register_unit_variant(w: &mut World, base: u8, id: u8, name: String, f: impl FnOnce(&mut UnitType)) -> bool {
+	let mut u = match w.unit_types.get(&base) {
+		Some(b) => b.variant(name),
+		None => {
+			eprintln!("warning [world]: Cannot derive from unregistered unit type id={}", base);
+			return false;
+		}
+	};
+	f(&mut u);
+	w.unit_types.insert(id, u);
+	true
+}
+
+/// Check the animations of all registered unit types against the size of their textures, as returned by `tex_size`.
+/// Returns a message for every animation with missing frames, or frames that lie (partly) outside the texture, and for unit types with missing textures or animations.
+pub fn validate_animations(w: &World, tex_size: impl Fn(u8) -> Option<(i32, i32)>) -> Vec<String> {
+	let mut ids: Vec<&u8> = w.unit_types.keys().collect();
+	ids.sort();
+	let mut msgs = vec![];
+	for id in ids {
+		let ut = &w.unit_types[id];
+		let (tw, th) = match tex_size(ut.tex_id) {
+			Some(s) => s,
+			None => {
+				msgs.push(format!("unit type '{}' [id:{}]: texture id={} has not been loaded", ut.name, id, ut.tex_id));
+				continue;
+			}
+		};
+		if ut.anim.len() < 9 {
+			msgs.push(format!("unit type '{}' [id:{}]: only {} of 9 animations defined", ut.name, id, ut.anim.len()));
+		}
+		let named = ut.anim.iter().enumerate().map(|(i, a)| (usize_as_state(i).map_or(format!("animation {}", i), |s| format!("{:?}", s)), a));
+		let idle = ut.idle_anim.iter().enumerate().map(|(i, a)| (format!("idle variation {}", i), a));
+		let stand = ut.stand_anim.iter().enumerate().filter_map(|(i, a)| a.as_ref().map(|a| (format!("Stand facing {:?}", u8_as_facing(i as u8).unwrap()), a)));
+		for (name, a) in named.chain(idle).chain(stand) {
+			if a.nframes == 0 {
+				msgs.push(format!("unit type '{}' [id:{}], {}: animation has no frames", ut.name, id, name));
+				continue;
+			}
+			if (a.sfr_y + a.frame_height) as i32 > th {
+				msgs.push(format!("unit type '{}' [id:{}], {}: frames span y={}..{}, beyond texture height {}", ut.name, id, name, a.sfr_y, a.sfr_y + a.frame_height, th));
+			}
+			if let Some(f) = (0..a.nframes).find(|f| (a.sfr_x + (*f as u32 + 1)*a.frame_width) as i32 > tw) {
+				msgs.push(format!("unit type '{}' [id:{}], {}: frame {} of {} exceeds texture width {}", ut.name, id, name, f, a.nframes, tw));
+			}
+		}
+	}
+	msgs
 }
 
 /// Spawn a unit of the given type with specified tint, and position.
@@ -672,6 +2481,12 @@ pub fn spawn_unit(w: &mut World, type_id: u8, co_ords: (i32, i32), tint: i32, pl
 	return f;
 }
 
+/// Remove the unit with the specified id from the world.
+/// Returns false if the unit does not exist.
+pub fn despawn_unit(w: &mut World, uid: u8) -> bool {
+	w.units.remove(&uid).is_some()
+}
+
 /// Returns true if given order has not yet been completed, else false.
 pub fn order_pending(o: &Order, w: &mut World, next_state: &mut Option<u8>) -> bool {
 	match o {
@@ -688,12 +2503,14 @@ pub fn order_pending(o: &Order, w: &mut World, next_state: &mut Option<u8>) -> b
 		Order::MutHealthA(id, delta) => {
 			let u = w.units.get_mut(id).unwrap();
 			u.health += delta;
+			w.health_popup(*id, *delta);
 			false
 		}
 		Order::MutHealthR(id, delta) => {
 			let u = w.units.get_mut(id).unwrap();
 			let absdel = delta * w.unit_types.get(&u.type_id).unwrap().max_health;
 			u.health += absdel;
+			w.health_popup(*id, absdel);
 			false
 		}
 	}
@@ -714,6 +2531,8 @@ fn has_unit_moved(w: &mut World, uid: u8, co_ords: (i32, i32)) -> bool {
 			return true;
 		}
 	} else {
+		let dx = co_ords.0 - u.wpos.x as i32;
+		u.slant = if co_ords.1 != u.wpos.y as i32 && dx.abs() == 1 { dx as f32 } else { 0.0 };
 		_chust(u,_gdir(u, co_ords.0, co_ords.1, uid));
 		u.busy = true;
 		return true;
@@ -728,6 +2547,7 @@ fn has_unit_attacked(w: &mut World, uid: u8, trg: u8, co_ords: (i32,i32)) -> boo
 		}
 	}.wpos;
 	//.expect("Invalid unit ID").wpos;
+	let grid = w.grid;
 	let u: &mut Unit = w.units.get_mut(&uid).expect("Invalid unit ID");
 	let ut = w.unit_types.get(&u.type_id).expect("Invalid unit type ID");
 	if u.busy {
@@ -737,19 +2557,33 @@ fn has_unit_attacked(w: &mut World, uid: u8, trg: u8, co_ords: (i32,i32)) -> boo
 			_chust(u,UnitState::Stand);
 			u.busy = false;
 			let atk_id = u.type_id;
-			let t = match w.units.get_mut(&trg) {
+			let def_id = match w.units.get(&trg) {
 				None => {return false},	// お前 和 網 死んでいる
-				Some(t) => t
+				Some(t) => t.type_id
 			};
-			let dmg = w.dmg_func.invoke(atk_id, t.type_id);
-			t.health -= dmg; //ut.max_health*ut.base_attack;
+			let mut dmg = match &mut w.dmg_func {
+				DamageFunc::Builtin => default_damage(w, uid, trg),
+				DamageFunc::Handle(r) => r(atk_id, def_id),
+				DamageFunc::CHandle(r) => r(atk_id, def_id)
+			};
+			if !is_unit_player_controlled(w, trg) {
+				dmg /= w.difficulty.enemy_health;
+			}
+			#[cfg(feature = "debug_tools")]
+			if w.cheats.god_mode && is_unit_player_controlled(w, trg) {
+				dmg = 0.0;
+			}
+			if let Some(t) = w.units.get_mut(&trg) {
+				t.health -= dmg;
+			}
+			w.events.push(BattleEvent::Attack(uid, trg, dmg));
 			return false;
 		} else {
 			return true;
 		}
 	} else {
 		_chust(u,_gadir(u, tp, uid));
-		let dst = i32::abs(tp.x as i32 - co_ords.0) + i32::abs(tp.y as i32 - co_ords.1);
+		let dst = grid.distance((tp.x as i32, tp.y as i32), co_ords);
 		if dst > 1 {
 			let vec = Vector2::new(co_ords.0 as f32, co_ords.1 as f32);
 			w.projectiles.push(Projectile::new(tp, vec, 4.0, 0.5));
@@ -779,7 +2613,12 @@ pub fn set_unit_state(w: &mut World, uid: u8, us: UnitState) {
 }
 
 fn _chust(u: &mut Unit, us: UnitState) {
+	if let Some(f) = us.facing() {
+		u.facing = f;
+	}
 	u.state = us;
+	u.idle = None;
+	u.itime = 0.0;
 	u.stime = 0.0;
 	u.ftime = 0.0;
 	u.frame = 0;
@@ -790,7 +2629,45 @@ pub fn _guinfo(w: &World, u: &Unit) -> (u8, u8, String, bool) {
 	let mut s = String::new();
 	let ut = w.unit_types.get(&u.type_id).expect("Invalid unit type ID");
 	s.push_str(&ut.name);
-	(ut.movement, ut.range, s, u.player)
+	(_movement(w, u), ut.range, s, u.player)
+}
+
+/// Returns the live stats of the unit with specified id, or None if the unit does not exist.
+pub fn unit_stats(w: &World, uid: u8) -> Option<UnitStats> {
+	let u = w.units.get(&uid)?;
+	let ut = w.unit_types.get(&u.type_id)?;
+	let boost = u.boost.max(0.0);
+	let mut statuses = Vec::new();
+	if boost > 0.0 {
+		statuses.push(UnitStatus::Boosted);
+	}
+	match u.behavior {
+		Some(Behavior::Hold) => statuses.push(UnitStatus::Holding),
+		Some(Behavior::Guard { .. }) => statuses.push(UnitStatus::Guarding),
+		Some(Behavior::Patrol { .. }) => statuses.push(UnitStatus::Patrolling),
+		None => {}
+	}
+	Some(UnitStats {
+		name: ut.name.clone(),
+		player: u.player,
+		health: u.health,
+		max_health: ut.max_health,
+		attack: ut.attack * (1.0 + boost),
+		boost: boost,
+		defense: ut.defense,
+		movement: _movement(w, u),
+		range: ut.range,
+		statuses: statuses
+	})
+}
+
+/// Returns the tiles the unit may move per turn.
+fn _movement(w: &World, u: &Unit) -> u8 {
+	#[cfg(feature = "debug_tools")]
+	if u.player && w.cheats.infinite_movement {
+		return crate::debug_tools::INFINITE_MOVEMENT;
+	}
+	w.unit_types.get(&u.type_id).map(|ut| ut.movement).unwrap_or(0)
 }
 
 fn _gdir(v: &Unit, tx: i32, ty: i32, uid: u8) -> UnitState{
@@ -799,11 +2676,12 @@ fn _gdir(v: &Unit, tx: i32, ty: i32, uid: u8) -> UnitState{
 	let tx = tx - wx;
 	let ty = ty - wy;
 	println!("uid:{}, wx: {}, wy: {}, tx: {}, ty: {}", uid, wx, wy, tx, ty);
-	if tx == 0 && ty == 1 {
+	// Diagonal steps, between hexagonal tiles, walk up or down.
+	if tx.abs() <= 1 && ty == 1 {
 		return UnitState::WalkDown;
 	} else if tx == 1 && ty == 0 {
 		return UnitState::WalkRight;
-	} else if tx == 0 && ty == -1 {
+	} else if tx.abs() <= 1 && ty == -1 {
 		return UnitState::WalkUp;
 	} else if tx == -1 && ty == 0 {
 		return UnitState::WalkLeft;
@@ -834,6 +2712,9 @@ pub fn is_tile_atrange(t1: (i32, i32), t2: (i32, i32), r: u8) -> bool{
 const MAGIC: [u8; 4] = [0xfa, 0xde, 0x00, 0xff];
 const CONT_READ: [u8; 2] = [0xfe,0xed];
 const MPSIG: [u8; 2] = [0xda, 0xd7];
+const SLIDESIG: [u8; 2] = [0x51, 0x1d];
+const LAYERSIG: [u8; 2] = [0x1a, 0xe7];
+const AMBIENTSIG: [u8; 2] = [0xab, 0x1e];
 macro_rules! bferr {
 	($f:ident, $emsg:literal) => {
 		{
@@ -874,6 +2755,62 @@ fn read_string(f: &mut File, fpath: &str, st: &mut String, mut buf2: [u8; 2]) ->
 	true
 }
 
+fn read_layer(f: &mut File, fpath: &str, tm: &mut TileMap) -> bool {
+	let mut buf1 = [0];
+	let n = f.read(&mut buf1).expect("Failed to read layer kind.");
+	if n < 1 {
+		bferr!(fpath, "Failed to read layer kind.");
+	}
+	let kind = match LayerKind::from_u8(buf1[0]) {
+		Some(k) => k,
+		None => bferr!(fpath, "Invalid layer kind. Allowed: 0 (ground), 1 (decoration) or 2 (overlay)")
+	};
+	let mut tiles = vec![0; tm.map_tiles.len()];
+	let n = f.read(&mut tiles).expect("Failed to read layer tiles.");
+	if n < tiles.len() {
+		bferr!(fpath, "Tile layer does not specify all tiles (premature termination of file).");
+	}
+	tm.layers.push(TileLayer { kind: kind, tiles: tiles });
+	true
+}
+
+fn read_ambient(f: &mut File, fpath: &str, tm: &mut TileMap) -> bool {
+	let mut buf1 = [0];
+	let n = f.read(&mut buf1).expect("Failed to read ambient region count.");
+	if n < 1 {
+		bferr!(fpath, "Failed to read ambient region count.");
+	}
+	for _ in 0..buf1[0] {
+		let mut b = [0; 7];
+		let n = f.read(&mut b).expect("Failed to read ambient region.");
+		if n < 7 {
+			bferr!(fpath, "Ambient regions must be specified as a raw 7-byte sequence comprising track, x, y, x, y, fall-off and volume.");
+		}
+		let (from, to) = ((b[1] as i32, b[2] as i32), (b[3] as i32, b[4] as i32));
+		tm.ambient.push(AmbientRegion::new(b[0], from, to, b[5] as f32, b[6] as f32 / 100.0));
+	}
+	true
+}
+
+fn read_slides(f: &mut File, fpath: &str, slides: &mut Vec<Slide>) -> bool {
+	let mut buf1 = [0];
+	let n = f.read(&mut buf1).expect("Failed to read slide count.");
+	if n < 1 {
+		bferr!(fpath, "Failed to read slide count.");
+	}
+	for _ in 0..buf1[0] {
+		let mut buf2 = [0, 0];
+		let n = f.read(&mut buf2).expect("Failed to read slide.");
+		if n < 2 {
+			bferr!(fpath, "Slides must begin with a texture id and a duration.");
+		}
+		let mut caption = String::new();
+		if !read_string(f, fpath, &mut caption, [0, 0]) {return false;}
+		slides.push(Slide { tex: buf2[0], caption: caption, duration: buf2[1] as f32 / 10.0 });
+	}
+	true
+}
+
 /// Load tile map data from the specified file into the world
 /// * `_w` - The world to load [TileMap] into
 /// * `fpath` - The path to the file containing map data.  
@@ -891,6 +2828,7 @@ fn read_string(f: &mut File, fpath: &str, st: &mut String, mut buf2: [u8; 2]) ->
 /// > The fourth byte is the `type-parameter` for a given game object. The fifth and sixth bytes define the x and y co-ordinates of the game object.  
 ///
 /// If any of the mandatory components of the format are missing in the file specified, the file is termed as *a malformed world file*.
+/// Maps are written in this format by [save_world]; see [load_world_ron] for a human-readable format.
 /// The term Game Object is *merely an abstract construct* (with no direct counterpart in the engine) used to allow for a common format of specification for statics, and units.
 /// The following table summarizes `type` and `type-parameter` relations:
 ///
@@ -899,12 +2837,37 @@ fn read_string(f: &mut File, fpath: &str, st: &mut String, mut buf2: [u8; 2]) ->
 /// | Static (0) | The texture id of the static |
 /// | Player Unit (1) | The type id of the unit |
 /// | Enemy Unit (2) | The type id of the unit |
+/// | Hold (3) | Unused; the last unit defined holds its position |
+/// | Guard (4) | The radius guarded, around the tile at the given co-ordinates, by the last unit defined |
+/// | Patrol Waypoint (5) | Unused; the tile at the given co-ordinates is added to the patrol of the last unit defined |
+/// | Healing Pickup (6) | The percentage of maximum health restored |
+/// | Boost Pickup (7) | The percentage by which attack strength is raised |
+/// | Gold Pickup (8) | The amount of gold |
+///
+/// See [Behavior] for the behaviors of types 3 to 5, which are ignored (with a warning) if no unit has been defined before them,
+/// and [pickup] for the pickups of types 6 to 8, drawn with the textures [MAP_TEXTURES](pickup::MAP_TEXTURES).
+///
+/// Game objects may be followed by the slideshow shown before the intro text (see [World::set_intro_slides]): the bytes `[81, 29]`, the number of slides,
+/// and for each slide, its texture id, its duration in tenths of a second, and its caption, encoded like the texts of the map (a big-endian `u16` length, then UTF-8).
+/// They may also be followed by any number of [tile layers](World::add_tile_layer), each the bytes `[26, 231]`, the [LayerKind] and `w*h` bytes of tiles,
+/// and by the [ambient regions](ambient) of the map: the bytes `[171, 30]`, the number of regions, and for each region, its track id, the x and y co-ordinates of two opposite corners,
+/// its fall-off in tiles and its volume in percent. The slideshow, layers and ambient regions may come in any order.
 /// ## Panics
 /// The function panics with appropriate error messages if:  
 /// 1. The file could not be found or opened (does not block until file is available)
 /// 2. An I/O Error occurs, and read fails.
 /// 3. Memory allocation of map data fails.
-pub fn load_world(_w: &mut World, fpath: &str) -> bool {
+///
+/// Once loaded, the `on_map_loaded` [callback](crate::utils::StateListener) is invoked with the [MapInfo] before the next frame (i.e, before the intro, if the game has not yet begun).
+pub fn load_world(w: &mut World, fpath: &str) -> bool {
+	if !read_world(w, fpath) {
+		return false;
+	}
+	w.loaded_map = Some(fpath.to_string());
+	true
+}
+
+fn read_world(_w: &mut World, fpath: &str) -> bool {
 	let mut f = match File::open(fpath) {
 		Err(e) => panic!("Failed to load world file: {}, due to an error. Cause: {}", fpath, e),
 		Ok(a) => a
@@ -971,8 +2934,12 @@ pub fn load_world(_w: &mut World, fpath: &str) -> bool {
 		intro_text: intro_text,
 		defeat_text: defeat_text,
 		victory_text: victory_text,
+		layers: vec![],
+		heights: vec![],
+		ambient: vec![],
 		show: true
 	};
+	_w.presentation.slides.clear();
 
 	/*match f.seek(SeekFrom::Current(6)) {
 		Err(_e) => {
@@ -987,6 +2954,7 @@ pub fn load_world(_w: &mut World, fpath: &str) -> bool {
 		eprintln!("debug [load_world]: Reached EOF");
 		return true;
 	}
+	let mut last_unit = None;
 	while n == 2 && buf2 == CONT_READ {
 		n = f.read(&mut buf4).expect("Failed to read game object data.");
 		if n < 4 {
@@ -995,19 +2963,190 @@ pub fn load_world(_w: &mut World, fpath: &str) -> bool {
 		eprintln!("Game Object Data: {:?}", buf4);
 		match buf4[0] {
 			0 => create_static(_w, buf4[1], (buf4[2] as i32, buf4[3] as i32)),
-			1 => {spawn_unit(_w, buf4[1], (buf4[2] as i32, buf4[3] as i32), -1, true);},
-			2 => {spawn_unit(_w, buf4[1], (buf4[2] as i32, buf4[3] as i32), -0x38ffc328, false);},
+			1 => {last_unit = Some(spawn_unit(_w, buf4[1], (buf4[2] as i32, buf4[3] as i32), -1, true));},
+			2 => {last_unit = Some(spawn_unit(_w, buf4[1], (buf4[2] as i32, buf4[3] as i32), -0x38ffc328, false));},
+			3..=5 => match last_unit {
+				Some(uid) => {
+					let tile = (buf4[2] as i32, buf4[3] as i32);
+					match buf4[0] {
+						3 => set_behavior(_w, uid, Some(Behavior::Hold)),
+						4 => set_behavior(_w, uid, Some(Behavior::Guard { tile: tile, radius: buf4[1] })),
+						_ => add_patrol_point(_w, uid, tile)
+					};
+				},
+				None => eprintln!("warning: Behavior TYPE={} given before any unit", buf4[0])
+			},
+			6..=8 => {
+				let k = buf4[0] - 6;
+				let v = if k == 2 { buf4[1] as f32 } else { buf4[1] as f32 / 100.0 };
+				if let Some(e) = PickupEffect::from_raw(k, v) {
+					pickup::place(_w, pickup::MAP_TEXTURES[k as usize], (buf4[2] as i32, buf4[3] as i32), e);
+				}
+			},
 			a => {eprintln!("warning: Unrecognized game object TYPE={}", a);}
 		};
 		n = f.read(&mut buf2).expect("Failed to read continue notifier.")
 	}
+	// Optional sections follow the game objects, in any order.
+	while n == 2 {
+		let ok = match buf2 {
+			SLIDESIG => read_slides(&mut f, fpath, &mut _w.presentation.slides),
+			LAYERSIG => read_layer(&mut f, fpath, &mut _w.tilemap),
+			AMBIENTSIG => read_ambient(&mut f, fpath, &mut _w.tilemap),
+			_ => break
+		};
+		if !ok {
+			return false;
+		}
+		n = f.read(&mut buf2).expect("Failed to read section notifier.");
+	}
 	return true;
 }
 
+/// Save the map of the world to the file at `path`, in the binary format read by [load_world], so that tools and editors may round-trip maps.
+/// The file holds the tiles (with their movement permissions), texts, statics, units (with their behaviors), pickups, intro slides, tile layers and ambient regions of the world.
+/// Units are saved by type and side at their tiles, in order of id; the rest of the battle in progress, e.g, their health, is not (see [save_state]).
+/// Pickups are saved by their effects, and drawn with [MAP_TEXTURES](pickup::MAP_TEXTURES) once loaded.
+///
+/// Returns an error message if the file could not be written, or the map does not fit the format, i.e, it is wider or taller than 255 tiles,
+/// an object lies off tiles 0 to 255, a value (e.g, the gold of a pickup, or the fall-off of an ambient region) exceeds a byte, or a text exceeds 65535 bytes.
+pub fn save_world(w: &World, path: &str) -> Result<(), String> {
+	let bytes = write_world(w).map_err(|e| format!("cannot save {}: {}", path, e))?;
+	crate::save::write_atomic_bytes(path, &bytes).map_err(|e| format!("failed to write {}: {}", path, e))
+}
+
+fn write_world(w: &World) -> Result<Vec<u8>, String> {
+	let byte = |v: i64, what: &str| if (0..=255).contains(&v) { Ok(v as u8) } else { Err(format!("{} {} does not fit in a byte", what, v)) };
+	let tile = |(x, y): (i32, i32), what: &str| -> Result<(u8, u8), String> { Ok((byte(x as i64, what)?, byte(y as i64, what)?)) };
+	let string = |out: &mut Vec<u8>, s: &str| {
+		if s.len() > u16::MAX as usize {
+			return Err(format!("text of {} bytes is too long", s.len()));
+		}
+		out.extend_from_slice(&(s.len() as u16).to_be_bytes());
+		out.extend_from_slice(s.as_bytes());
+		Ok(())
+	};
+	let tm = &w.tilemap;
+	let mut out = MAGIC.to_vec();
+	out.push(byte(tm.map_width as i64, "map width")?);
+	out.push(byte(tm.map_height as i64, "map height")?);
+
+	if tm.tile_perm.values().any(|p| !matches!(p, TileType::Allowed)) {
+		out.extend_from_slice(&MPSIG);
+		for perm in [TileType::Prohibited, TileType::Heal, TileType::Damage].iter() {
+			let mut list: Vec<u8> = tm.tile_perm.iter().filter(|(_, p)| std::mem::discriminant(*p) == std::mem::discriminant(perm)).map(|(t, _)| *t).collect();
+			list.sort_unstable();
+			out.push(byte(list.len() as i64, "number of tiles listed")?);
+			out.extend(list);
+		}
+	} else {
+		out.extend_from_slice(&[0, 0]);
+	}
+	out.extend_from_slice(&tm.map_tiles);
+	for s in [&tm.title, &tm.intro_text, &tm.victory_text, &tm.defeat_text].iter() {
+		string(&mut out, s)?;
+	}
+
+	let object = |out: &mut Vec<u8>, kind: u8, param: u8, (x, y): (u8, u8)| out.extend_from_slice(&[CONT_READ[0], CONT_READ[1], kind, param, x, y]);
+	for s in &w.statics {
+		object(&mut out, 0, s.tex_id, tile((s.wx, s.wy), "static tile")?);
+	}
+	let mut ids: Vec<&u8> = w.units.keys().collect();
+	ids.sort();
+	for id in ids {
+		let u = &w.units[id];
+		let at = tile((u.wpos.x as i32, u.wpos.y as i32), "unit tile")?;
+		object(&mut out, if u.player { 1 } else { 2 }, u.type_id, at);
+		match &u.behavior {
+			Some(Behavior::Hold) => object(&mut out, 3, 0, at),
+			Some(Behavior::Guard { tile: t, radius }) => object(&mut out, 4, *radius, tile(*t, "guarded tile")?),
+			Some(Behavior::Patrol { waypoints, .. }) => for p in waypoints {
+				object(&mut out, 5, 0, tile(*p, "waypoint")?);
+			},
+			None => {}
+		}
+	}
+	for p in &w.pickups {
+		let (kind, v) = p.effect.to_raw();
+		let v = if kind == 2 { v } else { (v * 100.0).round() };
+		object(&mut out, 6 + kind, byte(v as i64, "pickup value")?, tile(p.tile, "pickup tile")?);
+	}
+
+	let slides = &w.presentation.slides;
+	if !slides.is_empty() {
+		out.extend_from_slice(&SLIDESIG);
+		out.push(byte(slides.len() as i64, "number of slides")?);
+		for s in slides {
+			out.push(s.tex);
+			out.push(byte((s.duration * 10.0).round() as i64, "slide duration (in tenths of a second)")?);
+			string(&mut out, &s.caption)?;
+		}
+	}
+	for l in &tm.layers {
+		out.extend_from_slice(&LAYERSIG);
+		out.push(l.kind as u8);
+		out.extend_from_slice(&l.tiles);
+	}
+	if !tm.ambient.is_empty() {
+		out.extend_from_slice(&AMBIENTSIG);
+		out.push(byte(tm.ambient.len() as i64, "number of ambient regions")?);
+		for r in &tm.ambient {
+			out.push(r.track);
+			let (from, to) = (tile(r.from, "ambient region corner")?, tile(r.to, "ambient region corner")?);
+			out.extend_from_slice(&[from.0, from.1, to.0, to.1]);
+			out.push(byte(r.falloff.round() as i64, "ambient fall-off (in tiles)")?);
+			out.push((r.volume * 100.0).round() as u8);
+		}
+	}
+	Ok(out)
+}
+
+/// Load the map from the text file at `fpath`, in the [human-readable format](ron) that can be edited by hand, as [load_world] does from a binary file.
+/// Returns false (leaving the world unchanged) if the file could not be read or is malformed, printing the reason with the line at fault.
+pub fn load_world_ron(w: &mut World, fpath: &str) -> bool {
+	let text = match std::fs::read_to_string(fpath) {
+		Ok(t) => t,
+		Err(e) => {
+			eprintln!("fatal [load_world]: Failed to read world file {}: {}", fpath, e);
+			return false;
+		}
+	};
+	if let Err(e) = ron::parse(w, &text) {
+		eprintln!("fatal [load_world]: Malformed world file {}: {}", fpath, e);
+		return false;
+	}
+	w.loaded_map = Some(fpath.to_string());
+	true
+}
+
+/// Save the map of the world to the file at `path`, in the [human-readable format](ron) read by [load_world_ron]; the map is saved as by [save_world], without its limits on size.
+/// Returns an error message if the file could not be written.
+pub fn save_world_ron(w: &World, path: &str) -> Result<(), String> {
+	crate::save::write_atomic(path, &ron::to_string(w)).map_err(|e| format!("failed to write {}: {}", path, e))
+}
+
 /// Return position of tile texture in tileset and tile position on-screen.
 pub fn prep_tiledraw(w: &World, x: i32, y: i32, n: i32) -> (Vector2, Vector2) {
 	let idx = ((y as usize)*w.tilemap.map_width+(x as usize)) % w.tilemap.map_tiles.len();
-	let t = w.tilemap.map_tiles[idx];
+	tile_rects(w, w.tilemap.map_tiles[idx], x, y, n)
+}
+
+/// Return position of tile texture in tileset and tile position on-screen, for the tile of the layer with specified index; None if the tile is empty, or there is no such layer.
+pub fn prep_layer_tiledraw(w: &World, layer: usize, x: i32, y: i32, n: i32) -> Option<(Vector2, Vector2)> {
+	let t = layer_tile_at(w, layer, x, y)?;
+	(t != EMPTY_TILE).then(|| tile_rects(w, t, x, y, n))
+}
+
+/// Returns the tile of the layer with specified index at the specified tile position, or None if out of the map, or there is no such layer.
+pub fn layer_tile_at(w: &World, layer: usize, x: i32, y: i32) -> Option<u8> {
+	let l = w.tilemap.layers.get(layer)?;
+	if x < 0 || y < 0 || x as usize >= w.tilemap.map_width || y as usize >= w.tilemap.map_height {
+		return None;
+	}
+	Some(l.tiles[(y as usize)*w.tilemap.map_width+(x as usize)])
+}
+
+fn tile_rects(w: &World, t: u8, x: i32, y: i32, n: i32) -> (Vector2, Vector2) {
 	let ty = t as i32 / n;
 	let tx = t as i32 % n;
 	let u = crate::world::wots(w, x, y);
@@ -1029,6 +3168,9 @@ pub fn tile_type_at(w: &World, x: i32, y: i32) -> TileType {
 	if x < 0 || y < 0 {
 		return TileType::Prohibited;
 	}
+	if w.tilemap.map_tiles.is_empty() {
+		return TileType::Allowed;
+	}
 	let idx = ((y as usize)*w.tilemap.map_width+(x as usize)) % w.tilemap.map_tiles.len();
 	let t = w.tilemap.map_tiles[idx];
 	if w.tilemap.tile_perm.contains_key(&t) {
@@ -1038,6 +3180,653 @@ pub fn tile_type_at(w: &World, x: i32, y: i32) -> TileType {
 	}
 }
 
+/// Returns the elevation of the tile at the specified position, or 0 if out of the map, or the map is flat. See [World::set_elevation].
+pub fn elevation_at(w: &World, x: i32, y: i32) -> u8 {
+	if x < 0 || y < 0 || x as usize >= w.tilemap.map_width || y as usize >= w.tilemap.map_height {
+		return 0;
+	}
+	w.tilemap.heights.get((y as usize)*w.tilemap.map_width+(x as usize)).cloned().unwrap_or(0)
+}
+
+/// Returns true if a unit may step between two neighbouring tiles, i.e, their elevations differ by at most [ElevationRules::max_climb]; otherwise, a cliff lies between them.
+pub fn can_step(w: &World, from: (i32, i32), to: (i32, i32)) -> bool {
+	(elevation_at(w, from.0, from.1) as i32 - elevation_at(w, to.0, to.1) as i32).abs() <= w.elevation.max_climb as i32
+}
+
+/// Returns the tiles added to the attack range of a unit on tile `from` against a unit on tile `to`, i.e, [ElevationRules::range_bonus] if `from` is higher, or else 0.
+pub fn range_bonus(w: &World, from: (i32, i32), to: (i32, i32)) -> i32 {
+	if elevation_at(w, from.0, from.1) > elevation_at(w, to.0, to.1) { w.elevation.range_bonus as i32 } else { 0 }
+}
+
+/// Returns true if a unit with attack range `range` on tile `from` may attack a unit on tile `to`: at exactly its range, or (standing higher) up to [range_bonus] tiles farther.
+pub fn is_in_attack_range(w: &World, from: (i32, i32), to: (i32, i32), range: i32) -> bool {
+	let d = w.grid.distance(from, to);
+	d >= range && d <= range + range_bonus(w, from, to)
+}
+
+/// Returns the levels of elevation the unit with id `atk_uid` stands above the unit with id `def_uid` (negative if below), e.g, for a bound damage function. Returns 0 if either unit does not exist.
+pub fn height_advantage(w: &World, atk_uid: u8, def_uid: u8) -> i32 {
+	match (unit_tile(w, atk_uid), unit_tile(w, def_uid)) {
+		(Some(a), Some(d)) => elevation_at(w, a.0, a.1) as i32 - elevation_at(w, d.0, d.1) as i32,
+		_ => 0
+	}
+}
+
+/// The built-in damage formula, used when no damage function has been bound.
+/// Base damage is `attack * attack / (attack + defense)` of the respective unit types (with the attack raised by the [boost](Unit::boost) of the attacker), which is
+/// * multiplied by 0.75 if the defender stands on a healing tile, and by 1.25 if on a damaging tile.
+/// * reduced by 10% for every tile of distance beyond the first, down to half, for ranged attacks.
+/// * raised by [ElevationRules::damage_bonus] for every level of [height advantage](height_advantage), or lowered for every level below, down to half.
+/// * multiplied by the damage multiplier of the attacker's side, see [Difficulty].
+///
+/// Returns 0 if either unit does not exist, or the attacker has no attack strength.
+pub fn default_damage(w: &World, atk_uid: u8, def_uid: u8) -> f32 {
+	let (a, d) = match (w.units.get(&atk_uid), w.units.get(&def_uid)) {
+		(Some(a), Some(d)) => (a, d),
+		_ => return 0.0
+	};
+	let (atk, def) = match (w.unit_types.get(&a.type_id), w.unit_types.get(&d.type_id)) {
+		(Some(at), Some(d)) => (at.attack * (1.0 + a.boost.max(0.0)), d.defense.max(0.0)),
+		_ => return 0.0
+	};
+	if atk <= 0.0 {
+		return 0.0;
+	}
+	let mut dmg = atk * atk / (atk + def);
+	let dt = (d.wpos.x as i32, d.wpos.y as i32);
+	dmg *= match tile_type_at(w, dt.0, dt.1) {
+		TileType::Heal => 0.75,
+		TileType::Damage => 1.25,
+		_ => 1.0
+	};
+	let dist = w.grid.distance((a.wpos.x as i32, a.wpos.y as i32), dt);
+	if dist > 1 {
+		dmg *= f32::max(1.0 - 0.1 * (dist - 1) as f32, 0.5);
+	}
+	dmg *= f32::max(1.0 + w.elevation.damage_bonus * height_advantage(w, atk_uid, def_uid) as f32, 0.5);
+	dmg * if a.player { w.difficulty.player_damage } else { w.difficulty.enemy_damage }
+}
+
+/// Save the state of the battle, i.e, its units (with their health, positions, behaviors and whether they have acted in the turn), tilemap, turn, objectives and the rest of a [snapshot](crate::save), to the file at `path`, to be resumed at the player turn.
+/// Returns an error message if the file could not be written.
+pub fn save_state(w: &World, path: &str) -> Result<(), String> {
+	crate::save::write_atomic(path, &crate::save::snapshot_turn(w, TurnPhase::PlayerTurn, &w.acted)).map_err(|e| format!("failed to write {}: {}", path, e))
+}
+
+/// Restore the state of the battle saved to the file at `path`, by [save_state] or a save slot, before the game begins; the level introduction is then skipped.
+/// The map the state was saved on, and its unit types, must be loaded. Returns the phase the battle resumes at, or an error message (leaving the world unchanged) if the file could not be read, or is invalid.
+pub fn load_state(w: &mut World, path: &str) -> Result<TurnPhase, String> {
+	let text = std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path, e))?;
+	let (p, acted) = crate::save::restore_turn(w, &text).map_err(|e| format!("failed to load {}: {}", path, e))?;
+	w.resume = Some((p, acted));
+	Ok(p)
+}
+
+/// Returns the tile position of the unit with specified id, or None if the unit does not exist.
+pub fn unit_tile(w: &World, uid: u8) -> Option<(i32, i32)> {
+	w.units.get(&uid).map(|u| (u.wpos.x as i32, u.wpos.y as i32))
+}
+
+/// Returns the health and maximum health of the unit with specified id, rounded as set by [World::set_hp_rounding]; None if the unit does not exist.
+pub fn unit_hp(w: &World, uid: u8) -> Option<(i32, i32)> {
+	let u = w.units.get(&uid)?;
+	let mh = w.unit_types.get(&u.type_id)?.max_health;
+	Some((w.presentation.hp_rounding.apply(u.health), w.presentation.hp_rounding.apply(mh)))
+}
+
+/// Returns the actions the unit with specified id may take in its turn, i.e, those of its type or else those of the world; None if the unit does not exist.
+pub fn unit_actions(w: &World, uid: u8) -> Option<Actions> {
+	let u = w.units.get(&uid)?;
+	Some(w.unit_types.get(&u.type_id).and_then(|ut| ut.actions).unwrap_or(w.actions))
+}
+
+/// Returns how many tiles away the unit with specified id sees under fog of war, i.e, the sight of its type or else its movement and range; None if the unit does not exist.
+pub fn unit_sight(w: &World, uid: u8) -> Option<u8> {
+	let u = w.units.get(&uid)?;
+	let ut = w.unit_types.get(&u.type_id)?;
+	Some(ut.sight.unwrap_or_else(|| ut.movement.saturating_add(ut.range)))
+}
+
+/// Recompute the tiles seen by player units under fog of war; does nothing if fog of war is disabled.
+pub fn update_visibility(w: &mut World) {
+	let mut seen = match w.fog.take() {
+		Some(s) => s,
+		None => return
+	};
+	seen.clear();
+	for (id, u) in &w.units {
+		if !u.player {
+			continue;
+		}
+		let s = unit_sight(w, *id).unwrap_or(0) as i32;
+		seen.extend(w.grid.range((u.wpos.x as i32, u.wpos.y as i32), s));
+	}
+	w.fog = Some(seen);
+}
+
+/// Returns true if the specified tile is seen by a player unit, or fog of war is disabled.
+pub fn is_tile_visible(w: &World, tx: i32, ty: i32) -> bool {
+	match &w.fog {
+		Some(s) => s.contains(&(tx, ty)),
+		None => true
+	}
+}
+
+/// Returns true if the unit with specified id is shown, i.e, it is a player unit, it stands on a [visible](is_tile_visible) tile, or fog of war is disabled.
+/// Returns false if the unit does not exist.
+pub fn is_unit_visible(w: &World, uid: u8) -> bool {
+	match w.units.get(&uid) {
+		Some(u) => u.player || is_tile_visible(w, u.wpos.x as i32, u.wpos.y as i32),
+		None => false
+	}
+}
+
+/// Returns true if the unit with specified id is walking between tiles.
+pub(crate) fn is_unit_walking(w: &World, uid: u8) -> bool {
+	w.units.get(&uid).iter().any(|u| u.state.is_walking())
+}
+
+/// Returns the texture id of the portrait of the unit with specified id, i.e, its own or else that of its type; None if it has no portrait, or the unit does not exist.
+pub fn unit_portrait(w: &World, uid: u8) -> Option<u8> {
+	let u = w.units.get(&uid)?;
+	u.portrait.or_else(|| w.unit_types.get(&u.type_id).and_then(|ut| ut.portrait))
+}
+
+/// Returns the id of the unit standing on the specified tile, if any.
+pub fn unit_at(w: &World, tx: i32, ty: i32) -> Option<u8> {
+	w.units.iter().find(|(_, u)| (u.wpos.x as i32) == tx && (u.wpos.y as i32) == ty).map(|(id, _)| *id)
+}
+
+/// Returns the (manhattan) distance between two tiles, i.e, the number of steps a unit takes to walk between them on isometric squares; see [Grid::distance] for any grid.
+pub fn tile_distance(t1: (i32, i32), t2: (i32, i32)) -> i32 {
+	i32::abs(t1.0 - t2.0) + i32::abs(t1.1 - t2.1)
+}
+
+/// Returns all tiles the unit with specified id can move to in a single turn, excluding the tile it stands on.
+/// Movement follows the same rules as player input, i.e, tiles must allow movement, units (friend or foe) block the way, and cliffs cannot be crossed (see [can_step]).
+/// Returns an empty vector if the unit does not exist.
+pub fn reachable_tiles(w: &World, uid: u8) -> Vec<(i32, i32)> {
+	let start = match unit_tile(w, uid) { Some(t) => t, None => return vec![] };
+	let movn = w.units.get(&uid).map(|u| _movement(w, u)).unwrap_or(0) as i32;
+	let (mw, mh) = w.map_size();
+	let in_bounds = |(x, y): (i32, i32)| !w.show_map() || ((x as usize) < mw && (y as usize) < mh);
+
+	let mut seen = HashSet::new();
+	let mut frontier = vec![start];
+	seen.insert(start);
+	for _ in 0..movn {
+		let mut next = Vec::new();
+		for f in frontier {
+			for t in w.grid.neighbours(f) {
+				if seen.contains(&t) || !tile_type_at(w, t.0, t.1).allowed() || !in_bounds(t) || unit_at(w, t.0, t.1).is_some() || !can_step(w, f, t) {
+					continue;
+				}
+				seen.insert(t);
+				next.push(t);
+			}
+		}
+		frontier = next;
+	}
+	seen.remove(&start);
+	seen.into_iter().collect()
+}
+
+/// Returns the orders moving every unit of `group` by the same offset, so that the first unit ends on `target` and the formation is kept.
+/// Each unit takes a [shortest route](path::find) within its movement; tiles must allow movement, units outside the group block the way, and cliffs cannot be crossed.
+/// Returns None if the group is empty, or any unit cannot reach its place.
+pub fn group_move(w: &World, group: &[u8], target: (i32, i32)) -> Option<Vec<Order>> {
+	let lead = unit_tile(w, *group.first()?)?;
+	let (dx, dy) = (target.0 - lead.0, target.1 - lead.1);
+	let mut orders = Vec::new();
+	for &uid in group {
+		let (x, y) = unit_tile(w, uid)?;
+		let movn = w.units.get(&uid).map(|u| _movement(w, u)).unwrap_or(0) as i32;
+		let to = (x + dx, y + dy);
+		if w.grid.distance((x, y), to) > movn {
+			return None;
+		}
+		let path = path::find_within(w, (x, y), to, movn, |u| group.contains(&u))?;
+		orders.extend(path.into_iter().map(|(tx, ty)| Order::MOVE(uid, tx, ty)));
+	}
+	Some(orders)
+}
+
+/// Returns a hash of the state of the battle that orders change: the turn, statistics, objectives, tiles, pickups, and the units with their positions, health and boosts.
+/// The hash (FNV-1a over the exact bits of each value) is the same on every platform for the same state, so that peers (or runs of a [replay](crate::replay)) can check that their simulations agree;
+/// use [diff] to find what differs once they do not.
+pub fn state_hash(w: &World) -> u64 {
+	let mut h = Fnv(0xcbf29ce484222325);
+	h.write(&w.turn.to_le_bytes());
+	let st = &w.stats;
+	for v in [st.turns, st.damage_dealt.to_bits(), st.damage_received.to_bits(), st.units_lost, st.enemies_defeated, st.xp_gained].iter() {
+		h.write(&v.to_le_bytes());
+	}
+	for o in &w.objectives {
+		h.write(&[o.state as u8]);
+	}
+	h.write(&(w.tilemap.map_width as u64).to_le_bytes());
+	h.write(&(w.tilemap.map_height as u64).to_le_bytes());
+	h.write(&w.tilemap.map_tiles);
+	for p in &w.pickups {
+		let (kind, value) = p.effect.to_raw();
+		for v in [p.tile.0 as u32, p.tile.1 as u32, kind as u32, value.to_bits()].iter() {
+			h.write(&v.to_le_bytes());
+		}
+	}
+	let mut ids = id_list(w);
+	ids.sort_unstable();
+	for id in ids {
+		let u = &w.units[&id];
+		h.write(&[id, u.type_id, u.player as u8]);
+		for v in [u.health, u.wpos.x, u.wpos.y, u.boost].iter() {
+			h.write(&v.to_bits().to_le_bytes());
+		}
+	}
+	h.0
+}
+
+struct Fnv(u64);
+
+impl Fnv {
+	fn write(&mut self, bytes: &[u8]) {
+		for b in bytes {
+			self.0 = (self.0 ^ *b as u64).wrapping_mul(0x100000001b3);
+		}
+	}
+}
+
+/// A difference between the states of two worlds, as found by [diff]. Values of the first world come first.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Divergence {
+	/// The turns differ.
+	Turn(u32, u32),
+	/// A battle statistic differs, named as the field of [BattleStats].
+	Stat(&'static str, f32, f32),
+	/// The state of the objective at the index differs, or it exists in only one world.
+	Objective(usize),
+	/// The sizes of the maps differ.
+	MapSize((usize, usize), (usize, usize)),
+	/// The tile at the co-ordinates differs.
+	Tile((i32, i32), u8, u8),
+	/// The pickups on the tile differ, or a pickup lies on it in only one world.
+	Pickup((i32, i32)),
+	/// The unit with the id is alive in only one world; true if it is the first.
+	Unit(u8, bool),
+	/// A field of the unit with the id differs: "type_id", "player", "health", "x", "y" or "boost".
+	UnitField(u8, &'static str, f32, f32)
+}
+
+impl std::fmt::Display for Divergence {
+	fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+		match self {
+			Divergence::Turn(a, b) => write!(f, "turn {} != {}", a, b),
+			Divergence::Stat(s, a, b) => write!(f, "stat {} {} != {}", s, a, b),
+			Divergence::Objective(i) => write!(f, "objective {}", i),
+			Divergence::MapSize(a, b) => write!(f, "map size {:?} != {:?}", a, b),
+			Divergence::Tile(t, a, b) => write!(f, "tile {:?} {} != {}", t, a, b),
+			Divergence::Pickup(t) => write!(f, "pickup at {:?}", t),
+			Divergence::Unit(u, first) => write!(f, "unit {} only in the {} world", u, if *first { "first" } else { "second" }),
+			Divergence::UnitField(u, s, a, b) => write!(f, "unit {} {} {} != {}", u, s, a, b)
+		}
+	}
+}
+
+/// Returns every difference in the state of the battle between two worlds (that [state_hash] covers), in a fixed order; empty if their hashes agree.
+/// Values are compared by their exact bits, so that differences in rounding, e.g, from a damage function, are found.
+pub fn diff(a: &World, b: &World) -> Vec<Divergence> {
+	let mut out = Vec::new();
+	let ne = |x: f32, y: f32| x.to_bits() != y.to_bits();
+	if a.turn != b.turn {
+		out.push(Divergence::Turn(a.turn, b.turn));
+	}
+	let (sa, sb) = (&a.stats, &b.stats);
+	let stats = [
+		("turns", sa.turns as f32, sb.turns as f32),
+		("damage_dealt", sa.damage_dealt, sb.damage_dealt),
+		("damage_received", sa.damage_received, sb.damage_received),
+		("units_lost", sa.units_lost as f32, sb.units_lost as f32),
+		("enemies_defeated", sa.enemies_defeated as f32, sb.enemies_defeated as f32),
+		("xp_gained", sa.xp_gained as f32, sb.xp_gained as f32)
+	];
+	for (s, x, y) in stats.iter() {
+		if ne(*x, *y) {
+			out.push(Divergence::Stat(s, *x, *y));
+		}
+	}
+	for i in 0..a.objectives.len().max(b.objectives.len()) {
+		if a.objectives.get(i).map(|o| o.state) != b.objectives.get(i).map(|o| o.state) {
+			out.push(Divergence::Objective(i));
+		}
+	}
+	let (ma, mb) = (&a.tilemap, &b.tilemap);
+	if (ma.map_width, ma.map_height) != (mb.map_width, mb.map_height) || ma.map_tiles.len() != mb.map_tiles.len() {
+		out.push(Divergence::MapSize((ma.map_width, ma.map_height), (mb.map_width, mb.map_height)));
+	} else {
+		for (i, (x, y)) in ma.map_tiles.iter().zip(&mb.map_tiles).enumerate() {
+			if x != y {
+				let t = ((i % ma.map_width.max(1)) as i32, (i / ma.map_width.max(1)) as i32);
+				out.push(Divergence::Tile(t, *x, *y));
+			}
+		}
+	}
+	let mut tiles: Vec<(i32, i32)> = a.pickups.iter().chain(&b.pickups).map(|p| p.tile).collect();
+	tiles.sort_unstable();
+	tiles.dedup();
+	for t in tiles {
+		let raw = |w: &World| w.pickups.iter().find(|p| p.tile == t).map(|p| { let (k, v) = p.effect.to_raw(); (k, v.to_bits()) });
+		if raw(a) != raw(b) {
+			out.push(Divergence::Pickup(t));
+		}
+	}
+	let mut ids: Vec<u8> = a.units.keys().chain(b.units.keys()).cloned().collect();
+	ids.sort_unstable();
+	ids.dedup();
+	for id in ids {
+		let (ua, ub) = match (a.units.get(&id), b.units.get(&id)) {
+			(Some(ua), Some(ub)) => (ua, ub),
+			(ua, _) => {
+				out.push(Divergence::Unit(id, ua.is_some()));
+				continue;
+			}
+		};
+		let fields = [
+			("type_id", ua.type_id as f32, ub.type_id as f32),
+			("player", ua.player as u8 as f32, ub.player as u8 as f32),
+			("health", ua.health, ub.health),
+			("x", ua.wpos.x, ub.wpos.x),
+			("y", ua.wpos.y, ub.wpos.y),
+			("boost", ua.boost, ub.boost)
+		];
+		for (s, x, y) in fields.iter() {
+			if ne(*x, *y) {
+				out.push(Divergence::UnitField(id, s, *x, *y));
+			}
+		}
+	}
+	out
+}
+
+/// Set the standing behavior of the unit with specified id, or clear it with None. Returns false if the unit does not exist.
+pub fn set_behavior(w: &mut World, uid: u8, b: Option<Behavior>) -> bool {
+	match w.units.get_mut(&uid) {
+		Some(u) => {
+			u.behavior = b;
+			true
+		},
+		None => false
+	}
+}
+
+/// Append a waypoint to the patrol of the unit with specified id, starting a patrol if the unit has another behavior. Returns false if the unit does not exist.
+pub fn add_patrol_point(w: &mut World, uid: u8, tile: (i32, i32)) -> bool {
+	match w.units.get_mut(&uid) {
+		Some(u) => {
+			match &mut u.behavior {
+				Some(Behavior::Patrol { waypoints, .. }) => waypoints.push(tile),
+				b => *b = Some(Behavior::Patrol { waypoints: vec![tile], next: 0 })
+			}
+			true
+		},
+		None => false
+	}
+}
+
+/// Returns the steps of the shortest walk (within `movn` steps) from `start` to the tile nearest `goal` for which `ok` holds, or an empty walk if `start` is no farther.
+/// Tiles must allow movement, units block the way, and cliffs cannot be crossed, as in [reachable_tiles]. Every candidate tile is appended to `scores` with its distance to `goal`.
+fn walk_toward(w: &World, start: (i32, i32), movn: i32, goal: (i32, i32), ok: impl Fn((i32, i32)) -> bool, scores: &mut Vec<((i32, i32), i32)>) -> Vec<(i32, i32)> {
+	let (mw, mh) = w.map_size();
+	let in_bounds = |(x, y): (i32, i32)| !w.show_map() || ((x as usize) < mw && (y as usize) < mh);
+	let mut parent = HashMap::new();
+	let mut frontier = vec![start];
+	let mut best = (w.grid.distance(start, goal), start);
+	parent.insert(start, start);
+	for _ in 0..movn {
+		let mut next = Vec::new();
+		for f in frontier {
+			for t in w.grid.neighbours(f) {
+				if parent.contains_key(&t) || !tile_type_at(w, t.0, t.1).allowed() || !in_bounds(t) || unit_at(w, t.0, t.1).is_some() || !can_step(w, f, t) {
+					continue;
+				}
+				parent.insert(t, f);
+				if ok(t) {
+					let d = w.grid.distance(t, goal);
+					scores.push((t, d));
+					if d < best.0 {
+						best = (d, t);
+					}
+				}
+				next.push(t);
+			}
+		}
+		frontier = next;
+	}
+	let mut path = vec![];
+	let mut t = best.1;
+	while t != start {
+		path.push(t);
+		t = parent[&t];
+	}
+	path.reverse();
+	path
+}
+
+/// State of the built-in AI while it decides on the turn of one unit, see [behavior_orders].
+pub(crate) struct AiTurn<'a> {
+	/// Player units, as (id, tile, health).
+	pub(crate) players: &'a [(u8, (i32, i32), f32)],
+	/// Tiles other units end their moves on this turn.
+	claimed: &'a HashSet<(i32, i32)>,
+	pub(crate) start: (i32, i32),
+	pub(crate) movn: i32,
+	pub(crate) range: i32,
+	/// Layout of the tiles, for distances.
+	pub(crate) grid: Grid,
+	pub(crate) plan: AiPlan,
+	/// Influence map seen by enemy units, computed when first needed during the turn.
+	influence: &'a OnceCell<influence::InfluenceMap>
+}
+
+impl<'a> AiTurn<'a> {
+	fn new(w: &World, uid: u8, players: &'a [(u8, (i32, i32), f32)], claimed: &'a HashSet<(i32, i32)>, influence: &'a OnceCell<influence::InfluenceMap>) -> AiTurn<'a> {
+		let ut = &w.unit_types[&get_type_id(w, uid)];
+		AiTurn {
+			players: players,
+			claimed: claimed,
+			start: unit_tile(w, uid).unwrap(),
+			movn: ut.movement as i32,
+			range: ut.range as i32,
+			grid: w.grid,
+			plan: AiPlan { uid: uid, target: None, path: vec![], scores: vec![] },
+			influence: influence
+		}
+	}
+
+	/// Returns the influence map seen by enemy units, see [influence].
+	pub(crate) fn influence(&self, w: &World) -> &'a influence::InfluenceMap {
+		self.influence.get_or_init(|| influence::compute(w, false))
+	}
+
+	/// Returns true if no other unit ends its move on the tile.
+	pub(crate) fn is_free(&self, t: (i32, i32)) -> bool {
+		!self.claimed.contains(&t)
+	}
+
+	/// Returns the tile the unit ends its move on.
+	pub(crate) fn end(&self) -> (i32, i32) {
+		self.plan.path.last().cloned().unwrap_or(self.start)
+	}
+
+	/// Walk toward `goal`, ending on a tile for which `ok` holds and no other unit ends on, see [walk_toward].
+	pub(crate) fn walk(&mut self, w: &World, goal: (i32, i32), ok: impl Fn((i32, i32)) -> bool) {
+		let claimed = self.claimed;
+		self.plan.path = walk_toward(w, self.start, self.movn, goal, |t| !claimed.contains(&t) && ok(t), &mut self.plan.scores);
+	}
+
+	/// Returns the id of the weakest player unit within range of the tile the unit ends on, see [range_bonus].
+	pub(crate) fn weakest_in_range(&self, w: &World) -> Option<u8> {
+		let end = self.end();
+		self.players.iter().filter(|p| self.grid.distance(p.1, end) <= self.range + range_bonus(w, end, p.1)).min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal)).map(|p| p.0)
+	}
+
+	/// Returns the player unit nearest to the unit, if any within `dist` tiles.
+	pub(crate) fn nearest_player(&self, dist: i32) -> Option<(u8, (i32, i32), f32)> {
+		self.players.iter().filter(|p| self.grid.distance(p.1, self.start) <= dist).min_by_key(|p| self.grid.distance(p.1, self.start)).cloned()
+	}
+}
+
+/// Plan the walk of a unit carrying out its standing [Behavior]. Returns false if it has none.
+pub(crate) fn standing_walk(w: &mut World, t: &mut AiTurn) -> bool {
+	let aggr = w.difficulty.aggression;
+	let uid = t.plan.uid;
+	// Player unit an alert patrol breaks off to chase, see [Difficulty::aggression].
+	let chase = match ((aggr - 1.0) * t.movn as f32).round() as i32 {
+		extra if extra > 0 => t.nearest_player(t.range + extra),
+		_ => None
+	};
+	match w.units[&uid].behavior.clone() {
+		Some(Behavior::Hold) => {},
+		Some(Behavior::Guard { tile, radius }) => {
+			let radius = (radius as f32 * aggr).round() as i32;
+			let near = |p: (i32, i32)| w.grid.distance(p, tile) <= radius;
+			match t.players.iter().filter(|p| near(p.1)).min_by_key(|p| t.grid.distance(p.1, t.start)) {
+				Some(p) => {
+					t.walk(w, p.1, near);
+					if t.plan.path.is_empty() && !near(t.start) {
+						t.walk(w, tile, |_| true);
+					}
+				},
+				None => t.walk(w, tile, |_| true)
+			}
+		},
+		Some(Behavior::Patrol { .. }) if chase.is_some() => t.walk(w, chase.unwrap().1, |_| true),
+		Some(Behavior::Patrol { waypoints, mut next }) if !waypoints.is_empty() => {
+			next %= waypoints.len();
+			if t.start == waypoints[next] {
+				next = (next + 1) % waypoints.len();
+			}
+			t.walk(w, waypoints[next], |_| true);
+			if t.plan.path.last() == Some(&waypoints[next]) {
+				next = (next + 1) % waypoints.len();
+			}
+			if let Some(Behavior::Patrol { next: n, .. }) = &mut w.units.get_mut(&uid).unwrap().behavior {
+				*n = next;
+			}
+		},
+		_ => return false
+	}
+	true
+}
+
+/// Plan the walk of a unit left to the [AiController]: stay put if a player unit is in range, or else advance on the nearest player unit in sight.
+fn controller_walk(w: &World, t: &mut AiTurn, c: AiController) {
+	let start = t.start;
+	if t.players.iter().any(|p| t.grid.distance(p.1, start) <= t.range + range_bonus(w, start, p.1)) {
+		return;
+	}
+	let sight = c.aggressiveness * w.difficulty.aggression * (t.movn + t.range) as f32;
+	let sight = if sight >= i32::MAX as f32 { i32::MAX } else { sight.max(0.0) as i32 };
+	if let Some(p) = t.nearest_player(sight) {
+		t.walk(w, p.1, |_| true);
+	}
+}
+
+/// Returns the behavior tree controlling the unit with specified id: the one assigned to its type by the map (see [World::load_ai]), or else by its [UnitType].
+fn behavior_tree(w: &World, uid: u8) -> Option<BehaviorTree> {
+	let tid = w.units.get(&uid)?.type_id;
+	w.ai_trees.get(&tid).or_else(|| w.unit_types.get(&tid)?.ai.as_ref()).cloned()
+}
+
+/// Decisions of the built-in AI for one enemy turn, made one unit at a time so they can be spread across frames.
+/// See [Display::set_ai_budget](crate::display::Display::set_ai_budget).
+#[derive(Debug)]
+pub(crate) struct AiJob {
+	/// Units yet to be decided for, in reverse order.
+	ids: Vec<u8>,
+	players: Vec<(u8, (i32, i32), f32)>,
+	claimed: HashSet<(i32, i32)>,
+	influence: OnceCell<influence::InfluenceMap>
+}
+
+impl AiJob {
+	/// Begin the decisions for enemy units with a [Behavior] or a [BehaviorTree] (or any enemy unit, with the [AiController] enabled), which have not been given orders in `ovec` already.
+	pub(crate) fn new(w: &mut World, ovec: &[Order]) -> AiJob {
+		w.ai_plans.clear();
+		let busy: HashSet<u8> = ovec.iter().filter_map(|o| match o {
+			Order::MOVE(u, ..) | Order::ATTACK(u, ..) => Some(*u),
+			_ => None
+		}).collect();
+		let all = w.ai_controller.is_some();
+		let mut ids: Vec<u8> = w.units.iter().filter(|(id, u)| !u.player && !busy.contains(id) && (all || u.behavior.is_some() || behavior_tree(w, **id).is_some())).map(|(id, _)| *id).collect();
+		ids.sort_by(|a, b| b.cmp(a));
+		AiJob {
+			ids: ids,
+			players: w.units.iter().filter(|(_, u)| u.player).map(|(id, u)| (*id, (u.wpos.x as i32, u.wpos.y as i32), u.health)).collect(),
+			claimed: HashSet::new(),
+			influence: OnceCell::new()
+		}
+	}
+
+	/// Decide for the next unit, issuing its orders and recording its [AiPlan]. Returns false if there were no units left.
+	pub(crate) fn step(&mut self, w: &mut World, ovec: &mut Vec<Order>) -> bool {
+		let uid = match self.ids.pop() {
+			Some(u) => u,
+			None => return false
+		};
+		if !w.units.contains_key(&uid) {
+			return true;
+		}
+		let mut t = AiTurn::new(w, uid, &self.players, &self.claimed, &self.influence);
+		match behavior_tree(w, uid) {
+			Some(tree) => {
+				tree.run(w, &mut t);
+			},
+			None => {
+				if !standing_walk(w, &mut t) {
+					if let Some(c) = w.ai_controller {
+						controller_walk(w, &mut t, c);
+					}
+				}
+				t.plan.target = t.weakest_in_range(w);
+			}
+		}
+		let end = t.end();
+		let plan = t.plan;
+		self.claimed.insert(end);
+		ovec.extend(plan.path.iter().map(|&(tx, ty)| Order::MOVE(uid, tx, ty)));
+		if let Some(p) = plan.target {
+			ovec.push(Order::ATTACK(uid, p, end.0, end.1));
+		}
+		w.ai_plans.push(plan);
+		true
+	}
+}
+
+/// Issue orders to enemy units with a [Behavior] or a [BehaviorTree] (or any enemy unit, with the [AiController] enabled), which have not been given orders already, recording them as [AiPlan]s.
+pub(crate) fn behavior_orders(w: &mut World, ovec: &mut Vec<Order>) {
+	let mut job = AiJob::new(w, ovec);
+	while job.step(w, ovec) {}
+}
+
+/// Returns the total and maximum health of the units of `group`, and a summary of the group for the unit panel.
+pub(crate) fn _group_info(w: &World, group: &[u8]) -> (f32, f32, String) {
+	let (mut h, mut mh) = (0.0, 0.0);
+	let mut counts: Vec<(&str, u32)> = Vec::new();
+	let mut movn = u8::MAX;
+	for u in group.iter().filter_map(|id| w.units.get(id)) {
+		let ut = w.unit_types.get(&u.type_id).expect("Invalid unit type ID");
+		h += u.health;
+		mh += ut.max_health;
+		movn = movn.min(_movement(w, u));
+		match counts.iter_mut().find(|(n, _)| *n == ut.name) {
+			Some((_, c)) => *c += 1,
+			None => counts.push((&ut.name, 1))
+		}
+	}
+	counts.sort();
+	let types = counts.iter().map(|(n, c)| format!("{} x{}", n, c)).collect::<Vec<_>>().join("\n");
+	(h, mh, format!("Group of {}\n\n{}\nMov:{}", group.len(), types, if movn == u8::MAX { 0 } else { movn }))
+}
+
 /// Get the id of the unit type of a given unit. 
 pub fn get_type_id(w: &World, uid: u8) -> u8 {
 	w.units.get(&uid).expect("No such unit").type_id
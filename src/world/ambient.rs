@@ -0,0 +1,91 @@
+//! Looping ambient sounds of areas of the map, e.g, a river or a marketplace, heard louder the closer the camera is to them.
+//! Each [AmbientRegion] loops a music track, at its volume (times the [volume of sound effects](super::mixer::Mixer::sfx_volume)) while the centre of the screen is on the area,
+//! fading out to silence `falloff` tiles away from it. Regions sharing a track play it as loud as the loudest of them.
+//!
+//! Regions are part of the map: they are read from `.alw` files (see [load_world](super::load_world)) and the `ambient` field of [RON](super::ron) maps, and replaced when another map is loaded.
+//! Tracks of ambient regions are started and stopped by the engine, and are not affected by the [music volume](super::World::set_music_volume); they should not be used as background music.
+
+use super::AudioCmd;
+use super::World;
+
+/// An area of the map with a looping ambient sound.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientRegion {
+	/// Id of the music track looped.
+	pub track: u8,
+	/// A corner of the area, a rectangle of tiles.
+	pub from: (i32, i32),
+	/// The corner of the area opposite `from`, also in the area.
+	pub to: (i32, i32),
+	/// Distance (in tiles) from the area at which the sound fades out.
+	pub falloff: f32,
+	/// Volume on the area, in the range [0, 1].
+	pub volume: f32
+}
+
+impl AmbientRegion {
+	/// Constructor method. The volume is clamped to [0, 1], and negative or non-finite fall-offs are replaced by 0, i.e, the sound is only heard on the area.
+	pub fn new(track: u8, from: (i32, i32), to: (i32, i32), falloff: f32, volume: f32) -> AmbientRegion {
+		AmbientRegion {
+			track: track,
+			from: from,
+			to: to,
+			falloff: if falloff.is_finite() { falloff.max(0.0) } else { 0.0 },
+			volume: if volume.is_nan() { 0.0 } else { volume.clamp(0.0, 1.0) }
+		}
+	}
+
+	/// Returns true if the tile (x, y) is in the area.
+	pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+		let (x0, x1) = (self.from.0.min(self.to.0), self.from.0.max(self.to.0));
+		let (y0, y1) = (self.from.1.min(self.to.1), self.from.1.max(self.to.1));
+		x >= x0 && x <= x1 && y >= y0 && y <= y1
+	}
+
+	/// Returns the volume of the sound heard from the tile (x, y), before the volume of sound effects.
+	pub fn volume_at(&self, (x, y): (i32, i32)) -> f32 {
+		let gap = |p: i32, a: i32, b: i32| (a.min(b) - p).max(p - a.max(b)).max(0) as f32;
+		let (dx, dy) = (gap(x, self.from.0, self.to.0), gap(y, self.from.1, self.to.1));
+		let d = (dx * dx + dy * dy).sqrt();
+		if d == 0.0 {
+			self.volume
+		} else if d >= self.falloff {
+			0.0
+		} else {
+			self.volume * (1.0 - d / self.falloff)
+		}
+	}
+}
+
+/// Least change of the volume of an ambient track that is carried out, so that moving the camera does not adjust it every frame.
+const VOLUME_STEP: f32 = 0.01;
+
+/// Adjust the volumes of the tracks of ambient regions to the tile `listener` at the centre of the screen, starting the tracks that became audible and stopping those that fell silent.
+pub(crate) fn update(w: &mut World, listener: (i32, i32)) {
+	let sfx = w.mixer.sfx_volume();
+	let mut heard: Vec<(u8, f32)> = vec![];
+	for r in &w.tilemap.ambient {
+		let v = r.volume_at(listener) * sfx;
+		match heard.iter_mut().find(|(t, _)| *t == r.track) {
+			Some(h) => h.1 = h.1.max(v),
+			None => heard.push((r.track, v))
+		}
+	}
+	let playing: Vec<u8> = w.mixer.ambient.keys().cloned().collect();
+	for t in playing {
+		if !heard.iter().any(|(h, _)| *h == t) {
+			heard.push((t, 0.0));
+		}
+	}
+	for (t, v) in heard {
+		let old = w.mixer.ambient.get(&t).cloned().unwrap_or(0.0);
+		if (v > 0.0) != (old > 0.0) || (v - old).abs() >= VOLUME_STEP {
+			if v > 0.0 {
+				w.mixer.ambient.insert(t, v);
+			} else {
+				w.mixer.ambient.remove(&t);
+			}
+			w.audio.push(AudioCmd::AmbientVolume(t, v));
+		}
+	}
+}
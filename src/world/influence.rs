@@ -0,0 +1,92 @@
+//! Influence maps: the threat posed to, and the support given to, the units of one side on every tile.
+//! Shared by the built-in AI (see [crate::ai]), scripting, and the danger-zone overlay.
+//!
+//! A unit influences every tile it could attack next turn, i.e, those within its range of a tile it can reach (see [reachable_tiles](super::reachable_tiles)), farther from higher ground (see [range_bonus](super::range_bonus)).
+//! Its influence is its strength: its attack strength (or 1, if it has none) scaled by the fraction of its health left.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use super::World;
+use super::range_bonus;
+use super::reachable_tiles;
+use super::unit_tile;
+
+/// Threat and support on the tiles of the map, as seen by one side. Tiles without influence are not stored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InfluenceMap {
+	threat: HashMap<(i32, i32), f32>,
+	support: HashMap<(i32, i32), f32>
+}
+
+impl InfluenceMap {
+	/// Returns the total strength of the opposing units that could attack the tile next turn.
+	pub fn threat(&self, tile: (i32, i32)) -> f32 {
+		self.threat.get(&tile).cloned().unwrap_or(0.0)
+	}
+
+	/// Returns the total strength of the friendly units that could attack the tile next turn.
+	pub fn support(&self, tile: (i32, i32)) -> f32 {
+		self.support.get(&tile).cloned().unwrap_or(0.0)
+	}
+
+	/// Returns the threatened tiles with their threat, sorted by tile.
+	pub fn threatened(&self) -> Vec<((i32, i32), f32)> {
+		let mut v: Vec<((i32, i32), f32)> = self.threat.iter().map(|(t, v)| (*t, *v)).collect();
+		v.sort_by_key(|e| e.0);
+		v
+	}
+
+	/// Returns the supported tiles, sorted.
+	pub fn supported(&self) -> Vec<(i32, i32)> {
+		let mut v: Vec<(i32, i32)> = self.support.keys().cloned().collect();
+		v.sort();
+		v
+	}
+
+	/// Returns the highest threat on any tile, or 0 if there is none.
+	pub fn max_threat(&self) -> f32 {
+		self.threat.values().cloned().fold(0.0, f32::max)
+	}
+}
+
+/// Returns the strength of the unit with specified id, see the [module documentation](self).
+pub fn strength(w: &World, uid: u8) -> f32 {
+	let u = match w.units.get(&uid) { Some(u) => u, None => return 0.0 };
+	let ut = match w.unit_types.get(&u.type_id) { Some(ut) => ut, None => return 0.0 };
+	let atk = if ut.attack > 0.0 { ut.attack } else { 1.0 };
+	if ut.max_health > 0.0 { atk * (u.health / ut.max_health).clamp(0.0, 1.0) } else { atk }
+}
+
+/// Returns the tiles the unit with specified id could attack next turn.
+pub fn reach(w: &World, uid: u8) -> HashSet<(i32, i32)> {
+	let mut out = HashSet::new();
+	let start = match unit_tile(w, uid) { Some(t) => t, None => return out };
+	let range = w.unit_types.get(&w.units[&uid].type_id).map(|ut| ut.range).unwrap_or(0) as i32;
+	let (mw, mh) = w.map_size();
+	let in_bounds = |(x, y): (i32, i32)| x >= 0 && y >= 0 && (!w.show_map() || ((x as usize) < mw && (y as usize) < mh));
+	for (x, y) in reachable_tiles(w, uid).into_iter().chain(std::iter::once(start)) {
+		let far = range + w.elevation.range_bonus as i32;
+		for dx in -far..=far {
+			for dy in -far..=far {
+				let t = (x + dx, y + dy);
+				if w.grid().distance(t, (x, y)) <= range + range_bonus(w, (x, y), t) && in_bounds(t) {
+					out.insert(t);
+				}
+			}
+		}
+	}
+	out
+}
+
+/// Compute the influence map seen by player units if `player` is true, or by enemy units otherwise.
+pub fn compute(w: &World, player: bool) -> InfluenceMap {
+	let mut m = InfluenceMap::default();
+	for (&uid, u) in &w.units {
+		let s = strength(w, uid);
+		let layer = if u.player == player { &mut m.support } else { &mut m.threat };
+		for t in reach(w, uid) {
+			*layer.entry(t).or_insert(0.0) += s;
+		}
+	}
+	m
+}
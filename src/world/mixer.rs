@@ -0,0 +1,149 @@
+//! Audio mixer of a world: the master volume and the volumes of music and sound effects, muting, and musical stingers, short pieces of music marking an event, e.g, a unit lost.
+//! While a stinger plays, the background music is ducked, i.e, faded down to a [fraction](Mixer::duck_level) of its volume, and faded back up once the stinger ends.
+//!
+//! Stingers are sounds, mapped on the [ResourceSet](crate::utils::ResourceSet) like any other; see [World::set_stinger](super::World::set_stinger).
+//! The volumes are part of the [Settings](crate::utils::Settings), see [World::apply_audio_settings](super::World::apply_audio_settings) and [Settings::update_audio](crate::utils::Settings::update_audio).
+//!
+//! The engine plays the stinger of a unit lost, when a player unit dies, and that of an objective complete, when one is [marked completed](super::World::set_objective_state); a level-up is left to the game.
+
+use std::collections::HashMap;
+use super::AudioCmd;
+use super::World;
+
+/// Default fraction of its volume the background music is ducked to while a stinger plays.
+pub const DUCK_LEVEL: f32 = 0.25;
+/// Seconds the background music takes to fade down, or back up, when ducked.
+pub const DUCK_FADE: f32 = 0.25;
+
+/// Event marked by a stinger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StingerKind {
+	/// A unit gained a level.
+	LevelUp = 0,
+	/// A player unit died.
+	UnitLost = 1,
+	/// An objective was completed.
+	ObjectiveComplete = 2
+}
+
+impl StingerKind {
+	/// Convert a raw kind, as used by the native API. Returns None if out of range.
+	pub fn from_u8(k: u8) -> Option<StingerKind> {
+		match k {
+			0 => Some(StingerKind::LevelUp),
+			1 => Some(StingerKind::UnitLost),
+			2 => Some(StingerKind::ObjectiveComplete),
+			_ => None
+		}
+	}
+}
+
+/// Volumes of a world, and the state of the ducking of its music.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mixer {
+	pub(crate) master_volume: f32,
+	/// Flag set while all audio is muted, and the option to mute it while the window is not focused.
+	pub(crate) muted: bool,
+	pub(crate) mute_unfocused: bool,
+	pub(crate) music_volume: f32,
+	pub(crate) sfx_volume: f32,
+	/// Sound id and length (in seconds) of the stinger of each kind, if any.
+	pub(crate) stingers: [Option<(u8, f32)>; 3],
+	pub(crate) duck_level: f32,
+	/// Factor the music volume is ducked by, and seconds left of the stingers playing.
+	pub(crate) duck: f32,
+	pub(crate) hold: f32,
+	/// Volume of each track of [ambient regions](super::ambient) playing.
+	pub(crate) ambient: HashMap<u8, f32>
+}
+
+impl Default for Mixer {
+	fn default() -> Mixer {
+		Mixer {
+			master_volume: 1.0,
+			muted: false,
+			mute_unfocused: false,
+			music_volume: 1.0,
+			sfx_volume: 1.0,
+			stingers: [None; 3],
+			duck_level: DUCK_LEVEL,
+			duck: 1.0,
+			hold: 0.0,
+			ambient: HashMap::new()
+		}
+	}
+}
+
+impl Mixer {
+	/// Returns the master volume, in the range [0, 1], muting aside.
+	pub fn master_volume(&self) -> f32 {
+		self.master_volume
+	}
+
+	/// Returns the master volume audio is played at: 0 while muted.
+	pub fn effective_master_volume(&self) -> f32 {
+		if self.muted { 0.0 } else { self.master_volume }
+	}
+
+	/// Returns true while all audio is muted, see [World::set_muted].
+	pub fn is_muted(&self) -> bool {
+		self.muted
+	}
+
+	/// Returns true if all audio is muted while the window is not focused, see [World::set_mute_unfocused].
+	pub fn mute_unfocused(&self) -> bool {
+		self.mute_unfocused
+	}
+
+	/// Returns the volume of background music set, in the range [0, 1], ducking aside.
+	pub fn music_volume(&self) -> f32 {
+		self.music_volume
+	}
+
+	/// Returns the volume of sound effects, in the range [0, 1].
+	pub fn sfx_volume(&self) -> f32 {
+		self.sfx_volume
+	}
+
+	/// Returns the sound id and length (in seconds) of the stinger of `kind`, if any.
+	pub fn stinger(&self, kind: StingerKind) -> Option<(u8, f32)> {
+		self.stingers[kind as usize]
+	}
+
+	/// Returns the fraction of its volume the background music is ducked to while a stinger plays. Default is [DUCK_LEVEL].
+	pub fn duck_level(&self) -> f32 {
+		self.duck_level
+	}
+
+	/// Returns the volume the background music is played at, i.e, the volume set as ducked now.
+	pub fn effective_music_volume(&self) -> f32 {
+		self.music_volume * self.duck
+	}
+
+	/// Returns the volume the track of [ambient regions](super::ambient) with specified id is played at, 0 if it is not playing.
+	pub fn ambient_volume(&self, track: u8) -> f32 {
+		self.ambient.get(&track).cloned().unwrap_or(0.0)
+	}
+
+	/// Returns true while a stinger plays, or the music has yet to fade back up.
+	pub fn is_ducking(&self) -> bool {
+		self.hold > 0.0 || self.duck < 1.0
+	}
+}
+
+/// Advance the stingers playing by `dt` seconds, fading the background music down or back up.
+pub(crate) fn tick(w: &mut World, dt: f32) {
+	let m = &mut w.mixer;
+	if !m.is_ducking() {
+		return;
+	}
+	let target = if m.hold > 0.0 { m.duck_level } else { 1.0 };
+	m.hold = (m.hold - dt).max(0.0);
+	let step = dt / DUCK_FADE;
+	let duck = if m.duck > target { (m.duck - step).max(target) } else { (m.duck + step).min(target) };
+	if duck != m.duck {
+		m.duck = duck;
+		let v = m.effective_music_volume();
+		w.audio.push(AudioCmd::MusicVolume(v));
+	}
+}
@@ -0,0 +1,85 @@
+//! A* pathfinding: routes across the map between two tiles, for the built-in AI, scripts and hosts.
+//! Units walk one tile per step, to a [neighbouring](super::Grid::neighbours) tile. Tiles on the route must allow movement, units block the way, and cliffs cannot be crossed, as in [reachable_tiles](super::reachable_tiles);
+//! a unit standing on the start tile (i.e, the unit walking the route) does not.
+//! # Example
+//! ```
+//! use alesia::world::{self, World, path};
+//! let mut w = World::blank();
+//! let ut = world::UnitType::new(0, "Archer".to_string(), 10.0, 2.0, 2, 3, 0.5);
+//! world::register_unit_type(&mut w, ut, 0);
+//! world::spawn_unit(&mut w, 0, (1, 0), -1, true);
+//! let route = path::find(&w, (0, 0), (2, 0)).unwrap();
+//! assert_eq!(route.len(), 4);
+//! assert_eq!(route.last(), Some(&(2, 0)));
+//! assert!(!route.contains(&(1, 0)));
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use super::World;
+use super::can_step;
+use super::tile_type_at;
+use super::unit_at;
+
+/// Most tiles explored by a search before it gives up, so that a goal walled off from the start does not search an unbounded map (i.e, one not loaded) forever.
+pub const MAX_EXPANDED: usize = 1 << 16;
+
+/// Returns the shortest route from `start` to `goal`, as the tiles stepped onto in order, ending with `goal`; empty if `start` is `goal`.
+/// Returns None if there is no route, i.e, the goal is blocked (by its tile or a unit), or walled off from the start.
+pub fn find(w: &World, start: (i32, i32), goal: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+	find_within(w, start, goal, i32::MAX, |_| false)
+}
+
+/// Returns the shortest route from `start` to `goal` as [find] does, if it takes at most `max` steps; units for which `passable` returns true do not block the way.
+pub(crate) fn find_within(w: &World, start: (i32, i32), goal: (i32, i32), max: i32, passable: impl Fn(u8) -> bool) -> Option<Vec<(i32, i32)>> {
+	if start == goal {
+		return Some(Vec::new());
+	}
+	let (mw, mh) = w.map_size();
+	let open = |(x, y): (i32, i32)| {
+		tile_type_at(w, x, y).allowed() && (!w.show_map() || ((x as usize) < mw && (y as usize) < mh))
+			&& unit_at(w, x, y).filter(|u| !passable(*u)).is_none()
+	};
+	if !open(goal) {
+		return None;
+	}
+	let mut from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+	let mut cost: HashMap<(i32, i32), i32> = HashMap::new();
+	let mut heap = BinaryHeap::new();
+	cost.insert(start, 0);
+	heap.push(Reverse((w.grid().distance(start, goal), 0, start)));
+	let mut expanded = 0;
+	while let Some(Reverse((_, g, t))) = heap.pop() {
+		if t == goal {
+			let mut route = vec![t];
+			let mut t = t;
+			while let Some(p) = from.get(&t) {
+				if *p == start {
+					break;
+				}
+				route.push(*p);
+				t = *p;
+			}
+			route.reverse();
+			return Some(route);
+		}
+		// Skip entries superseded by a cheaper route to the tile.
+		if g > cost[&t] {
+			continue;
+		}
+		expanded += 1;
+		if expanded > MAX_EXPANDED {
+			return None;
+		}
+		for n in w.grid().neighbours(t) {
+			if g + 1 > max || !open(n) || !can_step(w, t, n) || matches!(cost.get(&n), Some(c) if *c <= g + 1) {
+				continue;
+			}
+			cost.insert(n, g + 1);
+			from.insert(n, t);
+			heap.push(Reverse((g + 1 + w.grid().distance(n, goal), g + 1, n)));
+		}
+	}
+	None
+}
@@ -0,0 +1,98 @@
+//! Loot lying on tiles, collected by the first unit to end its move on the tile.
+//! Pickups are placed by the map (see [load_world](super::load_world), game object types 6 to 8) or at runtime with [place], and collection is reported as a [BattleEvent::Pickup].
+
+use super::World;
+use super::PopupKind;
+use super::BattleEvent;
+use super::unit_tile;
+
+/// Texture ids of pickups placed by the map, by effect: heal (`0xe0`), boost (`0xe1`) and gold (`0xe2`).
+pub const MAP_TEXTURES: [u8; 3] = [0xe0, 0xe1, 0xe2];
+
+/// Effect of a [Pickup] on the unit collecting it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PickupEffect {
+	/// Restore a fraction of the maximum health of the unit.
+	Heal(f32),
+	/// Raise the attack strength of the unit by a fraction, e.g, 0.25 for +25%, for the rest of the battle. See [Unit::boost](super::Unit::boost).
+	Boost(f32),
+	/// Add gold to the counter "gold" of the [Profile](crate::utils::Profile), if collected by a player unit.
+	Gold(u32)
+}
+
+impl PickupEffect {
+	/// Convert a raw kind (as used by the native API) and value: 0 - heal, 1 - boost, 2 - gold. Returns None if out of range, or the value is not finite and non-negative.
+	pub fn from_raw(kind: u8, value: f32) -> Option<PickupEffect> {
+		if !value.is_finite() || value < 0.0 {
+			return None;
+		}
+		match kind {
+			0 => Some(PickupEffect::Heal(value)),
+			1 => Some(PickupEffect::Boost(value)),
+			2 => Some(PickupEffect::Gold(value as u32)),
+			_ => None
+		}
+	}
+
+	/// Inverse of [from_raw](PickupEffect::from_raw).
+	pub fn to_raw(self) -> (u8, f32) {
+		match self {
+			PickupEffect::Heal(v) => (0, v),
+			PickupEffect::Boost(v) => (1, v),
+			PickupEffect::Gold(v) => (2, v as f32)
+		}
+	}
+}
+
+/// An item lying on a tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pickup {
+	/// The id of the texture drawn on the tile.
+	pub tex_id: u8,
+	/// The tile the pickup lies on.
+	pub tile: (i32, i32),
+	/// What the pickup does to the unit collecting it.
+	pub effect: PickupEffect
+}
+
+/// Place a pickup on a tile, replacing any pickup already there.
+pub fn place(w: &mut World, tex_id: u8, tile: (i32, i32), effect: PickupEffect) {
+	remove(w, tile);
+	w.pickups.push(Pickup { tex_id: tex_id, tile: tile, effect: effect });
+}
+
+/// Remove the pickup on a tile without applying it, returning it if there was one.
+pub fn remove(w: &mut World, tile: (i32, i32)) -> Option<Pickup> {
+	let i = w.pickups.iter().position(|p| p.tile == tile)?;
+	Some(w.pickups.remove(i))
+}
+
+/// Returns the pickup on a tile, if any.
+pub fn at(w: &World, tile: (i32, i32)) -> Option<&Pickup> {
+	w.pickups.iter().find(|p| p.tile == tile)
+}
+
+/// Let the unit with specified id collect the pickup on its tile, if any, applying its effect and recording the event.
+pub(crate) fn collect(w: &mut World, uid: u8) -> Option<PickupEffect> {
+	let p = remove(w, unit_tile(w, uid)?)?;
+	let u = w.units.get_mut(&uid)?;
+	match p.effect {
+		PickupEffect::Heal(f) => {
+			let max = w.unit_types.get(&u.type_id).map_or(u.health, |ut| ut.max_health);
+			let h = u.health;
+			u.health = (u.health + f * max).min(max.max(u.health));
+			let delta = u.health - h;
+			w.health_popup(uid, delta);
+		},
+		PickupEffect::Boost(f) => {
+			u.boost += f;
+			w.show_popup(uid, &format!("+{:.0}% ATK", f * 100.0), PopupKind::Status);
+		},
+		PickupEffect::Gold(n) => if u.player {
+			w.profile.incr("gold", n as i64);
+			w.show_popup(uid, &format!("+{} gold", n), PopupKind::Status);
+		}
+	}
+	w.events.push(BattleEvent::Pickup(uid, p.effect));
+	Some(p.effect)
+}
@@ -0,0 +1,582 @@
+//! Maps in a human-readable text format, a subset of [RON](https://github.com/ron-rs/ron), so that maps can be edited by hand and diffed in version control.
+//! A map file holds everything a binary `.alw` file does (see [load_world](super::load_world)), read with [load_world_ron](super::load_world_ron) and written with [save_world_ron](super::save_world_ron).
+//!
+//! The file is a single struct `Map(...)` (the name may be left out) of the following fields, in any order; comments (`// ...` and `/* ... */`) and trailing commas are allowed.
+//!
+//! | Field | Value | Default |
+//! | ----- | ----- | ------- |
+//! | `width`, `height` | size of the map, in tiles | required |
+//! | `tiles` | list of `width * height` tiles, row by row | required |
+//! | `prohibited`, `heal`, `damage` | lists of tiles with the respective [TileType](super::TileType) | `[]` |
+//! | `title`, `intro`, `victory`, `defeat` | texts of the map | `""` |
+//! | `statics` | list of `(tex: id, tile: (x, y))` | `[]` |
+//! | `units` | list of `(type_id: id, tile: (x, y), player: bool, behavior: b)`; `behavior` is optional, one of `Hold`, `Guard(tile: (x, y), radius: r)` and `Patrol([(x, y), ...])` | `[]` |
+//! | `pickups` | list of `(tile: (x, y), effect: e)`, with `e` one of `Heal(fraction)`, `Boost(fraction)` and `Gold(amount)`, drawn with [MAP_TEXTURES](super::pickup::MAP_TEXTURES) | `[]` |
+//! | `slides` | list of `(tex: id, duration: seconds, caption: "...")`, see [World::set_intro_slides](super::World::set_intro_slides) | `[]` |
+//! | `layers` | list of `(kind: k, tiles: [...])`, with `k` one of `Ground`, `Decoration` and `Overlay`, see [World::add_tile_layer](super::World::add_tile_layer) | `[]` |
+//! | `heights` | list of `width * height` elevations, row by row, see [World::set_elevation](super::World::set_elevation) | `[]`, i.e, flat |
+//! | `ambient` | list of `(track: id, from: (x, y), to: (x, y), falloff: tiles, volume: v)`; `volume` is optional, 1 by default, see [ambient](super::ambient) | `[]` |
+//!
+//! Unlike `.alw` files, maps may be larger than 255 tiles, tiles may be elevated, and pickup values and fall-offs of ambient regions are kept exactly.
+//! # Example
+//! ```
+//! use alesia::world::{self, World, UnitType};
+//! let text = r#"
+//! // A ford held by an archer.
+//! Map(
+//!     width: 2, height: 2,
+//!     tiles: [0, 1, 1, 0],
+//!     prohibited: [1],
+//!     title: "Ford",
+//!     units: [
+//!         (type_id: 0, tile: (0, 0), player: false, behavior: Hold),
+//!     ],
+//! )
+//! "#;
+//! let mut w = World::blank();
+//! world::register_unit_type(&mut w, UnitType::new(0, "Archer".to_string(), 10.0, 0.5, 2, 3, 1.5), 0);
+//! world::ron::parse(&mut w, text).unwrap();
+//! assert_eq!(w.map_title(), "Ford");
+//! assert!(!world::tile_type_at(&w, 1, 0).allowed());
+//! assert!(world::ron::to_string(&w).contains("behavior: Hold"));
+//! ```
+
+use std::collections::HashMap;
+use super::Behavior;
+use super::ambient::AmbientRegion;
+use super::LayerKind;
+use super::Slide;
+use super::TileLayer;
+use super::TileMap;
+use super::TileType;
+use super::World;
+use super::pickup;
+use super::pickup::PickupEffect;
+
+/// A value of the RON subset read.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+	Bool(bool),
+	/// A number, as written.
+	Num(String),
+	Str(String),
+	/// A unit variant, e.g, `Hold`.
+	Ident(String),
+	List(Vec<Value>),
+	/// A tuple, or tuple variant (with its name), e.g, `(1, 2)` or `Heal(0.5)`.
+	Tuple(Option<String>, Vec<Value>),
+	/// A struct, with its name if given, e.g, `Map(width: 2)`.
+	Struct(Option<String>, Vec<(String, Value)>)
+}
+
+struct Parser<'a> {
+	text: &'a str,
+	i: usize
+}
+
+impl<'a> Parser<'a> {
+	fn line(&self) -> usize {
+		self.text[..self.i].matches('\n').count() + 1
+	}
+
+	fn err<T>(&self, what: &str) -> Result<T, String> {
+		Err(format!("line {}: {}", self.line(), what))
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.text[self.i..].chars().next()
+	}
+
+	/// Skip whitespace and comments.
+	fn skip(&mut self) -> Result<(), String> {
+		loop {
+			let rest = &self.text[self.i..];
+			let trimmed = rest.trim_start();
+			self.i += rest.len() - trimmed.len();
+			if trimmed.starts_with("//") {
+				self.i += trimmed.find('\n').unwrap_or(trimmed.len());
+			} else if trimmed.starts_with("/*") {
+				match trimmed.find("*/") {
+					Some(n) => self.i += n + 2,
+					None => return self.err("unterminated comment")
+				}
+			} else {
+				return Ok(());
+			}
+		}
+	}
+
+	/// Consume the character `c`, after whitespace and comments, if it is next. Returns true if it was.
+	fn eat(&mut self, c: char) -> Result<bool, String> {
+		self.skip()?;
+		if self.peek() == Some(c) {
+			self.i += c.len_utf8();
+			return Ok(true);
+		}
+		Ok(false)
+	}
+
+	fn expect(&mut self, c: char) -> Result<(), String> {
+		if !self.eat(c)? {
+			return self.err(&format!("expected '{}'", c));
+		}
+		Ok(())
+	}
+
+	/// Consume a run of characters matching `f`.
+	fn take(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+		let rest = &self.text[self.i..];
+		let n = rest.find(|c: char| !f(c)).unwrap_or(rest.len());
+		self.i += n;
+		&rest[..n]
+	}
+
+	fn value(&mut self) -> Result<Value, String> {
+		self.skip()?;
+		match self.peek() {
+			Some('"') => self.string().map(Value::Str),
+			Some('[') => {
+				self.i += 1;
+				let mut items = vec![];
+				while !self.eat(']')? {
+					items.push(self.value()?);
+					if !self.eat(',')? {
+						self.expect(']')?;
+						break;
+					}
+				}
+				Ok(Value::List(items))
+			},
+			Some('(') => self.parens(None),
+			Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+				let n = self.take(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.' | '_'));
+				Ok(Value::Num(n.replace('_', "")))
+			},
+			Some(c) if c.is_alphabetic() || c == '_' => {
+				let name = self.take(|c| c.is_alphanumeric() || c == '_');
+				match name {
+					"true" => return Ok(Value::Bool(true)),
+					"false" => return Ok(Value::Bool(false)),
+					_ => {}
+				}
+				self.skip()?;
+				if self.peek() == Some('(') {
+					self.parens(Some(name.to_string()))
+				} else {
+					Ok(Value::Ident(name.to_string()))
+				}
+			},
+			Some(c) => self.err(&format!("unexpected '{}'", c)),
+			None => self.err("unexpected end of file")
+		}
+	}
+
+	/// Parse the contents of parentheses, as a struct if they begin with a field name, or a tuple otherwise.
+	fn parens(&mut self, name: Option<String>) -> Result<Value, String> {
+		self.expect('(')?;
+		self.skip()?;
+		let start = self.i;
+		let field = self.take(|c| c.is_alphanumeric() || c == '_');
+		let is_struct = !field.is_empty() && !field.starts_with(|c: char| c.is_ascii_digit()) && self.eat(':')?;
+		self.i = start;
+		if !is_struct {
+			let mut items = vec![];
+			while !self.eat(')')? {
+				items.push(self.value()?);
+				if !self.eat(',')? {
+					self.expect(')')?;
+					break;
+				}
+			}
+			return Ok(Value::Tuple(name, items));
+		}
+		let mut fields: Vec<(String, Value)> = vec![];
+		while !self.eat(')')? {
+			let f = self.take(|c| c.is_alphanumeric() || c == '_').to_string();
+			if f.is_empty() {
+				return self.err("expected a field name");
+			}
+			if fields.iter().any(|(k, _)| *k == f) {
+				return self.err(&format!("field {} given twice", f));
+			}
+			self.expect(':')?;
+			fields.push((f, self.value()?));
+			if !self.eat(',')? {
+				self.expect(')')?;
+				break;
+			}
+			self.skip()?;
+		}
+		Ok(Value::Struct(name, fields))
+	}
+
+	fn string(&mut self) -> Result<String, String> {
+		self.i += 1;
+		let mut out = String::new();
+		let mut chars = self.text[self.i..].char_indices();
+		while let Some((k, c)) = chars.next() {
+			match c {
+				'"' => {
+					self.i += k + 1;
+					return Ok(out);
+				},
+				'\\' => match chars.next().map(|(_, e)| e) {
+					Some('n') => out.push('\n'),
+					Some('t') => out.push('\t'),
+					Some('r') => out.push('\r'),
+					Some(e @ ('"' | '\\')) => out.push(e),
+					_ => {
+						self.i += k;
+						return self.err("invalid escape in string");
+					}
+				},
+				c => out.push(c)
+			}
+		}
+		self.err("unterminated string")
+	}
+}
+
+/// Parse RON text (of the subset read) into a value, which must be all of the text but comments.
+fn parse_value(text: &str) -> Result<Value, String> {
+	let mut p = Parser { text: text, i: 0 };
+	let v = p.value()?;
+	p.skip()?;
+	if p.i < text.len() {
+		return p.err("expected end of file");
+	}
+	Ok(v)
+}
+
+/// Fields of a struct value, checked against the allowed field names, and the struct name, if given.
+fn fields<'v>(v: &'v Value, name: &str, allowed: &[&str], what: &str) -> Result<HashMap<&'v str, &'v Value>, String> {
+	match v {
+		Value::Struct(n, fs) if n.as_deref().unwrap_or(name) == name => {
+			let mut out = HashMap::new();
+			for (k, v) in fs {
+				if !allowed.contains(&k.as_str()) {
+					return Err(format!("{}: unknown field {}", what, k));
+				}
+				out.insert(k.as_str(), v);
+			}
+			Ok(out)
+		},
+		_ => Err(format!("{}: expected a struct with fields {}", what, allowed.join(", ")))
+	}
+}
+
+fn required<'v>(f: &HashMap<&str, &'v Value>, k: &str, what: &str) -> Result<&'v Value, String> {
+	f.get(k).copied().ok_or_else(|| format!("{}: missing field {}", what, k))
+}
+
+fn int(v: &Value, what: &str) -> Result<i64, String> {
+	match v {
+		Value::Num(n) => n.parse().map_err(|_| format!("{}: expected an integer, found {}", what, n)),
+		_ => Err(format!("{}: expected an integer", what))
+	}
+}
+
+fn byte(v: &Value, what: &str) -> Result<u8, String> {
+	let n = int(v, what)?;
+	if (0..=255).contains(&n) { Ok(n as u8) } else { Err(format!("{}: {} is not between 0 and 255", what, n)) }
+}
+
+fn num(v: &Value, what: &str) -> Result<f32, String> {
+	match v {
+		Value::Num(n) => n.parse::<f32>().ok().filter(|x| x.is_finite()).ok_or_else(|| format!("{}: expected a number, found {}", what, n)),
+		_ => Err(format!("{}: expected a number", what))
+	}
+}
+
+fn tile(v: &Value, what: &str) -> Result<(i32, i32), String> {
+	let coord = |v: &Value| {
+		let n = int(v, what)?;
+		if n >= i32::MIN as i64 && n <= i32::MAX as i64 { Ok(n as i32) } else { Err(format!("{}: co-ordinate {} is out of range", what, n)) }
+	};
+	match v {
+		Value::Tuple(None, xy) if xy.len() == 2 => Ok((coord(&xy[0])?, coord(&xy[1])?)),
+		_ => Err(format!("{}: expected a tile (x, y)", what))
+	}
+}
+
+fn list<'v>(v: Option<&&'v Value>, what: &str) -> Result<&'v [Value], String> {
+	match v {
+		None => Ok(&[]),
+		Some(Value::List(items)) => Ok(items),
+		Some(_) => Err(format!("{}: expected a list", what))
+	}
+}
+
+fn bytes(v: Option<&&Value>, what: &str) -> Result<Vec<u8>, String> {
+	list(v, what)?.iter().enumerate().map(|(i, t)| byte(t, &format!("{}[{}]", what, i))).collect()
+}
+
+fn string(v: Option<&&Value>, what: &str) -> Result<String, String> {
+	match v {
+		None => Ok(String::new()),
+		Some(Value::Str(s)) => Ok(s.clone()),
+		Some(_) => Err(format!("{}: expected a string", what))
+	}
+}
+
+fn behavior(v: &Value, what: &str) -> Result<Behavior, String> {
+	match v {
+		Value::Ident(n) if n == "Hold" => Ok(Behavior::Hold),
+		Value::Struct(Some(n), _) if n == "Guard" => {
+			let f = fields(v, "Guard", &["tile", "radius"], what)?;
+			Ok(Behavior::Guard { tile: tile(required(&f, "tile", what)?, what)?, radius: byte(required(&f, "radius", what)?, what)? })
+		},
+		Value::Tuple(Some(n), items) if n == "Patrol" && items.len() == 1 => {
+			let pts = list(Some(&&items[0]), what)?.iter().map(|p| tile(p, what)).collect::<Result<Vec<_>, String>>()?;
+			Ok(Behavior::Patrol { waypoints: pts, next: 0 })
+		},
+		_ => Err(format!("{}: expected Hold, Guard(tile: (x, y), radius: r) or Patrol([(x, y), ...])", what))
+	}
+}
+
+fn effect(v: &Value, what: &str) -> Result<PickupEffect, String> {
+	let e = match v {
+		Value::Tuple(Some(n), items) if items.len() == 1 => match n.as_str() {
+			"Heal" => PickupEffect::from_raw(0, num(&items[0], what)?),
+			"Boost" => PickupEffect::from_raw(1, num(&items[0], what)?),
+			"Gold" => {
+				let g = int(&items[0], what)?;
+				if g >= 0 && g <= u32::MAX as i64 { Some(PickupEffect::Gold(g as u32)) } else { None }
+			},
+			_ => None
+		},
+		_ => None
+	};
+	e.ok_or_else(|| format!("{}: expected Heal(fraction), Boost(fraction) or Gold(amount), not negative", what))
+}
+
+/// Load a map from RON text into the world, as [load_world](super::load_world) does from a binary file: the tiles, texts, slides, layers and ambient regions of the world are replaced,
+/// and its statics, units and pickups are added. Returns an error message (leaving the world unchanged) if the text is malformed, or refers to unregistered unit types.
+pub fn parse(w: &mut World, text: &str) -> Result<(), String> {
+	let root = parse_value(text)?;
+	let f = fields(&root, "Map", &["width", "height", "tiles", "prohibited", "heal", "damage", "title", "intro", "victory", "defeat", "statics", "units", "pickups", "slides", "layers", "heights", "ambient"], "map")?;
+	let size = |k: &str| int(required(&f, k, "map")?, k).and_then(|n| if n >= 0 { Ok(n as usize) } else { Err(format!("{}: {} is negative", k, n)) });
+	let (width, height) = (size("width")?, size("height")?);
+	let tiles = bytes(Some(&required(&f, "tiles", "map")?), "tiles")?;
+	if tiles.len() != width * height {
+		return Err(format!("tiles: expected {} tiles for a {}x{} map, found {}", width * height, width, height, tiles.len()));
+	}
+	let mut perm = HashMap::new();
+	for (k, p) in [("prohibited", TileType::Prohibited), ("heal", TileType::Heal), ("damage", TileType::Damage)].iter() {
+		for t in bytes(f.get(k), k)? {
+			perm.insert(t, p.clone());
+		}
+	}
+
+	let mut statics = vec![];
+	for (i, s) in list(f.get("statics"), "statics")?.iter().enumerate() {
+		let what = format!("statics[{}]", i);
+		let sf = fields(s, "Static", &["tex", "tile"], &what)?;
+		statics.push((byte(required(&sf, "tex", &what)?, &what)?, tile(required(&sf, "tile", &what)?, &what)?));
+	}
+	let mut units = vec![];
+	for (i, u) in list(f.get("units"), "units")?.iter().enumerate() {
+		let what = format!("units[{}]", i);
+		let uf = fields(u, "Unit", &["type_id", "tile", "player", "behavior"], &what)?;
+		let tid = byte(required(&uf, "type_id", &what)?, &what)?;
+		if !w.unit_types.contains_key(&tid) {
+			return Err(format!("{}: unit type {} is not registered", what, tid));
+		}
+		let player = match required(&uf, "player", &what)? {
+			Value::Bool(b) => *b,
+			_ => return Err(format!("{}: expected player: true or false", what))
+		};
+		let b = uf.get("behavior").map(|b| behavior(b, &what)).transpose()?;
+		units.push((tid, tile(required(&uf, "tile", &what)?, &what)?, player, b));
+	}
+	let mut pickups = vec![];
+	for (i, p) in list(f.get("pickups"), "pickups")?.iter().enumerate() {
+		let what = format!("pickups[{}]", i);
+		let pf = fields(p, "Pickup", &["tile", "effect"], &what)?;
+		pickups.push((tile(required(&pf, "tile", &what)?, &what)?, effect(required(&pf, "effect", &what)?, &what)?));
+	}
+	let mut slides = vec![];
+	for (i, s) in list(f.get("slides"), "slides")?.iter().enumerate() {
+		let what = format!("slides[{}]", i);
+		let sf = fields(s, "Slide", &["tex", "duration", "caption"], &what)?;
+		let d = num(required(&sf, "duration", &what)?, &what)?;
+		if d < 0.0 {
+			return Err(format!("{}: duration is negative", what));
+		}
+		slides.push(Slide { tex: byte(required(&sf, "tex", &what)?, &what)?, caption: string(sf.get("caption"), &what)?, duration: d });
+	}
+	let mut layers = vec![];
+	for (i, l) in list(f.get("layers"), "layers")?.iter().enumerate() {
+		let what = format!("layers[{}]", i);
+		let lf = fields(l, "Layer", &["kind", "tiles"], &what)?;
+		let kind = match required(&lf, "kind", &what)? {
+			Value::Ident(k) => LayerKind::from_name(k),
+			_ => None
+		}.ok_or_else(|| format!("{}: expected kind Ground, Decoration or Overlay", what))?;
+		let t = bytes(lf.get("tiles"), &what)?;
+		if t.len() != tiles.len() {
+			return Err(format!("{}: expected {} tiles, found {}", what, tiles.len(), t.len()));
+		}
+		layers.push(TileLayer { kind: kind, tiles: t });
+	}
+	let heights = bytes(f.get("heights"), "heights")?;
+	if !heights.is_empty() && heights.len() != tiles.len() {
+		return Err(format!("heights: expected {} heights, found {}", tiles.len(), heights.len()));
+	}
+	let mut ambient = vec![];
+	for (i, a) in list(f.get("ambient"), "ambient")?.iter().enumerate() {
+		let what = format!("ambient[{}]", i);
+		let af = fields(a, "Ambient", &["track", "from", "to", "falloff", "volume"], &what)?;
+		let falloff = num(required(&af, "falloff", &what)?, &what)?;
+		let volume = af.get("volume").map(|v| num(v, &what)).transpose()?.unwrap_or(1.0);
+		if falloff < 0.0 || !(0.0..=1.0).contains(&volume) {
+			return Err(format!("{}: expected a fall-off of 0 or more, and a volume between 0 and 1", what));
+		}
+		let (from, to) = (tile(required(&af, "from", &what)?, &what)?, tile(required(&af, "to", &what)?, &what)?);
+		ambient.push(AmbientRegion::new(byte(required(&af, "track", &what)?, &what)?, from, to, falloff, volume));
+	}
+
+	w.tilemap = TileMap {
+		map_width: width,
+		map_height: height,
+		map_tiles: tiles,
+		tile_perm: perm,
+		title: string(f.get("title"), "title")?,
+		intro_text: string(f.get("intro"), "intro")?,
+		victory_text: string(f.get("victory"), "victory")?,
+		defeat_text: string(f.get("defeat"), "defeat")?,
+		layers: layers,
+		heights: heights,
+		ambient: ambient,
+		show: true
+	};
+	w.presentation.slides = slides;
+	for (tex, t) in statics {
+		super::create_static(w, tex, t);
+	}
+	for (tid, t, player, b) in units {
+		let uid = super::spawn_unit(w, tid, t, if player { -1 } else { -0x38ffc328 }, player);
+		super::set_behavior(w, uid, b);
+	}
+	for (t, e) in pickups {
+		let (k, _) = e.to_raw();
+		pickup::place(w, pickup::MAP_TEXTURES[k as usize], t, e);
+	}
+	Ok(())
+}
+
+fn escape(s: &str) -> String {
+	let mut out = String::from("\"");
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\t' => out.push_str("\\t"),
+			'\r' => out.push_str("\\r"),
+			c => out.push(c)
+		}
+	}
+	out.push('"');
+	out
+}
+
+/// Write tiles as a list, a row of the map per line.
+fn tile_rows(out: &mut String, tiles: &[u8], width: usize, indent: &str) {
+	out.push_str("[\n");
+	for row in tiles.chunks(width.max(1)) {
+		out.push_str(indent);
+		out.push_str("    ");
+		out.push_str(&row.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", "));
+		out.push_str(",\n");
+	}
+	out.push_str(indent);
+	out.push(']');
+}
+
+/// Returns the map of the world as RON text, read back by [parse]; units are written by type and side at their tiles, in order of id.
+pub fn to_string(w: &World) -> String {
+	let tm = &w.tilemap;
+	let mut out = format!("// alesia map\nMap(\n    width: {},\n    height: {},\n    tiles: ", tm.map_width, tm.map_height);
+	tile_rows(&mut out, &tm.map_tiles, tm.map_width, "    ");
+	out.push_str(",\n");
+	for (k, p) in [("prohibited", TileType::Prohibited), ("heal", TileType::Heal), ("damage", TileType::Damage)].iter() {
+		let mut ts: Vec<u8> = tm.tile_perm.iter().filter(|(_, q)| std::mem::discriminant(*q) == std::mem::discriminant(p)).map(|(t, _)| *t).collect();
+		if !ts.is_empty() {
+			ts.sort_unstable();
+			out.push_str(&format!("    {}: [{}],\n", k, ts.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ")));
+		}
+	}
+	for (k, s) in [("title", &tm.title), ("intro", &tm.intro_text), ("victory", &tm.victory_text), ("defeat", &tm.defeat_text)].iter() {
+		out.push_str(&format!("    {}: {},\n", k, escape(s)));
+	}
+	if !w.statics.is_empty() {
+		out.push_str("    statics: [\n");
+		for s in &w.statics {
+			out.push_str(&format!("        (tex: {}, tile: ({}, {})),\n", s.tex_id, s.wx, s.wy));
+		}
+		out.push_str("    ],\n");
+	}
+	if !w.units.is_empty() {
+		out.push_str("    units: [\n");
+		let mut ids: Vec<&u8> = w.units.keys().collect();
+		ids.sort();
+		for id in ids {
+			let u = &w.units[id];
+			out.push_str(&format!("        (type_id: {}, tile: ({}, {}), player: {}", u.type_id, u.wpos.x as i32, u.wpos.y as i32, u.player));
+			match &u.behavior {
+				Some(Behavior::Hold) => out.push_str(", behavior: Hold"),
+				Some(Behavior::Guard { tile, radius }) => out.push_str(&format!(", behavior: Guard(tile: ({}, {}), radius: {})", tile.0, tile.1, radius)),
+				Some(Behavior::Patrol { waypoints, .. }) => {
+					let pts: Vec<String> = waypoints.iter().map(|(x, y)| format!("({}, {})", x, y)).collect();
+					out.push_str(&format!(", behavior: Patrol([{}])", pts.join(", ")));
+				},
+				None => {}
+			}
+			out.push_str("),\n");
+		}
+		out.push_str("    ],\n");
+	}
+	if !w.pickups.is_empty() {
+		out.push_str("    pickups: [\n");
+		for p in &w.pickups {
+			let e = match p.effect {
+				PickupEffect::Heal(v) => format!("Heal({:?})", v),
+				PickupEffect::Boost(v) => format!("Boost({:?})", v),
+				PickupEffect::Gold(g) => format!("Gold({})", g)
+			};
+			out.push_str(&format!("        (tile: ({}, {}), effect: {}),\n", p.tile.0, p.tile.1, e));
+		}
+		out.push_str("    ],\n");
+	}
+	if !w.presentation.slides.is_empty() {
+		out.push_str("    slides: [\n");
+		for s in &w.presentation.slides {
+			out.push_str(&format!("        (tex: {}, duration: {:?}, caption: {}),\n", s.tex, s.duration, escape(&s.caption)));
+		}
+		out.push_str("    ],\n");
+	}
+	if !tm.layers.is_empty() {
+		out.push_str("    layers: [\n");
+		for l in &tm.layers {
+			out.push_str(&format!("        (kind: {:?}, tiles: ", l.kind));
+			tile_rows(&mut out, &l.tiles, tm.map_width, "        ");
+			out.push_str("),\n");
+		}
+		out.push_str("    ],\n");
+	}
+	if w.has_elevation() {
+		out.push_str("    heights: ");
+		tile_rows(&mut out, &tm.heights, tm.map_width, "    ");
+		out.push_str(",\n");
+	}
+	if !tm.ambient.is_empty() {
+		out.push_str("    ambient: [\n");
+		for a in &tm.ambient {
+			out.push_str(&format!("        (track: {}, from: ({}, {}), to: ({}, {}), falloff: {:?}, volume: {:?}),\n", a.track, a.from.0, a.from.1, a.to.0, a.to.1, a.falloff, a.volume));
+		}
+		out.push_str("    ],\n");
+	}
+	out.push_str(")\n");
+	out
+}
@@ -0,0 +1,89 @@
+//! Voice lines ("barks") of unit types: groups of sounds, one of which is picked at random and played when a unit is selected, starts to move, attacks or dies.
+//! Each group has a cooldown, shared by the units of the type, so that a group of archers does not answer in chorus. See [UnitType::set_voice](super::UnitType::set_voice).
+//!
+//! Enemy units hidden by fog of war do not bark.
+
+use super::World;
+
+/// Moment at which a unit barks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarkKind {
+	/// The player selected the unit.
+	Select = 0,
+	/// The unit started to move.
+	Move = 1,
+	/// The unit dealt damage.
+	Attack = 2,
+	/// The unit died.
+	Death = 3
+}
+
+impl BarkKind {
+	/// Convert a raw kind, as used by the native API. Returns None if out of range.
+	pub fn from_u8(k: u8) -> Option<BarkKind> {
+		match k {
+			0 => Some(BarkKind::Select),
+			1 => Some(BarkKind::Move),
+			2 => Some(BarkKind::Attack),
+			3 => Some(BarkKind::Death),
+			_ => None
+		}
+	}
+}
+
+/// The groups of voice lines of a unit type, by [BarkKind], with the seconds after a bark of each group before the group barks again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoiceSet {
+	groups: [(Vec<u8>, f32); 4]
+}
+
+impl VoiceSet {
+	/// Set the sound ids of a group, and its cooldown in seconds; negative or non-finite cooldowns are replaced by 0. An empty group is silent.
+	pub fn set(&mut self, kind: BarkKind, sounds: Vec<u8>, cooldown: f32) {
+		self.groups[kind as usize] = (sounds, if cooldown.is_finite() { cooldown.max(0.0) } else { 0.0 });
+	}
+
+	/// Returns the sound ids of a group.
+	pub fn sounds(&self, kind: BarkKind) -> &[u8] {
+		&self.groups[kind as usize].0
+	}
+
+	/// Returns the cooldown of a group, in seconds.
+	pub fn cooldown(&self, kind: BarkKind) -> f32 {
+		self.groups[kind as usize].1
+	}
+
+	/// Returns true if no group has any sound.
+	pub fn is_empty(&self) -> bool {
+		self.groups.iter().all(|g| g.0.is_empty())
+	}
+}
+
+/// Play a voice line of the unit with specified id from the group of `kind` of its type, unless the group is empty or cooling down, or the unit is a hidden enemy.
+/// Returns the id of the sound played, if any.
+pub fn bark(w: &mut World, uid: u8, kind: BarkKind) -> Option<u8> {
+	if !super::is_unit_visible(w, uid) {
+		return None;
+	}
+	let tid = w.units.get(&uid)?.type_id;
+	let v = &w.unit_types.get(&tid)?.voice;
+	let sounds = v.sounds(kind);
+	if sounds.is_empty() || w.bark_cooldowns.get(&(tid, kind)).iter().any(|t| **t > 0.0) {
+		return None;
+	}
+	let id = sounds[raylib::core::misc::get_random_value::<i32>(0, sounds.len() as i32 - 1) as usize];
+	let cd = v.cooldown(kind);
+	if cd > 0.0 {
+		w.bark_cooldowns.insert((tid, kind), cd);
+	}
+	w.play_sound(id);
+	Some(id)
+}
+
+/// Advance the cooldowns of voice lines by `dt` seconds.
+pub(crate) fn tick(w: &mut World, dt: f32) {
+	w.bark_cooldowns.retain(|_, t| {
+		*t -= dt;
+		*t > 0.0
+	});
+}
@@ -4,6 +4,7 @@
 ## 2. Main tile data (single layer)
 ## ---- Padding --- [6 bytes, 0x00]
 ## 3. Static Game Objects encoded as [0xfe, 0xed, o_type ,id, worldx, worldy]
+##    Unit behaviors follow the unit they apply to, see put_behavior.
 import sys
 
 magic = bytes([0xfa, 0xde, 0x00, 0xff]);
@@ -46,6 +47,18 @@ class WorldFile:
 		self.file.write(contread);
 		self.file.write(bytes([t, tid, tx, ty]));
 
+	# Behavior of the last unit put: ('hold',), ('guard', radius, x, y) or ('patrol', (x, y), ...)
+	def put_behavior(self, b):
+		if b[0] == 'hold':
+			self.put_object(0, 3, 0, 0);
+		elif b[0] == 'guard':
+			self.put_object(b[1], 4, b[2], b[3]);
+		elif b[0] == 'patrol':
+			for (x, y) in b[1:]:
+				self.put_object(0, 5, x, y);
+		else:
+			print("Unrecognized behavior: ", b);
+
 	def __exit__(self, t, v, trac):
 		self.file.close();
 
@@ -88,6 +101,10 @@ def main(name, w, h, m=None):
 				if s != -1:
 					s, t = m[(s,0)];
 					w.put_object(s, t, tx, ty);
+					# Behaviors are keyed by the tile of the unit.
+					b = (m.get('behaviors') or {}).get((tx, ty));
+					if b is not None and t in (1, 2):
+						w.put_behavior(b);
 				tx += 1;
 			ty += 1;
 